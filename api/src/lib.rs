@@ -0,0 +1,245 @@
+//! The core operations of `forge` and `cast` as a library: compiling a project, running its tests,
+//! executing calls on a fork and decoding call traces.
+//!
+//! This is meant for tools that embed foundry, e.g. CI bots or security scanners, so they do not
+//! need to spawn `forge` and parse its output.
+
+#![deny(missing_docs, unsafe_code)]
+
+use ethers::{
+    abi::Abi,
+    solc::ArtifactId,
+    types::{Address, U256},
+};
+use eyre::Result;
+use forge::{
+    executor::{builder::Backend, ExecutorBuilder},
+    fuzz::{random_seed, seeded_runner},
+    matches_any_glob,
+    trace::{identifier::LocalTraceIdentifier, CallTraceDecoderBuilder},
+    utils::try_evm_spec,
+    MultiContractRunnerBuilder,
+};
+use foundry_config::figment::Figment;
+use regex::Regex;
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+pub use ethers::solc::ProjectCompileOutput;
+pub use forge::{
+    executor::{opts::EvmOpts, RawCallResult},
+    trace::CallTraceArena,
+    SuiteResult, TestFilter, TestResult,
+};
+pub use foundry_config::Config;
+
+/// A project with its config and the EVM options derived from it
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// The config of the project
+    pub config: Config,
+    /// The options of the EVM the tests and calls are executed with
+    pub evm_opts: EvmOpts,
+}
+
+impl Workspace {
+    /// Loads the config of the project at `root` the same way `forge` does, from its
+    /// `foundry.toml` and `FOUNDRY_` environment variables
+    pub fn load(root: impl Into<PathBuf>) -> Result<Self> {
+        Self::from_figment(Config::figment_with_root(root))
+    }
+
+    /// Creates the workspace from a figment, e.g. one with additional providers merged into it
+    pub fn from_figment(figment: Figment) -> Result<Self> {
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::from_provider(figment).sanitized();
+        Ok(Self { config, evm_opts })
+    }
+
+    /// Compiles the project, failing if the compiler reports any errors
+    pub fn compile(&self) -> Result<ProjectCompileOutput> {
+        let output = self.config.project()?.compile()?;
+        if output.has_compiler_errors() {
+            eyre::bail!(output.to_string())
+        }
+        Ok(output)
+    }
+
+    /// Runs the tests of the compiled project that match the filter
+    ///
+    /// The test runner drives its own async runtime, so this blocks and must not be called from
+    /// within an async runtime, e.g. use `tokio::task::spawn_blocking` there.
+    pub fn test(
+        &self,
+        output: ProjectCompileOutput,
+        filter: &(impl TestFilter + Send + Sync),
+    ) -> Result<TestOutput> {
+        let config = &self.config;
+        let root = &config.__root.0;
+        let cfg = proptest::test_runner::Config {
+            failure_persistence: None,
+            cases: config.fuzz_runs,
+            max_local_rejects: config.fuzz_max_local_rejects,
+            max_global_rejects: config.fuzz_max_global_rejects,
+            ..Default::default()
+        };
+        let fuzzer = seeded_runner(cfg, config.fuzz_seed.unwrap_or_else(random_seed));
+        let mut runner = MultiContractRunnerBuilder::default()
+            .fuzzer(fuzzer)
+            .initial_balance(self.evm_opts.initial_balance)
+            .evm_spec(try_evm_spec(&config.evm_version)?)
+            .sender(self.evm_opts.sender)
+            .with_fork(self.evm_opts.get_fork(&config.rpc_storage_caching))
+            .with_fuzz_dictionary_weight(config.fuzz_dictionary_weight)
+            .with_invariant_config(config.invariant)
+            .with_constructor_args(config.constructor_args.resolve(root)?)
+            .with_timeout(config.test_timeout.map(std::time::Duration::from_secs))
+            .build(root, output, self.evm_opts.clone())?;
+        let results = runner.test(filter, None, true)?;
+        Ok(TestOutput { results, known_contracts: runner.known_contracts })
+    }
+
+    /// Executes a call on the state of the configured fork, or on an empty state if there is no
+    /// fork
+    ///
+    /// The state changes of the call are not persisted. The call is traced, the traces can be
+    /// decoded with [decode_trace].
+    pub async fn call(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: Vec<u8>,
+        value: U256,
+    ) -> Result<RawCallResult> {
+        let spec = try_evm_spec(&self.config.evm_version)?;
+        let env = self.evm_opts.try_evm_env().await?;
+        // the chain id of the fork is part of its env already, fetching it again would block
+        let fork = self
+            .evm_opts
+            .get_fork_with_chain_id(&self.config.rpc_storage_caching, env.cfg.chain_id.as_u64());
+        let db = Backend::new(fork, &env).await;
+        let executor = ExecutorBuilder::new()
+            .with_config(env)
+            .with_spec(spec)
+            .with_gas_limit(self.evm_opts.gas_limit())
+            .with_tracing()
+            .build(db);
+        executor.call_raw(from, to, calldata.into(), value)
+    }
+}
+
+/// The results of a test run
+#[derive(Clone)]
+pub struct TestOutput {
+    /// The results of all test suites, by contract identifier
+    pub results: BTreeMap<String, SuiteResult>,
+    /// The ABIs and runtime code of the compiled contracts, for decoding traces
+    pub known_contracts: Arc<BTreeMap<ArtifactId, (Abi, Vec<u8>)>>,
+}
+
+impl TestOutput {
+    /// Returns all tests, `(test signature, result)`
+    pub fn tests(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.results.values().flat_map(|suite| suite.test_results.iter())
+    }
+
+    /// Returns the failed tests
+    pub fn failures(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.tests().filter(|(_, result)| !result.success)
+    }
+}
+
+/// Decodes the calls, events and return values of the traces with the ABIs of the known contracts
+///
+/// The traces can be printed with their `Display` implementation afterwards.
+pub fn decode_trace(
+    traces: &mut CallTraceArena,
+    known_contracts: &BTreeMap<ArtifactId, (Abi, Vec<u8>)>,
+) {
+    let identifier = LocalTraceIdentifier::new(known_contracts);
+    let mut decoder = CallTraceDecoderBuilder::new().with_events(identifier.events()).build();
+    decoder.identify(traces, &identifier);
+    decoder.decode(traces);
+}
+
+/// A [TestFilter] that selects tests by patterns, like the `--match-*` arguments of `forge test`
+///
+/// The default filter matches all tests.
+#[derive(Debug, Clone, Default)]
+pub struct TestPatterns {
+    test: Option<Regex>,
+    contract: Option<Regex>,
+    path: Option<String>,
+}
+
+impl TestPatterns {
+    /// Only matches test functions whose signature matches the regex
+    pub fn match_test(mut self, pattern: &str) -> Result<Self> {
+        self.test = Some(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Only matches test contracts whose name matches the regex
+    pub fn match_contract(mut self, pattern: &str) -> Result<Self> {
+        self.contract = Some(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Only matches test files whose path matches the glob, e.g. `test/fork/*.t.sol`
+    #[must_use]
+    pub fn match_path(mut self, glob: impl Into<String>) -> Self {
+        self.path = Some(glob.into());
+        self
+    }
+}
+
+impl TestFilter for TestPatterns {
+    fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
+        self.test.as_ref().map_or(true, |re| re.is_match(test_name.as_ref()))
+    }
+
+    fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
+        self.contract.as_ref().map_or(true, |re| re.is_match(contract_name.as_ref()))
+    }
+
+    fn matches_path(&self, path: impl AsRef<str>) -> bool {
+        self.path.as_ref().map_or(true, |glob| matches_any_glob(&[glob.clone()], path.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_filter_tests_by_patterns() {
+        let filter = TestPatterns::default()
+            .match_test("^testDeposit")
+            .unwrap()
+            .match_contract("Vault")
+            .unwrap()
+            .match_path("test/*.t.sol");
+
+        assert!(filter.matches_qualified_test("test/Vault.t.sol", "VaultTest", "testDeposit()"));
+        assert!(!filter.matches_qualified_test("test/Vault.t.sol", "VaultTest", "testWithdraw()"));
+        assert!(!filter.matches_qualified_test("test/Vault.t.sol", "PoolTest", "testDeposit()"));
+        assert!(!filter.matches_qualified_test("src/Vault.sol", "VaultTest", "testDeposit()"));
+        assert!(TestPatterns::default().matches_qualified_test("a", "b", "c"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_call_within_runtime() {
+        let workspace = Workspace::load(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let to = Address::repeat_byte(0x11);
+        let result = workspace.call(Address::zero(), to, vec![], U256::zero()).await.unwrap();
+        assert!(!result.reverted);
+    }
+
+    #[tokio::test]
+    async fn errors_on_unsupported_evm_version() {
+        let mut workspace = Workspace::load(env!("CARGO_MANIFEST_DIR")).unwrap();
+        workspace.config.evm_version = ethers::solc::EvmVersion::Byzantium;
+        let to = Address::repeat_byte(0x11);
+        let err = workspace.call(Address::zero(), to, vec![], U256::zero()).await;
+        assert!(err.unwrap_err().to_string().contains("Unsupported EVM version"));
+    }
+}
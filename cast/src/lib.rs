@@ -7,7 +7,7 @@ use ethers_core::{
         token::{LenientTokenizer, Tokenizer},
         Abi, AbiParser, Token,
     },
-    types::{Chain, *},
+    types::{transaction::eip2718::TypedTransaction, Chain, *},
     utils::{self, get_contract_address, keccak256, parse_units},
 };
 use ethers_etherscan::Client;
@@ -424,6 +424,118 @@ where
         Ok(self.provider.get_chainid().await?)
     }
 
+    /// Returns the pending transaction of `from` with the nonce, looked up in the txpool of the
+    /// node
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use ethers_core::types::{Address, U256};
+    /// use std::{str::FromStr, convert::TryFrom};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let from = Address::from_str("0x7eD52863829AB99354F3a0503A622e82AcD5F7d3")?;
+    /// let tx = cast.pending_transaction(from, U256::from(5)).await?;
+    /// println!("{:?}", tx);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn pending_transaction(
+        &self,
+        from: Address,
+        nonce: U256,
+    ) -> Result<Option<Transaction>> {
+        let content = self.provider.txpool_content().await?;
+        let hash = content.pending.get(&from).and_then(|txs| txs.get(&nonce.to_string()));
+        match hash.map(|tx| tx.hash) {
+            Some(hash) => Ok(self.provider.get_transaction(hash).await?),
+            None => Ok(None),
+        }
+    }
+
+    /// Builds a transaction that replaces the pending transaction `original`: it has the same
+    /// sender and nonce, and its fees are those of `original` multiplied by `fee_multiplier`, or
+    /// the current fees of the network if they are higher
+    ///
+    /// If `cancel` is set, the replacement is an empty transfer of 0 ether from the sender to
+    /// itself, which cancels `original` once it is mined.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Middleware, Provider, Http};
+    /// use ethers_core::types::H256;
+    /// use std::{str::FromStr, convert::TryFrom};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let hash = H256::from_str("0x5a91b9e1fc45e1caa0d4d9d6f1f36b6b4d2f1d96d1a5bb2f2b4d1a1e1a2b3c4d")?;
+    /// let original = provider.get_transaction(hash).await?.unwrap();
+    /// let cast = Cast::new(provider);
+    /// let replacement = cast.replacement(&original, 1.2, false).await?;
+    /// println!("{:?}", replacement);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replacement(
+        &self,
+        original: &Transaction,
+        fee_multiplier: f64,
+        cancel: bool,
+    ) -> Result<TypedTransaction> {
+        let (to, value, data, gas) = if cancel {
+            (Some(original.from), U256::zero(), Bytes::default(), U256::from(21000))
+        } else {
+            (original.to, original.value, original.input.clone(), original.gas)
+        };
+
+        let fees = (original.max_fee_per_gas, original.max_priority_fee_per_gas);
+        let tx: TypedTransaction = match fees {
+            (Some(max_fee), Some(max_priority_fee)) => {
+                let (network_max_fee, network_priority_fee) =
+                    self.provider.estimate_eip1559_fees(None).await.unwrap_or_default();
+                let mut tx = Eip1559TransactionRequest::new()
+                    .from(original.from)
+                    .value(value)
+                    .data(data)
+                    .gas(gas)
+                    .nonce(original.nonce)
+                    .max_fee_per_gas(bump_fee(max_fee, fee_multiplier)?.max(network_max_fee))
+                    .max_priority_fee_per_gas(
+                        bump_fee(max_priority_fee, fee_multiplier)?.max(network_priority_fee),
+                    );
+                tx.to = to.map(Into::into);
+                if !cancel {
+                    tx.access_list = original.access_list.clone().unwrap_or_default();
+                }
+                tx.into()
+            }
+            _ => {
+                let network_gas_price = self.provider.get_gas_price().await?;
+                let gas_price = bump_fee(original.gas_price.unwrap_or_default(), fee_multiplier)?
+                    .max(network_gas_price);
+                let mut tx = TransactionRequest::new()
+                    .from(original.from)
+                    .value(value)
+                    .data(data)
+                    .gas(gas)
+                    .nonce(original.nonce)
+                    .gas_price(gas_price);
+                tx.to = to.map(Into::into);
+                // EIP-2930 transactions keep their access list
+                let is_eip2930 = original.transaction_type == Some(U64::one());
+                match original.access_list.clone() {
+                    Some(access_list) if is_eip2930 && !cancel => {
+                        Eip2930TransactionRequest::new(tx, access_list).into()
+                    }
+                    _ => tx.into(),
+                }
+            }
+        };
+        Ok(tx)
+    }
+
     pub async fn block_number(&self) -> Result<U64> {
         Ok(self.provider.get_block_number().await?)
     }
@@ -1292,6 +1404,18 @@ impl SimpleCast {
     }
 }
 
+/// Returns the fee multiplied by the multiplier, rounded up
+fn bump_fee(fee: U256, multiplier: f64) -> Result<U256> {
+    if !multiplier.is_finite() || multiplier < 1. {
+        eyre::bail!("Invalid fee multiplier {multiplier}, it must be a finite number of at least 1")
+    }
+    let permille = U256::from((multiplier * 1000.).ceil() as u64);
+    fee.checked_mul(permille)
+        .and_then(|fee| fee.checked_add(U256::from(999)))
+        .map(|fee| fee / 1000)
+        .ok_or_else(|| eyre::eyre!("The fee {fee} multiplied by {multiplier} overflows"))
+}
+
 fn strip_0x(s: &str) -> &str {
     s.strip_prefix("0x").unwrap_or(s)
 }
@@ -1299,6 +1423,16 @@ fn strip_0x(s: &str) -> &str {
 #[cfg(test)]
 mod tests {
     use super::SimpleCast as Cast;
+    use async_trait::async_trait;
+    use ethers_core::types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessList, AccessListItem},
+        },
+        Address, NameOrAddress, Transaction, U256, U64,
+    };
+    use ethers_providers::{JsonRpcClient, Middleware, ProviderError};
+    use serde::{de::DeserializeOwned, Serialize};
 
     #[test]
     fn calldata_uint() {
@@ -1316,9 +1450,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bump_fee() {
+        use ethers_core::types::U256;
+        assert_eq!(super::bump_fee(U256::from(100), 1.2).unwrap(), U256::from(120));
+        assert_eq!(super::bump_fee(U256::from(101), 1.1).unwrap(), U256::from(112));
+        assert_eq!(super::bump_fee(U256::zero(), 1.5).unwrap(), U256::zero());
+        assert!(super::bump_fee(U256::MAX, 1.1).is_err());
+        assert!(super::bump_fee(U256::from(100), f64::NAN).is_err());
+        assert!(super::bump_fee(U256::from(100), f64::INFINITY).is_err());
+    }
+
     #[test]
     fn concat_hex() {
         assert_eq!(Cast::concat_hex(vec!["0x00".to_string(), "0x01".to_string()]), "0x0001");
         assert_eq!(Cast::concat_hex(vec!["1".to_string(), "2".to_string()]), "0x12");
     }
+
+    /// Answers the fee requests of a replacement with fixed network fees
+    #[derive(Debug)]
+    struct FeeProvider;
+
+    #[async_trait]
+    impl JsonRpcClient for FeeProvider {
+        type Error = ProviderError;
+
+        async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+            &self,
+            _method: &str,
+            _params: T,
+        ) -> Result<R, Self::Error> {
+            unreachable!("There is no `request`");
+        }
+    }
+
+    #[async_trait]
+    impl Middleware for FeeProvider {
+        type Error = ProviderError;
+        type Provider = FeeProvider;
+        type Inner = FeeProvider;
+
+        fn inner(&self) -> &Self::Inner {
+            self
+        }
+
+        async fn get_gas_price(&self) -> Result<U256, Self::Error> {
+            Ok(U256::from(100))
+        }
+
+        async fn estimate_eip1559_fees(
+            &self,
+            _estimator: Option<fn(U256, Vec<Vec<U256>>) -> (U256, U256)>,
+        ) -> Result<(U256, U256), Self::Error> {
+            Ok((U256::from(100), U256::from(10)))
+        }
+    }
+
+    #[tokio::test]
+    async fn replacement() {
+        let cast = super::Cast::new(FeeProvider);
+        let access_list =
+            AccessList(vec![AccessListItem { address: Address::random(), storage_keys: vec![] }]);
+        let original = Transaction {
+            from: Address::random(),
+            to: Some(Address::random()),
+            nonce: U256::from(7),
+            gas: U256::from(50_000),
+            gas_price: Some(U256::from(200)),
+            transaction_type: Some(U64::one()),
+            access_list: Some(access_list.clone()),
+            ..Default::default()
+        };
+
+        let tx = cast.replacement(&original, 1.1, false).await.unwrap();
+        match tx {
+            TypedTransaction::Eip2930(ref tx) => assert_eq!(tx.access_list, access_list),
+            _ => panic!("the replacement of an EIP-2930 tx must be an EIP-2930 tx"),
+        }
+        assert_eq!(tx.nonce(), Some(&original.nonce));
+        assert_eq!(tx.gas_price(), Some(U256::from(220)));
+
+        // the network gas price is used if it's higher, and a cancellation drops the access list
+        let original = Transaction { gas_price: Some(U256::from(50)), ..original };
+        let tx = cast.replacement(&original, 1.1, true).await.unwrap();
+        assert!(matches!(tx, TypedTransaction::Legacy(_)));
+        assert_eq!(tx.gas_price(), Some(U256::from(100)));
+        assert_eq!(tx.to(), Some(&NameOrAddress::Address(original.from)));
+
+        let original = Transaction {
+            max_fee_per_gas: Some(U256::from(200)),
+            max_priority_fee_per_gas: Some(U256::from(2)),
+            transaction_type: Some(U64::from(2)),
+            ..original
+        };
+        match cast.replacement(&original, 1.5, false).await.unwrap() {
+            TypedTransaction::Eip1559(tx) => {
+                assert_eq!(tx.max_fee_per_gas, Some(U256::from(300)));
+                assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(10)));
+                assert_eq!(tx.access_list, access_list);
+            }
+            _ => panic!("the replacement of an EIP-1559 tx must be an EIP-1559 tx"),
+        }
+
+        assert!(cast.replacement(&original, f64::NAN, false).await.is_err());
+    }
 }
@@ -1,6 +1,7 @@
 pub mod cmd;
 pub mod compile;
 
+mod error;
 mod suggestions;
 mod term;
 mod utils;
@@ -13,7 +14,7 @@ use ethers::{
     core::{
         abi::AbiParser,
         rand::thread_rng,
-        types::{BlockId, BlockNumber::Latest, H256},
+        types::{transaction::eip2718::TypedTransaction, BlockId, BlockNumber::Latest, H256},
     },
     providers::{Middleware, Provider},
     signers::{LocalWallet, Signer},
@@ -39,9 +40,10 @@ use clap::{IntoApp, Parser};
 use clap_complete::generate;
 
 use crate::{
-    cmd::Cmd,
+    cmd::{cast::da::DaSubcommands, Cmd},
     utils::{consume_config_rpc_url, read_secret},
 };
+use error::ErrorKind;
 use eyre::WrapErr;
 
 #[tokio::main]
@@ -51,7 +53,21 @@ async fn main() -> eyre::Result<()> {
     utils::enable_paint();
 
     let opts = Opts::parse();
-    match opts.sub {
+    utils::set_offline(opts.offline);
+    if let Err(err) = run(opts.sub).await {
+        let kind = ErrorKind::classify(&err);
+        if opts.json_errors {
+            eprintln!("{}", error::json_envelope(&err, kind));
+        } else {
+            eprintln!("Error: {err:?}");
+        }
+        std::process::exit(kind.exit_code())
+    }
+    Ok(())
+}
+
+async fn run(sub: Subcommands) -> eyre::Result<()> {
+    match sub {
         Subcommands::MaxInt => {
             println!("{}", SimpleCast::max_int()?);
         }
@@ -389,6 +405,46 @@ async fn main() -> eyre::Result<()> {
                 println!("{}", serde_json::json!(receipt));
             }
         }
+        Subcommands::Bump { hash, fee_multiplier, cast_async, confirmations, to_json, eth } => {
+            ensure_fee_multiplier(fee_multiplier)?;
+            let config = Config::from(&eth);
+            let provider = Provider::try_from(
+                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
+            )?;
+            let signer = replacement_signer(&eth, &provider).await?;
+
+            let original = provider
+                .get_transaction(hash)
+                .await?
+                .ok_or_else(|| eyre::eyre!("tx {hash:?} not found"))?;
+            if original.block_number.is_some() {
+                eyre::bail!("tx {hash:?} is already mined and can not be replaced")
+            }
+            if original.from != wallet_address(&signer) {
+                eyre::bail!(
+                    "tx {hash:?} was sent by {:?}, only its sender can replace it",
+                    original.from
+                )
+            }
+            let tx = Cast::new(&provider).replacement(&original, fee_multiplier, false).await?;
+            send_replacement(signer, tx, cast_async, confirmations, to_json).await?;
+        }
+        Subcommands::Cancel { nonce, fee_multiplier, cast_async, confirmations, to_json, eth } => {
+            ensure_fee_multiplier(fee_multiplier)?;
+            let config = Config::from(&eth);
+            let provider = Provider::try_from(
+                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
+            )?;
+            let signer = replacement_signer(&eth, &provider).await?;
+            let from = wallet_address(&signer);
+
+            let cast = Cast::new(&provider);
+            let original = cast.pending_transaction(from, nonce).await?.ok_or_else(|| {
+                eyre::eyre!("No pending tx of {from:?} with nonce {nonce} in the txpool")
+            })?;
+            let tx = cast.replacement(&original, fee_multiplier, true).await?;
+            send_replacement(signer, tx, cast_async, confirmations, to_json).await?;
+        }
         Subcommands::Estimate { to, sig, args, value, eth } => {
             let config = Config::from(&eth);
             let provider = Provider::try_from(
@@ -430,10 +486,12 @@ async fn main() -> eyre::Result<()> {
             println!("{encoded}");
         }
         Subcommands::FourByte { selector } => {
+            utils::ensure_online("Looking up the selector on 4byte.directory")?;
             let sigs = foundry_utils::fourbyte(&selector).await?;
             sigs.iter().for_each(|sig| println!("{}", sig.0));
         }
         Subcommands::FourByteDecode { calldata, id } => {
+            utils::ensure_online("Looking up the calldata on 4byte.directory")?;
             let sigs = foundry_utils::fourbyte_possible_sigs(&calldata, id).await?;
             sigs.iter().enumerate().for_each(|(i, sig)| println!("{}) \"{}\"", i + 1, sig));
 
@@ -456,15 +514,17 @@ async fn main() -> eyre::Result<()> {
             tokens.for_each(|t| println!("{t}"));
         }
         Subcommands::FourByteEvent { topic } => {
+            utils::ensure_online("Looking up the topic on 4byte.directory")?;
             let sigs = foundry_utils::fourbyte_event(&topic).await?;
             sigs.iter().for_each(|sig| println!("{}", sig.0));
         }
 
-        Subcommands::PrettyCalldata { calldata, offline } => {
+        Subcommands::PrettyCalldata { calldata, skip_lookup } => {
             if !calldata.starts_with("0x") {
                 eprintln!("Expected calldata hex string, received \"{calldata}\"");
                 std::process::exit(0)
             }
+            let offline = skip_lookup || utils::is_offline();
             let pretty_data = foundry_utils::pretty_calldata(&calldata, offline).await?;
             println!("{pretty_data}");
         }
@@ -769,6 +829,34 @@ async fn main() -> eyre::Result<()> {
                 };
                 println!("Signature: 0x{sig}");
             }
+            WalletSubcommands::SignAuth { implementation, nonce, chain_id, wallet } => {
+                let wallet = EthereumOpts {
+                    wallet,
+                    rpc_url: Some("http://localhost:8545".to_string()),
+                    flashbots: false,
+                    chain: Chain::Mainnet,
+                    etherscan_api_key: None,
+                }
+                .signer(0.into())
+                .await?
+                .unwrap();
+
+                let wallet = match wallet {
+                    WalletType::Local(wallet) => wallet.signer().clone(),
+                    _ => eyre::bail!("Only local wallets can sign authorizations"),
+                };
+                let digest =
+                    foundry_utils::delegation_digest(chain_id.into(), implementation, nonce);
+                let signature = wallet.sign_hash(digest);
+                let authorization = foundry_utils::encode_signed_delegation(
+                    chain_id.into(),
+                    implementation,
+                    nonce,
+                    &signature,
+                );
+                println!("Authority: {}", SimpleCast::checksum_address(&wallet.address())?);
+                println!("Authorization: 0x{}", hex::encode(authorization));
+            }
             WalletSubcommands::Verify { message, signature, address } => {
                 let pubkey = Address::from_str(&address).expect("invalid pubkey provided");
                 let signature = Signature::from_str(&signature)?;
@@ -787,6 +875,15 @@ async fn main() -> eyre::Result<()> {
             generate(shell, &mut Opts::command(), "cast", &mut std::io::stdout())
         }
         Subcommands::Run(cmd) => cmd.run()?,
+        Subcommands::SourceMap(cmd) => cmd.run()?,
+        Subcommands::Fuzz(cmd) => cmd.run()?,
+        Subcommands::InterfaceCheck(cmd) => cmd.run()?.await?,
+        Subcommands::Monitor(cmd) => cmd.run()?.await?,
+        Subcommands::Da(cmd) => match cmd.sub {
+            DaSubcommands::Encode(cmd) => cmd.run()?,
+            DaSubcommands::VersionedHash(cmd) => cmd.run()?,
+            DaSubcommands::Gas(cmd) => cmd.run()?,
+        },
     };
     Ok(())
 }
@@ -807,6 +904,75 @@ where
     })
 }
 
+/// Nodes only accept replacements of pending transactions whose fees are at least 10% higher
+fn ensure_fee_multiplier(fee_multiplier: f64) -> eyre::Result<()> {
+    if !fee_multiplier.is_finite() || fee_multiplier < 1.1 {
+        eyre::bail!(
+            "The fees of a replacement have to be at least 10% higher, `--fee-multiplier` must be \
+             at least 1.1"
+        )
+    }
+    Ok(())
+}
+
+/// Returns the wallet that signs the replacement of a pending transaction
+async fn replacement_signer(
+    eth: &EthereumOpts,
+    provider: &Provider<ethers::providers::Http>,
+) -> eyre::Result<WalletType> {
+    let chain_id = Cast::new(provider).chain_id().await?;
+    eth.signer_with(chain_id, provider.clone()).await?.ok_or_else(|| {
+        eyre::eyre!("No wallet provided, the replacement has to be signed by the sender of the tx")
+    })
+}
+
+fn wallet_address(wallet: &WalletType) -> Address {
+    match wallet {
+        WalletType::Ledger(signer) => signer.address(),
+        WalletType::Local(signer) => signer.address(),
+        WalletType::Trezor(signer) => signer.address(),
+    }
+}
+
+/// Signs and sends the replacement of a pending transaction
+async fn send_replacement(
+    wallet: WalletType,
+    tx: TypedTransaction,
+    cast_async: bool,
+    confs: usize,
+    to_json: bool,
+) -> eyre::Result<()> {
+    match wallet {
+        WalletType::Ledger(signer) => send_tx(signer, tx, cast_async, confs, to_json).await,
+        WalletType::Local(signer) => send_tx(signer, tx, cast_async, confs, to_json).await,
+        WalletType::Trezor(signer) => send_tx(signer, tx, cast_async, confs, to_json).await,
+    }
+}
+
+async fn send_tx<M: Middleware>(
+    provider: M,
+    tx: TypedTransaction,
+    cast_async: bool,
+    confs: usize,
+    to_json: bool,
+) -> eyre::Result<()>
+where
+    M::Error: 'static,
+{
+    let cast = Cast::new(provider);
+    let pending_tx = cast.send((tx, None)).await?;
+    let tx_hash = *pending_tx;
+
+    if cast_async {
+        println!("{:#x}", tx_hash);
+    } else {
+        let receipt = cast.receipt(format!("{:#x}", tx_hash), None, confs, false, to_json).await?;
+        println!("{receipt}");
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn cast_send<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>>(
     provider: M,
@@ -0,0 +1,246 @@
+//! cast da subcommands, utilities for EIP-4844 blobs
+
+use crate::cmd::Cmd;
+use clap::{Parser, Subcommand, ValueHint};
+use ethers::types::{H256, U256};
+use eyre::WrapErr;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// The number of field elements of a blob
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// The size of a blob in bytes
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * 32;
+/// The data bytes of a field element, the first byte is zero to keep the element below the BLS
+/// modulus
+const DATA_BYTES_PER_FIELD_ELEMENT: usize = 31;
+/// The gas a blob consumes
+pub const GAS_PER_BLOB: u64 = 1 << 17;
+/// The maximum number of blobs of a block
+pub const MAX_BLOBS_PER_BLOCK: usize = 6;
+const MIN_BASE_FEE_PER_BLOB_GAS: u64 = 1;
+const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3_338_477;
+/// The version byte of the versioned hash of a KZG commitment
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+#[derive(Debug, Parser)]
+pub struct DaArgs {
+    #[clap(subcommand)]
+    pub sub: DaSubcommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DaSubcommands {
+    #[clap(about = "Encodes a file into blobs of 4096 field elements.")]
+    Encode(EncodeArgs),
+    #[clap(about = "Computes the versioned hash of a KZG commitment.")]
+    VersionedHash(VersionedHashArgs),
+    #[clap(about = "Estimates the blob gas and the blob fee of a blob transaction.")]
+    Gas(GasArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct EncodeArgs {
+    #[clap(help = "The file to encode.", value_hint = ValueHint::FilePath, value_name = "FILE")]
+    file: PathBuf,
+
+    #[clap(
+        help = "Write the blobs as JSON to the file instead of stdout.",
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH"
+    )]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct VersionedHashArgs {
+    #[clap(help = "The 48 byte KZG commitment, hex encoded.", value_name = "COMMITMENT")]
+    commitment: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct GasArgs {
+    #[clap(
+        help = "The number of blobs, or the file whose blobs to count.",
+        long_help = "The number of blobs of the transaction, or the file whose blobs to count if the value is a path.",
+        value_name = "BLOBS_OR_FILE"
+    )]
+    blobs: String,
+
+    #[clap(
+        help = "The excess blob gas of the parent block, which sets the blob base fee.",
+        long,
+        default_value = "0",
+        value_name = "GAS"
+    )]
+    excess_blob_gas: u64,
+}
+
+impl Cmd for EncodeArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let data = std::fs::read(&self.file)
+            .wrap_err_with(|| format!("Failed to read {}", self.file.display()))?;
+        let blobs: Vec<String> =
+            encode_blobs(&data).iter().map(|blob| format!("0x{}", hex::encode(blob))).collect();
+        if blobs.len() > MAX_BLOBS_PER_BLOCK {
+            eprintln!(
+                "Warning: {} blobs exceed the limit of {MAX_BLOBS_PER_BLOCK} blobs per block",
+                blobs.len()
+            );
+        }
+        let json = serde_json::to_string_pretty(&serde_json::json!({ "blobs": blobs }))?;
+        match self.out {
+            Some(out) => {
+                std::fs::write(&out, json)
+                    .wrap_err_with(|| format!("Failed to write {}", out.display()))?;
+                println!("Wrote {} blobs to {}", blobs.len(), out.display());
+            }
+            None => println!("{json}"),
+        }
+        Ok(())
+    }
+}
+
+impl Cmd for VersionedHashArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let commitment = self.commitment.strip_prefix("0x").unwrap_or(&self.commitment);
+        let commitment = hex::decode(commitment).wrap_err("Invalid hex commitment")?;
+        if commitment.len() != 48 {
+            eyre::bail!("A KZG commitment has 48 bytes, got {}", commitment.len())
+        }
+        println!("{:?}", versioned_hash(&commitment));
+        Ok(())
+    }
+}
+
+impl Cmd for GasArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let blobs = match self.blobs.parse::<usize>() {
+            Ok(blobs) => blobs,
+            Err(_) => {
+                let len = std::fs::metadata(&self.blobs)
+                    .wrap_err_with(|| format!("`{}` is no number of blobs or file", self.blobs))?
+                    .len();
+                blob_count(len as usize)
+            }
+        };
+        let blob_gas = GAS_PER_BLOB
+            .checked_mul(blobs as u64)
+            .ok_or_else(|| eyre::eyre!("The blob gas of {blobs} blobs overflows"))?;
+        let base_fee = blob_base_fee(self.excess_blob_gas)?;
+        let fee = base_fee
+            .checked_mul(U256::from(blob_gas))
+            .ok_or_else(|| eyre::eyre!("The blob fee of {blobs} blobs overflows"))?;
+        println!("blobs: {blobs}");
+        println!("blob gas: {blob_gas}");
+        println!("blob base fee: {base_fee} wei");
+        println!("blob fee: {fee} wei");
+        if blobs > MAX_BLOBS_PER_BLOCK {
+            eprintln!(
+                "Warning: {blobs} blobs exceed the limit of {MAX_BLOBS_PER_BLOCK} blobs per block"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of blobs [`encode_blobs`] encodes data of the length into
+pub fn blob_count(len: usize) -> usize {
+    let per_blob = FIELD_ELEMENTS_PER_BLOB * DATA_BYTES_PER_FIELD_ELEMENT;
+    len.saturating_sub(1) / per_blob + 1
+}
+
+/// Encodes the data into blobs, 31 bytes per field element
+///
+/// The first byte of every field element is zero, which keeps it below the BLS modulus. The last
+/// blob is padded with zeros, the length of the data is not encoded.
+pub fn encode_blobs(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut blobs = vec![vec![0u8; BYTES_PER_BLOB]; blob_count(data.len())];
+    for (i, chunk) in data.chunks(DATA_BYTES_PER_FIELD_ELEMENT).enumerate() {
+        let blob = &mut blobs[i / FIELD_ELEMENTS_PER_BLOB];
+        let start = (i % FIELD_ELEMENTS_PER_BLOB) * 32 + 1;
+        blob[start..start + chunk.len()].copy_from_slice(chunk);
+    }
+    blobs
+}
+
+/// Returns the versioned hash of a KZG commitment, `0x01 ++ sha256(commitment)[1..]`
+pub fn versioned_hash(commitment: &[u8]) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    H256(hash)
+}
+
+/// Returns the blob base fee of a block with the excess blob gas of its parent
+///
+/// Fails if the fee does not fit into 256 bits, which no block reaches.
+pub fn blob_base_fee(excess_blob_gas: u64) -> eyre::Result<U256> {
+    fake_exponential(
+        U256::from(MIN_BASE_FEE_PER_BLOB_GAS),
+        U256::from(excess_blob_gas),
+        U256::from(BLOB_BASE_FEE_UPDATE_FRACTION),
+    )
+    .ok_or_else(|| {
+        eyre::eyre!("The blob base fee of an excess blob gas of {excess_blob_gas} overflows")
+    })
+}
+
+/// Approximates `factor * e ** (numerator / denominator)` with the Taylor expansion of EIP-4844,
+/// `None` if it overflows
+fn fake_exponential(factor: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    let mut output = U256::zero();
+    let mut accumulator = factor.checked_mul(denominator)?;
+    let mut i = U256::one();
+    while !accumulator.is_zero() {
+        output = output.checked_add(accumulator)?;
+        accumulator = accumulator.checked_mul(numerator)? / denominator.checked_mul(i)?;
+        i += U256::one();
+    }
+    Some(output / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_encode_blobs() {
+        let data: Vec<u8> = (1..=62).collect();
+        let blobs = encode_blobs(&data);
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].len(), BYTES_PER_BLOB);
+        assert_eq!(blobs[0][0], 0);
+        assert_eq!(&blobs[0][1..32], &data[..31]);
+        assert_eq!(blobs[0][32], 0);
+        assert_eq!(&blobs[0][33..64], &data[31..]);
+        assert!(blobs[0][64..].iter().all(|byte| *byte == 0));
+
+        assert_eq!(blob_count(0), 1);
+        assert_eq!(blob_count(FIELD_ELEMENTS_PER_BLOB * 31), 1);
+        assert_eq!(blob_count(FIELD_ELEMENTS_PER_BLOB * 31 + 1), 2);
+    }
+
+    #[test]
+    fn can_compute_blob_base_fee() {
+        assert_eq!(blob_base_fee(0).unwrap(), U256::one());
+        // e ** 1
+        assert_eq!(blob_base_fee(BLOB_BASE_FEE_UPDATE_FRACTION).unwrap(), U256::from(2));
+        // e ** 10
+        assert_eq!(blob_base_fee(10 * BLOB_BASE_FEE_UPDATE_FRACTION).unwrap(), U256::from(22026));
+        assert!(blob_base_fee(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn can_compute_versioned_hash() {
+        let hash = versioned_hash(&[0u8; 48]);
+        assert_eq!(hash.0[0], VERSIONED_HASH_VERSION_KZG);
+        assert_eq!(&hash.0[1..], &Sha256::digest([0u8; 48])[1..]);
+    }
+}
@@ -0,0 +1,267 @@
+//! cast fuzz subcommand
+
+use crate::{
+    cmd::Cmd,
+    utils::{self, consume_config_rpc_url},
+};
+use clap::{ArgEnum, Parser};
+use ethers::{
+    abi::{Function, ParamType, Token},
+    solc::utils::RuntimeOrHandle,
+    types::{Address, I256},
+};
+use forge::{
+    executor::{builder::Backend, opts::EvmOpts, ExecutorBuilder, RawCallResult},
+    fuzz::{fuzz_param, random_seed, seeded_runner},
+    CALLER,
+};
+use foundry_config::Config;
+use foundry_utils::{decode_revert, format_token, format_tokens, get_func};
+use proptest::strategy::{Strategy, ValueTree};
+use std::{cmp::Ordering, collections::BTreeMap};
+use yansi::Paint;
+
+#[derive(Debug, Clone, Parser)]
+pub struct FuzzArgs {
+    #[clap(help = "The address of the contract.", value_name = "ADDRESS")]
+    address: Address,
+    #[clap(
+        help = "The signature of the function, e.g. `getAmountOut(uint256)(uint256)`.",
+        long_help = "The signature of the function, e.g. `getAmountOut(uint256)(uint256)`. The return types are needed to decode and check the outputs.",
+        value_name = "SIG"
+    )]
+    sig: String,
+    #[clap(long, short, help = "The number of calls.", default_value = "256", value_name = "RUNS")]
+    runs: u32,
+    #[clap(
+        long,
+        help = "The seed of the generated inputs.",
+        long_help = "The seed of the generated inputs. Every run prints its seed, so its inputs can be generated again.",
+        value_name = "SEED"
+    )]
+    seed: Option<u64>,
+    #[clap(
+        long,
+        arg_enum,
+        help = "The direction the output must change in when the input increases.",
+        long_help = "The direction the output must change in when the input increases. Applies to functions with a single integer input and an integer output. By default the direction most calls agree on is expected.",
+        value_name = "DIRECTION"
+    )]
+    monotonic: Option<Monotonic>,
+    #[clap(long, short = 'B', help = "The block number to fork. Defaults to the latest block.")]
+    block: Option<u64>,
+    #[clap(long, env = "ETH_RPC_URL")]
+    rpc_url: Option<String>,
+}
+
+/// The expected direction of the output of a monotonic function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum Monotonic {
+    Increasing,
+    Decreasing,
+}
+
+impl Monotonic {
+    fn name(&self) -> &'static str {
+        match self {
+            Monotonic::Increasing => "increasing",
+            Monotonic::Decreasing => "decreasing",
+        }
+    }
+}
+
+impl Cmd for FuzzArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let func = get_func(&self.sig)?;
+        if func.inputs.is_empty() {
+            eyre::bail!("`{}` has no inputs to generate", func.signature())
+        }
+
+        let figment = Config::figment();
+        let mut evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::from_provider(figment).sanitized();
+        evm_opts.fork_url = Some(consume_config_rpc_url(self.rpc_url));
+        evm_opts.fork_block_number = self.block;
+
+        let runtime = RuntimeOrHandle::new();
+        let env = runtime.block_on(evm_opts.evm_env());
+        let fork = utils::get_fork(&evm_opts, &config.rpc_storage_caching);
+        let db = runtime.block_on(Backend::new(fork, &env));
+        let executor = ExecutorBuilder::new()
+            .with_config(env)
+            .with_gas_limit(evm_opts.gas_limit())
+            .with_spec(utils::evm_spec(&config.evm_version))
+            .build(db);
+
+        let seed = self.seed.unwrap_or_else(random_seed);
+        let mut runner = seeded_runner(Default::default(), seed);
+        let strategy = func.inputs.iter().map(|input| fuzz_param(&input.kind)).collect::<Vec<_>>();
+
+        println!("Calling {} {} times (seed: {seed})...", func.signature(), self.runs);
+        let mut report = FuzzReport::default();
+        for _ in 0..self.runs {
+            let inputs = strategy
+                .new_tree(&mut runner)
+                .map_err(|err| eyre::eyre!("Failed to generate inputs: {err}"))?
+                .current();
+            let calldata = func.encode_input(&inputs)?;
+            let RawCallResult { reverted, result, .. } =
+                executor.call_raw(*CALLER, self.address, calldata.into(), 0.into())?;
+            report.record(&func, inputs, reverted, &result);
+        }
+
+        report.print(&func, self.monotonic);
+        Ok(())
+    }
+}
+
+/// The outcomes of all calls
+#[derive(Debug, Default)]
+struct FuzzReport {
+    /// The inputs and outputs of all successful calls
+    calls: Vec<(Vec<Token>, Vec<Token>)>,
+    /// The number of reverts and the first reverting inputs, by revert reason
+    reverts: BTreeMap<String, (usize, Vec<Token>)>,
+    /// The inputs of calls whose output could not be decoded as the return types
+    undecodable: Vec<Vec<Token>>,
+}
+
+impl FuzzReport {
+    fn record(&mut self, func: &Function, inputs: Vec<Token>, reverted: bool, output: &[u8]) {
+        if reverted {
+            let reason =
+                decode_revert(output, None).unwrap_or_else(|_| "unknown reason".to_string());
+            self.reverts.entry(reason).or_insert((0, inputs)).0 += 1;
+        } else if !func.outputs.is_empty() {
+            match func.decode_output(output) {
+                Ok(outputs) => self.calls.push((inputs, outputs)),
+                Err(_) => self.undecodable.push(inputs),
+            }
+        } else {
+            self.calls.push((inputs, Vec::new()));
+        }
+    }
+
+    fn print(&self, func: &Function, monotonic: Option<Monotonic>) {
+        let reverts = self.reverts.values().map(|(count, _)| count).sum::<usize>();
+        let total = self.calls.len() + self.undecodable.len() + reverts;
+        println!("{} of {total} calls succeeded", self.calls.len());
+
+        for (reason, (count, inputs)) in &self.reverts {
+            println!(
+                "{} {count} calls with `{reason}`, e.g. ({})",
+                Paint::yellow("Reverted:"),
+                format_tokens(inputs).collect::<Vec<_>>().join(", ")
+            );
+        }
+        if let Some(inputs) = self.undecodable.first() {
+            println!(
+                "{} {} calls returned data that is not of the return types, e.g. ({})",
+                Paint::red("Invalid output:"),
+                self.undecodable.len(),
+                format_tokens(inputs).collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        if !is_integer_function(func) {
+            return
+        }
+        let points = self
+            .calls
+            .iter()
+            .map(|(inputs, outputs)| (inputs[0].clone(), outputs[0].clone()))
+            .collect::<Vec<_>>();
+        let (direction, violations) = match monotonic {
+            Some(direction) => (direction, monotonicity_violations(&points, direction)),
+            None => {
+                let increasing = monotonicity_violations(&points, Monotonic::Increasing);
+                let decreasing = monotonicity_violations(&points, Monotonic::Decreasing);
+                if increasing.len() <= decreasing.len() {
+                    (Monotonic::Increasing, increasing)
+                } else {
+                    (Monotonic::Decreasing, decreasing)
+                }
+            }
+        };
+        if violations.is_empty() {
+            let name = direction.name();
+            println!("{} the output is monotonically {name}", Paint::green("Monotonic:"));
+            return
+        }
+        println!(
+            "{} the output is not monotonically {} at {} inputs, e.g.",
+            Paint::red("Not monotonic:"),
+            direction.name(),
+            violations.len()
+        );
+        for ((input, output), (next_input, next_output)) in violations.iter().take(3) {
+            println!(
+                "  f({}) = {}, but f({}) = {}",
+                format_token(input),
+                format_token(output),
+                format_token(next_input),
+                format_token(next_output)
+            );
+        }
+    }
+}
+
+/// Returns true if the function has a single integer input and returns an integer first
+fn is_integer_function(func: &Function) -> bool {
+    let is_integer = |kind: &ParamType| matches!(kind, ParamType::Uint(_) | ParamType::Int(_));
+    func.inputs.len() == 1 &&
+        is_integer(&func.inputs[0].kind) &&
+        func.outputs.first().map_or(false, |output| is_integer(&output.kind))
+}
+
+/// Compares two integer tokens of the same type
+fn compare(a: &Token, b: &Token) -> Option<Ordering> {
+    match (a, b) {
+        (Token::Uint(a), Token::Uint(b)) => Some(a.cmp(b)),
+        (Token::Int(a), Token::Int(b)) => Some(I256::from_raw(*a).cmp(&I256::from_raw(*b))),
+        _ => None,
+    }
+}
+
+type Point = (Token, Token);
+
+/// Returns the pairs of neighbouring inputs, ordered by input, whose outputs change against the
+/// direction
+fn monotonicity_violations(points: &[Point], direction: Monotonic) -> Vec<(Point, Point)> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| compare(&a.0, &b.0).unwrap_or(Ordering::Equal));
+    points.dedup_by(|a, b| compare(&a.0, &b.0) == Some(Ordering::Equal));
+    let against = match direction {
+        Monotonic::Increasing => Ordering::Less,
+        Monotonic::Decreasing => Ordering::Greater,
+    };
+    points
+        .windows(2)
+        .filter(|pair| compare(&pair[1].1, &pair[0].1) == Some(against))
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(values: &[(u64, u64)]) -> Vec<Point> {
+        values.iter().map(|(x, y)| (Token::Uint((*x).into()), Token::Uint((*y).into()))).collect()
+    }
+
+    #[test]
+    fn can_find_monotonicity_violations() {
+        let increasing = points(&[(3, 30), (1, 10), (2, 20), (2, 20)]);
+        assert!(monotonicity_violations(&increasing, Monotonic::Increasing).is_empty());
+        assert_eq!(monotonicity_violations(&increasing, Monotonic::Decreasing).len(), 2);
+
+        let spike = points(&[(1, 10), (2, 50), (3, 30), (4, 40)]);
+        assert_eq!(
+            monotonicity_violations(&spike, Monotonic::Increasing),
+            [(spike[1].clone(), spike[2].clone())]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! cast interface-check subcommand
+
+use crate::{cmd::Cmd, utils::consume_config_rpc_url};
+use clap::Parser;
+use ethers::{
+    abi::{self, Token},
+    prelude::*,
+    utils::id,
+};
+use eyre::Result;
+use futures::future::BoxFuture;
+use std::collections::BTreeSet;
+use yansi::Paint;
+
+#[derive(Debug, Clone, Parser)]
+pub struct InterfaceCheckArgs {
+    #[clap(help = "The address of the contract.", value_name = "ADDRESS")]
+    address: Address,
+    #[clap(long, env = "ETH_RPC_URL")]
+    rpc_url: Option<String>,
+}
+
+impl Cmd for InterfaceCheckArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        let InterfaceCheckArgs { address, rpc_url } = self;
+        Ok(Box::pin(Self::check(address, rpc_url)))
+    }
+}
+
+/// A standard detected by the selectors of its functions, and its ERC-165 interface id if it has
+/// one
+struct Standard {
+    name: &'static str,
+    interface_id: Option<[u8; 4]>,
+    functions: &'static [&'static str],
+}
+
+const STANDARDS: &[Standard] = &[
+    Standard {
+        name: "ERC-20",
+        interface_id: None,
+        functions: &[
+            "totalSupply()",
+            "balanceOf(address)",
+            "transfer(address,uint256)",
+            "transferFrom(address,address,uint256)",
+            "approve(address,uint256)",
+            "allowance(address,address)",
+        ],
+    },
+    Standard {
+        name: "ERC-721",
+        interface_id: Some([0x80, 0xac, 0x58, 0xcd]),
+        functions: &[
+            "balanceOf(address)",
+            "ownerOf(uint256)",
+            "safeTransferFrom(address,address,uint256,bytes)",
+            "safeTransferFrom(address,address,uint256)",
+            "transferFrom(address,address,uint256)",
+            "approve(address,uint256)",
+            "setApprovalForAll(address,bool)",
+            "getApproved(uint256)",
+            "isApprovedForAll(address,address)",
+        ],
+    },
+    Standard {
+        name: "ERC-1155",
+        interface_id: Some([0xd9, 0xb6, 0x7a, 0x26]),
+        functions: &[
+            "balanceOf(address,uint256)",
+            "balanceOfBatch(address[],uint256[])",
+            "setApprovalForAll(address,bool)",
+            "isApprovedForAll(address,address)",
+            "safeTransferFrom(address,address,uint256,uint256,bytes)",
+            "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+        ],
+    },
+    Standard {
+        name: "ERC-4626",
+        interface_id: None,
+        functions: &[
+            "asset()",
+            "totalAssets()",
+            "convertToShares(uint256)",
+            "convertToAssets(uint256)",
+            "maxDeposit(address)",
+            "previewDeposit(uint256)",
+            "deposit(uint256,address)",
+            "maxMint(address)",
+            "previewMint(uint256)",
+            "mint(uint256,address)",
+            "maxWithdraw(address)",
+            "previewWithdraw(uint256)",
+            "withdraw(uint256,address,address)",
+            "maxRedeem(address)",
+            "previewRedeem(uint256)",
+            "redeem(uint256,address,address)",
+        ],
+    },
+    Standard {
+        name: "Ownable",
+        interface_id: None,
+        functions: &["owner()", "transferOwnership(address)", "renounceOwnership()"],
+    },
+];
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// `bytes32(uint256(keccak256("eip1967.proxy.beacon")) - 1)`
+const EIP1967_BEACON_SLOT: &str =
+    "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// `keccak256("PROXIABLE")`
+const EIP1822_PROXIABLE_SLOT: &str =
+    "0xc5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7";
+
+/// The code of an EIP-1167 minimal proxy before and after the address of its implementation
+const MINIMAL_PROXY_PREFIX: &str = "363d3d373d3d3d363d73";
+const MINIMAL_PROXY_SUFFIX: &str = "5af43d82803e903d91602b57fd5bf3";
+
+const ERC165_INTERFACE_ID: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+
+impl InterfaceCheckArgs {
+    async fn check(address: Address, rpc_url: Option<String>) -> Result<()> {
+        let provider = Provider::try_from(consume_config_rpc_url(rpc_url))?;
+
+        let code = provider.get_code(address, None).await?;
+        if code.0.is_empty() {
+            eyre::bail!("{address:?} has no code")
+        }
+        println!("Code size: {} bytes", code.0.len());
+
+        let mut selectors = push_values(&code);
+        if let Some((kind, implementation)) = find_proxy(&provider, address, &code).await? {
+            println!("Proxy: {kind}, implementation {implementation:?}");
+            let implementation_code = provider.get_code(implementation, None).await?;
+            selectors.extend(push_values(&implementation_code));
+        }
+
+        let erc165 = supports_interface(&provider, address, ERC165_INTERFACE_ID).await &&
+            !supports_interface(&provider, address, [0xff; 4]).await;
+        println!("ERC-165: {}", if erc165 { "supported" } else { "not supported" });
+
+        for standard in STANDARDS {
+            let via_erc165 = match standard.interface_id {
+                Some(interface_id) if erc165 => {
+                    supports_interface(&provider, address, interface_id).await
+                }
+                _ => false,
+            };
+            let missing = standard
+                .functions
+                .iter()
+                .filter(|function| !selectors.contains(&id(function)))
+                .collect::<Vec<_>>();
+            let found = standard.functions.len() - missing.len();
+            let summary = format!("{found}/{} functions", standard.functions.len());
+
+            if via_erc165 || missing.is_empty() {
+                let source = if via_erc165 { ", supported via ERC-165" } else { "" };
+                println!("{}: {} ({summary}{source})", standard.name, Paint::green("matches"));
+            } else if found * 2 >= standard.functions.len() {
+                println!("{}: {} ({summary})", standard.name, Paint::yellow("partial"));
+            } else {
+                println!("{}: {} ({summary})", standard.name, Paint::new("no").dimmed());
+                continue
+            }
+            for function in missing {
+                println!("  missing {function}");
+            }
+        }
+
+        println!();
+        println!(
+            "Functions are detected by their selectors in the code, which may miss functions that \
+             are dispatched in other ways, e.g. by fallbacks."
+        );
+        Ok(())
+    }
+}
+
+/// Returns the implementation of the contract if it is a known kind of proxy
+async fn find_proxy(
+    provider: &Provider<Http>,
+    address: Address,
+    code: &Bytes,
+) -> Result<Option<(&'static str, Address)>> {
+    if let Some(implementation) = minimal_proxy_implementation(code) {
+        return Ok(Some(("EIP-1167 minimal proxy", implementation)))
+    }
+
+    let read_address = |slot: &'static str| async move {
+        let value = provider.get_storage_at(address, slot.parse::<H256>()?, None).await?;
+        Ok::<_, eyre::Report>(Some(Address::from(value)).filter(|address| !address.is_zero()))
+    };
+    if let Some(implementation) = read_address(EIP1967_IMPLEMENTATION_SLOT).await? {
+        return Ok(Some(("EIP-1967 proxy", implementation)))
+    }
+    if let Some(beacon) = read_address(EIP1967_BEACON_SLOT).await? {
+        let tx = TransactionRequest::new().to(beacon).data(id("implementation()").to_vec());
+        let output = provider.call(&tx.into(), None).await?;
+        if output.len() == 32 {
+            return Ok(Some(("EIP-1967 beacon proxy", Address::from_slice(&output[12..]))))
+        }
+    }
+    if let Some(implementation) = read_address(EIP1822_PROXIABLE_SLOT).await? {
+        return Ok(Some(("EIP-1822 proxy", implementation)))
+    }
+    Ok(None)
+}
+
+/// Returns true if `supportsInterface(interface_id)` returns true
+async fn supports_interface(
+    provider: &Provider<Http>,
+    address: Address,
+    interface_id: [u8; 4],
+) -> bool {
+    let mut data = id("supportsInterface(bytes4)").to_vec();
+    data.extend(abi::encode(&[Token::FixedBytes(interface_id.to_vec())]));
+    let tx = TransactionRequest::new().to(address).data(data);
+    match provider.call(&tx.into(), None).await {
+        Ok(output) => output.len() == 32 && output[31] == 1 && output[..31].iter().all(|b| *b == 0),
+        Err(_) => false,
+    }
+}
+
+/// Returns the implementation of an EIP-1167 minimal proxy
+fn minimal_proxy_implementation(code: &[u8]) -> Option<Address> {
+    let code = hex::encode(code);
+    let address = code.strip_prefix(MINIMAL_PROXY_PREFIX)?.strip_suffix(MINIMAL_PROXY_SUFFIX)?;
+    (address.len() == 40).then(|| address.parse().ok()).flatten()
+}
+
+/// Returns the values of all `PUSH1` to `PUSH4` instructions, left padded to 4 bytes
+///
+/// Function dispatchers compare the selector of the calldata with the selectors of all functions,
+/// which are pushed with `PUSH4`, or a shorter push if the selector starts with zero bytes.
+fn push_values(code: &[u8]) -> BTreeSet<[u8; 4]> {
+    let mut values = BTreeSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        // PUSH1 is 0x60, PUSH32 is 0x7f
+        if (0x60..=0x7f).contains(&op) {
+            let size = (op - 0x5f) as usize;
+            if size <= 4 && pc + size < code.len() {
+                let mut value = [0u8; 4];
+                value[4 - size..].copy_from_slice(&code[pc + 1..pc + 1 + size]);
+                values.insert(value);
+            }
+            pc += size;
+        }
+        pc += 1;
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_pushed_selectors() {
+        // PUSH4 0x70a08231 PUSH3 0xfdd58e PUSH32 0x63aabbccdd...
+        let mut code = hex::decode("6370a0823162fdd58e7f").unwrap();
+        code.extend([0x63, 0xaa, 0xbb, 0xcc, 0xdd]);
+        code.extend([0; 27]);
+
+        let values = push_values(&code);
+        assert!(values.contains(&id("balanceOf(address)")));
+        assert!(values.contains(&id("balanceOf(address,uint256)")));
+        assert!(!values.contains(&[0xaa, 0xbb, 0xcc, 0xdd]));
+    }
+
+    #[test]
+    fn can_detect_minimal_proxies() {
+        let implementation = "bebebebebebebebebebebebebebebebebebebebe";
+        let code = hex::decode(format!(
+            "{MINIMAL_PROXY_PREFIX}{implementation}{MINIMAL_PROXY_SUFFIX}"
+        ))
+        .unwrap();
+
+        assert_eq!(minimal_proxy_implementation(&code), Some(implementation.parse().unwrap()));
+        assert_eq!(minimal_proxy_implementation(&code[1..]), None);
+    }
+}
@@ -5,5 +5,10 @@
 //! implement `figment::Provider` which allows the subcommand to override the config's defaults, see
 //! [`foundry_config::Config`].
 
+pub mod da;
 pub mod find_block;
+pub mod fuzz;
+pub mod interface_check;
+pub mod monitor;
 pub mod run;
+pub mod source_map;
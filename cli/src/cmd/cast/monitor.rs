@@ -0,0 +1,375 @@
+//! cast monitor subcommand
+
+use crate::{cmd::Cmd, utils::consume_config_rpc_url};
+use clap::{Parser, ValueHint};
+use ethers::{
+    abi::{Abi, AbiParser, Event, RawLog},
+    etherscan::Client,
+    prelude::*,
+    utils::keccak256,
+};
+use eyre::{Result, WrapErr};
+use foundry_config::Config;
+use foundry_utils::format_token;
+use futures::future::{try_join_all, BoxFuture};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+use walkdir::WalkDir;
+use yansi::Paint;
+
+/// The maximum number of blocks queried by a single `eth_getLogs` request
+const MAX_BLOCK_RANGE: u64 = 2_000;
+
+#[derive(Debug, Clone, Parser)]
+pub struct MonitorArgs {
+    #[clap(
+        long = "address",
+        short,
+        help = "An address to watch. Can be passed multiple times, all addresses are watched by default.",
+        value_name = "ADDRESS"
+    )]
+    addresses: Vec<Address>,
+    #[clap(
+        long = "topic",
+        short,
+        help = "An event to watch. Can be passed multiple times, all events are watched by default.",
+        long_help = "An event to watch, as topic hash or event signature, e.g. `Transfer(address indexed from, address indexed to, uint256 value)`. Events given by their full signature are decoded without an ABI. Can be passed multiple times, all events are watched by default.",
+        value_name = "TOPIC"
+    )]
+    topics: Vec<String>,
+    #[clap(
+        long = "abi",
+        help = "An ABI or artifact file to decode events with. Can be passed multiple times.",
+        long_help = "An ABI or artifact file to decode events with. Can be passed multiple times. The artifacts of the project and the verified ABIs of the watched addresses, if an Etherscan API key is set, are used as well.",
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH"
+    )]
+    abis: Vec<PathBuf>,
+    #[clap(
+        long,
+        help = "The block to start at. Defaults to the latest block.",
+        value_name = "BLOCK"
+    )]
+    from_block: Option<u64>,
+    #[clap(
+        long,
+        help = "The number of blocks an event must be confirmed by before it is forwarded.",
+        default_value = "0",
+        value_name = "BLOCKS"
+    )]
+    confirmations: u64,
+    #[clap(
+        long,
+        help = "The number of seconds between polls.",
+        default_value = "5",
+        value_name = "SECONDS"
+    )]
+    interval: u64,
+    #[clap(long, help = "POST every event as JSON to the URL.", value_name = "URL")]
+    webhook: Option<String>,
+    #[clap(
+        long,
+        short,
+        help = "Append every event as a line of JSON to the file.",
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH"
+    )]
+    output: Option<PathBuf>,
+    #[clap(long, env = "ETHERSCAN_API_KEY")]
+    etherscan_api_key: Option<String>,
+    #[clap(
+        long = "rpc-url",
+        env = "ETH_RPC_URL",
+        help = "The RPC endpoint to watch. Can be passed multiple times to watch multiple chains.",
+        value_name = "URL"
+    )]
+    rpc_urls: Vec<String>,
+}
+
+impl Cmd for MonitorArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(Box::pin(self.monitor()))
+    }
+}
+
+impl MonitorArgs {
+    async fn monitor(self) -> Result<()> {
+        let mut events = Events::default();
+        let mut topics = Vec::with_capacity(self.topics.len());
+        for topic in &self.topics {
+            topics.push(events.add_topic(topic)?);
+        }
+        for path in &self.abis {
+            events.add_abi(&read_abi(path)?);
+        }
+        events.add_artifacts(&Config::load().out);
+
+        let rpc_urls = if self.rpc_urls.is_empty() {
+            vec![consume_config_rpc_url(None)]
+        } else {
+            self.rpc_urls.clone()
+        };
+        let sinks = Sinks {
+            webhook: self.webhook.clone().map(|url| (reqwest::Client::new(), url)),
+            output: match &self.output {
+                Some(path) => Some(Mutex::new(
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .wrap_err_with(|| format!("Failed to open {}", path.display()))?,
+                )),
+                None => None,
+            },
+        };
+
+        let mut watchers = Vec::with_capacity(rpc_urls.len());
+        for rpc_url in rpc_urls {
+            let provider = Provider::<Http>::try_from(rpc_url.as_str())?;
+            let chain_id = provider.get_chainid().await?.as_u64();
+            let mut events = events.clone();
+            if let Some(api_key) = &self.etherscan_api_key {
+                if let Ok(chain) = Chain::try_from(chain_id) {
+                    events.add_verified_abis(chain, api_key, &self.addresses).await;
+                }
+            }
+            eprintln!("Watching chain {chain_id} at {rpc_url}");
+            watchers.push(Watcher {
+                provider,
+                chain_id,
+                events,
+                filter: self.filter(&topics),
+                confirmations: self.confirmations,
+                from_block: self.from_block,
+            });
+        }
+
+        let interval = Duration::from_secs(self.interval);
+        try_join_all(watchers.into_iter().map(|watcher| watcher.watch(interval, &sinks))).await?;
+        Ok(())
+    }
+
+    fn filter(&self, topics: &[H256]) -> Filter {
+        let mut filter = Filter::new();
+        if !self.addresses.is_empty() {
+            filter = filter.address(ValueOrArray::Array(self.addresses.clone()));
+        }
+        if !topics.is_empty() {
+            filter = filter.topic0(ValueOrArray::Array(topics.iter().copied().map(Some).collect()));
+        }
+        filter
+    }
+}
+
+/// The events logs are decoded with, by topic
+#[derive(Debug, Clone, Default)]
+struct Events(BTreeMap<H256, Vec<Event>>);
+
+impl Events {
+    fn add(&mut self, event: Event) {
+        let events = self.0.entry(event.signature()).or_default();
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+
+    fn add_abi(&mut self, abi: &Abi) {
+        abi.events().cloned().for_each(|event| self.add(event));
+    }
+
+    /// Parses the topic hash or event signature and returns its topic
+    fn add_topic(&mut self, topic: &str) -> Result<H256> {
+        if let Ok(hash) = topic.parse::<H256>() {
+            return Ok(hash)
+        }
+        let signature = topic.trim();
+        let signature = signature.strip_prefix("event ").unwrap_or(signature);
+        match AbiParser::default().parse_event(&format!("event {signature}")) {
+            Ok(event) => {
+                let hash = event.signature();
+                self.add(event);
+                Ok(hash)
+            }
+            Err(_) if signature.contains('(') => Ok(H256::from(keccak256(signature))),
+            Err(err) => eyre::bail!("Invalid topic `{topic}`: {err}"),
+        }
+    }
+
+    /// Adds the events of all artifacts in the directory, artifacts that can't be read are skipped
+    fn add_artifacts(&mut self, out: &Path) {
+        let artifacts = WalkDir::new(out)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "json"));
+        for artifact in artifacts {
+            if let Ok(abi) = read_abi(artifact.path()) {
+                self.add_abi(&abi);
+            }
+        }
+    }
+
+    async fn add_verified_abis(&mut self, chain: Chain, api_key: &str, addresses: &[Address]) {
+        let client = match Client::new(chain, api_key) {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        for address in addresses {
+            match client.contract_abi(*address).await {
+                Ok(abi) => self.add_abi(&abi),
+                Err(err) => {
+                    let warning = Paint::yellow("Warning:");
+                    eprintln!("{warning} no verified ABI for {address:?}: {err}")
+                }
+            }
+        }
+    }
+
+    /// Returns the event and its decoded parameters
+    fn decode(&self, log: &Log) -> Option<(&Event, Map<String, Value>)> {
+        self.0.get(log.topics.first()?)?.iter().find_map(|event| {
+            let raw = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+            let decoded = event.parse_log(raw).ok()?;
+            let params = decoded
+                .params
+                .into_iter()
+                .map(|param| (param.name, Value::String(format_token(&param.value))))
+                .collect();
+            Some((event, params))
+        })
+    }
+
+    /// Returns the log as JSON, with the event name and parameters if it can be decoded
+    fn to_json(&self, chain_id: u64, log: &Log) -> Value {
+        let mut json = json!({
+            "chainId": chain_id,
+            "address": log.address,
+            "blockNumber": log.block_number,
+            "transactionHash": log.transaction_hash,
+            "logIndex": log.log_index,
+            "topics": log.topics,
+            "data": log.data,
+        });
+        if let Some((event, params)) = self.decode(log) {
+            json["event"] = Value::String(event.name.clone());
+            json["params"] = Value::Object(params);
+        }
+        json
+    }
+}
+
+/// Reads the ABI of an ABI file or of an artifact
+fn read_abi(path: &Path) -> Result<Abi> {
+    let file = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    let mut json: Value = serde_json::from_str(&file)?;
+    let json = if !json["abi"].is_null() { json["abi"].take() } else { json };
+    serde_json::from_value(json).wrap_err_with(|| format!("Failed to parse ABI {}", path.display()))
+}
+
+/// Where events are forwarded to in addition to stdout
+struct Sinks {
+    webhook: Option<(reqwest::Client, String)>,
+    output: Option<Mutex<File>>,
+}
+
+impl Sinks {
+    /// Forwards the event, failures are reported but do not stop the monitor
+    async fn forward(&self, event: &Value) {
+        println!("{event}");
+        if let Some(output) = &self.output {
+            let mut file = output.lock().unwrap();
+            if let Err(err) = writeln!(file, "{event}") {
+                eprintln!("{} failed to write the event: {err}", Paint::yellow("Warning:"));
+            }
+        }
+        if let Some((client, url)) = &self.webhook {
+            let response = client.post(url).json(event).send().await;
+            if let Err(err) = response.and_then(|response| response.error_for_status()) {
+                eprintln!("{} failed to post the event: {err}", Paint::yellow("Warning:"));
+            }
+        }
+    }
+}
+
+/// Polls the logs of one chain
+struct Watcher {
+    provider: Provider<Http>,
+    chain_id: u64,
+    events: Events,
+    filter: Filter,
+    confirmations: u64,
+    /// The next block to query, the latest block by default
+    from_block: Option<u64>,
+}
+
+impl Watcher {
+    async fn watch(mut self, interval: Duration, sinks: &Sinks) -> Result<()> {
+        loop {
+            if let Err(err) = self.poll(sinks).await {
+                eprintln!(
+                    "{} failed to poll chain {}: {err}",
+                    Paint::yellow("Warning:"),
+                    self.chain_id
+                );
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Forwards the events of all confirmed blocks since the last poll
+    async fn poll(&mut self, sinks: &Sinks) -> Result<()> {
+        let latest = self.provider.get_block_number().await?.as_u64();
+        let to = match latest.checked_sub(self.confirmations) {
+            Some(to) => to,
+            None => return Ok(()),
+        };
+        let mut from = *self.from_block.get_or_insert(to);
+        while from <= to {
+            let end = to.min(from + MAX_BLOCK_RANGE - 1);
+            let filter = self.filter.clone().from_block(from).to_block(end);
+            for log in self.provider.get_logs(&filter).await? {
+                sinks.forward(&self.events.to_json(self.chain_id, &log)).await;
+            }
+            from = end + 1;
+            self.from_block = Some(from);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_decode_logs() {
+        let mut events = Events::default();
+        let topic = events
+            .add_topic("Transfer(address indexed from, address indexed to, uint256 value)")
+            .unwrap();
+        assert_eq!(topic, H256::from(keccak256("Transfer(address,address,uint256)")));
+
+        let from = H256::from(Address::from_low_u64_be(1));
+        let log = Log {
+            topics: vec![topic, from, H256::zero()],
+            data: H256::from_low_u64_be(100).as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+        let json = events.to_json(1, &log);
+        assert_eq!(json["event"], "Transfer");
+        assert_eq!(json["params"]["from"], "0x0000000000000000000000000000000000000001");
+        assert_eq!(json["params"]["value"], "100");
+
+        let unknown = Log { topics: vec![H256::zero()], ..Default::default() };
+        assert!(events.to_json(1, &unknown).get("event").is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! cast source-map subcommand
+
+use crate::{cmd::Cmd, compile, utils};
+use clap::Parser;
+use ethers::solc::{
+    artifacts::{CompactContractBytecode, ContractBytecode},
+    Artifact,
+};
+use eyre::{eyre, WrapErr};
+use forge::coverage::instruction_counters;
+use foundry_config::Config;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Parser)]
+pub struct SourceMapArgs {
+    #[clap(
+        help = "The contract to resolve the program counter in, `<path>:<contract name>`.",
+        long_help = r#"The contract to resolve the program counter in, `<path>:<contract name>`.
+
+The contract name may be omitted if the file contains a single contract."#,
+        value_name = "CONTRACT"
+    )]
+    contract: String,
+    #[clap(help = "The program counter, as a decimal or hex number.", value_name = "PC")]
+    pc: String,
+    #[clap(
+        long,
+        help = "Resolve the program counter in the creation code instead of the runtime code."
+    )]
+    create: bool,
+    #[clap(
+        long,
+        default_value = "2",
+        help = "The number of lines to print before and after the source location.",
+        value_name = "LINES"
+    )]
+    context: usize,
+}
+
+impl Cmd for SourceMapArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let pc = usize::try_from(utils::parse_u256(&self.pc)?)
+            .map_err(|_| eyre!("Program counter {} is too large", self.pc))?;
+        let (path, name) = match self.contract.split_once(':') {
+            Some((path, name)) => (path, Some(name)),
+            None => (self.contract.as_str(), None),
+        };
+        let target = dunce::canonicalize(path)
+            .wrap_err_with(|| format!("Failed to find source file {path}"))?;
+
+        // The source map refers to source files by their index in the compiler run
+        let project = Config::load().ephemeral_no_artifacts_project()?;
+        let output = compile::compile_files(&project, vec![target.clone()])?;
+        let (artifacts, sources) = output.into_artifacts_with_sources();
+
+        let mut matches = artifacts
+            .into_iter()
+            .filter(|(id, _)| id.source == target && name.map_or(true, |name| id.name == name));
+        let (id, artifact) =
+            matches.next().ok_or_else(|| eyre!("No contract found for {}", self.contract))?;
+        if matches.next().is_some() {
+            eyre::bail!(
+                "Multiple contracts in {path}, please specify one with `{path}:<contract name>`"
+            )
+        }
+
+        let contract: CompactContractBytecode = artifact.into_contract_bytecode();
+        let contract: ContractBytecode = contract.into();
+        let bytecode = if self.create {
+            contract.bytecode
+        } else {
+            contract.deployed_bytecode.and_then(|code| code.bytecode)
+        }
+        .ok_or_else(|| eyre!("No bytecode found for {}", id.name))?;
+        let source_map = bytecode
+            .source_map()
+            .ok_or_else(|| eyre!("No source map found for {}", id.name))?
+            .map_err(|err| eyre!("Failed to parse the source map of {}: {err}", id.name))?;
+        let code = bytecode
+            .object
+            .as_bytes()
+            .ok_or_else(|| eyre!("The bytecode of {} is not linked", id.name))?;
+
+        let ic = *instruction_counters(code).get(pc).ok_or_else(|| {
+            eyre!("Program counter {pc} is out of bounds, the code is {} bytes long", code.len())
+        })?;
+        let element =
+            source_map.get(ic).ok_or_else(|| eyre!("No source map entry for instruction {ic}"))?;
+        let index = element
+            .index
+            .ok_or_else(|| eyre!("Program counter {pc} does not map to any source file"))?;
+
+        let sources: BTreeMap<u32, String> = sources.into_ids().collect();
+        let source_path = sources.get(&index).ok_or_else(|| {
+            eyre!("Program counter {pc} maps to source file {index}, which is compiler generated")
+        })?;
+        let resolved = project
+            .paths
+            .resolve_library_import(Path::new(source_path))
+            .unwrap_or_else(|| PathBuf::from(source_path));
+        let content = std::fs::read_to_string(&resolved)
+            .wrap_err_with(|| format!("Failed to read source file {source_path}"))?;
+
+        print!("{}", snippet(source_path, &content, element.offset, element.length, self.context));
+        Ok(())
+    }
+}
+
+/// Formats the lines of `content` that contain the range `offset..offset + length`, marked with
+/// `>`, surrounded by `context` lines before and after
+fn snippet(path: &str, content: &str, offset: usize, length: usize, context: usize) -> String {
+    let offset = offset.min(content.len());
+    let end = (offset + length.saturating_sub(1)).min(content.len()).max(offset);
+    let line_of = |pos: usize| content[..pos].matches('\n').count();
+    let (first, last) = (line_of(offset), line_of(end));
+    let column = offset - content[..offset].rfind('\n').map_or(0, |pos| pos + 1) + 1;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let to = (last + context).min(lines.len().saturating_sub(1));
+    let width = (to + 1).to_string().len();
+
+    let mut out = format!("{path}:{}:{column}\n", first + 1);
+    for (idx, line) in lines.iter().enumerate().take(to + 1).skip(first.saturating_sub(context)) {
+        let marker = if (first..=last).contains(&idx) { '>' } else { ' ' };
+        let _ = writeln!(out, "{marker} {:>width$} | {line}", idx + 1);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_format_snippet() {
+        let content =
+            "contract A {\n    uint x;\n    function f() public {\n        x++;\n    }\n}\n";
+        let offset = content.find("x++").unwrap();
+        let snippet = snippet("src/A.sol", content, offset, 3, 1);
+        assert_eq!(
+            snippet,
+            "src/A.sol:4:9\n  3 |     function f() public {\n> 4 |         x++;\n  5 |     }\n"
+        );
+    }
+}
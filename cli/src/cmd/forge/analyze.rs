@@ -0,0 +1,268 @@
+//! Analyze command
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, Cmd},
+    compile::ProjectCompiler,
+};
+use clap::{Parser, ValueHint};
+use ethers::solc::{Project, ProjectCompileOutput};
+use eyre::WrapErr;
+use forge::{
+    analysis::{parse_mythril, parse_slither, to_sarif, Analyzer, Finding, Severity},
+    matches_any_glob,
+};
+use foundry_config::Config;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use yansi::Paint;
+
+// Loads project's figment and merges the build cli arguments into it
+foundry_config::impl_figment_convert!(AnalyzeArgs, opts);
+
+#[derive(Debug, Clone, Parser)]
+pub struct AnalyzeArgs {
+    /// The analyzers to run, overrides the `analyzers` config.
+    #[clap(long = "analyzer", value_name = "ANALYZER", possible_values = ["slither", "mythril"])]
+    analyzers: Vec<Analyzer>,
+
+    /// Only report findings of at least this severity.
+    #[clap(
+        long,
+        value_name = "SEVERITY",
+        default_value = "informational",
+        possible_values = ["informational", "low", "medium", "high"]
+    )]
+    min_severity: Severity,
+
+    /// Don't report findings of the check, e.g. `reentrancy-eth` or `SWC-107`.
+    #[clap(long, value_name = "CHECK")]
+    exclude_check: Vec<String>,
+
+    /// Only report findings in the source files that match the glob, e.g. `src/Vault.sol`.
+    ///
+    /// By default findings in tests and libraries are not reported.
+    #[clap(long, value_name = "GLOB")]
+    match_path: Option<String>,
+
+    /// Print the findings as JSON.
+    #[clap(long)]
+    json: bool,
+
+    /// Write the findings as a SARIF log to the file, e.g. for GitHub code scanning.
+    #[clap(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    sarif: Option<PathBuf>,
+
+    /// Exit with code 1 if a finding of at least this severity is reported.
+    #[clap(
+        long,
+        value_name = "SEVERITY",
+        possible_values = ["informational", "low", "medium", "high"]
+    )]
+    fail_on: Option<Severity>,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+impl Cmd for AnalyzeArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let mut config: Config = From::from(&self);
+        let analyzers = if self.analyzers.is_empty() {
+            config
+                .analyzers
+                .iter()
+                .map(|analyzer| analyzer.parse().map_err(|err: String| eyre::eyre!(err)))
+                .collect::<eyre::Result<Vec<Analyzer>>>()?
+        } else {
+            self.analyzers.clone()
+        };
+        if analyzers.is_empty() {
+            eyre::bail!("No analyzer configured, set `analyzers` or pass `--analyzer`")
+        }
+
+        // The analyzers read the standard json input and output of the build info files, a build
+        // without them is compiled again
+        config.build_info = true;
+        let project = config.project()?;
+        let build_info = project.paths.artifacts.join("build-info");
+        if !build_info.exists() {
+            project.cleanup()?;
+        }
+        let output = ProjectCompiler::default().compile(&project)?;
+        let root = project.paths.root.clone();
+        if !self.json {
+            println!("Build info written to {}", build_info.display());
+        }
+
+        let mut findings = Vec::new();
+        for analyzer in analyzers {
+            if !self.json {
+                println!("Running {analyzer}...");
+            }
+            let found = match analyzer {
+                Analyzer::Slither => run_slither(&project)?,
+                Analyzer::Mythril => run_mythril(&project, &config, output.clone())?,
+            };
+            findings.extend(found);
+        }
+
+        let is_reported = |finding: &Finding| {
+            let path = match &finding.file {
+                Some(file) => file.as_str(),
+                None => return self.match_path.is_none(),
+            };
+            match &self.match_path {
+                Some(glob) => matches_any_glob(&[glob.clone()], path),
+                None => {
+                    let abs = root.join(path);
+                    !abs.starts_with(&project.paths.tests) &&
+                        !project.paths.libraries.iter().any(|lib| abs.starts_with(lib))
+                }
+            }
+        };
+        let mut findings: Vec<Finding> = findings
+            .into_iter()
+            .map(|mut finding| {
+                finding.file = finding.file.map(|file| relative_path(&root, &file));
+                finding
+            })
+            .filter(|finding| {
+                finding.severity >= self.min_severity &&
+                    !self.exclude_check.contains(&finding.check) &&
+                    is_reported(finding)
+            })
+            .collect();
+        findings.sort_by(|a, b| {
+            b.severity.cmp(&a.severity).then_with(|| (&a.file, a.line).cmp(&(&b.file, b.line)))
+        });
+
+        if let Some(path) = &self.sarif {
+            std::fs::write(path, serde_json::to_string_pretty(&to_sarif(&findings))?)
+                .wrap_err_with(|| format!("Failed to write SARIF log to {}", path.display()))?;
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+        } else {
+            print_findings(&findings);
+        }
+
+        if let Some(fail_on) = self.fail_on {
+            if findings.iter().any(|finding| finding.severity >= fail_on) {
+                eprintln!("{}", Paint::red(format!("Found issues of at least {fail_on} severity")));
+                std::process::exit(1)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs slither on the compiled project and returns its findings
+///
+/// Slither reads the remappings and the solc versions of the sources from the build info files.
+fn run_slither(project: &Project) -> eyre::Result<Vec<Finding>> {
+    let output = Command::new(Analyzer::Slither.executable())
+        .arg(&project.paths.root)
+        .arg("--foundry-ignore-compile")
+        .arg("--foundry-out-directory")
+        .arg(&project.paths.artifacts)
+        .args(&["--json", "-"])
+        .current_dir(&project.paths.root)
+        .output()
+        .wrap_err("Failed to run slither, is it installed? `pip3 install slither-analyzer`")?;
+    // slither exits with a non-zero code if it reports findings
+    parse_slither(&String::from_utf8_lossy(&output.stdout)).wrap_err_with(|| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        format!("Failed to parse the output of slither: {stderr}")
+    })
+}
+
+/// Runs mythril on every source file of the project that is not a test or a library and returns
+/// its findings
+fn run_mythril(
+    project: &Project,
+    config: &Config,
+    output: ProjectCompileOutput,
+) -> eyre::Result<Vec<Finding>> {
+    // The solc settings file mythril passes the remappings to solc with
+    let settings = config.cache_path.join("analyze").join("solc.json");
+    std::fs::create_dir_all(settings.parent().expect("file has a parent"))?;
+    let remappings: Vec<String> = project.paths.remappings.iter().map(|r| r.to_string()).collect();
+    let content = serde_json::json!({ "remappings": remappings });
+    std::fs::write(&settings, serde_json::to_string(&content)?)?;
+
+    let (_, sources) = output.into_artifacts_with_sources();
+    let versions: BTreeMap<String, String> = sources
+        .0
+        .into_iter()
+        .filter_map(|(path, versions)| {
+            Some((path, versions.into_iter().next()?.version.to_string()))
+        })
+        .collect();
+
+    let mut findings = Vec::new();
+    for (path, version) in versions {
+        let abs = project.paths.root.join(&path);
+        if !abs.starts_with(&project.paths.sources) ||
+            abs.starts_with(&project.paths.tests) ||
+            project.paths.libraries.iter().any(|lib| abs.starts_with(lib))
+        {
+            continue
+        }
+        let output = Command::new(Analyzer::Mythril.executable())
+            .arg("analyze")
+            .arg(&abs)
+            .args(&["--solv", &version])
+            .arg("--solc-json")
+            .arg(&settings)
+            .args(&["-o", "json"])
+            .current_dir(&project.paths.root)
+            .output()
+            .wrap_err("Failed to run mythril, is it installed? `pip3 install mythril`")?;
+        findings.extend(parse_mythril(&String::from_utf8_lossy(&output.stdout)).wrap_err_with(
+            || {
+                format!(
+                    "Failed to parse the output of mythril for {}: {}",
+                    abs.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                )
+            },
+        )?);
+    }
+    Ok(findings)
+}
+
+/// Returns the path relative to the root if it is absolute, with `/` separators
+fn relative_path(root: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    foundry_config::slash_path(path.strip_prefix(root).unwrap_or(path))
+}
+
+fn print_findings(findings: &[Finding]) {
+    println!();
+    if findings.is_empty() {
+        println!("{}", Paint::green("No issues found"));
+        return
+    }
+    for finding in findings {
+        let severity = match finding.severity {
+            Severity::High => Paint::red("[HIGH]"),
+            Severity::Medium => Paint::yellow("[MEDIUM]"),
+            Severity::Low => Paint::cyan("[LOW]"),
+            Severity::Informational => Paint::new("[INFO]").dimmed(),
+        };
+        println!("{severity} {finding}");
+    }
+    let mut counts: BTreeMap<Severity, usize> = BTreeMap::new();
+    for finding in findings {
+        *counts.entry(finding.severity).or_default() += 1;
+    }
+    let counts: Vec<String> =
+        counts.iter().rev().map(|(severity, count)| format!("{count} {severity}")).collect();
+    println!();
+    println!("Found {} issues ({})", findings.len(), counts.join(", "));
+}
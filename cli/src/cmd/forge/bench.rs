@@ -0,0 +1,281 @@
+//! bench command
+use crate::{
+    cmd::{
+        forge::{build::CoreBuildArgs, test::Filter},
+        Cmd,
+    },
+    compile::ProjectCompiler,
+    utils,
+};
+use clap::{Parser, ValueHint};
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Cell, Color, Table};
+use eyre::WrapErr;
+use forge::{
+    bench::{BenchComparison, BenchConfig, BenchResults, Change, Stats},
+    executor::opts::EvmOpts,
+    MultiContractRunnerBuilder,
+};
+use foundry_common::evm::EvmArgs;
+use foundry_config::{figment::Figment, Config};
+use std::path::{Path, PathBuf};
+use yansi::Paint;
+
+// Loads project's figment and merges the build cli arguments into it
+foundry_config::impl_figment_convert!(BenchArgs, opts, evm_opts);
+
+/// The file the baseline is stored in by default
+const DEFAULT_BASELINE: &str = ".bench-baseline.json";
+
+#[derive(Debug, Clone, Parser)]
+pub struct BenchArgs {
+    /// The number of runs of every benchmark before it is measured.
+    #[clap(long, default_value = "5", value_name = "RUNS")]
+    warmup: usize,
+
+    /// The number of measured runs of every benchmark.
+    #[clap(long, default_value = "50", value_name = "RUNS")]
+    iterations: usize,
+
+    /// Store the results as the baseline later runs are compared against.
+    ///
+    /// By default the baseline is stored in .bench-baseline.json.
+    #[clap(
+        long,
+        conflicts_with = "baseline",
+        value_hint = ValueHint::FilePath,
+        value_name = "BASELINE_FILE"
+    )]
+    save_baseline: Option<Option<PathBuf>>,
+
+    /// Compare the results against a stored baseline.
+    ///
+    /// By default the comparison is done with .bench-baseline.json.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "BASELINE_FILE")]
+    baseline: Option<Option<PathBuf>>,
+
+    /// The significance level of the comparison with the baseline.
+    ///
+    /// A change is significant if the Mann-Whitney U test of the measurements has a p-value below
+    /// this level.
+    #[clap(long, default_value = "0.05", value_name = "ALPHA")]
+    alpha: f64,
+
+    /// Exit with code 1 if a benchmark uses significantly more gas or is significantly slower
+    /// than in the baseline.
+    #[clap(long, requires = "baseline")]
+    check: bool,
+
+    #[clap(flatten, next_help_heading = "TEST FILTERING")]
+    filter: Filter,
+
+    #[clap(flatten, next_help_heading = "EVM OPTIONS")]
+    evm_opts: EvmArgs,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+impl Cmd for BenchArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        if self.iterations == 0 {
+            eyre::bail!("The number of iterations must be at least 1")
+        }
+        let figment: Figment = From::from(&self);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::from_provider(figment).sanitized();
+
+        let project = config.project()?;
+        let output = ProjectCompiler::default().compile(&project)?;
+        let root = project.paths.root.clone();
+
+        let filter = self.filter.with_merged_config();
+        let mut runner = MultiContractRunnerBuilder::default()
+            .initial_balance(evm_opts.initial_balance)
+            .evm_spec(utils::evm_spec(&config.evm_version))
+            .sender(evm_opts.sender)
+            .with_fork(utils::get_fork(&evm_opts, &config.rpc_storage_caching))
+            .with_rpc_storage_caching(config.rpc_storage_caching.clone())
+            .with_constructor_args(config.constructor_args.resolve(&root)?)
+            .build(&root, output, evm_opts)?;
+
+        let bench_config = BenchConfig { warmup: self.warmup, iterations: self.iterations };
+        println!(
+            "Running benchmarks ({} warmup runs, {} measured runs)...",
+            bench_config.warmup, bench_config.iterations
+        );
+        let results = runner.bench(&filter, bench_config)?;
+        if results.is_empty() {
+            println!("No benchmarks found, benchmarks are functions prefixed with `bench`.");
+            return Ok(())
+        }
+
+        let baseline = match &self.baseline {
+            Some(path) => Some(read_baseline(&baseline_path(&root, path))?),
+            None => None,
+        };
+        println!("{}", table(&results, baseline.as_ref(), self.alpha));
+
+        let reverted = results
+            .values()
+            .flat_map(|benches| benches.iter())
+            .filter(|(_, result)| !result.success())
+            .collect::<Vec<_>>();
+        for (sig, result) in &reverted {
+            eprintln!(
+                "{} {sig}: {}",
+                Paint::red("Reverted"),
+                result.reason.as_deref().unwrap_or_default()
+            );
+        }
+
+        if let Some(path) = &self.save_baseline {
+            let path = baseline_path(&root, path);
+            std::fs::write(&path, serde_json::to_string_pretty(&results)?)
+                .wrap_err_with(|| format!("Failed to write baseline {}", path.display()))?;
+            println!("Saved the baseline to {}", path.display());
+        }
+
+        if self.check {
+            let regressions = regressions(&results, baseline.as_ref(), self.alpha);
+            if !regressions.is_empty() {
+                eprintln!("{}", Paint::red("Benchmarks regressed compared to the baseline:"));
+                for name in regressions {
+                    eprintln!("  {name}");
+                }
+                std::process::exit(1)
+            }
+        }
+        if !reverted.is_empty() {
+            std::process::exit(1)
+        }
+        Ok(())
+    }
+}
+
+fn baseline_path(root: &Path, path: &Option<PathBuf>) -> PathBuf {
+    root.join(path.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_BASELINE)))
+}
+
+fn read_baseline(path: &Path) -> eyre::Result<BenchResults> {
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read baseline {}", path.display()))?;
+    serde_json::from_str(&content)
+        .wrap_err_with(|| format!("Failed to parse baseline {}", path.display()))
+}
+
+/// Returns the `<contract>:<benchmark>` names of the benchmarks that regressed significantly
+fn regressions(results: &BenchResults, baseline: Option<&BenchResults>, alpha: f64) -> Vec<String> {
+    results
+        .iter()
+        .flat_map(|(contract, benches)| benches.iter().map(move |bench| (contract, bench)))
+        .filter(|(contract, (sig, result))| {
+            baseline
+                .and_then(|baseline| baseline.get(*contract)?.get(*sig))
+                .filter(|base| base.success() && result.success())
+                .map_or(false, |base| BenchComparison::new(base, result).is_regression(alpha))
+        })
+        .map(|(contract, (sig, _))| format!("{contract}:{sig}"))
+        .collect()
+}
+
+fn table(results: &BenchResults, baseline: Option<&BenchResults>, alpha: f64) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+    let mut header = vec!["Contract", "Benchmark", "Gas (median ± σ)", "Time (median ± σ)"];
+    if baseline.is_some() {
+        header.extend(["Gas change", "Time change"]);
+    }
+    table.set_header(header);
+
+    for (contract, benches) in results {
+        let name = contract.rsplit(':').next().unwrap_or(contract);
+        for (sig, result) in benches {
+            if !result.success() {
+                let reverted = Cell::new("reverted").fg(Color::Red);
+                table.add_row([Cell::new(name), Cell::new(sig), reverted]);
+                continue
+            }
+            let (gas, time) = (result.gas_stats(), result.time_stats());
+            let mut row = vec![
+                Cell::new(name),
+                Cell::new(sig),
+                Cell::new(format!("{:.0} ± {:.0}", gas.median, gas.stddev)),
+                Cell::new(format_time(&time)),
+            ];
+            let base = baseline.and_then(|baseline| baseline.get(contract)?.get(sig));
+            match base.filter(|base| base.success()) {
+                Some(base) => {
+                    let comparison = BenchComparison::new(base, result);
+                    row.push(change_cell(&comparison.gas, alpha));
+                    row.push(change_cell(&comparison.time, alpha));
+                }
+                None if baseline.is_some() => {
+                    row.extend([Cell::new("new"), Cell::new("new")]);
+                }
+                None => {}
+            }
+            table.add_row(row);
+        }
+    }
+    table
+}
+
+/// Formats the change, insignificant changes are shown dimmed with a `~`
+fn change_cell(change: &Change, alpha: f64) -> Cell {
+    let text = format!("{:+.2}% (p = {:.3})", change.percent, change.p_value);
+    if !change.is_significant(alpha) {
+        Cell::new(format!("~ {text}")).fg(Color::DarkGrey)
+    } else if change.percent > 0. {
+        Cell::new(text).fg(Color::Red)
+    } else {
+        Cell::new(text).fg(Color::Green)
+    }
+}
+
+/// Formats the median and the standard deviation of wall times in nanoseconds
+fn format_time(stats: &Stats) -> String {
+    let (unit, scale) = match stats.median {
+        x if x >= 1e9 => ("s", 1e9),
+        x if x >= 1e6 => ("ms", 1e6),
+        x if x >= 1e3 => ("µs", 1e3),
+        _ => ("ns", 1.),
+    };
+    format!("{:.2}{unit} ± {:.2}{unit}", stats.median / scale, stats.stddev / scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge::bench::BenchResult;
+
+    fn results(gas: u64, time: u64) -> BenchResults {
+        let time = (0..20).map(|i| time + i).collect();
+        let result = BenchResult { gas: vec![gas; 20], time, reason: None };
+        [("src/A.t.sol:A".to_string(), [("benchA()".to_string(), result)].into())].into()
+    }
+
+    #[test]
+    fn can_find_regressions() {
+        let baseline = results(1000, 1000);
+
+        assert!(regressions(&baseline, Some(&baseline), 0.05).is_empty());
+        assert!(regressions(&results(900, 1000), Some(&baseline), 0.05).is_empty());
+        assert_eq!(
+            regressions(&results(1100, 1000), Some(&baseline), 0.05),
+            ["src/A.t.sol:A:benchA()"]
+        );
+        assert_eq!(
+            regressions(&results(1000, 2000), Some(&baseline), 0.05),
+            ["src/A.t.sol:A:benchA()"]
+        );
+        assert!(regressions(&results(1100, 1000), None, 0.05).is_empty());
+    }
+
+    #[test]
+    fn can_format_times() {
+        let stats = Stats { median: 1_500_000., mean: 1_500_000., stddev: 20_000. };
+        assert_eq!(format_time(&stats), "1.50ms ± 0.02ms");
+    }
+}
@@ -1,7 +1,7 @@
-use crate::cmd::utils::Cmd;
+use crate::{cmd::utils::Cmd, compile::ProjectCompiler};
 
 use clap::{Parser, ValueHint};
-use ethers::contract::MultiAbigen;
+use ethers::{contract::MultiAbigen, utils::keccak256};
 use foundry_config::{
     figment::{
         self,
@@ -13,12 +13,16 @@ use foundry_config::{
 };
 use serde::Serialize;
 use std::{fs, path::PathBuf};
+use walkdir::WalkDir;
 
 impl_figment_convert!(BindArgs);
 
 static DEFAULT_CRATE_NAME: &str = "foundry-contracts";
 static DEFAULT_CRATE_VERSION: &str = "0.0.1";
 
+/// The file in the cache folder the fingerprint of the last generated bindings is written to
+pub const BINDINGS_FINGERPRINT_FILENAME: &str = "bindings-fingerprint";
+
 #[derive(Debug, Clone, Parser, Serialize)]
 pub struct BindArgs {
     #[clap(
@@ -67,6 +71,17 @@ pub struct BindArgs {
     #[clap(long = "single-file", help = "Generate bindings as a single file.")]
     #[serde(skip)]
     single_file: bool,
+
+    #[clap(
+        long,
+        help = "Generate the bindings even if no artifacts changed since they were last generated."
+    )]
+    #[serde(skip)]
+    force: bool,
+
+    #[clap(long, help = "Do not build the project before generating the bindings.")]
+    #[serde(skip)]
+    skip_build: bool,
 }
 
 impl BindArgs {
@@ -86,6 +101,44 @@ impl BindArgs {
         self.bindings_root().is_dir()
     }
 
+    /// Get the path to the file the fingerprint of the generated bindings is written to
+    fn fingerprint_path(&self) -> PathBuf {
+        let c: Config = self.into();
+        c.cache_path.join(BINDINGS_FINGERPRINT_FILENAME)
+    }
+
+    /// Returns a hash of the artifacts and the options the bindings are generated with
+    fn fingerprint(&self) -> eyre::Result<String> {
+        let mut artifacts: Vec<PathBuf> = WalkDir::new(self.artifacts())
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|entry| entry.into_path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect();
+        artifacts.sort();
+
+        let mut data = format!(
+            "{}:{}:{}:{}:{}",
+            self.bindings_root().display(),
+            self.crate_name,
+            self.crate_version,
+            self.module,
+            self.single_file
+        )
+        .into_bytes();
+        for artifact in artifacts {
+            data.extend(artifact.to_string_lossy().as_bytes());
+            data.extend(keccak256(fs::read(&artifact)?));
+        }
+        Ok(hex::encode(keccak256(data)))
+    }
+
+    /// `true` if the bindings were generated from the same artifacts with the same options
+    fn is_up_to_date(&self, fingerprint: &str) -> bool {
+        fs::read_to_string(self.fingerprint_path())
+            .map_or(false, |recorded| recorded == fingerprint)
+    }
+
     /// Instantiate the multi-abigen
     fn get_multi(&self) -> eyre::Result<MultiAbigen> {
         let multi = MultiAbigen::from_json_files(self.artifacts())?;
@@ -93,7 +146,7 @@ impl BindArgs {
         eyre::ensure!(
             !multi.is_empty(),
             r#"
-No contract artifacts found. Hint: Have you built your contracts yet? `forge bind` does not currently invoke `forge build`, although this is planned for future versions.
+No contract artifacts found. Hint: Have you built your contracts yet? `forge bind --skip-build` does not invoke `forge build`.
             "#
         );
         Ok(multi)
@@ -139,16 +192,31 @@ impl Cmd for BindArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
+        if !self.skip_build {
+            let config: Config = (&self).into();
+            ProjectCompiler::default().compile(&config.project()?)?;
+        }
+
         if !self.overwrite && self.bindings_exist() {
             println!("Bindings found. Checking for consistency.");
             return self.check_existing_bindings()
         }
 
-        if self.overwrite {
+        // the bindings are only generated again if the artifacts changed
+        let fingerprint = self.fingerprint()?;
+        if self.bindings_exist() {
+            if !self.force && self.is_up_to_date(&fingerprint) {
+                println!("No artifacts changed, the bindings are up to date.");
+                return Ok(())
+            }
             fs::remove_dir_all(self.bindings_root())?;
         }
 
         self.generate_bindings()?;
+        if let Some(parent) = self.fingerprint_path().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(self.fingerprint_path(), fingerprint)?;
 
         println!("Bindings have been output to {}", self.bindings_root().to_str().unwrap());
         Ok(())
@@ -5,11 +5,16 @@ use std::path::PathBuf;
 
 use crate::{
     cmd::{forge::watch::WatchArgs, Cmd},
-    compile,
+    compile::{self, ProjectCompiler},
     opts::forge::CompilerArgs,
 };
 use clap::{Parser, ValueHint};
-use ethers::solc::{artifacts::RevertStrings, remappings::Remapping, utils::canonicalized};
+use ethers::solc::{
+    artifacts::{BytecodeHash, RevertStrings},
+    remappings::Remapping,
+    utils::canonicalized,
+};
+use eyre::WrapErr;
 use foundry_config::{
     figment::{
         self,
@@ -101,20 +106,29 @@ pub struct CoreBuildArgs {
 
     #[clap(
         help_heading = "COMPILER OPTIONS",
-        help = "Do not access the network.",
-        long_help = "Do not access the network. Missing solc versions will not be installed.",
+        help = "Use the Yul intermediate representation compilation pipeline.",
         long
     )]
     #[serde(skip)]
-    pub offline: bool,
+    pub via_ir: bool,
 
     #[clap(
         help_heading = "COMPILER OPTIONS",
-        help = "Use the Yul intermediate representation compilation pipeline.",
+        help = "The hash of the metadata appended to the bytecode.",
+        long,
+        value_name = "HASH",
+        possible_values = ["none", "ipfs", "bzzr1"]
+    )]
+    #[serde(skip)]
+    pub bytecode_hash: Option<BytecodeHash>,
+
+    #[clap(
+        help_heading = "COMPILER OPTIONS",
+        help = "Enable the stack allocation of the Yul optimizer, requires --via-ir.",
         long
     )]
     #[serde(skip)]
-    pub via_ir: bool,
+    pub yul_stack_allocation: bool,
 
     #[clap(flatten, next_help_heading = "PROJECT OPTIONS")]
     #[serde(flatten)]
@@ -138,6 +152,14 @@ pub struct CoreBuildArgs {
     )]
     #[serde(skip)]
     pub revert_strings: Option<RevertStrings>,
+
+    #[clap(
+        help_heading = "PROJECT OPTIONS",
+        help = "Write a build info file with the solc input and output of each compilation.",
+        long
+    )]
+    #[serde(skip)]
+    pub build_info: bool,
 }
 
 impl CoreBuildArgs {
@@ -176,18 +198,29 @@ impl Provider for CoreBuildArgs {
             dict.insert("solc".to_string(), solc.trim_start_matches("solc:").into());
         }
 
-        if self.offline {
-            dict.insert("offline".to_string(), true.into());
-        }
-
         if self.via_ir {
             dict.insert("via_ir".to_string(), true.into());
         }
 
+        if let Some(hash) = self.bytecode_hash {
+            dict.insert("bytecode_hash".to_string(), hash.to_string().to_lowercase().into());
+        }
+
+        if self.yul_stack_allocation {
+            // merged into the configured `optimizer_details`
+            let yul_details = Dict::from([("stackAllocation".to_string(), true.into())]);
+            let details = Dict::from([("yulDetails".to_string(), yul_details.into())]);
+            dict.insert("optimizer_details".to_string(), details.into());
+        }
+
         if self.force {
             dict.insert("force".to_string(), self.force.into());
         }
 
+        if self.build_info {
+            dict.insert("build_info".to_string(), true.into());
+        }
+
         if self.compiler.optimize {
             dict.insert("optimizer".to_string(), self.compiler.optimize.into());
         }
@@ -246,6 +279,32 @@ pub struct BuildArgs {
     #[serde(skip)]
     pub sizes: bool,
 
+    #[clap(
+        help = "Print a note on the size, deployment gas and external functions of every contract.",
+        long_help = "Print a note on the size, the estimated deployment gas and the number of external functions of every compiled contract. The deployment gas is the gas of the deployment transaction without the constructor execution. The build fails if a contract exceeds a limit of the `[contract_limits]` table.",
+        long
+    )]
+    #[serde(skip)]
+    pub notes: bool,
+
+    #[clap(
+        help = "Print the solc output of all sources as JSON, or write it to the file.",
+        long_help = r#"Print the solc output of all sources as JSON, or write it to the file.
+
+The output contains the errors, the sources and the contracts of the solc standard JSON output,
+by file and contract, with the solc version they were compiled with. All sources are compiled
+again without the cache, the cache and artifacts of the project are left untouched. Compiler
+errors are part of the output, the command fails after emitting it."#,
+        long,
+        value_name = "PATH",
+        conflicts_with_all = &["names", "sizes"],
+        min_values = 0,
+        require_equals = true,
+        value_hint = ValueHint::FilePath
+    )]
+    #[serde(skip)]
+    pub format_json: Option<Option<PathBuf>>,
+
     #[clap(flatten, next_help_heading = "WATCH OPTIONS")]
     #[serde(skip)]
     pub watch: WatchArgs,
@@ -254,8 +313,33 @@ pub struct BuildArgs {
 impl Cmd for BuildArgs {
     type Output = ProjectCompileOutput;
     fn run(self) -> eyre::Result<Self::Output> {
-        let project = self.project()?;
-        compile::compile(&project, self.names, self.sizes)
+        let config = Config::from(&self);
+        if let Some(path) = self.format_json {
+            // the solc output of cached sources is not kept, so everything is compiled again,
+            // without touching the cache and artifacts of the project
+            let project = config.ephemeral_no_artifacts_project()?;
+            let output = compile::compile_silently(&project)?;
+            let json = serde_json::to_string_pretty(&output.clone().output())?;
+            match path {
+                Some(path) => {
+                    std::fs::write(&path, json).wrap_err_with(|| {
+                        format!("Failed to write the solc output to {}", path.display())
+                    })?;
+                    println!("Wrote the solc output to {}", path.display());
+                }
+                None => println!("{json}"),
+            }
+            if output.has_compiler_errors() {
+                eyre::bail!(output.to_string())
+            }
+            return Ok(output)
+        }
+        let project = config.project()?;
+        ProjectCompiler::new(self.names, self.sizes)
+            .with_deny_oversized(config.deny_oversized_contracts)
+            .with_notes(self.notes || config.notes)
+            .with_limits(config.contract_limits)
+            .compile(&project)
     }
 }
 
@@ -304,6 +388,10 @@ impl Provider for BuildArgs {
             dict.insert("sizes".to_string(), true.into());
         }
 
+        if self.notes {
+            dict.insert("notes".to_string(), true.into());
+        }
+
         Ok(Map::from([(Config::selected_profile(), dict)]))
     }
 }
@@ -14,6 +14,30 @@ pub struct ConfigArgs {
     json: bool,
     #[clap(help = "prints basic set of currently set config values", long)]
     basic: bool,
+    #[clap(
+        help = "prints or edits the global config at `~/.foundry/foundry.toml`",
+        long_help = r#"prints or edits the global config at `~/.foundry/foundry.toml`
+
+The global config provides machine-level defaults, e.g. etherscan keys and rpc endpoints. It is
+merged beneath the project's `foundry.toml`, which is overridden by environment variables and
+command line arguments in turn."#,
+        long
+    )]
+    global: bool,
+    #[clap(
+        help = "sets a value in the global config, e.g. `rpc_endpoints.mainnet=<url>`",
+        long,
+        requires = "global",
+        value_name = "KEY=VALUE"
+    )]
+    set: Vec<String>,
+    #[clap(
+        help = "removes a value from the global config",
+        long,
+        requires = "global",
+        value_name = "KEY"
+    )]
+    unset: Vec<String>,
     // support nested build arguments
     #[clap(flatten)]
     opts: BuildArgs,
@@ -25,6 +49,10 @@ impl Cmd for ConfigArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
+        if self.global {
+            return self.run_global()
+        }
+
         let figment: Figment = From::from(&self);
         let config = Config::from_provider(figment);
         let s = if self.basic {
@@ -44,3 +72,28 @@ impl Cmd for ConfigArgs {
         Ok(())
     }
 }
+
+impl ConfigArgs {
+    /// Applies the edits to the global config and prints it
+    fn run_global(self) -> eyre::Result<()> {
+        for assignment in &self.set {
+            let (key, value) = assignment
+                .split_once('=')
+                .ok_or_else(|| eyre::eyre!("Invalid value `{assignment}`, expected KEY=VALUE"))?;
+            Config::set_global(key.trim(), value.trim())?;
+        }
+        for key in &self.unset {
+            Config::unset_global(key)?;
+        }
+
+        let path = Config::foundry_dir_toml()
+            .ok_or_else(|| eyre::eyre!("Failed to find the home directory"))?;
+        if path.exists() {
+            println!("# {}", path.display());
+            print!("{}", std::fs::read_to_string(&path)?);
+        } else {
+            println!("No global config at {}", path.display());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Coverage command
+use crate::{
+    cmd::{
+        forge::{build::CoreBuildArgs, test::Filter},
+        Cmd,
+    },
+    compile::ProjectCompiler,
+    utils,
+};
+use clap::{ArgEnum, Parser};
+use ethers::solc::{
+    artifacts::{CompactContractBytecode, ContractBytecode},
+    sourcemap::SourceMap,
+    Artifact,
+};
+use eyre::WrapErr;
+use forge::{
+    coverage::{merge_coverage, CoverageReport, SourceFile, SourceHits},
+    executor::opts::EvmOpts,
+    fuzz::{random_seed, seeded_runner},
+    matches_any_glob, MultiContractRunnerBuilder,
+};
+use foundry_common::evm::EvmArgs;
+use foundry_config::{figment::Figment, Config};
+use std::{fs::File, io::BufWriter, path::Path};
+use yansi::Paint;
+
+// Loads project's figment and merges the build cli arguments into it
+foundry_config::impl_figment_convert!(CoverageArgs, opts, evm_opts);
+
+#[derive(Debug, Clone, Parser)]
+pub struct CoverageArgs {
+    /// The report type to use for coverage.
+    ///
+    /// `lcov` writes the report to `lcov.info` in the project root, e.g. for Codecov. `html` writes
+    /// a browsable report to the `coverage` directory in the project root.
+    #[clap(long, arg_enum, default_value = "summary")]
+    report: CoverageReportKind,
+
+    /// Exit with code 1 if the coverage is below the minimum configured in `[coverage]`.
+    #[clap(long)]
+    check: bool,
+
+    #[clap(flatten, next_help_heading = "TEST FILTERING")]
+    filter: Filter,
+
+    #[clap(flatten, next_help_heading = "EVM OPTIONS")]
+    evm_opts: EvmArgs,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum CoverageReportKind {
+    Summary,
+    Lcov,
+    Html,
+    Debug,
+}
+
+impl Cmd for CoverageArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let figment: Figment = From::from(&self);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let mut config = Config::from_provider(figment).sanitized();
+        // the optimizer reorders and merges instructions, which makes source maps unreliable
+        config.optimizer = false;
+
+        // Compile all sources without reading or writing the cache, the AST of every source is
+        // needed for the analysis
+        let project = config.ephemeral_no_artifacts_project()?;
+        let output = ProjectCompiler::default().compile(&project)?;
+        let root = project.paths.root.clone();
+
+        let (artifacts, sources) =
+            output.clone().with_stripped_file_prefixes(&root).into_artifacts_with_sources();
+        let contracts: Vec<(Vec<u8>, SourceMap)> = artifacts
+            .into_iter()
+            .filter_map(|(_, artifact)| {
+                let contract: CompactContractBytecode = artifact.into_contract_bytecode();
+                let contract: ContractBytecode = contract.into();
+                let bytecode = contract.deployed_bytecode?.bytecode?;
+                let source_map = bytecode.source_map()?.ok()?;
+                Some((bytecode.object.as_bytes()?.to_vec(), source_map))
+            })
+            .collect();
+
+        // Only report the coverage of the project's own contracts
+        let is_excluded = |path: &Path| {
+            matches_any_glob(&config.coverage_ignore, &path.to_string_lossy()) || {
+                let path = root.join(path);
+                path.starts_with(&project.paths.tests) ||
+                    project.paths.libraries.iter().any(|lib| path.starts_with(lib))
+            }
+        };
+        let mut source_files = Vec::new();
+        for (path, versions) in sources.0 {
+            if is_excluded(Path::new(&path)) {
+                continue
+            }
+            let content = std::fs::read_to_string(root.join(&path))
+                .wrap_err_with(|| format!("Failed to read source file {path}"))?;
+            for source in versions {
+                if let Some(ast) = source.source_file.ast {
+                    source_files.push(SourceFile {
+                        id: source.source_file.id,
+                        path: path.clone(),
+                        content: content.clone(),
+                        ast: serde_json::to_value(ast)?,
+                    });
+                }
+            }
+        }
+
+        // Run all tests with coverage collection enabled
+        let cfg = proptest::test_runner::Config {
+            failure_persistence: None,
+            cases: config.fuzz_runs,
+            max_local_rejects: config.fuzz_max_local_rejects,
+            max_global_rejects: config.fuzz_max_global_rejects,
+            ..Default::default()
+        };
+        let fuzzer = seeded_runner(cfg, config.fuzz_seed.unwrap_or_else(random_seed));
+        let filter = self.filter.with_merged_config().with_stdin_ids()?;
+        let mut runner = MultiContractRunnerBuilder::default()
+            .fuzzer(fuzzer)
+            .initial_balance(evm_opts.initial_balance)
+            .evm_spec(utils::evm_spec(&config.evm_version))
+            .sender(evm_opts.sender)
+            .with_fork(utils::get_fork(&evm_opts, &config.rpc_storage_caching))
+            .with_rpc_storage_caching(config.rpc_storage_caching.clone())
+            .with_fuzz_dictionary_weight(config.fuzz_dictionary_weight)
+            .with_invariant_config(config.invariant)
+            .with_constructor_args(config.constructor_args.resolve(&root)?)
+            .with_table_fixtures_dir(Some(project.paths.tests.join("fixtures")))
+            .set_coverage(true)
+            .build(&root, output, evm_opts)?;
+
+        println!("Running tests...");
+        let results = runner.test(&filter, None, true)?;
+
+        let mut coverage = None;
+        let mut failures = 0;
+        for suite in results.into_values() {
+            for (_, result) in suite.test_results {
+                failures += !result.success as usize;
+                merge_coverage(&mut coverage, result.coverage);
+            }
+        }
+        if failures > 0 {
+            println!(
+                "{} {failures} tests failed, their coverage is still included in the report",
+                Paint::yellow("Warning:").bold()
+            );
+        }
+
+        let mut hits = SourceHits::default();
+        hits.add_hit_maps(&coverage.unwrap_or_default(), &contracts);
+        let report = CoverageReport::new(&source_files, &hits, &config.coverage_ignore);
+
+        match self.report {
+            CoverageReportKind::Summary => println!("{}", report.summary_table()),
+            CoverageReportKind::Lcov => {
+                let path = root.join("lcov.info");
+                report.write_lcov(BufWriter::new(File::create(&path)?))?;
+                println!("Wrote LCOV report to {}", path.display());
+            }
+            CoverageReportKind::Html => {
+                let dir = root.join("coverage");
+                std::fs::create_dir_all(&dir)
+                    .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+                for (name, page) in report.html_pages(&source_files) {
+                    std::fs::write(dir.join(name), page)?;
+                }
+                println!("Wrote HTML report to {}", dir.join("index.html").display());
+            }
+            CoverageReportKind::Debug => {
+                for (path, items) in &report.files {
+                    for item in items {
+                        println!("{path}: {item}");
+                    }
+                }
+            }
+        }
+
+        if self.check {
+            if !config.coverage.has_thresholds() {
+                eyre::bail!("No minimum coverage configured, see `[coverage]` in foundry.toml")
+            }
+            let failures = report.check_thresholds(&config.coverage);
+            if !failures.is_empty() {
+                for failure in failures {
+                    eprintln!("{} {failure}", Paint::red("Error:").bold());
+                }
+                std::process::exit(1)
+            }
+            println!("Coverage is above the configured minimum");
+        }
+        Ok(())
+    }
+}
@@ -9,8 +9,8 @@ use crate::{
 use clap::{Parser, ValueHint};
 use ethers::{
     abi::{Abi, Constructor, Token},
-    prelude::{artifacts::BytecodeObject, ContractFactory, Http, Middleware, Provider},
-    solc::utils::RuntimeOrHandle,
+    prelude::{artifacts::CompactBytecode, ContractFactory, Http, Middleware, Provider},
+    solc::{utils::RuntimeOrHandle, Project},
     types::{transaction::eip2718::TypedTransaction, Chain, U256},
 };
 use eyre::{Context, Result};
@@ -37,7 +37,7 @@ pub struct CreateArgs {
 
     #[clap(
         long,
-        help = "The path to a file containing the constructor arguments.",
+        help = "The path to a file containing the constructor arguments, one per line.",
         value_hint = ValueHint::FilePath,
         name = "constructor_args_path",
         conflicts_with = "constructor_args",
@@ -128,20 +128,8 @@ impl CreateArgs {
         // Get ABI and BIN
         let (abi, bin, _) = crate::cmd::utils::read_artifact(&project, self.contract.clone())?;
 
-        let bin = match bin.object {
-            BytecodeObject::Bytecode(_) => bin.object,
-            _ => {
-                let link_refs = bin
-                    .link_references
-                    .iter()
-                    .flat_map(|(path, names)| {
-                        names.keys().map(move |name| format!("\t{}: {}", name, path))
-                    })
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                eyre::bail!("Dynamic linking not supported in `create` command - deploy the following library contracts first, then provide the address to link at compile time\n{}", link_refs)
-            }
-        };
+        // The libraries that are not linked at compile time with `libraries` are deployed first
+        let libraries = unlinked_libraries(&project, &bin)?;
 
         // Add arguments to constructor
         let config = Config::from(&self.eth);
@@ -155,8 +143,7 @@ impl CreateArgs {
                         if !std::path::Path::new(&constructor_args_path).exists() {
                             eyre::bail!("constructor args path not found");
                         }
-                        let file = fs::read_to_string(constructor_args_path)?;
-                        file.split(' ').map(|s| s.to_string()).collect::<Vec<String>>()
+                        read_constructor_args(&fs::read_to_string(constructor_args_path)?)
                     } else {
                         self.constructor_args.clone()
                     };
@@ -169,11 +156,17 @@ impl CreateArgs {
         let chain_id = provider.get_chainid().await?;
         match self.eth.signer_with(chain_id, provider).await? {
             Some(signer) => match signer {
-                WalletType::Ledger(signer) => self.deploy(abi, bin, params, signer).await?,
-                WalletType::Local(signer) => self.deploy(abi, bin, params, signer).await?,
-                WalletType::Trezor(signer) => self.deploy(abi, bin, params, signer).await?,
+                WalletType::Ledger(signer) => {
+                    self.deploy(abi, bin, libraries, params, signer).await?
+                }
+                WalletType::Local(signer) => {
+                    self.deploy(abi, bin, libraries, params, signer).await?
+                }
+                WalletType::Trezor(signer) => {
+                    self.deploy(abi, bin, libraries, params, signer).await?
+                }
             },
-            None => eyre::bail!("could not find artifact"),
+            None => eyre::bail!("could not find a signer, pass a private key or keystore"),
         };
 
         Ok(())
@@ -182,17 +175,48 @@ impl CreateArgs {
     async fn deploy<M: Middleware + 'static>(
         self,
         abi: Abi,
-        bin: BytecodeObject,
+        mut bin: CompactBytecode,
+        libraries: Vec<Library>,
         args: Vec<Token>,
         provider: M,
     ) -> Result<()> {
         let chain = provider.get_chainid().await?.as_u64();
         let deployer_address =
             provider.default_sender().expect("no sender address set for provider");
-        let bin = bin.into_bytes().unwrap_or_else(|| {
+        let is_legacy =
+            self.legacy || Chain::try_from(chain).map(|x| Chain::is_legacy(&x)).unwrap_or_default();
+        let provider = Arc::new(provider);
+
+        let mut deployed_libraries = Vec::new();
+        let mut linked_libraries = Vec::new();
+        for mut library in libraries {
+            for (file, name, address) in &deployed_libraries {
+                library.bytecode.link(file, name, *address);
+            }
+            let code = library.bytecode.object.into_bytes().ok_or_else(|| {
+                eyre::eyre!("Library `{}` links to a library that is not deployed", library.name)
+            })?;
+            let deployer = ContractFactory::new(Abi::default(), code, provider.clone())
+                .deploy_tokens(Vec::new())
+                .wrap_err_with(|| format!("Failed to deploy library `{}`", library.name))?;
+            let mut deployer = if is_legacy { deployer.legacy() } else { deployer };
+            if let Some(gas_price) = self.gas_price {
+                deployer.tx.set_gas_price(gas_price);
+            }
+            let address = deployer.send().await?.address();
+            if !self.json {
+                println!("Deployed library {} to: {:?}", library.name, address);
+            }
+            // the `libraries` entry that links the library at compile time
+            linked_libraries.push(format!("{}:{address:?}", library.identifier));
+            deployed_libraries.push((library.file, library.name, address));
+        }
+        for (file, name, address) in &deployed_libraries {
+            bin.link(file, name, *address);
+        }
+        let bin = bin.object.into_bytes().unwrap_or_else(|| {
             panic!("no bytecode found in bin object for {}", self.contract.name)
         });
-        let provider = Arc::new(provider);
         let factory = ContractFactory::new(abi.clone(), bin.clone(), provider.clone());
 
         let is_args_empty = args.is_empty();
@@ -204,8 +228,6 @@ impl CreateArgs {
                     e
                 }
             })?;
-        let is_legacy =
-            self.legacy || Chain::try_from(chain).map(|x| Chain::is_legacy(&x)).unwrap_or_default();
         let mut deployer = if is_legacy { deployer.legacy() } else { deployer };
 
         // fill tx first because if you target a lower gas than current base, eth_estimateGas
@@ -227,7 +249,7 @@ impl CreateArgs {
         // set priority fee if specified
         if let Some(priority_fee) = self.priority_fee {
             if is_legacy {
-                panic!("there is no priority fee for legacy txs");
+                eyre::bail!("there is no priority fee for legacy txs");
             }
             deployer.tx = match deployer.tx {
                 TypedTransaction::Eip1559(eip1559_tx_request) => TypedTransaction::Eip1559(
@@ -245,16 +267,28 @@ impl CreateArgs {
         let (deployed_contract, receipt) = deployer.send_with_receipt().await?;
         let address = deployed_contract.address();
         if self.json {
-            let output = json!({
+            let mut output = json!({
                 "deployer": deployer_address,
                 "deployedTo": address,
                 "transactionHash": receipt.transaction_hash
             });
+            if !linked_libraries.is_empty() {
+                output["libraries"] = json!(linked_libraries);
+            }
             println!("{output}");
         } else {
             println!("Deployer: {deployer_address:?}");
             println!("Deployed to: {:?}", address);
             println!("Transaction hash: {:?}", receipt.transaction_hash);
+            if !linked_libraries.is_empty() {
+                println!(
+                    "Add the libraries to `libraries` in foundry.toml to link them at compile \
+                     time instead of deploying them again:"
+                );
+                for library in &linked_libraries {
+                    println!("  {library:?}");
+                }
+            }
         };
 
         if !self.verify {
@@ -262,17 +296,9 @@ impl CreateArgs {
         }
 
         println!("Starting contract verification...");
-        let constructor_args = if !args.is_empty() {
-            let encoded_args = abi
-                .constructor()
-                .ok_or(eyre::eyre!("could not find constructor"))?
-                .encode_input(bin.to_vec(), &args)?;
-            Some(String::from_utf8(encoded_args).wrap_err(format!(
-                "ABI encoded constructor arguments contain invalid UTF-8 and can't be submitted for verification."
-            ))?)
-        } else {
-            None
-        };
+        // etherscan expects the hex encoded arguments, without the creation code
+        let constructor_args =
+            if !args.is_empty() { Some(hex::encode(ethers::abi::encode(&args))) } else { None };
         let num_of_optimizations =
             if self.opts.compiler.optimize { self.opts.compiler.optimizer_runs } else { None };
         let verify = verify::VerifyArgs {
@@ -282,10 +308,7 @@ impl CreateArgs {
             constructor_args,
             num_of_optimizations,
             chain: chain.into(),
-            etherscan_key: self
-                .eth
-                .etherscan_api_key
-                .ok_or(eyre::eyre!("ETHERSCAN_API_KEY must be set"))?,
+            etherscan_key: self.eth.etherscan_api_key,
             project_paths: self.opts.project_paths,
             flatten: false,
             force: false,
@@ -301,6 +324,13 @@ impl CreateArgs {
         constructor: &Constructor,
         constructor_args: &[String],
     ) -> Result<Vec<Token>> {
+        if constructor.inputs.len() != constructor_args.len() {
+            eyre::bail!(
+                "the constructor expects {} arguments, got {}",
+                constructor.inputs.len(),
+                constructor_args.len()
+            )
+        }
         let params = constructor
             .inputs
             .iter()
@@ -311,3 +341,153 @@ impl CreateArgs {
         parse_tokens(params, true)
     }
 }
+
+/// A library the contract links to, that is not linked at compile time
+#[derive(Debug, Clone)]
+struct Library {
+    /// The source file of the library, as in the link references of the bytecode
+    file: String,
+    name: String,
+    /// `<path>:<name>`, the path is relative to the project root
+    identifier: String,
+    bytecode: CompactBytecode,
+}
+
+/// Returns the libraries the bytecode links to, directly or through other libraries, in the order
+/// they have to be deployed
+fn unlinked_libraries(project: &Project, bytecode: &CompactBytecode) -> Result<Vec<Library>> {
+    link_order(bytecode, |file, name| {
+        let path = project.paths.root.join(file);
+        let contract =
+            ContractInfo { path: Some(path.display().to_string()), name: name.to_string() };
+        // only the bytecode of the library is needed, its ABI is not parsed
+        let library = crate::cmd::utils::read_lazy_artifact(project, contract)
+            .wrap_err_with(|| format!("Failed to read the artifact of library `{name}`"))?
+            .bytecode
+            .ok_or_else(|| eyre::eyre!("bytecode not found for {name}"))?;
+        let relative = path.strip_prefix(&project.paths.root).unwrap_or(&path);
+        Ok((format!("{}:{name}", foundry_config::slash_path(relative)), library))
+    })
+}
+
+/// Orders the libraries the bytecode links to so that every library is deployed after the
+/// libraries it links to
+///
+/// `artifact` returns the identifier and bytecode of the library `name` in `file`. Libraries that
+/// link to each other in a cycle can't be deployed and are an error.
+fn link_order(
+    bytecode: &CompactBytecode,
+    artifact: impl Fn(&str, &str) -> Result<(String, CompactBytecode)>,
+) -> Result<Vec<Library>> {
+    fn visit(
+        bytecode: &CompactBytecode,
+        artifact: &dyn Fn(&str, &str) -> Result<(String, CompactBytecode)>,
+        visiting: &mut Vec<(String, String)>,
+        out: &mut Vec<Library>,
+    ) -> Result<()> {
+        for (file, names) in &bytecode.link_references {
+            for name in names.keys() {
+                if out.iter().any(|lib| lib.file == *file && lib.name == *name) {
+                    continue
+                }
+                let key = (file.clone(), name.clone());
+                if visiting.contains(&key) {
+                    let cycle = visiting
+                        .iter()
+                        .skip_while(|visited| **visited != key)
+                        .chain(std::iter::once(&key))
+                        .map(|(_, name)| name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    eyre::bail!("Libraries link to each other in a cycle: {cycle}")
+                }
+                let (identifier, library) = artifact(file, name)?;
+                // the libraries of the library are deployed before it
+                visiting.push(key);
+                visit(&library, artifact, visiting, out)?;
+                visiting.pop();
+                out.push(Library {
+                    file: file.clone(),
+                    name: name.clone(),
+                    identifier,
+                    bytecode: library,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    let mut libraries = Vec::new();
+    visit(bytecode, &artifact, &mut Vec::new(), &mut libraries)?;
+    Ok(libraries)
+}
+
+/// Returns the constructor arguments of an args file, one per line
+///
+/// Arguments are not split at whitespace, so strings may contain spaces. Empty lines are skipped.
+fn read_constructor_args(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Returns bytecode that links to the `(file, name)` libraries
+    fn bytecode(links: &[(&str, &str)]) -> CompactBytecode {
+        let mut refs = BTreeMap::<String, BTreeMap<String, Vec<serde_json::Value>>>::new();
+        for (file, name) in links {
+            refs.entry(file.to_string()).or_default().insert(name.to_string(), vec![]);
+        }
+        serde_json::from_value(json!({ "object": "0x00", "linkReferences": refs })).unwrap()
+    }
+
+    fn order(
+        contract: &[(&str, &str)],
+        libraries: &[(&str, &[(&str, &str)])],
+    ) -> Result<Vec<String>> {
+        let libraries = link_order(&bytecode(contract), |file, name| {
+            let (_, links) = libraries
+                .iter()
+                .find(|(lib, _)| *lib == name)
+                .ok_or_else(|| eyre::eyre!("Failed to read the artifact of library `{name}`"))?;
+            Ok((format!("{file}:{name}"), bytecode(links)))
+        })?;
+        Ok(libraries.into_iter().map(|lib| lib.identifier).collect())
+    }
+
+    #[test]
+    fn deploys_nested_libraries_first() {
+        let libraries: &[(&str, &[(&str, &str)])] = &[
+            ("A", &[("src/B.sol", "B")]),
+            ("B", &[]),
+            ("C", &[("src/B.sol", "B")]),
+        ];
+        let order = order(&[("src/A.sol", "A"), ("src/C.sol", "C")], libraries).unwrap();
+        assert_eq!(order, ["src/B.sol:B", "src/A.sol:A", "src/C.sol:C"]);
+    }
+
+    #[test]
+    fn fails_on_cyclic_and_missing_libraries() {
+        let libraries: &[(&str, &[(&str, &str)])] =
+            &[("A", &[("src/B.sol", "B")]), ("B", &[("src/A.sol", "A")])];
+        let err = order(&[("src/A.sol", "A")], libraries).unwrap_err();
+        assert_eq!(err.to_string(), "Libraries link to each other in a cycle: A -> B -> A");
+
+        let libraries: &[(&str, &[(&str, &str)])] = &[("A", &[("src/D.sol", "D")])];
+        let err = order(&[("src/A.sol", "A")], libraries).unwrap_err();
+        assert_eq!(err.to_string(), "Failed to read the artifact of library `D`");
+    }
+
+    #[test]
+    fn reads_one_constructor_arg_per_line() {
+        let args = read_constructor_args("1\r\n\nhello world\n  [1, 2]  \n");
+        assert_eq!(args, ["1", "hello world", "[1, 2]"]);
+    }
+}
@@ -0,0 +1,95 @@
+//! decode-trace command
+
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, Cmd},
+    compile,
+};
+use clap::{Parser, ValueHint};
+use ethers::{
+    abi::{Abi, Address},
+    prelude::ArtifactId,
+    solc::artifacts::CompactContractBytecode,
+};
+use eyre::WrapErr;
+use forge::trace::{
+    identifier::LocalTraceIdentifier, import::parse_trace, CallTraceDecoderBuilder,
+};
+use foundry_config::Config;
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+
+foundry_config::impl_figment_convert!(DecodeTraceArgs, opts);
+
+/// Command to decode a transaction trace recorded by a node with the project's ABIs
+#[derive(Debug, Clone, Parser)]
+pub struct DecodeTraceArgs {
+    #[clap(
+        help = "The JSON file with the trace.",
+        long_help = r#"The JSON file with the trace.
+
+Supports the output of geth's `debug_traceTransaction` with the `callTracer`, and of parity's
+`trace_transaction` and `trace_replayTransaction`."#,
+        value_hint = ValueHint::FilePath,
+        value_name = "FILE"
+    )]
+    path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Label an address in the trace, e.g. 0x6B175474E89094C44Da98b954EedeAC495271d0F:DAI",
+        value_name = "ADDRESS:LABEL"
+    )]
+    label: Vec<String>,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+impl Cmd for DecodeTraceArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let json = std::fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("Failed to read trace {}", self.path.display()))?;
+        let mut trace = parse_trace(&json)?;
+
+        let labels = self
+            .label
+            .iter()
+            .map(|label| {
+                label
+                    .split_once(':')
+                    .and_then(|(address, label)| {
+                        Some((Address::from_str(address).ok()?, label.to_string()))
+                    })
+                    .ok_or_else(|| eyre::eyre!("Invalid label `{label}`, expected ADDRESS:LABEL"))
+            })
+            .collect::<eyre::Result<BTreeMap<_, _>>>()?;
+
+        // Compile the project to decode the calls, events and errors of its contracts
+        let config: Config = From::from(&self);
+        let project = config.project()?;
+        let output = compile::suppress_compile(&project)?;
+        let known_contracts: BTreeMap<ArtifactId, (Abi, Vec<u8>)> = output
+            .into_artifacts()
+            .filter_map(|(id, artifact)| {
+                let CompactContractBytecode { abi, deployed_bytecode, .. } = artifact.into();
+                let code = deployed_bytecode?.bytecode?.object.into_bytes()?;
+                Some((id, (abi?, code.to_vec())))
+            })
+            .collect();
+
+        // Contracts created in the trace are identified by their code, calls to other contracts
+        // are decoded by their selector
+        let local_identifier = LocalTraceIdentifier::new(&known_contracts);
+        let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels(labels)
+            .with_abis(known_contracts.values().map(|(abi, _)| abi))
+            .build();
+        decoder.identify(&trace, &local_identifier);
+        decoder.decode(&mut trace);
+
+        println!("Traces:");
+        println!("{trace}");
+        Ok(())
+    }
+}
@@ -0,0 +1,691 @@
+//! Doc command
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, Cmd},
+    compile::ProjectCompiler,
+};
+use clap::{Parser, ValueHint};
+use eyre::WrapErr;
+use foundry_config::Config;
+use semver::Version;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+// Loads project's figment and merges the build cli arguments into it
+foundry_config::impl_figment_convert!(DocArgs, opts);
+
+#[derive(Debug, Clone, Parser)]
+pub struct DocArgs {
+    /// The directory the documentation is written to, defaults to `docs` in the project root.
+    #[clap(long, short, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    out: Option<PathBuf>,
+
+    /// Write a markdown file per contract and an index, instead of an mdbook.
+    #[clap(long)]
+    markdown: bool,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+impl Cmd for DocArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let config: Config = From::from(&self);
+        let root = config.__root.0.clone();
+        let out = self.out.clone().unwrap_or_else(|| root.join("docs"));
+
+        // Compile all sources without the cache, the natspec is read from the AST of every source
+        let project = config.ephemeral_no_artifacts_project()?;
+        let output = ProjectCompiler::default().compile(&project)?;
+        let (_, sources) = output.with_stripped_file_prefixes(&root).into_artifacts_with_sources();
+
+        let mut contracts = BTreeMap::new();
+        for (path, versions) in sources.0 {
+            for source in versions {
+                if let Some(ast) = source.source_file.ast {
+                    let ast = serde_json::to_value(ast)?;
+                    collect_contracts(&ast, &path, &source.version, &mut contracts);
+                }
+            }
+        }
+
+        // Only the project's own contracts get a page, contracts of tests and libraries are only
+        // read for the docs their children inherit
+        let is_documented = |path: &str| {
+            let path = root.join(path);
+            path.starts_with(&project.paths.sources) &&
+                !path.starts_with(&project.paths.tests) &&
+                !project.paths.libraries.iter().any(|lib| path.starts_with(lib))
+        };
+        let pages = DocPages::new(&contracts, |contract| is_documented(&contract.path));
+        if pages.pages.is_empty() {
+            eyre::bail!("No contracts to document in {}", project.paths.sources.display())
+        }
+
+        let pages_dir = if self.markdown { out.clone() } else { out.join("src") };
+        for (path, content) in &pages.pages {
+            let path = pages_dir.join(path);
+            std::fs::create_dir_all(path.parent().expect("page has a parent"))?;
+            std::fs::write(&path, content)
+                .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+        }
+        if self.markdown {
+            std::fs::write(out.join("README.md"), pages.index("# Contracts"))?;
+            println!("Documentation written to {}", out.display());
+        } else {
+            let title = root.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            let book = format!("[book]\ntitle = {title:?}\nsrc = \"src\"\n");
+            std::fs::write(out.join("book.toml"), book)?;
+            std::fs::write(pages_dir.join("SUMMARY.md"), pages.index("# Summary"))?;
+            println!("Documentation written to {}", out.display());
+            println!("Serve it with `mdbook serve {}`", out.display());
+        }
+        Ok(())
+    }
+}
+
+/// The natspec of a contract or one of its items
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Natspec {
+    title: Option<String>,
+    author: Option<String>,
+    notice: String,
+    dev: String,
+    /// `@param` tags, by the name of the parameter
+    params: Vec<(String, String)>,
+    returns: Vec<String>,
+    /// The base contract of `@inheritdoc`
+    inheritdoc: Option<String>,
+    /// `@custom:<tag>` tags
+    custom: Vec<(String, String)>,
+}
+
+impl Natspec {
+    /// Parses the text of the documentation, as solc stores it in the AST without the comment
+    /// markers
+    ///
+    /// Text before the first tag is the `@notice`, lines without a tag continue the previous tag.
+    fn parse(text: &str) -> Self {
+        let mut natspec = Natspec::default();
+        let mut tag = String::from("notice");
+        let mut content = String::new();
+        for line in text.lines() {
+            let line = line.trim().trim_start_matches('*').trim();
+            if let Some(tagged) = line.strip_prefix('@') {
+                natspec.add(&tag, &content);
+                let (name, rest) = tagged.split_once(char::is_whitespace).unwrap_or((tagged, ""));
+                tag = name.to_string();
+                content = rest.trim().to_string();
+            } else if !line.is_empty() {
+                if !content.is_empty() {
+                    content.push(' ');
+                }
+                content.push_str(line);
+            }
+        }
+        natspec.add(&tag, &content);
+        natspec
+    }
+
+    fn add(&mut self, tag: &str, content: &str) {
+        if content.is_empty() {
+            return
+        }
+        let content = content.to_string();
+        match tag {
+            "title" => self.title = Some(content),
+            "author" => self.author = Some(content),
+            "notice" => self.notice = content,
+            "dev" => self.dev = content,
+            "param" => {
+                let (name, description) = content.split_once(' ').unwrap_or((&content, ""));
+                self.params.push((name.to_string(), description.trim().to_string()));
+            }
+            "return" => self.returns.push(content),
+            "inheritdoc" => self.inheritdoc = Some(content),
+            _ => {
+                if let Some(custom) = tag.strip_prefix("custom:") {
+                    self.custom.push((custom.to_string(), content));
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Natspec::default()
+    }
+}
+
+/// The kind of a documented item of a contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ItemKind {
+    Variable,
+    Event,
+    Error,
+    Modifier,
+    Function,
+}
+
+impl ItemKind {
+    fn heading(self) -> &'static str {
+        match self {
+            ItemKind::Variable => "State Variables",
+            ItemKind::Event => "Events",
+            ItemKind::Error => "Errors",
+            ItemKind::Modifier => "Modifiers",
+            ItemKind::Function => "Functions",
+        }
+    }
+}
+
+/// A parameter, or a return value, of an item
+#[derive(Debug, Clone, PartialEq)]
+struct Param {
+    name: String,
+    ty: String,
+}
+
+/// A function, event, error, modifier or state variable of a contract
+#[derive(Debug, Clone)]
+struct Item {
+    kind: ItemKind,
+    name: String,
+    /// The declaration as it would be written in solidity, without the body
+    signature: String,
+    params: Vec<Param>,
+    returns: Vec<Param>,
+    /// Private items are not inherited
+    private: bool,
+    natspec: Natspec,
+}
+
+impl Item {
+    /// Identifies the item across the contracts of an inheritance hierarchy, an overriding
+    /// function has the key of the function it overrides
+    fn key(&self) -> (ItemKind, String, Vec<String>) {
+        (self.kind, self.name.clone(), self.params.iter().map(|param| param.ty.clone()).collect())
+    }
+}
+
+/// Identifies a contract across all compiled sources
+///
+/// AST ids are only unique within the compilation of a single solc version, so a source that is
+/// compiled with multiple versions has a contract of the same id per version.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ContractKey {
+    path: String,
+    version: Version,
+    id: u64,
+}
+
+/// A contract, interface or library and its documented items
+#[derive(Debug, Clone)]
+struct ContractDoc {
+    name: String,
+    kind: String,
+    /// The source file, relative to the root
+    path: String,
+    /// The solc version the source was compiled with
+    version: Version,
+    natspec: Natspec,
+    /// The AST ids of the base contracts, from the most derived to the most base, in the
+    /// compilation of the same version
+    bases: Vec<u64>,
+    items: Vec<Item>,
+}
+
+impl ContractDoc {
+    /// The path of its page, relative to the directory of the pages
+    fn page(&self) -> String {
+        format!("{}/{}.md", self.path, self.name)
+    }
+}
+
+/// Collects the contracts of the AST of a source file compiled with the `version`
+fn collect_contracts(
+    ast: &Value,
+    path: &str,
+    version: &Version,
+    contracts: &mut BTreeMap<ContractKey, ContractDoc>,
+) {
+    let nodes = ast.get("nodes").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    for node in nodes {
+        if node_type(node) != Some("ContractDefinition") {
+            continue
+        }
+        let (id, name) = match (node.get("id").and_then(Value::as_u64), str_field(node, "name")) {
+            (Some(id), Some(name)) => (id, name),
+            _ => continue,
+        };
+        let bases = node
+            .get("linearizedBaseContracts")
+            .and_then(Value::as_array)
+            .map(|bases| {
+                bases.iter().filter_map(Value::as_u64).filter(|base| *base != id).collect()
+            })
+            .unwrap_or_default();
+        let items = node
+            .get("nodes")
+            .and_then(Value::as_array)
+            .map(|nodes| nodes.iter().filter_map(item).collect())
+            .unwrap_or_default();
+        contracts.insert(
+            ContractKey { path: path.to_string(), version: version.clone(), id },
+            ContractDoc {
+                name: name.to_string(),
+                kind: str_field(node, "contractKind").unwrap_or("contract").to_string(),
+                path: path.to_string(),
+                version: version.clone(),
+                natspec: natspec(node),
+                bases,
+                items,
+            },
+        );
+    }
+}
+
+/// Returns the documented item of a node of a contract definition
+fn item(node: &Value) -> Option<Item> {
+    let name = str_field(node, "name").unwrap_or_default().to_string();
+    let visibility = str_field(node, "visibility").unwrap_or_default();
+    let params = params(node.get("parameters"));
+    let list = |params: &[Param]| {
+        params
+            .iter()
+            .map(|param| format!("{} {}", param.ty, param.name).trim().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let (kind, returns, signature) = match node_type(node)? {
+        "FunctionDefinition" => {
+            let returns = params(node.get("returnParameters"));
+            let function_kind = str_field(node, "kind").unwrap_or("function");
+            let mut signature = match function_kind {
+                "function" => format!("function {name}({})", list(&params)),
+                kind => format!("{kind}({})", list(&params)),
+            };
+            for attribute in [Some(visibility), str_field(node, "stateMutability")] {
+                match attribute {
+                    Some("nonpayable") | Some("") | None => {}
+                    Some(attribute) => write!(signature, " {attribute}").unwrap(),
+                }
+            }
+            if !returns.is_empty() {
+                write!(signature, " returns ({})", list(&returns)).unwrap();
+            }
+            (ItemKind::Function, returns, signature)
+        }
+        "ModifierDefinition" => {
+            (ItemKind::Modifier, Vec::new(), format!("modifier {name}({})", list(&params)))
+        }
+        "EventDefinition" => {
+            (ItemKind::Event, Vec::new(), format!("event {name}({})", list(&params)))
+        }
+        "ErrorDefinition" => {
+            (ItemKind::Error, Vec::new(), format!("error {name}({})", list(&params)))
+        }
+        "VariableDeclaration" => {
+            let mut signature = type_string(node);
+            if visibility != "internal" {
+                write!(signature, " {visibility}").unwrap();
+            }
+            if let Some(mutability @ ("constant" | "immutable")) = str_field(node, "mutability") {
+                write!(signature, " {mutability}").unwrap();
+            }
+            write!(signature, " {name}").unwrap();
+            (ItemKind::Variable, Vec::new(), signature)
+        }
+        _ => return None,
+    };
+    Some(Item {
+        kind,
+        name,
+        signature,
+        params,
+        returns,
+        private: visibility == "private",
+        natspec: natspec(node),
+    })
+}
+
+fn params(list: Option<&Value>) -> Vec<Param> {
+    list.and_then(|list| list.get("parameters"))
+        .and_then(Value::as_array)
+        .map(|params| {
+            params
+                .iter()
+                .map(|param| Param {
+                    name: str_field(param, "name").unwrap_or_default().to_string(),
+                    ty: type_string(param),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the solidity type of a declaration, e.g. `IERC20` instead of `contract IERC20`
+fn type_string(node: &Value) -> String {
+    let ty = node
+        .get("typeDescriptions")
+        .and_then(|ty| ty.get("typeString"))
+        .and_then(Value::as_str)
+        .unwrap_or("?");
+    ["contract ", "struct ", "enum ", "library "]
+        .iter()
+        .find_map(|prefix| ty.strip_prefix(prefix))
+        .unwrap_or(ty)
+        .to_string()
+}
+
+/// Returns the natspec of a node, solc versions before 0.6.3 store the documentation as a string
+fn natspec(node: &Value) -> Natspec {
+    match node.get("documentation") {
+        Some(Value::String(text)) => Natspec::parse(text),
+        Some(documentation) => {
+            str_field(documentation, "text").map(Natspec::parse).unwrap_or_default()
+        }
+        None => Natspec::default(),
+    }
+}
+
+fn node_type(node: &Value) -> Option<&str> {
+    str_field(node, "nodeType")
+}
+
+fn str_field<'a>(node: &'a Value, field: &str) -> Option<&'a str> {
+    node.get(field).and_then(Value::as_str)
+}
+
+/// The rendered markdown pages, by their path relative to the directory of the pages
+struct DocPages<'a> {
+    contracts: &'a BTreeMap<ContractKey, ContractDoc>,
+    /// The contracts by the solc version they were compiled with and their AST id
+    ids: BTreeMap<(&'a Version, u64), &'a ContractKey>,
+    /// The contracts that get a page
+    documented: BTreeSet<&'a ContractKey>,
+    pages: BTreeMap<String, String>,
+}
+
+impl<'a> DocPages<'a> {
+    fn new(
+        contracts: &'a BTreeMap<ContractKey, ContractDoc>,
+        is_documented: impl Fn(&ContractDoc) -> bool,
+    ) -> Self {
+        let ids = contracts.keys().map(|key| ((&key.version, key.id), key)).collect();
+        let mut pages =
+            DocPages { contracts, ids, documented: BTreeSet::new(), pages: BTreeMap::new() };
+        // a source compiled with multiple versions gets the page of the lowest version
+        for (key, contract) in contracts.iter().filter(|(_, contract)| is_documented(contract)) {
+            if pages.pages.contains_key(&contract.page()) {
+                continue
+            }
+            pages.documented.insert(key);
+            let page = pages.render(contract);
+            pages.pages.insert(contract.page(), page);
+        }
+        pages
+    }
+
+    /// Returns the base contract `id` of the contract
+    fn base(&self, contract: &ContractDoc, id: u64) -> Option<(&'a ContractKey, &'a ContractDoc)> {
+        let key = *self.ids.get(&(&contract.version, id))?;
+        Some((key, &self.contracts[key]))
+    }
+
+    /// Returns the natspec of an item, resolved from the base contracts if it has none or
+    /// `@inheritdoc`
+    fn resolve(&self, contract: &ContractDoc, item: &Item) -> Natspec {
+        let base_item = |base: &ContractDoc| {
+            base.items.iter().find(|base_item| base_item.key() == item.key()).cloned()
+        };
+        let mut bases =
+            contract.bases.iter().filter_map(|id| self.base(contract, *id)).map(|(_, base)| base);
+        if let Some(base) = &item.natspec.inheritdoc {
+            return bases
+                .find(|contract| contract.name == *base)
+                .and_then(|base| base_item(base).map(|base_item| self.resolve(base, &base_item)))
+                .unwrap_or_default()
+        }
+        let inherits = matches!(item.kind, ItemKind::Function | ItemKind::Modifier);
+        if !item.natspec.is_empty() || !inherits {
+            return item.natspec.clone()
+        }
+        // like solc, an overriding function without natspec inherits the natspec of the function
+        // it overrides
+        for base in bases {
+            if let Some(base_item) = base_item(base) {
+                let natspec = self.resolve(base, &base_item);
+                if !natspec.is_empty() {
+                    return natspec
+                }
+            }
+        }
+        Natspec::default()
+    }
+
+    /// Returns a link to the page of the base contract `id`, or its name if it has no page
+    fn link(&self, from: &ContractDoc, id: u64) -> Option<String> {
+        let (key, contract) = self.base(from, id)?;
+        if !self.documented.contains(key) {
+            return Some(format!("`{}`", contract.name))
+        }
+        // the page is in `<source path>/`, the links are relative to the directory of the pages
+        let up = "../".repeat(Path::new(&from.path).components().count());
+        Some(format!("[{}]({up}{})", contract.name, contract.page()))
+    }
+
+    fn render(&self, contract: &ContractDoc) -> String {
+        let mut page = String::new();
+        writeln!(page, "# {}\n", contract.name).unwrap();
+        writeln!(page, "*{} in `{}`*\n", contract.kind, contract.path).unwrap();
+        let bases: Vec<String> =
+            contract.bases.iter().filter_map(|id| self.link(contract, *id)).collect();
+        if !bases.is_empty() {
+            writeln!(page, "**Inherits:** {}\n", bases.join(", ")).unwrap();
+        }
+        render_natspec(&mut page, &contract.natspec);
+
+        let mut defined = BTreeSet::new();
+        let mut items: Vec<(&Item, Natspec)> = Vec::new();
+        for item in &contract.items {
+            defined.insert(item.key());
+            items.push((item, self.resolve(contract, item)));
+        }
+        self.render_items(&mut page, "##", &items);
+
+        // the items of the bases that the contract does not override
+        for id in &contract.bases {
+            let base = match self.base(contract, *id) {
+                Some((_, base)) => base,
+                None => continue,
+            };
+            let mut inherited = Vec::new();
+            for item in &base.items {
+                let is_constructor = item.signature.starts_with("constructor(");
+                if item.private || is_constructor || !defined.insert(item.key()) {
+                    continue
+                }
+                inherited.push((item, self.resolve(base, item)));
+            }
+            if !inherited.is_empty() {
+                let link = self.link(contract, *id).unwrap_or_default();
+                writeln!(page, "## Inherited from {link}\n").unwrap();
+                self.render_items(&mut page, "###", &inherited);
+            }
+        }
+        page
+    }
+
+    fn render_items(&self, page: &mut String, heading: &str, items: &[(&Item, Natspec)]) {
+        let kinds: BTreeSet<ItemKind> = items.iter().map(|(item, _)| item.kind).collect();
+        for kind in kinds {
+            writeln!(page, "{heading} {}\n", kind.heading()).unwrap();
+            for (item, natspec) in items.iter().filter(|(item, _)| item.kind == kind) {
+                let name = if item.name.is_empty() { &item.signature } else { &item.name };
+                writeln!(page, "{heading}# {name}\n").unwrap();
+                writeln!(page, "```solidity\n{}\n```\n", item.signature).unwrap();
+                render_natspec(page, natspec);
+                render_params(page, "Parameters", &item.params, &natspec.params);
+                let returns: Vec<(String, String)> = item
+                    .returns
+                    .iter()
+                    .zip(natspec.returns.iter().map(Some).chain(std::iter::repeat(None)))
+                    .map(|(param, doc)| (param.name.clone(), doc.cloned().unwrap_or_default()))
+                    .collect();
+                render_params(page, "Returns", &item.returns, &returns);
+            }
+        }
+    }
+
+    /// Returns the index of the pages, grouped by source file, as a markdown list
+    fn index(&self, heading: &str) -> String {
+        let mut index = format!("{heading}\n\n");
+        let mut by_path: BTreeMap<&str, Vec<&ContractDoc>> = BTreeMap::new();
+        for key in &self.documented {
+            let contract = &self.contracts[*key];
+            by_path.entry(contract.path.as_str()).or_default().push(contract);
+        }
+        for (path, contracts) in by_path {
+            writeln!(index, "- [{path}]()").unwrap();
+            for contract in contracts {
+                writeln!(index, "  - [{}]({})", contract.name, contract.page()).unwrap();
+            }
+        }
+        index
+    }
+}
+
+fn render_natspec(page: &mut String, natspec: &Natspec) {
+    if let Some(title) = &natspec.title {
+        writeln!(page, "**{title}**\n").unwrap();
+    }
+    if let Some(author) = &natspec.author {
+        writeln!(page, "**Author:** {author}\n").unwrap();
+    }
+    if !natspec.notice.is_empty() {
+        writeln!(page, "{}\n", natspec.notice).unwrap();
+    }
+    if !natspec.dev.is_empty() {
+        writeln!(page, "*{}*\n", natspec.dev).unwrap();
+    }
+    for (tag, content) in &natspec.custom {
+        writeln!(page, "**{tag}:** {content}\n").unwrap();
+    }
+}
+
+/// Renders a table of the parameters and their description, if any is documented
+fn render_params(page: &mut String, heading: &str, params: &[Param], docs: &[(String, String)]) {
+    if params.is_empty() {
+        return
+    }
+    writeln!(page, "**{heading}**\n\n|Name|Type|Description|\n|----|----|-----------|").unwrap();
+    for (i, param) in params.iter().enumerate() {
+        let doc = docs
+            .iter()
+            .find(|(name, _)| !name.is_empty() && *name == param.name)
+            .or_else(|| docs.get(i).filter(|(name, _)| name.is_empty()))
+            .map(|(_, doc)| doc.as_str())
+            .unwrap_or_default();
+        writeln!(page, "|`{}`|`{}`|{doc}|", param.name, param.ty).unwrap();
+    }
+    page.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_parse_natspec() {
+        let natspec = Natspec::parse(
+            " @title A vault\n @notice Deposits tokens\n and mints shares\n \
+             @param amount The amount\n @return The shares\n @custom:security Audited",
+        );
+        assert_eq!(natspec.title.as_deref(), Some("A vault"));
+        assert_eq!(natspec.notice, "Deposits tokens and mints shares");
+        assert_eq!(natspec.params, [("amount".to_string(), "The amount".to_string())]);
+        assert_eq!(natspec.returns, ["The shares"]);
+        assert_eq!(natspec.custom, [("security".to_string(), "Audited".to_string())]);
+
+        assert_eq!(Natspec::parse("Untagged text").notice, "Untagged text");
+        assert_eq!(Natspec::parse(" @inheritdoc IVault").inheritdoc.as_deref(), Some("IVault"));
+    }
+
+    fn function(name: &str, documentation: Option<&str>) -> Value {
+        json!({
+            "nodeType": "FunctionDefinition",
+            "name": name,
+            "kind": "function",
+            "visibility": "external",
+            "stateMutability": "nonpayable",
+            "documentation": documentation.map(|text| json!({ "text": text })),
+            "parameters": { "parameters": [
+                { "name": "amount", "typeDescriptions": { "typeString": "uint256" } }
+            ] },
+            "returnParameters": { "parameters": [] }
+        })
+    }
+
+    #[test]
+    fn can_render_inherited_docs() {
+        let ast = json!({ "nodes": [
+            {
+                "nodeType": "ContractDefinition",
+                "id": 1,
+                "name": "IVault",
+                "contractKind": "interface",
+                "linearizedBaseContracts": [1],
+                "nodes": [
+                    function(
+                        "deposit",
+                        Some(" @notice Deposits tokens\n @param amount The amount")
+                    ),
+                    function("withdraw", Some(" @notice Withdraws tokens"))
+                ]
+            },
+            {
+                "nodeType": "ContractDefinition",
+                "id": 2,
+                "name": "Vault",
+                "contractKind": "contract",
+                "documentation": { "text": " @title The vault" },
+                "linearizedBaseContracts": [2, 1],
+                "nodes": [function("deposit", None)]
+            }
+        ]});
+        let mut contracts = BTreeMap::new();
+        collect_contracts(&ast, "src/Vault.sol", &Version::new(0, 8, 10), &mut contracts);
+        // the same ids of another compilation do not mix with the contracts of 0.8.10
+        let other = json!({ "nodes": [{
+            "nodeType": "ContractDefinition",
+            "id": 1,
+            "name": "Other",
+            "contractKind": "contract",
+            "linearizedBaseContracts": [1],
+            "nodes": []
+        }]});
+        collect_contracts(&other, "src/Other.sol", &Version::new(0, 8, 15), &mut contracts);
+        let pages = DocPages::new(&contracts, |contract| contract.kind == "contract");
+
+        let page = &pages.pages["src/Vault.sol/Vault.md"];
+        assert!(page.contains("**The vault**"));
+        assert!(page.contains("**Inherits:** `IVault`"));
+        assert!(page.contains("function deposit(uint256 amount) external"));
+        assert!(page.contains("Deposits tokens"));
+        assert!(page.contains("|`amount`|`uint256`|The amount|"));
+        assert!(page.contains("## Inherited from `IVault`"));
+        assert!(page.contains("Withdraws tokens"));
+        assert!(pages.pages["src/Other.sol/Other.md"].contains("# Other"));
+        assert_eq!(
+            pages.index("# Summary"),
+            "# Summary\n\n- [src/Other.sol]()\n  - [Other](src/Other.sol/Other.md)\n\
+             - [src/Vault.sol]()\n  - [Vault](src/Vault.sol/Vault.md)\n"
+        );
+    }
+}
@@ -1,8 +1,17 @@
 use super::build::{CoreBuildArgs, ProjectPathsArgs};
 use crate::cmd::Cmd;
 use clap::{Parser, ValueHint};
+use ethers::solc::ProjectPathsConfig;
+use eyre::WrapErr;
 use foundry_config::Config;
-use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use semver::{Comparator, Version, VersionReq};
+use std::{
+    collections::BTreeSet,
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, Parser)]
 pub struct FlattenArgs {
@@ -35,20 +44,20 @@ impl Cmd for FlattenArgs {
             ignored_error_codes: vec![],
             no_auto_detect: false,
             use_solc: None,
-            offline: false,
             force: false,
             libraries: vec![],
             via_ir: false,
+            bytecode_hash: None,
+            yul_stack_allocation: false,
             revert_strings: None,
+            build_info: false,
         };
 
         let config = Config::from(&build_args);
 
         let paths = config.project_paths();
         let target_path = dunce::canonicalize(target_path)?;
-        let flattened = paths
-            .flatten(&target_path)
-            .map_err(|err| eyre::Error::msg(format!("Failed to flatten the file: {err}")))?;
+        let flattened = flatten(&paths, &target_path).wrap_err("Failed to flatten the file")?;
 
         match output {
             Some(output) => {
@@ -62,3 +71,510 @@ impl Cmd for FlattenArgs {
         Ok(())
     }
 }
+
+static IMPORT_STATEMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^[ \t]*import\b[^;]*;[ \t]*\r?\n?"#).unwrap());
+
+static IMPORT_PATH: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["']([^"']+)["']"#).unwrap());
+
+/// `import "x" as X;` or `import * as X from "x";`
+static NAMESPACE_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r#"^\s*import\s+(?:\*\s*as\s+([\w$]+)\s+from\s+["'][^"']*["']"#,
+        r#"|["'][^"']*["']\s+as\s+([\w$]+))"#
+    ))
+    .unwrap()
+});
+
+/// `import {A, B as C} from "x";`
+static SYMBOL_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*import\s*\{([^}]*)\}"#).unwrap());
+
+static LICENSE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^[ \t]*//[ \t]*SPDX-License-Identifier:[ \t]*([^\r\n]*?)[ \t]*\r?(\n|$)"#)
+        .unwrap()
+});
+
+static PRAGMA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^[ \t]*pragma[ \t]+([^;]+);[ \t]*\r?\n?"#).unwrap());
+
+/// Flattens the target file and all files it imports, directly or indirectly, into a single file
+///
+/// The files are ordered so every file comes after the files it imports. The SPDX license
+/// identifiers of all files are combined into a single header, and so are their `pragma`
+/// directives. The `pragma solidity` requirements of all files are combined into one, which fails
+/// if no solc version satisfies all of them.
+pub fn flatten(paths: &ProjectPathsConfig, target: &Path) -> eyre::Result<String> {
+    let mut sources = Vec::new();
+    let mut visited = BTreeSet::new();
+    collect_sources(paths, target, &mut visited, &mut sources)?;
+
+    let mut licenses: Vec<String> = Vec::new();
+    let mut solidity: Vec<(PathBuf, String)> = Vec::new();
+    let mut pragmas: Vec<String> = Vec::new();
+    let mut body = String::new();
+    for (path, source) in sources {
+        licenses.extend(source.licenses);
+        for pragma in source.pragmas {
+            match pragma.strip_prefix("solidity ") {
+                Some(req) => solidity.push((path.clone(), req.to_string())),
+                None => pragmas.push(pragma),
+            }
+        }
+
+        let relative = path.strip_prefix(&paths.root).unwrap_or(&path);
+        body.push_str(&format!("\n// {}\n", foundry_config::slash_path(relative)));
+        body.push_str(&source.code);
+        body.push('\n');
+    }
+
+    let mut flattened = String::new();
+    if let Some(license) = combine_licenses(&licenses) {
+        flattened.push_str(&format!("// SPDX-License-Identifier: {license}\n"));
+    }
+    if let Some(req) = combine_solidity_pragmas(&solidity)? {
+        flattened.push_str(&format!("pragma solidity {req};\n"));
+    }
+    for pragma in combine_pragmas(&pragmas) {
+        flattened.push_str(&format!("pragma {pragma};\n"));
+    }
+    flattened.push_str(&body);
+    Ok(flattened)
+}
+
+/// Appends the file to the sources after all the files it imports
+fn collect_sources(
+    paths: &ProjectPathsConfig,
+    file: &Path,
+    visited: &mut BTreeSet<PathBuf>,
+    sources: &mut Vec<(PathBuf, Source)>,
+) -> eyre::Result<()> {
+    // cyclic imports are valid solidity, the file is only added once
+    if !visited.insert(file.to_path_buf()) {
+        return Ok(())
+    }
+    let content = std::fs::read_to_string(file)
+        .wrap_err_with(|| format!("Failed to read {}", file.display()))?;
+    let source = Source::parse(&content);
+    for import in &source.imports {
+        let import = resolve_import(paths, file, import)?;
+        collect_sources(paths, &import, visited, sources)?;
+    }
+    sources.push((file.to_path_buf(), source));
+    Ok(())
+}
+
+/// A source file split into the directives that are combined into the header of the flattened
+/// file and the code that is inlined
+#[derive(Debug, Default)]
+struct Source {
+    /// The paths of the imported files, as they are written in the import statements
+    imports: Vec<String>,
+    /// The SPDX license identifiers
+    licenses: Vec<String>,
+    /// The `pragma` directives without the `pragma` keyword, with normalized whitespace
+    pragmas: Vec<String>,
+    /// The code without the imports, licenses and pragmas
+    ///
+    /// Aliases of imported symbols are replaced by the symbols and namespace aliases are removed,
+    /// since all imported files are inlined into the same file.
+    code: String,
+}
+
+impl Source {
+    fn parse(content: &str) -> Self {
+        let non_code = non_code_ranges(content);
+        let is_code = |pos: usize| !non_code.iter().any(|range| range.contains(&pos));
+
+        let mut source = Source::default();
+        let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+        let mut aliases = Vec::new();
+        for import in IMPORT_STATEMENT.find_iter(content).filter(|m| is_code(m.start())) {
+            if let Some(cap) = IMPORT_PATH.captures(import.as_str()) {
+                source.imports.push(cap[1].to_string());
+            }
+            aliases.extend(import_aliases(import.as_str()));
+            edits.push((import.range(), String::new()));
+        }
+        for cap in PRAGMA.captures_iter(content) {
+            let pragma = cap.get(0).unwrap();
+            if is_code(pragma.start()) {
+                source.pragmas.push(cap[1].split_whitespace().collect::<Vec<_>>().join(" "));
+                edits.push((pragma.range(), String::new()));
+            }
+        }
+        for cap in LICENSE.captures_iter(content) {
+            if !cap[1].is_empty() {
+                source.licenses.push(cap[1].to_string());
+            }
+            edits.push((cap.get(0).unwrap().range(), String::new()));
+        }
+        for (alias, replacement) in &aliases {
+            for m in alias.find_iter(content) {
+                // members of other contracts or namespaces may have the same name
+                let is_member = content[..m.start()].trim_end().ends_with('.');
+                let is_edited = edits.iter().any(|(range, _)| range.contains(&m.start()));
+                if is_code(m.start()) && !is_member && !is_edited {
+                    edits.push((m.range(), replacement.clone()));
+                }
+            }
+        }
+
+        edits.sort_by_key(|(range, _)| range.start);
+        let mut code = String::new();
+        let mut pos = 0;
+        for (range, replacement) in edits {
+            code.push_str(&content[pos..range.start]);
+            code.push_str(&replacement);
+            pos = range.end;
+        }
+        code.push_str(&content[pos..]);
+        source.code = code.trim().to_string();
+        source
+    }
+}
+
+/// Returns the aliases an import statement declares, as a pattern that matches their uses and the
+/// code that replaces them once the imported file is inlined
+///
+/// `X.Symbol` of a namespace `X` becomes `Symbol` and an alias `B` of `import {A as B}` becomes
+/// `A`.
+fn import_aliases(statement: &str) -> Vec<(Regex, String)> {
+    if let Some(cap) = NAMESPACE_IMPORT.captures(statement) {
+        let namespace = cap.get(1).or_else(|| cap.get(2)).expect("one alternative matches");
+        let pattern = format!(r"\b{}\s*\.\s*", regex::escape(namespace.as_str()));
+        return vec![(Regex::new(&pattern).unwrap(), String::new())]
+    }
+    let symbols = match SYMBOL_IMPORT.captures(statement) {
+        Some(cap) => cap[1].to_string(),
+        None => return Vec::new(),
+    };
+    symbols
+        .split(',')
+        .filter_map(|symbol| match symbol.split_whitespace().collect::<Vec<_>>()[..] {
+            [name, "as", alias] if name != alias => {
+                let pattern = format!(r"\b{}\b", regex::escape(alias));
+                Some((Regex::new(&pattern).unwrap(), name.to_string()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of the comments and string literals of the solidity code
+fn non_code_ranges(content: &str) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'/', Some(b'/')) => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+            }
+            (quote @ (b'"' | b'\''), _) => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+            }
+            _ => {
+                i += 1;
+                continue
+            }
+        }
+        ranges.push(start..i);
+    }
+    ranges
+}
+
+/// Combines the SPDX license identifiers of the files into a single expression that requires all
+/// of them
+///
+/// Compound expressions like `MIT OR Apache-2.0` are parenthesized, so their operators keep
+/// applying to their own identifiers only.
+fn combine_licenses(licenses: &[String]) -> Option<String> {
+    let mut distinct: Vec<&str> = Vec::new();
+    for license in licenses {
+        if !distinct.contains(&license.as_str()) {
+            distinct.push(license);
+        }
+    }
+    match distinct[..] {
+        [] => None,
+        [license] => Some(license.to_string()),
+        _ => Some(
+            distinct
+                .iter()
+                .map(|license| {
+                    if license.contains(char::is_whitespace) {
+                        format!("({license})")
+                    } else {
+                        license.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" AND "),
+        ),
+    }
+}
+
+/// Deduplicates the `pragma` directives other than `pragma solidity` of the files
+///
+/// A file can only select one ABI coder. If any file uses ABI coder v2 it's selected for the
+/// flattened file, since v2 supports everything v1 does.
+fn combine_pragmas(pragmas: &[String]) -> Vec<String> {
+    let abicoder_v2 = pragmas
+        .iter()
+        .any(|pragma| pragma == "abicoder v2" || pragma == "experimental ABIEncoderV2");
+    let abicoder = pragmas.iter().any(|pragma| pragma.starts_with("abicoder "));
+    let mut combined: Vec<String> = Vec::new();
+    for pragma in pragmas {
+        let pragma = match pragma.as_str() {
+            "abicoder v1" if abicoder_v2 => "abicoder v2",
+            "experimental ABIEncoderV2" if abicoder => "abicoder v2",
+            pragma => pragma,
+        };
+        if !combined.iter().any(|combined| combined == pragma) {
+            combined.push(pragma.to_string());
+        }
+    }
+    combined
+}
+
+/// Resolves an import like solc does, relative to the importing file, with the remappings or
+/// relative to the root and the library folders
+fn resolve_import(paths: &ProjectPathsConfig, file: &Path, import: &str) -> eyre::Result<PathBuf> {
+    let path = if import.starts_with("./") || import.starts_with("../") {
+        file.parent().expect("file has a parent").join(import)
+    } else if let Some(remapping) = paths
+        .remappings
+        .iter()
+        .filter(|remapping| import.starts_with(&remapping.name))
+        .max_by_key(|remapping| remapping.name.len())
+    {
+        paths.root.join(&remapping.path).join(&import[remapping.name.len()..])
+    } else {
+        std::iter::once(&paths.root)
+            .chain(&paths.libraries)
+            .map(|dir| paths.root.join(dir).join(import))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| paths.root.join(import))
+    };
+    dunce::canonicalize(&path).wrap_err_with(|| {
+        format!("Failed to resolve the import \"{import}\" of {}", file.display())
+    })
+}
+
+/// Combines the `pragma solidity` requirements of the files into a single requirement
+///
+/// Requirements with `||` alternatives are distributed over the others, alternatives that no solc
+/// version satisfies are dropped.
+fn combine_solidity_pragmas(pragmas: &[(PathBuf, String)]) -> eyre::Result<Option<String>> {
+    let mut distinct: Vec<&str> = Vec::new();
+    for (_, req) in pragmas {
+        if !distinct.contains(&req.as_str()) {
+            distinct.push(req);
+        }
+    }
+    match distinct.len() {
+        0 => return Ok(None),
+        1 => return Ok(Some(distinct[0].to_string())),
+        _ => {}
+    }
+
+    // every combination of one alternative of each requirement
+    let mut combinations: Vec<Vec<&str>> = vec![Vec::new()];
+    for req in &distinct {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                req.split("||").map(move |alternative| {
+                    let mut combination = combination.clone();
+                    combination.push(alternative.trim());
+                    combination
+                })
+            })
+            .collect();
+    }
+    let satisfiable: Vec<String> = combinations
+        .into_iter()
+        .filter(|combination| {
+            let reqs: Option<Vec<VersionReq>> =
+                combination.iter().map(|req| solidity_version_req(req)).collect();
+            // requirements that can't be parsed are kept as they are
+            reqs.map_or(true, |reqs| is_satisfiable(&reqs))
+        })
+        .map(|combination| combination.join(" "))
+        .collect();
+
+    if satisfiable.is_empty() {
+        let pragmas: Vec<String> = pragmas
+            .iter()
+            .map(|(path, req)| format!("  {}: pragma solidity {req};", path.display()))
+            .collect();
+        eyre::bail!(
+            "The solidity pragmas conflict, no solc version satisfies all of them:\n{}",
+            pragmas.join("\n")
+        )
+    }
+    Ok(Some(satisfiable.join(" || ")))
+}
+
+/// Parses a solidity version requirement without alternatives, e.g. `>=0.6.0 <0.9.0`
+///
+/// Unlike semver, a version without an operator is an exact requirement.
+fn solidity_version_req(req: &str) -> Option<VersionReq> {
+    let mut comparators = Vec::new();
+    let mut op = String::new();
+    for token in req.split_whitespace() {
+        if token.chars().all(|c| "<>=^~".contains(c)) {
+            op.push_str(token);
+            continue
+        }
+        let mut comparator = std::mem::take(&mut op);
+        if comparator.is_empty() && token.starts_with(|c: char| c.is_ascii_digit()) {
+            comparator.push('=');
+        }
+        comparator.push_str(token);
+        comparators.push(comparator);
+    }
+    VersionReq::parse(&comparators.join(", ")).ok()
+}
+
+/// Returns whether a version satisfies all requirements
+///
+/// If there is one, one of the versions of the comparators or the patch release after it does.
+fn is_satisfiable(reqs: &[VersionReq]) -> bool {
+    let mut candidates = reqs
+        .iter()
+        .flat_map(|req| req.comparators.iter())
+        .flat_map(|c: &Comparator| {
+            let (minor, patch) = (c.minor.unwrap_or(0), c.patch.unwrap_or(0));
+            [Version::new(c.major, minor, patch), Version::new(c.major, minor, patch + 1)]
+        })
+        .chain(std::iter::once(Version::new(0, 0, 0)));
+    candidates.any(|candidate| reqs.iter().all(|req| req.matches(&candidate)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pragmas(reqs: &[&str]) -> Vec<(PathBuf, String)> {
+        reqs.iter().map(|req| (PathBuf::from("A.sol"), req.to_string())).collect()
+    }
+
+    #[test]
+    fn can_parse_solidity_version_req() {
+        let req = solidity_version_req(">= 0.6.0 <0.9.0").unwrap();
+        assert!(req.matches(&"0.8.10".parse().unwrap()));
+        assert!(!req.matches(&"0.9.0".parse().unwrap()));
+
+        let exact = solidity_version_req("0.8.10").unwrap();
+        assert!(exact.matches(&"0.8.10".parse().unwrap()));
+        assert!(!exact.matches(&"0.8.11".parse().unwrap()));
+    }
+
+    #[test]
+    fn can_combine_solidity_pragmas() {
+        assert_eq!(combine_solidity_pragmas(&pragmas(&[])).unwrap(), None);
+        assert_eq!(
+            combine_solidity_pragmas(&pragmas(&["^0.8.0", "^0.8.0"])).unwrap(),
+            Some("^0.8.0".to_string())
+        );
+        assert_eq!(
+            combine_solidity_pragmas(&pragmas(&["^0.8.0", ">=0.6.2 <0.9.0"])).unwrap(),
+            Some("^0.8.0 >=0.6.2 <0.9.0".to_string())
+        );
+        assert_eq!(
+            combine_solidity_pragmas(&pragmas(&["^0.7.0 || ^0.8.0", ">=0.8.4"])).unwrap(),
+            Some("^0.8.0 >=0.8.4".to_string())
+        );
+        assert!(combine_solidity_pragmas(&pragmas(&["^0.7.6", "^0.8.0"])).is_err());
+    }
+
+    #[test]
+    fn can_inline_aliased_imports() {
+        let source = Source::parse(
+            r#"import {Base as B, Token} from "./Base.sol";
+import "./Math.sol" as M;
+import * as U from "./Utils.sol";
+contract Vault is B {
+    Token token;
+    function f(uint256 x) public returns (uint256) {
+        return M.max(U.min(x, 1), other.B);
+    }
+}"#,
+        );
+        assert_eq!(source.imports, vec!["./Base.sol", "./Math.sol", "./Utils.sol"]);
+        assert_eq!(
+            source.code,
+            r#"contract Vault is Base {
+    Token token;
+    function f(uint256 x) public returns (uint256) {
+        return max(min(x, 1), other.B);
+    }
+}"#
+        );
+    }
+
+    #[test]
+    fn ignores_commented_directives() {
+        let source = Source::parse(
+            r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+/*
+import "./Old.sol";
+pragma abicoder v1;
+*/
+import "./New.sol";
+contract A {
+    string s = "import";
+}"#,
+        );
+        assert_eq!(source.imports, vec!["./New.sol"]);
+        assert_eq!(source.licenses, vec!["MIT"]);
+        assert_eq!(source.pragmas, vec!["solidity ^0.8.0"]);
+        assert!(source.code.contains("import \"./Old.sol\";\npragma abicoder v1;"));
+        assert!(!source.code.contains("./New.sol"));
+    }
+
+    #[test]
+    fn can_combine_licenses() {
+        let licenses = |licenses: &[&str]| {
+            combine_licenses(&licenses.iter().map(|l| l.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(licenses(&[]), None);
+        assert_eq!(licenses(&["MIT", "MIT"]), Some("MIT".to_string()));
+        assert_eq!(licenses(&["MIT OR Apache-2.0"]), Some("MIT OR Apache-2.0".to_string()));
+        assert_eq!(
+            licenses(&["MIT OR Apache-2.0", "GPL-3.0-or-later"]),
+            Some("(MIT OR Apache-2.0) AND GPL-3.0-or-later".to_string())
+        );
+    }
+
+    #[test]
+    fn can_combine_abicoder_pragmas() {
+        let pragmas = |pragmas: &[&str]| {
+            combine_pragmas(&pragmas.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(pragmas(&["abicoder v1", "abicoder v1"]), vec!["abicoder v1"]);
+        assert_eq!(pragmas(&["abicoder v1", "abicoder v2"]), vec!["abicoder v2"]);
+        assert_eq!(pragmas(&["experimental ABIEncoderV2", "abicoder v1"]), vec!["abicoder v2"]);
+        assert_eq!(
+            pragmas(&["experimental ABIEncoderV2", "experimental SMTChecker"]),
+            vec!["experimental ABIEncoderV2", "experimental SMTChecker"]
+        );
+    }
+}
@@ -12,7 +12,8 @@ use ethers::solc::ProjectPathsConfig;
 use rayon::prelude::*;
 use similar::{ChangeTag, TextDiff};
 
-use forge_fmt::{Formatter, FormatterConfig, Visitable};
+use forge_fmt::{Formatter, Visitable};
+use foundry_config::Config;
 
 use crate::cmd::Cmd;
 
@@ -67,17 +68,20 @@ impl Cmd for FmtArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
+        let project_root = self.root.clone().unwrap_or_else(|| {
+            std::env::current_dir().expect("failed to get current directory")
+        });
+        // the `[fmt]` settings of the project's `foundry.toml`
+        let fmt_config = Config::load_with_root(&project_root).fmt;
+
         let root = if let Some(path) = self.path {
             path
         } else {
-            let root = self.root.unwrap_or_else(|| {
-                std::env::current_dir().expect("failed to get current directory")
-            });
-            if !root.is_dir() {
+            if !project_root.is_dir() {
                 return Err(eyre::eyre!("Root path should be a directory"))
             }
 
-            ProjectPathsConfig::find_source_dir(&root)
+            ProjectPathsConfig::find_source_dir(&project_root)
         };
 
         let inputs = if root == PathBuf::from("-") || !atty::is(atty::Stream::Stdin) {
@@ -109,8 +113,7 @@ impl Cmd for FmtArgs {
                         ))?;
 
                 let mut output = String::new();
-                let mut formatter =
-                    Formatter::new(&mut output, &source, FormatterConfig::default());
+                let mut formatter = Formatter::new(&mut output, &source, fmt_config);
 
                 source_unit.visit(&mut formatter).unwrap();
 
@@ -0,0 +1,349 @@
+//! gas-diff command
+use crate::{
+    cmd::{forge::test::Filter, Cmd},
+    compile::ProjectCompiler,
+    utils,
+};
+use clap::{Parser, ValueHint};
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Cell, Color, Table};
+use ethers::types::U256;
+use eyre::WrapErr;
+use forge::{
+    executor::opts::EvmOpts,
+    fuzz::seeded_runner,
+    gas_report::{ContractInfo, GasReport},
+    trace::{identifier::LocalTraceIdentifier, CallTraceDecoderBuilder},
+    MultiContractRunnerBuilder,
+};
+use foundry_config::{find_project_root_path, Config};
+use proptest::test_runner::{TestRng, TestRunner};
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Command to compare the gas usage of the project's functions between two revisions
+#[derive(Debug, Clone, Parser)]
+pub struct GasDiffArgs {
+    #[clap(
+        help = "The git ref to compare the working tree against, e.g. a branch, tag or commit.",
+        value_name = "REF"
+    )]
+    base: String,
+
+    #[clap(long, help = "Print the changes as a markdown table, e.g. for a PR comment.")]
+    markdown: bool,
+
+    #[clap(
+        long,
+        help = "The project's root path. Defaults to the current working directory.",
+        value_hint = ValueHint::DirPath,
+        value_name = "PATH"
+    )]
+    root: Option<PathBuf>,
+
+    #[clap(flatten, next_help_heading = "TEST FILTERING")]
+    filter: Filter,
+}
+
+impl Cmd for GasDiffArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let root = match self.root {
+            Some(root) => root,
+            None => find_project_root_path()?,
+        };
+        let root = dunce::canonicalize(&root)?;
+        let config = Config::load_with_root(&root).sanitized();
+        let filter = self.filter.with_merged_config();
+
+        let worktree = Worktree::add(&root, &self.base)?;
+        let base_root = worktree.path.join(root.strip_prefix(&worktree.git_root)?);
+        // submodules are not checked out in the worktree, so the base uses the current libraries
+        for lib in &config.libs {
+            let lib = lib.strip_prefix(&root).unwrap_or(lib);
+            let (source, target) = (root.join(lib), base_root.join(lib));
+            if source.exists() && !target.join(".git").exists() {
+                let _ = std::fs::remove_dir(&target);
+                link_dir(&source, &target)?;
+            }
+        }
+
+        println!("Running tests at {}...", self.base);
+        let base = gas_report(&base_root, &config, &filter)?;
+        println!("Running tests in the working tree...");
+        let head = gas_report(&root, &config, &filter)?;
+
+        let changes = gas_changes(&base, &head);
+        if changes.is_empty() {
+            println!("No gas changes.");
+        } else if self.markdown {
+            println!("{}", markdown_table(&changes));
+        } else {
+            println!("{}", table(&changes));
+        }
+        Ok(())
+    }
+}
+
+/// A detached git worktree of a ref in a temporary directory, removed on drop
+struct Worktree {
+    /// The root of the repository the worktree was added to
+    git_root: PathBuf,
+    path: PathBuf,
+}
+
+impl Worktree {
+    fn add(root: &Path, git_ref: &str) -> eyre::Result<Self> {
+        let output =
+            Command::new("git").args(&["rev-parse", "--show-toplevel"]).current_dir(root).output()?;
+        if !output.status.success() {
+            eyre::bail!("{} is not in a git repository", root.display())
+        }
+        let git_root = dunce::canonicalize(String::from_utf8(output.stdout)?.trim())?;
+
+        let path = std::env::temp_dir().join(format!("forge-gas-diff-{}", std::process::id()));
+        let output = Command::new("git")
+            .args(&["worktree", "add", "--detach"])
+            .arg(&path)
+            .arg(git_ref)
+            .current_dir(&git_root)
+            .output()?;
+        if !output.status.success() {
+            eyre::bail!(
+                "Failed to check out {git_ref}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        }
+        Ok(Self { git_root, path })
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let _ = Command::new("git")
+            .args(&["worktree", "remove", "--force"])
+            .arg(&self.path)
+            .current_dir(&self.git_root)
+            .output();
+    }
+}
+
+#[cfg(unix)]
+fn link_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+fn link_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(source, target)
+}
+
+/// Runs the tests of the project at `root` and returns their gas report
+///
+/// The fuzzer, the reported contracts and the filter are taken from the working tree, so both
+/// revisions are measured with the same inputs.
+fn gas_report(root: &Path, current: &Config, filter: &Filter) -> eyre::Result<GasReport> {
+    let figment = Config::figment_with_root(root);
+    let mut evm_opts = figment.extract::<EvmOpts>()?;
+    // calls are only traced at verbosity 3
+    evm_opts.verbosity = 3;
+    let config = Config::from_provider(figment).sanitized();
+
+    let project = config.project()?;
+    let output = ProjectCompiler::default()
+        .compile(&project)
+        .wrap_err_with(|| format!("Failed to compile {}", root.display()))?;
+
+    let cfg = proptest::test_runner::Config {
+        failure_persistence: None,
+        cases: current.fuzz_runs,
+        max_local_rejects: current.fuzz_max_local_rejects,
+        max_global_rejects: current.fuzz_max_global_rejects,
+        ..Default::default()
+    };
+    let fuzzer = match current.fuzz_seed {
+        Some(seed) => seeded_runner(cfg, seed),
+        None => {
+            let rng = TestRng::deterministic_rng(cfg.rng_algorithm);
+            TestRunner::new_with_rng(cfg, rng)
+        }
+    };
+
+    let mut runner = MultiContractRunnerBuilder::default()
+        .fuzzer(fuzzer)
+        .initial_balance(evm_opts.initial_balance)
+        .evm_spec(utils::evm_spec(&config.evm_version))
+        .sender(evm_opts.sender)
+        .with_fork(utils::get_fork(&evm_opts, &config.rpc_storage_caching))
+        .with_rpc_storage_caching(config.rpc_storage_caching.clone())
+        .with_fuzz_dictionary_weight(current.fuzz_dictionary_weight)
+        .with_invariant_config(current.invariant)
+        .with_constructor_args(config.constructor_args.resolve(&project.paths.root)?)
+        .with_table_fixtures_dir(Some(project.paths.tests.join("fixtures")))
+        .build(&project.paths.root, output, evm_opts)?;
+    let results = runner.test(filter, None, true)?;
+
+    let local_identifier = LocalTraceIdentifier::new(&runner.known_contracts);
+    let mut report =
+        GasReport::new(current.gas_reports.clone(), current.gas_reports_ignore.clone());
+    for suite in results.into_values() {
+        for (_, mut result) in suite.test_results {
+            let mut decoder = CallTraceDecoderBuilder::new()
+                .with_labels(result.labeled_addresses.clone())
+                .with_events(local_identifier.events())
+                .build();
+            for (_, trace) in &mut result.traces {
+                decoder.identify(trace, &local_identifier);
+                decoder.decode(trace);
+            }
+            report.analyze(&result.traces);
+        }
+    }
+    Ok(report.finalize())
+}
+
+/// The mean gas of a function, or of a contract's deployment, in the base and the working tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GasChange {
+    contract: String,
+    function: String,
+    /// `None` if the function does not exist at the base
+    base: Option<U256>,
+    /// `None` if the function no longer exists
+    head: Option<U256>,
+}
+
+impl GasChange {
+    fn delta(&self) -> String {
+        match (self.base, self.head) {
+            (Some(base), Some(head)) => {
+                let (sign, delta) =
+                    if head >= base { ('+', head - base) } else { ('-', base - head) };
+                if base.is_zero() {
+                    return format!("{sign}{delta}")
+                }
+                let percent = delta.as_u128() as f64 * 100. / base.as_u128() as f64;
+                format!("{sign}{delta} ({sign}{percent:.2}%)")
+            }
+            (None, _) => "added".to_string(),
+            (_, None) => "removed".to_string(),
+        }
+    }
+
+    fn row(&self) -> [String; 5] {
+        let gas = |gas: Option<U256>| gas.map_or_else(|| "-".to_string(), |gas| gas.to_string());
+        [self.contract.clone(), self.function.clone(), gas(self.base), gas(self.head), self.delta()]
+    }
+}
+
+const DEPLOYMENT: &str = "(deployment)";
+const HEADER: [&str; 5] = ["Contract", "Function", "Base", "Head", "Change"];
+
+/// Returns the deployments and functions whose mean gas changed between the two reports
+fn gas_changes(base: &GasReport, head: &GasReport) -> Vec<GasChange> {
+    let mut changes = Vec::new();
+    let mut push = |contract: &str, function: &str, base: Option<U256>, head: Option<U256>| {
+        if base != head {
+            changes.push(GasChange {
+                contract: contract.to_string(),
+                function: function.to_string(),
+                base,
+                head,
+            });
+        }
+    };
+
+    let contracts = base.contracts.keys().chain(head.contracts.keys()).collect::<BTreeSet<_>>();
+    for name in contracts {
+        let (base, head) = (base.contracts.get(name), head.contracts.get(name));
+        push(name, DEPLOYMENT, base.map(|c| c.gas), head.map(|c| c.gas));
+
+        let functions = base
+            .into_iter()
+            .chain(head)
+            .flat_map(|contract| contract.functions.keys())
+            .collect::<BTreeSet<_>>();
+        for function in functions {
+            let mean = |contract: Option<&ContractInfo>| {
+                contract.and_then(|c| c.functions.get(function)).map(|f| f.mean)
+            };
+            push(name, function, mean(base), mean(head));
+        }
+    }
+    changes
+}
+
+fn table(changes: &[GasChange]) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+    table.set_header(HEADER);
+    for change in changes {
+        let color = match (change.base, change.head) {
+            (Some(base), Some(head)) if head > base => Color::Red,
+            (Some(_), Some(_)) => Color::Green,
+            _ => Color::Yellow,
+        };
+        let mut row = change.row().map(Cell::new);
+        row[4] = Cell::new(change.delta()).fg(color);
+        table.add_row(row);
+    }
+    table
+}
+
+fn markdown_table(changes: &[GasChange]) -> String {
+    let mut out = format!("| {} |\n|---|---|--:|--:|--:|\n", HEADER.join(" | "));
+    for change in changes {
+        let _ = writeln!(out, "| {} |", change.row().join(" | "));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge::gas_report::GasInfo;
+
+    fn report(contracts: &[(&str, u64, &[(&str, u64)])]) -> GasReport {
+        let mut report = GasReport::default();
+        for (name, gas, functions) in contracts {
+            let functions = functions
+                .iter()
+                .map(|(f, mean)| {
+                    (f.to_string(), GasInfo { mean: (*mean).into(), ..Default::default() })
+                })
+                .collect();
+            report.contracts.insert(
+                name.to_string(),
+                ContractInfo { gas: (*gas).into(), size: 0.into(), functions },
+            );
+        }
+        report
+    }
+
+    #[test]
+    fn can_diff_gas_reports() {
+        let base = report(&[("Counter", 1000, &[("increment()", 200), ("reset()", 100)])]);
+        let head = report(&[("Counter", 1000, &[("increment()", 150), ("set(uint256)", 300)])]);
+
+        let changes = gas_changes(&base, &head);
+        assert_eq!(
+            changes.iter().map(|c| c.function.as_str()).collect::<Vec<_>>(),
+            ["increment()", "reset()", "set(uint256)"]
+        );
+
+        assert_eq!(
+            markdown_table(&changes),
+            concat!(
+                "| Contract | Function | Base | Head | Change |\n",
+                "|---|---|--:|--:|--:|\n",
+                "| Counter | increment() | 200 | 150 | -50 (-25.00%) |\n",
+                "| Counter | reset() | 100 | - | removed |\n",
+                "| Counter | set(uint256) | - | 300 | added |\n",
+            )
+        );
+    }
+}
@@ -3,10 +3,11 @@
 use crate::{
     cmd::{forge::install::install, Cmd},
     opts::forge::Dependency,
-    utils::p_println,
+    utils::{self, p_println},
 };
 use clap::{Parser, ValueHint};
-use foundry_config::Config;
+use eyre::WrapErr;
+use foundry_config::{fmt::IndentStyle, Config};
 
 use crate::cmd::forge::{install::DependencyInstallOpts, remappings};
 use yansi::Paint;
@@ -36,10 +37,9 @@ pub struct InitArgs {
     #[clap(
         help = "Do not install dependencies from the network.",
         conflicts_with = "template",
-        long,
-        alias = "no-deps"
+        long
     )]
-    offline: bool,
+    no_deps: bool,
     #[clap(
         help = "Create the project even if the specified root directory is not empty.",
         conflicts_with = "template",
@@ -48,17 +48,47 @@ pub struct InitArgs {
     force: bool,
     #[clap(
         help = "Create a .vscode/settings.json file with Solidity settings, and generate a remappings.txt file.",
+        long_help = "Create a .vscode/settings.json file with Solidity settings, the remappings and the formatter settings of the `[fmt]` config, and generate a remappings.txt file.",
         conflicts_with = "template",
         long
     )]
     vscode: bool,
+    #[clap(help = "Create a .solhint.json file.", conflicts_with = "template", long)]
+    solhint: bool,
+    #[clap(
+        help = "Create a .gitattributes file that keeps the gas snapshot from being merged.",
+        conflicts_with = "template",
+        long
+    )]
+    gitattributes: bool,
+    #[clap(
+        help = "The directory of the templates of the editor files.",
+        long_help = "The directory of the templates of the editor files. A `.solhint.json` or `.gitattributes` file in it replaces the default, the settings of a `.vscode/settings.json` file in it are added to the generated settings. Ignored with `--template`.",
+        long,
+        env = "FOUNDRY_EDITOR_TEMPLATES",
+        value_hint = ValueHint::DirPath,
+        value_name = "PATH"
+    )]
+    editor_templates: Option<PathBuf>,
 }
 
 impl Cmd for InitArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
-        let InitArgs { root, template, no_git, no_commit, quiet, offline, force, vscode } = self;
+        let InitArgs {
+            root,
+            template,
+            no_git,
+            no_commit,
+            quiet,
+            no_deps,
+            force,
+            vscode,
+            solhint,
+            gitattributes,
+            editor_templates,
+        } = self;
 
         let root = root.unwrap_or_else(|| std::env::current_dir().unwrap());
         // create the root dir if it does not exist
@@ -116,12 +146,33 @@ impl Cmd for InitArgs {
                 std::fs::write(dest, config.to_string_pretty()?)?;
             }
 
+            // editor files that are part of the initial commit
+            let templates = editor_templates.as_deref();
+            if solhint {
+                write_editor_file(
+                    &root,
+                    templates,
+                    quiet,
+                    ".solhint.json",
+                    include_str!("../../../assets/solhintTemplate.json"),
+                )?;
+            }
+            if gitattributes {
+                write_editor_file(
+                    &root,
+                    templates,
+                    quiet,
+                    ".gitattributes",
+                    include_str!("../../../assets/.gitattributesTemplate"),
+                )?;
+            }
+
             // sets up git
             if !no_git {
                 init_git_repo(&root, no_commit)?;
             }
 
-            if !offline {
+            if !no_deps && !utils::is_offline() {
                 let opts = DependencyInstallOpts { no_git, no_commit, quiet };
 
                 if root.join("lib/forge-std").exists() {
@@ -132,9 +183,9 @@ impl Cmd for InitArgs {
                         .and_then(|dependency| install(&root, vec![dependency], opts))?;
                 }
             }
-            // vscode init
+            // the vscode settings contain the remappings of the installed dependencies
             if vscode {
-                init_vscode(&root)?;
+                init_vscode(&root, templates)?;
             }
         }
 
@@ -186,19 +237,43 @@ fn init_git_repo(root: &Path, no_commit: bool) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Returns the content of the template file in the templates dir, or the default content
+fn editor_template(templates: Option<&Path>, name: &str, default: &str) -> eyre::Result<String> {
+    match templates.map(|dir| dir.join(name)).filter(|path| path.exists()) {
+        Some(path) => std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read the template {}", path.display())),
+        None => Ok(default.to_string()),
+    }
+}
+
+/// Writes the editor file `name` to the root from its template, unless it already exists
+fn write_editor_file(
+    root: &Path,
+    templates: Option<&Path>,
+    quiet: bool,
+    name: &str,
+    default: &str,
+) -> eyre::Result<()> {
+    let path = root.join(name);
+    if path.exists() {
+        p_println!(!quiet => "\"{}\" already exists, skipping.", name);
+        return Ok(())
+    }
+    std::fs::write(path, editor_template(templates, name, default)?)?;
+    Ok(())
+}
+
 /// initializes the `.vscode/settings.json` file
-fn init_vscode(root: &Path) -> eyre::Result<()> {
+fn init_vscode(root: &Path, templates: Option<&Path>) -> eyre::Result<()> {
+    let mut remappings = remappings::relative_remappings(&root.join("lib"), root)
+        .into_iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>();
+    remappings.sort();
     let remappings_file = root.join("remappings.txt");
-    if !remappings_file.exists() {
-        let mut remappings = remappings::relative_remappings(&root.join("lib"), root)
-            .into_iter()
-            .map(|r| r.to_string())
-            .collect::<Vec<_>>();
-        remappings.sort();
-        if !remappings.is_empty() {
-            let content = remappings.join("\n");
-            std::fs::write(remappings_file, content)?;
-        }
+    if !remappings_file.exists() && !remappings.is_empty() {
+        let content = remappings.join("\n");
+        std::fs::write(remappings_file, content)?;
     }
 
     let vscode_dir = root.join(".vscode");
@@ -222,9 +297,48 @@ fn init_vscode(root: &Path) -> eyre::Result<()> {
     if !obj.contains_key(lib_key) {
         obj.insert(lib_key.to_string(), serde_json::Value::String("lib".to_string()));
     }
+    if !remappings.is_empty() {
+        obj.entry("solidity.remappings").or_insert_with(|| remappings.into());
+    }
+
+    // format with `forge fmt`, and its settings
+    let fmt = Config::load_with_root(root).fmt;
+    obj.entry("solidity.formatter").or_insert_with(|| "forge".into());
+    obj.entry("[solidity]").or_insert_with(|| {
+        serde_json::json!({
+            "editor.tabSize": fmt.tab_width,
+            "editor.insertSpaces": fmt.style == IndentStyle::Space,
+            "editor.rulers": [fmt.line_length],
+        })
+    });
+
+    // the settings of the template that are not set yet
+    if let Some(path) = templates.map(|dir| dir.join(".vscode/settings.json")) {
+        if path.exists() {
+            let template: serde_json::Value = ethers::solc::utils::read_json_file(&path)?;
+            if let serde_json::Value::Object(template) = template {
+                for (key, value) in template {
+                    obj.entry(key).or_insert(value);
+                }
+            }
+        }
+    }
 
     let content = serde_json::to_string_pretty(&settings)?;
     std::fs::write(settings_file, content)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_use_template_with_editor_templates_env() {
+        std::env::set_var("FOUNDRY_EDITOR_TEMPLATES", "templates");
+        let args = InitArgs::try_parse_from(["init", "--template", "foundry-rs/forge-template"]);
+        std::env::remove_var("FOUNDRY_EDITOR_TEMPLATES");
+        assert_eq!(args.unwrap().template.as_deref(), Some("foundry-rs/forge-template"));
+    }
+}
@@ -5,14 +5,24 @@ use crate::{
     },
     compile,
     opts::forge::CompilerArgs,
+    utils,
 };
 use clap::Parser;
 use comfy_table::Table;
-use ethers::prelude::artifacts::output_selection::{
-    ContractOutputSelection, EvmOutputSelection, EwasmOutputSelection,
+use ethers::{
+    abi::{self, param_type::Reader},
+    prelude::{
+        artifacts::output_selection::{
+            ContractOutputSelection, EvmOutputSelection, EwasmOutputSelection,
+        },
+        Http, Middleware, Provider,
+    },
+    types::Address,
 };
+use foundry_utils::format_token;
+use serde::Serialize;
 use serde_json::{to_value, Value};
-use std::{fmt, str::FromStr};
+use std::{collections::BTreeMap, fmt, str::FromStr};
 
 /// Contract level output selection
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -31,6 +41,7 @@ pub enum ContractArtifactFields {
     Metadata,
     UserDoc,
     Ewasm,
+    Immutables,
 }
 
 impl fmt::Display for ContractArtifactFields {
@@ -50,6 +61,7 @@ impl fmt::Display for ContractArtifactFields {
             ContractArtifactFields::Metadata => f.write_str("metadata"),
             ContractArtifactFields::UserDoc => f.write_str("userdoc"),
             ContractArtifactFields::Ewasm => f.write_str("ewasm"),
+            ContractArtifactFields::Immutables => f.write_str("immutables"),
         }
     }
 }
@@ -83,6 +95,9 @@ impl FromStr for ContractArtifactFields {
             "metadata" | "meta" => Ok(ContractArtifactFields::Metadata),
             "userdoc" | "userDoc" | "user-doc" => Ok(ContractArtifactFields::UserDoc),
             "ewasm" | "e-wasm" => Ok(ContractArtifactFields::Ewasm),
+            "immutables" | "immutable" | "immutableReferences" | "immutable-references" => {
+                Ok(ContractArtifactFields::Immutables)
+            }
             _ => Err(format!("Unknown field: {s}")),
         }
     }
@@ -99,6 +114,21 @@ pub struct InspectArgs {
     #[clap(long, help = "Pretty print the selected field, if supported.")]
     pub pretty: bool,
 
+    #[clap(
+        long,
+        help = "The address of a deployed instance to read the values of the immutables from.",
+        value_name = "ADDRESS"
+    )]
+    pub address: Option<Address>,
+
+    #[clap(
+        long,
+        env = "ETH_RPC_URL",
+        help = "The RPC endpoint to read the code of the deployed instance from.",
+        value_name = "URL"
+    )]
+    pub rpc_url: Option<String>,
+
     /// All build arguments are supported
     #[clap(flatten)]
     build: build::CoreBuildArgs,
@@ -107,7 +137,7 @@ pub struct InspectArgs {
 impl Cmd for InspectArgs {
     type Output = ();
     fn run(self) -> eyre::Result<Self::Output> {
-        let InspectArgs { contract, field, build, pretty } = self;
+        let InspectArgs { contract, field, build, pretty, address, rpc_url } = self;
 
         // Map field to ContractOutputSelection
         let mut cos = build.compiler.extra_output;
@@ -138,6 +168,7 @@ impl Cmd for InspectArgs {
                 ContractArtifactFields::Ewasm => {
                     cos.push(ContractOutputSelection::Ewasm(EwasmOutputSelection::All))
                 }
+                ContractArtifactFields::Immutables => { /* Auto Generated */ }
             }
         }
 
@@ -266,8 +297,134 @@ impl Cmd for InspectArgs {
                     ))?
                 );
             }
+            ContractArtifactFields::Immutables => {
+                // The references only contain the AST ids of the immutables, their names and types
+                // are in the AST of their source file
+                let mut declarations = BTreeMap::new();
+                let (_, sources) = outcome.clone().into_artifacts_with_sources();
+                for source in sources.0.values().flatten() {
+                    if let Some(ast) = &source.source_file.ast {
+                        collect_immutables(&to_value(ast)?, &mut declarations);
+                    }
+                }
+
+                let code = match address {
+                    Some(address) => {
+                        let provider =
+                            Provider::<Http>::try_from(utils::consume_config_rpc_url(rpc_url))?;
+                        let code = utils::block_on(provider.get_code(address, None))?;
+                        if code.is_empty() {
+                            eyre::bail!("No code deployed at {address:?}")
+                        }
+                        Some(code)
+                    }
+                    None => None,
+                };
+
+                let references = artifact
+                    .deployed_bytecode
+                    .as_ref()
+                    .map(|bytecode| bytecode.immutable_references.clone())
+                    .unwrap_or_default();
+                let immutables = references
+                    .into_iter()
+                    .map(|(id, offsets)| {
+                        let (name, ty) = declarations
+                            .get(&id)
+                            .cloned()
+                            .unwrap_or_else(|| (format!("<unknown {id}>"), "?".to_string()));
+                        // every reference holds the same 32 byte word, the first one is decoded
+                        let value = code.as_ref().and_then(|code| {
+                            let offset = offsets.first()?;
+                            let start = offset.start as usize;
+                            let word = code.get(start..start + offset.length as usize)?;
+                            Some(decode_immutable(&ty, word))
+                        });
+                        let offsets = offsets.iter().map(|offset| offset.start as usize).collect();
+                        Immutable { name, ty, offsets, value }
+                    })
+                    .collect::<Vec<_>>();
+
+                if pretty {
+                    let mut table = Table::new();
+                    let mut header = vec!["Name", "Type", "Offsets"];
+                    if code.is_some() {
+                        header.push("Value");
+                    }
+                    table.set_header(header);
+                    for immutable in immutables {
+                        let offsets =
+                            immutable.offsets.iter().map(ToString::to_string).collect::<Vec<_>>();
+                        let mut row = vec![immutable.name, immutable.ty, offsets.join(", ")];
+                        row.extend(immutable.value);
+                        table.add_row(row);
+                    }
+                    println!("{table}");
+                } else {
+                    println!("{}", serde_json::to_string_pretty(&immutables)?);
+                }
+            }
         };
 
         Ok(())
     }
 }
+
+/// An immutable of a contract and where its value is placed in the deployed bytecode
+#[derive(Debug, Serialize)]
+struct Immutable {
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    /// The byte offsets of the 32 byte words holding the value, one for every read of it
+    offsets: Vec<usize>,
+    /// The value in the code of a deployed instance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+/// Collects the name and type of all immutables declared in the AST, by the id of their
+/// declaration
+fn collect_immutables(node: &Value, declarations: &mut BTreeMap<String, (String, String)>) {
+    match node {
+        Value::Object(node) => {
+            let is_immutable = node.get("nodeType").and_then(Value::as_str) ==
+                Some("VariableDeclaration") &&
+                node.get("mutability").and_then(Value::as_str) == Some("immutable");
+            if is_immutable {
+                let id = node.get("id").and_then(Value::as_u64);
+                let name = node.get("name").and_then(Value::as_str);
+                let ty = node
+                    .get("typeDescriptions")
+                    .and_then(|ty| ty.get("typeString"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("?");
+                if let (Some(id), Some(name)) = (id, name) {
+                    declarations.insert(id.to_string(), (name.to_string(), ty.to_string()));
+                }
+            }
+            node.values().for_each(|value| collect_immutables(value, declarations));
+        }
+        Value::Array(values) => {
+            values.iter().for_each(|value| collect_immutables(value, declarations))
+        }
+        _ => {}
+    }
+}
+
+/// Decodes the 32 byte word of an immutable of the Solidity type `ty`, or returns it as hex if the
+/// type is not an ABI type, e.g. a user defined value type
+fn decode_immutable(ty: &str, word: &[u8]) -> String {
+    let ty = if ty.starts_with("address") || ty.starts_with("contract ") {
+        "address"
+    } else if ty.starts_with("enum ") {
+        "uint8"
+    } else {
+        ty
+    };
+    Reader::read(ty)
+        .ok()
+        .and_then(|ty| abi::decode(&[ty], word).ok())
+        .and_then(|mut tokens| tokens.pop())
+        .map_or_else(|| format!("0x{}", hex::encode(word)), |token| format_token(&token))
+}
@@ -0,0 +1,588 @@
+//! Migrate command
+use crate::cmd::Cmd;
+use clap::{Parser, ValueHint};
+use eyre::WrapErr;
+use foundry_config::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// Command to migrate a hardhat or truffle project to forge
+#[derive(Debug, Clone, Parser)]
+pub struct MigrateArgs {
+    /// The root of the hardhat or truffle project, defaults to the current working directory.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    root: Option<PathBuf>,
+
+    /// Overwrite an existing `foundry.toml`.
+    #[clap(long)]
+    force: bool,
+
+    /// Do not create a test stub for every contract.
+    #[clap(long)]
+    no_tests: bool,
+}
+
+impl Cmd for MigrateArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let root = self.root.unwrap_or_else(|| std::env::current_dir().unwrap());
+        let dest = root.join(Config::FILE_NAME);
+        if dest.exists() && !self.force {
+            eyre::bail!(
+                "{} already exists, run `forge migrate --force` to overwrite it",
+                dest.display()
+            )
+        }
+
+        let (toolchain, config_file) = Toolchain::detect(&root).ok_or_else(|| {
+            eyre::eyre!("No hardhat or truffle config found in {}", root.display())
+        })?;
+        let content = std::fs::read_to_string(&config_file)
+            .wrap_err_with(|| format!("Failed to read {}", config_file.display()))?;
+        let mut migration = Migration::parse(toolchain, &content);
+        migration.remappings = node_modules_remappings(&root, &root.join(&migration.src))?;
+
+        let file_name = config_file.file_name().unwrap_or_default().to_string_lossy();
+        std::fs::write(&dest, migration.to_toml(&file_name))?;
+        println!("{} {} from {file_name}", Paint::green("Created"), Config::FILE_NAME);
+        if !migration.rpc_endpoints.is_empty() {
+            let names: Vec<&str> =
+                migration.rpc_endpoints.iter().map(|(name, _)| name.as_str()).collect();
+            println!("  rpc endpoints: {}", names.join(", "));
+        }
+        if !migration.remappings.is_empty() {
+            println!("  remappings: {}", migration.remappings.join(", "));
+        }
+
+        if !self.no_tests {
+            let stubs = write_test_stubs(&root, &migration.src, &migration.test)?;
+            for stub in &stubs {
+                println!("{} {}", Paint::green("Created"), stub.display());
+            }
+            if !stubs.is_empty() && !root.join("lib/forge-std").exists() {
+                println!(
+                    "The tests import forge-std, install it with \
+                     `forge install foundry-rs/forge-std`"
+                );
+            }
+        }
+
+        for warning in &migration.warnings {
+            eprintln!("{} {warning}", Paint::yellow("Warning:"));
+        }
+        Ok(())
+    }
+}
+
+/// The toolchain a project is migrated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Toolchain {
+    Hardhat,
+    Truffle,
+}
+
+impl Toolchain {
+    /// Returns the toolchain of the project and its config file
+    fn detect(root: &Path) -> Option<(Self, PathBuf)> {
+        let hardhat = ["hardhat.config.js", "hardhat.config.ts", "hardhat.config.cjs"];
+        let truffle = ["truffle-config.js", "truffle.js"];
+        let candidates = hardhat
+            .iter()
+            .map(|name| (Toolchain::Hardhat, name))
+            .chain(truffle.iter().map(|name| (Toolchain::Truffle, name)));
+        for (toolchain, name) in candidates {
+            let path = root.join(name);
+            if path.exists() {
+                return Some((toolchain, path))
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Toolchain::Hardhat => f.write_str("hardhat"),
+            Toolchain::Truffle => f.write_str("truffle"),
+        }
+    }
+}
+
+/// The settings of a hardhat or truffle project, translated to the config
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Migration {
+    src: String,
+    test: String,
+    solc_version: Option<String>,
+    optimizer: Option<bool>,
+    optimizer_runs: Option<u64>,
+    evm_version: Option<String>,
+    via_ir: bool,
+    /// The networks, by name
+    rpc_endpoints: Vec<(String, String)>,
+    remappings: Vec<String>,
+    /// The settings that could not be migrated
+    warnings: Vec<String>,
+}
+
+impl Migration {
+    /// Reads the settings from the JS source of the config
+    ///
+    /// The config is not evaluated, the settings are read from its object literals. Env vars
+    /// read with `process.env` are kept as `${VAR}` references, so the secrets stay in the env.
+    fn parse(toolchain: Toolchain, config: &str) -> Self {
+        let config = strip_comments(config);
+        let mut migration = Migration {
+            src: "contracts".to_string(),
+            // the JS tests stay in `test`
+            test: "test/foundry".to_string(),
+            ..Default::default()
+        };
+
+        let (src, compiler) = match toolchain {
+            Toolchain::Hardhat => {
+                let src = block(&config, "paths").and_then(|paths| string_value(paths, "sources"));
+                let compiler = block(&config, "solidity");
+                // `solidity: "0.8.10"`
+                if compiler.is_none() {
+                    migration.solc_version = string_value(&config, "solidity");
+                }
+                (src, compiler)
+            }
+            Toolchain::Truffle => {
+                let src = string_value(&config, "contracts_directory");
+                (src, block(&config, "compilers").and_then(|compilers| block(compilers, "solc")))
+            }
+        };
+        if let Some(src) = src {
+            migration.src = src.trim_start_matches("./").trim_end_matches('/').to_string();
+        }
+        if let Some(compiler) = compiler {
+            migration.solc_version = string_value(compiler, "version");
+            if let Some(optimizer) = block(compiler, "optimizer") {
+                migration.optimizer = raw_value(optimizer, "enabled").map(|v| v == "true");
+                migration.optimizer_runs =
+                    raw_value(optimizer, "runs").and_then(|v| v.parse().ok());
+            }
+            migration.evm_version = string_value(compiler, "evmVersion");
+            migration.via_ir = raw_value(compiler, "viaIR") == Some("true");
+            if compiler.matches("version").count() > 1 {
+                migration.warnings.push(
+                    "Multiple solc versions are configured, only the first one is migrated"
+                        .to_string(),
+                );
+            }
+        }
+        if let Some(version) = migration.solc_version.clone() {
+            // a requirement like `^0.8.0` is left to the auto detection
+            if semver::Version::parse(&version).is_err() {
+                migration.warnings.push(format!(
+                    "The solc version `{version}` is not an exact version, the solc version is \
+                     detected from the pragmas instead"
+                ));
+                migration.solc_version = None;
+            }
+        }
+
+        for (name, network) in block(&config, "networks").map(entries).unwrap_or_default() {
+            let url = match toolchain {
+                Toolchain::Hardhat => string_value(network, "url"),
+                Toolchain::Truffle => truffle_url(network),
+            };
+            match url {
+                Some(url) => migration.rpc_endpoints.push((name, url)),
+                // the in-process hardhat network has no url
+                None if name == "hardhat" => {}
+                None => migration
+                    .warnings
+                    .push(format!("The url of the network `{name}` could not be migrated")),
+            }
+        }
+        if config.contains("accounts") || config.contains("mnemonic") {
+            migration.warnings.push(
+                "Accounts are not migrated, pass them to forge with `--private-key` or \
+                 `--mnemonic-path`"
+                    .to_string(),
+            );
+        }
+        migration
+    }
+
+    /// Returns the `foundry.toml` with the migrated settings
+    fn to_toml(&self, source: &str) -> String {
+        let mut toml = format!("# Migrated from {source} with `forge migrate`\n[default]\n");
+        writeln!(toml, "src = {}", quoted(&self.src)).unwrap();
+        writeln!(toml, "test = {}", quoted(&self.test)).unwrap();
+        toml.push_str("out = \"out\"\nlibs = [\"node_modules\", \"lib\"]\n");
+        if !self.remappings.is_empty() {
+            toml.push_str("remappings = [\n");
+            for remapping in &self.remappings {
+                writeln!(toml, "    {},", quoted(remapping)).unwrap();
+            }
+            toml.push_str("]\n");
+        }
+        if let Some(version) = &self.solc_version {
+            writeln!(toml, "solc_version = {}", quoted(version)).unwrap();
+        }
+        if let Some(optimizer) = self.optimizer {
+            writeln!(toml, "optimizer = {optimizer}").unwrap();
+        }
+        if let Some(runs) = self.optimizer_runs {
+            writeln!(toml, "optimizer_runs = {runs}").unwrap();
+        }
+        if let Some(evm_version) = &self.evm_version {
+            writeln!(toml, "evm_version = {}", quoted(evm_version)).unwrap();
+        }
+        if self.via_ir {
+            toml.push_str("via_ir = true\n");
+        }
+        if !self.rpc_endpoints.is_empty() {
+            toml.push_str("\n[default.rpc_endpoints]\n");
+            for (name, url) in &self.rpc_endpoints {
+                writeln!(toml, "{name} = {}", quoted(url)).unwrap();
+            }
+        }
+        toml
+    }
+}
+
+/// Returns the value as a TOML string
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).expect("strings are serializable")
+}
+
+static COMMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?s)/\*.*?\*/|(?m)(^|[^:"'])//[^\n]*"#).unwrap());
+
+/// Removes the comments of the JS source, `//` after a `:` is kept for urls
+fn strip_comments(source: &str) -> String {
+    COMMENT.replace_all(source, "$1").into_owned()
+}
+
+/// Returns the content of the object or array literal of the key, without its brackets
+fn block<'a>(source: &'a str, key: &str) -> Option<&'a str> {
+    let re = Regex::new(&format!(r#"(?:\b{key}|["']{key}["'])\s*:\s*[\{{\[]"#)).unwrap();
+    let start = re.find(source)?.end();
+    let end = closing_bracket(&source[start..])?;
+    Some(&source[start..start + end])
+}
+
+/// Returns the offset of the bracket that closes the literal the source is the content of
+fn closing_bracket(source: &str) -> Option<usize> {
+    top_level_end(source, &[])
+}
+
+/// Returns the offset of the first of the chars, or of an unmatched closing bracket, that is
+/// outside of string literals and nested brackets
+fn top_level_end(source: &str, stop_at: &[char]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in source.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '{' | '[' | '(') => depth += 1,
+            (None, '}' | ']' | ')') if depth == 0 => return Some(i),
+            (None, '}' | ']' | ')') => depth -= 1,
+            (None, c) if depth == 0 && stop_at.contains(&c) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+static ENTRY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?:(\w+)|["']([^"']+)["'])\s*:\s*\{"#).unwrap());
+
+/// Returns the object literals of an object literal, by their key
+fn entries(source: &str) -> Vec<(String, &str)> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while let Some(cap) = ENTRY.captures(&source[offset..]) {
+        let name = cap.get(1).or_else(|| cap.get(2)).unwrap().as_str().to_string();
+        let start = offset + cap.get(0).unwrap().end();
+        let end = match closing_bracket(&source[start..]) {
+            Some(end) => start + end,
+            None => break,
+        };
+        entries.push((name, &source[start..end]));
+        // the nested objects of the entry are not entries
+        offset = end + 1;
+    }
+    entries
+}
+
+/// Returns the raw value of the key, e.g. `true` or `200`
+fn raw_value<'a>(source: &'a str, key: &str) -> Option<&'a str> {
+    let re = Regex::new(&format!(r#"(?:\b{key}|["']{key}["'])\s*:"#)).unwrap();
+    let value = &source[re.find(source)?.end()..];
+    let end = top_level_end(value, &[',', '\n']).unwrap_or(value.len());
+    Some(value[..end].trim()).filter(|value| !value.is_empty())
+}
+
+/// Returns the value of the key if it is a string, see [`js_string`]
+fn string_value(source: &str, key: &str) -> Option<String> {
+    raw_value(source, key).and_then(js_string)
+}
+
+static ENV_VAR: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^process\.env\.(\w+)"#).unwrap());
+static ENV_VAR_ANYWHERE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"process\.env\.(\w+)"#).unwrap());
+static TEMPLATE_ENV_VAR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\$\{\s*process\.env\.(\w+)\s*\}"#).unwrap());
+
+/// Evaluates a JS string expression of string literals, template literals and env vars
+///
+/// The env vars are kept as `${VAR}` references, other variables can't be evaluated.
+fn js_string(expr: &str) -> Option<String> {
+    let mut value = String::new();
+    for part in split_concatenation(expr) {
+        let part = part.trim();
+        let quoted = |q: char| part.strip_prefix(q).and_then(|part| part.strip_suffix(q));
+        if let Some(literal) = quoted('"').or_else(|| quoted('\'')) {
+            value.push_str(literal);
+        } else if let Some(template) = quoted('`') {
+            // other interpolations can't be evaluated
+            if template.matches("${").count() != TEMPLATE_ENV_VAR.find_iter(template).count() {
+                return None
+            }
+            let template = TEMPLATE_ENV_VAR.replace_all(template, "$${$1}");
+            value.push_str(&template);
+        } else if let Some(cap) = ENV_VAR.captures(part) {
+            // a default like `process.env.URL || "..."` is dropped
+            write!(value, "${{{}}}", &cap[1]).unwrap();
+        } else {
+            return None
+        }
+    }
+    Some(value)
+}
+
+/// Splits the expression at the `+` operators outside of literals
+fn split_concatenation(expr: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in expr.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '+') => {
+                parts.push(&expr[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&expr[start..]);
+    parts
+}
+
+static URL_LITERAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"["'`](?:https?|wss?)://[^"'`]*["'`](?:\s*\+\s*[\w.]+)*"#).unwrap());
+
+/// Returns the url of a truffle network, from its `host` and `port` or its provider
+fn truffle_url(network: &str) -> Option<String> {
+    if let Some(host) = string_value(network, "host") {
+        let port = raw_value(network, "port").unwrap_or("8545");
+        return Some(format!("http://{host}:{port}"))
+    }
+    // e.g. `provider: () => new HDWalletProvider(mnemonic, "https://...")`
+    let provider = block(network, "provider").unwrap_or(network);
+    URL_LITERAL.find(provider).and_then(|url| js_string(url.as_str())).or_else(|| {
+        ENV_VAR_ANYWHERE.captures(provider).map(|cap| format!("${{{}}}", &cap[1]))
+    })
+}
+
+static IMPORT_PATH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^\s*import\s+[^"';]*["']([^"']+)["']"#).unwrap());
+
+/// Returns the remappings of the packages in `node_modules` the sources import
+fn node_modules_remappings(root: &Path, src: &Path) -> eyre::Result<Vec<String>> {
+    let mut remappings = BTreeSet::new();
+    for file in ethers::solc::utils::source_files(src) {
+        let content = std::fs::read_to_string(&file)?;
+        for cap in IMPORT_PATH.captures_iter(&content) {
+            let import = &cap[1];
+            if import.starts_with('.') {
+                continue
+            }
+            // scoped packages like `@openzeppelin/contracts` are remapped by their scope
+            let package = import.split('/').next().unwrap_or_default();
+            if !package.is_empty() && root.join("node_modules").join(package).is_dir() {
+                remappings.insert(format!("{package}/=node_modules/{package}/"));
+            }
+        }
+    }
+    Ok(remappings.into_iter().collect())
+}
+
+static CONTRACT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?m)^\s*contract\s+(\w+)"#).unwrap());
+
+static PRAGMA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^\s*pragma\s+solidity\s+[^;]+;"#).unwrap());
+
+static SOLIDITY_COMMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?s)/\*.*?\*/|//[^\n]*"#).unwrap());
+
+/// Removes the comments of the Solidity source, so commented out contracts are not found
+fn strip_solidity_comments(source: &str) -> String {
+    SOLIDITY_COMMENT.replace_all(source, "").into_owned()
+}
+
+/// Writes a test stub for every contract of the sources that has none, returns the stubs
+fn write_test_stubs(root: &Path, src: &str, test: &str) -> eyre::Result<Vec<PathBuf>> {
+    let test_dir = root.join(test);
+    // the imports are relative to the test dir
+    let up = "../".repeat(Path::new(test).components().count());
+    let mut stubs = Vec::new();
+    for file in ethers::solc::utils::source_files(root.join(src)) {
+        let content = std::fs::read_to_string(&file)?;
+        let content = strip_solidity_comments(&content);
+        let pragma = PRAGMA.find(&content).map_or("pragma solidity ^0.8.0;", |m| m.as_str().trim());
+        let source = foundry_config::slash_path(file.strip_prefix(root).unwrap_or(&file));
+        for cap in CONTRACT.captures_iter(&content) {
+            let name = &cap[1];
+            let stub = test_dir.join(format!("{name}.t.sol"));
+            if stub.exists() {
+                continue
+            }
+            std::fs::create_dir_all(&test_dir)?;
+            std::fs::write(&stub, test_stub(name, pragma, &format!("{up}{source}")))?;
+            stubs.push(stub.strip_prefix(root).unwrap_or(&stub).to_path_buf());
+        }
+    }
+    Ok(stubs)
+}
+
+fn test_stub(name: &str, pragma: &str, import: &str) -> String {
+    let mut chars = name.chars();
+    let instance = match chars.next() {
+        Some(first) => format!("{}{}", first.to_lowercase(), chars.as_str()),
+        None => String::new(),
+    };
+    format!(
+        r#"// SPDX-License-Identifier: UNLICENSED
+{pragma}
+
+import "forge-std/Test.sol";
+import "{import}";
+
+contract {name}Test is Test {{
+    {name} internal {instance};
+
+    function setUp() public {{
+        // {instance} = new {name}(...);
+    }}
+
+    function testTodo() public {{}}
+}}
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_migrate_hardhat_config() {
+        let config = r#"
+require("@nomiclabs/hardhat-waffle");
+// the networks
+module.exports = {
+  solidity: {
+    version: "0.8.10",
+    settings: { optimizer: { enabled: true, runs: 1000 }, evmVersion: "london" },
+  },
+  paths: { sources: "./src" },
+  networks: {
+    hardhat: { chainId: 1337 },
+    goerli: { url: process.env.GOERLI_URL || "", accounts: [process.env.PRIVATE_KEY] },
+    mainnet: { url: `https://eth-mainnet.alchemyapi.io/v2/${process.env.ALCHEMY_KEY}` },
+    local: { url: "http://127.0.0.1:8545" }, // a local node
+  },
+};
+"#;
+        let migration = Migration::parse(Toolchain::Hardhat, config);
+        assert_eq!(migration.src, "src");
+        assert_eq!(migration.solc_version.as_deref(), Some("0.8.10"));
+        assert_eq!(migration.optimizer, Some(true));
+        assert_eq!(migration.optimizer_runs, Some(1000));
+        assert_eq!(migration.evm_version.as_deref(), Some("london"));
+        assert_eq!(
+            migration.rpc_endpoints,
+            [
+                ("goerli".to_string(), "${GOERLI_URL}".to_string()),
+                (
+                    "mainnet".to_string(),
+                    "https://eth-mainnet.alchemyapi.io/v2/${ALCHEMY_KEY}".to_string()
+                ),
+                ("local".to_string(), "http://127.0.0.1:8545".to_string()),
+            ]
+        );
+        assert_eq!(migration.warnings.len(), 1);
+
+        let toml = migration.to_toml("hardhat.config.js");
+        assert!(toml.contains("src = \"src\"\n"));
+        assert!(toml.contains("optimizer_runs = 1000\n"));
+        assert!(toml.contains("[default.rpc_endpoints]\ngoerli = \"${GOERLI_URL}\"\n"));
+    }
+
+    #[test]
+    fn can_migrate_truffle_config() {
+        let config = r#"
+const HDWalletProvider = require("@truffle/hdwallet-provider");
+module.exports = {
+  networks: {
+    development: { host: "127.0.0.1", port: 7545, network_id: "*" },
+    ropsten: {
+      provider: () =>
+        new HDWalletProvider(mnemonic, "https://ropsten.infura.io/v3/" + process.env.INFURA_KEY),
+      network_id: 3,
+    },
+  },
+  compilers: {
+    solc: { version: "^0.8.0", settings: { optimizer: { enabled: false, runs: 200 } } },
+  },
+};
+"#;
+        let migration = Migration::parse(Toolchain::Truffle, config);
+        assert_eq!(migration.src, "contracts");
+        assert_eq!(migration.solc_version, None);
+        assert_eq!(migration.optimizer, Some(false));
+        assert_eq!(
+            migration.rpc_endpoints,
+            [
+                ("development".to_string(), "http://127.0.0.1:7545".to_string()),
+                ("ropsten".to_string(), "https://ropsten.infura.io/v3/${INFURA_KEY}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_find_contracts_outside_comments() {
+        let source = r#"
+// contract Commented {}
+/*
+contract Block {}
+*/
+contract Vault {}
+    contract Émission {}
+"#;
+        let source = strip_solidity_comments(source);
+        let names: Vec<_> = CONTRACT.captures_iter(&source).map(|cap| cap[1].to_string()).collect();
+        assert_eq!(names, vec!["Vault", "Émission"]);
+        assert!(test_stub("Émission", "pragma solidity ^0.8.0;", "../src/E.sol")
+            .contains("Émission internal émission;"));
+    }
+}
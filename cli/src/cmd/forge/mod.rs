@@ -37,20 +37,31 @@
 //! let config: Config = From::from(&args);
 //! ```
 
+pub mod analyze;
+pub mod bench;
 pub mod bind;
 pub mod build;
 pub mod cache;
 pub mod config;
+pub mod coverage;
 pub mod create;
+pub mod decode_trace;
+pub mod doc;
 pub mod flatten;
 pub mod fmt;
+pub mod gas_diff;
 pub mod init;
 pub mod inspect;
 pub mod install;
+pub mod migrate;
+pub mod mutate;
 pub mod remappings;
+pub mod reporter;
 pub mod run;
+pub mod selectors;
 pub mod snapshot;
 pub mod test;
 pub mod tree;
 pub mod verify;
+pub mod verify_build;
 pub mod watch;
@@ -0,0 +1,322 @@
+//! Mutate command
+use crate::{
+    cmd::{
+        forge::{build::CoreBuildArgs, test::Filter},
+        Cmd,
+    },
+    compile::{suppress_compile, ProjectCompiler},
+    utils,
+};
+use clap::Parser;
+use ethers::solc::ProjectCompileOutput;
+use eyre::WrapErr;
+use forge::{
+    coverage::SourceFile,
+    executor::opts::EvmOpts,
+    fuzz::{random_seed, seeded_runner},
+    matches_any_glob,
+    mutation::{mutants, Imports, Mutant},
+    MultiContractRunnerBuilder, TestFilter,
+};
+use foundry_common::evm::EvmArgs;
+use foundry_config::{figment::Figment, Config};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+use yansi::Paint;
+
+// Loads project's figment and merges the build cli arguments into it
+foundry_config::impl_figment_convert!(MutateArgs, opts, evm_opts);
+
+#[derive(Debug, Clone, Parser)]
+pub struct MutateArgs {
+    /// Only mutate the source files that match the glob, e.g. `src/Vault.sol`.
+    ///
+    /// By default all source files are mutated, except tests and libraries.
+    #[clap(long, value_name = "GLOB")]
+    mutate_path: Option<String>,
+
+    /// Exit with code 1 if the mutation score is below the percentage.
+    #[clap(long, value_name = "PERCENT")]
+    min_score: Option<f64>,
+
+    #[clap(flatten, next_help_heading = "TEST FILTERING")]
+    filter: Filter,
+
+    #[clap(flatten, next_help_heading = "EVM OPTIONS")]
+    evm_opts: EvmArgs,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+/// The outcome of the tests of a mutant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MutantStatus {
+    /// A test failed
+    Killed,
+    /// All tests passed
+    Survived,
+    /// The mutant does not compile
+    Invalid,
+}
+
+impl Cmd for MutateArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let figment: Figment = From::from(&self);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::from_provider(figment).sanitized();
+        let filter = self.filter.with_merged_config();
+
+        // Mutants of a previous run that was aborted are restored first
+        let backup_dir = config.cache_path.join("mutation-backup");
+        restore_backups(&backup_dir, &config.__root.0)?;
+
+        // Compile all sources without the cache, the AST of every source is needed to generate
+        // the mutants and to find the tests that import them
+        let project = config.ephemeral_no_artifacts_project()?;
+        let output = ProjectCompiler::default().compile(&project)?;
+        let root = project.paths.root.clone();
+
+        let (_, sources) =
+            output.clone().with_stripped_file_prefixes(&root).into_artifacts_with_sources();
+        let mut source_files = Vec::new();
+        for (path, versions) in sources.0 {
+            let content = std::fs::read_to_string(root.join(&path))
+                .wrap_err_with(|| format!("Failed to read source file {path}"))?;
+            if let Some(source) = versions.into_iter().next() {
+                if let Some(ast) = source.source_file.ast {
+                    let ast = serde_json::to_value(ast)?;
+                    source_files.push(SourceFile { id: source.source_file.id, path, content, ast });
+                }
+            }
+        }
+        let imports = Imports::new(&source_files, &root);
+
+        println!("Running the tests of the unmutated sources...");
+        if run_tests(&config, &evm_opts, &root, output, &filter)? > 0 {
+            eyre::bail!("The tests must pass before mutation testing, fix the failing tests first")
+        }
+
+        let is_mutated = |path: &str| {
+            let abs = root.join(path);
+            abs.starts_with(&project.paths.sources) &&
+                !abs.starts_with(&project.paths.tests) &&
+                !project.paths.libraries.iter().any(|lib| abs.starts_with(lib)) &&
+                self.mutate_path
+                    .as_ref()
+                    .map_or(true, |glob| matches_any_glob(&[glob.clone()], path))
+        };
+        let all_mutants: Vec<(&SourceFile, Mutant)> = source_files
+            .iter()
+            .filter(|source| is_mutated(&source.path))
+            .flat_map(|source| mutants(source).into_iter().map(move |mutant| (source, mutant)))
+            .collect();
+        println!("Testing {} mutants...", all_mutants.len());
+
+        // Mutants are compiled with the cache, so only the mutated file and its dependents are
+        // compiled again
+        let project = config.project()?;
+        let mut survived = Vec::new();
+        let (mut killed, mut invalid) = (0, 0);
+        for (source, mutant) in &all_mutants {
+            let dependents = imports.dependents(Path::new(&source.path));
+            let filter = MutantFilter { filter: &filter, dependents: &dependents };
+
+            let mutated = MutatedFile::new(
+                root.join(&source.path),
+                backup_dir.join(&source.path),
+                &source.content,
+                &mutant.apply(&source.content),
+            )?;
+            let status = match suppress_compile(&project) {
+                Ok(output) => match run_tests(&config, &evm_opts, &root, output, &filter)? {
+                    0 => MutantStatus::Survived,
+                    _ => MutantStatus::Killed,
+                },
+                Err(_) => MutantStatus::Invalid,
+            };
+            mutated.restore()?;
+
+            match status {
+                MutantStatus::Killed => {
+                    killed += 1;
+                    println!("{} {mutant}", Paint::green("[KILLED]"));
+                }
+                MutantStatus::Survived => {
+                    println!("{} {mutant}", Paint::red("[SURVIVED]"));
+                    survived.push(mutant);
+                }
+                MutantStatus::Invalid => {
+                    invalid += 1;
+                    println!("{} {mutant}", Paint::new("[INVALID]").dimmed());
+                }
+            }
+        }
+
+        let tested = killed + survived.len();
+        let score = if tested == 0 { 100. } else { killed as f64 * 100. / tested as f64 };
+        println!();
+        println!(
+            "Mutation score: {score:.2}% ({killed} killed, {} survived, {invalid} not compiling)",
+            survived.len()
+        );
+        if !survived.is_empty() {
+            println!();
+            println!("Surviving mutants, which no test detects:");
+            for mutant in &survived {
+                println!("  {mutant}");
+            }
+        }
+
+        if let Some(min_score) = self.min_score {
+            if score < min_score {
+                eprintln!(
+                    "{}",
+                    Paint::red(format!("The mutation score is below the minimum of {min_score}%"))
+                );
+                std::process::exit(1)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs the tests and returns the number of failed tests
+fn run_tests(
+    config: &Config,
+    evm_opts: &EvmOpts,
+    root: &Path,
+    output: ProjectCompileOutput,
+    filter: &impl TestFilter,
+) -> eyre::Result<usize> {
+    let cfg = proptest::test_runner::Config {
+        failure_persistence: None,
+        cases: config.fuzz_runs,
+        max_local_rejects: config.fuzz_max_local_rejects,
+        max_global_rejects: config.fuzz_max_global_rejects,
+        ..Default::default()
+    };
+    let fuzzer = seeded_runner(cfg, config.fuzz_seed.unwrap_or_else(random_seed));
+    let mut runner = MultiContractRunnerBuilder::default()
+        .fuzzer(fuzzer)
+        .initial_balance(evm_opts.initial_balance)
+        .evm_spec(utils::evm_spec(&config.evm_version))
+        .sender(evm_opts.sender)
+        .with_fork(utils::get_fork(evm_opts, &config.rpc_storage_caching))
+        .with_rpc_storage_caching(config.rpc_storage_caching.clone())
+        .with_fuzz_dictionary_weight(config.fuzz_dictionary_weight)
+        .with_invariant_config(config.invariant)
+        .with_constructor_args(config.constructor_args.resolve(root)?)
+        .build(root, output, evm_opts.clone())?;
+    let results = runner.test(filter, None, true)?;
+    Ok(results
+        .values()
+        .flat_map(|suite| suite.test_results.values())
+        .filter(|result| !result.success)
+        .count())
+}
+
+/// A source file that is replaced by a mutant until it is restored or dropped
+///
+/// The original content is also backed up to a file, so it can be restored by the next run if
+/// the process is killed.
+struct MutatedFile<'a> {
+    path: PathBuf,
+    backup: PathBuf,
+    original: &'a str,
+    restored: bool,
+}
+
+impl<'a> MutatedFile<'a> {
+    /// Backs up the `original` content of the file at `path` and writes the `mutated` content
+    fn new(path: PathBuf, backup: PathBuf, original: &'a str, mutated: &str) -> eyre::Result<Self> {
+        std::fs::create_dir_all(backup.parent().expect("file has a parent"))?;
+        std::fs::write(&backup, original)?;
+        let file = Self { path, backup, original, restored: false };
+        std::fs::write(&file.path, mutated)?;
+        Ok(file)
+    }
+
+    /// Writes the original content back and removes the backup
+    fn restore(mut self) -> eyre::Result<()> {
+        self.restored = true;
+        std::fs::write(&self.path, self.original)
+            .wrap_err_with(|| format!("Failed to restore {}", self.path.display()))?;
+        std::fs::remove_file(&self.backup)?;
+        Ok(())
+    }
+}
+
+impl<'a> Drop for MutatedFile<'a> {
+    fn drop(&mut self) {
+        // the tests failed to run, the backup is kept if the file can't be restored
+        if !self.restored && std::fs::write(&self.path, self.original).is_ok() {
+            let _ = std::fs::remove_file(&self.backup);
+        }
+    }
+}
+
+/// Restores the source files backed up before they were mutated
+fn restore_backups(backup_dir: &Path, root: &Path) -> eyre::Result<()> {
+    let backups: Vec<PathBuf> = WalkDir::new(backup_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .collect();
+    for backup in backups {
+        let relative = backup.strip_prefix(backup_dir)?;
+        let path = root.join(relative);
+        eprintln!(
+            "{} restoring {} from an aborted mutation run",
+            Paint::yellow("Warning:"),
+            path.display()
+        );
+        std::fs::copy(&backup, &path)
+            .wrap_err_with(|| format!("Failed to restore {}", path.display()))?;
+        std::fs::remove_file(&backup)?;
+    }
+    Ok(())
+}
+
+/// Only runs the tests of test files that import the mutated file, directly or indirectly
+struct MutantFilter<'a> {
+    filter: &'a Filter,
+    /// The paths of the mutated file and its dependents, relative to the root
+    dependents: &'a BTreeSet<PathBuf>,
+}
+
+impl<'a> MutantFilter<'a> {
+    fn is_dependent(&self, path: &str) -> bool {
+        self.dependents.iter().any(|dependent| Path::new(path).ends_with(dependent))
+    }
+}
+
+impl<'a> TestFilter for MutantFilter<'a> {
+    fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
+        self.filter.matches_test(test_name)
+    }
+
+    fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
+        self.filter.matches_contract(contract_name)
+    }
+
+    fn matches_path(&self, path: impl AsRef<str>) -> bool {
+        self.is_dependent(path.as_ref()) && self.filter.matches_path(path)
+    }
+
+    fn matches_qualified_test(
+        &self,
+        path: impl AsRef<str>,
+        contract_name: impl AsRef<str>,
+        test_name: impl AsRef<str>,
+    ) -> bool {
+        self.is_dependent(path.as_ref()) &&
+            self.filter.matches_qualified_test(path, contract_name, test_name)
+    }
+}
@@ -0,0 +1,203 @@
+//! Reporters for the results of `forge test`
+use clap::ArgEnum;
+use forge::{fuzz::CounterExample, SuiteResult, TestKind, TestResult};
+use std::{collections::BTreeMap, io::Write, time::Duration};
+
+/// The format test results are reported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum ReporterKind {
+    /// Human readable output, printed while the tests are running
+    Pretty,
+    /// All results as a single JSON object once all tests finished
+    Json,
+    /// A JUnit XML report once all tests finished, e.g. for GitLab or Jenkins
+    Junit,
+}
+
+impl Default for ReporterKind {
+    fn default() -> Self {
+        ReporterKind::Pretty
+    }
+}
+
+impl ReporterKind {
+    /// Returns `true` if results are printed while the tests are running
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, ReporterKind::Pretty)
+    }
+
+    /// Writes the report of all finished test suites
+    ///
+    /// Does nothing for the [`ReporterKind::Pretty`] reporter, which prints every suite as soon as
+    /// it finished.
+    pub fn report(
+        &self,
+        results: &BTreeMap<String, SuiteResult>,
+        mut out: impl Write,
+    ) -> eyre::Result<()> {
+        match self {
+            ReporterKind::Pretty => {}
+            ReporterKind::Json => writeln!(out, "{}", serde_json::to_string(results)?)?,
+            ReporterKind::Junit => write_junit(results, &mut out)?,
+        }
+        Ok(())
+    }
+}
+
+/// Writes the results as a JUnit XML document
+///
+/// Every test contract is a `<testsuite>`, its tests are `<testcase>`s. The gas usage, and the
+/// number of runs of fuzz and invariant tests, are recorded as `<properties>` of a test case.
+pub fn write_junit(
+    results: &BTreeMap<String, SuiteResult>,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let tests: usize = results.values().map(SuiteResult::len).sum();
+    let failures = results
+        .values()
+        .flat_map(|suite| suite.test_results.values())
+        .filter(|result| !result.success)
+        .count();
+    let duration = results.values().map(|suite| suite.duration).sum::<Duration>();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites name="forge" tests="{tests}" failures="{failures}" time="{:.3}">"#,
+        duration.as_secs_f64()
+    )?;
+    for (contract, suite) in results {
+        let failures = suite.test_results.values().filter(|result| !result.success).count();
+        writeln!(
+            out,
+            r#"  <testsuite name="{}" tests="{}" failures="{failures}" time="{:.3}">"#,
+            escape(contract),
+            suite.len(),
+            suite.duration.as_secs_f64()
+        )?;
+        for (name, result) in &suite.test_results {
+            write_testcase(contract, name, result, out)?;
+        }
+        writeln!(out, "  </testsuite>")?;
+    }
+    writeln!(out, "</testsuites>")
+}
+
+fn write_testcase(
+    contract: &str,
+    name: &str,
+    result: &TestResult,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+        escape(name),
+        escape(contract),
+        result.duration.as_secs_f64()
+    )?;
+
+    writeln!(out, "      <properties>")?;
+    let mut properties = vec![("gas", result.kind.gas_used().gas().to_string())];
+    match result.kind {
+        TestKind::Fuzz(ref cases) => properties.push(("runs", cases.cases().len().to_string())),
+        TestKind::Invariant { runs, calls, reverts, .. } => {
+            properties.push(("runs", runs.to_string()));
+            properties.push(("calls", calls.to_string()));
+            properties.push(("reverts", reverts.to_string()));
+        }
+        TestKind::Standard(_) => {}
+    }
+    for (name, value) in properties {
+        writeln!(out, r#"        <property name="{name}" value="{value}"/>"#)?;
+    }
+    writeln!(out, "      </properties>")?;
+
+    if !result.success {
+        let message = result.reason.as_deref().unwrap_or("test failed");
+        match result.counterexample {
+            Some(CounterExample::Single(ref counterexample)) => writeln!(
+                out,
+                r#"      <failure message="{}">Counterexample: {}</failure>"#,
+                escape(message),
+                escape(&counterexample.to_string())
+            )?,
+            Some(CounterExample::Sequence(ref sequence)) => {
+                let sequence: Vec<_> = sequence.iter().map(|call| call.to_string()).collect();
+                writeln!(
+                    out,
+                    r#"      <failure message="{}">Sequence:&#10;{}</failure>"#,
+                    escape(message),
+                    escape(&sequence.join("\n"))
+                )?
+            }
+            None => writeln!(out, r#"      <failure message="{}"/>"#, escape(message))?,
+        }
+    }
+    writeln!(out, "    </testcase>")
+}
+
+/// Escapes the characters that are not allowed in XML attributes and text
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_result(success: bool, reason: Option<&str>) -> TestResult {
+        TestResult {
+            success,
+            reason: reason.map(str::to_string),
+            counterexample: None,
+            logs: vec![],
+            kind: TestKind::Standard(21000),
+            traces: vec![],
+            labeled_addresses: BTreeMap::new(),
+            coverage: None,
+            duration: Duration::from_millis(1500),
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn can_write_junit_report() {
+        let suite = SuiteResult::new(
+            Duration::from_secs(2),
+            BTreeMap::from([
+                ("testPass()".to_string(), test_result(true, None)),
+                ("testFail()".to_string(), test_result(false, Some("a < b"))),
+            ]),
+            vec![],
+        );
+        let results = BTreeMap::from([("src/Test.t.sol:MyTest".to_string(), suite)]);
+
+        let mut out = Vec::new();
+        ReporterKind::Junit.report(&results, &mut out).unwrap();
+        let report = String::from_utf8(out).unwrap();
+
+        assert!(report
+            .contains(r#"<testsuites name="forge" tests="2" failures="1" time="2.000">"#));
+        assert!(report.contains(
+            r#"<testsuite name="src/Test.t.sol:MyTest" tests="2" failures="1" time="2.000">"#
+        ));
+        assert!(report.contains(
+            r#"<testcase name="testPass()" classname="src/Test.t.sol:MyTest" time="1.500">"#
+        ));
+        assert!(report.contains(r#"<property name="gas" value="21000"/>"#));
+        assert!(report.contains(r#"<failure message="a &lt; b"/>"#));
+        assert_eq!(report.matches("<failure").count(), 1);
+    }
+}
@@ -13,19 +13,24 @@ use ethers::{
     },
     types::{Address, Bytes, U256},
 };
+use eyre::WrapErr;
 use forge::{
     debug::DebugArena,
     decode::decode_console_logs,
     executor::{
-        builder::Backend, opts::EvmOpts, CallResult, DatabaseRef, DeployResult, EvmError, Executor,
-        ExecutorBuilder, RawCallResult,
+        builder::Backend, inspector::ExecutionContext, opts::EvmOpts, CallResult, DatabaseRef,
+        DeployResult, EvmError, Executor, ExecutorBuilder, RawCallResult, DEFAULT_CREATE2_DEPLOYER,
+        DEFAULT_CREATE2_DEPLOYER_CODE,
+    },
+    trace::{
+        identifier::LocalTraceIdentifier, label_addresses, CallTraceArena,
+        CallTraceDecoderBuilder, TraceKind,
     },
-    trace::{identifier::LocalTraceIdentifier, CallTraceArena, CallTraceDecoderBuilder, TraceKind},
     CALLER,
 };
 use foundry_common::evm::EvmArgs;
 use foundry_config::{figment::Figment, Config};
-use foundry_utils::{encode_args, format_token, IntoFunction, PostLinkInput};
+use foundry_utils::{decode_revert, encode_args, format_token, IntoFunction, PostLinkInput};
 use std::{collections::BTreeMap, path::PathBuf};
 use ui::{TUIExitReason, Tui, Ui};
 use yansi::Paint;
@@ -57,6 +62,20 @@ pub struct RunArgs {
     #[clap(long)]
     pub debug: bool,
 
+    /// Etch the CREATE2 factory and the libraries configured in `libraries` if they do not exist
+    /// on the chain, e.g. when running against a fork.
+    ///
+    /// These contracts must be deployed on the real chain before broadcasting.
+    #[clap(long)]
+    pub predeploy: bool,
+
+    /// Disable the cheatcodes that run commands, or access the file system, env vars or the
+    /// network, and forking, regardless of the config.
+    ///
+    /// Use this to run scripts of untrusted projects.
+    #[clap(long)]
+    pub sandbox: bool,
+
     #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
     pub opts: CoreBuildArgs,
 
@@ -68,9 +87,12 @@ impl Cmd for RunArgs {
     type Output = ();
     fn run(self) -> eyre::Result<Self::Output> {
         let figment: Figment = From::from(&self);
-        let evm_opts = figment.extract::<EvmOpts>()?;
+        let mut evm_opts = figment.extract::<EvmOpts>()?;
         let verbosity = evm_opts.verbosity;
         let config = Config::from_provider(figment).sanitized();
+        if self.sandbox {
+            evm_opts = evm_opts.sandboxed();
+        }
 
         let BuildOutput {
             project,
@@ -119,6 +141,10 @@ impl Cmd for RunArgs {
 
         let mut builder = ExecutorBuilder::new()
             .with_cheatcodes(evm_opts.ffi)
+            .with_fs_permissions(&config.__root.0, &evm_opts.fs_permissions)
+            .with_sandbox(evm_opts.sandbox)
+            .with_labeled_account_balance(evm_opts.labeled_account_balance)
+            .with_execution_context(ExecutionContext::Script)
             .with_config(env)
             .with_spec(crate::utils::evm_spec(&config.evm_version))
             .with_gas_limit(evm_opts.gas_limit());
@@ -150,9 +176,15 @@ impl Cmd for RunArgs {
             }
         };
 
+        let mut executor = builder.build(db);
+        if self.predeploy {
+            for warning in etch_predeploys(&mut executor, &config, &highlevel_known_contracts)? {
+                println!("{} {}", Paint::yellow("Warning:").bold(), warning);
+            }
+        }
+
         let mut result = {
-            let mut runner =
-                Runner::new(builder.build(db), evm_opts.initial_balance, evm_opts.sender);
+            let mut runner = Runner::new(executor, evm_opts.initial_balance, evm_opts.sender);
             let (address, mut result) =
                 runner.setup(&predeploy_libraries, bytecode, needs_setup)?;
 
@@ -255,6 +287,9 @@ impl Cmd for RunArgs {
                 println!("{}", Paint::green("Script ran successfully."));
             } else {
                 println!("{}", Paint::red("Script failed."));
+                if let Ok(reason) = decode_revert(&result.returned, None) {
+                    println!("Reason: {}", label_addresses(&reason, &result.labeled_addresses));
+                }
             }
 
             println!("Gas used: {}", result.gas);
@@ -282,7 +317,7 @@ impl Cmd for RunArgs {
             let console_logs = decode_console_logs(&result.logs);
             if !console_logs.is_empty() {
                 for log in console_logs {
-                    println!("  {log}");
+                    println!("  {}", label_addresses(&log, &result.labeled_addresses));
                 }
             }
         }
@@ -290,6 +325,56 @@ impl Cmd for RunArgs {
     }
 }
 
+/// Etches the CREATE2 factory and all libraries linked at fixed addresses via the `libraries`
+/// config, if there is no code at their address.
+///
+/// Returns a warning for every contract that must be deployed before broadcasting.
+fn etch_predeploys<DB: DatabaseRef>(
+    executor: &mut Executor<DB>,
+    config: &Config,
+    known_contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
+) -> eyre::Result<Vec<String>> {
+    let mut warnings = Vec::new();
+
+    if !executor.has_code(DEFAULT_CREATE2_DEPLOYER) {
+        executor.set_code(DEFAULT_CREATE2_DEPLOYER, DEFAULT_CREATE2_DEPLOYER_CODE.into());
+        warnings.push(format!(
+            "The CREATE2 factory does not exist at {:?}, it must be deployed before broadcasting.",
+            DEFAULT_CREATE2_DEPLOYER
+        ));
+    }
+
+    let libraries = config.parsed_libraries()?.with_applied_remappings(&config.project_paths());
+    for (file, libs) in &libraries.libs {
+        for (name, address) in libs {
+            let address: Address = address
+                .parse()
+                .wrap_err_with(|| format!("Invalid address `{address}` of library `{name}`"))?;
+            if executor.has_code(address) {
+                continue
+            }
+
+            let code = known_contracts
+                .iter()
+                .find(|(id, _)| id.name == *name && id.source.ends_with(file))
+                .and_then(|(_, contract)| contract.deployed_bytecode.clone().into_bytes());
+            match code {
+                Some(code) => {
+                    executor.set_code(address, code.0);
+                    warnings.push(format!(
+                        "Library `{name}` does not exist at {address:?}, it must be deployed before broadcasting."
+                    ));
+                }
+                None => warnings.push(format!(
+                    "Library `{name}` does not exist at {address:?} and was not found in the compiled contracts."
+                )),
+            }
+        }
+    }
+
+    Ok(warnings)
+}
+
 struct ExtraLinkingInfo<'a> {
     no_target_name: bool,
     target_fname: String,
@@ -0,0 +1,339 @@
+//! selectors command
+
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, Cmd},
+    compile, utils,
+};
+use clap::{ArgEnum, Parser, Subcommand};
+use ethers::{
+    abi::{Abi, ParamType},
+    prelude::artifacts::CompactContractBytecode,
+    solc::ArtifactId,
+    utils::keccak256,
+};
+use eyre::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use yansi::Paint;
+
+#[derive(Debug, Parser)]
+pub struct SelectorsArgs {
+    #[clap(subcommand)]
+    pub sub: SelectorsSubcommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SelectorsSubcommands {
+    #[clap(about = "Lists the selectors and event topics of the project's contracts.")]
+    List(ListArgs),
+    #[clap(about = "Checks two contracts for function selectors they share, e.g. diamond facets.")]
+    Collisions(CollisionsArgs),
+    #[clap(about = "Uploads the signatures of the project's contracts to a signature registry.")]
+    Upload(UploadArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct ListArgs {
+    #[clap(help = "The name of the contract, defaults to all contracts of the `src` directory.")]
+    contract: Option<String>,
+
+    #[clap(help = "Print the selectors as JSON.", long)]
+    json: bool,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    build: CoreBuildArgs,
+}
+
+#[derive(Debug, Parser)]
+pub struct CollisionsArgs {
+    #[clap(help = "The name of the first contract.")]
+    first: String,
+
+    #[clap(help = "The name of the second contract.")]
+    second: String,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    build: CoreBuildArgs,
+}
+
+#[derive(Debug, Parser)]
+pub struct UploadArgs {
+    #[clap(help = "The name of the contract, defaults to all contracts of the `src` directory.")]
+    contract: Option<String>,
+
+    #[clap(
+        help = "The registry to upload the signatures to.",
+        long,
+        arg_enum,
+        default_value = "openchain"
+    )]
+    registry: Registry,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    build: CoreBuildArgs,
+}
+
+/// A public database of signatures by their selector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum Registry {
+    Openchain,
+    #[clap(name = "4byte")]
+    Fourbyte,
+}
+
+impl Cmd for ListArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        let contracts = compile_abis(&self.build, self.contract.as_deref())?;
+        // contracts are keyed by `path:name`, contracts of different files may share a name
+        let selectors: BTreeMap<String, Selectors> = contracts
+            .iter()
+            .map(|(id, abi)| {
+                let name = format!("{}:{}", foundry_config::slash_path(&id.source), id.name);
+                (name, Selectors::new(abi))
+            })
+            .collect();
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&selectors)?);
+            return Ok(())
+        }
+        for (name, selectors) in selectors {
+            println!("{}", Paint::new(name).bold());
+            let kinds = [
+                ("Functions", &selectors.functions),
+                ("Errors", &selectors.errors),
+                ("Events", &selectors.events),
+            ];
+            for (kind, signatures) in kinds {
+                if signatures.is_empty() {
+                    continue
+                }
+                println!("  {kind}:");
+                for (signature, selector) in signatures {
+                    println!("    {selector}  {signature}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Cmd for CollisionsArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        let contracts = compile_abis(&self.build, None)?;
+        let find = |name: &str| {
+            contracts
+                .iter()
+                .find(|(id, _)| id.name == name)
+                .map(|(_, abi)| Selectors::new(abi))
+                .ok_or_else(|| {
+                    eyre::eyre!("Could not find artifact `{name}` in the compiled artifacts")
+                })
+        };
+        let collisions = find(&self.first)?.collisions(&find(&self.second)?);
+        if collisions.is_empty() {
+            println!("{} and {} share no function selectors", self.first, self.second);
+            return Ok(())
+        }
+        for collision in &collisions {
+            println!(
+                "{}  {}::{}  {}::{}",
+                Paint::red(&collision.selector),
+                self.first,
+                collision.first,
+                self.second,
+                collision.second
+            );
+        }
+        eyre::bail!(
+            "{} and {} share {} function selectors",
+            self.first,
+            self.second,
+            collisions.len()
+        )
+    }
+}
+
+impl Cmd for UploadArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        utils::ensure_online("Uploading the signatures")?;
+        let contracts = compile_abis(&self.build, self.contract.as_deref())?;
+        let imported = match self.registry {
+            Registry::Openchain => {
+                let mut functions = Vec::new();
+                let mut events = Vec::new();
+                for (_, abi) in &contracts {
+                    let selectors = Selectors::new(abi);
+                    functions.extend(selectors.functions.into_keys());
+                    functions.extend(selectors.errors.into_keys());
+                    events.extend(selectors.events.into_keys());
+                }
+                functions.sort();
+                functions.dedup();
+                events.sort();
+                events.dedup();
+                utils::block_on(foundry_utils::openchain_import(&functions, &events))?
+            }
+            Registry::Fourbyte => {
+                let mut imported = foundry_utils::ImportedSignatures::default();
+                for (_, abi) in &contracts {
+                    let contract = utils::block_on(foundry_utils::fourbyte_import(abi))?;
+                    imported.imported += contract.imported;
+                    imported.duplicated += contract.duplicated;
+                }
+                imported
+            }
+        };
+        println!(
+            "Uploaded the signatures of {} contracts: {} imported, {} already known",
+            contracts.len(),
+            imported.imported,
+            imported.duplicated
+        );
+        Ok(())
+    }
+}
+
+/// Compiles the project and returns the ABIs of the contract with the name, or of all contracts
+/// of the `src` directory
+///
+/// The sources of the artifact ids are relative to the project root.
+fn compile_abis(build: &CoreBuildArgs, contract: Option<&str>) -> Result<Vec<(ArtifactId, Abi)>> {
+    let project = build.project()?;
+    let output = compile::suppress_compile(&project)?;
+    let contracts: Vec<(ArtifactId, Abi)> = output
+        .into_artifacts()
+        .filter(|(id, _)| match contract {
+            Some(contract) => id.name == contract,
+            None => id.source.starts_with(&project.paths.sources),
+        })
+        .filter_map(|(mut id, artifact)| {
+            let CompactContractBytecode { abi, .. } = artifact.into();
+            if let Ok(source) = id.source.strip_prefix(&project.paths.root) {
+                id.source = source.to_path_buf();
+            }
+            Some((id, abi?))
+        })
+        .collect();
+    if let (Some(contract), true) = (contract, contracts.is_empty()) {
+        eyre::bail!("Could not find artifact `{contract}` in the compiled artifacts")
+    }
+    Ok(contracts)
+}
+
+/// The selectors of the functions and errors and the topics of the events of an ABI, by their
+/// signature
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+struct Selectors {
+    functions: BTreeMap<String, String>,
+    errors: BTreeMap<String, String>,
+    events: BTreeMap<String, String>,
+}
+
+impl Selectors {
+    fn new(abi: &Abi) -> Self {
+        let selector = |signature: &str| format!("0x{}", hex::encode(ethers::utils::id(signature)));
+        let topic = |signature: &str| format!("0x{}", hex::encode(keccak256(signature)));
+        let mut selectors = Selectors::default();
+        for function in abi.functions() {
+            let signature = signature(&function.name, function.inputs.iter().map(|p| &p.kind));
+            selectors.functions.insert(signature.clone(), selector(&signature));
+        }
+        for error in abi.errors() {
+            let signature = signature(&error.name, error.inputs.iter().map(|p| &p.kind));
+            selectors.errors.insert(signature.clone(), selector(&signature));
+        }
+        for event in abi.events() {
+            let signature = signature(&event.name, event.inputs.iter().map(|p| &p.kind));
+            selectors.events.insert(signature.clone(), topic(&signature));
+        }
+        selectors
+    }
+
+    /// Returns the function selectors both contracts have, a diamond can't route a selector to
+    /// two facets even if the functions are the same
+    fn collisions(&self, other: &Selectors) -> Vec<Collision> {
+        let mut collisions = Vec::new();
+        for (first, selector) in &self.functions {
+            for (second, other_selector) in &other.functions {
+                if selector == other_selector {
+                    collisions.push(Collision {
+                        selector: selector.clone(),
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+        collisions.sort_by(|a, b| a.selector.cmp(&b.selector));
+        collisions
+    }
+}
+
+/// A function selector of two contracts
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Collision {
+    selector: String,
+    first: String,
+    second: String,
+}
+
+/// Returns the canonical signature, e.g. `transfer(address,uint256)`
+fn signature<'a>(name: &str, inputs: impl Iterator<Item = &'a ParamType>) -> String {
+    let inputs: Vec<String> = inputs.map(|kind| kind.to_string()).collect();
+    format!("{name}({})", inputs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::parse_abi;
+
+    #[test]
+    fn can_collect_selectors() {
+        let abi = parse_abi(&[
+            "function transfer(address to, uint256 amount) external returns (bool)",
+            "event Transfer(address indexed from, address indexed to, uint256 amount)",
+        ])
+        .unwrap();
+        let selectors = Selectors::new(&abi);
+        assert_eq!(selectors.functions["transfer(address,uint256)"], "0xa9059cbb");
+        assert_eq!(
+            selectors.events["Transfer(address,address,uint256)"],
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+
+    #[test]
+    fn can_find_collisions() {
+        let facet = parse_abi(&["function burn(uint256)", "function owner() returns (address)"])
+            .unwrap();
+        let other = parse_abi(&[
+            "function collate_propagate_storage(bytes16)",
+            "function owner() returns (address)",
+            "function mint(uint256)",
+        ])
+        .unwrap();
+        let collisions = Selectors::new(&facet).collisions(&Selectors::new(&other));
+        assert_eq!(
+            collisions,
+            [
+                Collision {
+                    selector: "0x42966c68".to_string(),
+                    first: "burn(uint256)".to_string(),
+                    second: "collate_propagate_storage(bytes16)".to_string(),
+                },
+                Collision {
+                    selector: "0x8da5cb5b".to_string(),
+                    first: "owner()".to_string(),
+                    second: "owner()".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -7,7 +7,7 @@ use crate::cmd::{
     },
     Cmd,
 };
-use clap::{Parser, ValueHint};
+use clap::{ArgGroup, Parser, ValueHint};
 use eyre::Context;
 use forge::TestKindGas;
 use once_cell::sync::Lazy;
@@ -31,6 +31,7 @@ pub static RE_BASIC_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
 });
 
 #[derive(Debug, Clone, Parser)]
+#[clap(group = ArgGroup::new("snapshot-compare").args(&["diff", "check"]))]
 pub struct SnapshotArgs {
     /// All test arguments are supported
     #[clap(flatten, next_help_heading = "TEST OPTIONS")]
@@ -64,6 +65,14 @@ pub struct SnapshotArgs {
     )]
     check: Option<Option<PathBuf>>,
 
+    /// The gas change of a single test in percent that is tolerated by --diff and --check.
+    ///
+    /// With --check, tests may use up to this much more or less gas than in the snapshot. With
+    /// --diff, the command exits with code 1 if a test uses more than this much more gas than in
+    /// the snapshot.
+    #[clap(long, value_name = "PERCENT", requires = "snapshot-compare")]
+    tolerance: Option<f64>,
+
     // Hidden because there is only one option
     #[clap(help = "How to format the output.", long, hide(true))]
     format: Option<Format>,
@@ -110,11 +119,13 @@ impl Cmd for SnapshotArgs {
         if let Some(path) = self.diff {
             let snap = path.as_ref().unwrap_or(&self.snap);
             let snaps = read_snapshot(snap)?;
-            diff(tests, snaps)?;
+            if !diff(tests, snaps, self.tolerance)? {
+                std::process::exit(1)
+            }
         } else if let Some(path) = self.check {
             let snap = path.as_ref().unwrap_or(&self.snap);
             let snaps = read_snapshot(snap)?;
-            if check(tests, snaps) {
+            if check(tests, snaps, self.tolerance.unwrap_or_default()) {
                 std::process::exit(0)
             } else {
                 std::process::exit(1)
@@ -174,6 +185,8 @@ impl SnapshotConfig {
     fn apply(&self, outcome: TestOutcome) -> Vec<Test> {
         let mut tests = outcome
             .into_tests()
+            // invariant tests do not have a gas value that could be snapshotted
+            .filter(|test| !test.result.is_invariant())
             .filter(|test| self.is_in_gas_range(test.gas_used()))
             .collect::<Vec<_>>();
 
@@ -288,14 +301,19 @@ impl SnapshotDiff {
 
     /// Determines the percentage change
     fn gas_diff(&self) -> f64 {
-        self.gas_change() as f64 / self.target_gas_used.gas() as f64
+        pct_change(self.gas_change(), self.target_gas_used.gas())
+    }
+
+    /// Returns true if the source used more than `tolerance` percent more gas than the target
+    fn exceeds_tolerance(&self, tolerance: f64) -> bool {
+        self.gas_change() > 0 && self.gas_diff() > tolerance
     }
 }
 
 /// Compares the set of tests with an existing snapshot
 ///
-/// Returns true all tests match
-fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
+/// Returns true if the gas usage of all tests differs by at most `tolerance` percent
+fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>, tolerance: f64) -> bool {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
@@ -305,14 +323,19 @@ fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
         if let Some(target_gas) =
             snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
         {
-            let source_gas = test.result.kind.gas_used();
-            if source_gas.gas() != target_gas.gas() {
+            let diff = SnapshotDiff {
+                signature: test.signature.clone(),
+                source_gas_used: test.result.kind.gas_used(),
+                target_gas_used: target_gas,
+            };
+            if diff.gas_change() != 0 && diff.gas_diff().abs() > tolerance {
                 eprintln!(
-                    "Diff in \"{}::{}\": consumed \"{}\" gas, expected \"{}\" gas ",
+                    "Diff in \"{}::{}\": consumed \"{}\" gas, expected \"{}\" gas ({})",
                     test.contract_name(),
                     test.signature,
-                    source_gas,
-                    target_gas
+                    diff.source_gas_used,
+                    diff.target_gas_used,
+                    fmt_pct_change(diff.gas_diff())
                 );
                 has_diff = true;
             }
@@ -329,7 +352,10 @@ fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
 }
 
 /// Compare the set of tests with an existing snapshot
-fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
+///
+/// Returns false if a tolerance is given and the gas usage of a test increased by more than
+/// `tolerance` percent
+fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>, tolerance: Option<f64>) -> eyre::Result<bool> {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
@@ -353,7 +379,8 @@ fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
         });
     }
     let mut overall_gas_change = 0i128;
-    let mut overall_gas_diff = 0f64;
+    let mut overall_target_gas = 0u64;
+    let mut regressions = 0;
 
     diffs.sort_by(|a, b| {
         a.gas_diff().abs().partial_cmp(&b.gas_diff().abs()).unwrap_or(Ordering::Equal)
@@ -362,22 +389,39 @@ fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
     for diff in diffs {
         let gas_change = diff.gas_change();
         overall_gas_change += gas_change;
-        let gas_diff = diff.gas_diff();
-        overall_gas_diff += gas_diff;
+        overall_target_gas += diff.target_gas_used.gas();
+        let exceeds_tolerance =
+            tolerance.map(|tolerance| diff.exceeds_tolerance(tolerance)).unwrap_or_default();
+        regressions += exceeds_tolerance as usize;
         println!(
-            "{} (gas: {} ({})) ",
+            "{} (gas: {} ({})){}",
             diff.signature,
             fmt_change(gas_change),
-            fmt_pct_change(gas_diff)
+            fmt_pct_change(diff.gas_diff()),
+            if exceeds_tolerance { Paint::red(" exceeds tolerance").to_string() } else { "".into() }
         );
     }
 
     println!(
         "Overall gas change: {} ({})",
         fmt_change(overall_gas_change),
-        fmt_pct_change(overall_gas_diff)
+        fmt_pct_change(pct_change(overall_gas_change, overall_target_gas))
     );
-    Ok(())
+    match tolerance {
+        Some(tolerance) if regressions > 0 => {
+            eprintln!("{regressions} tests exceed the gas tolerance of {tolerance}%");
+            Ok(false)
+        }
+        _ => Ok(true),
+    }
+}
+
+/// Returns `change` in percent of `target`, or 0 if there is no target gas, e.g. no tests
+fn pct_change(change: i128, target: u64) -> f64 {
+    if target == 0 {
+        return 0.
+    }
+    change as f64 / target as f64 * 100.
 }
 
 fn fmt_pct_change(change: f64) -> String {
@@ -418,6 +462,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_detect_gas_regressions() {
+        let diff = |source, target| SnapshotDiff {
+            signature: "deposit()".to_string(),
+            source_gas_used: TestKindGas::Standard(source),
+            target_gas_used: TestKindGas::Standard(target),
+        };
+
+        assert_eq!(diff(1100, 1000).gas_diff(), 10.);
+        assert!(diff(1100, 1000).exceeds_tolerance(5.));
+        assert!(!diff(1100, 1000).exceeds_tolerance(10.));
+        assert!(!diff(500, 1000).exceeds_tolerance(0.));
+        assert_eq!(diff(100, 0).gas_diff(), 0.);
+    }
+
+    #[test]
+    fn can_diff_empty_snapshot() {
+        assert_eq!(pct_change(0, 0), 0.);
+        assert!(diff(vec![], vec![], Some(1.)).unwrap());
+    }
+
     #[test]
     fn can_parse_fuzz_snapshot_entry() {
         let s = "Test:deposit() (runs: 256, μ: 100, ~:200)";
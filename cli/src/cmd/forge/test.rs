@@ -1,32 +1,50 @@
 //! Test command
 use crate::{
     cmd::{
-        forge::{build::CoreBuildArgs, run::RunArgs, watch::WatchArgs},
+        forge::{
+            build::CoreBuildArgs, reporter::ReporterKind, run::RunArgs, watch::WatchArgs,
+        },
         Cmd,
     },
     compile::ProjectCompiler,
     suggestions, utils,
     utils::FoundryPathExt,
 };
-use clap::{AppSettings, Parser};
-use ethers::solc::FileFilter;
+use clap::{AppSettings, Parser, ValueHint};
+use ethers::{
+    abi::{AbiParser, Address},
+    solc::{
+        artifacts::{CompactContractBytecode, ContractBytecode},
+        FileFilter, ProjectCompileOutput,
+    },
+    utils::keccak256,
+};
+use eyre::{eyre, WrapErr};
 use forge::{
     decode::decode_console_logs,
     executor::opts::EvmOpts,
+    fuzz::{random_seed, seeded_runner, CounterExample, FuzzInputStats},
     gas_report::GasReport,
+    result_cache::{
+        FuzzFailure, TestFailures, TestResultsCache, FUZZ_FAILURES_DIR,
+        TEST_FAILURES_CACHE_FILENAME, TEST_RESULTS_CACHE_FILENAME,
+    },
+    stack_trace::StackTraceDecoder,
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier},
-        CallTraceDecoderBuilder, TraceKind,
+        label_addresses, CallTraceDecoderBuilder, TraceKind, TraceOptions,
     },
     MultiContractRunner, MultiContractRunnerBuilder, SuiteResult, TestFilter, TestKind,
 };
 use foundry_common::evm::EvmArgs;
-use foundry_config::{figment::Figment, Config};
+use foundry_config::{figment::Figment, slash_path, Config, PROFILE_TARGET};
 use regex::Regex;
 use std::{
     collections::BTreeMap,
     fmt,
+    io::BufRead,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::mpsc::channel,
     thread,
     time::Duration,
@@ -43,59 +61,163 @@ pub struct Filter {
     pub pattern: Option<regex::Regex>,
 
     /// Only run test functions matching the specified regex pattern.
+    ///
+    /// Can be passed multiple times, a test must match all patterns.
     #[clap(long = "match-test", alias = "mt", conflicts_with = "pattern")]
-    pub test_pattern: Option<regex::Regex>,
+    pub test_pattern: Vec<regex::Regex>,
 
     /// Only run test functions that do not match the specified regex pattern.
+    ///
+    /// Can be passed multiple times, a test must match none of the patterns.
     #[clap(long = "no-match-test", alias = "nmt", conflicts_with = "pattern")]
-    pub test_pattern_inverse: Option<regex::Regex>,
+    pub test_pattern_inverse: Vec<regex::Regex>,
 
     /// Only run tests in contracts matching the specified regex pattern.
+    ///
+    /// Can be passed multiple times, a contract must match all patterns.
     #[clap(long = "match-contract", alias = "mc", conflicts_with = "pattern")]
-    pub contract_pattern: Option<regex::Regex>,
+    pub contract_pattern: Vec<regex::Regex>,
 
     /// Only run tests in contracts that do not match the specified regex pattern.
+    ///
+    /// Can be passed multiple times, a contract must match none of the patterns.
     #[clap(long = "no-match-contract", alias = "nmc", conflicts_with = "pattern")]
-    pub contract_pattern_inverse: Option<regex::Regex>,
+    pub contract_pattern_inverse: Vec<regex::Regex>,
 
-    /// Only run tests in source files matching the specified glob pattern.
+    /// Only run tests in source files matching the specified glob pattern, e.g.
+    /// `test/integration/**`.
+    ///
+    /// Can be passed multiple times, a source file must match all patterns.
     #[clap(long = "match-path", alias = "mp", conflicts_with = "pattern")]
-    pub path_pattern: Option<globset::Glob>,
+    pub path_pattern: Vec<globset::Glob>,
 
     /// Only run tests in source files that do not match the specified glob pattern.
+    ///
+    /// Can be passed multiple times, a source file must match none of the patterns.
     #[clap(
         name = "no-match-path",
         long = "no-match-path",
         alias = "nmp",
         conflicts_with = "pattern"
     )]
-    pub path_pattern_inverse: Option<globset::Glob>,
+    pub path_pattern_inverse: Vec<globset::Glob>,
+
+    /// Only run the test with the given id, can be passed multiple times.
+    ///
+    /// The id takes the form `<source file>:<contract>:<test>`, the source file is optional and
+    /// matched against the end of the test's source path, for example
+    /// `Contract.t.sol:ContractTest:testFoo`.
+    #[clap(long, value_name = "TEST_ID", conflicts_with = "pattern")]
+    pub exact: Vec<TestId>,
+
+    /// Read additional test ids from stdin, one per line. See --exact
+    #[clap(long = "exact-stdin", conflicts_with = "pattern")]
+    pub exact_stdin: bool,
 }
 
 impl Filter {
     pub fn with_merged_config(&self) -> Self {
         let config = Config::load();
         let mut filter = self.clone();
-        if filter.test_pattern.is_none() {
-            filter.test_pattern = config.test_pattern.map(|p| p.into());
+        if filter.test_pattern.is_empty() {
+            filter.test_pattern.extend(config.test_pattern.map(|p| p.into()));
         }
-        if filter.test_pattern_inverse.is_none() {
-            filter.test_pattern_inverse = config.test_pattern_inverse.map(|p| p.into());
+        if filter.test_pattern_inverse.is_empty() {
+            filter.test_pattern_inverse.extend(config.test_pattern_inverse.map(|p| p.into()));
         }
-        if filter.contract_pattern.is_none() {
-            filter.contract_pattern = config.contract_pattern.map(|p| p.into());
+        if filter.contract_pattern.is_empty() {
+            filter.contract_pattern.extend(config.contract_pattern.map(|p| p.into()));
         }
-        if filter.contract_pattern_inverse.is_none() {
-            filter.contract_pattern_inverse = config.contract_pattern_inverse.map(|p| p.into());
+        if filter.contract_pattern_inverse.is_empty() {
+            filter
+                .contract_pattern_inverse
+                .extend(config.contract_pattern_inverse.map(|p| p.into()));
         }
-        if filter.path_pattern.is_none() {
-            filter.path_pattern = config.path_pattern;
+        if filter.path_pattern.is_empty() {
+            filter.path_pattern.extend(config.path_pattern);
         }
-        if filter.path_pattern_inverse.is_none() {
-            filter.path_pattern_inverse = config.path_pattern_inverse;
+        if filter.path_pattern_inverse.is_empty() {
+            filter.path_pattern_inverse.extend(config.path_pattern_inverse);
         }
         filter
     }
+
+    /// Appends all test ids read from stdin to the `--exact` ids, if `--exact-stdin` is set
+    pub fn with_stdin_ids(mut self) -> eyre::Result<Self> {
+        if self.exact_stdin {
+            for line in std::io::stdin().lock().lines() {
+                let line = line?;
+                let line = line.trim();
+                if !line.is_empty() {
+                    self.exact.push(line.parse()?);
+                }
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Identifies a single test function in the form `<source file>:<contract>:<test>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestId {
+    /// The (trailing part of the) source file the contract is declared in
+    pub path: Option<String>,
+    /// The name of the test contract
+    pub contract: String,
+    /// The name of the test function
+    pub test: String,
+}
+
+impl TestId {
+    /// Returns true if this id matches the test function `test_name` of `contract_name` in `path`
+    ///
+    /// The test name may either be the function's name or its full signature
+    pub fn matches(&self, path: &str, contract_name: &str, test_name: &str) -> bool {
+        if self.contract != contract_name {
+            return false
+        }
+        let name = test_name.split('(').next().unwrap_or(test_name);
+        if self.test != name && self.test != test_name {
+            return false
+        }
+        self.matches_path(path)
+    }
+
+    /// Returns true if this id's source file matches the end of the given `path`
+    pub fn matches_path(&self, path: impl AsRef<Path>) -> bool {
+        self.path.as_ref().map(|p| path.as_ref().ends_with(p)).unwrap_or(true)
+    }
+}
+
+impl FromStr for TestId {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(3, ':');
+        let test = parts.next().unwrap_or_default().trim();
+        let contract = parts.next().unwrap_or_default().trim();
+        let path = parts.next().map(str::trim).filter(|p| !p.is_empty());
+        if test.is_empty() || contract.is_empty() {
+            eyre::bail!(
+                "Invalid test id `{}`, expected `<source file>:<contract>:<test>` or `<contract>:<test>`",
+                s
+            )
+        }
+        Ok(Self {
+            path: path.map(str::to_string),
+            contract: contract.to_string(),
+            test: test.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for TestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref path) = self.path {
+            write!(f, "{}:", path)?;
+        }
+        write!(f, "{}:{}", self.contract, self.test)
+    }
 }
 
 impl FileFilter for Filter {
@@ -104,13 +226,11 @@ impl FileFilter for Filter {
     /// If no file regex is set this returns true if the file ends with `.t.sol`, see
     /// [FoundryPathExr::is_sol_test()]
     fn is_match(&self, file: &Path) -> bool {
-        if let Some(file) = file.as_os_str().to_str() {
-            if let Some(ref glob) = self.path_pattern {
-                return glob.compile_matcher().is_match(file)
-            }
-            if let Some(ref glob) = self.path_pattern_inverse {
-                return !glob.compile_matcher().is_match(file)
-            }
+        if !self.exact.is_empty() && self.exact.iter().all(|id| id.path.is_some()) {
+            return self.exact.iter().any(|id| id.matches_path(file))
+        }
+        if !self.path_pattern.is_empty() || !self.path_pattern_inverse.is_empty() {
+            return self.matches_path(file.to_string_lossy())
         }
         file.is_sol_test()
     }
@@ -118,43 +238,40 @@ impl FileFilter for Filter {
 
 impl TestFilter for Filter {
     fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
-        let mut ok = true;
         let test_name = test_name.as_ref();
         // Handle the deprecated option match
-        if let Some(re) = &self.pattern {
-            ok &= re.is_match(test_name);
-        }
-        if let Some(re) = &self.test_pattern {
-            ok &= re.is_match(test_name);
-        }
-        if let Some(re) = &self.test_pattern_inverse {
-            ok &= !re.is_match(test_name);
-        }
-        ok
+        self.pattern.iter().chain(&self.test_pattern).all(|re| re.is_match(test_name)) &&
+            !self.test_pattern_inverse.iter().any(|re| re.is_match(test_name))
     }
 
     fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
-        let mut ok = true;
         let contract_name = contract_name.as_ref();
-        if let Some(re) = &self.contract_pattern {
-            ok &= re.is_match(contract_name);
-        }
-        if let Some(re) = &self.contract_pattern_inverse {
-            ok &= !re.is_match(contract_name);
-        }
-        ok
+        self.contract_pattern.iter().all(|re| re.is_match(contract_name)) &&
+            !self.contract_pattern_inverse.iter().any(|re| re.is_match(contract_name))
     }
 
     fn matches_path(&self, path: impl AsRef<str>) -> bool {
-        let mut ok = true;
-        let path = path.as_ref();
-        if let Some(ref glob) = self.path_pattern {
-            ok &= glob.compile_matcher().is_match(path);
-        }
-        if let Some(ref glob) = self.path_pattern_inverse {
-            ok &= !glob.compile_matcher().is_match(path);
+        let path = slash_path(path.as_ref());
+        self.path_pattern.iter().all(|glob| glob.compile_matcher().is_match(&path)) &&
+            !self.path_pattern_inverse.iter().any(|glob| glob.compile_matcher().is_match(&path))
+    }
+
+    fn matches_qualified_test(
+        &self,
+        path: impl AsRef<str>,
+        contract_name: impl AsRef<str>,
+        test_name: impl AsRef<str>,
+    ) -> bool {
+        let (path, contract_name, test_name) =
+            (path.as_ref(), contract_name.as_ref(), test_name.as_ref());
+        if !self.exact.is_empty() &&
+            !self.exact.iter().any(|id| id.matches(path, contract_name, test_name))
+        {
+            return false
         }
-        ok
+        self.matches_path(path) &&
+            self.matches_contract(contract_name) &&
+            self.matches_test(test_name)
     }
 }
 
@@ -164,24 +281,27 @@ impl fmt::Display for Filter {
         if let Some(ref p) = self.pattern {
             patterns.push(format!("\tmatch: `{}`", p.as_str()));
         }
-        if let Some(ref p) = self.test_pattern {
+        for p in &self.test_pattern {
             patterns.push(format!("\tmatch-test: `{}`", p.as_str()));
         }
-        if let Some(ref p) = self.test_pattern_inverse {
+        for p in &self.test_pattern_inverse {
             patterns.push(format!("\tno-match-test: `{}`", p.as_str()));
         }
-        if let Some(ref p) = self.contract_pattern {
+        for p in &self.contract_pattern {
             patterns.push(format!("\tmatch-contract: `{}`", p.as_str()));
         }
-        if let Some(ref p) = self.contract_pattern_inverse {
+        for p in &self.contract_pattern_inverse {
             patterns.push(format!("\tno-match-contract: `{}`", p.as_str()));
         }
-        if let Some(ref p) = self.path_pattern {
+        for p in &self.path_pattern {
             patterns.push(format!("\tmatch-path: `{}`", p.glob()));
         }
-        if let Some(ref p) = self.path_pattern_inverse {
+        for p in &self.path_pattern_inverse {
             patterns.push(format!("\tno-match-path: `{}`", p.glob()));
         }
+        for id in &self.exact {
+            patterns.push(format!("\texact: `{}`", id));
+        }
         write!(f, "{}", patterns.join("\n"))
     }
 }
@@ -217,14 +337,92 @@ pub struct TestArgs {
     #[clap(long, env = "FORGE_GAS_REPORT")]
     gas_report: bool,
 
+    /// Write the gas report as JSON to a file, implies --gas-report.
+    #[clap(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    gas_report_json: Option<PathBuf>,
+
     /// Exit with code 0 even if a test fails.
     #[clap(long, env = "FORGE_ALLOW_FAILURE")]
     allow_failure: bool,
 
+    /// Only run the tests that failed in the previous run.
+    ///
+    /// Failed fuzz tests are replayed with their recorded counterexample instead of being fuzzed
+    /// again.
+    #[clap(long)]
+    replay_failures: bool,
+
+    /// Replay a fuzz test with the minimal failing input stored in the given file, instead of
+    /// fuzzing it.
+    ///
+    /// These files are written to `cache/fuzz/failures` whenever a fuzz test fails.
+    #[clap(long, value_name = "PATH")]
+    fuzz_input: Option<PathBuf>,
+
+    /// The seed the fuzzer generates inputs from.
+    ///
+    /// Every failed fuzz and invariant test prints the seed of its run, passing it here generates
+    /// the same inputs again.
+    #[clap(long, value_name = "SEED")]
+    fuzz_seed: Option<u64>,
+
+    /// Disable the cheatcodes that run commands, or access the file system, env vars or the
+    /// network, and forking, regardless of the config.
+    ///
+    /// Use this to run the tests of untrusted projects.
+    #[clap(long)]
+    sandbox: bool,
+
+    /// The number of threads test contracts are run on.
+    ///
+    /// Defaults to the number of logical CPUs.
+    #[clap(long, env = "FORGE_JOBS", value_name = "JOBS")]
+    jobs: Option<usize>,
+
+    /// The maximum wall-clock time of a single test in seconds.
+    ///
+    /// Fuzz and invariant tests stop running cases once it is exceeded and fail, other tests fail
+    /// if they took longer.
+    #[clap(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// List the tests that took at least this many milliseconds after the run.
+    #[clap(long, help_heading = "DISPLAY OPTIONS", value_name = "MS")]
+    slow_threshold: Option<u64>,
+
     /// Output test results in JSON format.
-    #[clap(long, short, help_heading = "DISPLAY OPTIONS")]
+    ///
+    /// Shorthand for `--reporter json`.
+    #[clap(long, short, help_heading = "DISPLAY OPTIONS", conflicts_with = "reporter")]
     json: bool,
 
+    /// The format test results are reported in.
+    ///
+    /// `json` and `junit` print a single report once all tests finished.
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "pretty",
+        value_name = "REPORTER",
+        help_heading = "DISPLAY OPTIONS"
+    )]
+    reporter: ReporterKind,
+
+    /// Print the distribution of the generated inputs of every fuzz test, and how many runs were
+    /// rejected by `assume` or hit its assertions.
+    #[clap(long, help_heading = "DISPLAY OPTIONS")]
+    fuzz_stats: bool,
+
+    /// Show the gas every call of a trace used itself and the gas it refunded.
+    ///
+    /// This is always enabled at verbosity level 5.
+    #[clap(long, help_heading = "DISPLAY OPTIONS")]
+    gas: bool,
+
+    /// Show static calls in traces on a single line, without their subcalls and logs.
+    #[clap(long, help_heading = "DISPLAY OPTIONS")]
+    collapse_static_calls: bool,
+
     #[clap(flatten, next_help_heading = "EVM OPTIONS")]
     evm_opts: EvmArgs,
 
@@ -257,17 +455,49 @@ impl TestArgs {
         self.filter.with_merged_config()
     }
 
+    /// Returns the reporter the test results are printed with
+    pub fn reporter(&self) -> ReporterKind {
+        if self.json {
+            ReporterKind::Json
+        } else {
+            self.reporter
+        }
+    }
+
     /// Returns the currently configured [Config] and the extracted [EvmOpts] from that config
     pub fn config_and_evm_opts(&self) -> eyre::Result<(Config, EvmOpts)> {
         // merge all configs
         let figment: Figment = self.into();
-        let evm_opts = figment.extract()?;
+        let mut evm_opts: EvmOpts = figment.extract()?;
         let mut config = Config::from_provider(figment).sanitized();
 
+        // `--fork-url` may be the alias of an endpoint in `[rpc_endpoints]`
+        if let Some(alias) = evm_opts.fork_url.clone() {
+            if config.rpc_endpoints.get(&alias).is_some() {
+                let url = config.rpc_endpoints.resolved().get(&alias).map_err(|err| eyre!(err))?;
+                evm_opts.fork_url = Some(url);
+            }
+        }
+
         // merging etherscan api key into Config
         if let Some(etherscan_api_key) = &self.etherscan_api_key {
             config.etherscan_api_key = Some(etherscan_api_key.to_string());
         }
+
+        if let Some(timeout) = self.timeout {
+            config.test_timeout = Some(timeout);
+        }
+        if let Some(seed) = self.fuzz_seed {
+            config.fuzz_seed = Some(seed);
+        }
+        if let Some(slow_threshold) = self.slow_threshold {
+            config.slow_test_threshold = Some(slow_threshold);
+        }
+
+        if self.sandbox {
+            config.ffi = false;
+            evm_opts = evm_opts.sandboxed();
+        }
         Ok((config, evm_opts))
     }
 
@@ -367,8 +597,9 @@ impl TestOutcome {
             if failures > 0 {
                 println!();
                 println!("Failed tests:");
+                let labels = self.labels();
                 for (name, result) in self.failures() {
-                    short_test_result(name, result);
+                    short_test_result(name, result, &labels);
                 }
                 println!();
 
@@ -384,6 +615,11 @@ impl TestOutcome {
         Ok(())
     }
 
+    /// Returns the labels of all addresses labeled in any test
+    pub fn labels(&self) -> BTreeMap<Address, String> {
+        self.tests().flat_map(|(_, result)| result.labeled_addresses.clone()).collect()
+    }
+
     pub fn duration(&self) -> Duration {
         self.results
             .values()
@@ -403,27 +639,107 @@ impl TestOutcome {
     }
 }
 
-fn short_test_result(name: &str, result: &forge::TestResult) {
+fn short_test_result(name: &str, result: &forge::TestResult, labels: &BTreeMap<Address, String>) {
     let status = if result.success {
         Paint::green("[PASS]".to_string())
     } else {
-        let txt = match (&result.reason, &result.counterexample) {
-            (Some(ref reason), Some(ref counterexample)) => {
+        let reason = result.reason.as_ref().map(|reason| label_addresses(reason, labels));
+        let txt = match (&reason, &result.counterexample) {
+            (Some(ref reason), Some(CounterExample::Single(ref counterexample))) => {
                 format!("[FAIL. Reason: {reason}. Counterexample: {counterexample}]")
             }
-            (None, Some(ref counterexample)) => {
+            (None, Some(CounterExample::Single(ref counterexample))) => {
                 format!("[FAIL. Counterexample: {counterexample}]")
             }
-            (Some(ref reason), None) => {
+            (Some(ref reason), _) => {
                 format!("[FAIL. Reason: {reason}]")
             }
-            (None, None) => "[FAIL]".to_string(),
+            (None, _) => "[FAIL]".to_string(),
         };
 
         Paint::red(txt)
     };
 
-    println!("{} {} {}", status, name, result.kind.gas_used());
+    let cached = if result.cached { Paint::cyan(" (cached)").to_string() } else { String::new() };
+    println!("{} {} {}{}", status, name, result.kind.gas_used(), cached);
+
+    if let (false, Some(CounterExample::Sequence(ref sequence))) =
+        (result.success, &result.counterexample)
+    {
+        println!("\t[Sequence]");
+        for call in sequence {
+            println!("\t\t{call}");
+        }
+    }
+}
+
+/// Prints the tests that took at least `threshold`, slowest first
+///
+/// Cached results are skipped, their tests were not run.
+fn slow_tests_report(results: &BTreeMap<String, SuiteResult>, threshold: Duration) {
+    let mut slow: Vec<_> = results
+        .iter()
+        .flat_map(|(id, suite)| {
+            suite.test_results.iter().map(move |(name, result)| (id, name, result))
+        })
+        .filter(|(_, _, result)| !result.cached && result.duration >= threshold)
+        .collect();
+    if slow.is_empty() {
+        return
+    }
+    slow.sort_by(|(_, _, a), (_, _, b)| b.duration.cmp(&a.duration));
+
+    println!();
+    println!("{} tests took at least {threshold:?}:", slow.len());
+    for (id, name, result) in slow {
+        println!(
+            "  {} {}::{name}",
+            Paint::yellow(format!("{:.2?}", result.duration)),
+            utils::get_contract_name(id)
+        );
+    }
+}
+
+/// Records the outcome of the executed tests, so failed tests can be rerun with
+/// `--replay-failures`
+fn record_failures(cache_path: &Path, results: &BTreeMap<String, SuiteResult>) -> eyre::Result<()> {
+    let path = cache_path.join(TEST_FAILURES_CACHE_FILENAME);
+    let mut failures = TestFailures::read(&path).unwrap_or_default();
+    failures.update(results);
+    failures.write(path)
+}
+
+/// Returns the cache of previous test results if `cache_test_results` is enabled
+///
+/// With `--force` the previous results are discarded and all tests are run again. Results are
+/// never cached when running against a fork without a pinned block, since they are not
+/// reproducible.
+fn load_result_cache(
+    config: &Config,
+    evm_opts: &EvmOpts,
+) -> eyre::Result<Option<TestResultsCache>> {
+    if !config.cache_test_results ||
+        (evm_opts.fork_url.is_some() && evm_opts.fork_block_number.is_none())
+    {
+        return Ok(None)
+    }
+
+    // all settings that affect the outcome of a test
+    let config_hash = hex::encode(keccak256(serde_json::to_vec(&(
+        evm_opts,
+        config.fuzz_runs,
+        config.fuzz_max_local_rejects,
+        config.fuzz_max_global_rejects,
+        config.fuzz_seed,
+        config.fuzz_dictionary_weight,
+        config.invariant,
+        config.evm_version,
+    ))?));
+    let path = config.cache_path.join(TEST_RESULTS_CACHE_FILENAME);
+    if config.force {
+        return Ok(Some(TestResultsCache { config_hash, path, ..Default::default() }))
+    }
+    Ok(Some(TestResultsCache::read(path, config_hash)))
 }
 
 pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<TestOutcome> {
@@ -439,8 +755,35 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
         max_global_rejects: config.fuzz_max_global_rejects,
         ..Default::default()
     };
-    let fuzzer = proptest::test_runner::TestRunner::new(cfg);
-    let mut filter = args.filter();
+    let fuzz_seed = config.fuzz_seed.unwrap_or_else(random_seed);
+    let fuzzer = seeded_runner(cfg, fuzz_seed);
+    let mut filter = args.filter().with_stdin_ids()?;
+
+    // Select the previously failed tests and their recorded inputs
+    let mut replay_inputs = BTreeMap::new();
+    if args.replay_failures {
+        let failures = TestFailures::read(config.cache_path.join(TEST_FAILURES_CACHE_FILENAME))?;
+        if failures.is_empty() {
+            println!("No failed tests recorded, nothing to replay.");
+            return Ok(TestOutcome::new(BTreeMap::new(), args.allow_failure))
+        }
+        for (id, tests) in failures.failures.iter() {
+            filter.exact.extend(tests.keys().map(|sig| TestId {
+                path: Some(utils::get_file_name(id).to_string()),
+                contract: utils::get_contract_name(id).to_string(),
+                test: sig.clone(),
+            }));
+        }
+        replay_inputs = failures.replay_inputs();
+    }
+    if let Some(ref path) = args.fuzz_input {
+        let failure = FuzzFailure::read(path)
+            .wrap_err_with(|| format!("Failed to read fuzz input {}", path.display()))?;
+        replay_inputs
+            .entry(failure.contract)
+            .or_insert_with(BTreeMap::new)
+            .insert(failure.test, failure.calldata);
+    }
 
     // Set up the project
     let project = config.project()?;
@@ -453,10 +796,28 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
 
     // Determine print verbosity and executor verbosity
     let verbosity = evm_opts.verbosity;
-    if args.gas_report && evm_opts.verbosity < 3 {
+    // Stack traces are printed with the traces of failed tests
+    let stack_traces = if verbosity >= 3 {
+        stack_trace_decoder(&output, &project.paths.root)
+    } else {
+        StackTraceDecoder::default()
+    };
+    let gas_reporting = args.gas_report || args.gas_report_json.is_some();
+    if gas_reporting && evm_opts.verbosity < 3 {
         evm_opts.verbosity = 3;
     }
 
+    // Only reuse previous results when actually running the tests
+    let result_cache = if args.debug.is_none() &&
+        !args.list &&
+        !args.replay_failures &&
+        args.fuzz_input.is_none()
+    {
+        load_result_cache(&config, &evm_opts)?
+    } else {
+        None
+    };
+
     // Prepare the test builder
     let evm_spec = utils::evm_spec(&config.evm_version);
     let mut runner = MultiContractRunnerBuilder::default()
@@ -465,10 +826,20 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
         .evm_spec(evm_spec)
         .sender(evm_opts.sender)
         .with_fork(utils::get_fork(&evm_opts, &config.rpc_storage_caching))
+        .with_rpc_storage_caching(config.rpc_storage_caching.clone())
+        .with_result_cache(result_cache)
+        .with_replay_inputs(replay_inputs)
+        .with_fuzz_corpus_dir(config.cache.then(|| config.cache_path.join("fuzz")))
+        .with_fuzz_dictionary_weight(config.fuzz_dictionary_weight)
+        .with_invariant_config(config.invariant)
+        .with_constructor_args(config.constructor_args.resolve(&project.paths.root)?)
+        .with_table_fixtures_dir(Some(project.paths.tests.join("fixtures")))
+        .with_jobs(args.jobs)
+        .with_timeout(config.test_timeout.map(Duration::from_secs))
         .build(project.paths.root, output, evm_opts)?;
 
     if args.debug.is_some() {
-        filter.test_pattern = args.debug;
+        filter.test_pattern = args.debug.into_iter().collect();
         match runner.count_filtered_tests(&filter) {
                 1 => {
                     // Run the test
@@ -484,10 +855,9 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
                     // Build debugger args if this is a fuzz test
                     let sig = match test_kind {
                         TestKind::Fuzz(cases) => {
-                            if let Some(counterexample) = counterexample {
-                                counterexample.calldata.to_string()
-                            } else {
-                                cases.cases().first().expect("no fuzz cases run").calldata.to_string()
+                            match counterexample.as_ref().and_then(|c| c.calldata()) {
+                                Some(calldata) => calldata.to_string(),
+                                None => cases.cases().first().expect("no fuzz cases run").calldata.to_string(),
                             }
                         },
                         _ => sig,
@@ -500,6 +870,7 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
                         sig,
                         args: Vec::new(),
                         debug: true,
+                        predeploy: false,
                         opts: args.opts,
                         evm_opts: args.evm_opts,
                     };
@@ -514,21 +885,54 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
                         Use --match-contract and --match-path to further limit the search."))
             }
     } else if args.list {
-        list(runner, filter, args.json)
+        list(runner, filter, args.reporter() == ReporterKind::Json)
     } else {
         test(
             config,
             runner,
             verbosity,
             filter,
-            args.json,
+            args.reporter(),
             args.allow_failure,
             include_fuzz_tests,
-            args.gas_report,
+            gas_reporting,
+            args.gas_report_json,
+            args.fuzz_stats,
+            fuzz_seed,
+            stack_traces,
+            TraceOptions {
+                gas: args.gas || verbosity >= 5,
+                collapse_static_calls: args.collapse_static_calls,
+            },
         )
     }
 }
 
+/// Collects the runtime code and source maps of all compiled contracts, so reverts can be mapped
+/// to source lines
+fn stack_trace_decoder(output: &ProjectCompileOutput, root: &Path) -> StackTraceDecoder {
+    let (artifacts, sources) =
+        output.clone().with_stripped_file_prefixes(root).into_artifacts_with_sources();
+    let contracts = artifacts
+        .into_iter()
+        .filter_map(|(id, artifact)| {
+            let contract: CompactContractBytecode = artifact.into_contract_bytecode();
+            let contract: ContractBytecode = contract.into();
+            let bytecode = contract.deployed_bytecode?.bytecode?;
+            let source_map = bytecode.source_map()?.ok()?;
+            Some((id.name, (bytecode.object.as_bytes()?.to_vec(), source_map)))
+        })
+        .collect();
+    let sources = sources
+        .into_ids()
+        .filter_map(|(id, path)| {
+            let content = std::fs::read_to_string(root.join(&path)).ok()?;
+            Some((id, (path, content)))
+        })
+        .collect();
+    StackTraceDecoder::new(contracts, sources)
+}
+
 /// Lists all matching tests
 fn list(runner: MultiContractRunner, filter: Filter, json: bool) -> eyre::Result<TestOutcome> {
     let results = runner.list(&filter);
@@ -554,10 +958,15 @@ fn test(
     mut runner: MultiContractRunner,
     verbosity: u8,
     filter: Filter,
-    json: bool,
+    reporter: ReporterKind,
     allow_failure: bool,
     include_fuzz_tests: bool,
     gas_reporting: bool,
+    gas_report_json: Option<PathBuf>,
+    fuzz_stats: bool,
+    fuzz_seed: u64,
+    stack_traces: StackTraceDecoder,
+    trace_options: TraceOptions,
 ) -> eyre::Result<TestOutcome> {
     if runner.count_filtered_tests(&filter) == 0 {
         let filter_str = filter.to_string();
@@ -569,7 +978,7 @@ fn test(
             println!("\nNo tests match the provided pattern:");
             println!("{}", filter_str);
             // Try to suggest a test when there's no match
-            if let Some(ref test_pattern) = filter.test_pattern {
+            if let Some(test_pattern) = filter.test_pattern.first() {
                 let test_name = test_pattern.as_str();
                 let candidates = runner.get_tests(&filter);
                 if let Some(suggestion) = suggestions::did_you_mean(test_name, &candidates).pop() {
@@ -579,20 +988,26 @@ fn test(
         }
     }
 
-    if json {
+    if !reporter.is_streaming() {
         let results = runner.test(&filter, None, include_fuzz_tests)?;
-        println!("{}", serde_json::to_string(&results)?);
+        if let Some(cache) = runner.result_cache {
+            cache.write()?;
+        }
+        record_failures(&config.cache_path, &results)?;
+        reporter.report(&results, std::io::stdout().lock())?;
         Ok(TestOutcome::new(results, allow_failure))
     } else {
-        // Set up identifiers
-        let local_identifier = LocalTraceIdentifier::new(&runner.known_contracts);
+        // Set up identifiers, the known contracts are shared with the runner thread
+        let known_contracts = runner.known_contracts.clone();
+        let local_identifier = LocalTraceIdentifier::new(&known_contracts);
         let remote_chain_id = runner.evm_opts.get_remote_chain_id();
         // Do not re-query etherscan for contracts that you've already queried today.
         // TODO: Make this configurable.
         let cache_ttl = Duration::from_secs(24 * 60 * 60);
+        // contracts are not identified on etherscan in offline mode
         let etherscan_identifier = EtherscanIdentifier::new(
             remote_chain_id,
-            config.etherscan_api_key,
+            config.etherscan_api_key.filter(|_| !config.offline),
             remote_chain_id.and_then(Config::foundry_etherscan_cache_dir),
             cache_ttl,
         );
@@ -601,11 +1016,15 @@ fn test(
         let (tx, rx) = channel::<(String, SuiteResult)>();
 
         // Run tests
-        let handle =
-            thread::spawn(move || runner.test(&filter, Some(tx), include_fuzz_tests).unwrap());
+        let handle = thread::spawn(move || {
+            runner.test(&filter, Some(tx), include_fuzz_tests).unwrap();
+            runner.result_cache
+        });
 
         let mut results: BTreeMap<String, SuiteResult> = BTreeMap::new();
-        let mut gas_report = GasReport::new(config.gas_reports);
+        // Labels are shared by all tests of the run, a test's own labels take precedence
+        let mut labels: BTreeMap<Address, String> = BTreeMap::new();
+        let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore);
         for (contract_name, suite_result) in rx {
             let mut tests = suite_result.test_results.clone();
             println!();
@@ -616,34 +1035,97 @@ fn test(
                 let term = if tests.len() > 1 { "tests" } else { "test" };
                 println!("Running {} {} for {}", tests.len(), term, contract_name);
             }
+            labels.extend(tests.values().flat_map(|result| result.labeled_addresses.clone()));
             for (name, result) in &mut tests {
-                short_test_result(name, result);
+                let mut test_labels = labels.clone();
+                test_labels.extend(result.labeled_addresses.clone());
+                short_test_result(name, result, &test_labels);
+
+                if let (false, true, Some(CounterExample::Single(ref counterexample))) =
+                    (result.success, result.is_fuzz(), &result.counterexample)
+                {
+                    let path = FuzzFailure::path(
+                        config.cache_path.join(FUZZ_FAILURES_DIR),
+                        &contract_name,
+                        name,
+                    );
+                    let failure = FuzzFailure {
+                        contract: contract_name.clone(),
+                        test: name.clone(),
+                        calldata: counterexample.calldata.clone(),
+                        args: foundry_utils::format_tokens(&counterexample.args).collect(),
+                        reason: result.reason.clone(),
+                    };
+                    match failure.write(&path) {
+                        Ok(()) => println!(
+                            "\tReplay with: forge test --match-contract {} --match-test {} \
+                             --fuzz-input {}",
+                            utils::get_contract_name(&contract_name),
+                            name.split('(').next().unwrap_or(name),
+                            path.display()
+                        ),
+                        Err(err) => tracing::warn!(?err, "failed to write fuzz failure"),
+                    }
+                }
+                if !result.success &&
+                    !result.cached &&
+                    (result.is_fuzz() || result.is_invariant())
+                {
+                    println!("\tRerun with the same inputs: --fuzz-seed {fuzz_seed}");
+                }
 
-                // We only display logs at level 2 and above
+                if fuzz_stats {
+                    if let TestKind::Fuzz(ref cases) = result.kind {
+                        let func = AbiParser::default().parse_function(&format!("function {name}"));
+                        if let Ok(func) = func {
+                            println!("{}", FuzzInputStats::new(&func, cases));
+                        }
+                    }
+                }
+
+                // We only display reverted invariant calls and logs at level 2 and above
                 if verbosity >= 2 {
+                    if let TestKind::Invariant { ref reverted_calls, .. } = result.kind {
+                        if !reverted_calls.is_empty() {
+                            println!("Reverted calls:");
+                            for (name, reverts) in reverted_calls {
+                                println!("  {name}: {reverts}");
+                            }
+                            println!();
+                        }
+                    }
+
                     // We only decode logs from Hardhat and DS-style console events
                     let console_logs = decode_console_logs(&result.logs);
                     if !console_logs.is_empty() {
                         println!("Logs:");
                         for log in console_logs {
-                            println!("  {log}");
+                            println!("  {}", label_addresses(&log, &test_labels));
                         }
                         println!();
                     }
                 }
 
                 if !result.traces.is_empty() {
+                    let contract = utils::get_contract_name(&contract_name);
+                    let _span = tracing::info_span!(
+                        target: PROFILE_TARGET,
+                        "decode",
+                        name = %format!("{contract}.{name}")
+                    )
+                    .entered();
+
                     // Identify addresses in each trace
                     let mut decoder = CallTraceDecoderBuilder::new()
-                        .with_labels(result.labeled_addresses.clone())
+                        .with_labels(test_labels)
                         .with_events(local_identifier.events())
                         .build();
 
                     // Decode the traces
                     let mut decoded_traces = Vec::new();
+                    let mut stack_trace = Vec::new();
                     for (kind, trace) in &mut result.traces {
                         decoder.identify(trace, &local_identifier);
-                        decoder.identify(trace, &etherscan_identifier);
 
                         let should_include = match kind {
                             // At verbosity level 3, we only display traces for failed tests
@@ -660,13 +1142,17 @@ fn test(
                         };
 
                         // We decode the trace if we either need to build a gas report or we need
-                        // to print it
+                        // to print it, only then unknown addresses are looked up on Etherscan
                         if should_include || gas_reporting {
+                            decoder.identify(trace, &etherscan_identifier);
                             decoder.decode(trace);
                         }
 
                         if should_include {
-                            decoded_traces.push(trace.to_string());
+                            decoded_traces.push(trace.display(trace_options));
+                        }
+                        if should_include && !result.success && *kind == TraceKind::Execution {
+                            stack_trace = stack_traces.stack_trace(trace);
                         }
                     }
 
@@ -675,6 +1161,12 @@ fn test(
                         decoded_traces.into_iter().for_each(|trace| println!("{trace}"));
                     }
 
+                    if !stack_trace.is_empty() {
+                        println!("Stack trace:");
+                        stack_trace.iter().for_each(|frame| println!("  {frame}"));
+                        println!();
+                    }
+
                     if gas_reporting {
                         gas_report.analyze(&result.traces);
                     }
@@ -689,11 +1181,24 @@ fn test(
         }
 
         if gas_reporting {
-            println!("{}", gas_report.finalize());
+            let gas_report = gas_report.finalize();
+            println!("{gas_report}");
+            if let Some(path) = gas_report_json {
+                std::fs::write(&path, serde_json::to_string_pretty(&gas_report)?).wrap_err_with(
+                    || format!("Failed to write the gas report to {}", path.display()),
+                )?;
+            }
+        }
+
+        if let Some(threshold) = config.slow_test_threshold {
+            slow_tests_report(&results, Duration::from_millis(threshold));
         }
 
-        // reattach the thread
-        let _ = handle.join();
+        // reattach the thread and persist the results for the next run
+        if let Ok(Some(cache)) = handle.join() {
+            cache.write()?;
+        }
+        record_failures(&config.cache_path, &results)?;
 
         Ok(TestOutcome::new(results, allow_failure))
     }
@@ -4,6 +4,8 @@ use crate::cmd::{forge::build::ProjectPathsArgs, Cmd};
 use clap::Parser;
 use ethers::solc::Graph;
 use foundry_config::Config;
+use std::{collections::BTreeSet, fmt::Write};
+use yansi::Paint;
 
 foundry_config::impl_figment_convert!(TreeArgs, opts);
 use ethers::solc::resolver::{Charset, TreeOptions};
@@ -15,6 +17,13 @@ pub struct TreeArgs {
     no_dedupe: bool,
     #[clap(help = "Character set to use in output: utf8, ascii", default_value = "utf8", long)]
     charset: Charset,
+    #[clap(
+        help = "Print the import graph in the DOT format of graphviz",
+        long_help = "Print the import graph in the DOT format of graphviz, e.g. `forge tree --dot | dot -Tsvg > imports.svg`. The imports of cycles are red.",
+        long,
+        conflicts_with_all = &["no_dedupe", "charset"]
+    )]
+    dot: bool,
     #[clap(flatten, next_help_heading = "PROJECT OPTIONS")]
     opts: ProjectPathsArgs,
 }
@@ -24,10 +33,165 @@ impl Cmd for TreeArgs {
 
     fn run(self) -> eyre::Result<Self::Output> {
         let config: Config = From::from(&self);
-        let graph = Graph::resolve(&config.project_paths())?;
+        let paths = config.project_paths();
+        let graph = Graph::resolve(&paths)?;
+
+        // the files of the graph by their index, relative to the root
+        let mut files = vec![String::new(); graph.files().len()];
+        for (path, idx) in graph.files() {
+            let path = path.strip_prefix(&paths.root).unwrap_or(path);
+            files[*idx] = foundry_config::slash_path(path);
+        }
+        let imports: Vec<Vec<usize>> =
+            (0..files.len()).map(|idx| graph.imported_nodes(idx).to_vec()).collect();
+        let cycles = import_cycles(&imports);
+
+        if self.dot {
+            print!("{}", to_dot(&files, &imports, &cycles));
+            return Ok(())
+        }
+
+        for cycle in &cycles {
+            let cycle: Vec<&str> = cycle.iter().map(|idx| files[*idx].as_str()).collect();
+            eprintln!("{} Import cycle between {}", Paint::yellow("Warning:"), cycle.join(", "));
+        }
+        if self.no_dedupe && !cycles.is_empty() {
+            eyre::bail!("The import cycles can not be printed without de-duplication")
+        }
         let opts = TreeOptions { charset: self.charset, no_dedupe: self.no_dedupe };
         graph.print_with_options(opts);
 
         Ok(())
     }
 }
+
+/// Returns the groups of files that import each other, directly or indirectly
+///
+/// These are the strongly connected components of the import graph with more than one file, or
+/// a file that imports itself.
+fn import_cycles(imports: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    /// Tarjan's algorithm
+    struct Components<'a> {
+        imports: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        lowlink: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl<'a> Components<'a> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.lowlink[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+
+            for &import in &self.imports[node] {
+                match self.index[import] {
+                    None => {
+                        self.visit(import);
+                        self.lowlink[node] = self.lowlink[node].min(self.lowlink[import]);
+                    }
+                    Some(index) if self.on_stack[import] => {
+                        self.lowlink[node] = self.lowlink[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.lowlink[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut components = Components {
+        imports,
+        index: vec![None; imports.len()],
+        lowlink: vec![0; imports.len()],
+        on_stack: vec![false; imports.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..imports.len() {
+        if components.index[node].is_none() {
+            components.visit(node);
+        }
+    }
+
+    let mut cycles: Vec<Vec<usize>> = components
+        .components
+        .into_iter()
+        .filter(|component| component.len() > 1 || imports[component[0]].contains(&component[0]))
+        .collect();
+    cycles.sort();
+    cycles
+}
+
+/// Returns the import graph in the DOT format, the imports of cycles are red
+fn to_dot(files: &[String], imports: &[Vec<usize>], cycles: &[Vec<usize>]) -> String {
+    let in_cycle = |from: usize, to: usize| {
+        cycles.iter().any(|cycle| cycle.contains(&from) && cycle.contains(&to))
+    };
+    let mut dot = String::from("digraph imports {\n    node [shape=box];\n");
+    let mut edges = BTreeSet::new();
+    for (from, imports) in imports.iter().enumerate() {
+        writeln!(dot, "    \"{}\";", files[from]).unwrap();
+        for &to in imports {
+            edges.insert((from, to));
+        }
+    }
+    for (from, to) in edges {
+        let attributes = if in_cycle(from, to) { " [color=red]" } else { "" };
+        writeln!(dot, "    \"{}\" -> \"{}\"{attributes};", files[from], files[to]).unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_import_cycles() {
+        // 0 -> 1 -> 2 -> 1, 3 -> 3, 4 -> 0
+        let imports = vec![vec![1], vec![2], vec![1], vec![3], vec![0]];
+        assert_eq!(import_cycles(&imports), vec![vec![1, 2], vec![3]]);
+        assert!(import_cycles(&[vec![1], vec![]]).is_empty());
+    }
+
+    #[test]
+    fn can_print_dot() {
+        let files: Vec<String> =
+            ["src/A.sol", "src/B.sol", "lib/C.sol"].iter().map(|s| s.to_string()).collect();
+        let imports = vec![vec![1, 2], vec![0], vec![]];
+        let dot = to_dot(&files, &imports, &import_cycles(&imports));
+        assert_eq!(
+            dot,
+            r#"digraph imports {
+    node [shape=box];
+    "src/A.sol";
+    "src/B.sol";
+    "lib/C.sol";
+    "src/A.sol" -> "src/B.sol" [color=red];
+    "src/A.sol" -> "lib/C.sol";
+    "src/B.sol" -> "src/A.sol" [color=red];
+}
+"#
+        );
+    }
+}
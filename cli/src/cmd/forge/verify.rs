@@ -1,7 +1,10 @@
 //! Verify contract source on etherscan
 
-use super::build::{CoreBuildArgs, ProjectPathsArgs};
-use crate::{cmd::RetryArgs, opts::forge::ContractInfo};
+use super::{
+    build::{CoreBuildArgs, ProjectPathsArgs},
+    flatten::flatten,
+};
+use crate::{cmd::RetryArgs, opts::forge::ContractInfo, utils};
 use clap::Parser;
 use ethers::{
     abi::Address,
@@ -65,8 +68,12 @@ pub struct VerifyArgs {
     )]
     pub chain: Chain,
 
-    #[clap(help = "Your Etherscan API key.", env = "ETHERSCAN_API_KEY")]
-    pub etherscan_key: String,
+    #[clap(
+        help = "Your Etherscan API key.",
+        long_help = "Your Etherscan API key, defaults to the key of the chain's `[etherscan]` config or `etherscan_api_key`.",
+        env = "ETHERSCAN_API_KEY"
+    )]
+    pub etherscan_key: Option<String>,
 
     #[clap(help = "Flatten the source code before verifying.", long = "flatten")]
     pub flatten: bool,
@@ -91,7 +98,10 @@ pub struct VerifyArgs {
 impl VerifyArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
     pub async fn run(mut self) -> eyre::Result<()> {
-        let etherscan = Client::new(self.chain.try_into()?, &self.etherscan_key)
+        utils::ensure_online("Verifying the contract on Etherscan")?;
+        let config = Config::from(&self.project_paths);
+        let etherscan_key = etherscan_key(self.etherscan_key.take(), self.chain, &config)?;
+        let etherscan = Client::new(self.chain.try_into()?, &etherscan_key)
             .wrap_err("Failed to create etherscan client")?;
 
         let verify_args = self.create_verify_request().await?;
@@ -142,7 +152,7 @@ impl VerifyArgs {
                     guid: resp.result,
                     chain: self.chain,
                     retry: RETRY_CHECK_ON_VERIFY,
-                    etherscan_key: self.etherscan_key,
+                    etherscan_key: Some(etherscan_key),
                 };
                 return check_args.run().await
             }
@@ -163,15 +173,17 @@ impl VerifyArgs {
             ignored_error_codes: vec![],
             no_auto_detect: false,
             use_solc: None,
-            offline: false,
             force: false,
             libraries: vec![],
             via_ir: false,
+            bytecode_hash: None,
+            yul_stack_allocation: false,
             revert_strings: None,
+            build_info: false,
         };
 
         let project = build_args.project()?;
-        let config = Config::load();
+        let config = Config::from(&self.project_paths);
 
         if self.contract.path.is_none() && !config.cache {
             eyre::bail!(
@@ -343,13 +355,25 @@ To skip this solc dry, pass `--force`.
             .and_then(|m| m.bytecode_hash)
             .unwrap_or_default();
 
+        let use_literal_content = project
+            .solc_config
+            .settings
+            .metadata
+            .as_ref()
+            .and_then(|m| m.use_literal_content)
+            .unwrap_or_default();
+        eyre::ensure!(
+            !use_literal_content,
+            "When using flattened source, Etherscan re-compiles your code with the default metadata settings. Hint: Remove `use_literal_content` from your foundry.toml or verify without `--flatten`"
+        );
+
         eyre::ensure!(
             bch == BytecodeHash::Ipfs,
             "When using flattened source, bytecodeHash must be set to ipfs because Etherscan uses IPFS in its Compiler Settings when re-compiling your code. BytecodeHash is currently: {}. Hint: Set the bytecodeHash key in your foundry.toml :)",
             bch,
         );
 
-        let source = project.flatten(target).wrap_err("Failed to flatten contract")?;
+        let source = flatten(&project.paths, target).wrap_err("Failed to flatten contract")?;
 
         if !self.force {
             // solc dry run of flattened code
@@ -417,6 +441,19 @@ async fn ensure_solc_build_metadata(version: Version) -> eyre::Result<Version> {
     }
 }
 
+/// Returns the `--etherscan-key`, or the key of the chain configured in the `config`
+fn etherscan_key(key: Option<String>, chain: Chain, config: &Config) -> eyre::Result<String> {
+    if let Some(key) = key {
+        return Ok(key)
+    }
+    config.get_etherscan_api_key(Some(chain))?.ok_or_else(|| {
+        eyre!(
+            "No etherscan API key for chain {chain}, pass it or set it in the `[etherscan]` config \
+             or `etherscan_api_key` of foundry.toml"
+        )
+    })
+}
+
 /// Check verification status arguments
 #[derive(Debug, Clone, Parser)]
 pub struct VerifyCheckArgs {
@@ -435,14 +472,20 @@ pub struct VerifyCheckArgs {
     #[clap(flatten)]
     retry: RetryArgs,
 
-    #[clap(help = "Your Etherscan API key.", env = "ETHERSCAN_API_KEY")]
-    etherscan_key: String,
+    #[clap(
+        help = "Your Etherscan API key.",
+        long_help = "Your Etherscan API key, defaults to the key of the chain's `[etherscan]` config or `etherscan_api_key`.",
+        env = "ETHERSCAN_API_KEY"
+    )]
+    etherscan_key: Option<String>,
 }
 
 impl VerifyCheckArgs {
     /// Executes the command to check verification status on Etherscan
     pub async fn run(self) -> eyre::Result<()> {
-        let etherscan = Client::new(self.chain.try_into()?, &self.etherscan_key)
+        utils::ensure_online("Checking the verification status on Etherscan")?;
+        let etherscan_key = etherscan_key(self.etherscan_key.clone(), self.chain, &Config::load())?;
+        let etherscan = Client::new(self.chain.try_into()?, &etherscan_key)
             .wrap_err("Failed to create etherscan client")?;
 
         println!("Waiting for verification result...");
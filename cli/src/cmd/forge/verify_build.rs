@@ -0,0 +1,165 @@
+//! verify-build command
+
+use crate::{cmd::Cmd, utils};
+use clap::{Parser, ValueHint};
+use ethers::{
+    solc::{
+        artifacts::{BytecodeObject, CompilerInput, CompilerOutput},
+        Solc,
+    },
+    utils::keccak256,
+};
+use eyre::WrapErr;
+use semver::Version;
+use serde_json::Value;
+use std::{collections::BTreeMap, path::PathBuf};
+use yansi::Paint;
+
+/// Command to check that a build is reproducible from the solc input recorded in its build info
+#[derive(Debug, Clone, Parser)]
+pub struct VerifyBuildArgs {
+    #[clap(
+        help = "The build info file to verify.",
+        long_help = r#"The build info file to verify.
+
+Build info files are written to `<out>/build-info` if `build_info` is enabled or `forge build`
+is run with `--build-info`."#,
+        value_hint = ValueHint::FilePath,
+        value_name = "BUILD_INFO"
+    )]
+    path: PathBuf,
+}
+
+impl Cmd for VerifyBuildArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let content = std::fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("Failed to read build info {}", self.path.display()))?;
+        let build_info: Value = serde_json::from_str(&content)
+            .wrap_err_with(|| format!("Failed to parse build info {}", self.path.display()))?;
+
+        let version: Version = build_info["solcVersion"]
+            .as_str()
+            .ok_or_else(|| eyre::eyre!("The build info has no solc version"))?
+            .parse()?;
+        let input: CompilerInput = serde_json::from_value(build_info["input"].clone())
+            .wrap_err("Failed to parse the solc input of the build info")?;
+        let recorded: CompilerOutput = serde_json::from_value(build_info["output"].clone())
+            .wrap_err("Failed to parse the solc output of the build info")?;
+
+        println!("Input hash: 0x{}", hex::encode(keccak256(serde_json::to_vec(&input)?)));
+
+        let solc = match Solc::find_svm_installed_version(version.to_string())? {
+            Some(solc) => solc,
+            None if utils::is_offline() => {
+                eyre::bail!("solc {version} is not installed, it can not be installed offline")
+            }
+            None => Solc::blocking_install(&version)?,
+        };
+
+        println!("Compiling {} sources with solc {version}...", input.sources.len());
+        let output = solc.compile(&input)?;
+        if output.has_error() {
+            let errors = output.errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+            eyre::bail!("Failed to compile the recorded input:\n{}", errors.join("\n"))
+        }
+
+        let mismatches = bytecode_mismatches(&recorded, &output);
+        if !mismatches.is_empty() {
+            eprintln!("{}", Paint::red("The build is not reproducible:"));
+            for mismatch in mismatches {
+                eprintln!("  {mismatch}");
+            }
+            std::process::exit(1)
+        }
+
+        println!(
+            "{} {} contracts compiled to identical bytecode.",
+            Paint::green("The build is reproducible:"),
+            bytecodes(&recorded).len()
+        );
+        Ok(())
+    }
+}
+
+type Bytecodes = (Option<BytecodeObject>, Option<BytecodeObject>);
+
+/// Returns the creation and the runtime bytecode of every contract, by `<file>:<contract>`
+fn bytecodes(output: &CompilerOutput) -> BTreeMap<String, Bytecodes> {
+    output
+        .contracts
+        .iter()
+        .flat_map(|(file, contracts)| {
+            contracts.iter().map(move |(name, contract)| {
+                let evm = contract.evm.as_ref();
+                let creation = evm.and_then(|evm| evm.bytecode.as_ref()).map(|b| b.object.clone());
+                let runtime = evm
+                    .and_then(|evm| evm.deployed_bytecode.as_ref())
+                    .and_then(|deployed| deployed.bytecode.as_ref())
+                    .map(|b| b.object.clone());
+                (format!("{file}:{name}"), (creation, runtime))
+            })
+        })
+        .collect()
+}
+
+/// Returns a description of every contract whose bytecode differs between the recorded and the
+/// rebuilt output
+fn bytecode_mismatches(recorded: &CompilerOutput, rebuilt: &CompilerOutput) -> Vec<String> {
+    let mut rebuilt = bytecodes(rebuilt);
+    let mut mismatches = Vec::new();
+    for (id, (creation, runtime)) in bytecodes(recorded) {
+        match rebuilt.remove(&id) {
+            None => mismatches.push(format!("{id}: missing from the rebuilt output")),
+            Some((rebuilt_creation, rebuilt_runtime)) => {
+                if creation != rebuilt_creation {
+                    mismatches.push(format!("{id}: creation bytecode differs"));
+                }
+                if runtime != rebuilt_runtime {
+                    mismatches.push(format!("{id}: runtime bytecode differs"));
+                }
+            }
+        }
+    }
+    mismatches.extend(rebuilt.into_keys().map(|id| format!("{id}: not in the recorded output")));
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(contracts: &[(&str, &str, &str)]) -> CompilerOutput {
+        let mut files = serde_json::Map::new();
+        for (name, creation, runtime) in contracts {
+            files.insert(
+                name.to_string(),
+                serde_json::json!({
+                    "evm": {
+                        "bytecode": { "object": creation },
+                        "deployedBytecode": { "object": runtime }
+                    }
+                }),
+            );
+        }
+        serde_json::from_value(serde_json::json!({ "contracts": { "src/A.sol": files } })).unwrap()
+    }
+
+    #[test]
+    fn can_find_bytecode_mismatches() {
+        let recorded = output(&[("A", "6080", "6001"), ("B", "6080", "6002")]);
+
+        assert!(bytecode_mismatches(&recorded, &recorded).is_empty());
+
+        let rebuilt = output(&[("A", "6080", "6003"), ("C", "6080", "6002")]);
+        assert_eq!(
+            bytecode_mismatches(&recorded, &rebuilt),
+            [
+                "src/A.sol:A: runtime bytecode differs",
+                "src/A.sol:B: missing from the rebuilt output",
+                "src/A.sol:C: not in the recorded output",
+            ]
+        );
+    }
+}
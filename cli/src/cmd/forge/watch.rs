@@ -135,9 +135,11 @@ pub async fn watch_test(args: TestArgs) -> eyre::Result<()> {
     let filter = args.filter();
     // marker to check whether to override the command
     let no_reconfigure = filter.pattern.is_some() ||
-        filter.test_pattern.is_some() ||
-        filter.path_pattern.is_some() ||
-        filter.contract_pattern.is_some() ||
+        !filter.test_pattern.is_empty() ||
+        !filter.path_pattern.is_empty() ||
+        !filter.contract_pattern.is_empty() ||
+        !filter.exact.is_empty() ||
+        filter.exact_stdin ||
         args.watch.run_all;
 
     let config: Config = args.build_args().into();
@@ -4,13 +4,19 @@ use ethers::{
     abi::Abi,
     prelude::cache::CacheEntry,
     solc::{
-        artifacts::{CompactBytecode, CompactContractBytecode, CompactDeployedBytecode},
+        artifacts::{CompactBytecode, CompactDeployedBytecode},
         cache::SolFilesCache,
         Project,
     },
 };
+use eyre::WrapErr;
 use foundry_utils::Retry;
-use std::path::PathBuf;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 /// Common trait for all cli commands
 pub trait Cmd: clap::Parser + Sized {
@@ -25,25 +31,74 @@ pub fn read_artifact(
     project: &Project,
     contract: ContractInfo,
 ) -> eyre::Result<(Abi, CompactBytecode, CompactDeployedBytecode)> {
+    let name = contract.name.clone();
+    let artifact = read_lazy_artifact(project, contract)?;
+    Ok((
+        artifact.abi()?.ok_or_else(|| eyre::Error::msg(format!("abi not found for {}", name)))?,
+        artifact
+            .bytecode
+            .ok_or_else(|| eyre::Error::msg(format!("bytecode not found for {}", name)))?,
+        artifact.deployed_bytecode.ok_or_else(|| {
+            eyre::Error::msg(format!("deployed bytecode not found for {}", name))
+        })?,
+    ))
+}
+
+/// Reads the artifact of the given contract from the project's compiled artifacts, see
+/// [`LazyArtifact`]
+pub fn read_lazy_artifact(project: &Project, contract: ContractInfo) -> eyre::Result<LazyArtifact> {
     let cache = SolFilesCache::read_joined(&project.paths)?;
     let contract_path = match contract.path {
         Some(path) => dunce::canonicalize(PathBuf::from(path))?,
         None => get_cached_entry_by_name(&cache, &contract.name)?.0,
     };
+    let artifact_path =
+        cache.find_artifact_path(&contract_path, &contract.name).ok_or_else(|| {
+            eyre::eyre!(
+                "could not find artifact `{}` in {}",
+                contract.name,
+                contract_path.display()
+            )
+        })?;
+    LazyArtifact::read(artifact_path)
+}
 
-    let artifact: CompactContractBytecode = cache.read_artifact(contract_path, &contract.name)?;
+/// The ABI and bytecode of an artifact file
+///
+/// Artifacts also contain the AST, metadata and other compiler output, which make up most of the
+/// file. Only the ABI and bytecode fields are deserialized, all other fields are skipped without
+/// allocating them, and the ABI is kept as raw JSON until it is requested with
+/// [`LazyArtifact::abi`]. The file is read into memory, not memory-mapped.
+///
+/// This is only used by commands that read single artifacts from disk, like `forge create`. The
+/// artifacts of a build, e.g. for `forge test`, are still fully deserialized by the ethers-solc
+/// compile output.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LazyArtifact {
+    abi: Option<Box<RawValue>>,
+    pub bytecode: Option<CompactBytecode>,
+    pub deployed_bytecode: Option<CompactDeployedBytecode>,
+}
 
-    Ok((
-        artifact
-            .abi
-            .ok_or_else(|| eyre::Error::msg(format!("abi not found for {}", contract.name)))?,
-        artifact
-            .bytecode
-            .ok_or_else(|| eyre::Error::msg(format!("bytecode not found for {}", contract.name)))?,
-        artifact.deployed_bytecode.ok_or_else(|| {
-            eyre::Error::msg(format!("deployed bytecode not found for {}", contract.name))
-        })?,
-    ))
+impl LazyArtifact {
+    /// Reads the artifact file at `path`
+    pub fn read(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read(path)
+            .wrap_err_with(|| format!("Failed to read artifact {}", path.display()))?;
+        serde_json::from_slice(&content)
+            .wrap_err_with(|| format!("Failed to parse artifact {}", path.display()))
+    }
+
+    /// Parses the ABI of the artifact, if it has one
+    pub fn abi(&self) -> eyre::Result<Option<Abi>> {
+        self.abi
+            .as_ref()
+            .map(|abi| serde_json::from_str(abi.get()))
+            .transpose()
+            .wrap_err("Failed to parse the ABI of the artifact")
+    }
 }
 
 /// Helper function for finding a contract by ContractName
@@ -123,3 +178,37 @@ impl From<RetryArgs> for Retry {
         Retry::new(r.retries, r.delay)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_lazy_artifact() {
+        let artifact: LazyArtifact = serde_json::from_str(
+            r#"{
+                "abi": [{
+                    "type": "function",
+                    "name": "increment",
+                    "inputs": [],
+                    "outputs": [],
+                    "stateMutability": "nonpayable"
+                }],
+                "bytecode": {"object": "0x6080", "sourceMap": "", "linkReferences": {}},
+                "deployedBytecode": {"object": "0x6080", "sourceMap": "", "linkReferences": {}},
+                "ast": {"absolutePath": "src/Counter.sol", "nodes": [{"nodeType": "Pragma"}]},
+                "metadata": {"compiler": {"version": "0.8.10"}}
+            }"#,
+        )
+        .unwrap();
+        assert!(artifact.bytecode.is_some());
+        assert!(artifact.deployed_bytecode.is_some());
+        let abi = artifact.abi().unwrap().unwrap();
+        assert!(abi.function("increment").is_ok());
+
+        let artifact: LazyArtifact =
+            serde_json::from_str(r#"{"abi": null, "bytecode": null}"#).unwrap();
+        assert!(artifact.abi().unwrap().is_none());
+        assert!(artifact.bytecode.is_none());
+    }
+}
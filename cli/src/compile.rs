@@ -2,8 +2,20 @@
 
 use crate::term;
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
-use ethers::solc::{report::NoReporter, Artifact, FileFilter, Project, ProjectCompileOutput};
-use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
+use ethers::solc::{
+    artifacts::CompactContractBytecode, cache::SolFilesCache, remappings::Remapping,
+    report::NoReporter, FileFilter, Project, ProjectCompileOutput,
+};
+use foundry_config::ContractLimits;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
 
 /// Compiles the provided [`Project`], throws if there's any compiler error and logs whether
 /// compilation was successful or if there was a cache hit.
@@ -18,6 +30,7 @@ pub fn compile(
 // https://eips.ethereum.org/EIPS/eip-170
 const CONTRACT_SIZE_LIMIT: usize = 24576;
 
+/// The runtime sizes of the contracts of a project
 pub struct SizeReport {
     pub contracts: BTreeMap<String, ContractInfo>,
 }
@@ -25,9 +38,48 @@ pub struct SizeReport {
 pub struct ContractInfo {
     pub size: usize,
     pub is_test_contract: bool,
+    /// The estimated gas of the deployment, `None` if the bytecode is not linked
+    pub deployment_gas: Option<u64>,
+    /// The number of public and external functions
+    pub external_functions: usize,
 }
 
 impl SizeReport {
+    /// Creates the report of all contracts of the output, including the cached ones
+    pub fn new(output: &ProjectCompileOutput) -> Self {
+        let contracts = output
+            .clone()
+            .into_artifacts()
+            .map(|(id, artifact)| {
+                let CompactContractBytecode { abi, bytecode, deployed_bytecode } = artifact.into();
+                let size = deployed_bytecode
+                    .and_then(|code| code.bytecode?.object.into_bytes())
+                    .map_or(0, |bytes| bytes.len());
+                let is_test_contract = abi.as_ref().map_or(false, |abi| {
+                    abi.functions()
+                        .any(|func| func.name.starts_with("test") || func.name == "IS_TEST")
+                });
+                let deployment_gas = bytecode
+                    .and_then(|code| code.object.into_bytes())
+                    .map(|code| deployment_gas(&code, size));
+                let external_functions = abi.map_or(0, |abi| abi.functions().count());
+                let info =
+                    ContractInfo { size, is_test_contract, deployment_gas, external_functions };
+                (id.name, info)
+            })
+            .collect();
+        Self { contracts }
+    }
+
+    /// Returns the names of the contracts that exceed the size limit, excluding test contracts.
+    pub fn oversized(&self) -> Vec<&str> {
+        self.contracts
+            .iter()
+            .filter(|(_, c)| !c.is_test_contract && c.size > CONTRACT_SIZE_LIMIT)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
     /// Returns the size of the largest contract, excluding test contracts.
     pub fn max_size(&self) -> usize {
         let mut max_size = 0;
@@ -43,6 +95,74 @@ impl SizeReport {
     pub fn exceeds_size_limit(&self) -> bool {
         self.max_size() > CONTRACT_SIZE_LIMIT
     }
+
+    /// Returns a note on the size, the estimated deployment gas and the number of external
+    /// functions of every contract, excluding test contracts
+    pub fn notes(&self, limits: &ContractLimits) -> String {
+        let mut notes = String::new();
+        let contracts = self.contracts.iter().filter(|(_, c)| !c.is_test_contract && c.size > 0);
+        for (name, contract) in contracts {
+            let exceeded = !contract.exceeded_limits(limits).is_empty() ||
+                contract.size > CONTRACT_SIZE_LIMIT;
+            let gas = contract.deployment_gas.map_or_else(
+                || "unknown deployment gas of the unlinked bytecode".to_string(),
+                |gas| format!("~{gas} gas to deploy"),
+            );
+            let note = format!(
+                "{name}: {:.3} kB ({:.1}% of the size limit), {gas}, {} external functions",
+                contract.size as f64 / 1000.0,
+                contract.size as f64 * 100.0 / CONTRACT_SIZE_LIMIT as f64,
+                contract.external_functions
+            );
+            let label = if exceeded { Paint::red("Note:") } else { Paint::cyan("Note:") };
+            notes.push_str(&format!("{} {note}\n", label.bold()));
+        }
+        notes
+    }
+
+    /// Returns the limits the contracts exceed, excluding test contracts
+    pub fn exceeded_limits(&self, limits: &ContractLimits) -> Vec<String> {
+        self.contracts
+            .iter()
+            .filter(|(_, c)| !c.is_test_contract)
+            .flat_map(|(name, contract)| {
+                let exceeded = contract.exceeded_limits(limits).into_iter();
+                exceeded.map(move |limit| format!("{name}: {limit}"))
+            })
+            .collect()
+    }
+}
+
+impl ContractInfo {
+    /// Returns the limits the contract exceeds
+    fn exceeded_limits(&self, limits: &ContractLimits) -> Vec<String> {
+        let mut exceeded = Vec::new();
+        if let Some(max_size) = limits.max_size.filter(|max| self.size > *max) {
+            exceeded.push(format!("{} bytes of code exceed max_size = {max_size}", self.size));
+        }
+        if let (Some(max), Some(gas)) = (limits.max_deployment_gas, self.deployment_gas) {
+            if gas > max {
+                exceeded.push(format!("~{gas} deployment gas exceeds max_deployment_gas = {max}"));
+            }
+        }
+        let external_functions = self.external_functions;
+        if let Some(max) = limits.max_external_functions.filter(|max| external_functions > *max) {
+            exceeded.push(format!(
+                "{external_functions} external functions exceed max_external_functions = {max}"
+            ));
+        }
+        exceeded
+    }
+}
+
+/// Returns the estimated gas of a transaction that deploys the creation code, without the gas of
+/// the constructor execution
+///
+/// This is the intrinsic gas of a contract creation, the calldata cost of the creation code and
+/// the code deposit cost of the deployed code.
+fn deployment_gas(creation_code: &[u8], deployed_size: usize) -> u64 {
+    let calldata: u64 = creation_code.iter().map(|byte| if *byte == 0 { 4 } else { 16 }).sum();
+    21_000 + 32_000 + calldata + 200 * deployed_size as u64
 }
 
 impl Display for SizeReport {
@@ -72,6 +192,17 @@ impl Display for SizeReport {
         }
 
         writeln!(f, "{}", table)?;
+
+        let oversized = self.oversized();
+        if !oversized.is_empty() {
+            writeln!(
+                f,
+                "{} Contracts exceed the size limit of {CONTRACT_SIZE_LIMIT} bytes (EIP-170) and \
+                 can not be deployed on mainnet: {}",
+                Paint::yellow("Warning:").bold(),
+                oversized.join(", ")
+            )?;
+        }
         Ok(())
     }
 }
@@ -86,12 +217,46 @@ pub struct ProjectCompiler {
     print_names: bool,
     /// whether to also print the contract sizes
     print_sizes: bool,
+    /// whether to fail if a contract exceeds the contract size limit
+    deny_oversized: bool,
+    /// whether to print a note on the size and deployment gas of every contract
+    print_notes: bool,
+    /// the limits the contracts must not exceed
+    limits: ContractLimits,
 }
 
 impl ProjectCompiler {
     /// Create a new instance with the settings
     pub fn new(print_names: bool, print_sizes: bool) -> Self {
-        Self { print_names, print_sizes }
+        Self {
+            print_names,
+            print_sizes,
+            deny_oversized: false,
+            print_notes: false,
+            limits: Default::default(),
+        }
+    }
+
+    /// Fails the compilation if a contract exceeds the contract size limit of EIP-170
+    #[must_use]
+    pub fn with_deny_oversized(mut self, deny_oversized: bool) -> Self {
+        self.deny_oversized = deny_oversized;
+        self
+    }
+
+    /// Prints a note on the size, the estimated deployment gas and the number of external
+    /// functions of every contract
+    #[must_use]
+    pub fn with_notes(mut self, print_notes: bool) -> Self {
+        self.print_notes = print_notes;
+        self
+    }
+
+    /// Fails the compilation if a contract exceeds one of the limits
+    #[must_use]
+    pub fn with_limits(mut self, limits: ContractLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Compiles the project with [`Project::compile()`]
@@ -125,7 +290,8 @@ impl ProjectCompiler {
     where
         F: FnOnce(&Project) -> eyre::Result<ProjectCompileOutput>,
     {
-        let ProjectCompiler { print_sizes, print_names } = self;
+        let ProjectCompiler { print_sizes, print_names, deny_oversized, print_notes, limits } =
+            self;
         if !project.paths.sources.exists() {
             eyre::bail!(
                 r#"no contracts to compile, contracts folder "{}" does not exist.
@@ -140,6 +306,7 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
         let now = std::time::Instant::now();
         tracing::trace!(target : "forge_compile", "start compiling project");
 
+        invalidate_remapped_sources(project)?;
         let output = term::with_spinner_reporter(|| f(project))?;
 
         let elapsed = now.elapsed();
@@ -147,13 +314,15 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
 
         if output.has_compiler_errors() {
             eyre::bail!(output.to_string())
-        } else if output.is_unchanged() {
+        }
+        record_remappings(project)?;
+        if output.is_unchanged() {
             println!("No files changed, compilation skipped");
         } else {
             // print the compiler output / warnings
             println!("{output}");
 
-            // print any sizes or names
+            // print any names
             if print_names {
                 let compiled_contracts = output.compiled_contracts_by_compiler_version();
                 for (version, contracts) in compiled_contracts.into_iter() {
@@ -166,35 +335,33 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
                     }
                 }
             }
+        }
+
+        if print_sizes || deny_oversized || print_notes || !limits.is_empty() {
+            let size_report = SizeReport::new(&output);
             if print_sizes {
                 // add extra newline if names were already printed
                 if print_names {
                     println!();
                 }
-                let compiled_contracts = output.compiled_contracts_by_compiler_version();
-                let mut size_report = SizeReport { contracts: BTreeMap::new() };
-                for (_, contracts) in compiled_contracts.into_iter() {
-                    for (name, contract) in contracts {
-                        let size = contract
-                            .get_bytecode_bytes()
-                            .map(|bytes| bytes.0.len())
-                            .unwrap_or_default();
-
-                        let test_functions =
-                            contract.abi.as_ref().unwrap().abi.functions().into_iter().filter(
-                                |func| func.name.starts_with("test") || func.name.eq("IS_TEST"),
-                            );
-
-                        let is_test_contract = test_functions.into_iter().count() > 0;
-                        size_report.contracts.insert(name, ContractInfo { size, is_test_contract });
-                    }
-                }
-
                 println!("{size_report}");
-
-                // exit with error if any contract exceeds the size limit, excluding test contracts.
-                let exit_status = if size_report.exceeds_size_limit() { 1 } else { 0 };
-                std::process::exit(exit_status);
+            }
+            if print_notes {
+                print!("{}", size_report.notes(&limits));
+            }
+            if deny_oversized && size_report.exceeds_size_limit() {
+                eyre::bail!(
+                    "Contracts exceed the size limit of {CONTRACT_SIZE_LIMIT} bytes (EIP-170), \
+                     which `deny_oversized_contracts` does not allow: {}",
+                    size_report.oversized().join(", ")
+                )
+            }
+            let exceeded = size_report.exceeded_limits(&limits);
+            if !exceeded.is_empty() {
+                eyre::bail!(
+                    "Contracts exceed the limits of `[contract_limits]`:\n{}",
+                    exceeded.join("\n")
+                )
             }
         }
 
@@ -217,6 +384,7 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
         );
     }
 
+    invalidate_remapped_sources(project)?;
     let output = ethers::solc::report::with_scoped(
         &ethers::solc::report::Report::new(NoReporter::default()),
         || project.compile(),
@@ -225,10 +393,19 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
     if output.has_compiler_errors() {
         eyre::bail!(output.to_string())
     }
+    record_remappings(project)?;
 
     Ok(output)
 }
 
+/// Compiles the project without any output, and without failing on compiler errors
+pub fn compile_silently(project: &Project) -> eyre::Result<ProjectCompileOutput> {
+    Ok(ethers::solc::report::with_scoped(
+        &ethers::solc::report::Report::new(NoReporter::default()),
+        || project.compile(),
+    )?)
+}
+
 /// Compile a set of files not necessarily included in the `project`'s source dir
 pub fn compile_files(project: &Project, files: Vec<PathBuf>) -> eyre::Result<ProjectCompileOutput> {
     let output = term::with_spinner_reporter(|| project.compile_files(files))?;
@@ -239,3 +416,179 @@ pub fn compile_files(project: &Project, files: Vec<PathBuf>) -> eyre::Result<Pro
     println!("{output}");
     Ok(output)
 }
+
+/// The name of the file next to the solidity files cache that records the remappings the imports
+/// of every source file were resolved with
+pub const REMAPPINGS_CACHE_FILENAME: &str = "remappings-cache.json";
+
+static IMPORT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s*import\s+(?:[^;]*?\bfrom\s+)?["'](?P<path>[^"']+)["']"#).unwrap()
+});
+
+/// The remappings the imports of every source file were resolved with in the last build,
+/// `source file -> [prefix=path]`
+///
+/// The solidity files cache invalidates a source file if its content hash or the solc settings,
+/// e.g. the optimizer settings, changed, but not if its imports resolve to different files
+/// because the remappings changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct RemappingsCache {
+    files: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl RemappingsCache {
+    /// Records the remappings of all source files in the solidity files cache
+    fn new(project: &Project, cache: &SolFilesCache) -> Self {
+        let root = &project.paths.root;
+        let files = cache
+            .files
+            .keys()
+            .filter_map(|file| {
+                let content = std::fs::read_to_string(root.join(file)).ok()?;
+                Some((root.join(file), applied_remappings(&project.paths.remappings, &content)))
+            })
+            .collect();
+        Self { files }
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+    }
+}
+
+/// Returns the remappings the imports of the source are resolved with, solc applies the one
+/// with the longest matching prefix
+fn applied_remappings(remappings: &[Remapping], content: &str) -> BTreeSet<String> {
+    IMPORT_REGEX
+        .captures_iter(content)
+        .filter_map(|cap| {
+            let import = cap.name("path")?.as_str();
+            remappings
+                .iter()
+                .filter(|remapping| import.starts_with(&remapping.name))
+                .max_by_key(|remapping| remapping.name.len())
+                .map(|remapping| remapping.to_string())
+        })
+        .collect()
+}
+
+/// Removes the source files whose imports are resolved with different remappings than in the
+/// last build, and all files that import them, from the solidity files cache, so only they are
+/// compiled again
+fn invalidate_remapped_sources(project: &Project) -> eyre::Result<()> {
+    if !project.cached || !project.paths.cache.exists() {
+        return Ok(())
+    }
+    let previous = match RemappingsCache::read(&remappings_cache_path(project)) {
+        Some(previous) => previous,
+        None => return Ok(()),
+    };
+    let mut cache = SolFilesCache::read(&project.paths.cache)?;
+    let root = &project.paths.root;
+    let current = RemappingsCache::new(project, &cache);
+
+    let mut dirty: BTreeSet<PathBuf> = current
+        .files
+        .iter()
+        .filter(|(file, remappings)| {
+            previous.files.get(*file).map_or(false, |previous| previous != *remappings)
+        })
+        .map(|(file, _)| file.clone())
+        .collect();
+    if dirty.is_empty() {
+        return Ok(())
+    }
+
+    // Files that import a dirty file, directly or indirectly, are dirty as well
+    loop {
+        let importers: Vec<PathBuf> = cache
+            .files
+            .iter()
+            .filter(|(file, entry)| {
+                !dirty.contains(&root.join(file)) &&
+                    entry.imports.iter().any(|import| dirty.contains(&root.join(import)))
+            })
+            .map(|(file, _)| root.join(file))
+            .collect();
+        if importers.is_empty() {
+            break
+        }
+        dirty.extend(importers);
+    }
+
+    tracing::trace!(target : "forge_compile", ?dirty, "remappings changed");
+    cache.files.retain(|file, _| !dirty.contains(&root.join(file)));
+    cache.write(&project.paths.cache)?;
+    Ok(())
+}
+
+/// Records the remappings the imports of the compiled source files were resolved with
+fn record_remappings(project: &Project) -> eyre::Result<()> {
+    if !project.cached || !project.paths.cache.exists() {
+        return Ok(())
+    }
+    let cache = SolFilesCache::read(&project.paths.cache)?;
+    let remappings = RemappingsCache::new(project, &cache);
+    std::fs::write(remappings_cache_path(project), serde_json::to_string_pretty(&remappings)?)?;
+    Ok(())
+}
+
+fn remappings_cache_path(project: &Project) -> PathBuf {
+    project.paths.cache.with_file_name(REMAPPINGS_CACHE_FILENAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_applied_remappings() {
+        let remappings: Vec<Remapping> = vec![
+            "ds-test/=lib/ds-test/src/".parse().unwrap(),
+            "@oz/=lib/openzeppelin/contracts/".parse().unwrap(),
+            "@oz/token/=lib/oz-token/".parse().unwrap(),
+        ];
+        let source = r#"
+import "ds-test/test.sol";
+import {ERC20} from "@oz/token/ERC20.sol";
+import * as Utils from "@oz/utils/Address.sol";
+import {
+    A,
+    B
+} from "./Local.sol";
+"#;
+        assert_eq!(
+            applied_remappings(&remappings, source).into_iter().collect::<Vec<_>>(),
+            vec![
+                "@oz/=lib/openzeppelin/contracts/".to_string(),
+                "@oz/token/=lib/oz-token/".to_string(),
+                "ds-test/=lib/ds-test/src/".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_check_contract_limits() {
+        // 53000 intrinsic gas, 2 * 16 + 4 calldata gas and 200 gas per deployed byte
+        assert_eq!(deployment_gas(&[0x60, 0x80, 0x00], 10), 55_036);
+
+        let contract = ContractInfo {
+            size: 30_000,
+            is_test_contract: false,
+            deployment_gas: Some(6_000_000),
+            external_functions: 12,
+        };
+        let limits = ContractLimits {
+            max_size: Some(24_000),
+            max_deployment_gas: Some(5_000_000),
+            max_external_functions: Some(20),
+        };
+        assert_eq!(
+            contract.exceeded_limits(&limits),
+            vec![
+                "30000 bytes of code exceed max_size = 24000".to_string(),
+                "~6000000 deployment gas exceeds max_deployment_gas = 5000000".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! The kinds of errors cast exits with
+//!
+//! Scripts can tell them apart by the exit code of cast, or by the JSON object `--json-errors`
+//! prints, instead of matching the error message.
+use serde_json::json;
+use std::{error::Error, io};
+
+/// The kind of an error, each kind has its own exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Any other error
+    Other,
+    /// The arguments could not be parsed, e.g. an invalid address or signature
+    BadInput,
+    /// The RPC endpoint could not be reached or timed out
+    Connection,
+    /// The call or transaction reverted
+    Revert,
+    /// The data could not be decoded, e.g. with the given signature
+    Decoding,
+}
+
+impl ErrorKind {
+    /// The kinds in the order they take precedence in if an error has causes of multiple kinds,
+    /// e.g. a decoding error caused by a connection that was closed
+    const PRECEDENCE: [ErrorKind; 4] =
+        [ErrorKind::Connection, ErrorKind::Revert, ErrorKind::Decoding, ErrorKind::BadInput];
+
+    /// Returns the exit code of cast for this kind of error
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::BadInput => 2,
+            ErrorKind::Connection => 3,
+            ErrorKind::Revert => 4,
+            ErrorKind::Decoding => 5,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "other",
+            ErrorKind::BadInput => "bad_input",
+            ErrorKind::Connection => "connection",
+            ErrorKind::Revert => "revert",
+            ErrorKind::Decoding => "decoding",
+        }
+    }
+
+    /// Returns whether running the command again may succeed
+    pub fn is_transient(self) -> bool {
+        self == ErrorKind::Connection
+    }
+
+    /// Classifies the error by the kinds of its causes
+    pub fn classify(err: &eyre::Report) -> Self {
+        let kinds: Vec<ErrorKind> = err.chain().filter_map(Self::of_cause).collect();
+        Self::PRECEDENCE.into_iter().find(|kind| kinds.contains(kind)).unwrap_or(ErrorKind::Other)
+    }
+
+    /// Returns the kind of a single cause of an error, by its type or its message
+    fn of_cause(cause: &(dyn Error + 'static)) -> Option<Self> {
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            if err.is_connect() || err.is_timeout() || err.is_request() {
+                return Some(ErrorKind::Connection)
+            }
+        }
+        if let Some(err) = cause.downcast_ref::<io::Error>() {
+            if matches!(
+                err.kind(),
+                io::ErrorKind::ConnectionRefused |
+                    io::ErrorKind::ConnectionReset |
+                    io::ErrorKind::ConnectionAborted |
+                    io::ErrorKind::NotConnected |
+                    io::ErrorKind::TimedOut |
+                    io::ErrorKind::BrokenPipe
+            ) {
+                return Some(ErrorKind::Connection)
+            }
+        }
+        if let Some(err) = cause.downcast_ref::<ethers::abi::Error>() {
+            return Some(match err {
+                ethers::abi::Error::InvalidData => ErrorKind::Decoding,
+                _ => ErrorKind::BadInput,
+            })
+        }
+        if cause.is::<ethers::contract::AbiError>() {
+            return Some(ErrorKind::Decoding)
+        }
+        if cause.is::<rustc_hex::FromHexError>() ||
+            cause.is::<hex::FromHexError>() ||
+            cause.is::<std::num::ParseIntError>()
+        {
+            return Some(ErrorKind::BadInput)
+        }
+
+        // JSON-RPC errors of the node are only known by their message
+        let message = cause.to_string().to_lowercase();
+        if message.contains("execution reverted") || message.starts_with("revert") {
+            Some(ErrorKind::Revert)
+        } else if message.contains("could not decode") || message.contains("failed to decode") {
+            Some(ErrorKind::Decoding)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the JSON object `--json-errors` prints for the error
+pub fn json_envelope(err: &eyre::Report, kind: ErrorKind) -> serde_json::Value {
+    let causes: Vec<String> = err.chain().skip(1).map(|cause| cause.to_string()).collect();
+    json!({
+        "error": {
+            "kind": kind.as_str(),
+            "code": kind.exit_code(),
+            "transient": kind.is_transient(),
+            "message": err.to_string(),
+            "causes": causes,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::WrapErr;
+
+    #[test]
+    fn can_classify_errors() {
+        let bad_input: eyre::Result<u64> =
+            "0xzz".parse::<u64>().wrap_err("Failed to parse the block number");
+        assert_eq!(ErrorKind::classify(&bad_input.unwrap_err()), ErrorKind::BadInput);
+
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "connection refused");
+        let connection = eyre::Report::new(refused).wrap_err("Failed to get the block number");
+        assert_eq!(ErrorKind::classify(&connection), ErrorKind::Connection);
+        assert!(ErrorKind::classify(&connection).is_transient());
+
+        let revert = eyre::eyre!("(code: 3, message: execution reverted: not owner, data: None)");
+        assert_eq!(ErrorKind::classify(&revert), ErrorKind::Revert);
+
+        assert_eq!(ErrorKind::classify(&eyre::eyre!("No wallet found")), ErrorKind::Other);
+    }
+
+    #[test]
+    fn can_create_json_envelope() {
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "connection refused");
+        let err = eyre::Report::new(refused).wrap_err("Failed to get the block number");
+        let envelope = json_envelope(&err, ErrorKind::classify(&err));
+        assert_eq!(
+            envelope,
+            json!({
+                "error": {
+                    "kind": "connection",
+                    "code": 3,
+                    "transient": true,
+                    "message": "Failed to get the block number",
+                    "causes": ["connection refused"],
+                }
+            })
+        );
+    }
+}
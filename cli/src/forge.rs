@@ -1,15 +1,17 @@
 pub mod cmd;
 pub mod compile;
 mod opts;
+mod profile;
 mod suggestions;
 mod term;
 mod utils;
 
 use crate::cmd::{
-    forge::{cache::CacheSubcommands, watch},
+    forge::{cache::CacheSubcommands, selectors::SelectorsSubcommands, watch},
     Cmd,
 };
 use opts::forge::{Dependency, Opts, Subcommands};
+use profile::Profiler;
 use std::process::Command;
 
 use clap::{IntoApp, Parser};
@@ -17,11 +19,25 @@ use clap_complete::generate;
 
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
-    utils::subscriber();
+    let opts = Opts::parse();
+
+    let profiler = opts.profile_exec.map(Profiler::new);
+    match &profiler {
+        Some(profiler) => profiler.clone().init(),
+        None => utils::subscriber(),
+    }
     utils::enable_paint();
+    utils::set_offline(opts.offline);
 
-    let opts = Opts::parse();
-    match opts.sub {
+    let result = run(opts.sub);
+    if let Some(profiler) = profiler {
+        profiler.report()?;
+    }
+    result
+}
+
+fn run(sub: Subcommands) -> eyre::Result<()> {
+    match sub {
         Subcommands::Test(cmd) => {
             if cmd.is_watch() {
                 utils::block_on(watch::watch_test(cmd))?;
@@ -49,6 +65,9 @@ fn main() -> eyre::Result<()> {
         Subcommands::VerifyCheck(args) => {
             utils::block_on(args.run())?;
         }
+        Subcommands::VerifyBuild(cmd) => {
+            cmd.run()?;
+        }
         Subcommands::Cache(cmd) => match cmd.sub {
             CacheSubcommands::Clean(cmd) => {
                 cmd.run()?;
@@ -57,6 +76,17 @@ fn main() -> eyre::Result<()> {
                 cmd.run()?;
             }
         },
+        Subcommands::Selectors(cmd) => match cmd.sub {
+            SelectorsSubcommands::List(cmd) => {
+                cmd.run()?;
+            }
+            SelectorsSubcommands::Collisions(cmd) => {
+                cmd.run()?;
+            }
+            SelectorsSubcommands::Upload(cmd) => {
+                cmd.run()?;
+            }
+        },
         Subcommands::Create(cmd) => {
             cmd.run()?;
         }
@@ -85,6 +115,9 @@ fn main() -> eyre::Result<()> {
         Subcommands::Init(cmd) => {
             cmd.run()?;
         }
+        Subcommands::Migrate(cmd) => {
+            cmd.run()?;
+        }
         Subcommands::Completions { shell } => {
             generate(shell, &mut Opts::command(), "forge", &mut std::io::stdout())
         }
@@ -99,12 +132,33 @@ fn main() -> eyre::Result<()> {
                 cmd.run()?;
             }
         }
-         Subcommands::Fmt(cmd) => {
-             cmd.run()?;
-         }
+        Subcommands::Fmt(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::Doc(cmd) => {
+            cmd.run()?;
+        }
         Subcommands::Config(cmd) => {
             cmd.run()?;
         }
+        Subcommands::Coverage(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::DecodeTrace(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::GasDiff(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::Bench(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::Mutate(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::Analyze(cmd) => {
+            cmd.run()?;
+        }
         Subcommands::Flatten(cmd) => {
             cmd.run()?;
         }
@@ -1,6 +1,9 @@
 use super::{ClapChain, EthereumOpts, Wallet};
 use crate::{
-    cmd::cast::{find_block::FindBlockArgs, run::RunArgs},
+    cmd::cast::{
+        da::DaArgs, find_block::FindBlockArgs, fuzz::FuzzArgs, interface_check::InterfaceCheckArgs,
+        monitor::MonitorArgs, run::RunArgs, source_map::SourceMapArgs,
+    },
     utils::{parse_ether_value, parse_u256},
 };
 use clap::{Parser, Subcommand, ValueHint};
@@ -351,6 +354,69 @@ This is automatically enabled for common networks without EIP1559."#
         #[clap(flatten)]
         eth: EthereumOpts,
     },
+    #[clap(name = "bump")]
+    #[clap(about = "Replace a pending transaction with the same transaction with higher fees.")]
+    Bump {
+        #[clap(help = "The hash of the pending transaction.", value_name = "TX_HASH")]
+        hash: H256,
+        #[clap(
+            long,
+            help = "The factor the fees of the pending transaction are multiplied by.",
+            long_help = r#"The factor the fees of the pending transaction are multiplied by.
+
+Nodes only accept replacements whose fees are at least 10% higher. If the current fees of the network are higher, they are used instead."#,
+            default_value = "1.2",
+            value_name = "FACTOR"
+        )]
+        fee_multiplier: f64,
+        #[clap(long, env = "CAST_ASYNC")]
+        cast_async: bool,
+        #[clap(
+            short,
+            long,
+            help = "The number of confirmations until the receipt is fetched.",
+            default_value = "1"
+        )]
+        confirmations: usize,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+        #[clap(flatten)]
+        eth: EthereumOpts,
+    },
+    #[clap(name = "cancel")]
+    #[clap(
+        about = "Cancel a pending transaction by replacing it with an empty transaction with higher fees."
+    )]
+    Cancel {
+        #[clap(
+            help = "The nonce of the pending transaction of the wallet.",
+            parse(try_from_str = parse_u256)
+        )]
+        nonce: U256,
+        #[clap(
+            long,
+            help = "The factor the fees of the pending transaction are multiplied by.",
+            long_help = r#"The factor the fees of the pending transaction are multiplied by.
+
+Nodes only accept replacements whose fees are at least 10% higher. If the current fees of the network are higher, they are used instead."#,
+            default_value = "1.2",
+            value_name = "FACTOR"
+        )]
+        fee_multiplier: f64,
+        #[clap(long, env = "CAST_ASYNC")]
+        cast_async: bool,
+        #[clap(
+            short,
+            long,
+            help = "The number of confirmations until the receipt is fetched.",
+            default_value = "1"
+        )]
+        confirmations: usize,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+        #[clap(flatten)]
+        eth: EthereumOpts,
+    },
     #[clap(name = "estimate")]
     #[clap(alias = "e")]
     #[clap(about = "Estimate the gas cost of a transaction.")]
@@ -459,13 +525,13 @@ The index can also be earliest or latest."#
         about = "Pretty print calldata.",
         long_about = r#"Pretty print calldata.
 
-Tries to decode the calldata using 4byte.directory unless --offline is passed."#
+Tries to decode the calldata using 4byte.directory unless --offline or -o is passed."#
     )]
     PrettyCalldata {
         #[clap(help = "The calldata.")]
         calldata: String,
-        #[clap(long, short, help = "Skip the 4byte directory lookup.")]
-        offline: bool,
+        #[clap(short = 'o', help = "Skip the 4byte directory lookup, like --offline.")]
+        skip_lookup: bool,
     },
     #[clap(name = "age")]
     #[clap(alias = "a")]
@@ -696,6 +762,36 @@ If an address is specified, then the ABI is fetched from Etherscan."#
         about = "Runs a published transaction in a local environment and prints the trace."
     )]
     Run(RunArgs),
+    #[clap(
+        name = "source-map",
+        about = "Resolves a program counter of a contract to its location in the source code."
+    )]
+    SourceMap(SourceMapArgs),
+    #[clap(
+        name = "fuzz",
+        about = "Call a function of a deployed contract with generated inputs and report reverts and anomalies."
+    )]
+    Fuzz(FuzzArgs),
+    #[clap(
+        name = "interface-check",
+        about = "Detect the standards a deployed contract implements via ERC-165 and its function selectors."
+    )]
+    InterfaceCheck(InterfaceCheckArgs),
+    #[clap(
+        name = "monitor",
+        about = "Watch the events of contracts on one or more chains and forward them as JSON to stdout, a webhook or a file."
+    )]
+    Monitor(MonitorArgs),
+    #[clap(
+        name = "da",
+        about = "Utilities for EIP-4844 blobs: encode data into blobs, versioned hashes and blob gas.",
+        long_about = r#"Utilities for EIP-4844 blobs: encode data into blobs, versioned hashes and blob gas.
+
+KZG commitments and proofs of blobs are not computed, they need a KZG library and the trusted setup of the ceremony. Compute them with a KZG library such as c-kzg and pass the commitment to `cast da versioned-hash`.
+
+Blob (type-3) transactions can't be sent with cast yet."#
+    )]
+    Da(DaArgs),
 }
 
 #[derive(Debug, Parser)]
@@ -748,6 +844,30 @@ pub enum WalletSubcommands {
         #[clap(flatten)]
         wallet: Wallet,
     },
+    #[clap(
+        name = "sign-auth",
+        about = "Sign an EIP-7702 authorization to delegate the wallet to an implementation.",
+        long_about = r#"Sign an EIP-7702 authorization to delegate the wallet to an implementation.
+
+Prints the RLP encoded authorization, which can be included in the authorization list of a type 4
+transaction. Only local wallets can sign authorizations.
+
+cast can't send type 4 transactions yet, and anvil does not accept them."#
+    )]
+    SignAuth {
+        #[clap(help = "The address of the implementation to delegate to.")]
+        implementation: Address,
+        #[clap(long, help = "The nonce of the wallet the authorization is valid for.")]
+        nonce: u64,
+        #[clap(
+            long,
+            help = "The chain ID the authorization is valid on, 0 for all chains.",
+            default_value = "1"
+        )]
+        chain_id: u64,
+        #[clap(flatten)]
+        wallet: Wallet,
+    },
     #[clap(name = "verify", alias = "v", about = "Verify the signature of a message.")]
     Verify {
         #[clap(help = "The original message.")]
@@ -791,4 +911,30 @@ fn parse_slot(s: &str) -> eyre::Result<H256> {
 pub struct Opts {
     #[clap(subcommand)]
     pub sub: Subcommands,
+
+    #[clap(
+        help = "Do not access the network.",
+        long_help = r#"Do not access the network.
+
+Compilers, fork state and signatures are only read from the local caches, anything that is not
+cached is an error. Can also be enabled with FOUNDRY_OFFLINE=1."#,
+        long,
+        global = true
+    )]
+    pub offline: bool,
+
+    #[clap(
+        help = "Print errors as a JSON object with their kind and exit code.",
+        long_help = r#"Print errors as a JSON object with their kind and exit code.
+
+The exit code of cast depends on the kind of the error, the kinds are stable:
+  1  other       any other error
+  2  bad_input   the arguments could not be parsed, e.g. an invalid address
+  3  connection  the RPC endpoint could not be reached or timed out, retrying may succeed
+  4  revert      the call or transaction reverted
+  5  decoding    the data could not be decoded, e.g. with the given signature"#,
+        long,
+        global = true
+    )]
+    pub json_errors: bool,
 }
@@ -4,20 +4,30 @@ use ethers::solc::{artifacts::output_selection::ContractOutputSelection, EvmVers
 use std::{path::PathBuf, str::FromStr};
 
 use crate::cmd::forge::{
+    analyze::AnalyzeArgs,
+    bench::BenchArgs,
     bind::BindArgs,
     build::BuildArgs,
     cache::CacheArgs,
     config,
+    coverage::CoverageArgs,
     create::CreateArgs,
+    decode_trace::DecodeTraceArgs,
+    doc::DocArgs,
     flatten,
     fmt::FmtArgs,
+    gas_diff::GasDiffArgs,
     init::InitArgs,
     inspect,
     install::InstallArgs,
+    migrate::MigrateArgs,
+    mutate::MutateArgs,
     remappings::RemappingArgs,
     run::RunArgs,
+    selectors::SelectorsArgs,
     snapshot, test, tree,
     verify::{VerifyArgs, VerifyCheckArgs},
+    verify_build::VerifyBuildArgs,
 };
 use serde::Serialize;
 
@@ -37,6 +47,34 @@ static GH_REPO_PREFIX_REGEX: Lazy<Regex> = Lazy::new(|| {
 pub struct Opts {
     #[clap(subcommand)]
     pub sub: Subcommands,
+
+    #[clap(
+        help = "Do not access the network.",
+        long_help = r#"Do not access the network.
+
+Compilers, fork state and signatures are only read from the local caches, anything that is not
+cached is an error. Can also be enabled with FOUNDRY_OFFLINE=1."#,
+        long,
+        global = true
+    )]
+    pub offline: bool,
+
+    #[clap(
+        help = "Report where the wall time of the command went.",
+        long_help = r#"Report where the wall time of the command went.
+
+The time spent loading the config, resolving the dependency graph, running solc, executing each
+test, decoding traces and waiting for RPC responses is reported as a table, or with
+`--profile-exec=json` written to forge-profile.json in the chrome tracing format."#,
+        long,
+        global = true,
+        value_name = "FORMAT",
+        possible_values = ["table", "json"],
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "table"
+    )]
+    pub profile_exec: Option<ProfileFormat>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -105,12 +143,18 @@ pub enum Subcommands {
     )]
     VerifyCheck(VerifyCheckArgs),
 
+    #[clap(about = "Check that a build is reproducible from the solc input in its build info.")]
+    VerifyBuild(VerifyBuildArgs),
+
     #[clap(alias = "c", about = "Deploy a smart contract.")]
     Create(CreateArgs),
 
     #[clap(about = "Create a new Forge project.")]
     Init(InitArgs),
 
+    #[clap(about = "Migrate a hardhat or truffle project to Forge.")]
+    Migrate(MigrateArgs),
+
     #[clap(alias = "com", about = "Generate shell completions script")]
     Completions {
         #[clap(arg_enum)]
@@ -136,12 +180,36 @@ pub enum Subcommands {
     #[clap(alias = "co", about = "Display the current config.")]
     Config(config::ConfigArgs),
 
+    #[clap(about = "Generate a coverage report of the project's tests.")]
+    Coverage(CoverageArgs),
+
+    #[clap(about = "Decode a transaction trace recorded by a node with the project's ABIs.")]
+    DecodeTrace(DecodeTraceArgs),
+
+    #[clap(about = "Compare the gas usage of the project's functions with a git ref.")]
+    GasDiff(GasDiffArgs),
+
+    #[clap(about = "Run the project's benchmarks and compare them with a baseline.")]
+    Bench(BenchArgs),
+
+    #[clap(about = "Mutate the project's sources and report the mutants its tests don't detect.")]
+    Mutate(MutateArgs),
+
+    #[clap(about = "Run static analyzers on the project and report their findings.")]
+    Analyze(AnalyzeArgs),
+
     #[clap(alias = "f", about = "Flatten a source file and all of its imports into one file.")]
     Flatten(flatten::FlattenArgs),
 
     #[clap(about = "formats Solidity source files")]
     Fmt(FmtArgs),
 
+    #[clap(about = "Generate documentation of the project's contracts from their natspec.")]
+    Doc(DocArgs),
+
+    #[clap(about = "List, check and upload the function selectors of the project's contracts.")]
+    Selectors(SelectorsArgs),
+
     #[clap(alias = "in", about = "Get specialized information about a smart contract")]
     Inspect(inspect::InspectArgs),
 
@@ -152,6 +220,27 @@ pub enum Subcommands {
     Tree(tree::TreeArgs),
 }
 
+/// How `--profile-exec` reports the profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// A table of the time spent in each phase, printed to stderr
+    Table,
+    /// A chrome tracing JSON file, for `chrome://tracing` or <https://ui.perfetto.dev>
+    Json,
+}
+
+impl FromStr for ProfileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(ProfileFormat::Table),
+            "json" => Ok(ProfileFormat::Json),
+            _ => Err(format!("Unknown profile format `{s}`, expected `table` or `json`")),
+        }
+    }
+}
+
 // A set of solc compiler settings that can be set via command line arguments, which are intended
 // to be merged into an existing `foundry_config::Config`.
 //
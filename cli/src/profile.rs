@@ -0,0 +1,326 @@
+//! Profiler of `forge --profile-exec`
+//!
+//! The profiler is a tracing layer that records the spans and events of the
+//! [`foundry_config::PROFILE_TARGET`] target: the phases of an invocation, e.g. loading the config,
+//! running solc or executing a test.
+use crate::opts::forge::ProfileFormat;
+use foundry_config::PROFILE_TARGET;
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    Event, Subscriber,
+};
+use tracing_error::ErrorLayer;
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+use yansi::Paint;
+
+/// The file the chrome tracing profile is written to
+pub const CHROME_TRACE_FILENAME: &str = "forge-profile.json";
+
+/// The order the phases are reported in, phases that are not listed are reported last
+const PHASES: &[&str] = &["config", "graph", "solc", "evm", "decode", "rpc"];
+
+/// A timed span or event
+#[derive(Debug, Clone)]
+struct Record {
+    /// The phase, the name of the span
+    phase: String,
+    /// What was timed, e.g. the solc version or the test
+    name: Option<String>,
+    /// The index of the thread the span was created on
+    thread: u64,
+    start: Instant,
+    duration: Duration,
+}
+
+/// Records the time spent in each phase of the invocation
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    format: ProfileFormat,
+    start: Instant,
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl Profiler {
+    pub fn new(format: ProfileFormat) -> Self {
+        Self { format, start: Instant::now(), records: Default::default() }
+    }
+
+    /// Initializes the tracing subscriber for logging, like [`crate::utils::subscriber`], with the
+    /// profiler as an additional layer
+    ///
+    /// The profiling events are enabled for the profiler, but not logged.
+    pub fn init(self) {
+        let directive = format!("{PROFILE_TARGET}=info").parse().expect("valid directive");
+        let not_profiling =
+            tracing_subscriber::filter::filter_fn(|metadata| metadata.target() != PROFILE_TARGET);
+        tracing_subscriber::Registry::default()
+            .with(tracing_subscriber::EnvFilter::from_default_env().add_directive(directive))
+            .with(ErrorLayer::default())
+            .with(tracing_subscriber::fmt::layer().with_filter(not_profiling))
+            .with(self)
+            .init()
+    }
+
+    fn record(&self, record: Record) {
+        self.records.lock().unwrap().push(record);
+    }
+
+    /// Reports the recorded profile in the configured format
+    pub fn report(&self) -> eyre::Result<()> {
+        let wall_time = self.start.elapsed();
+        let records = self.records.lock().unwrap();
+        match self.format {
+            ProfileFormat::Table => {
+                eprintln!("{}", ProfileTable::new(&records, wall_time));
+            }
+            ProfileFormat::Json => {
+                let path = Path::new(CHROME_TRACE_FILENAME);
+                let trace = chrome_trace(&records, self.start);
+                std::fs::write(path, serde_json::to_string(&trace)?)?;
+                eprintln!("Wrote the profile to {}", path.display());
+                eprintln!("Open it in chrome://tracing or https://ui.perfetto.dev");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The timing of a span that is not closed yet, stored in its extensions
+struct Timing {
+    name: Option<String>,
+    thread: u64,
+    start: Instant,
+}
+
+impl<S> Layer<S> for Profiler
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().target() != PROFILE_TARGET {
+            return
+        }
+        let mut fields = ProfileFields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Timing {
+                name: fields.name,
+                thread: thread_index(),
+                start: Instant::now(),
+            });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let timing = match span.extensions_mut().remove::<Timing>() {
+            Some(timing) => timing,
+            None => return,
+        };
+        self.record(Record {
+            phase: span.name().to_string(),
+            name: timing.name,
+            thread: timing.thread,
+            start: timing.start,
+            duration: timing.start.elapsed(),
+        });
+    }
+
+    /// Records the events of phases that were timed elsewhere, e.g. by solc, they have a `phase`
+    /// and an `elapsed_us` field
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != PROFILE_TARGET {
+            return
+        }
+        let mut fields = ProfileFields::default();
+        event.record(&mut fields);
+        let (phase, elapsed) = match (fields.phase, fields.elapsed_us) {
+            (Some(phase), Some(elapsed)) => (phase, Duration::from_micros(elapsed)),
+            _ => return,
+        };
+        let now = Instant::now();
+        self.record(Record {
+            phase,
+            name: fields.name,
+            thread: thread_index(),
+            start: now.checked_sub(elapsed).unwrap_or(now),
+            duration: elapsed,
+        });
+    }
+}
+
+/// The fields of the spans and events the profiler records
+#[derive(Default)]
+struct ProfileFields {
+    name: Option<String>,
+    phase: Option<String>,
+    elapsed_us: Option<u64>,
+}
+
+impl Visit for ProfileFields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "elapsed_us" {
+            self.elapsed_us = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"))
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "name" => self.name = Some(format!("{value:?}")),
+            "phase" => self.phase = Some(format!("{value:?}")),
+            _ => {}
+        }
+    }
+}
+
+/// Returns a small, stable index of the current thread for the chrome tracing profile
+fn thread_index() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    thread_local! {
+        static INDEX: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
+
+/// Returns the records as a chrome tracing profile
+fn chrome_trace(records: &[Record], start: Instant) -> serde_json::Value {
+    let events: Vec<_> = records
+        .iter()
+        .map(|record| {
+            json!({
+                "name": record.name.as_deref().unwrap_or(&record.phase),
+                "cat": record.phase,
+                "ph": "X",
+                "ts": record.start.saturating_duration_since(start).as_micros() as u64,
+                "dur": record.duration.as_micros() as u64,
+                "pid": 1,
+                "tid": record.thread,
+            })
+        })
+        .collect();
+    json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+}
+
+/// The time spent in a phase
+#[derive(Debug, Default)]
+struct PhaseSummary {
+    calls: usize,
+    total: Duration,
+    /// The longest call and what was timed
+    max: Duration,
+    slowest: Option<String>,
+}
+
+/// The time spent in each phase, displayed as a table
+struct ProfileTable {
+    phases: Vec<(String, PhaseSummary)>,
+    wall_time: Duration,
+}
+
+impl ProfileTable {
+    fn new(records: &[Record], wall_time: Duration) -> Self {
+        let mut summaries: BTreeMap<&str, PhaseSummary> = BTreeMap::new();
+        for record in records {
+            let summary = summaries.entry(record.phase.as_str()).or_default();
+            summary.calls += 1;
+            summary.total += record.duration;
+            if record.duration >= summary.max {
+                summary.max = record.duration;
+                summary.slowest = record.name.clone();
+            }
+        }
+        let mut phases: Vec<_> =
+            summaries.into_iter().map(|(phase, summary)| (phase.to_string(), summary)).collect();
+        phases.sort_by_key(|(phase, _)| {
+            PHASES.iter().position(|p| *p == phase.as_str()).unwrap_or(PHASES.len())
+        });
+        Self { phases, wall_time }
+    }
+}
+
+impl fmt::Display for ProfileTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}",
+            Paint::new(format!(
+                "{:<8} {:>8} {:>12} {:>12}  {}",
+                "Phase", "Calls", "Total", "Max", "Slowest"
+            ))
+            .bold()
+        )?;
+        for (phase, summary) in &self.phases {
+            writeln!(
+                f,
+                "{:<8} {:>8} {:>12} {:>12}  {}",
+                phase,
+                summary.calls,
+                format!("{:.2?}", summary.total),
+                format!("{:.2?}", summary.max),
+                summary.slowest.as_deref().unwrap_or_default()
+            )?;
+        }
+        writeln!(f, "\nWall time: {:.2?}", self.wall_time)?;
+        write!(
+            f,
+            "{}",
+            Paint::new("Phases that run in parallel, like tests, can total more than the wall time")
+                .dimmed()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_summarize_phases() {
+        let start = Instant::now();
+        let record = |phase: &str, name: &str, millis| Record {
+            phase: phase.to_string(),
+            name: Some(name.to_string()),
+            thread: 0,
+            start,
+            duration: Duration::from_millis(millis),
+        };
+        let records = vec![
+            record("evm", "VaultTest.testDeposit()", 5),
+            record("solc", "0.8.10", 100),
+            record("evm", "VaultTest.testWithdraw()", 20),
+            record("config", "load", 1),
+        ];
+
+        let table = ProfileTable::new(&records, Duration::from_millis(150));
+        let phases: Vec<_> = table.phases.iter().map(|(phase, _)| phase.as_str()).collect();
+        assert_eq!(phases, ["config", "solc", "evm"]);
+        let evm = &table.phases[2].1;
+        assert_eq!(evm.calls, 2);
+        assert_eq!(evm.total, Duration::from_millis(25));
+        assert_eq!(evm.slowest.as_deref(), Some("VaultTest.testWithdraw()"));
+
+        let trace = chrome_trace(&records, start);
+        assert_eq!(trace["traceEvents"][1]["name"], "0.8.10");
+        assert_eq!(trace["traceEvents"][1]["cat"], "solc");
+        assert_eq!(trace["traceEvents"][1]["dur"], 100_000);
+    }
+}
@@ -6,6 +6,7 @@ use ethers::solc::{
     report::{BasicStdoutReporter, Reporter, SolcCompilerIoReporter},
     CompilerInput, CompilerOutput, Solc,
 };
+use foundry_config::PROFILE_TARGET;
 use once_cell::sync::Lazy;
 use semver::Version;
 use std::{
@@ -13,10 +14,11 @@ use std::{
     io::prelude::*,
     path::{Path, PathBuf},
     sync::{
+        atomic::{AtomicBool, Ordering},
         mpsc::{self, TryRecvError},
         Arc, Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 use yansi::Paint;
 
@@ -267,15 +269,77 @@ impl Reporter for SpinnerReporter {
     }
 }
 
+/// Forwards the events to the wrapped [`Reporter`], and reports the time spent running solc to
+/// the profiler of `--profile-exec`
+///
+/// The time before the first solc run is spent resolving the dependency graph and checking the
+/// cache.
+#[derive(Debug)]
+struct ProfilingReporter<R> {
+    inner: R,
+    start: Instant,
+    spawned: AtomicBool,
+}
+
+impl<R> ProfilingReporter<R> {
+    fn new(inner: R) -> Self {
+        Self { inner, start: Instant::now(), spawned: AtomicBool::new(false) }
+    }
+}
+
+impl<R: Reporter> Reporter for ProfilingReporter<R> {
+    fn on_solc_spawn(
+        &self,
+        solc: &Solc,
+        version: &Version,
+        input: &CompilerInput,
+        dirty_files: &[PathBuf],
+    ) {
+        if !self.spawned.swap(true, Ordering::Relaxed) {
+            let elapsed_us = self.start.elapsed().as_micros() as u64;
+            tracing::info!(target: PROFILE_TARGET, phase = "graph", name = "sources", elapsed_us);
+        }
+        self.inner.on_solc_spawn(solc, version, input, dirty_files)
+    }
+
+    fn on_solc_success(
+        &self,
+        solc: &Solc,
+        version: &Version,
+        output: &CompilerOutput,
+        duration: &Duration,
+    ) {
+        let elapsed_us = duration.as_micros() as u64;
+        tracing::info!(target: PROFILE_TARGET, phase = "solc", name = %version, elapsed_us);
+        self.inner.on_solc_success(solc, version, output, duration)
+    }
+
+    fn on_solc_installation_start(&self, version: &Version) {
+        self.inner.on_solc_installation_start(version)
+    }
+
+    fn on_solc_installation_success(&self, version: &Version) {
+        self.inner.on_solc_installation_success(version)
+    }
+
+    fn on_solc_installation_error(&self, version: &Version, error: &str) {
+        self.inner.on_solc_installation_error(version, error)
+    }
+
+    fn on_unresolved_import(&self, import: &Path, remappings: &[Remapping]) {
+        self.inner.on_unresolved_import(import, remappings)
+    }
+}
+
 /// If the output medium is terminal, this calls `f` within the [`SpinnerReporter`] that displays a
 /// spinning cursor to display solc progress.
 ///
 /// If no terminal is available this falls back to common `println!` in [`BasicStdoutReporter`].
 pub fn with_spinner_reporter<T>(f: impl FnOnce() -> T) -> T {
     let reporter = if TERM_SETTINGS.indicate_progress {
-        ethers::solc::report::Report::new(SpinnerReporter::spawn())
+        ethers::solc::report::Report::new(ProfilingReporter::new(SpinnerReporter::spawn()))
     } else {
-        ethers::solc::report::Report::new(BasicStdoutReporter::default())
+        ethers::solc::report::Report::new(ProfilingReporter::new(BasicStdoutReporter::default()))
     };
     ethers::solc::report::with_scoped(&reporter, f)
 }
@@ -1,13 +1,12 @@
 use ethers::{
     abi::token::{LenientTokenizer, Tokenizer},
-    solc::EvmVersion,
     types::U256,
 };
-use forge::executor::{opts::EvmOpts, Fork, SpecId};
+use forge::executor::{opts::EvmOpts, Fork};
 use foundry_config::{cache::StorageCachingConfig, Config};
 use std::{
     future::Future,
-    path::{Path, PathBuf},
+    path::Path,
     str::FromStr,
     time::Duration,
 };
@@ -70,14 +69,7 @@ pub fn subscriber() {
         .init()
 }
 
-pub fn evm_spec(evm: &EvmVersion) -> SpecId {
-    match evm {
-        EvmVersion::Istanbul => SpecId::ISTANBUL,
-        EvmVersion::Berlin => SpecId::BERLIN,
-        EvmVersion::London => SpecId::LONDON,
-        _ => panic!("Unsupported EVM version"),
-    }
-}
+pub use forge::utils::evm_spec;
 
 /// Securely reads a secret from stdin, or proceeds to return a fallback value
 /// which was provided in cleartext via CLI or env var
@@ -108,7 +100,7 @@ pub fn read_secret(secret: bool, unsafe_secret: Option<String>) -> eyre::Result<
 /// );
 /// ```
 pub fn get_contract_name(id: &str) -> &str {
-    id.rsplit(':').next().unwrap_or(id)
+    split_id(id).map_or(id, |(_, name)| name)
 }
 
 /// This returns the `file name` part, See [`get_contract_name`]
@@ -120,9 +112,23 @@ pub fn get_contract_name(id: &str) -> &str {
 ///     "SafeTransferLibTest.json",
 ///     utils::get_file_name("SafeTransferLibTest.json:SafeTransferLibTest")
 /// );
+/// assert_eq!("C:\\src\\Vault.sol", utils::get_file_name("C:\\src\\Vault.sol:Vault"));
+/// assert_eq!("C:\\src\\Vault.sol", utils::get_file_name("C:\\src\\Vault.sol"));
 /// ```
 pub fn get_file_name(id: &str) -> &str {
-    id.split(':').next().unwrap_or(id)
+    split_id(id).map_or(id, |(file, _)| file)
+}
+
+/// Splits the id into `(file, contract)` at the last `:`
+///
+/// Absolute paths on Windows contain a `:` as well, so the id is only split if the part after the
+/// last `:` is a valid contract identifier, `C:\src\Vault.sol` is a file without a contract name.
+fn split_id(id: &str) -> Option<(&str, &str)> {
+    let (file, name) = id.rsplit_once(':')?;
+    let is_identifier = !name.is_empty() &&
+        !name.starts_with(|c: char| c.is_ascii_digit()) &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    is_identifier.then(|| (file, name))
 }
 
 /// parse a hex str or decimal str as U256
@@ -178,58 +184,30 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
     rt.block_on(future)
 }
 
-/// Helper function that returns the [Fork] to use, if any.
-///
-/// storage caching for the [Fork] will be enabled if
-///   - `fork_url` is present
-///   - `fork_block_number` is present
-///   - [StorageCachingConfig] allows the `fork_url` +  chain id pair
-///   - storage is allowed (`no_storage_caching = false`)
-///
-/// If all these criteria are met, then storage caching is enabled and storage info will be written
-/// to [Config::foundry_cache_dir()]/<str(chainid)>/<block>/storage.json
-///
-/// for `mainnet` and `--fork-block-number 14435000` on mac the corresponding storage cache will be
-/// at `~/.foundry/cache/mainnet/14435000/storage.json`
+/// Helper function that returns the [Fork] to use, if any, see [EvmOpts::get_fork]
 pub fn get_fork(evm_opts: &EvmOpts, config: &StorageCachingConfig) -> Option<Fork> {
-    /// Returns the path where the cache file should be stored
-    ///
-    /// or `None` if caching should not be enabled
-    ///
-    /// See also [ Config::foundry_block_cache_file()]
-    fn get_block_storage_path(
-        evm_opts: &EvmOpts,
-        config: &StorageCachingConfig,
-        chain_id: u64,
-    ) -> Option<PathBuf> {
-        if evm_opts.no_storage_caching {
-            // storage caching explicitly opted out of
-            return None
-        }
-        let url = evm_opts.fork_url.as_ref()?;
-        // cache only if block explicitly pinned
-        let block = evm_opts.fork_block_number?;
-
-        if config.enable_for_endpoint(url) && config.enable_for_chain_id(chain_id) {
-            return Config::foundry_block_cache_file(chain_id, block)
-        }
+    evm_opts.get_fork(config)
+}
 
-        None
+/// Enables offline mode for the whole process, including the configs loaded later on, see
+/// [Config::offline]
+pub fn set_offline(offline: bool) {
+    if offline {
+        std::env::set_var("FOUNDRY_OFFLINE", "true");
     }
+}
 
-    if let Some(ref url) = evm_opts.fork_url {
-        let chain_id = evm_opts.get_chain_id();
-        let cache_storage = get_block_storage_path(evm_opts, config, chain_id);
-        let fork = Fork {
-            url: url.clone(),
-            pin_block: evm_opts.fork_block_number,
-            cache_path: cache_storage,
-            chain_id,
-        };
-        return Some(fork)
-    }
+/// Whether offline mode is enabled, with `--offline`, `FOUNDRY_OFFLINE` or the config
+pub fn is_offline() -> bool {
+    Config::load().offline
+}
 
-    None
+/// Fails in offline mode, as `action` requires network access
+pub fn ensure_online(action: &str) -> eyre::Result<()> {
+    if is_offline() {
+        eyre::bail!("{action} requires network access, which is disabled in offline mode")
+    }
+    Ok(())
 }
 
 /// Conditionally print a message
@@ -272,4 +250,19 @@ mod tests {
         let p = Path::new("contracts/Greeter.sol");
         assert!(!p.is_sol_test());
     }
+
+    #[test]
+    fn can_split_contract_ids() {
+        assert_eq!(get_file_name("src/Vault.sol:Vault"), "src/Vault.sol");
+        assert_eq!(get_contract_name("src/Vault.sol:Vault"), "Vault");
+        assert_eq!(get_file_name("C:\\src\\Vault.sol:Vault"), "C:\\src\\Vault.sol");
+        assert_eq!(get_contract_name("C:\\src\\Vault.sol:Vault"), "Vault");
+
+        // ids without a contract name
+        assert_eq!(get_file_name("src/Vault.sol"), "src/Vault.sol");
+        assert_eq!(get_contract_name("Vault"), "Vault");
+        assert_eq!(get_file_name("C:\\src\\Vault.sol"), "C:\\src\\Vault.sol");
+        assert_eq!(get_contract_name("C:\\src\\Vault.sol"), "C:\\src\\Vault.sol");
+        assert_eq!(get_file_name("\\\\?\\C:\\src\\Vault.sol"), "\\\\?\\C:\\src\\Vault.sol");
+    }
 }
@@ -0,0 +1,249 @@
+//! Assertions on the config `forge config --json` resolves
+//!
+//! ```no_run
+//! use foundry_cli_test_utils::{config::assert_config, ethers_solc::PathStyle, TestProject};
+//! let prj = TestProject::new("config", PathStyle::Dapptools);
+//! assert_config(&prj)
+//!     .with_args(["--optimizer-runs", "200", "--via-ir"])
+//!     .key("optimizer_runs")
+//!     .eq(200)
+//!     .key("via_ir")
+//!     .eq(true);
+//! ```
+use crate::TestProject;
+use foundry_config::Config;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::Write,
+};
+
+/// Returns the assertions on the config of the project
+pub fn assert_config(prj: &TestProject) -> ConfigAssertion<'_> {
+    ConfigAssertion { prj, args: Vec::new(), envs: Vec::new(), config: None }
+}
+
+/// Assertions on the config `forge config --json` resolves with the arguments and env vars
+///
+/// `forge config` runs once, when the first assertion is made.
+pub struct ConfigAssertion<'a> {
+    prj: &'a TestProject,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    config: Option<Value>,
+}
+
+impl<'a> ConfigAssertion<'a> {
+    /// Adds the arguments to `forge config`
+    #[must_use]
+    pub fn with_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: AsRef<OsStr>,
+    {
+        self.args.extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        self.config = None;
+        self
+    }
+
+    /// Sets the env var for `forge config`
+    #[must_use]
+    pub fn with_env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.envs.push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self.config = None;
+        self
+    }
+
+    /// Returns the assertion on a value of the config, nested keys are separated by `.`, e.g.
+    /// `optimizer_details.yul`
+    pub fn key(self, key: &str) -> KeyAssertion<'a> {
+        KeyAssertion { assertion: self, key: key.to_string() }
+    }
+
+    /// Asserts that the whole config equals the expected config
+    #[track_caller]
+    pub fn eq_config(mut self, expected: &Config) -> Self {
+        let expected = serde_json::to_value(expected).expect("Config is serializable");
+        let mut differences = Vec::new();
+        diff("", Some(&expected), Some(self.config()), &mut differences);
+        if !differences.is_empty() {
+            panic!("{}", self.report(&differences));
+        }
+        self
+    }
+
+    /// Returns the config, runs `forge config` if it did not run yet
+    fn config(&mut self) -> &Value {
+        if self.config.is_none() {
+            let mut cmd = self.prj.forge_bin();
+            cmd.arg("config").arg("--root").arg(self.prj.root()).args(&self.args).arg("--json");
+            cmd.envs(self.envs.iter().map(|(key, value)| (key, value)));
+            let output = cmd.output().unwrap();
+            if !output.status.success() {
+                panic!(
+                    "forge config {:?} failed:\n{}",
+                    self.args,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+            let config = serde_json::from_slice(&output.stdout).unwrap_or_else(|err| {
+                panic!("forge config {:?} did not print a JSON config: {err}", self.args)
+            });
+            self.config = Some(config);
+        }
+        self.config.as_ref().unwrap()
+    }
+
+    /// Returns the failure message for the differences, one line per key
+    fn report(&self, differences: &[Difference]) -> String {
+        let mut report = format!("The config of `forge config {:?}` differs:\n", self.args);
+        for difference in differences {
+            writeln!(
+                report,
+                "  {}:\n    expected: {}\n       found: {}",
+                difference.key, difference.expected, difference.found
+            )
+            .unwrap();
+        }
+        report
+    }
+}
+
+/// An assertion on a single value of the config
+pub struct KeyAssertion<'a> {
+    assertion: ConfigAssertion<'a>,
+    key: String,
+}
+
+impl<'a> KeyAssertion<'a> {
+    /// Asserts that the value of the key equals the expected value
+    #[track_caller]
+    pub fn eq(self, expected: impl Serialize) -> ConfigAssertion<'a> {
+        let expected = serde_json::to_value(expected).expect("value is serializable");
+        self.assert(|found| {
+            let mut differences = Vec::new();
+            diff("", Some(&expected), Some(found), &mut differences);
+            differences
+        })
+    }
+
+    /// Asserts that the value of the key is not the given value
+    #[track_caller]
+    pub fn ne(self, unexpected: impl Serialize) -> ConfigAssertion<'a> {
+        let unexpected = serde_json::to_value(unexpected).expect("value is serializable");
+        self.assert(|found| {
+            if *found == unexpected {
+                vec![Difference {
+                    key: String::new(),
+                    expected: format!("anything but {unexpected}"),
+                    found: found.to_string(),
+                }]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    /// Asserts that the value of the key is `null`, e.g. an unset `Option`
+    #[track_caller]
+    pub fn is_none(self) -> ConfigAssertion<'a> {
+        self.eq(Value::Null)
+    }
+
+    #[track_caller]
+    fn assert(
+        mut self,
+        differences: impl FnOnce(&Value) -> Vec<Difference>,
+    ) -> ConfigAssertion<'a> {
+        let config = self.assertion.config();
+        let found = self.key.split('.').try_fold(config, |value, key| value.get(key)).cloned();
+        let found = match found {
+            Some(found) => found,
+            None => panic!(
+                "The config of `forge config {:?}` has no key `{}`",
+                self.assertion.args, self.key
+            ),
+        };
+        let mut differences = differences(&found);
+        if !differences.is_empty() {
+            for difference in &mut differences {
+                difference.key = match difference.key.as_str() {
+                    "" => self.key.clone(),
+                    nested => format!("{}.{nested}", self.key),
+                };
+            }
+            panic!("{}", self.assertion.report(&differences));
+        }
+        self.assertion
+    }
+}
+
+/// A value of the config that differs from the expected value
+#[derive(Debug, PartialEq)]
+struct Difference {
+    /// The dotted path of the value, relative to the compared values
+    key: String,
+    expected: String,
+    found: String,
+}
+
+/// Collects the differences of the values, objects are compared key by key
+fn diff(key: &str, expected: Option<&Value>, found: Option<&Value>, out: &mut Vec<Difference>) {
+    match (expected, found) {
+        (Some(Value::Object(expected)), Some(Value::Object(found))) => {
+            let added = found.keys().filter(|name| !expected.contains_key(*name));
+            for name in expected.keys().chain(added) {
+                let nested = if key.is_empty() { name.clone() } else { format!("{key}.{name}") };
+                diff(&nested, expected.get(name), found.get(name), out);
+            }
+        }
+        _ if expected != found => {
+            let display = |value: Option<&Value>| {
+                value.map(|value| value.to_string()).unwrap_or_else(|| "<missing>".to_string())
+            };
+            out.push(Difference {
+                key: key.to_string(),
+                expected: display(expected),
+                found: display(found),
+            })
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_diff_nested_values() {
+        let expected =
+            json!({ "via_ir": true, "optimizer_details": { "yul": true, "peephole": 1 } });
+        let found = json!({ "via_ir": true, "optimizer_details": { "yul": false }, "ffi": false });
+        let mut differences = Vec::new();
+        diff("", Some(&expected), Some(&found), &mut differences);
+        differences.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            differences,
+            [
+                Difference {
+                    key: "ffi".to_string(),
+                    expected: "<missing>".to_string(),
+                    found: "false".to_string(),
+                },
+                Difference {
+                    key: "optimizer_details.peephole".to_string(),
+                    expected: "1".to_string(),
+                    found: "<missing>".to_string(),
+                },
+                Difference {
+                    key: "optimizer_details.yul".to_string(),
+                    expected: "true".to_string(),
+                    found: "false".to_string(),
+                },
+            ]
+        );
+    }
+}
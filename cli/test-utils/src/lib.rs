@@ -3,6 +3,12 @@ extern crate core;
 // Macros useful for testing.
 mod macros;
 
+// Assertions on the resolved config.
+pub mod config;
+
+// A fake RPC server that replays recorded responses.
+pub mod rpc;
+
 // Utilities for making it easier to handle tests.
 pub mod util;
 pub use util::{TestCommand, TestProject};
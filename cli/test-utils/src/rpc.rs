@@ -0,0 +1,213 @@
+//! A fake JSON-RPC server that replays the responses of a fixture file
+//!
+//! Tests of commands that talk to an RPC endpoint can point them at the [`FakeRpc`] instead of a
+//! live endpoint. The fixtures are recorded by running the tests with [`RECORD_RPC_ENV`] set to the
+//! url of a live endpoint.
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
+
+use parking_lot::Mutex;
+
+/// The environment variable with the url of the RPC endpoint the [`FakeRpc`] records the fixtures
+/// from
+pub const RECORD_RPC_ENV: &str = "FOUNDRY_RECORD_RPC";
+
+/// A JSON-RPC call and the response to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    /// The `result` or the `error` of the response
+    pub response: Value,
+}
+
+/// Where the responses of the [`FakeRpc`] come from
+#[derive(Debug)]
+enum Responses {
+    /// The calls of the fixture file
+    Replay(Vec<RecordedCall>),
+    /// The live endpoint, the calls are recorded to the fixture file
+    Record { url: String, calls: Vec<RecordedCall> },
+}
+
+impl Responses {
+    /// Returns the response to the call without the `id` and the `jsonrpc` version
+    fn respond(&mut self, method: &str, params: &Value) -> Value {
+        match self {
+            Responses::Replay(calls) => calls
+                .iter()
+                .find(|call| call.method == method && call.params == *params)
+                .map(|call| call.response.clone())
+                .unwrap_or_else(|| {
+                    json!({
+                        "error": {
+                            "code": -32601,
+                            "message": format!("no recorded response to {method} {params}"),
+                        }
+                    })
+                }),
+            Responses::Record { url, calls } => {
+                let request =
+                    json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+                let response: Value = reqwest::blocking::Client::new()
+                    .post(url.as_str())
+                    .json(&request)
+                    .send()
+                    .and_then(|response| response.json())
+                    .expect("Failed to forward the request to the RPC endpoint");
+                let response = match response.get("error") {
+                    Some(error) => json!({ "error": error }),
+                    None => json!({ "result": response["result"] }),
+                };
+                calls.push(RecordedCall {
+                    method: method.to_string(),
+                    params: params.clone(),
+                    response: response.clone(),
+                });
+                response
+            }
+        }
+    }
+}
+
+/// A JSON-RPC server on localhost that replays the responses of a fixture file
+///
+/// If [`RECORD_RPC_ENV`] is set, the requests are forwarded to that endpoint instead and the calls
+/// are written to the fixture file when the server is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use foundry_cli_test_utils::rpc::FakeRpc;
+/// let rpc = FakeRpc::start("tests/fixtures/rpc/block_number.json");
+/// // cmd.args(["block-number", "--rpc-url", rpc.url()]);
+/// ```
+pub struct FakeRpc {
+    url: String,
+    fixture: PathBuf,
+    responses: Arc<Mutex<Responses>>,
+}
+
+impl FakeRpc {
+    /// Starts the server with the calls of the fixture file, or in recording mode
+    pub fn start(fixture: impl Into<PathBuf>) -> Self {
+        let fixture = fixture.into();
+        let responses = match env::var(RECORD_RPC_ENV) {
+            Ok(url) => Responses::Record { url, calls: Vec::new() },
+            Err(_) => Responses::Replay(read_fixture(&fixture)),
+        };
+        let responses = Arc::new(Mutex::new(responses));
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind the fake RPC");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server_responses = Arc::clone(&responses);
+        // the server runs until the test process exits
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let responses = Arc::clone(&server_responses);
+                thread::spawn(move || serve(stream, responses));
+            }
+        });
+        Self { url, fixture, responses }
+    }
+
+    /// Returns the url of the server
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for FakeRpc {
+    fn drop(&mut self) {
+        if let Responses::Record { calls, .. } = &*self.responses.lock() {
+            if let Some(parent) = self.fixture.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+            fs::write(&self.fixture, serde_json::to_string_pretty(calls).unwrap())
+                .expect("Failed to write the RPC fixture");
+        }
+    }
+}
+
+fn read_fixture(path: &Path) -> Vec<RecordedCall> {
+    let content = fs::read_to_string(path).unwrap_or_else(|err| {
+        panic!(
+            "Failed to read the RPC fixture {}, record it with {RECORD_RPC_ENV}=<url>: {err}",
+            path.display()
+        )
+    });
+    serde_json::from_str(&content).expect("Invalid RPC fixture")
+}
+
+/// Answers the HTTP requests of a connection until it is closed
+fn serve(stream: TcpStream, responses: Arc<Mutex<Responses>>) {
+    let mut writer = stream.try_clone().expect("Failed to clone the connection");
+    let mut reader = BufReader::new(stream);
+    while let Some(body) = read_request(&mut reader) {
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Array(batch)) => {
+                Value::Array(batch.iter().map(|request| answer(request, &responses)).collect())
+            }
+            Ok(request) => answer(&request, &responses),
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": err.to_string() },
+            }),
+        };
+        let body = response.to_string();
+        let written = write!(
+            writer,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        if written.is_err() {
+            return
+        }
+    }
+}
+
+/// Returns the response to a single JSON-RPC request
+fn answer(request: &Value, responses: &Mutex<Responses>) -> Value {
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let mut response = responses.lock().respond(method, &params);
+    response["jsonrpc"] = "2.0".into();
+    response["id"] = request["id"].clone();
+    response
+}
+
+/// Reads the body of the next HTTP request of the connection
+fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Vec<u8>> {
+    let mut content_length = 0;
+    let mut line = String::new();
+    // the request line and the headers
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(body)
+}
@@ -4,7 +4,7 @@ use ethers_solc::{
     project_util::{copy_dir, TempProject},
     ArtifactOutput, ConfigurableArtifacts, PathStyle, ProjectPathsConfig,
 };
-use foundry_config::Config;
+use foundry_config::{long_path, Config};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use regex::Regex;
@@ -169,10 +169,12 @@ impl TestProject {
     }
 
     /// Copies the project's root directory to the given target
+    ///
+    /// Both dirs are accessed by their long path, see [`long_path`]
     pub fn copy_to(&self, target: impl AsRef<Path>) {
-        let target = target.as_ref();
-        pretty_err(target, fs::create_dir_all(target));
-        pretty_err(target, copy_dir(self.root(), target));
+        let target = long_path(target);
+        pretty_err(&target, fs::create_dir_all(&target));
+        pretty_err(&target, copy_dir(long_path(self.root()), &target));
     }
 
     /// Creates a file with contents `contents` in the test project's directory. The
@@ -183,10 +185,11 @@ impl TestProject {
             panic!("create_file(): file path is absolute");
         }
         let path = self.root().join(path);
-        if let Some(parent) = path.parent() {
+        let long = long_path(&path);
+        if let Some(parent) = long.parent() {
             pretty_err(parent, std::fs::create_dir_all(parent));
         }
-        let file = pretty_err(&path, File::create(&path));
+        let file = pretty_err(&path, File::create(&long));
         let mut writer = BufWriter::new(file);
         pretty_err(&path, writer.write_all(contents.as_bytes()));
         path
@@ -243,16 +246,23 @@ impl TestProject {
 
     /// Returns the path to the forge executable.
     pub fn forge_bin(&self) -> process::Command {
-        let forge = self.root.join(format!("../forge{}", env::consts::EXE_SUFFIX));
-        let mut cmd = process::Command::new(forge);
+        let mut cmd = process::Command::new(self.bin_path("forge"));
         cmd.current_dir(self.inner.root());
         cmd
     }
 
     /// Returns the path to the cast executable.
     pub fn cast_bin(&self) -> process::Command {
-        let cast = self.root.join(format!("../cast{}", env::consts::EXE_SUFFIX));
-        process::Command::new(cast)
+        process::Command::new(self.bin_path("cast"))
+    }
+
+    /// Returns the path to a binary of the workspace, e.g. `forge.exe` on Windows
+    ///
+    /// Tests run in `target/<profile>/deps`, the binaries are in its parent. The parent is taken
+    /// instead of joining `..`, which is not resolved in verbatim `\\?\` paths on Windows.
+    fn bin_path(&self, name: &str) -> PathBuf {
+        let dir = self.root.parent().unwrap_or(&self.root);
+        dir.join(format!("{name}{}", env::consts::EXE_SUFFIX))
     }
 
     /// Returns the `Config` as spit out by `forge config`
@@ -271,8 +281,9 @@ impl TestProject {
 
     /// Removes all files and dirs inside the project's root dir
     pub fn wipe(&self) {
-        pretty_err(self.root(), fs::remove_dir_all(self.root()));
-        pretty_err(self.root(), fs::create_dir_all(self.root()));
+        let root = long_path(self.root());
+        pretty_err(&root, fs::remove_dir_all(&root));
+        pretty_err(&root, fs::create_dir_all(&root));
     }
 }
 
@@ -430,6 +441,21 @@ impl TestCommand {
         String::from_utf8_lossy(&self.output().stdout).to_string()
     }
 
+    /// Runs the command with `input` piped to its stdin and returns the `stdout` as `String`. If
+    /// the command failed, then this panics.
+    pub fn stdout_with_stdin(&mut self, input: &str) -> String {
+        let mut child = self
+            .cmd
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = self.expect_success(child.wait_with_output().unwrap());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
     /// Returns the output but does not expect that the command was successful
     pub fn unchecked_output(&mut self) -> process::Output {
         self.cmd.output().unwrap()
@@ -1,8 +1,9 @@
 //! Contains various tests for checking cast commands
-use std::env;
+use std::{env, path::PathBuf};
 
 use foundry_cli_test_utils::{
     casttest,
+    rpc::FakeRpc,
     util::{TestCommand, TestProject},
 };
 
@@ -29,3 +30,64 @@ casttest!(finds_block, |_: TestProject, mut cmd: TestCommand| {
     // Output block time: Mar 21 2022 06:20:09 UTC
     assert!(output.contains("14428082"), "{}", output);
 });
+
+// tests that `cast 4byte` does not access the network in offline mode
+casttest!(fourbyte_fails_offline, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["4byte", "0xa9059cbb", "--offline"]);
+    cmd.assert_err();
+    let stderr = cmd.stderr_lossy();
+    assert!(stderr.contains("disabled in offline mode"), "{}", stderr);
+});
+
+// tests that replacements have to raise the fees by at least 10%
+casttest!(bump_requires_higher_fees, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "bump",
+        "0x5a91b9e1fc45e1caa0d4d9d6f1f36b6b4d2f1d96d1a5bb2f2b4d1a1e1a2b3c4d",
+        "--fee-multiplier",
+        "1.05",
+    ]);
+    cmd.assert_err();
+    let stderr = cmd.stderr_lossy();
+    assert!(stderr.contains("must be at least 1.1"), "{}", stderr);
+});
+
+// tests that `--json-errors` reports connection errors as transient with their exit code
+casttest!(reports_connection_errors, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["block-number", "--rpc-url", "http://127.0.0.1:1", "--json-errors"]);
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let envelope: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+    assert_eq!(envelope["error"]["kind"], "connection");
+    assert_eq!(envelope["error"]["transient"], true);
+});
+
+// tests that cast can be run against the fake RPC server with the recorded mainnet responses
+casttest!(replays_recorded_rpc_responses, |_: TestProject, mut cmd: TestCommand| {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpc/mainnet.json");
+    let rpc = FakeRpc::start(fixture);
+
+    cmd.args(["block-number", "--rpc-url", rpc.url()]);
+    assert_eq!(cmd.stdout_lossy().trim(), "14428082");
+
+    cmd.cast_fuse().args(["chain-id", "--rpc-url", rpc.url()]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1");
+});
+
+// tests that `cast da gas` estimates the blob gas and fee of the blobs of a file
+casttest!(estimates_blob_gas, |prj: TestProject, mut cmd: TestCommand| {
+    let file = prj.root().join("data.bin");
+    std::fs::write(&file, vec![1u8; 4096 * 31 + 1]).unwrap();
+
+    cmd.args(["da", "gas"]).arg(&file).args(["--excess-blob-gas", "3338477"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("blobs: 2\nblob gas: 262144\nblob base fee: 2 wei\nblob fee: 524288 wei"));
+});
+
+// tests that `cast pretty-calldata -o` skips the 4byte lookup
+casttest!(pretty_calldata_skips_lookup, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["pretty-calldata", "-o", "0xa9059cbb"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Method: a9059cbb"), "{}", output);
+});
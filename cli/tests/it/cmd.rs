@@ -4,12 +4,14 @@ use ethers::solc::{
     ConfigurableContractArtifact,
 };
 use foundry_cli_test_utils::{
-    ethers_solc::PathStyle,
+    ethers_solc::{remappings::Remapping, PathStyle},
     forgetest, forgetest_ignore, forgetest_init,
     util::{pretty_err, read_string, TestCommand, TestProject},
 };
-use foundry_config::{parse_with_profile, BasicConfig, Chain, Config, SolidityErrorCode};
-use std::{env, fs};
+use foundry_config::{
+    parse_with_profile, BasicConfig, Chain, Config, ContractLimits, SolcBackend, SolidityErrorCode,
+};
+use std::{env, fs, process::Command, str::FromStr};
 use yansi::Paint;
 
 // import forge utils as mod
@@ -165,7 +167,17 @@ forgetest!(can_init_vscode, |prj: TestProject, mut cmd: TestCommand| {
         settings,
         serde_json::json!({
              "solidity.packageDefaultDependenciesContractsDirectory": "src",
-            "solidity.packageDefaultDependenciesDirectory": "lib"
+            "solidity.packageDefaultDependenciesDirectory": "lib",
+            "solidity.remappings": [
+                "ds-test/=lib/forge-std/lib/ds-test/src/",
+                "forge-std/=lib/forge-std/src/"
+            ],
+            "solidity.formatter": "forge",
+            "[solidity]": {
+                "editor.tabSize": 4,
+                "editor.insertSpaces": true,
+                "editor.rulers": [80]
+            }
         })
     );
 
@@ -175,6 +187,49 @@ forgetest!(can_init_vscode, |prj: TestProject, mut cmd: TestCommand| {
     assert_eq!(content, "ds-test/=lib/forge-std/lib/ds-test/src/\nforge-std/=lib/forge-std/src/");
 });
 
+// Checks that the editor files are generated from the templates
+forgetest!(can_init_editor_files_from_templates, |prj: TestProject, mut cmd: TestCommand| {
+    prj.wipe();
+    let templates = prj.root().join("templates");
+    fs::create_dir_all(templates.join(".vscode")).unwrap();
+    fs::write(templates.join(".solhint.json"), r#"{ "extends": "solhint:all" }"#).unwrap();
+    fs::write(templates.join(".gitattributes"), "*.sol linguist-language=Solidity").unwrap();
+    // existing files are kept
+    fs::write(prj.root().join(".gitattributes"), "*.t.sol linguist-vendored").unwrap();
+    fs::write(
+        templates.join(".vscode/settings.json"),
+        r#"{ "solidity.compileUsingRemoteVersion": "v0.8.15", "solidity.formatter": "prettier" }"#,
+    )
+    .unwrap();
+
+    cmd.arg("init")
+        .arg(prj.root())
+        .args(["--force", "--no-deps", "--vscode", "--solhint", "--gitattributes"])
+        .arg("--editor-templates")
+        .arg(&templates);
+    cmd.assert_non_empty_stdout();
+
+    let solhint = fs::read_to_string(prj.root().join(".solhint.json")).unwrap();
+    assert_eq!(solhint, r#"{ "extends": "solhint:all" }"#);
+    let gitattributes = fs::read_to_string(prj.root().join(".gitattributes")).unwrap();
+    assert_eq!(gitattributes, "*.t.sol linguist-vendored");
+
+    // the files are part of the initial commit
+    let tracked = Command::new("git")
+        .args(["ls-files", ".solhint.json", ".gitattributes"])
+        .current_dir(prj.root())
+        .output()
+        .unwrap();
+    let tracked = String::from_utf8_lossy(&tracked.stdout);
+    assert!(tracked.contains(".solhint.json") && tracked.contains(".gitattributes"), "{tracked}");
+
+    let settings: serde_json::Value =
+        ethers::solc::utils::read_json_file(prj.root().join(".vscode/settings.json")).unwrap();
+    assert_eq!(settings["solidity.compileUsingRemoteVersion"], "v0.8.15");
+    // the generated settings take precedence
+    assert_eq!(settings["solidity.formatter"], "forge");
+});
+
 // checks that `clean` removes dapptools style paths
 forgetest!(can_clean, |prj: TestProject, mut cmd: TestCommand| {
     prj.assert_create_dirs_exists();
@@ -482,6 +537,52 @@ result: uint256 255
     )));
 });
 
+// Tests that `--predeploy` etches the CREATE2 factory and warns that it must exist before broadcast
+forgetest!(can_run_with_predeployed_create2_factory, |prj: TestProject, mut cmd: TestCommand| {
+    let script = prj
+        .inner()
+        .add_source(
+            "Deploy",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Child {
+    function value() external pure returns (uint256) {
+        return 42;
+    }
+}
+contract Deploy {
+    address constant FACTORY = 0x4e59b44847b379578588920cA78FbF26c0B4956C;
+    function run() external {
+        require(FACTORY.code.length > 0, "no CREATE2 factory");
+        bytes memory initCode = type(Child).creationCode;
+        (bool ok, bytes memory ret) = FACTORY.call(abi.encodePacked(bytes32(0), initCode));
+        require(ok && ret.length == 20, "CREATE2 deployment failed");
+        require(Child(address(bytes20(ret))).value() == 42, "wrong child");
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("run").arg(&script).args(["--target-contract", "Deploy"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains(&Paint::red("Script failed.").to_string()), "{output}");
+    assert!(output.contains("no CREATE2 factory"), "{output}");
+    assert!(!output.contains("must be deployed before broadcasting"), "{output}");
+
+    cmd.arg("--predeploy");
+    let output = cmd.stdout_lossy();
+    assert!(output.contains(&Paint::green("Script ran successfully.").to_string()), "{output}");
+    assert!(
+        output.contains(
+            "The CREATE2 factory does not exist at 0x4e59b44847b379578588920ca78fbf26c0b4956c, \
+             it must be deployed before broadcasting."
+        ),
+        "{output}"
+    );
+});
+
 // tests that the `inspect` command works correctly
 forgetest!(can_execute_inspect_command, |prj: TestProject, mut cmd: TestCommand| {
     // explicitly set to include the ipfs bytecode hash
@@ -518,6 +619,35 @@ contract Foo {
     assert_eq!(dynamic_bytecode, output);
 });
 
+// tests that `inspect immutables` resolves the immutables in the deployed bytecode
+forgetest!(can_inspect_immutables, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Vault",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Vault {
+    address public immutable owner;
+    uint256 public immutable cap;
+    constructor(address _owner, uint256 _cap) {
+        owner = _owner;
+        cap = _cap;
+    }
+}
+    "#,
+        )
+        .unwrap();
+
+    cmd.args(["inspect", "Vault", "immutables"]);
+    let immutables: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let immutables = immutables.as_array().unwrap();
+    assert_eq!(immutables.len(), 2);
+    assert!(immutables.iter().any(|i| i["name"] == "owner" && i["type"] == "address"));
+    assert!(immutables.iter().any(|i| i["name"] == "cap" && i["type"] == "uint256"));
+    assert!(immutables.iter().all(|i| !i["offsets"].as_array().unwrap().is_empty()));
+});
+
 // test that `forge snapshot` commands work
 forgetest!(can_check_snapshot, |prj: TestProject, mut cmd: TestCommand| {
     prj.insert_ds_test();
@@ -734,3 +864,464 @@ contract CTest is DSTest {
     let cache_after = fs::read_to_string(prj.cache_path()).unwrap();
     assert_eq!(cache, cache_after);
 });
+
+// tests that oversized contracts are flagged by `--sizes` and fail the build if denied
+forgetest!(can_deny_oversized_contracts, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Big.sol",
+            format!(
+                r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Big {{
+    function data() external pure returns (bytes memory) {{
+        return hex"{}";
+    }}
+}}
+   "#,
+                "ff".repeat(25000)
+            ),
+        )
+        .unwrap();
+
+    cmd.args(["build", "--sizes"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("exceed the size limit of 24576 bytes (EIP-170)"), "{}", stdout);
+    assert!(stdout.contains("mainnet: Big"), "{}", stdout);
+
+    prj.write_config(Config { deny_oversized_contracts: true, ..Default::default() });
+    cmd.forge_fuse().arg("build");
+    cmd.assert_err();
+});
+
+// tests that `--notes` prints the contract notes and the build fails on the contract limits
+forgetest!(can_print_notes_and_check_contract_limits, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Counter.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Counter {
+    uint256 public number;
+    function increment() external { number++; }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["build", "--notes"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Counter: "), "{}", stdout);
+    assert!(stdout.contains("gas to deploy, 2 external functions"), "{}", stdout);
+
+    let limits = ContractLimits { max_external_functions: Some(1), ..Default::default() };
+    prj.write_config(Config { contract_limits: limits, ..Default::default() });
+    cmd.forge_fuse().arg("build");
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Counter: 2 external functions exceed max_external_functions = 1"));
+});
+
+// tests that sources are compiled again if their imports are remapped to other files
+forgetest!(can_recompile_remapped_sources, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_lib("a/Lib.sol", "pragma solidity 0.8.10;\ncontract Lib { function a() public {} }")
+        .unwrap();
+    prj.inner()
+        .add_lib("b/Lib.sol", "pragma solidity 0.8.10;\ncontract Lib { function b() public {} }")
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "Remapped.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "mylib/Lib.sol";
+contract Remapped is Lib {}
+   "#,
+        )
+        .unwrap();
+    let artifact = prj.paths().artifacts.join("Remapped.sol/Remapped.json");
+
+    prj.write_config(Config {
+        remappings: vec![Remapping::from_str("mylib/=lib/a/").unwrap().into()],
+        ..Default::default()
+    });
+    cmd.arg("build");
+    cmd.assert_non_empty_stdout();
+    assert!(read_string(&artifact).contains(r#""a()""#));
+
+    cmd.assert_non_empty_stdout();
+    assert!(cmd.stdout_lossy().contains("No files changed"));
+
+    prj.write_config(Config {
+        remappings: vec![Remapping::from_str("mylib/=lib/b/").unwrap().into()],
+        ..Default::default()
+    });
+    let stdout = cmd.stdout_lossy();
+    assert!(!stdout.contains("No files changed"), "{}", stdout);
+    assert!(read_string(&artifact).contains(r#""b()""#));
+});
+
+// checks that `--profile-exec` reports the time spent in each phase
+forgetest_init!(can_profile_exec, |prj: TestProject, mut cmd: TestCommand| {
+    cmd.set_current_dir(prj.root());
+
+    cmd.args(["test", "--profile-exec"]);
+    let stderr = cmd.stderr_lossy();
+    for phase in ["config", "graph", "solc", "evm"] {
+        assert!(stderr.lines().any(|line| line.starts_with(phase)), "{}", stderr);
+    }
+    assert!(stderr.contains("Wall time"), "{}", stderr);
+
+    cmd.forge_fuse().args(["build", "--force", "--profile-exec=json"]);
+    cmd.assert_non_empty_stdout();
+    let profile: serde_json::Value =
+        serde_json::from_str(&read_string(prj.root().join("forge-profile.json"))).unwrap();
+    let events = profile["traceEvents"].as_array().unwrap();
+    assert!(events.iter().any(|event| event["cat"] == "solc"));
+});
+
+// checks that `--format-json` emits the solc output of all sources, including cached ones
+forgetest_init!(can_build_format_json, |prj: TestProject, mut cmd: TestCommand| {
+    cmd.set_current_dir(prj.root());
+    cmd.arg("build");
+    cmd.assert_non_empty_stdout();
+
+    cmd.arg("--format-json");
+    let output: serde_json::Value = serde_json::from_str(&cmd.stdout()).unwrap();
+    let contracts = output["contracts"].as_object().unwrap();
+    assert!(contracts.keys().any(|file| file.ends_with("src/Contract.sol")), "{:?}", contracts);
+
+    let path = prj.root().join("solc-output.json");
+    cmd.forge_fuse().args(["build", &format!("--format-json={}", path.display())]);
+    cmd.assert_non_empty_stdout();
+    let output: serde_json::Value = serde_json::from_str(&read_string(&path)).unwrap();
+    assert!(output["sources"].as_object().map_or(false, |sources| !sources.is_empty()));
+
+    // the project's cache is kept, the next build compiles nothing
+    cmd.forge_fuse().arg("build");
+    assert!(cmd.stdout_lossy().contains("No files changed"));
+
+    cmd.forge_fuse().args(["build", "--format-json", "--sizes"]);
+    cmd.assert_err();
+});
+
+// checks that `--format-json` emits compiler errors as JSON and fails
+forgetest!(can_build_format_json_with_errors, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Broken",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+contract Broken {
+    function f() public { undefined(); }
+}
+"#,
+        )
+        .unwrap();
+
+    cmd.args(["build", "--format-json", "--root"]).arg(prj.root());
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let output: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let errors = output["errors"].as_array().unwrap();
+    assert!(errors.iter().any(|error| error["severity"] == "error"), "{errors:?}");
+});
+
+// checks that `flatten` orders the imports first and combines the licenses and pragmas
+forgetest!(can_flatten_with_combined_headers, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Base",
+            r#"
+// SPDX-License-Identifier: MIT
+pragma solidity >=0.6.2 <0.9.0;
+pragma abicoder v2;
+contract Base {}
+"#,
+        )
+        .unwrap();
+    let target = prj
+        .inner()
+        .add_source(
+            "Vault",
+            r#"
+// SPDX-License-Identifier: GPL-3.0-or-later
+pragma solidity ^0.8.0;
+pragma abicoder v2;
+import {Base} from "./Base.sol";
+contract Vault is Base {}
+"#,
+        )
+        .unwrap();
+
+    cmd.arg("flatten").arg(&target);
+    let flattened = cmd.stdout_lossy();
+    assert!(flattened.starts_with(
+        "// SPDX-License-Identifier: MIT AND GPL-3.0-or-later
+pragma solidity >=0.6.2 <0.9.0 ^0.8.0;
+pragma abicoder v2;
+"
+    ));
+    assert_eq!(flattened.matches("SPDX-License-Identifier").count(), 1);
+    assert!(!flattened.contains("import"));
+    assert!(flattened.find("contract Base").unwrap() < flattened.find("contract Vault").unwrap());
+
+    prj.inner()
+        .add_source(
+            "Legacy",
+            r#"
+pragma solidity ^0.7.6;
+import "./Vault.sol";
+contract Legacy {}
+"#,
+        )
+        .unwrap();
+    cmd.forge_fuse().arg("flatten").arg(prj.root().join("src/Legacy.sol"));
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("no solc version satisfies all of them"));
+});
+
+// checks that `bind` builds the project and only generates the bindings again if the artifacts
+// changed
+forgetest_init!(can_bind_only_changed_artifacts, |prj: TestProject, mut cmd: TestCommand| {
+    cmd.set_current_dir(prj.root());
+    cmd.arg("bind");
+    assert!(cmd.stdout_lossy().contains("Bindings have been output to"));
+    let bindings = prj.paths().artifacts.join("bindings");
+    assert!(bindings.join("Cargo.toml").exists());
+
+    cmd.forge_fuse().args(["bind", "--overwrite"]);
+    assert!(cmd.stdout_lossy().contains("No artifacts changed"));
+
+    cmd.forge_fuse().args(["bind", "--overwrite", "--force"]);
+    assert!(cmd.stdout_lossy().contains("Bindings have been output to"));
+});
+
+// checks that `tree --dot` prints the import graph and marks the imports of cycles
+forgetest!(can_print_tree_dot_with_cycles, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "A",
+            r#"
+pragma solidity ^0.8.10;
+import "./B.sol";
+contract A {}
+"#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "B",
+            r#"
+pragma solidity ^0.8.10;
+import "./A.sol";
+contract B {}
+"#,
+        )
+        .unwrap();
+
+    cmd.arg("tree").arg("--root").arg(prj.root()).arg("--dot");
+    let dot = cmd.stdout_lossy();
+    assert!(dot.starts_with("digraph imports {"));
+    assert!(dot.contains(r#""src/A.sol" -> "src/B.sol" [color=red];"#));
+
+    cmd.forge_fuse().arg("tree").arg("--root").arg(prj.root());
+    let output = cmd.unchecked_output();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Import cycle between"));
+});
+
+// checks that `doc` renders the natspec of the contracts and of the functions they inherit
+forgetest!(can_generate_docs, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "IVault",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+interface IVault {
+    /// @notice Deposits tokens into the vault
+    /// @param amount The amount of tokens
+    function deposit(uint256 amount) external;
+
+    /// @notice Withdraws all tokens
+    function withdraw() external;
+}
+"#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "Vault",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+import "./IVault.sol";
+/// @title A vault
+abstract contract Vault is IVault {
+    /// @inheritdoc IVault
+    function deposit(uint256 amount) external override {}
+}
+"#,
+        )
+        .unwrap();
+
+    cmd.arg("doc").arg("--root").arg(prj.root());
+    assert!(cmd.stdout_lossy().contains("Documentation written to"));
+
+    let docs = prj.root().join("docs");
+    assert!(docs.join("book.toml").exists());
+    let summary = fs::read_to_string(docs.join("src/SUMMARY.md")).unwrap();
+    assert!(summary.contains("[Vault](src/Vault.sol/Vault.md)"));
+
+    let page = fs::read_to_string(docs.join("src/src/Vault.sol/Vault.md")).unwrap();
+    assert!(page.contains("**A vault**"));
+    assert!(page.contains("Deposits tokens into the vault"));
+    assert!(page.contains("|`amount`|`uint256`|The amount of tokens|"));
+    assert!(page.contains("## Inherited from [IVault](../../src/IVault.sol/IVault.md)"));
+    assert!(page.contains("Withdraws all tokens"));
+});
+
+// checks that `forge migrate` translates a hardhat config and scaffolds the test stubs
+forgetest!(can_migrate_hardhat_project, |prj: TestProject, mut cmd: TestCommand| {
+    let root = prj.root();
+    fs::write(
+        root.join("hardhat.config.js"),
+        r#"
+module.exports = {
+  solidity: {
+    version: "0.8.10",
+    settings: { optimizer: { enabled: true, runs: 500 } },
+  },
+  networks: {
+    goerli: { url: process.env.GOERLI_RPC_URL, accounts: [] },
+  },
+};
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("node_modules/@openzeppelin/contracts/token")).unwrap();
+    fs::create_dir_all(root.join("contracts")).unwrap();
+    fs::write(
+        root.join("contracts/Token.sol"),
+        r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+import "@openzeppelin/contracts/token/ERC20.sol";
+contract Token {}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["migrate", "--force", "--root"]).arg(root);
+    assert!(cmd.stdout_lossy().contains("Created foundry.toml from hardhat.config.js"));
+
+    let config = fs::read_to_string(root.join("foundry.toml")).unwrap();
+    assert!(config.contains("src = \"contracts\"\n"));
+    assert!(config.contains("\"@openzeppelin/=node_modules/@openzeppelin/\","));
+    assert!(config.contains("solc_version = \"0.8.10\"\noptimizer = true\noptimizer_runs = 500\n"));
+    assert!(config.contains("[default.rpc_endpoints]\ngoerli = \"${GOERLI_RPC_URL}\"\n"));
+
+    let stub = fs::read_to_string(root.join("test/foundry/Token.t.sol")).unwrap();
+    assert!(stub.contains("import \"../../contracts/Token.sol\";"));
+    assert!(stub.contains("contract TokenTest is Test {"));
+});
+
+// checks that `forge selectors` lists the selectors and detects the selectors two contracts share
+forgetest!(can_list_selectors_and_collisions, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Facets",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+contract BurnFacet {
+    event Burned(uint256 amount);
+    function burn(uint256 amount) external {}
+}
+contract StorageFacet {
+    function collate_propagate_storage(bytes16 key) external {}
+}
+"#,
+        )
+        .unwrap();
+
+    cmd.args(["selectors", "list", "BurnFacet", "--root"]).arg(prj.root());
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("src/Facets.sol:BurnFacet"));
+    assert!(stdout.contains("0x42966c68  burn(uint256)"));
+    assert!(stdout.contains("Burned(uint256)"));
+
+    cmd.forge_fuse().args(["selectors", "collisions", "BurnFacet", "StorageFacet", "--root"]);
+    cmd.arg(prj.root());
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(
+        "0x42966c68  BurnFacet::burn(uint256)  StorageFacet::collate_propagate_storage(bytes16)"
+    ));
+});
+
+/// Returns true if `program` can be run with `arg`
+fn can_run(program: &str, arg: &str) -> bool {
+    Command::new(program).arg(arg).output().map_or(false, |out| out.status.success())
+}
+
+/// Compiles a project that imports from `lib` with the `backend`
+fn compile_with_backend(prj: TestProject, mut cmd: TestCommand, backend: SolcBackend) {
+    prj.write_config(Config {
+        solc: Some("0.8.10".into()),
+        solc_backend: backend,
+        ..Default::default()
+    });
+    prj.inner()
+        .add_source(
+            "Foo",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "../lib/bar/Bar.sol";
+contract Foo is Bar {}
+   "#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_lib(
+            "bar/Bar",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Bar {}
+   "#,
+        )
+        .unwrap();
+
+    // solc is passed the root as `--base-path` and `lib` as `--allow-paths`
+    cmd.arg("build");
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Compiler run successful"), "{output}");
+    assert!(prj.paths().artifacts.join("Foo.sol/Foo.json").exists());
+}
+
+// tests that a project compiles with the `ethereum/solc` image if docker is available
+forgetest!(can_compile_with_docker_solc, |prj: TestProject, cmd: TestCommand| {
+    if !can_run("docker", "info") {
+        eprintln!("skipping, docker is not available");
+        return
+    }
+    compile_with_backend(prj, cmd, SolcBackend::Docker);
+});
+
+// tests that a project compiles with solc-js if node and npm are available
+forgetest!(can_compile_with_solcjs, |prj: TestProject, cmd: TestCommand| {
+    let npm = if cfg!(windows) { "npm.cmd" } else { "npm" };
+    if !can_run("node", "--version") || !can_run(npm, "--version") {
+        eprintln!("skipping, node is not available");
+        return
+    }
+    compile_with_backend(prj, cmd, SolcBackend::SolcJs);
+});
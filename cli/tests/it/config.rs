@@ -1,18 +1,20 @@
 //! Contains various tests for checking forge commands related to config values
 use ethers::{
     prelude::artifacts::YulDetails,
-    solc::artifacts::RevertStrings,
+    solc::artifacts::{BytecodeHash, RevertStrings},
     types::{Address, U256},
 };
 use forge::executor::opts::EvmOpts;
 use foundry_cli_test_utils::{
+    config::assert_config,
     ethers_solc::{remappings::Remapping, EvmVersion},
     forgetest, forgetest_init, pretty_eq,
     util::{pretty_err, OutputExt, TestCommand, TestProject},
 };
 use foundry_config::{
     cache::{CachedChains, CachedEndpoints, StorageCachingConfig},
-    Config, OptimizerDetails, SolcReq,
+    Config, ContractLimits, CoverageConfig, CoverageThresholds, FormatterConfig, InvariantConfig,
+    OptimizerDetails, SolcBackend, SolcReq,
 };
 use std::{fs, path::PathBuf, str::FromStr};
 
@@ -37,7 +39,11 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         force: true,
         evm_version: EvmVersion::Byzantium,
         gas_reports: vec!["Contract".to_string()],
+        gas_reports_ignore: vec!["Mock*".to_string()],
+        coverage_ignore: vec!["test/**".to_string()],
+        analyzers: vec!["slither".to_string(), "mythril".to_string()],
         solc: Some(SolcReq::Local(PathBuf::from("custom-solc"))),
+        solc_backend: SolcBackend::Auto,
         auto_detect_solc: false,
         offline: true,
         optimizer: false,
@@ -52,6 +58,8 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         extra_output_files: Default::default(),
         names: true,
         sizes: true,
+        deny_oversized_contracts: true,
+        notes: true,
         test_pattern: None,
         test_pattern_inverse: None,
         contract_pattern: None,
@@ -61,10 +69,23 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         fuzz_runs: 1000,
         fuzz_max_local_rejects: 2000,
         fuzz_max_global_rejects: 100203,
+        fuzz_seed: Some(1337),
+        fuzz_dictionary_weight: 75,
+        test_timeout: Some(30),
+        slow_test_threshold: Some(500),
+        invariant: InvariantConfig { runs: 64, depth: 30, fail_on_revert: true },
+        coverage: CoverageConfig {
+            thresholds: CoverageThresholds { min_lines: Some(80.), ..Default::default() },
+            paths: Default::default(),
+        },
+        fmt: FormatterConfig { line_length: 100, bracket_spacing: true, ..Default::default() },
+        contract_limits: ContractLimits { max_size: Some(20000), ..Default::default() },
+        constructor_args: Default::default(),
         ffi: true,
         sender: "00a329c0648769A73afAc7F9381D08FB43dBEA72".parse().unwrap(),
         tx_origin: "00a329c0648769A73afAc7F9F81E08FB43dBEA72".parse().unwrap(),
         initial_balance: U256::from(0xffffffffffffffffffffffffu128),
+        labeled_account_balance: U256::exp10(20),
         block_number: 10,
         fork_block_number: Some(200),
         chain_id: Some(9999.into()),
@@ -78,6 +99,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         memory_limit: 2u64.pow(25),
         eth_rpc_url: Some("localhost".to_string()),
         etherscan_api_key: None,
+        etherscan: Default::default(),
         verbosity: 4,
         remappings: vec![Remapping::from_str("forge-std=lib/forge-std/").unwrap().into()],
         libraries: vec![
@@ -89,10 +111,15 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
             chains: CachedChains::None,
             endpoints: CachedEndpoints::Remote,
         },
+        rpc_endpoints: Default::default(),
+        fs_permissions: Default::default(),
         no_storage_caching: true,
         bytecode_hash: Default::default(),
+        use_literal_content: true,
         revert_strings: Some(RevertStrings::Strip),
         sparse_mode: true,
+        build_info: true,
+        cache_test_results: true,
         __non_exhaustive: (),
     };
     prj.write_config(input.clone());
@@ -182,9 +209,12 @@ forgetest_init!(can_override_config, |prj: TestProject, mut cmd: TestCommand| {
 forgetest_init!(can_detect_config_vals, |prj: TestProject, mut cmd: TestCommand| {
     cmd.set_current_dir(prj.root());
     let url = "http://127.0.0.1:8545";
-    let config = prj.config_from_output(["--no-auto-detect", "--rpc-url", url]);
-    assert!(!config.auto_detect_solc);
-    assert_eq!(config.eth_rpc_url, Some(url.to_string()));
+    assert_config(&prj)
+        .with_args(["--no-auto-detect", "--rpc-url", url])
+        .key("auto_detect_solc")
+        .eq(false)
+        .key("eth_rpc_url")
+        .eq(url);
 
     let mut config = Config::load_with_root(prj.root());
     config.eth_rpc_url = Some("http://127.0.0.1:8545".to_string());
@@ -194,9 +224,12 @@ forgetest_init!(can_detect_config_vals, |prj: TestProject, mut cmd: TestCommand|
         Config::FILE_NAME,
         &config.to_string_pretty().unwrap().replace("eth_rpc_url", "eth-rpc-url"),
     );
-    let config = prj.config_from_output(["--force"]);
-    assert!(!config.auto_detect_solc);
-    assert_eq!(config.eth_rpc_url, Some(url.to_string()));
+    assert_config(&prj)
+        .with_args(["--force"])
+        .key("auto_detect_solc")
+        .eq(false)
+        .key("eth_rpc_url")
+        .eq(url);
 });
 
 // checks that `clean` removes dapptools style paths
@@ -216,8 +249,27 @@ forgetest_init!(can_get_evm_opts, |prj: TestProject, mut cmd: TestCommand| {
 
 // checks that we can set various config values
 forgetest_init!(can_set_config_values, |prj: TestProject, _cmd: TestCommand| {
-    let config = prj.config_from_output(["--via-ir"]);
-    assert!(config.via_ir);
+    assert_config(&prj).with_args(["--via-ir"]).key("via_ir").eq(true);
+
+    assert_config(&prj)
+        .with_args(["--via-ir", "--bytecode-hash", "none", "--yul-stack-allocation"])
+        .key("bytecode_hash")
+        .eq(BytecodeHash::None)
+        .key("optimizer_details.yulDetails.stackAllocation")
+        .eq(true);
+});
+
+// checks that unsupported combinations of solc settings are rejected before solc is invoked
+forgetest_init!(rejects_unsupported_solc_settings, |prj: TestProject, mut cmd: TestCommand| {
+    cmd.set_current_dir(prj.root());
+
+    cmd.args(["build", "--yul-stack-allocation"]);
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("requires `via_ir = true`"));
+
+    cmd.forge_fuse().args(["build", "--via-ir", "--use", "0.8.10"]);
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("requires solc 0.8.13 or later"));
 });
 
 // tests that solc can be explicitly set
@@ -132,3 +132,61 @@ forgetest!(can_create_simple_on_goerli, |prj: TestProject, cmd: TestCommand| {
 forgetest!(can_create_oracle_on_goerli, |prj: TestProject, cmd: TestCommand| {
     create_on_chain(EnvExternalities::goerli(), prj, cmd, |prj| setup_oracle(prj));
 });
+
+/// An endpoint nothing listens on, the constructor arguments are checked before it is called
+const UNREACHABLE_RPC: &str = "http://127.0.0.1:1";
+
+/// Adds a contract with a `(uint256, address, string)` constructor
+fn setup_with_constructor(prj: &TestProject) -> String {
+    prj.inner()
+        .add_source(
+            "ConstructorArgs",
+            r#"
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.13;
+contract ConstructorArgs {
+    constructor(uint256 a, address b, string memory c) {}
+}
+"#,
+        )
+        .unwrap();
+
+    "src/ConstructorArgs.sol:ConstructorArgs".to_string()
+}
+
+// tests that the number of constructor arguments is checked before anything is sent
+forgetest!(can_reject_wrong_number_of_constructor_args, |prj: TestProject, mut cmd: TestCommand| {
+    let contract = setup_with_constructor(&prj);
+    let address = format!("{:?}", Address::random());
+
+    cmd.args(["create", contract.as_str(), "--rpc-url", UNREACHABLE_RPC, "--constructor-args"])
+        .args(["1", address.as_str()]);
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("the constructor expects 3 arguments, got 2"));
+
+    cmd.forge_fuse()
+        .args(["create", contract.as_str(), "--rpc-url", UNREACHABLE_RPC, "--constructor-args"])
+        .args(["1", address.as_str(), "foo", "bar"]);
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("the constructor expects 3 arguments, got 4"));
+});
+
+// tests that `--constructor-args-path` reads one argument per line
+forgetest!(can_read_constructor_args_file_with_newlines, |prj: TestProject, mut cmd: TestCommand| {
+    let contract = setup_with_constructor(&prj);
+    let args = prj.root().join("args.txt");
+    std::fs::write(&args, format!("1\n{:?}\r\nhello world\n\n", Address::random())).unwrap();
+
+    cmd.args(["create", contract.as_str(), "--rpc-url", UNREACHABLE_RPC])
+        .arg("--constructor-args-path")
+        .arg(&args);
+    cmd.assert_err();
+    // the arguments are parsed, it only fails to reach the endpoint
+    let stderr = cmd.stderr_lossy();
+    assert!(!stderr.contains("constructor"), "{stderr}");
+    assert!(!stderr.contains("parse"), "{stderr}");
+
+    std::fs::write(&args, "1 foo\nbar\n").unwrap();
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("the constructor expects 3 arguments, got 2"));
+});
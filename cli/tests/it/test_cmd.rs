@@ -186,6 +186,328 @@ contract FailTest is DSTest {
     cmd.stdout().contains("[PASS]") && !cmd.stdout().contains("[FAIL]")
 });
 
+// tests that repeated and inverse filters must all match
+forgetest!(can_test_with_combined_filters, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "integration/VaultTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "../test.sol";
+contract VaultTest is DSTest {
+    function testDeposit() external {
+        assertTrue(true);
+    }
+
+    function testDepositAll() external {
+        assertTrue(true);
+    }
+
+    function testWithdraw() external {
+        assertTrue(true);
+    }
+}
+contract VaultForkTest is DSTest {
+    function testDeposit() external {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--match-path", "*/integration/**", "--match-path", "*Vault*"]);
+    cmd.args(["--match-test", "Deposit", "--no-match-test", "All$"]);
+    cmd.args(["--no-match-contract", "Fork"]);
+    let stdout = cmd.stdout();
+    assert!(stdout.contains("[PASS] testDeposit()"));
+    assert!(!stdout.contains("testDepositAll()"));
+    assert!(!stdout.contains("testWithdraw()"));
+    assert!(!stdout.contains("VaultForkTest"));
+});
+
+// tests that using the --exact option only runs the addressed test
+forgetest!(can_test_with_exact_id, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ATest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ATest is DSTest {
+    function testPass() external {
+        assertTrue(true);
+    }
+
+    function testFail() external {
+        assertTrue(false);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--exact", "ATest.t.sol:ATest:testPass"]);
+    let stdout = cmd.stdout();
+    assert!(stdout.contains("[PASS] testPass()"));
+    assert!(!stdout.contains("testFail()"));
+});
+
+// tests that test ids are read from stdin with --exact-stdin
+forgetest!(can_test_with_exact_ids_from_stdin, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ATest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ATest is DSTest {
+    function testA() external {}
+
+    function testB() external {}
+
+    function testC() external {}
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--exact-stdin"]);
+    let stdout = cmd.stdout_with_stdin("ATest.t.sol:ATest:testA\n\nATest:testC\n");
+    assert!(stdout.contains("[PASS] testA()"));
+    assert!(stdout.contains("[PASS] testC()"));
+    assert!(!stdout.contains("testB()"));
+});
+
+// tests that `--replay-failures` only reruns the tests that failed in the previous run
+forgetest!(can_replay_failures, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ReplayTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ReplayTest is DSTest {
+    function testPass() external {
+        assertTrue(true);
+    }
+
+    function testFuzzFail(uint256 x) external {
+        assertTrue(x == 0);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("test");
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("[PASS] testPass()"));
+    assert!(stdout.contains("testFuzzFail(uint256)"));
+
+    cmd.forge_fuse().args(["test", "--replay-failures"]);
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(!stdout.contains("testPass()"));
+    assert!(stdout.contains("[FAIL. Counterexample:"));
+    assert!(stdout.contains("(runs: 0,"));
+});
+
+// tests that failing fuzz inputs are shrunk and written to a file that can be replayed
+forgetest!(can_shrink_and_replay_fuzz_failure, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ShrinkTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ShrinkTest is DSTest {
+    function testFuzzShrink(uint256 x, bool flag) external {
+        flag;
+        assertTrue(x < 1000);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("test");
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("args=[1000, false]"));
+    assert!(stdout.contains("--match-test testFuzzShrink --fuzz-input"));
+
+    let path =
+        prj.root().join("cache/fuzz/failures/src/ShrinkTest.t.sol/ShrinkTest.testFuzzShrink.json");
+    assert!(path.exists());
+
+    cmd.forge_fuse().args(["test", "--fuzz-input"]).arg(&path);
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("args=[1000, false]"));
+    assert!(stdout.contains("(runs: 0,"));
+
+    // inputs that do not call the test are rejected instead of decoded
+    let mut failure: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    failure["calldata"] = "0x12".into();
+    std::fs::write(&path, failure.to_string()).unwrap();
+    cmd.forge_fuse().args(["test", "--fuzz-input"]).arg(&path);
+    cmd.assert_err();
+    let stderr = cmd.stderr_lossy();
+    assert!(stderr.contains("The recorded input 0x12 is no call of testFuzzShrink(uint256,bool)"));
+    assert!(!stderr.contains("panicked"));
+});
+
+// tests that fuzz runs with the same seed generate the same inputs, and that failures print it
+forgetest!(can_rerun_fuzz_tests_with_seed, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "SeedTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract SeedTest is DSTest {
+    function testFuzzBytes(uint256 x) external {
+        while (x > 0) {
+            x >>= 8;
+        }
+    }
+
+    function testFuzzFail(uint256 x) external {
+        assertTrue(x < 1000);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--fuzz-seed", "42", "--match-test", "testFuzzBytes"]);
+    let first = cmd.stdout_lossy();
+    let gas = |stdout: &str| {
+        stdout.lines().find(|line| line.contains("testFuzzBytes")).unwrap().to_string()
+    };
+    cmd.forge_fuse().args(["test", "--fuzz-seed", "42", "--match-test", "testFuzzBytes"]);
+    assert_eq!(gas(&first), gas(&cmd.stdout_lossy()));
+
+    cmd.forge_fuse().args(["test", "--fuzz-seed", "42", "--match-test", "testFuzzFail"]);
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("Rerun with the same inputs: --fuzz-seed 42"));
+});
+
+// tests that `--gas-report-json` writes the gas report of the configured contracts
+forgetest!(can_write_gas_report_json, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "CounterTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract Counter {
+    uint256 public count;
+    function increment() external {
+        count++;
+    }
+}
+contract Helper {
+    function help() external {}
+}
+contract CounterTest is DSTest {
+    function testIncrement() external {
+        Counter counter = new Counter();
+        counter.increment();
+        counter.increment();
+        new Helper().help();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let config = Config { gas_reports: vec!["Counter".to_string()], ..Default::default() };
+    prj.write_config(config);
+
+    let path = prj.root().join("gas.json");
+    cmd.args(["test", "--gas-report-json"]).arg(&path);
+    cmd.assert_non_empty_stdout();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let counter = &report["contracts"]["Counter"];
+    assert!(counter["size"].as_str().is_some());
+    assert_eq!(counter["functions"]["increment"]["calls"].as_array().unwrap().len(), 2);
+    assert!(report["contracts"].get("Helper").is_none());
+});
+
+// tests that `forge coverage` writes an lcov report of the project's sources
+forgetest!(can_write_lcov_coverage_report, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "Counter.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Counter {
+    uint256 public count;
+    function increment(uint256 max) external {
+        if (count < max) {
+            count += 1;
+        }
+    }
+}
+   "#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "CounterTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+import "./Counter.sol";
+contract CounterTest is DSTest {
+    function testIncrement() external {
+        Counter counter = new Counter();
+        counter.increment(1);
+        assertEq(counter.count(), 1);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["coverage", "--report", "lcov"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Wrote LCOV report"));
+
+    let lcov = std::fs::read_to_string(prj.root().join("lcov.info")).unwrap();
+    assert!(lcov.contains("SF:src/Counter.sol"));
+    assert!(lcov.contains("FN:6,increment"));
+    assert!(lcov.contains("BRF:2"));
+    assert!(lcov.contains("end_of_record"));
+});
+
 // tests that `forge test` will pick up tests that are stored in the `test = <path>` config value
 forgetest!(can_run_test_in_custom_test_folder, |prj: TestProject, mut cmd: TestCommand| {
     cmd.set_current_dir(prj.root());
@@ -277,3 +599,99 @@ contract ContractTest is DSTest {
             ));
     }
 );
+
+// tests that an invalid inline fork config is reported as a warning and the tests still run
+forgetest!(warns_on_invalid_inline_fork_config, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "InlineFork.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.8.10;
+import "./test.sol";
+/// forge-config: default.fork.block = latest
+contract InlineForkTest is DSTest {
+    function testLocal() public {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("test");
+    let output = cmd.unchecked_output();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[PASS] testLocal()"));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Invalid value `latest` of `fork.block` in inline config of InlineForkTest"));
+});
+
+// tests that a fork can't be run offline if its chain is unknown, which locates its cache
+forgetest!(fork_fails_offline_with_unknown_chain, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+    prj.write_config(Config {
+        eth_rpc_url: Some("https://rpc.example.org".to_string()),
+        fork_block_number: Some(1),
+        ..Default::default()
+    });
+
+    prj.inner()
+        .add_source(
+            "Offline.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.8.10;
+import "./test.sol";
+contract OfflineTest is DSTest {
+    function testFork() public {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--offline"]);
+    cmd.assert_err();
+    let stderr = cmd.stderr_lossy();
+    assert!(stderr.contains("The chain id of https://rpc.example.org can not be fetched offline"));
+});
+
+// tests that unchanged tests are reported as cached on the next run and run again with `--force`
+forgetest!(can_cache_test_results, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+    prj.write_config(Config { cache_test_results: true, ..Default::default() });
+
+    prj.inner()
+        .add_source(
+            "Cached.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.8.10;
+import "./test.sol";
+contract CachedTest is DSTest {
+    function testCached() public {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("test");
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testCached()"));
+    assert!(!stdout.contains("(cached)"));
+
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testCached()"));
+    assert!(stdout.contains("(cached)"));
+
+    cmd.arg("--force");
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testCached()"));
+    assert!(!stdout.contains("(cached)"));
+});
@@ -0,0 +1,112 @@
+//! Constructor arguments of parameterized test contracts, configured in the `[constructor_args]`
+//! table
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Container type for the constructor arguments of test contracts, `contract name -> arguments`
+///
+/// A test contract whose constructor takes arguments is deployed once per configured argument
+/// set and every instance is run separately. The argument sets are either listed inline or read
+/// from a JSON fixture file, relative to the project root:
+///
+/// ```toml
+/// [constructor_args]
+/// TokenTest = [["6"], ["18"]]
+/// VaultTest = "test/fixtures/vaults.json"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConstructorArgs {
+    contracts: BTreeMap<String, ArgumentSets>,
+}
+
+/// The constructor argument sets of a single test contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ArgumentSets {
+    /// The argument sets, every set is a list of arguments
+    Inline(Vec<Vec<String>>),
+    /// Path to a JSON file that contains an array of argument sets
+    Fixture(PathBuf),
+}
+
+impl ConstructorArgs {
+    /// Creates a new set of constructor arguments from `contract name -> argument sets` pairs
+    pub fn new(contracts: impl IntoIterator<Item = (impl Into<String>, ArgumentSets)>) -> Self {
+        Self { contracts: contracts.into_iter().map(|(name, args)| (name.into(), args)).collect() }
+    }
+
+    /// Returns true if no constructor arguments are configured
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// Returns the argument sets of all contracts, reading fixture files relative to `root`
+    ///
+    /// Values of fixture files that are not JSON strings are converted to their JSON
+    /// representation, so `[[6, true]]` is equivalent to `[["6", "true"]]`.
+    pub fn resolve(
+        &self,
+        root: impl AsRef<Path>,
+    ) -> eyre::Result<BTreeMap<String, Vec<Vec<String>>>> {
+        self.contracts
+            .iter()
+            .map(|(name, args)| {
+                let sets = match args {
+                    ArgumentSets::Inline(sets) => sets.clone(),
+                    ArgumentSets::Fixture(path) => read_fixture(root.as_ref().join(path))?,
+                };
+                Ok((name.clone(), sets))
+            })
+            .collect()
+    }
+}
+
+/// Reads the argument sets stored in the JSON fixture at `path`
+///
+/// The fixture is an array of argument sets, every set is an array of arguments.
+pub fn read_fixture(path: impl AsRef<Path>) -> eyre::Result<Vec<Vec<String>>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| eyre::eyre!("Failed to read fixture {}: {}", path.display(), err))?;
+    let sets: Vec<Vec<serde_json::Value>> = serde_json::from_str(&content)
+        .map_err(|err| eyre::eyre!("Invalid fixture {}: {}", path.display(), err))?;
+    Ok(sets
+        .into_iter()
+        .map(|set| {
+            set.into_iter()
+                .map(|value| match value {
+                    serde_json::Value::String(s) => s,
+                    value => value.to_string(),
+                })
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_resolve_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = r#"[[6, "USDC", true], [18, "DAI", false]]"#;
+        std::fs::write(dir.path().join("vaults.json"), fixture).unwrap();
+        let args = ConstructorArgs::new([
+            ("TokenTest", ArgumentSets::Inline(vec![vec!["6".to_string()]])),
+            ("VaultTest", ArgumentSets::Fixture("vaults.json".into())),
+        ]);
+
+        let resolved = args.resolve(dir.path()).unwrap();
+        assert_eq!(resolved["TokenTest"], vec![vec!["6".to_string()]]);
+        assert_eq!(
+            resolved["VaultTest"],
+            vec![vec!["6", "USDC", "true"], vec!["18", "DAI", "false"]]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! Configuration for `forge coverage`
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Settings for `forge coverage`, configured in the `[coverage]` table
+///
+/// The minimum coverage is enforced with `forge coverage --check`, for example
+///
+/// ```toml
+/// [coverage]
+/// min_lines = 80
+/// min_branches = 60
+///
+/// [coverage.paths."src/token/**"]
+/// min_lines = 95
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoverageConfig {
+    /// The minimum total coverage of all source files
+    #[serde(flatten)]
+    pub thresholds: CoverageThresholds,
+    /// The minimum coverage of every source file matching a glob
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub paths: BTreeMap<String, CoverageThresholds>,
+}
+
+impl CoverageConfig {
+    /// Returns true if any minimum coverage is configured
+    pub fn has_thresholds(&self) -> bool {
+        !self.thresholds.is_empty() || self.paths.values().any(|t| !t.is_empty())
+    }
+
+    /// Returns the minimum coverage of the first glob in `paths` that matches the source file
+    pub fn path_thresholds(&self, path: &str) -> Option<&CoverageThresholds> {
+        let path = crate::slash_path(path);
+        let path = path.as_str();
+        self.paths
+            .iter()
+            .find(|(pattern, _)| {
+                globset::Glob::new(pattern)
+                    .map_or(pattern.as_str() == path, |glob| glob.compile_matcher().is_match(path))
+            })
+            .map(|(_, thresholds)| thresholds)
+    }
+}
+
+/// The minimum coverage in percent, unset values are not enforced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoverageThresholds {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_lines: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_branches: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_functions: Option<f64>,
+}
+
+impl CoverageThresholds {
+    /// Returns true if no minimum coverage is set
+    pub fn is_empty(&self) -> bool {
+        self.min_lines.is_none() && self.min_branches.is_none() && self.min_functions.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_match_path_thresholds() {
+        let token = CoverageThresholds { min_lines: Some(95.), ..Default::default() };
+        let config = CoverageConfig {
+            thresholds: CoverageThresholds { min_lines: Some(80.), ..Default::default() },
+            paths: BTreeMap::from([("src/token/**".to_string(), token)]),
+        };
+
+        assert!(config.has_thresholds());
+        assert_eq!(config.path_thresholds("src/token/ERC20.sol"), Some(&token));
+        assert_eq!(config.path_thresholds("src/Vault.sol"), None);
+
+        // native separators, `\` on Windows
+        let native = std::path::Path::new("src").join("token").join("ERC20.sol");
+        assert_eq!(config.path_thresholds(&native.to_string_lossy()), Some(&token));
+    }
+}
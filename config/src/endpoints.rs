@@ -0,0 +1,143 @@
+//! Support for named RPC endpoints, configured in the `[rpc_endpoints]` table
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Matches env vars in the form of `${VAR_NAME}`
+const ENV_VAR_PATTERN: &str = r"\$\{\s*([a-zA-Z0-9_]+)\s*\}";
+
+/// Container type for named RPC endpoints, `alias -> url`
+///
+/// Urls may reference env vars in the form of `${VAR_NAME}`, for example
+///
+/// ```toml
+/// [rpc_endpoints]
+/// optimism = "https://optimism.alchemyapi.io/v2/${ALCHEMY_API_KEY}"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RpcEndpoints {
+    endpoints: BTreeMap<String, String>,
+}
+
+impl RpcEndpoints {
+    /// Creates a new set of endpoints from `alias -> url` pairs
+    pub fn new(
+        endpoints: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        Self { endpoints: endpoints.into_iter().map(|(a, u)| (a.into(), u.into())).collect() }
+    }
+
+    /// Returns true if no endpoints are configured
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Returns all configured aliases
+    pub fn aliases(&self) -> impl Iterator<Item = &str> + '_ {
+        self.endpoints.keys().map(String::as_str)
+    }
+
+    /// Returns the url configured for the `alias`, without interpolating env vars
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.endpoints.get(alias).map(String::as_str)
+    }
+
+    /// Returns all endpoints with their env vars interpolated
+    pub fn resolved(&self) -> ResolvedRpcEndpoints {
+        ResolvedRpcEndpoints {
+            endpoints: self
+                .endpoints
+                .iter()
+                .map(|(alias, url)| (alias.clone(), interpolate(url)))
+                .collect(),
+        }
+    }
+}
+
+/// Named RPC endpoints with their env vars interpolated
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedRpcEndpoints {
+    /// `alias -> url`, or the reason the url could not be resolved
+    pub endpoints: BTreeMap<String, Result<String, UnresolvedEnvVarError>>,
+}
+
+impl ResolvedRpcEndpoints {
+    /// Returns the url of the endpoint with the given `alias`
+    ///
+    /// Fails if there is no such endpoint, listing all available aliases, or if the url references
+    /// an env var that is not set.
+    pub fn get(&self, alias: &str) -> Result<String, String> {
+        match self.endpoints.get(alias) {
+            Some(Ok(url)) => Ok(url.clone()),
+            Some(Err(err)) => Err(err.to_string()),
+            None => {
+                let aliases = self.endpoints.keys().cloned().collect::<Vec<_>>();
+                if aliases.is_empty() {
+                    Err(format!(
+                        "No rpc endpoint `{}` found, no `[rpc_endpoints]` are configured",
+                        alias
+                    ))
+                } else {
+                    Err(format!(
+                        "No rpc endpoint `{}` found in `[rpc_endpoints]`, available aliases: {}",
+                        alias,
+                        aliases.join(", ")
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Error returned when a url references an env var that is not set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedEnvVarError {
+    /// The url that references the env var
+    pub unresolved: String,
+    /// The name of the env var
+    pub var: String,
+}
+
+impl fmt::Display for UnresolvedEnvVarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to resolve env var `{}` in `{}`", self.var, self.unresolved)
+    }
+}
+
+impl std::error::Error for UnresolvedEnvVarError {}
+
+/// Replaces all `${VAR_NAME}` occurrences in `input` with the value of the env var
+pub(crate) fn interpolate(input: &str) -> Result<String, UnresolvedEnvVarError> {
+    let re = Regex::new(ENV_VAR_PATTERN).expect("valid regex");
+    let mut res = input.to_string();
+    for caps in re.captures_iter(input) {
+        let var = &caps[1];
+        let value = std::env::var(var).map_err(|_| UnresolvedEnvVarError {
+            unresolved: input.to_string(),
+            var: var.to_string(),
+        })?;
+        res = res.replacen(&caps[0], &value, 1);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_resolve_endpoints() {
+        std::env::set_var("_FOUNDRY_TEST_RPC_KEY", "secret");
+        let endpoints = RpcEndpoints::new([
+            ("mainnet", "https://example.com/${_FOUNDRY_TEST_RPC_KEY}"),
+            ("optimism", "https://optimism.example.com/${ _FOUNDRY_TEST_MISSING_KEY }"),
+        ])
+        .resolved();
+
+        assert_eq!(endpoints.get("mainnet"), Ok("https://example.com/secret".to_string()));
+        assert!(endpoints.get("optimism").unwrap_err().contains("_FOUNDRY_TEST_MISSING_KEY"));
+        assert!(endpoints.get("arbitrum").unwrap_err().contains("mainnet, optimism"));
+    }
+}
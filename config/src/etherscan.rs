@@ -0,0 +1,93 @@
+//! Support for per chain etherscan API keys, configured in the `[etherscan]` table
+
+use crate::{
+    endpoints::{interpolate, UnresolvedEnvVarError},
+    Chain,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Container type for etherscan API keys by chain, `name -> config`
+///
+/// The name is the chain the key belongs to, unless the config sets the `chain` explicitly. Keys
+/// may reference env vars in the form of `${VAR_NAME}`, for example
+///
+/// ```toml
+/// [etherscan]
+/// mainnet = { key = "${ETHERSCAN_API_KEY}" }
+/// optimism = { key = "${OPTIMISM_API_KEY}" }
+/// arbiscan = { key = "${ARBISCAN_API_KEY}", chain = 42161 }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EtherscanConfigs {
+    configs: BTreeMap<String, EtherscanConfig>,
+}
+
+impl EtherscanConfigs {
+    /// Creates a new set of configs from `name -> config` pairs
+    pub fn new(configs: impl IntoIterator<Item = (impl Into<String>, EtherscanConfig)>) -> Self {
+        Self { configs: configs.into_iter().map(|(name, config)| (name.into(), config)).collect() }
+    }
+
+    /// Returns true if no configs are set
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// Returns the config for the `chain`, without interpolating env vars
+    ///
+    /// A config with an explicit `chain` takes precedence over a config named after the chain.
+    pub fn get(&self, chain: Chain) -> Option<&EtherscanConfig> {
+        let explicit =
+            self.configs.values().find(|config| config.chain.map(|c| c.id()) == Some(chain.id()));
+        explicit.or_else(|| {
+            self.configs.iter().find_map(|(name, config)| {
+                let named = name.parse::<Chain>().ok()?;
+                (config.chain.is_none() && named.id() == chain.id()).then(|| config)
+            })
+        })
+    }
+
+    /// Returns the API key for the `chain` with its env vars interpolated, if a config is set
+    pub fn resolve_key(&self, chain: Chain) -> Option<Result<String, UnresolvedEnvVarError>> {
+        self.get(chain).map(|config| interpolate(&config.key))
+    }
+}
+
+/// The etherscan API key of a chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EtherscanConfig {
+    /// The chain the key belongs to, defaults to the name of the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<Chain>,
+    /// The API key, may reference env vars in the form of `${VAR_NAME}`
+    pub key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::Chain as NamedChain;
+
+    #[test]
+    fn can_resolve_keys() {
+        std::env::set_var("_FOUNDRY_TEST_ETHERSCAN_KEY", "secret");
+        let configs = EtherscanConfigs::new([
+            ("mainnet", EtherscanConfig { chain: None, key: "mainnet-key".to_string() }),
+            (
+                "optimism",
+                EtherscanConfig { chain: None, key: "${_FOUNDRY_TEST_ETHERSCAN_KEY}".to_string() },
+            ),
+            (
+                "arbiscan",
+                EtherscanConfig { chain: Some(Chain::Id(42161)), key: "arbiscan-key".to_string() },
+            ),
+        ]);
+
+        assert_eq!(configs.resolve_key(NamedChain::Mainnet.into()), Some(Ok("mainnet-key".into())));
+        assert_eq!(configs.resolve_key(Chain::Id(10)), Some(Ok("secret".to_string())));
+        assert_eq!(configs.resolve_key(Chain::Id(42161)), Some(Ok("arbiscan-key".to_string())));
+        assert_eq!(configs.resolve_key(NamedChain::Goerli.into()), None);
+    }
+}
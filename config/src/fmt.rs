@@ -0,0 +1,79 @@
+//! Configuration for `forge fmt`
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of the solidity formatter, configured in the `[fmt]` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatterConfig {
+    /// Maximum line length where formatter will try to wrap the line
+    pub line_length: usize,
+    /// Number of spaces per indentation level, or the width of a tab if `style = "tab"`
+    pub tab_width: usize,
+    /// Whether to indent with spaces or tabs
+    pub style: IndentStyle,
+    /// Print spaces between brackets
+    pub bracket_spacing: bool,
+    /// How to print the `uint` and `int` aliases and their explicit `uint256` and `int256` forms
+    pub int_types: IntTypes,
+}
+
+impl Default for FormatterConfig {
+    fn default() -> Self {
+        FormatterConfig {
+            line_length: 80,
+            tab_width: 4,
+            style: IndentStyle::Space,
+            bracket_spacing: false,
+            int_types: IntTypes::Long,
+        }
+    }
+}
+
+/// The characters a line is indented with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndentStyle {
+    /// `tab_width` spaces per level
+    Space,
+    /// A tab per level
+    Tab,
+}
+
+/// The style of the 256 bit integer types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntTypes {
+    /// Print the explicit `uint256` and `int256`
+    Long,
+    /// Print the `uint` and `int` aliases
+    Short,
+    /// Print the types as they are written in the source
+    Preserve,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_fmt_table() {
+        let config: FormatterConfig = toml::from_str(
+            r#"
+            line_length = 120
+            style = "tab"
+            int_types = "short"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            FormatterConfig {
+                line_length: 120,
+                style: IndentStyle::Tab,
+                int_types: IntTypes::Short,
+                ..Default::default()
+            }
+        );
+    }
+}
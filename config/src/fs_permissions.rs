@@ -0,0 +1,140 @@
+//! Configuration of the file system access of the file system cheatcodes
+
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// The paths the file system cheatcodes may access, configured in `fs_permissions`
+///
+/// Access to a path is granted if any permission for the path, or one of its parent directories,
+/// allows it. For example
+///
+/// ```toml
+/// fs_permissions = [
+///     { access = "read", path = "./fixtures" },
+///     { access = "read-write", path = "./deployments" },
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FsPermissions {
+    permissions: Vec<PathPermission>,
+}
+
+impl FsPermissions {
+    pub fn new(permissions: impl IntoIterator<Item = PathPermission>) -> Self {
+        Self { permissions: permissions.into_iter().collect() }
+    }
+
+    /// Returns true if no permissions are configured
+    pub fn is_empty(&self) -> bool {
+        self.permissions.is_empty()
+    }
+
+    /// Returns the permissions with their relative paths joined to `root`
+    pub fn joined(&self, root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        let permissions = self
+            .permissions
+            .iter()
+            .map(|permission| PathPermission {
+                access: permission.access,
+                path: normalize(&root.join(&permission.path)),
+            })
+            .collect();
+        Self { permissions }
+    }
+
+    /// Returns true if the `access` to the `path` is allowed
+    ///
+    /// The path is normalized lexically, so `..` can not be used to escape an allowed directory.
+    /// Relative paths are compared to the paths of the permissions as is.
+    pub fn is_path_allowed(&self, path: impl AsRef<Path>, access: FsAccessKind) -> bool {
+        let path = normalize(path.as_ref());
+        self.permissions.iter().any(|permission| {
+            permission.access.allows(access) && path.starts_with(normalize(&permission.path))
+        })
+    }
+}
+
+/// The access granted to a path and everything in it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathPermission {
+    pub access: FsAccessPermission,
+    pub path: PathBuf,
+}
+
+impl PathPermission {
+    pub fn new(access: FsAccessPermission, path: impl Into<PathBuf>) -> Self {
+        Self { access, path: path.into() }
+    }
+}
+
+/// The kinds of access granted by a [`PathPermission`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FsAccessPermission {
+    /// Files may be read and written
+    ReadWrite,
+    /// Files may only be read
+    Read,
+    /// Files may only be written
+    Write,
+}
+
+impl FsAccessPermission {
+    /// Returns true if the permission allows the `access`
+    pub fn allows(&self, access: FsAccessKind) -> bool {
+        matches!(
+            (self, access),
+            (FsAccessPermission::ReadWrite, _) |
+                (FsAccessPermission::Read, FsAccessKind::Read) |
+                (FsAccessPermission::Write, FsAccessKind::Write)
+        )
+    }
+}
+
+/// The kind of access to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsAccessKind {
+    Read,
+    Write,
+}
+
+/// Removes all `.` components of the path and resolves its `..` components, without accessing the
+/// file system, since the path may not exist yet
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_check_path_permissions() {
+        let permissions = FsPermissions::new([
+            PathPermission::new(FsAccessPermission::Read, "./fixtures"),
+            PathPermission::new(FsAccessPermission::ReadWrite, "deployments"),
+        ])
+        .joined("/project");
+
+        assert!(permissions.is_path_allowed("/project/fixtures/a.json", FsAccessKind::Read));
+        assert!(!permissions.is_path_allowed("/project/fixtures/a.json", FsAccessKind::Write));
+        assert!(permissions.is_path_allowed("/project/deployments/1.json", FsAccessKind::Write));
+        assert!(!permissions.is_path_allowed("/project/src/A.sol", FsAccessKind::Read));
+        assert!(!permissions.is_path_allowed("/project/fixtures/../.env", FsAccessKind::Read));
+        assert!(!permissions.is_path_allowed("/project/fixtures2/a.json", FsAccessKind::Read));
+    }
+}
@@ -0,0 +1,21 @@
+//! Configuration for invariant testing
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for invariant tests, configured in the `[invariant]` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InvariantConfig {
+    /// The number of call sequences that are run for each invariant test
+    pub runs: u32,
+    /// The number of calls executed in a single call sequence
+    pub depth: u32,
+    /// Whether a reverting call fails the invariant test
+    pub fail_on_revert: bool,
+}
+
+impl Default for InvariantConfig {
+    fn default() -> Self {
+        InvariantConfig { runs: 256, depth: 15, fail_on_revert: false }
+    }
+}
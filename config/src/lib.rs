@@ -8,6 +8,7 @@ use ethers_solc::{
     artifacts::{
         output_selection::ContractOutputSelection, serde_helpers, BytecodeHash, DebuggingSettings,
         Libraries, ModelCheckerSettings, ModelCheckerTarget, Optimizer, RevertStrings, Settings,
+        SettingsMetadata,
     },
     cache::SOLIDITY_FILES_CACHE_FILENAME,
     error::SolcError,
@@ -43,10 +44,43 @@ use cache::{Cache, ChainCache};
 mod chain;
 pub use chain::Chain;
 
+pub mod endpoints;
+pub use endpoints::{ResolvedRpcEndpoints, RpcEndpoints, UnresolvedEnvVarError};
+
+pub mod etherscan;
+pub use etherscan::{EtherscanConfig, EtherscanConfigs};
+
+pub mod invariant;
+pub use invariant::InvariantConfig;
+
+pub mod coverage;
+pub use coverage::{CoverageConfig, CoverageThresholds};
+
+pub mod fmt;
+pub use fmt::FormatterConfig;
+
+pub mod limits;
+pub use limits::ContractLimits;
+
+pub mod fs_permissions;
+pub use fs_permissions::{FsAccessKind, FsAccessPermission, FsPermissions, PathPermission};
+
+pub mod constructor_args;
+pub use constructor_args::{ArgumentSets, ConstructorArgs};
+
+pub mod solc_backend;
+pub use solc_backend::SolcBackend;
+
 // reexport so cli types can implement `figment::Provider` to easily merge compiler arguments
 pub use figment;
 use regex::Regex;
 
+/// The tracing target of the spans and events the profiler of `forge --profile-exec` records
+///
+/// The name of a span is the phase it is reported in, e.g. `solc` or `evm`, and its `name` field
+/// what was timed, e.g. the solc version or the test.
+pub const PROFILE_TARGET: &str = "foundry::profile";
+
 /// Foundry configuration
 ///
 /// # Defaults
@@ -111,6 +145,13 @@ pub struct Config {
     pub evm_version: EvmVersion,
     /// list of contracts to report gas of
     pub gas_reports: Vec<String>,
+    /// list of contracts to exclude from gas reports, supports globs, e.g. `Mock*`
+    pub gas_reports_ignore: Vec<String>,
+    /// list of source paths and contracts to exclude from coverage reports, supports globs, e.g.
+    /// `test/**` or `Mock*`
+    pub coverage_ignore: Vec<String>,
+    /// static analyzers `forge analyze` runs, `slither` and/or `mythril`
+    pub analyzers: Vec<String>,
     /// The Solc instance to use if any.
     ///
     /// This takes precedence over `auto_detect_solc`, if a version is set then this overrides
@@ -119,14 +160,19 @@ pub struct Config {
     /// **Note** for backwards compatibility reasons this also accepts solc_version from the toml
     /// file, see [`BackwardsCompatProvider`]
     pub solc: Option<SolcReq>,
+    /// How solc is run, e.g. in docker on platforms without native solc builds
+    pub solc_backend: SolcBackend,
     /// whether to autodetect the solc compiler version to use
     pub auto_detect_solc: bool,
-    /// Offline mode, if set, network access (downloading solc) is disallowed.
+    /// Offline mode, if set, network access is disallowed: solc is not downloaded, Etherscan and
+    /// 4byte.directory are not queried and forks are only served from the fork cache. Also set
+    /// with `FOUNDRY_OFFLINE=1` or `--offline`.
     ///
     /// Relationship with `auto_detect_solc`:
     ///    - if `auto_detect_solc = true` and `offline = true`, the required solc version(s) will
     ///      be auto detected but if the solc version is not installed, it will _not_ try to
     ///      install it
+    #[serde(deserialize_with = "figment::util::bool_from_str_or_int")]
     pub offline: bool,
     /// Whether to activate optimizer
     pub optimizer: bool,
@@ -144,6 +190,9 @@ pub struct Config {
     pub eth_rpc_url: Option<String>,
     /// etherscan API key
     pub etherscan_api_key: Option<String>,
+    /// etherscan API keys by chain, take precedence over `etherscan_api_key`
+    #[serde(default, skip_serializing_if = "EtherscanConfigs::is_empty")]
+    pub etherscan: EtherscanConfigs,
     /// list of solidity error codes to always silence in the compiler output
     pub ignored_error_codes: Vec<SolidityErrorCode>,
     /// Only run test functions matching the specified regex pattern.
@@ -174,6 +223,9 @@ pub struct Config {
     pub tx_origin: Address,
     /// the initial balance of each deployed test contract
     pub initial_balance: U256,
+    /// the balance the addresses created with the `makeAddr` and `makeAccount` cheatcodes are
+    /// funded with
+    pub labeled_account_balance: U256,
     /// the block.number value during EVM execution
     pub block_number: u64,
     /// pins the block number for the state fork
@@ -233,15 +285,52 @@ pub struct Config {
     /// by proptest, to be encountered during usage of `vm.assume`
     /// cheatcode.
     pub fuzz_max_global_rejects: u32,
+    /// The seed the fuzzer generates inputs from, a new seed is used for every run if not set
+    pub fuzz_seed: Option<u64>,
+    /// The percentage of fuzz inputs whose values are drawn from the fuzz dictionary, the
+    /// constants of the bytecode, storage values and logs of the tested contracts
+    pub fuzz_dictionary_weight: u32,
+    /// The maximum wall-clock time of a single test in seconds
+    ///
+    /// Fuzz and invariant tests stop running cases once it is exceeded and fail
+    pub test_timeout: Option<u64>,
+    /// Tests that take at least this many milliseconds are listed in a report after the run
+    pub slow_test_threshold: Option<u64>,
+    /// Settings for invariant tests
+    pub invariant: InvariantConfig,
+    /// Settings for `forge coverage`, e.g. the minimum coverage
+    pub coverage: CoverageConfig,
+    /// Settings of the solidity formatter of `forge fmt`
+    pub fmt: FormatterConfig,
+    /// Limits of the compiled contracts, the build fails if a contract exceeds one
+    pub contract_limits: ContractLimits,
+    /// Constructor arguments of parameterized test contracts, `contract name -> argument sets`
+    ///
+    /// Every test contract is deployed and run once per argument set
+    #[serde(default, skip_serializing_if = "ConstructorArgs::is_empty")]
+    pub constructor_args: ConstructorArgs,
     /// Print the names of the compiled contracts
     pub names: bool,
     /// Print the sizes of the compiled contracts
     pub sizes: bool,
+    /// Fail the build if a contract exceeds the contract size limit of 24576 bytes (EIP-170)
+    pub deny_oversized_contracts: bool,
+    /// Print notes on the size, the estimated deployment gas and the number of external
+    /// functions of the compiled contracts
+    pub notes: bool,
     /// If set to true, changes compilation pipeline to go through the Yul intermediate
     /// representation.
     pub via_ir: bool,
     /// RPC storage caching settings determines what chains and endpoints to cache
     pub rpc_storage_caching: StorageCachingConfig,
+    /// Named RPC endpoints, `alias -> url`
+    ///
+    /// The aliases can be used instead of urls, for example with the `rpcUrl` cheatcode
+    #[serde(default, skip_serializing_if = "RpcEndpoints::is_empty")]
+    pub rpc_endpoints: RpcEndpoints,
+    /// The paths the file system cheatcodes may read and write, relative to the project root
+    #[serde(default, skip_serializing_if = "FsPermissions::is_empty")]
+    pub fs_permissions: FsPermissions,
     /// Disables storage caching entirely. This overrides any settings made in
     /// `rpc_storage_caching`
     pub no_storage_caching: bool,
@@ -250,6 +339,8 @@ pub struct Config {
     /// The metadata hash is machine dependent. By default, this is set to [BytecodeHash::None] to allow for deterministic code, See: <https://docs.soliditylang.org/en/latest/metadata.html>
     #[serde(with = "from_str_lowercase")]
     pub bytecode_hash: BytecodeHash,
+    /// Whether to include the source code in the metadata instead of the source hashes.
+    pub use_literal_content: bool,
     /// How to treat revert (and require) reason strings.
     #[serde(with = "serde_helpers::display_from_str_opt")]
     pub revert_strings: Option<RevertStrings>,
@@ -259,6 +350,14 @@ pub struct Config {
     /// included in solc's output selection, see also
     /// [OutputSelection](ethers_solc::artifacts::output_selection::OutputSelection)
     pub sparse_mode: bool,
+    /// Whether to cache the results of `forge test` and skip tests whose bytecode, linked
+    /// libraries and configuration did not change since they were last run
+    pub cache_test_results: bool,
+    /// Whether to write a build info file for every compilation to `<out>/build-info`
+    ///
+    /// The build info contains the exact solc input and output, the build can be reproduced from
+    /// it with `forge verify-build`.
+    pub build_info: bool,
     /// The root path where the config detection started from, `Config::with_root`
     #[doc(hidden)]
     //  We're skipping serialization here, so it won't be included in the [`Config::to_string()`]
@@ -362,6 +461,8 @@ impl Config {
     /// let config = Config::try_from(figment);
     /// ```
     pub fn try_from<T: Provider>(provider: T) -> Result<Self, figment::Error> {
+        let _span =
+            tracing::info_span!(target: PROFILE_TARGET, "config", name = "extract").entered();
         let figment = Figment::from(provider);
         let mut config = figment.extract::<Self>()?;
         config.profile = figment.profile().clone();
@@ -470,6 +571,7 @@ impl Config {
             .set_offline(self.offline)
             .set_cached(cached)
             .set_no_artifacts(no_artifacts)
+            .set_build_info(self.build_info)
             .build()?;
 
         if self.force {
@@ -489,15 +591,33 @@ impl Config {
     /// it's missing.
     ///
     /// If `solc` is [`SolcReq::Local`] then this will ensure that the path exists.
+    ///
+    /// With the docker and solc-js backends the version is run in its `ethereum/solc` image or
+    /// with solc-js instead, the `auto` backend falls back to them if no native build of the
+    /// version can be installed.
     fn ensure_solc(&self) -> Result<Option<Solc>, SolcError> {
         if let Some(ref solc) = self.solc {
             let solc = match solc {
+                SolcReq::Version(version)
+                    if matches!(self.solc_backend, SolcBackend::Docker | SolcBackend::SolcJs) =>
+                {
+                    self.solc_backend.solc(version, &self.solc_mounts(), self.offline)?
+                }
                 SolcReq::Version(version) => {
                     let v = version.to_string();
                     let mut solc = Solc::find_svm_installed_version(&v)?;
                     if solc.is_none() {
-                        Solc::blocking_install(version)?;
-                        solc = Solc::find_svm_installed_version(&v)?;
+                        match Solc::blocking_install(version) {
+                            Ok(_) => solc = Solc::find_svm_installed_version(&v)?,
+                            Err(_) if self.solc_backend == SolcBackend::Auto => {
+                                return self.solc_backend.solc(
+                                    version,
+                                    &self.solc_mounts(),
+                                    self.offline,
+                                )
+                            }
+                            Err(err) => return Err(err.into()),
+                        }
                     }
                     solc
                 }
@@ -514,9 +634,28 @@ impl Config {
             return Ok(solc)
         }
 
+        if matches!(self.solc_backend, SolcBackend::Docker | SolcBackend::SolcJs) {
+            return Err(SolcError::msg(
+                "the docker and solc-js backends require a `solc_version`, versions can't be \
+                 detected",
+            ))
+        }
         Ok(None)
     }
 
+    /// Returns the dirs solc is allowed to read from, the project root and the libs outside of it
+    fn solc_mounts(&self) -> Vec<PathBuf> {
+        let root = &self.__root.0;
+        let mut mounts = vec![root.clone()];
+        mounts.extend(
+            self.libs
+                .iter()
+                .filter_map(|lib| ethers_solc::utils::canonicalize(root.join(lib)).ok())
+                .filter(|lib| lib.is_dir() && !lib.starts_with(root)),
+        );
+        mounts
+    }
+
     /// Returns whether the compiler version should be auto-detected
     ///
     /// Returns `false` if `solc_version` is explicitly set, otherwise returns the value of
@@ -589,7 +728,7 @@ impl Config {
         {
             let mut src_remapping = Remapping {
                 name: format!("{src_dir_name}/"),
-                path: format!("{}", self.src.display()),
+                path: slash_path(&self.src),
             };
             if !src_remapping.path.ends_with('/') {
                 src_remapping.path.push('/')
@@ -621,11 +760,26 @@ impl Config {
         Libraries::parse(&self.libraries)
     }
 
+    /// Returns the etherscan API key for the `chain`
+    ///
+    /// The key of the `[etherscan]` config of the chain takes precedence over
+    /// `etherscan_api_key`. Fails if the key references an env var that is not set.
+    pub fn get_etherscan_api_key(
+        &self,
+        chain: Option<Chain>,
+    ) -> Result<Option<String>, UnresolvedEnvVarError> {
+        match chain.and_then(|chain| self.etherscan.resolve_key(chain)) {
+            Some(key) => key.map(Some),
+            None => Ok(self.etherscan_api_key.clone()),
+        }
+    }
+
     /// Returns the configured `solc` `Settings` that includes:
     ///   - all libraries
     ///   - the optimizer (including details, if configured)
     ///   - evm version
     pub fn solc_settings(&self) -> Result<Settings, SolcError> {
+        self.validate_solc_settings()?;
         let libraries = self.parsed_libraries()?.with_applied_remappings(&self.project_paths());
         let optimizer = self.optimizer();
 
@@ -643,7 +797,10 @@ impl Config {
             optimizer,
             evm_version: Some(self.evm_version),
             libraries,
-            metadata: Some(self.bytecode_hash.into()),
+            metadata: Some(SettingsMetadata {
+                use_literal_content: self.use_literal_content.then(|| true),
+                ..SettingsMetadata::from(self.bytecode_hash)
+            }),
             debug: self.revert_strings.map(|revert_strings| DebuggingSettings {
                 revert_strings: Some(revert_strings),
                 debug_info: Vec::new(),
@@ -661,6 +818,35 @@ impl Config {
         Ok(settings)
     }
 
+    /// Returns an error if the configured solc settings are a combination solc does not support
+    ///
+    /// The settings that require a newer solc are only checked if `solc_version` is set, a detected
+    /// version is validated by solc itself.
+    pub fn validate_solc_settings(&self) -> Result<(), SolcError> {
+        let stack_allocation = self
+            .optimizer_details
+            .as_ref()
+            .and_then(|details| details.yul_details.as_ref())
+            .and_then(|details| details.stack_allocation)
+            .unwrap_or_default();
+        if stack_allocation && !self.via_ir {
+            return Err(SolcError::msg(
+                "`optimizer_details.yulDetails.stackAllocation` only applies to the IR pipeline, \
+                 it requires `via_ir = true`",
+            ))
+        }
+
+        if let Some(SolcReq::Version(version)) = &self.solc {
+            // the IR pipeline is experimental before 0.8.13 but available since 0.7.2
+            if self.via_ir && *version < Version::new(0, 7, 2) {
+                return Err(SolcError::msg(format!(
+                    "`via_ir = true` requires solc 0.7.2 or later, `solc_version` is {version}"
+                )))
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the default figment
     ///
     /// The default figment reads from the following sources, in ascending
@@ -697,6 +883,7 @@ impl Config {
     /// let my_config = Config::figment_with_root(".").extract::<Config>();
     /// ```
     pub fn figment_with_root(root: impl Into<PathBuf>) -> Figment {
+        let _span = tracing::info_span!(target: PROFILE_TARGET, "config", name = "load").entered();
         Self::with_root(root).into()
     }
 
@@ -796,6 +983,66 @@ impl Config {
         Ok(())
     }
 
+    /// Sets `key` to `value` in the selected profile of the global `~/.foundry/foundry.toml`,
+    /// creating the file if it does not exist yet.
+    ///
+    /// Nested keys are separated by dots, e.g. `rpc_endpoints.mainnet`. The `value` is parsed as a
+    /// TOML value, e.g. `true` or `["lib"]`, and used as a string if it is not valid TOML.
+    ///
+    /// Returns the path of the global file.
+    pub fn set_global(key: &str, value: &str) -> eyre::Result<PathBuf> {
+        let value = value.parse::<toml_edit::Value>().unwrap_or_else(|_| value.into());
+        Self::update_global(|doc| {
+            let (parents, key) = split_config_key(key)?;
+            let mut item = &mut doc[Self::selected_profile().as_str().as_str()];
+            for parent in parents {
+                item = &mut item[parent];
+            }
+            item[key] = toml_edit::value(value);
+            Ok(())
+        })
+    }
+
+    /// Removes `key`, see [Self::set_global()], from the selected profile of the global
+    /// `~/.foundry/foundry.toml`
+    ///
+    /// Returns the path of the global file.
+    pub fn unset_global(key: &str) -> eyre::Result<PathBuf> {
+        Self::update_global(|doc| {
+            let (parents, key) = split_config_key(key)?;
+            let mut item = doc.as_table_mut().get_mut(Self::selected_profile().as_str().as_str());
+            for parent in parents {
+                item = item.and_then(|item| item.get_mut(parent));
+            }
+            if let Some(table) = item.and_then(|item| item.as_table_like_mut()) {
+                table.remove(key);
+            }
+            Ok(())
+        })
+    }
+
+    /// Updates the global `~/.foundry/foundry.toml` with the provided closure, creating the file
+    /// if it does not exist yet
+    fn update_global<F>(f: F) -> eyre::Result<PathBuf>
+    where
+        F: FnOnce(&mut toml_edit::Document) -> eyre::Result<()>,
+    {
+        let file_path = Self::foundry_dir_toml().wrap_err("Failed to find the home directory")?;
+        let content = if file_path.exists() {
+            fs::read_to_string(&file_path)
+                .wrap_err_with(|| format!("Failed to read {}", file_path.display()))?
+        } else {
+            String::new()
+        };
+        let mut doc = content.parse::<toml_edit::Document>()?;
+        f(&mut doc)?;
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&file_path, doc.to_string())?;
+        Ok(file_path)
+    }
+
     /// Sets the `libs` entry inside a `foundry.toml` file but only if it exists
     ///
     /// # Errors
@@ -851,6 +1098,15 @@ impl Config {
                 .replace("[model_checker]", &format!("[{}.model_checker]", self.profile));
         }
         s = s.replace("[rpc_storage_caching]", &format!("[{}.rpc_storage_caching]", self.profile));
+        s = s.replace("[rpc_endpoints]", &format!("[{}.rpc_endpoints]", self.profile));
+        s = s.replace("[etherscan]", &format!("[{}.etherscan]", self.profile));
+        s = s.replace("[etherscan.", &format!("[{}.etherscan.", self.profile));
+        s = s.replace("[invariant]", &format!("[{}.invariant]", self.profile));
+        s = s.replace("[coverage]", &format!("[{}.coverage]", self.profile));
+        s = s.replace("[coverage.paths.", &format!("[{}.coverage.paths.", self.profile));
+        s = s.replace("[fmt]", &format!("[{}.fmt]", self.profile));
+        s = s.replace("[contract_limits]", &format!("[{}.contract_limits]", self.profile));
+        s = s.replace("[constructor_args]", &format!("[{}.constructor_args]", self.profile));
 
         Ok(format!(
             r#"[{}]
@@ -952,8 +1208,7 @@ impl Config {
     /// Clears the foundry cache
     pub fn clean_foundry_cache() -> eyre::Result<()> {
         if let Some(cache_dir) = Config::foundry_cache_dir() {
-            let path = cache_dir.as_path();
-            let _ = fs::remove_dir_all(path);
+            let _ = fs::remove_dir_all(long_path(cache_dir));
         } else {
             eyre::bail!("failed to get foundry_cache_dir");
         }
@@ -964,8 +1219,7 @@ impl Config {
     /// Clears the foundry cache for `chain`
     pub fn clean_foundry_chain_cache(chain: Chain) -> eyre::Result<()> {
         if let Some(cache_dir) = Config::foundry_chain_cache_dir(chain) {
-            let path = cache_dir.as_path();
-            let _ = fs::remove_dir_all(path);
+            let _ = fs::remove_dir_all(long_path(cache_dir));
         } else {
             eyre::bail!("failed to get foundry_chain_cache_dir");
         }
@@ -976,8 +1230,7 @@ impl Config {
     /// Clears the foundry cache for `chain` and `block`
     pub fn clean_foundry_block_cache(chain: Chain, block: u64) -> eyre::Result<()> {
         if let Some(cache_dir) = Config::foundry_block_cache_dir(chain, block) {
-            let path = cache_dir.as_path();
-            let _ = fs::remove_dir_all(path);
+            let _ = fs::remove_dir_all(long_path(cache_dir));
         } else {
             eyre::bail!("failed to get foundry_block_cache_dir");
         }
@@ -989,10 +1242,11 @@ impl Config {
     pub fn list_foundry_cache() -> eyre::Result<Cache> {
         if let Some(cache_dir) = Config::foundry_cache_dir() {
             let mut cache = Cache { chains: vec![] };
+            let cache_dir = long_path(cache_dir);
             if !cache_dir.exists() {
                 return Ok(cache)
             }
-            if let Ok(entries) = cache_dir.as_path().read_dir() {
+            if let Ok(entries) = cache_dir.read_dir() {
                 for entry in entries.flatten().filter(|x| x.path().is_dir()) {
                     cache.chains.push(ChainCache {
                         name: entry.file_name().to_string_lossy().into_owned(),
@@ -1021,6 +1275,7 @@ impl Config {
     //The path provided to this function should point to a cached chain folder
     fn get_cached_blocks(chain_path: &Path) -> eyre::Result<Vec<(String, u64)>> {
         let mut blocks = vec![];
+        let chain_path = long_path(chain_path);
         if !chain_path.exists() {
             return Ok(blocks)
         }
@@ -1044,8 +1299,19 @@ impl From<Config> for Figment {
         let profile = Config::selected_profile();
         let mut figment = Figment::default().merge(DappHardhatDirProvider(&c.__root.0));
 
-        // check global foundry.toml file
+        // check global foundry.toml file, it is merged beneath the project's foundry.toml so that
+        // it only provides machine-level defaults
         if let Some(global_toml) = Config::foundry_dir_toml().filter(|p| p.exists()) {
+            if profile != Config::DEFAULT_PROFILE {
+                // the selected profile inherits from the `default` profile of the global file
+                figment = figment.merge(InheritProvider {
+                    provider: BackwardsCompatTomlProvider(ForcedSnakeCaseData(
+                        Toml::file(&global_toml).nested(),
+                    )),
+                    parent: Config::DEFAULT_PROFILE,
+                    profile: profile.clone(),
+                });
+            }
             figment = figment.merge(BackwardsCompatTomlProvider(ForcedSnakeCaseData(
                 Toml::file(global_toml).nested(),
             )))
@@ -1223,7 +1489,11 @@ impl Default for Config {
             force: false,
             evm_version: Default::default(),
             gas_reports: vec!["*".to_string()],
+            gas_reports_ignore: vec![],
+            coverage_ignore: vec![],
+            analyzers: vec!["slither".to_string()],
             solc: None,
+            solc_backend: SolcBackend::Native,
             auto_detect_solc: true,
             offline: false,
             optimizer: true,
@@ -1234,6 +1504,8 @@ impl Default for Config {
             extra_output_files: Default::default(),
             names: false,
             sizes: false,
+            deny_oversized_contracts: false,
+            notes: false,
             test_pattern: None,
             test_pattern_inverse: None,
             contract_pattern: None,
@@ -1243,10 +1515,20 @@ impl Default for Config {
             fuzz_runs: 256,
             fuzz_max_local_rejects: 1024,
             fuzz_max_global_rejects: 65536,
+            fuzz_seed: None,
+            fuzz_dictionary_weight: 40,
+            test_timeout: None,
+            slow_test_threshold: None,
+            invariant: Default::default(),
+            coverage: Default::default(),
+            fmt: Default::default(),
+            contract_limits: Default::default(),
+            constructor_args: Default::default(),
             ffi: false,
             sender: "00a329c0648769A73afAc7F9381E08FB43dBEA72".parse().unwrap(),
             tx_origin: "00a329c0648769A73afAc7F9381E08FB43dBEA72".parse().unwrap(),
             initial_balance: U256::from(0xffffffffffffffffffffffffu128),
+            labeled_account_balance: U256::zero(),
             block_number: 1,
             fork_block_number: None,
             chain_id: None,
@@ -1260,6 +1542,7 @@ impl Default for Config {
             memory_limit: 2u64.pow(25),
             eth_rpc_url: None,
             etherscan_api_key: None,
+            etherscan: Default::default(),
             verbosity: 0,
             remappings: vec![],
             libraries: vec![],
@@ -1267,10 +1550,15 @@ impl Default for Config {
             __non_exhaustive: (),
             via_ir: false,
             rpc_storage_caching: Default::default(),
+            rpc_endpoints: Default::default(),
+            fs_permissions: Default::default(),
             no_storage_caching: false,
             bytecode_hash: BytecodeHash::Ipfs,
+            use_literal_content: false,
             revert_strings: None,
             sparse_mode: false,
+            cache_test_results: false,
+            build_info: false,
         }
     }
 }
@@ -1485,6 +1773,15 @@ impl<P: Provider> Provider for ForcedSnakeCaseData<P> {
     }
 }
 
+/// Splits a dotted config key like `rpc_endpoints.mainnet` into its parent tables and the key
+fn split_config_key(key: &str) -> eyre::Result<(Vec<&str>, &str)> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    match parts.pop() {
+        Some(last) if parts.iter().chain([&last]).all(|part| !part.is_empty()) => Ok((parts, last)),
+        _ => eyre::bail!("Invalid config key `{key}`"),
+    }
+}
+
 /// A Provider that extracts the data for a `parent` profile and emits that as `profile`.
 struct InheritProvider<P> {
     provider: P,
@@ -1718,7 +2015,7 @@ impl<'a> RemappingsProvider<'a> {
         new_remappings.extend(
             lib_remappings
                 .into_iter()
-                .map(|(name, path)| Remapping { name, path: path.to_string_lossy().into() }),
+                .map(|(name, path)| Remapping { name, path: slash_path(path) }),
         );
 
         // remove duplicates at this point
@@ -1745,7 +2042,7 @@ impl<'a> RemappingsProvider<'a> {
                     if let Some(name) = lib.file_name().and_then(|s| s.to_str()) {
                         let mut r = Remapping {
                             name: format!("{}/", name),
-                            path: format!("{}", lib.join(&config.src).display()),
+                            path: slash_path(lib.join(&config.src)),
                         };
                         if !r.path.ends_with('/') {
                             r.path.push('/')
@@ -2153,6 +2450,54 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_solc_backend() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                solc_version = "0.8.12"
+                solc_backend = "docker"
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(config.solc_backend, SolcBackend::Docker);
+            let solc = config.ensure_solc().unwrap().unwrap();
+            assert_eq!(solc.solc, PathBuf::from("docker"));
+            let root = config.__root.0.display().to_string();
+            if cfg!(unix) {
+                assert_eq!(
+                    solc.args,
+                    vec![
+                        "run".to_string(),
+                        "--rm".to_string(),
+                        "-i".to_string(),
+                        "-v".to_string(),
+                        format!("{root}:{root}:ro"),
+                        "ethereum/solc:0.8.12".to_string(),
+                    ]
+                );
+            }
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_offline_env() {
+        figment::Jail::expect_with(|jail| {
+            assert!(!Config::load().offline);
+            jail.set_env("FOUNDRY_OFFLINE", "1");
+            assert!(Config::load().offline);
+            jail.set_env("FOUNDRY_OFFLINE", "false");
+            assert!(!Config::load().offline);
+
+            Ok(())
+        });
+    }
+
     #[test]
     #[should_panic]
     fn test_toml_file_parse_failure() {
@@ -2260,33 +2605,42 @@ mod tests {
                 "foundry.toml",
                 r#"
                 [default]
+                analyzers = ['slither']
                 auto_detect_solc = true
                 block_base_fee_per_gas = 0
                 block_coinbase = '0x0000000000000000000000000000000000000000'
                 block_difficulty = 0
                 block_number = 1
                 block_timestamp = 1
+                build_info = false
                 bytecode_hash = 'ipfs'
                 cache = true
                 cache_path = 'cache'
+                cache_test_results = false
+                coverage_ignore = []
+                deny_oversized_contracts = false
                 evm_version = 'london'
                 extra_output = []
                 extra_output_files = []
                 ffi = false
                 force = false
+                fuzz_dictionary_weight = 40
                 fuzz_max_global_rejects = 65536
                 fuzz_max_local_rejects = 1024
                 fuzz_runs = 256
                 gas_limit = 9223372036854775807
                 gas_price = 0
                 gas_reports = ['*']
+                gas_reports_ignore = []
                 ignored_error_codes = [1878]
                 initial_balance = '0xffffffffffffffffffffffff'
+                labeled_account_balance = '0x0'
                 libraries = []
                 libs = ['lib']
                 memory_limit = 33554432
                 names = false
                 no_storage_caching = false
+                notes = false
                 offline = false
                 optimizer = true
                 optimizer_runs = 200
@@ -2298,6 +2652,7 @@ mod tests {
                 src = 'src'
                 test = 'test'
                 tx_origin = '0x00a329c0648769a73afac7f9381e08fb43dbea72'
+                use_literal_content = false
                 verbosity = 0
                 via_ir = false
                 
@@ -2305,6 +2660,23 @@ mod tests {
                 chains = 'all'
                 endpoints = 'all'
 
+                [default.invariant]
+                runs = 256
+                depth = 15
+                fail_on_revert = false
+
+                [default.rpc_endpoints]
+                optimism = "https://example.com/"
+                mainnet = "${RPC_MAINNET}"
+
+                [default.constructor_args]
+                TokenTest = [["6"], ["18"]]
+                VaultTest = "test/fixtures/vaults.json"
+
+                [default.etherscan]
+                mainnet = { key = "${ETHERSCAN_API_KEY}" }
+                arbiscan = { key = "${ARBISCAN_API_KEY}", chain = 42161 }
+
             "#,
             )?;
 
@@ -2318,6 +2690,104 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_resolve_etherscan_api_key() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                etherscan_api_key = "fallback"
+
+                [default.etherscan]
+                optimism = { key = "${_CONFIG_OPTIMISM_KEY}" }
+                arbiscan = { key = "arbiscan", chain = 42161 }
+                polygon = { key = "${_CONFIG_MISSING_KEY}" }
+            "#,
+            )?;
+            jail.set_env("_CONFIG_OPTIMISM_KEY", "optimism");
+
+            let config = Config::load();
+            let key = |chain: u64| config.get_etherscan_api_key(Some(Chain::Id(chain)));
+            assert_eq!(key(10), Ok(Some("optimism".to_string())));
+            assert_eq!(key(42161), Ok(Some("arbiscan".to_string())));
+            assert_eq!(key(1), Ok(Some("fallback".to_string())));
+            assert!(key(137).is_err());
+            assert_eq!(config.get_etherscan_api_key(None), Ok(Some("fallback".to_string())));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_resolve_rpc_endpoints() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                [default.rpc_endpoints]
+                optimism = "https://example.com/"
+                mainnet = "https://mainnet.example.com/${_CONFIG_RPC_KEY}"
+            "#,
+            )?;
+            jail.set_env("_CONFIG_RPC_KEY", "123455");
+
+            let config = Config::load();
+            let endpoints = config.rpc_endpoints.resolved();
+            assert_eq!(endpoints.get("optimism"), Ok("https://example.com/".to_string()));
+            assert_eq!(endpoints.get("mainnet"), Ok("https://mainnet.example.com/123455".to_string()));
+            assert!(endpoints.get("arbitrum").is_err());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_global_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.set_env("HOME", jail.directory().join("home").display());
+            Config::set_global("etherscan_api_key", "global").unwrap();
+            Config::set_global("rpc_endpoints.mainnet", "https://example.com/").unwrap();
+            Config::set_global("optimizer_runs", "1000").unwrap();
+            Config::set_global("verbosity", "3").unwrap();
+            Config::unset_global("verbosity").unwrap();
+
+            let config = Config::load();
+            assert_eq!(config.etherscan_api_key, Some("global".to_string()));
+            assert_eq!(config.optimizer_runs, 1000);
+            assert_eq!(config.verbosity, 0);
+            assert_eq!(
+                config.rpc_endpoints.resolved().get("mainnet"),
+                Ok("https://example.com/".to_string())
+            );
+
+            // the project's config takes precedence over the global config
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                optimizer_runs = 200
+
+                [other]
+                src = "other-src"
+            "#,
+            )?;
+            let config = Config::load();
+            assert_eq!(config.etherscan_api_key, Some("global".to_string()));
+            assert_eq!(config.optimizer_runs, 200);
+
+            // other profiles inherit the global config
+            jail.set_env("FOUNDRY_PROFILE", "other");
+            let config = Config::load();
+            assert_eq!(config.etherscan_api_key, Some("global".to_string()));
+            assert_eq!(config.optimizer_runs, 200);
+            assert_eq!(config.src, PathBuf::from("other-src"));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_solc_req() {
         figment::Jail::expect_with(|jail| {
@@ -2700,6 +3170,32 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_validate_solc_settings() {
+        let config = Config::default();
+        assert!(config.validate_solc_settings().is_ok());
+
+        let config = Config {
+            optimizer_details: Some(OptimizerDetails {
+                yul_details: Some(YulDetails {
+                    stack_allocation: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(config.validate_solc_settings().is_err());
+        let config = Config { via_ir: true, ..config };
+        assert!(config.validate_solc_settings().is_ok());
+        let config = Config { solc: Some("0.8.12".into()), ..config };
+        assert!(config.validate_solc_settings().is_ok());
+        let config = Config { solc: Some("0.7.2".into()), ..config };
+        assert!(config.validate_solc_settings().is_ok());
+        let config = Config { solc: Some("0.7.1".into()), ..config };
+        assert!(config.validate_solc_settings().is_err());
+    }
+
     #[test]
     fn test_model_checker_settings_basic() {
         figment::Jail::expect_with(|jail| {
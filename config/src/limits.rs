@@ -0,0 +1,36 @@
+//! Configuration of the limits of the compiled contracts
+
+use serde::{Deserialize, Serialize};
+
+/// Limits of the compiled contracts, configured in the `[contract_limits]` table
+///
+/// `forge build` fails if a contract exceeds a limit, test contracts are excluded, for example
+///
+/// ```toml
+/// [contract_limits]
+/// max_size = 20000
+/// max_deployment_gas = 3000000
+/// max_external_functions = 40
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContractLimits {
+    /// The maximum size of the deployed code in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<usize>,
+    /// The maximum estimated gas of the deployment transaction, without the constructor execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_deployment_gas: Option<u64>,
+    /// The maximum number of public and external functions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_external_functions: Option<usize>,
+}
+
+impl ContractLimits {
+    /// Returns true if no limit is set
+    pub fn is_empty(&self) -> bool {
+        self.max_size.is_none() &&
+            self.max_deployment_gas.is_none() &&
+            self.max_external_functions.is_none()
+    }
+}
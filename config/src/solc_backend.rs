@@ -0,0 +1,165 @@
+//! Support for running solc without a native build, in docker or as solc-js
+
+use crate::Config;
+use ethers_solc::{error::SolcError, Solc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// How solc is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SolcBackend {
+    /// A native solc binary, installed via `svm` or configured as local path
+    Native,
+    /// The `ethereum/solc` docker image of the configured version
+    Docker,
+    /// solc-js, the wasm build of the configured version, run with node
+    ///
+    /// It is installed with npm to `~/.foundry/solc-js/<version>`
+    SolcJs,
+    /// A native binary, or if no native build of the version can be installed, solc-js if node
+    /// is available and the docker image otherwise
+    Auto,
+}
+
+impl SolcBackend {
+    /// Returns a [`Solc`] that runs the `version` with this backend, or `None` for native solc
+    ///
+    /// `mounts` are the dirs solc is given as `--base-path` and `--allow-paths`
+    pub(crate) fn solc(
+        &self,
+        version: &Version,
+        mounts: &[PathBuf],
+        offline: bool,
+    ) -> Result<Option<Solc>, SolcError> {
+        match self {
+            SolcBackend::Native => Ok(None),
+            SolcBackend::Docker => Ok(Some(docker_solc(version, mounts))),
+            SolcBackend::SolcJs => solcjs(version, offline).map(Some),
+            SolcBackend::Auto if has_node() => solcjs(version, offline).map(Some),
+            SolcBackend::Auto => Ok(Some(docker_solc(version, mounts))),
+        }
+    }
+}
+
+impl Default for SolcBackend {
+    fn default() -> Self {
+        SolcBackend::Native
+    }
+}
+
+/// Returns a [`Solc`] that runs the `ethereum/solc` docker image of the version
+///
+/// The sources are passed inline in the standard json input, but solc checks that its
+/// `--base-path` exists, so the `mounts` are mounted read-only at the same path. Windows paths
+/// can't be mounted at the same path in a linux container and are not mounted.
+pub(crate) fn docker_solc(version: &Version, mounts: &[PathBuf]) -> Solc {
+    let mut args = vec!["run".to_string(), "--rm".to_string(), "-i".to_string()];
+    if cfg!(unix) {
+        for dir in mounts.iter().filter(|dir| dir.is_absolute()) {
+            args.push("-v".to_string());
+            args.push(format!("{0}:{0}:ro", dir.display()));
+        }
+    }
+    args.push(format!("ethereum/solc:{version}"));
+    Solc::new("docker").args(args)
+}
+
+/// Runs solc-js like a native solc with `--standard-json`
+///
+/// The sources are part of the json input, so the path arguments are ignored.
+const SOLCJS_SHIM: &str = r#"const solc = require(process.argv[2]);
+if (process.argv.slice(3).includes("--version")) {
+  console.log("solc, the solidity compiler commandline interface");
+  console.log("Version: " + solc.version());
+} else {
+  const chunks = [];
+  process.stdin.on("data", (chunk) => chunks.push(chunk));
+  process.stdin.on("end", () => {
+    process.stdout.write(solc.compile(Buffer.concat(chunks).toString()));
+  });
+}
+"#;
+
+/// Returns a [`Solc`] that runs solc-js of the version with node, installing it if it's missing
+pub(crate) fn solcjs(version: &Version, offline: bool) -> Result<Solc, SolcError> {
+    let dir = Config::foundry_dir()
+        .ok_or_else(|| SolcError::msg("failed to get foundry_dir"))?
+        .join("solc-js")
+        .join(version.to_string());
+    let module = dir.join("node_modules").join("solc");
+    if !module.join("package.json").exists() {
+        if offline {
+            return Err(SolcError::msg(format!(
+                "solc-js {version} is not installed and can't be installed offline"
+            )))
+        }
+        install_solcjs(version, &dir)?;
+    }
+
+    let shim = dir.join("solc.js");
+    if !shim.exists() {
+        fs::write(&shim, SOLCJS_SHIM).map_err(|err| {
+            SolcError::msg(format!("failed to write {}: {err}", shim.display()))
+        })?;
+    }
+    Ok(Solc::new("node").args([shim.display().to_string(), module.display().to_string()]))
+}
+
+/// Installs the solc-js package of the version into `dir`
+fn install_solcjs(version: &Version, dir: &Path) -> Result<(), SolcError> {
+    fs::create_dir_all(dir)
+        .map_err(|err| SolcError::msg(format!("failed to create {}: {err}", dir.display())))?;
+    let npm = if cfg!(windows) { "npm.cmd" } else { "npm" };
+    let output = Command::new(npm)
+        .args(["install", "--silent", "--no-audit", "--no-fund", "--prefix"])
+        .arg(dir)
+        .arg(format!("solc@{version}"))
+        .output()
+        .map_err(|err| {
+            SolcError::msg(format!("failed to run npm to install solc-js {version}: {err}"))
+        })?;
+    if !output.status.success() {
+        return Err(SolcError::msg(format!(
+            "failed to install solc-js {version}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+    Ok(())
+}
+
+/// Returns true if node can be run
+fn has_node() -> bool {
+    Command::new("node").arg("--version").output().map_or(false, |out| out.status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_mount_paths_for_docker() {
+        let version = Version::new(0, 8, 10);
+        let solc = docker_solc(&version, &[PathBuf::from("/project"), PathBuf::from("lib")]);
+        assert_eq!(solc.solc, PathBuf::from("docker"));
+        if cfg!(unix) {
+            assert_eq!(
+                solc.args,
+                vec!["run", "--rm", "-i", "-v", "/project:/project:ro", "ethereum/solc:0.8.10"]
+            );
+        }
+    }
+
+    #[test]
+    fn can_deserialize_backends() {
+        let backend: SolcBackend = serde_json::from_str(r#""solc-js""#).unwrap();
+        assert_eq!(backend, SolcBackend::SolcJs);
+        let backend: SolcBackend = serde_json::from_str(r#""docker""#).unwrap();
+        assert_eq!(backend, SolcBackend::Docker);
+    }
+}
@@ -145,3 +145,79 @@ pub fn foundry_toml_dirs(root: impl AsRef<Path>) -> Vec<PathBuf> {
         .filter(|p| p.join(Config::FILE_NAME).exists())
         .collect()
 }
+
+/// Returns the path as a string with `/` separators
+///
+/// solc expects `/` in remappings and source names, and globs are matched against paths with
+/// `/`, but paths on Windows are joined with `\`. On other platforms `\` is a valid character of
+/// a file name and is kept.
+pub fn slash_path(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref().to_string_lossy();
+    if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.into_owned()
+    }
+}
+
+/// Returns the path in its verbatim `\\?\` form on Windows
+///
+/// Verbatim paths are not limited to `MAX_PATH` (260) characters, which deeply nested cache and
+/// project dirs can exceed. UNC paths `\\server\share` become `\\?\UNC\server\share`. Relative
+/// and already verbatim paths, and all paths on other platforms, are returned unchanged.
+///
+/// Verbatim paths are not normalized, so `path` should not contain `.` or `..` components.
+pub fn long_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if cfg!(windows) && path.is_absolute() {
+        PathBuf::from(verbatim(&path.to_string_lossy()))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Prefixes an absolute Windows path with `\\?\`, verbatim paths must only use `\` separators
+fn verbatim(path: &str) -> String {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return path.to_string()
+    }
+    let path = path.replace('/', "\\");
+    match path.strip_prefix(r"\\") {
+        Some(unc) => format!(r"\\?\UNC\{unc}"),
+        None => format!(r"\\?\{path}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_slash_paths() {
+        assert_eq!(slash_path("src/Vault.sol"), "src/Vault.sol");
+        let native = Path::new("lib").join("solmate").join("src");
+        assert_eq!(slash_path(native), "lib/solmate/src");
+        if cfg!(windows) {
+            assert_eq!(slash_path(r"C:\src\Vault.sol"), "C:/src/Vault.sol");
+        } else {
+            assert_eq!(slash_path(r"src\Vault.sol"), r"src\Vault.sol");
+        }
+    }
+
+    #[test]
+    fn can_convert_verbatim_paths() {
+        assert_eq!(
+            verbatim(r"C:\Users\foundry\.foundry\cache"),
+            r"\\?\C:\Users\foundry\.foundry\cache"
+        );
+        assert_eq!(verbatim("C:/Users/foundry"), r"\\?\C:\Users\foundry");
+        assert_eq!(verbatim(r"\\server\share\cache"), r"\\?\UNC\server\share\cache");
+        assert_eq!(verbatim(r"\\?\C:\cache"), r"\\?\C:\cache");
+        assert_eq!(verbatim(r"\\?\UNC\server\share"), r"\\?\UNC\server\share");
+
+        assert_eq!(long_path("cache"), PathBuf::from("cache"));
+        if !cfg!(windows) {
+            assert_eq!(long_path("/home/foundry/cache"), PathBuf::from("/home/foundry/cache"));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use bytes::Bytes;
+use ethers::types::H256;
+use std::collections::BTreeMap;
+
+/// Hit maps of all executed contracts, `keccak256(code) -> HitMap`
+pub type HitMaps = BTreeMap<H256, HitMap>;
+
+/// Merges the hit maps of `other` into `coverage`
+pub fn merge_coverage(coverage: &mut Option<HitMaps>, other: Option<HitMaps>) {
+    let other = match other {
+        Some(other) => other,
+        None => return,
+    };
+    let maps = coverage.get_or_insert_with(HitMaps::new);
+    for (hash, map) in other {
+        match maps.get_mut(&hash) {
+            Some(existing) => existing.merge(map),
+            None => {
+                maps.insert(hash, map);
+            }
+        }
+    }
+}
+
+/// Records how often each instruction of a contract's bytecode was executed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HitMap {
+    /// The executed code
+    pub bytecode: Bytes,
+    /// `program counter -> number of times the instruction was executed`
+    pub hits: BTreeMap<usize, u64>,
+}
+
+impl HitMap {
+    pub fn new(bytecode: Bytes) -> Self {
+        Self { bytecode, hits: BTreeMap::new() }
+    }
+
+    /// Records a single execution of the instruction at `pc`
+    pub fn hit(&mut self, pc: usize) {
+        *self.hits.entry(pc).or_default() += 1;
+    }
+
+    /// Adds the hits of another hit map of the same bytecode
+    pub fn merge(&mut self, other: HitMap) {
+        for (pc, hits) in other.hits {
+            *self.hits.entry(pc).or_default() += hits;
+        }
+    }
+}
@@ -14,43 +14,111 @@ pub static CHEATCODE_ADDRESS: Address = H160([
 ethers::contract::abigen!(
     HEVM,
     r#"[
+            struct BlockContext { uint256 number; uint256 timestamp; uint256 basefee; uint256 prevrandao; address coinbase; }
+            struct Log { bytes32[] topics; bytes data; address emitter; }
+            struct SignedDelegation { uint8 v; bytes32 r; bytes32 s; uint64 nonce; address implementation; }
             roll(uint256)
             warp(uint256)
+            rollBy(int256)
+            warpBy(int256)
             fee(uint256)
             coinbase(address)
+            setBlock(BlockContext)
             store(address,bytes32,bytes32)
             load(address,bytes32)(bytes32)
             ffi(string[])(bytes)
+            differential(string[],bytes)(bytes)
             addr(uint256)(address)
             sign(uint256,bytes32)(uint8,bytes32,bytes32)
+            signCompact(uint256,bytes32)(bytes32,bytes32)
+            signAggregate(uint256[],bytes32)(bytes)
+            signDelegation(address,uint256)(uint8,bytes32,bytes32,uint64,address)
+            attachDelegation(SignedDelegation)
+            deriveKey(string,uint32)(uint256)
+            deriveKey(string,string,uint32)(uint256)
             prank(address)
             startPrank(address)
             prank(address,address)
             startPrank(address,address)
             stopPrank()
+            readCallers()(uint8,address,address)
+            isContext(uint8)(bool)
             deal(address,uint256)
             etch(address,bytes)
             expectRevert()
             expectRevert(bytes)
             expectRevert(bytes4)
+            expectPartialRevert(bytes4)
             record()
             accesses(address)(bytes32[],bytes32[])
+            recordLogs()
+            getRecordedLogs()(Log[])
             expectEmit(bool,bool,bool,bool)
             expectEmit(bool,bool,bool,bool,address)
+            expectEmit(bool,bool,bool,bool,uint64)
+            expectEmit(bool,bool,bool,bool,address,uint64)
+            expectLog(bytes32[],bytes,uint64)
             mockCall(address,bytes,bytes)
             mockCall(address,uint256,bytes,bytes)
             clearMockedCalls()
             expectCall(address,bytes)
             expectCall(address,uint256,bytes)
+            expectCreate(bytes)
+            expectCreate2(address)
+            expectGasUsage(uint64,uint64)
             getCode(string)
             label(address,string)
+            makeAddr(string)(address)
+            makeAccount(string)(address,uint256)
             assume(bool)
             setNonce(address,uint64)
             getNonce(address)
             chainId(uint256)
+            rpcUrl(string)(string)
+            createFork(string)(uint256)
+            createFork(string,uint256)(uint256)
+            selectFork(uint256)
+            rollFork(uint256)
+            rollFork(uint256,uint256)
+            makePersistent(address)
+            isPersistent(address)(bool)
+            snapshot()(uint256)
+            revertTo(uint256)(bool)
+            readFile(string)(string)
+            writeFile(string,string)
+            loadAllocs(string)
+            setEnv(string,string)
+            envBool(string)(bool)
+            envUint(string)(uint256)
+            envInt(string)(int256)
+            envAddress(string)(address)
+            envBytes32(string)(bytes32)
+            envString(string)(string)
+            envBytes(string)(bytes)
+            envBool(string,bool)(bool)
+            envUint(string,uint256)(uint256)
+            envInt(string,int256)(int256)
+            envAddress(string,address)(address)
+            envBytes32(string,bytes32)(bytes32)
+            envString(string,string)(string)
+            envBytes(string,bytes)(bytes)
+            toString(address)(string)
+            toString(bytes)(string)
+            toString(bytes32)(string)
+            toString(bool)(string)
+            toString(uint256)(string)
+            toString(int256)(string)
+            parseBytes(string)(bytes)
+            parseAddress(string)(address)
+            parseUint(string)(uint256)
+            parseInt(string)(int256)
+            parseBytes32(string)(bytes32)
+            parseBool(string)(bool)
+            parseJson(string,string)(bytes)
+            parseJson(string)(bytes)
     ]"#,
 );
-pub use hevm_mod::{HEVMCalls, HEVM_ABI};
+pub use hevm_mod::{HEVMCalls, SignedDelegation, HEVM_ABI};
 
 /// The Hardhat console address (0x000000000000000000636F6e736F6c652e6c6f67).
 ///
@@ -60,6 +128,23 @@ pub static HARDHAT_CONSOLE_ADDRESS: Address = H160([
     0x2e, 0x6c, 0x6f, 0x67,
 ]);
 
+/// The address of the deterministic CREATE2 deployer, which exists on most chains
+///
+/// See: https://github.com/Arachnid/deterministic-deployment-proxy
+pub static DEFAULT_CREATE2_DEPLOYER: Address = H160([
+    0x4e, 0x59, 0xb4, 0x48, 0x47, 0xb3, 0x79, 0x57, 0x85, 0x88, 0x92, 0x0c, 0xa7, 0x8f, 0xbf, 0x26,
+    0xc0, 0xb4, 0x95, 0x6c,
+]);
+
+/// The runtime code of the [`DEFAULT_CREATE2_DEPLOYER`]
+pub static DEFAULT_CREATE2_DEPLOYER_CODE: &[u8] = &[
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xe0, 0x36, 0x01, 0x60, 0x00, 0x81, 0x60, 0x20, 0x82, 0x37, 0x80, 0x35, 0x82, 0x82, 0x34, 0xf5,
+    0x80, 0x15, 0x15, 0x60, 0x39, 0x57, 0x81, 0x82, 0xfd, 0x5b, 0x80, 0x82, 0x52, 0x50, 0x50, 0x50,
+    0x60, 0x14, 0x60, 0x0c, 0xf3,
+];
+
 // Bindings for DS-style event logs
 ethers::contract::abigen!(
     Console,
@@ -1,4 +1,5 @@
 use ethers::prelude::Provider;
+use foundry_config::{FsPermissions, ResolvedRpcEndpoints};
 use revm::{
     db::{DatabaseRef, EmptyDB},
     Env, SpecId,
@@ -6,8 +7,8 @@ use revm::{
 use std::{path::PathBuf, sync::Arc};
 
 use super::{
-    fork::SharedBackend,
-    inspector::{Cheatcodes, InspectorStackConfig},
+    fork::{MultiFork, SharedBackend},
+    inspector::{Cheatcodes, ExecutionContext, InspectorStackConfig},
     Executor,
 };
 
@@ -40,6 +41,8 @@ pub struct Fork {
     pub pin_block: Option<u64>,
     /// chain id retrieved from the endpoint
     pub chain_id: u64,
+    /// Whether the state is only read from the cache at `cache_path`, without network access
+    pub offline: bool,
 }
 
 impl Fork {
@@ -51,7 +54,7 @@ impl Fork {
     /// endpoint via channels and is intended to be cloned when multiple [revm::Database] are
     /// required. See also [crate::executor::fork::SharedBackend]
     pub async fn spawn_backend(self, env: &Env) -> SharedBackend {
-        let Fork { cache_path, url, pin_block, chain_id } = self;
+        let Fork { cache_path, url, pin_block, chain_id, offline } = self;
 
         let provider =
             Arc::new(Provider::try_from(url.clone()).expect("Failed to establish provider"));
@@ -66,6 +69,10 @@ impl Fork {
 
         let db = BlockchainDb::new(meta, cache_path);
 
+        if offline {
+            return SharedBackend::spawn_offline_backend(provider, db, pin_block.map(Into::into))
+                .await
+        }
         SharedBackend::spawn_backend(provider, db, pin_block.map(Into::into)).await
     }
 }
@@ -152,6 +159,68 @@ impl ExecutorBuilder {
         self
     }
 
+    /// Enables coverage collection
+    #[must_use]
+    pub fn with_coverage(mut self) -> Self {
+        self.inspector_config.coverage = true;
+        self
+    }
+
+    /// Sets the named RPC endpoints the `rpcUrl` cheatcode resolves
+    #[must_use]
+    pub fn with_rpc_endpoints(mut self, rpc_endpoints: ResolvedRpcEndpoints) -> Self {
+        self.inspector_config.rpc_endpoints = rpc_endpoints;
+        self
+    }
+
+    /// Sets the paths the file system cheatcodes may access
+    ///
+    /// Relative paths, of the permissions and those passed to the cheatcodes, are relative to
+    /// `root`.
+    #[must_use]
+    pub fn with_fs_permissions(
+        mut self,
+        root: impl Into<PathBuf>,
+        fs_permissions: &FsPermissions,
+    ) -> Self {
+        let root = root.into();
+        self.inspector_config.fs_permissions = fs_permissions.joined(&root);
+        self.inspector_config.root = root;
+        self
+    }
+
+    /// Enables forks to be created with the `createFork` cheatcode
+    ///
+    /// Executors that share the [MultiFork] share the backends of forks of the same block.
+    #[must_use]
+    pub fn with_forks(mut self, multi_fork: MultiFork) -> Self {
+        self.inspector_config.multi_fork = Some(multi_fork);
+        self
+    }
+
+    /// Disables the cheatcodes that run commands, or access the file system, env vars or the
+    /// network, if `sandbox` is true
+    #[must_use]
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.inspector_config.sandbox = sandbox;
+        self
+    }
+
+    /// Sets the command the EVM is executed by, which the `isContext` cheatcode checks
+    #[must_use]
+    pub fn with_execution_context(mut self, execution_context: ExecutionContext) -> Self {
+        self.inspector_config.execution_context = execution_context;
+        self
+    }
+
+    /// Sets the balance the addresses created with the `makeAddr` and `makeAccount` cheatcodes
+    /// are funded with
+    #[must_use]
+    pub fn with_labeled_account_balance(mut self, balance: U256) -> Self {
+        self.inspector_config.labeled_account_balance = balance;
+        self
+    }
+
     /// Sets the EVM spec to use
     #[must_use]
     pub fn with_spec(mut self, spec: SpecId) -> Self {
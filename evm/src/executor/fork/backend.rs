@@ -24,13 +24,19 @@ use std::{
         Arc,
     },
 };
-use tracing::{trace, warn};
+use foundry_config::PROFILE_TARGET;
+use tracing::{info_span, trace, warn};
 
 type AccountFuture<Err> =
     Pin<Box<dyn Future<Output = (Result<(U256, U256, Bytes), Err>, Address)> + Send>>;
 type StorageFuture<Err> = Pin<Box<dyn Future<Output = (Result<U256, Err>, Address, U256)> + Send>>;
 type BlockHashFuture<Err> = Pin<Box<dyn Future<Output = (Result<H256, Err>, u64)> + Send>>;
 
+/// The senders of the responses to the requests, the values or why they can't be provided
+type AccountInfoSender = OneshotSender<eyre::Result<AccountInfo>>;
+type StorageSender = OneshotSender<eyre::Result<U256>>;
+type BlockHashSender = OneshotSender<eyre::Result<H256>>;
+
 /// Request variants that are executed by the provider
 enum ProviderRequest<Err> {
     Account(AccountFuture<Err>),
@@ -42,11 +48,11 @@ enum ProviderRequest<Err> {
 #[derive(Debug)]
 enum BackendRequest {
     /// Fetch the account info
-    Basic(Address, OneshotSender<AccountInfo>),
+    Basic(Address, AccountInfoSender),
     /// Fetch a storage slot
-    Storage(Address, U256, OneshotSender<U256>),
+    Storage(Address, U256, StorageSender),
     /// Fetch a block hash
-    BlockHash(u64, OneshotSender<H256>),
+    BlockHash(u64, BlockHashSender),
     /// Sets the pinned block to fetch data from
     SetPinnedBlock(BlockId),
 }
@@ -63,11 +69,11 @@ pub struct BackendHandler<M: Middleware> {
     /// Requests currently in progress
     pending_requests: Vec<ProviderRequest<eyre::Error>>,
     /// Listeners that wait for a `get_account` related response
-    account_requests: HashMap<Address, Vec<OneshotSender<AccountInfo>>>,
+    account_requests: HashMap<Address, Vec<AccountInfoSender>>,
     /// Listeners that wait for a `get_storage_at` response
-    storage_requests: HashMap<(Address, U256), Vec<OneshotSender<U256>>>,
+    storage_requests: HashMap<(Address, U256), Vec<StorageSender>>,
     /// Listeners that wait for a `get_block` response
-    block_requests: HashMap<u64, Vec<OneshotSender<H256>>>,
+    block_requests: HashMap<u64, Vec<BlockHashSender>>,
     /// Incoming commands.
     incoming: Receiver<BackendRequest>,
     /// unprocessed queued requests
@@ -75,6 +81,8 @@ pub struct BackendHandler<M: Middleware> {
     /// The block to fetch data from.
     // This is an `Option` so that we can have less code churn in the functions below
     block_id: Option<BlockId>,
+    /// Whether values that are not cached are fetched from the provider
+    offline: bool,
 }
 
 impl<M> BackendHandler<M>
@@ -97,6 +105,7 @@ where
             queued_requests: Default::default(),
             incoming: rx,
             block_id,
+            offline: false,
         }
     }
 
@@ -115,7 +124,9 @@ where
                 // release the lock
                 drop(lock);
                 if let Some(basic) = basic {
-                    let _ = sender.send(basic);
+                    let _ = sender.send(Ok(basic));
+                } else if self.offline {
+                    let _ = sender.send(Err(not_cached(format!("account {addr:?}"))));
                 } else {
                     self.request_account(addr, sender);
                 }
@@ -126,7 +137,9 @@ where
                 // release the lock
                 drop(lock);
                 if let Some(hash) = hash {
-                    let _ = sender.send(hash);
+                    let _ = sender.send(Ok(hash));
+                } else if self.offline {
+                    let _ = sender.send(Err(not_cached(format!("hash of block {number}"))));
                 } else {
                     self.request_hash(number, sender);
                 }
@@ -140,7 +153,10 @@ where
 
                 // account is already stored in the cache
                 if let Some(value) = value {
-                    let _ = sender.send(value);
+                    let _ = sender.send(Ok(value));
+                } else if self.offline {
+                    let what = format!("storage slot {idx} of {addr:?}");
+                    let _ = sender.send(Err(not_cached(what)));
                 } else {
                     // account present but not storage -> fetch storage
                     self.request_account_storage(addr, idx, sender);
@@ -157,7 +173,7 @@ where
         &mut self,
         address: Address,
         idx: U256,
-        listener: OneshotSender<U256>,
+        listener: StorageSender,
     ) {
         match self.storage_requests.entry((address, idx)) {
             Entry::Occupied(mut entry) => {
@@ -197,7 +213,7 @@ where
     }
 
     /// process a request for an account
-    fn request_account(&mut self, address: Address, listener: OneshotSender<AccountInfo>) {
+    fn request_account(&mut self, address: Address, listener: AccountInfoSender) {
         match self.account_requests.entry(address) {
             Entry::Occupied(mut entry) => {
                 entry.get_mut().push(listener);
@@ -210,7 +226,7 @@ where
     }
 
     /// process a request for a block hash
-    fn request_hash(&mut self, number: u64, listener: OneshotSender<H256>) {
+    fn request_hash(&mut self, number: u64, listener: BlockHashSender) {
         match self.block_requests.entry(number) {
             Entry::Occupied(mut entry) => {
                 entry.get_mut().push(listener);
@@ -290,7 +306,7 @@ where
                             // notify all listeners
                             if let Some(listeners) = pin.account_requests.remove(&addr) {
                                 listeners.into_iter().for_each(|l| {
-                                    let _ = l.send(acc.clone());
+                                    let _ = l.send(Ok(acc.clone()));
                                 })
                             }
                             continue
@@ -308,7 +324,7 @@ where
                             // notify all listeners
                             if let Some(listeners) = pin.storage_requests.remove(&(addr, idx)) {
                                 listeners.into_iter().for_each(|l| {
-                                    let _ = l.send(value);
+                                    let _ = l.send(Ok(value));
                                 })
                             }
                             continue
@@ -326,7 +342,7 @@ where
                             // notify all listeners
                             if let Some(listeners) = pin.block_requests.remove(&number) {
                                 listeners.into_iter().for_each(|l| {
-                                    let _ = l.send(value);
+                                    let _ = l.send(Ok(value));
                                 })
                             }
                             continue
@@ -404,6 +420,25 @@ impl SharedBackend {
         shared
     }
 
+    /// _Spawns_ a `BackendHandler` like [SharedBackend::spawn_backend] that only serves the values
+    /// cached in the `db` and never uses the provider
+    ///
+    /// Requesting a value that is not cached returns an error naming the missing value.
+    pub async fn spawn_offline_backend<M>(
+        provider: M,
+        db: BlockchainDb,
+        pin_block: Option<BlockId>,
+    ) -> Self
+    where
+        M: Middleware + Unpin + 'static + Clone,
+    {
+        let (shared, mut handler) = Self::new(provider, db, pin_block);
+        handler.offline = true;
+        trace!(target: "backendhandler", "spawning offline Backendhandler");
+        tokio::spawn(handler);
+        shared
+    }
+
     /// Returns a new `SharedBackend` and the `BackendHandler`
     pub fn new<M>(
         provider: M,
@@ -431,8 +466,9 @@ impl SharedBackend {
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::Basic(address, sender);
+            let _span = info_span!(target: PROFILE_TARGET, "rpc", name = "basic").entered();
             self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))?;
-            Ok(rx.recv()?)
+            rx.recv()?
         })
     }
 
@@ -440,8 +476,9 @@ impl SharedBackend {
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::Storage(address, index, sender);
+            let _span = info_span!(target: PROFILE_TARGET, "rpc", name = "storage").entered();
             self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))?;
-            Ok(rx.recv()?)
+            rx.recv()?
         })
     }
 
@@ -449,17 +486,23 @@ impl SharedBackend {
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::BlockHash(number, sender);
+            let _span = info_span!(target: PROFILE_TARGET, "rpc", name = "block_hash").entered();
             self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))?;
-            Ok(rx.recv()?)
+            rx.recv()?
         })
     }
 }
 
+/// The error of a value that is requested offline but not cached
+fn not_cached(what: String) -> eyre::Report {
+    eyre::eyre!("The {what} is not in the fork cache, it can not be fetched offline")
+}
+
 impl DatabaseRef for SharedBackend {
     fn basic(&self, address: H160) -> AccountInfo {
         trace!( target: "sharedbackend", "request basic {:?}", address);
-        self.do_get_basic(address).unwrap_or_else(|_| {
-            warn!( target: "sharedbackend", "Failed to send/recv `basic` for {}", address);
+        self.do_get_basic(address).unwrap_or_else(|err| {
+            warn!( target: "sharedbackend", "Failed to send/recv `basic` for {}: {}", address, err);
             Default::default()
         })
     }
@@ -471,8 +514,8 @@ impl DatabaseRef for SharedBackend {
     fn storage(&self, address: H160, index: U256) -> U256 {
         trace!( target: "sharedbackend", "request storage {:?} at {:?}", address, index);
         self.do_get_storage(address, index)
-            .unwrap_or_else(|_| {
-            warn!( target: "sharedbackend", "Failed to send/recv `storage` for {} at {}", address, index);
+            .unwrap_or_else(|err| {
+            warn!( target: "sharedbackend", "Failed to send/recv `storage` for {} at {}: {}", address, index, err);
             Default::default()
         })
     }
@@ -483,8 +526,13 @@ impl DatabaseRef for SharedBackend {
         }
         let number = number.as_u64();
         trace!( target: "sharedbackend", "request block hash for number {:?}", number);
-        self.do_get_block_hash(number).unwrap_or_else(|_| {
-            warn!( target: "sharedbackend", "Failed to send/recv `block_hash` for {}", number);
+        self.do_get_block_hash(number).unwrap_or_else(|err| {
+            warn!(
+                target: "sharedbackend",
+                "Failed to send/recv `block_hash` for {}: {}",
+                number,
+                err
+            );
             Default::default()
         })
     }
@@ -552,6 +600,35 @@ mod tests {
         assert_eq!(slots.len() as u64, max_slots);
     }
 
+    #[test]
+    fn offline_backend_only_serves_cached_values() {
+        // the endpoint is never contacted
+        let provider = Provider::<Http>::try_from("http://localhost:8545").unwrap();
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: Default::default(),
+        };
+        let db = BlockchainDb::new(meta, None);
+        let cached = Address::repeat_byte(1);
+        db.accounts().write().insert(cached, AccountInfo { nonce: 1, ..Default::default() });
+
+        let runtime = RuntimeOrHandle::new();
+        let backend = runtime.block_on(SharedBackend::spawn_offline_backend(
+            Arc::new(provider),
+            db,
+            None,
+        ));
+
+        assert_eq!(backend.do_get_basic(cached).unwrap().nonce, 1);
+        let err = backend.do_get_basic(Address::repeat_byte(2)).unwrap_err();
+        assert!(err.to_string().contains("not in the fork cache"), "{err}");
+        let err = backend.do_get_storage(cached, U256::zero()).unwrap_err();
+        assert!(err.to_string().contains("storage slot 0"), "{err}");
+        // the handler keeps serving requests after a missing value
+        assert_eq!(backend.do_get_basic(cached).unwrap().nonce, 1);
+    }
+
     #[test]
     fn can_read_cache() {
         let cache_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/storage.json");
@@ -574,6 +651,7 @@ mod tests {
             url: ENDPOINT.to_string(),
             pin_block: Some(block_num),
             chain_id: 1,
+            offline: false,
         };
 
         let backend = runtime.block_on(fork.spawn_backend(&env));
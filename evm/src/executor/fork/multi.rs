@@ -0,0 +1,107 @@
+//! Support for forks created during execution, e.g. with the `createFork` cheatcode
+use crate::executor::fork::{BlockchainDb, BlockchainDbMeta, SharedBackend};
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::U256,
+};
+use eyre::WrapErr;
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use revm::Env;
+use std::{collections::HashMap, sync::Arc};
+
+/// A fork of an endpoint at a specific block
+#[derive(Debug, Clone)]
+pub struct BlockFork {
+    /// The URL of the endpoint
+    pub url: String,
+    /// The forked block
+    pub number: u64,
+    /// The timestamp of the forked block
+    pub timestamp: U256,
+    /// The backend that serves the state of the forked block
+    pub backend: SharedBackend,
+}
+
+/// Spawns the backends of forks created during execution.
+///
+/// All clones of a `MultiFork` share the spawned backends, so forks of the same endpoint and block
+/// share the RPC cache, even if they are created by different executors.
+#[derive(Debug, Clone)]
+pub struct MultiFork {
+    /// The environment the forks are created in
+    env: Env,
+    /// The runtime the [`BackendHandler`](super::BackendHandler)s are spawned on, created when
+    /// the first fork is created
+    runtime: Arc<OnceCell<tokio::runtime::Runtime>>,
+    /// All forks that were created, by endpoint and block number
+    forks: Arc<Mutex<HashMap<(String, u64), BlockFork>>>,
+    /// Whether creating forks is disallowed, as they require network access
+    offline: bool,
+}
+
+impl MultiFork {
+    pub fn new(env: Env) -> Self {
+        Self { env, runtime: Default::default(), forks: Default::default(), offline: false }
+    }
+
+    /// Disallows creating forks, as their state is not cached
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Returns the fork of `url` at `block`, or at the latest block if `block` is `None`
+    ///
+    /// The backend of the fork is only spawned if there is no fork of the same endpoint and block
+    /// yet.
+    pub fn fork(&self, url: &str, block: Option<u64>) -> eyre::Result<BlockFork> {
+        if let Some(number) = block {
+            if let Some(fork) = self.forks.lock().get(&(url.to_string(), number)) {
+                return Ok(fork.clone())
+            }
+        }
+        if self.offline {
+            eyre::bail!(
+                "Can not fork {url} in offline mode, forks created during execution are not cached"
+            )
+        }
+
+        let runtime = self.runtime.get_or_try_init(|| {
+            tokio::runtime::Builder::new_multi_thread().enable_all().build()
+        })?;
+        let provider = Arc::new(
+            Provider::<Http>::try_from(url).wrap_err_with(|| format!("Invalid fork url {url}"))?,
+        );
+        let (number, timestamp) = runtime
+            .block_on(async {
+                let number = match block {
+                    Some(number) => number,
+                    None => provider.get_block_number().await?.as_u64(),
+                };
+                let block = provider
+                    .get_block(number)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("Block {number} not found"))?;
+                Ok::<_, eyre::Error>((number, block.timestamp))
+            })
+            .wrap_err_with(|| format!("Failed to fork {url}"))?;
+
+        let mut forks = self.forks.lock();
+        if let Some(fork) = forks.get(&(url.to_string(), number)) {
+            return Ok(fork.clone())
+        }
+
+        let mut meta = BlockchainDbMeta::new(self.env.clone(), url.to_string());
+        meta.block_env.number = number.into();
+        meta.block_env.timestamp = timestamp;
+        let db = BlockchainDb::new(meta, None);
+        let backend =
+            runtime.block_on(SharedBackend::spawn_backend(provider, db, Some(number.into())));
+
+        let fork = BlockFork { url: url.to_string(), number, timestamp, backend };
+        forks.insert((url.to_string(), number), fork.clone());
+        Ok(fork)
+    }
+}
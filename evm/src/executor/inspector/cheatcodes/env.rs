@@ -1,14 +1,16 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, str::FromStr};
 
-use super::Cheatcodes;
+use super::{ext::allowed_path, Cheatcodes};
 use crate::abi::HEVMCalls;
 use bytes::Bytes;
 use ethers::{
     abi::{self, AbiEncode, Token, Tokenize},
-    types::{Address, H256, U256},
+    types::{Address, H256, I256, U256},
     utils::keccak256,
 };
+use foundry_config::FsAccessKind;
 use revm::{Database, EVMData};
+use serde::Deserialize;
 
 #[derive(Clone, Debug, Default)]
 pub struct Prank {
@@ -45,16 +47,176 @@ fn prank(
     Ok(Bytes::new())
 }
 
+/// The prank mode returned by `readCallers`, the discriminants match the `CallerMode` enum of the
+/// cheatcodes interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CallerMode {
+    None = 0,
+    Prank = 1,
+    RecurrentPrank = 2,
+}
+
+/// Returns the prank mode, and the pranked `msg.sender` and `tx.origin`
+///
+/// Both addresses are the default sender, i.e. the `tx.origin` of the test, if they are not
+/// pranked.
+fn read_callers(state: &Cheatcodes, default_sender: Address) -> Bytes {
+    let (mode, caller, origin) = match &state.prank {
+        Some(prank) => (
+            if prank.single_call { CallerMode::Prank } else { CallerMode::RecurrentPrank },
+            prank.new_caller,
+            prank.new_origin.unwrap_or(default_sender),
+        ),
+        None => (CallerMode::None, default_sender, default_sender),
+    };
+    abi::encode(&[Token::Uint((mode as u8).into()), Token::Address(caller), Token::Address(origin)])
+        .into()
+}
+
+/// An account of a genesis alloc, numbers are hex with a `0x` prefix or decimal
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GenesisAccount {
+    balance: Option<String>,
+    nonce: Option<String>,
+    code: Option<String>,
+    storage: BTreeMap<String, String>,
+}
+
+fn parse_genesis_number(value: &str) -> Result<U256, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str(hex).map_err(|err| format!("Invalid number {value}: {err}")),
+        None => U256::from_dec_str(value).map_err(|err| format!("Invalid number {value}: {err}")),
+    }
+}
+
+/// Sets the balance, nonce, code and storage of all accounts in the genesis alloc file at `path`
+///
+/// The file is either an alloc, i.e. accounts by address, or a genesis file with an `alloc`.
+fn load_allocs<DB: Database>(
+    state: &Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    path: &str,
+) -> Result<Bytes, Bytes> {
+    let path = allowed_path(state, path, FsAccessKind::Read)?;
+    let content = std::fs::read_to_string(&path).map_err(|err| err.to_string().encode())?;
+    let mut json: serde_json::Value =
+        serde_json::from_str(&content).map_err(|err| err.to_string().encode())?;
+    if let Some(alloc) = json.get_mut("alloc") {
+        json = alloc.take();
+    }
+    let allocs: BTreeMap<String, GenesisAccount> = serde_json::from_value(json)
+        .map_err(|err| format!("Invalid genesis alloc {path:?}: {err}").encode())?;
+
+    for (address, alloc) in allocs {
+        let result: Result<(), String> = (|| {
+            let address = Address::from_str(&address).map_err(|err| err.to_string())?;
+            data.subroutine.load_account(address, data.db);
+
+            if let Some(code) = alloc.code {
+                let code = hex::decode(code.strip_prefix("0x").unwrap_or(&code))
+                    .map_err(|err| err.to_string())?;
+                let hash = H256::from_slice(&keccak256(&code));
+                data.subroutine.set_code(address, code.into(), hash);
+            }
+
+            // we can safely unwrap because `load_account` inserted the account
+            let account = data.subroutine.state().get_mut(&address).unwrap();
+            if let Some(balance) = alloc.balance {
+                account.info.balance = parse_genesis_number(&balance)?;
+            }
+            if let Some(nonce) = alloc.nonce {
+                account.info.nonce = u64::try_from(parse_genesis_number(&nonce)?)
+                    .map_err(|_| format!("Nonce {nonce} is larger than 2^64 - 1"))?;
+            }
+
+            for (slot, value) in alloc.storage {
+                let (slot, value) = (parse_genesis_number(&slot)?, parse_genesis_number(&value)?);
+                data.subroutine.sstore(address, slot, value, data.db);
+            }
+            Ok(())
+        })();
+        result.map_err(|err| format!("Invalid alloc of {address}: {err}").encode())?;
+    }
+    Ok(Bytes::new())
+}
+
+/// The command the EVM is executed by, the discriminants match the `ExecutionContext` enum of the
+/// cheatcodes interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionContext {
+    /// `forge test`
+    Test = 0,
+    /// `forge coverage`, which runs the tests
+    Coverage = 1,
+    /// The simulation of a script, e.g. `forge run`
+    Script = 2,
+    /// A script whose transactions are sent to a chain
+    Broadcast = 3,
+}
+
+impl Default for ExecutionContext {
+    fn default() -> Self {
+        ExecutionContext::Test
+    }
+}
+
+impl ExecutionContext {
+    /// Returns whether `isContext(context)` is true in this context
+    ///
+    /// The tests also run under coverage, and broadcast scripts are scripts, so `Test` and `Script`
+    /// match these as well.
+    fn is(self, context: u8) -> bool {
+        match self {
+            ExecutionContext::Coverage => context == self as u8 || context == Self::Test as u8,
+            ExecutionContext::Broadcast => context == self as u8 || context == Self::Script as u8,
+            _ => context == self as u8,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct RecordAccess {
     pub reads: BTreeMap<Address, Vec<U256>>,
     pub writes: BTreeMap<Address, Vec<U256>>,
 }
 
+/// A log recorded since `recordLogs` was called
+#[derive(Clone, Debug, Default)]
+pub struct RecordedLog {
+    /// The address of the contract that emitted the log
+    pub emitter: Address,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
+
 fn start_record(state: &mut Cheatcodes) {
     state.accesses = Some(Default::default());
 }
 
+fn start_record_logs(state: &mut Cheatcodes) {
+    state.recorded_logs = Some(Default::default());
+}
+
+/// Returns the logs recorded since `recordLogs` or the last call, as `(bytes32[] topics, bytes
+/// data, address emitter)[]`, and keeps recording
+fn get_recorded_logs(state: &mut Cheatcodes) -> Bytes {
+    let logs = state.recorded_logs.as_mut().map(std::mem::take).unwrap_or_default();
+    let logs = logs
+        .into_iter()
+        .map(|log| {
+            Token::Tuple(vec![
+                Token::Array(
+                    log.topics.into_iter().map(|topic| Token::FixedBytes(topic.0.into())).collect(),
+                ),
+                Token::Bytes(log.data),
+                Token::Address(log.emitter),
+            ])
+        })
+        .collect();
+    abi::encode(&[Token::Array(logs)]).into()
+}
+
 fn accesses(state: &mut Cheatcodes, address: Address) -> Bytes {
     if let Some(storage_accesses) = &mut state.accesses {
         ethers::abi::encode(&[
@@ -67,6 +229,17 @@ fn accesses(state: &mut Cheatcodes, address: Address) -> Bytes {
     }
 }
 
+/// Adds a signed delta to a block value, failing if the result does not fit into a `uint256`
+fn add_delta(value: U256, delta: I256, what: &str) -> Result<U256, Bytes> {
+    let new_value = if delta.is_negative() {
+        value.checked_sub(delta.unsigned_abs())
+    } else {
+        value.checked_add(delta.into_raw())
+    };
+    new_value
+        .ok_or_else(|| format!("Moving {what} {value} by {delta} is out of bounds").encode().into())
+}
+
 pub fn apply<DB: Database>(
     state: &mut Cheatcodes,
     data: &mut EVMData<'_, DB>,
@@ -82,6 +255,28 @@ pub fn apply<DB: Database>(
             data.env.block.number = inner.0;
             Ok(Bytes::new())
         }
+        HEVMCalls::WarpBy(inner) => {
+            add_delta(data.env.block.timestamp, inner.0, "timestamp").map(|timestamp| {
+                data.env.block.timestamp = timestamp;
+                Bytes::new()
+            })
+        }
+        HEVMCalls::RollBy(inner) => {
+            add_delta(data.env.block.number, inner.0, "block number").map(|number| {
+                data.env.block.number = number;
+                Bytes::new()
+            })
+        }
+        HEVMCalls::SetBlock(inner) => {
+            let context = &inner.0;
+            data.env.block.number = context.number;
+            data.env.block.timestamp = context.timestamp;
+            data.env.block.basefee = context.basefee;
+            // `prevrandao` replaces `difficulty` after the merge
+            data.env.block.difficulty = context.prevrandao;
+            data.env.block.coinbase = context.coinbase;
+            Ok(Bytes::new())
+        }
         HEVMCalls::Fee(inner) => {
             data.env.block.basefee = inner.0;
             Ok(Bytes::new())
@@ -155,11 +350,18 @@ pub fn apply<DB: Database>(
             state.prank = None;
             Ok(Bytes::new())
         }
+        HEVMCalls::ReadCallers(_) => Ok(read_callers(state, data.env.tx.caller)),
+        HEVMCalls::IsContext(inner) => Ok(state.execution_context.is(inner.0).encode().into()),
         HEVMCalls::Record(_) => {
             start_record(state);
             Ok(Bytes::new())
         }
         HEVMCalls::Accesses(inner) => Ok(accesses(state, inner.0)),
+        HEVMCalls::RecordLogs(_) => {
+            start_record_logs(state);
+            Ok(Bytes::new())
+        }
+        HEVMCalls::GetRecordedLogs(_) => Ok(get_recorded_logs(state)),
         HEVMCalls::SetNonce(inner) => {
             // TODO:  this is probably not a good long-term solution since it might mess up the gas
             // calculations
@@ -175,6 +377,7 @@ pub fn apply<DB: Database>(
                 Err(format!("Nonce lower than account's current nonce. Please provide a higher nonce than {}", account.info.nonce).encode().into())
             }
         }
+        HEVMCalls::LoadAllocs(inner) => load_allocs(state, data, &inner.0),
         HEVMCalls::GetNonce(inner) => {
             // TODO:  this is probably not a good long-term solution since it might mess up the gas
             // calculations
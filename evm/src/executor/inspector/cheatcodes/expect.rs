@@ -1,13 +1,17 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt};
 
 use super::Cheatcodes;
 use crate::abi::HEVMCalls;
 use bytes::Bytes;
 use ethers::{
     abi::{AbiEncode, RawLog},
-    types::{Address, H160, U256},
+    types::{Address, H160, H256, U256},
+    utils::keccak256,
+};
+use once_cell::sync::Lazy;
+use revm::{
+    return_ok, CreateInputs, CreateScheme, Database, EVMData, Return, KECCAK_EMPTY,
 };
-use revm::{return_ok, Database, EVMData, Return};
 
 /// For some cheatcodes we may internally change the status of the call, i.e. in `expectRevert`.
 /// Solidity will see a successful call and attempt to decode the return data. Therefore, we need
@@ -21,29 +25,102 @@ static DUMMY_CALL_OUTPUT: [u8; 320] = [0u8; 320];
 static DUMMY_CREATE_ADDRESS: Address =
     H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
 
+/// The topic that matches any topic in the logs expected with `expectLog`,
+/// `keccak256("ANY_TOPIC")`
+pub static ANY_TOPIC: Lazy<H256> = Lazy::new(|| H256::from(keccak256("ANY_TOPIC")));
+
 #[derive(Clone, Debug, Default)]
 pub struct ExpectedRevert {
     /// The expected data returned by the revert
     pub reason: Bytes,
     /// The depth at which the revert is expected
     pub depth: u64,
+    /// Whether only the selector of the revert data is matched, ignoring the arguments, see
+    /// `expectPartialRevert`
+    pub partial: bool,
 }
 
-fn expect_revert(state: &mut Cheatcodes, reason: Bytes, depth: u64) -> Result<Bytes, Bytes> {
+fn expect_revert(
+    state: &mut Cheatcodes,
+    reason: Bytes,
+    depth: u64,
+    partial: bool,
+) -> Result<Bytes, Bytes> {
     if state.expected_revert.is_some() {
         Err("You must call another function prior to expecting a second revert."
             .to_string()
             .encode()
             .into())
     } else {
-        state.expected_revert = Some(ExpectedRevert { reason, depth });
+        state.expected_revert = Some(ExpectedRevert { reason, depth, partial });
+        Ok(Bytes::new())
+    }
+}
+
+fn expect_gas_usage(
+    state: &mut Cheatcodes,
+    min: u64,
+    max: u64,
+    depth: u64,
+) -> Result<Bytes, Bytes> {
+    if min > max {
+        Err(format!("Invalid gas range: minimum {min} is larger than maximum {max}")
+            .encode()
+            .into())
+    } else if state.expected_gas_usage.is_some() {
+        Err("You must call another function prior to expecting a second gas usage."
+            .to_string()
+            .encode()
+            .into())
+    } else {
+        state.expected_gas_usage = Some(ExpectedGasUsage { min, max, depth, active: false });
         Ok(Bytes::new())
     }
 }
 
+/// Describes how the revert data differs from the expected data
+///
+/// If both start with the same selector, e.g. the same custom error, the differing arguments are
+/// listed word by word.
+fn revert_data_diff(actual: &[u8], expected: &[u8]) -> String {
+    let mut diff = format!(
+        "Error != expected error: 0x{} != 0x{}",
+        hex::encode(actual),
+        hex::encode(expected)
+    );
+    if actual.len() < 4 || expected.len() < 4 {
+        return diff
+    }
+    if actual[..4] != expected[..4] {
+        diff.push_str(&format!(
+            "\n  selector: 0x{} != 0x{}",
+            hex::encode(&actual[..4]),
+            hex::encode(&expected[..4])
+        ));
+        return diff
+    }
+
+    let (actual, expected) = (&actual[4..], &expected[4..]);
+    let words = (actual.len().max(expected.len()) + 31) / 32;
+    for word in 0..words {
+        let range = word * 32..(word + 1) * 32;
+        let actual_word = actual.get(range.clone()).map(hex::encode);
+        let expected_word = expected.get(range).map(hex::encode);
+        if actual_word != expected_word {
+            diff.push_str(&format!(
+                "\n  word {}: {} != {}",
+                word,
+                actual_word.map_or_else(|| "<missing>".to_string(), |word| format!("0x{word}")),
+                expected_word.map_or_else(|| "<missing>".to_string(), |word| format!("0x{word}"))
+            ));
+        }
+    }
+    diff
+}
+
 pub fn handle_expect_revert(
     is_create: bool,
-    expected_revert: &Bytes,
+    expected_revert: &ExpectedRevert,
     status: Return,
     retdata: Bytes,
 ) -> Result<(Option<Address>, Bytes), Bytes> {
@@ -51,10 +128,32 @@ pub fn handle_expect_revert(
         return Err("Call did not revert as expected".to_string().encode().into())
     }
 
+    let ExpectedRevert { reason: expected_revert, partial, .. } = expected_revert;
     if !expected_revert.is_empty() && retdata.is_empty() {
         return Err("Call reverted as expected, but without data".to_string().encode().into())
     }
 
+    let success = if is_create {
+        (Some(DUMMY_CREATE_ADDRESS), Bytes::new())
+    } else {
+        (None, DUMMY_CALL_OUTPUT.to_vec().into())
+    };
+
+    // Only the selector is matched, the arguments of the error are ignored
+    if *partial {
+        return if retdata.starts_with(expected_revert) {
+            Ok(success)
+        } else {
+            Err(format!(
+                "Error selector != expected selector: 0x{} != 0x{}",
+                hex::encode(&retdata[..retdata.len().min(4)]),
+                hex::encode(&expected_revert)
+            )
+            .encode()
+            .into())
+        }
+    }
+
     let (err, actual_revert): (_, Bytes) = match retdata {
         _ if retdata.len() >= 4 && retdata[0..4] == [8, 195, 121, 160] => {
             // It's a revert string, so we do some conversion to perform the check
@@ -81,24 +180,11 @@ pub fn handle_expect_revert(
                 decoded_data,
             )
         }
-        _ => (
-            format!(
-                "Error != expected error: 0x{} != 0x{}",
-                hex::encode(&retdata),
-                hex::encode(&expected_revert)
-            )
-            .encode()
-            .into(),
-            retdata,
-        ),
+        _ => (revert_data_diff(&retdata, expected_revert).encode().into(), retdata),
     };
 
-    if actual_revert == expected_revert {
-        Ok(if is_create {
-            (Some(DUMMY_CREATE_ADDRESS), Bytes::new())
-        } else {
-            (None, DUMMY_CALL_OUTPUT.to_vec().into())
-        })
+    if actual_revert == *expected_revert {
+        Ok(success)
     } else {
         Err(err)
     }
@@ -120,6 +206,90 @@ pub struct ExpectedEmit {
     pub address: Option<Address>,
     /// Whether the log was actually found in the subcalls
     pub found: bool,
+    /// If present, the exact number of matching logs we expect.
+    ///
+    /// Counted expects match every log emitted in the subcalls instead of only the next one, so
+    /// they can be used to assert that an event is emitted `n` times, or not at all.
+    pub count: Option<u64>,
+    /// The number of matching logs emitted in the subcalls, if the emit is counted
+    pub matches: u64,
+}
+
+impl ExpectedEmit {
+    /// Returns `true` if `log`, emitted by `address`, matches the expected log
+    fn matches(&self, log: &RawLog, address: &Address) -> bool {
+        let expected = self.log.as_ref().expect("we should have a log to compare against here");
+
+        // Topic 0 can match, but the amount of topics can differ.
+        if expected.topics.first() != log.topics.first() ||
+            expected.topics.len() != log.topics.len()
+        {
+            return false
+        }
+
+        // Match topics
+        log.topics
+            .iter()
+            .skip(1)
+            .enumerate()
+            .filter(|(i, _)| self.checks[*i])
+            .all(|(i, topic)| topic == &expected.topics[i + 1]) &&
+            // Maybe match source address
+            self.address.map_or(true, |addr| addr == *address) &&
+            // Maybe match data
+            (!self.checks[3] || expected.data == log.data)
+    }
+
+    /// Returns the reason the emit was not satisfied once the call it was expected in terminated
+    pub fn error(&self) -> Option<String> {
+        match self.count {
+            None if !self.found => Some("Log != expected log".to_string()),
+            Some(_) if self.log.is_none() => Some("Log != expected log".to_string()),
+            Some(count) if count != self.matches => {
+                Some(format!("Expected {count} matching logs, but got {}", self.matches))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Expects `count` logs with the given topics and data, without emitting a reference log first
+///
+/// Topics equal to [`ANY_TOPIC`] and empty data match any value.
+fn expect_log<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &EVMData<'_, DB>,
+    topics: &[[u8; 32]],
+    log_data: &[u8],
+    count: u64,
+) -> Result<Bytes, Bytes> {
+    let topics: Vec<H256> = topics.iter().copied().map(H256::from).collect();
+    let error = match topics.first() {
+        None => Some("Expected log must have at least one topic"),
+        Some(topic) if *topic == *ANY_TOPIC => {
+            Some("Topic 0 of the expected log must not be ANY_TOPIC")
+        }
+        Some(_) if topics.len() > 4 => Some("Expected log can have at most 4 topics"),
+        Some(_) => None,
+    };
+    if let Some(error) = error {
+        return Err(error.to_string().encode().into())
+    }
+
+    let mut checks = [true; 4];
+    for (check, topic) in checks.iter_mut().zip(topics.iter().skip(1)) {
+        *check = *topic != *ANY_TOPIC;
+    }
+    checks[3] = !log_data.is_empty();
+
+    state.expected_emits.push(ExpectedEmit {
+        depth: data.subroutine.depth() - 1,
+        log: Some(RawLog { topics, data: log_data.to_vec() }),
+        checks,
+        count: Some(count),
+        ..Default::default()
+    });
+    Ok(Bytes::new())
 }
 
 pub fn handle_expect_emit(state: &mut Cheatcodes, log: RawLog, address: &Address) {
@@ -129,35 +299,28 @@ pub fn handle_expect_emit(state: &mut Cheatcodes, log: RawLog, address: &Address
     {
         // We have unfilled expects, so we fill the first one
         next_expect_to_fill.log = Some(log);
-    } else if let Some(next_expect) = state.expected_emits.iter_mut().find(|expect| !expect.found) {
-        // We do not have unfilled expects, so we try to match this log with the first unfound
-        // log that we expect
+        return
+    }
+
+    // Counted expects are matched against every log
+    for expect in state.expected_emits.iter_mut().filter(|expect| expect.count.is_some()) {
+        if expect.matches(&log, address) {
+            expect.matches += 1;
+        }
+    }
+
+    // We do not have unfilled expects, so we try to match this log with the first unfound log
+    // that we expect
+    if let Some(next_expect) = state
+        .expected_emits
+        .iter_mut()
+        .find(|expect| expect.count.is_none() && !expect.found)
+    {
+        // A log with a different topic 0 does not reset the expect, it is simply skipped
         let expected =
             next_expect.log.as_ref().expect("we should have a log to compare against here");
-        if expected.topics[0] == log.topics[0] {
-            // Topic 0 can match, but the amount of topics can differ.
-            if expected.topics.len() != log.topics.len() {
-                next_expect.found = false;
-            } else {
-                // Match topics
-                next_expect.found = log
-                    .topics
-                    .iter()
-                    .skip(1)
-                    .enumerate()
-                    .filter(|(i, _)| next_expect.checks[*i])
-                    .all(|(i, topic)| topic == &expected.topics[i + 1]);
-
-                // Maybe match source address
-                if let Some(addr) = next_expect.address {
-                    next_expect.found &= addr == *address;
-                }
-
-                // Maybe match data
-                if next_expect.checks[3] {
-                    next_expect.found &= expected.data == log.data;
-                }
-            }
+        if expected.topics.first() == log.topics.first() {
+            next_expect.found = next_expect.matches(&log, address);
         }
     }
 }
@@ -170,6 +333,53 @@ pub struct ExpectedCallData {
     pub value: Option<U256>,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct ExpectedGasUsage {
+    /// The minimum amount of gas the call may use
+    pub min: u64,
+    /// The maximum amount of gas the call may use
+    pub max: u64,
+    /// The depth of the contract that makes the call
+    pub depth: u64,
+    /// Whether the call the expectation applies to has started
+    pub active: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpectedCreate {
+    /// A contract created with creation code that starts with the given bytes
+    Code(Bytes),
+    /// A contract created with `CREATE2` at the given address
+    Create2(Address),
+}
+
+impl ExpectedCreate {
+    /// Returns true if the successful creation of `address` satisfies this expectation
+    pub fn matches(&self, call: &CreateInputs, address: Address) -> bool {
+        match self {
+            ExpectedCreate::Code(code) => call.init_code.starts_with(code),
+            ExpectedCreate::Create2(expected) => {
+                matches!(call.scheme, CreateScheme::Create2 { .. }) && *expected == address
+            }
+        }
+    }
+}
+
+impl fmt::Display for ExpectedCreate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpectedCreate::Code(code) => write!(
+                f,
+                "Expected a contract creation with code {}, but got none",
+                ethers::types::Bytes::from(code.clone())
+            ),
+            ExpectedCreate::Create2(address) => {
+                write!(f, "Expected a CREATE2 deployment at {:?}, but got none", address)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct MockCallDataContext {
     /// The partial calldata to match for mock
@@ -195,18 +405,46 @@ impl PartialOrd for MockCallDataContext {
     }
 }
 
+fn mock_call<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    callee: Address,
+    calldata: &[u8],
+    value: Option<U256>,
+    retdata: &[u8],
+) {
+    // Solidity checks that the callee has code before calling it, so a single `STOP` is etched to
+    // addresses without code, which allows mocking contracts that are not deployed
+    data.subroutine.load_account(callee, data.db);
+    if data.subroutine.account(callee).info.code_hash == KECCAK_EMPTY {
+        let code = Bytes::from_static(&[0u8]);
+        let hash = H256::from_slice(&keccak256(&code));
+        data.subroutine.set_code(callee, code, hash);
+    }
+
+    state.mocked_calls.entry(callee).or_default().insert(
+        MockCallDataContext { calldata: calldata.to_vec().into(), value },
+        retdata.to_vec().into(),
+    );
+}
+
 pub fn apply<DB: Database>(
     state: &mut Cheatcodes,
     data: &mut EVMData<'_, DB>,
     call: &HEVMCalls,
 ) -> Option<Result<Bytes, Bytes>> {
     Some(match call {
-        HEVMCalls::ExpectRevert0(_) => expect_revert(state, Bytes::new(), data.subroutine.depth()),
+        HEVMCalls::ExpectRevert0(_) => {
+            expect_revert(state, Bytes::new(), data.subroutine.depth(), false)
+        }
         HEVMCalls::ExpectRevert1(inner) => {
-            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth())
+            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth(), false)
         }
         HEVMCalls::ExpectRevert2(inner) => {
-            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth())
+            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth(), false)
+        }
+        HEVMCalls::ExpectPartialRevert(inner) => {
+            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth(), true)
         }
         HEVMCalls::ExpectEmit0(inner) => {
             state.expected_emits.push(ExpectedEmit {
@@ -225,6 +463,26 @@ pub fn apply<DB: Database>(
             });
             Ok(Bytes::new())
         }
+        HEVMCalls::ExpectEmit2(inner) => {
+            state.expected_emits.push(ExpectedEmit {
+                depth: data.subroutine.depth() - 1,
+                checks: [inner.0, inner.1, inner.2, inner.3],
+                count: Some(inner.4),
+                ..Default::default()
+            });
+            Ok(Bytes::new())
+        }
+        HEVMCalls::ExpectEmit3(inner) => {
+            state.expected_emits.push(ExpectedEmit {
+                depth: data.subroutine.depth() - 1,
+                checks: [inner.0, inner.1, inner.2, inner.3],
+                address: Some(inner.4),
+                count: Some(inner.5),
+                ..Default::default()
+            });
+            Ok(Bytes::new())
+        }
+        HEVMCalls::ExpectLog(inner) => expect_log(state, data, &inner.0, &inner.1, inner.2),
         HEVMCalls::ExpectCall0(inner) => {
             state
                 .expected_calls
@@ -241,18 +499,23 @@ pub fn apply<DB: Database>(
                 .push(ExpectedCallData { calldata: inner.2.to_vec().into(), value: Some(inner.1) });
             Ok(Bytes::new())
         }
+        HEVMCalls::ExpectCreate(inner) => {
+            state.expected_creates.push(ExpectedCreate::Code(inner.0.to_vec().into()));
+            Ok(Bytes::new())
+        }
+        HEVMCalls::ExpectCreate2(inner) => {
+            state.expected_creates.push(ExpectedCreate::Create2(inner.0));
+            Ok(Bytes::new())
+        }
+        HEVMCalls::ExpectGasUsage(inner) => {
+            expect_gas_usage(state, inner.0, inner.1, data.subroutine.depth())
+        }
         HEVMCalls::MockCall0(inner) => {
-            state.mocked_calls.entry(inner.0).or_default().insert(
-                MockCallDataContext { calldata: inner.1.to_vec().into(), value: None },
-                inner.2.to_vec().into(),
-            );
+            mock_call(state, data, inner.0, &inner.1, None, &inner.2);
             Ok(Bytes::new())
         }
         HEVMCalls::MockCall1(inner) => {
-            state.mocked_calls.entry(inner.0).or_default().insert(
-                MockCallDataContext { calldata: inner.2.to_vec().into(), value: Some(inner.1) },
-                inner.3.to_vec().into(),
-            );
+            mock_call(state, data, inner.0, &inner.2, Some(inner.1), &inner.3);
             Ok(Bytes::new())
         }
         HEVMCalls::ClearMockedCalls(_) => {
@@ -1,11 +1,26 @@
+use super::{parse::parse_value, Cheatcodes};
 use crate::abi::HEVMCalls;
 use bytes::Bytes;
 use ethers::{
-    abi::{self, AbiEncode, Token},
+    abi::{self, AbiEncode, ParamType, Token},
     prelude::{artifacts::CompactContractBytecode, ProjectPathsConfig},
 };
+use foundry_config::FsAccessKind;
+use parking_lot::Mutex;
 use serde::Deserialize;
-use std::{fs::File, io::Read, path::Path, process::Command};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
 fn ffi(args: &[String]) -> Result<Bytes, Bytes> {
     let output = Command::new(&args[0])
@@ -20,6 +35,109 @@ fn ffi(args: &[String]) -> Result<Bytes, Bytes> {
     Ok(abi::encode(&[Token::Bytes(decoded.to_vec())]).into())
 }
 
+/// The time a process started by `differential` has to answer a call
+const FFI_PROCESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The processes started by `differential`, by their command
+///
+/// All clones share the processes, so a process is started once and reused by all tests and fuzz
+/// runs of a test contract. Calls to the same process are serialized, calls to different processes
+/// run concurrently.
+#[derive(Debug, Clone, Default)]
+pub struct FfiProcesses(Arc<Mutex<BTreeMap<Vec<String>, Arc<Mutex<FfiProcess>>>>>);
+
+impl FfiProcesses {
+    /// Sends the input to the process running the command, which is started if it is not running
+    ///
+    /// A process that fails or does not answer within [FFI_PROCESS_TIMEOUT] is stopped, and
+    /// started again by the next call.
+    fn call(&self, args: &[String], input: &[u8]) -> Result<Vec<u8>, String> {
+        let process = match self.0.lock().entry(args.to_vec()) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(Mutex::new(FfiProcess::spawn(args)?))).clone()
+            }
+        };
+        let output = process.lock().call(input, FFI_PROCESS_TIMEOUT);
+        if output.is_err() {
+            let mut processes = self.0.lock();
+            // another call may have replaced the failed process already
+            if processes.get(args).map_or(false, |running| Arc::ptr_eq(running, &process)) {
+                processes.remove(args);
+            }
+        }
+        output
+    }
+}
+
+/// A long running process that reads hex encoded inputs from stdin and writes hex encoded outputs
+/// to stdout, one per line
+#[derive(Debug)]
+struct FfiProcess {
+    child: Child,
+    stdin: ChildStdin,
+    /// The lines written to stdout, read on a separate thread so a call can time out
+    stdout: Receiver<io::Result<String>>,
+}
+
+impl FfiProcess {
+    fn spawn(args: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Failed to start `{}`: {err}", args.join(" ")))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let (tx, rx) = mpsc::channel();
+        // the thread ends once the process is killed and its stdout is closed
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if tx.send(line).is_err() {
+                    break
+                }
+            }
+        });
+        Ok(Self { child, stdin, stdout: rx })
+    }
+
+    fn call(&mut self, input: &[u8], timeout: Duration) -> Result<Vec<u8>, String> {
+        writeln!(self.stdin, "0x{}", hex::encode(input))
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| format!("Failed to write the input: {err}"))?;
+        let line = match self.stdout.recv_timeout(timeout) {
+            Ok(line) => line.map_err(|err| format!("Failed to read the output: {err}"))?,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!("The process did not answer within {timeout:?}"))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err("The process exited without an output".to_string())
+            }
+        };
+        let output = line.trim();
+        hex::decode(output.strip_prefix("0x").unwrap_or(output))
+            .map_err(|err| format!("Invalid output `{output}`: {err}"))
+    }
+}
+
+impl Drop for FfiProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn differential(state: &Cheatcodes, args: &[String], input: &[u8]) -> Result<Bytes, Bytes> {
+    if args.is_empty() {
+        return Err("No command to run".to_string().encode().into())
+    }
+    let output = state.ffi_processes.call(args, input).map_err(|err| {
+        format!("Differential call to `{}` failed: {err}", args.join(" ")).encode()
+    })?;
+    Ok(abi::encode(&[Token::Bytes(output)]).into())
+}
+
 /// An enum which unifies the deserialization of Hardhat-style artifacts with Forge-style artifacts
 /// to get their bytecode.
 #[derive(Deserialize)]
@@ -76,16 +194,131 @@ fn get_code(path: &str) -> Result<Bytes, Bytes> {
     }
 }
 
-pub fn apply(ffi_enabled: bool, call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
+/// Resolves the path against the project root, and fails if the `access` to it is not allowed by
+/// the `fs_permissions`
+pub(super) fn allowed_path(
+    state: &Cheatcodes,
+    path: &str,
+    access: FsAccessKind,
+) -> Result<PathBuf, Bytes> {
+    let path = state.root.join(path);
+    if state.fs_permissions.is_path_allowed(&path, access) {
+        Ok(path)
+    } else {
+        let access = match access {
+            FsAccessKind::Read => "read",
+            FsAccessKind::Write => "write",
+        };
+        Err(format!(
+            "The path {path:?} is not allowed to be accessed for {access} operations, \
+             see `fs_permissions` in foundry.toml"
+        )
+        .encode()
+        .into())
+    }
+}
+
+fn read_file(state: &Cheatcodes, path: &str) -> Result<Bytes, Bytes> {
+    let path = allowed_path(state, path, FsAccessKind::Read)?;
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string().encode())?;
+    Ok(abi::encode(&[Token::String(content)]).into())
+}
+
+fn write_file(state: &Cheatcodes, path: &str, content: &str) -> Result<Bytes, Bytes> {
+    let path = allowed_path(state, path, FsAccessKind::Write)?;
+    std::fs::write(path, content).map_err(|err| err.to_string().encode())?;
+    Ok(Bytes::new())
+}
+
+/// Reads the env var `key` as a value of type `ty`, or returns the `default` if it is not set
+fn env(key: &str, ty: ParamType, default: Option<Token>) -> Result<Bytes, Bytes> {
+    let token = match std::env::var(key) {
+        Ok(value) => parse_value(&value, &ty)
+            .map_err(|err| format!("Failed to parse env var `{key}` as {ty}: {err}").encode())?,
+        Err(_) => default.ok_or_else(|| format!("Env var `{key}` is not set").encode())?,
+    };
+    Ok(abi::encode(&[token]).into())
+}
+
+pub fn apply(state: &Cheatcodes, call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
     Some(match call {
         HEVMCalls::Ffi(inner) => {
-            if !ffi_enabled {
+            if !state.ffi {
                 Err("FFI disabled: run again with `--ffi` if you want to allow tests to call external scripts.".to_string().encode().into())
             } else {
                 ffi(&inner.0)
             }
         }
+        HEVMCalls::Differential(inner) => {
+            if !state.ffi {
+                Err("FFI disabled: run again with `--ffi` if you want to allow tests to call external scripts.".to_string().encode().into())
+            } else {
+                differential(state, &inner.0, &inner.1)
+            }
+        }
         HEVMCalls::GetCode(inner) => get_code(&inner.0),
+        HEVMCalls::ReadFile(inner) => read_file(state, &inner.0),
+        HEVMCalls::WriteFile(inner) => write_file(state, &inner.0, &inner.1),
+        HEVMCalls::SetEnv(inner) => {
+            if inner.0.is_empty() || inner.0.contains(|c| c == '=' || c == '\0') {
+                Err(format!("Invalid env var name `{}`", inner.0).encode().into())
+            } else {
+                std::env::set_var(&inner.0, &inner.1);
+                Ok(Bytes::new())
+            }
+        }
+        HEVMCalls::EnvBool0(inner) => env(&inner.0, ParamType::Bool, None),
+        HEVMCalls::EnvUint0(inner) => env(&inner.0, ParamType::Uint(256), None),
+        HEVMCalls::EnvInt0(inner) => env(&inner.0, ParamType::Int(256), None),
+        HEVMCalls::EnvAddress0(inner) => env(&inner.0, ParamType::Address, None),
+        HEVMCalls::EnvBytes320(inner) => env(&inner.0, ParamType::FixedBytes(32), None),
+        HEVMCalls::EnvString0(inner) => env(&inner.0, ParamType::String, None),
+        HEVMCalls::EnvBytes0(inner) => env(&inner.0, ParamType::Bytes, None),
+        HEVMCalls::EnvBool1(inner) => env(&inner.0, ParamType::Bool, Some(Token::Bool(inner.1))),
+        HEVMCalls::EnvUint1(inner) => {
+            env(&inner.0, ParamType::Uint(256), Some(Token::Uint(inner.1)))
+        }
+        HEVMCalls::EnvInt1(inner) => {
+            env(&inner.0, ParamType::Int(256), Some(Token::Int(inner.1.into_raw())))
+        }
+        HEVMCalls::EnvAddress1(inner) => {
+            env(&inner.0, ParamType::Address, Some(Token::Address(inner.1)))
+        }
+        HEVMCalls::EnvBytes321(inner) => {
+            env(&inner.0, ParamType::FixedBytes(32), Some(Token::FixedBytes(inner.1.to_vec())))
+        }
+        HEVMCalls::EnvString1(inner) => {
+            env(&inner.0, ParamType::String, Some(Token::String(inner.1.clone())))
+        }
+        HEVMCalls::EnvBytes1(inner) => {
+            env(&inner.0, ParamType::Bytes, Some(Token::Bytes(inner.1.to_vec())))
+        }
+        HEVMCalls::RpcUrl(inner) => state
+            .rpc_endpoints
+            .get(&inner.0)
+            .map(|url| abi::encode(&[Token::String(url)]).into())
+            .map_err(|err| err.encode().into()),
         _ => return None,
     })
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ffi_process_times_out() {
+        let args = ["sh", "-c", "read line; sleep 10"].map(String::from);
+        let mut process = FfiProcess::spawn(&args).unwrap();
+        let err = process.call(&[1], Duration::from_millis(100)).unwrap_err();
+        assert!(err.contains("did not answer"));
+    }
+
+    #[test]
+    fn ffi_process_answers() {
+        let args = ["sh", "-c", "while read line; do echo $line; done"].map(String::from);
+        let mut process = FfiProcess::spawn(&args).unwrap();
+        assert_eq!(process.call(&[1, 2], Duration::from_secs(10)).unwrap(), vec![1, 2]);
+        assert_eq!(process.call(&[3], Duration::from_secs(10)).unwrap(), vec![3]);
+    }
+}
@@ -0,0 +1,371 @@
+use super::Cheatcodes;
+use crate::{
+    abi::HEVMCalls,
+    executor::{
+        fork::{BlockFork, SharedBackend},
+        CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS,
+    },
+    HashMap,
+};
+use bytes::Bytes;
+use ethers::{
+    abi::{self, AbiEncode, Token},
+    types::{Address, U256},
+};
+use revm::{
+    db::CacheDB, opcode, Account, AccountInfo, Database, DatabaseCommit, EVMData, Filth,
+    Interpreter, TransactTo,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A fork created with `createFork`
+#[derive(Clone, Debug)]
+struct CreatedFork {
+    /// The forked block
+    fork: BlockFork,
+    /// The state of the fork, the changes made while the fork was selected are recorded on top
+    /// of the forked block
+    db: CacheDB<SharedBackend>,
+}
+
+impl CreatedFork {
+    fn new(fork: BlockFork) -> Self {
+        let db = CacheDB::new(fork.backend.clone());
+        Self { fork, db }
+    }
+}
+
+/// The forks created with `createFork`, and which of them is selected.
+///
+/// While a fork is selected, all accounts that are not persistent are loaded from the fork instead
+/// of the executor's database. Every fork keeps its own state, so the changes made while a fork
+/// was selected are restored when it is selected again.
+#[derive(Clone, Debug, Default)]
+pub struct Forks {
+    /// All created forks, the id of a fork is its index
+    created: Vec<CreatedFork>,
+    /// The selected fork, `None` if the executor's database is selected
+    active: Option<usize>,
+    /// The accounts of the executor's database that were loaded before a fork was selected
+    default_state: BTreeMap<Address, Account>,
+    /// The block number and timestamp of the executor's environment while a fork is selected
+    default_block: Option<(U256, U256)>,
+    /// Accounts that keep their state when another fork is selected, e.g. the test contract
+    persistent: BTreeSet<Address>,
+}
+
+impl Forks {
+    /// Returns `true` if accounts need to be loaded from somewhere else than the executor's
+    /// database
+    fn is_redirecting(&self) -> bool {
+        self.active.is_some() || !self.default_state.is_empty()
+    }
+
+    fn is_persistent(&self, address: Address) -> bool {
+        address == CHEATCODE_ADDRESS ||
+            address == HARDHAT_CONSOLE_ADDRESS ||
+            self.persistent.contains(&address)
+    }
+
+    /// Returns the account in the selected fork, `None` if it should be loaded from the
+    /// executor's database
+    fn account(&mut self, address: Address) -> Option<Account> {
+        match self.active {
+            Some(id) => {
+                let db = &mut self.created[id].db;
+                Some(new_account(db.basic(address), |hash| db.code_by_hash(hash)))
+            }
+            None => self.default_state.get(&address).cloned(),
+        }
+    }
+
+    /// Returns the account as it is loaded into the journaled state, `None` if it's loaded from
+    /// the executor's database
+    pub(super) fn account_to_load(&mut self, address: Address) -> Option<Account> {
+        if self.is_persistent(address) {
+            return None
+        }
+        self.account(address)
+    }
+
+    /// Returns the storage slot as it is loaded into the journaled state, `None` if it's loaded
+    /// from the executor's database
+    pub(super) fn slot_to_load(&mut self, address: Address, index: U256) -> Option<U256> {
+        match self.active {
+            Some(id) if !self.is_persistent(address) => {
+                Some(self.created[id].db.storage(address, index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Loads the account from the selected fork into the journaled state, unless it is loaded
+    /// already
+    fn load_account<DB: Database>(&mut self, data: &mut EVMData<'_, DB>, address: Address) {
+        if self.is_persistent(address) || data.subroutine.state().contains_key(&address) {
+            return
+        }
+        if let Some(account) = self.account(address) {
+            data.subroutine.state().insert(address, account);
+        }
+    }
+
+    /// Loads the storage slot from the selected fork into the journaled state, unless it is
+    /// loaded already
+    fn load_slot<DB: Database>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        address: Address,
+        index: U256,
+    ) {
+        self.load_account(data, address);
+        let id = match self.active {
+            Some(id) if !self.is_persistent(address) => id,
+            _ => return,
+        };
+        if let Some(account) = data.subroutine.state().get_mut(&address) {
+            if !account.storage.contains_key(&index) && !account.filth.abandon_old_storage() {
+                account.storage.insert(index, self.created[id].db.storage(address, index));
+            }
+        }
+    }
+
+    /// Loads the accounts and storage slots the next instruction accesses from the selected fork
+    pub fn step<DB: Database>(&mut self, interpreter: &Interpreter, data: &mut EVMData<'_, DB>) {
+        if !self.is_redirecting() {
+            return
+        }
+
+        let stack = interpreter.stack();
+        match interpreter.contract.code[interpreter.program_counter()] {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(index) = stack.peek(0) {
+                    self.load_slot(data, interpreter.contract().address, index);
+                }
+            }
+            opcode::BALANCE |
+            opcode::EXTCODESIZE |
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::SELFDESTRUCT => {
+                if let Ok(word) = stack.peek(0) {
+                    self.load_account(data, word_to_address(word));
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                if let Ok(word) = stack.peek(1) {
+                    self.load_account(data, word_to_address(word));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Selects the fork `id`, or the executor's database if `None`
+    ///
+    /// The loaded accounts that are not persistent are saved to the previously selected fork, and
+    /// replaced by their state in the selected fork.
+    fn select<DB: Database>(&mut self, data: &mut EVMData<'_, DB>, id: Option<usize>) {
+        let accounts: Vec<(Address, Account)> = data
+            .subroutine
+            .state()
+            .iter()
+            .filter(|(address, _)| !self.is_persistent(**address))
+            .map(|(address, account)| (*address, account.clone()))
+            .collect();
+        let addresses: Vec<Address> = accounts.iter().map(|(address, _)| *address).collect();
+        self.save(accounts);
+
+        match (self.active, id) {
+            (None, Some(_)) => {
+                self.default_block = Some((data.env.block.number, data.env.block.timestamp))
+            }
+            (Some(_), None) => {
+                if let Some((number, timestamp)) = self.default_block.take() {
+                    data.env.block.number = number;
+                    data.env.block.timestamp = timestamp;
+                }
+            }
+            _ => {}
+        }
+        if let Some(id) = id {
+            data.env.block.number = self.created[id].fork.number.into();
+            data.env.block.timestamp = self.created[id].fork.timestamp;
+        }
+        self.active = id;
+
+        for address in addresses {
+            let account = self.account(address).unwrap_or_else(|| {
+                new_account(data.db.basic(address), |hash| data.db.code_by_hash(hash))
+            });
+            data.subroutine.state().insert(address, account);
+        }
+    }
+
+    /// Saves the state of the accounts to the selected fork
+    fn save(&mut self, accounts: Vec<(Address, Account)>) {
+        match self.active {
+            Some(id) => {
+                self.created[id].db.commit(accounts.into_iter().collect::<HashMap<_, _>>())
+            }
+            None => self.default_state.extend(accounts),
+        }
+    }
+
+    /// Called when the transaction ends, before its state is committed to the executor's
+    /// database.
+    ///
+    /// The state of the selected fork is saved to the fork, and the loaded accounts are reset to
+    /// their state in the executor's database, so the executor's database only ever records
+    /// changes made while it was selected.
+    pub fn on_transaction_end<DB: Database>(&mut self, data: &mut EVMData<'_, DB>) {
+        if let Some(id) = self.active {
+            let accounts: Vec<(Address, Account)> = data
+                .subroutine
+                .state()
+                .iter()
+                .filter(|(address, _)| !self.is_persistent(**address))
+                .map(|(address, account)| (*address, account.clone()))
+                .collect();
+            for (address, _) in &accounts {
+                let account = self.default_state.remove(address).unwrap_or_else(|| {
+                    new_account(data.db.basic(*address), |hash| data.db.code_by_hash(hash))
+                });
+                data.subroutine.state().insert(*address, account);
+            }
+            self.created[id].db.commit(accounts.into_iter().collect::<HashMap<_, _>>());
+        }
+
+        // Changes made to the executor's database before a fork was selected
+        for (address, account) in std::mem::take(&mut self.default_state) {
+            data.subroutine.state().entry(address).or_insert(account);
+        }
+    }
+}
+
+/// Returns an account with the given info, and its code loaded with `code_by_hash` if it is not
+/// loaded yet
+pub(super) fn new_account(
+    mut info: AccountInfo,
+    code_by_hash: impl FnOnce(ethers::types::H256) -> bytes::Bytes,
+) -> Account {
+    if info.code.is_none() {
+        info.code = Some(code_by_hash(info.code_hash));
+    }
+    Account { info, storage: Default::default(), filth: Filth::Clean }
+}
+
+fn word_to_address(word: U256) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+/// Returns the id of the fork, or an error if there is no such fork
+fn fork_id(state: &Cheatcodes, id: U256) -> Result<usize, Bytes> {
+    usize::try_from(id)
+        .ok()
+        .filter(|id| *id < state.forks.created.len())
+        .ok_or_else(|| format!("No fork with id {id} exists").encode().into())
+}
+
+fn block_number(block: U256) -> Result<u64, Bytes> {
+    u64::try_from(block).map_err(|_| format!("Invalid block number {block}").encode().into())
+}
+
+fn create_fork(
+    state: &mut Cheatcodes,
+    url_or_alias: &str,
+    block: Option<u64>,
+) -> Result<Bytes, Bytes> {
+    let url = if url_or_alias.starts_with("http") {
+        url_or_alias.to_string()
+    } else {
+        state.rpc_endpoints.get(url_or_alias).map_err(|err| err.encode())?
+    };
+    let multi_fork = state
+        .multi_fork
+        .as_ref()
+        .ok_or_else(|| "Forks can not be created in this context".to_string().encode())?;
+    let fork = multi_fork.fork(&url, block).map_err(|err| format!("{err:#}").encode())?;
+
+    state.forks.created.push(CreatedFork::new(fork));
+    Ok(abi::encode(&[Token::Uint((state.forks.created.len() - 1).into())]).into())
+}
+
+fn roll_fork<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    id: usize,
+    block: u64,
+) -> Result<Bytes, Bytes> {
+    let multi_fork = state
+        .multi_fork
+        .as_ref()
+        .ok_or_else(|| "Forks can not be rolled in this context".to_string().encode())?;
+    let fork = multi_fork
+        .fork(&state.forks.created[id].fork.url, Some(block))
+        .map_err(|err| format!("{err:#}").encode())?;
+
+    // The state of the fork is discarded, so the selected fork is reloaded at the new block
+    let is_active = state.forks.active == Some(id);
+    if is_active {
+        state.forks.select(data, None);
+    }
+    state.forks.created[id] = CreatedFork::new(fork);
+    if is_active {
+        state.forks.select(data, Some(id));
+    }
+    Ok(Bytes::new())
+}
+
+pub fn apply<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    caller: Address,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    // The test contract and the sender of the transaction keep their state across forks
+    match call {
+        HEVMCalls::CreateFork0(_) |
+        HEVMCalls::CreateFork1(_) |
+        HEVMCalls::SelectFork(_) |
+        HEVMCalls::RollFork0(_) |
+        HEVMCalls::RollFork1(_) |
+        HEVMCalls::MakePersistent(_) |
+        HEVMCalls::IsPersistent(_) => {}
+        _ => return None,
+    }
+    state.forks.persistent.extend([caller, data.env.tx.caller]);
+    if let TransactTo::Call(address) = data.env.tx.transact_to {
+        state.forks.persistent.insert(address);
+    }
+
+    Some(match call {
+        HEVMCalls::CreateFork0(inner) => create_fork(state, &inner.0, None),
+        HEVMCalls::CreateFork1(inner) => {
+            block_number(inner.1).and_then(|block| create_fork(state, &inner.0, Some(block)))
+        }
+        HEVMCalls::SelectFork(inner) => fork_id(state, inner.0).map(|id| {
+            state.forks.select(data, Some(id));
+            Bytes::new()
+        }),
+        HEVMCalls::RollFork0(inner) => {
+            let id = state
+                .forks
+                .active
+                .ok_or_else(|| "No fork is selected".to_string().encode().into());
+            id.and_then(|id| roll_fork(state, data, id, block_number(inner.0)?))
+        }
+        HEVMCalls::RollFork1(inner) => fork_id(state, inner.0)
+            .and_then(|id| roll_fork(state, data, id, block_number(inner.1)?)),
+        HEVMCalls::MakePersistent(inner) => {
+            state.forks.persistent.insert(inner.0);
+            Ok(Bytes::new())
+        }
+        HEVMCalls::IsPersistent(inner) => {
+            Ok(abi::encode(&[Token::Bool(state.forks.is_persistent(inner.0))]).into())
+        }
+        _ => return None,
+    })
+}
@@ -1,31 +1,50 @@
 /// Cheatcodes related to the execution environment.
 mod env;
-pub use env::{Prank, RecordAccess};
+pub use env::{ExecutionContext, Prank, RecordAccess, RecordedLog};
 /// Assertion helpers (such as `expectEmit`)
 mod expect;
-pub use expect::{ExpectedCallData, ExpectedEmit, ExpectedRevert, MockCallDataContext};
+pub use expect::{
+    ExpectedCallData, ExpectedCreate, ExpectedEmit, ExpectedGasUsage, ExpectedRevert,
+    MockCallDataContext,
+};
 /// Cheatcodes that interact with the external environment (FFI etc.)
 mod ext;
+pub use ext::FfiProcesses;
+/// Cheatcodes that create and select forks
+mod fork;
+pub use fork::Forks;
 /// Cheatcodes that configure the fuzzer
 mod fuzz;
+/// String conversion and parsing cheatcodes (`toString`, `parseJson` etc.)
+mod parse;
+/// Cheatcodes that snapshot and restore the EVM state
+mod snapshot;
+pub use snapshot::Snapshot;
 /// Utility cheatcodes (`sign` etc.)
 mod util;
 
 use self::expect::{handle_expect_emit, handle_expect_revert};
 use crate::{
-    abi::HEVMCalls,
-    executor::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    abi::{HEVMCalls, HEVM_ABI},
+    executor::{fork::MultiFork, CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
 };
 use bytes::Bytes;
 use ethers::{
     abi::{AbiDecode, AbiEncode, RawLog},
     types::{Address, H256, U256},
 };
+use foundry_config::{FsPermissions, ResolvedRpcEndpoints};
 use revm::{
-    opcode, BlockEnv, CallInputs, CreateInputs, Database, EVMData, Gas, Inspector, Interpreter,
-    Return,
+    opcode, return_ok, BlockEnv, CallInputs, CreateInputs, Database, EVMData, Gas, Inspector,
+    Interpreter, Return,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::PathBuf,
 };
-use std::collections::BTreeMap;
+
+/// The number of cheatcode calls that are reported when a cheatcode fails
+const REPORTED_CHEATCODE_CALLS: usize = 5;
 
 /// An inspector that handles calls to various cheatcodes, each with their own behavior.
 ///
@@ -36,6 +55,9 @@ pub struct Cheatcodes {
     /// Whether FFI is enabled or not
     pub ffi: bool,
 
+    /// The processes started by `differential`, shared by all clones
+    pub ffi_processes: FfiProcesses,
+
     /// The block environment
     ///
     /// Used in the cheatcode handler to overwrite the block environment separately from the
@@ -60,6 +82,9 @@ pub struct Cheatcodes {
     /// Recorded storage reads and writes
     pub accesses: Option<RecordAccess>,
 
+    /// Logs recorded since `recordLogs` was called
+    pub recorded_logs: Option<Vec<RecordedLog>>,
+
     /// Mocked calls
     pub mocked_calls: BTreeMap<Address, BTreeMap<MockCallDataContext, Bytes>>,
 
@@ -68,6 +93,61 @@ pub struct Cheatcodes {
 
     /// Expected emits
     pub expected_emits: Vec<ExpectedEmit>,
+
+    /// Expected contract creations
+    pub expected_creates: Vec<ExpectedCreate>,
+
+    /// Expected gas usage of the next call
+    pub expected_gas_usage: Option<ExpectedGasUsage>,
+
+    /// Named RPC endpoints, resolvable via `rpcUrl`
+    pub rpc_endpoints: ResolvedRpcEndpoints,
+
+    /// The directory relative paths of the file system cheatcodes are resolved against
+    pub root: PathBuf,
+
+    /// The paths the file system cheatcodes may read and write
+    pub fs_permissions: FsPermissions,
+
+    /// Spawns the backends of forks created with `createFork`, if forking is enabled
+    pub multi_fork: Option<MultiFork>,
+
+    /// The forks created with `createFork`, and the selected fork
+    pub forks: Forks,
+
+    /// The snapshots taken with `snapshot`, the id of a snapshot is its index
+    pub snapshots: Vec<Snapshot>,
+
+    /// The number of logs the log collector collected before the current call, kept up to date
+    /// by the inspector stack
+    pub collected_logs: usize,
+
+    /// The number of collected logs that are kept after `revertTo`, the inspector stack removes
+    /// the others
+    pub reverted_logs: Option<usize>,
+
+    /// The gas the current cheatcode charges on top of the call, e.g. for the authorization
+    /// applied by `attachDelegation`
+    pub cheatcode_gas: u64,
+
+    /// Whether the cheatcodes that run commands, or access the file system, env vars or the
+    /// network are disabled
+    pub sandbox: bool,
+
+    /// The command the EVM is executed by, returned by `isContext`
+    pub execution_context: ExecutionContext,
+
+    /// The balance the addresses created with `makeAddr` and `makeAccount` are funded with
+    pub labeled_account_balance: U256,
+
+    /// The addresses created with `makeAddr` and `makeAccount` that were funded
+    pub funded_accounts: BTreeSet<Address>,
+
+    /// The calldata of the last cheatcode calls, reported when a cheatcode fails
+    pub recent_calls: VecDeque<Bytes>,
+
+    /// The last unexpected cheatcode failure of the current transaction
+    pub failure: Option<CheatcodeFailure>,
 }
 
 impl Cheatcodes {
@@ -84,14 +164,109 @@ impl Cheatcodes {
         // Decode the cheatcode call
         let decoded = HEVMCalls::decode(&call.input).map_err(|err| err.to_string().encode())?;
 
+        if self.sandbox {
+            if let Some(name) = sandboxed_cheatcode(&decoded) {
+                return Err(format!("`{name}` is disabled in sandbox mode").encode().into())
+            }
+        }
+
         // TODO: Log the opcode for the debugger
         env::apply(self, data, caller, &decoded)
             .or_else(|| util::apply(self, data, &decoded))
             .or_else(|| expect::apply(self, data, &decoded))
+            .or_else(|| fork::apply(self, data, caller, &decoded))
+            .or_else(|| snapshot::apply(self, data, &decoded))
             .or_else(|| fuzz::apply(data, &decoded))
-            .or_else(|| ext::apply(self.ffi, &decoded))
+            .or_else(|| ext::apply(self, &decoded))
+            .or_else(|| parse::apply(&decoded))
             .ok_or_else(|| "Cheatcode was unhandled. This is a bug.".to_string().encode())?
     }
+
+    /// Records the cheatcode call, only the last [REPORTED_CHEATCODE_CALLS] calls are kept
+    fn record_call(&mut self, input: &Bytes) {
+        if self.recent_calls.len() == REPORTED_CHEATCODE_CALLS {
+            self.recent_calls.pop_front();
+        }
+        self.recent_calls.push_back(input.clone());
+    }
+
+    /// Records the failure of the last cheatcode call, unless the revert is expected
+    fn record_failure(&mut self, revert: &Bytes) {
+        if self.expected_revert.is_none() {
+            self.failure = Some(CheatcodeFailure {
+                revert: revert.clone(),
+                calls: self.recent_calls.iter().cloned().collect(),
+            });
+        }
+    }
+}
+
+/// A failed cheatcode call and the cheatcode calls leading up to it
+#[derive(Debug, Clone, Default)]
+pub struct CheatcodeFailure {
+    /// The revert data of the failed cheatcode
+    pub revert: Bytes,
+    /// The calldata of the last cheatcode calls, ending with the failed one
+    pub calls: Vec<Bytes>,
+}
+
+impl CheatcodeFailure {
+    /// Appends the cheatcode calls leading up to the failure to the reason, if the call reverted
+    /// with the revert data of the failed cheatcode
+    pub fn report(&self, reason: String, revert: &[u8]) -> String {
+        if self.revert.as_ref() != revert {
+            return reason
+        }
+        let mut reason = format!("{reason}\n\nCheatcode calls leading up to the failure:");
+        for (idx, call) in self.calls.iter().enumerate() {
+            let marker = if idx + 1 == self.calls.len() { " <- failed" } else { "" };
+            reason.push_str(&format!("\n    {}{marker}", format_cheatcode_call(call)));
+        }
+        reason
+    }
+}
+
+/// Formats the calldata of a cheatcode call as `vm.<name>(<args>)`
+fn format_cheatcode_call(input: &[u8]) -> String {
+    let func = HEVM_ABI
+        .functions()
+        .find(|func| input.len() >= 4 && func.short_signature() == input[..4]);
+    match func {
+        Some(func) => {
+            let args = func
+                .decode_input(&input[4..])
+                .map(|tokens| foundry_utils::format_tokens(&tokens).collect::<Vec<_>>().join(", "))
+                .unwrap_or_else(|_| "<invalid arguments>".to_string());
+            format!("vm.{}({args})", func.name)
+        }
+        None => {
+            format!("vm.<unknown cheatcode 0x{}>", hex::encode(input.get(..4).unwrap_or(input)))
+        }
+    }
+}
+
+/// Returns the name of the cheatcode if it runs commands, or accesses the file system, env vars or
+/// the network, which are disabled in sandbox mode
+fn sandboxed_cheatcode(call: &HEVMCalls) -> Option<&'static str> {
+    Some(match call {
+        HEVMCalls::Ffi(_) => "ffi",
+        HEVMCalls::Differential(_) => "differential",
+        HEVMCalls::ReadFile(_) => "readFile",
+        HEVMCalls::WriteFile(_) => "writeFile",
+        HEVMCalls::LoadAllocs(_) => "loadAllocs",
+        HEVMCalls::SetEnv(_) => "setEnv",
+        HEVMCalls::EnvBool0(_) | HEVMCalls::EnvBool1(_) => "envBool",
+        HEVMCalls::EnvUint0(_) | HEVMCalls::EnvUint1(_) => "envUint",
+        HEVMCalls::EnvInt0(_) | HEVMCalls::EnvInt1(_) => "envInt",
+        HEVMCalls::EnvAddress0(_) | HEVMCalls::EnvAddress1(_) => "envAddress",
+        HEVMCalls::EnvBytes320(_) | HEVMCalls::EnvBytes321(_) => "envBytes32",
+        HEVMCalls::EnvString0(_) | HEVMCalls::EnvString1(_) => "envString",
+        HEVMCalls::EnvBytes0(_) | HEVMCalls::EnvBytes1(_) => "envBytes",
+        HEVMCalls::RpcUrl(_) => "rpcUrl",
+        HEVMCalls::CreateFork0(_) | HEVMCalls::CreateFork1(_) => "createFork",
+        HEVMCalls::RollFork0(_) | HEVMCalls::RollFork1(_) => "rollFork",
+        _ => return None,
+    })
 }
 
 impl<DB> Inspector<DB> for Cheatcodes
@@ -104,12 +279,31 @@ where
         call: &mut CallInputs,
         _: bool,
     ) -> (Return, Gas, Bytes) {
+        if data.subroutine.depth() == 0 {
+            self.failure = None;
+        }
+
         if call.contract == CHEATCODE_ADDRESS {
-            match self.apply_cheatcode(data, call.context.caller, call) {
-                Ok(retdata) => (Return::Return, Gas::new(call.gas_limit), retdata),
-                Err(err) => (Return::Revert, Gas::new(call.gas_limit), err),
+            self.record_call(&call.input);
+            let result = self.apply_cheatcode(data, call.context.caller, call);
+            let mut gas = Gas::new(call.gas_limit);
+            let charged = gas.record_cost(std::mem::take(&mut self.cheatcode_gas));
+            match result {
+                Ok(_) if !charged => (Return::OutOfGas, gas, Bytes::new()),
+                Ok(retdata) => (Return::Return, gas, retdata),
+                Err(err) => {
+                    self.record_failure(&err);
+                    (Return::Revert, Gas::new(call.gas_limit), err)
+                }
             }
         } else if call.contract != HARDHAT_CONSOLE_ADDRESS {
+            // Mark the call whose gas usage is expected
+            if let Some(expected) = &mut self.expected_gas_usage {
+                if !expected.active && data.subroutine.depth() == expected.depth {
+                    expected.active = true;
+                }
+            }
+
             // Handle expected calls
             if let Some(expecteds) = self.expected_calls.get_mut(&call.contract) {
                 if let Some(found_match) = expecteds.iter().position(|expected| {
@@ -156,6 +350,20 @@ where
                 }
             }
 
+            // Accounts with an EIP-7702 delegation designator run the code of the implementation
+            // in their own context, the EVM would run the designator itself
+            data.subroutine.load_code(call.contract, data.db);
+            if let Some(implementation) = data
+                .subroutine
+                .account(call.contract)
+                .info
+                .code
+                .as_deref()
+                .and_then(util::delegated_implementation)
+            {
+                call.contract = implementation;
+            }
+
             (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
         } else {
             (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
@@ -180,7 +388,15 @@ where
         Return::Continue
     }
 
-    fn step(&mut self, interpreter: &mut Interpreter, _: &mut EVMData<'_, DB>, _: bool) -> Return {
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _: bool,
+    ) -> Return {
+        // Load the state the instruction accesses from the selected fork
+        self.forks.step(interpreter, data);
+
         // Record writes and reads if `record` has been called
         if let Some(storage_accesses) = &mut self.accesses {
             match interpreter.contract.code[interpreter.program_counter()] {
@@ -223,6 +439,15 @@ where
                 address,
             );
         }
+
+        // Record logs if `recordLogs` has been called
+        if let Some(recorded_logs) = &mut self.recorded_logs {
+            recorded_logs.push(RecordedLog {
+                emitter: *address,
+                topics: topics.to_vec(),
+                data: data.to_vec(),
+            });
+        }
     }
 
     fn call_end(
@@ -248,11 +473,28 @@ where
             }
         }
 
+        // Handle expected gas usage, the gas used by the call itself excludes the calling overhead
+        if let Some(expected) = &self.expected_gas_usage {
+            if expected.active && data.subroutine.depth() == expected.depth {
+                let ExpectedGasUsage { min, max, .. } = self.expected_gas_usage.take().unwrap();
+                let gas_used = remaining_gas.spend();
+                if gas_used < min || gas_used > max {
+                    return (
+                        Return::Revert,
+                        remaining_gas,
+                        format!("Gas used {gas_used} is not in the expected range [{min}, {max}]")
+                            .encode()
+                            .into(),
+                    )
+                }
+            }
+        }
+
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
             if data.subroutine.depth() <= expected_revert.depth {
                 let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match handle_expect_revert(false, &expected_revert.reason, status, retdata) {
+                return match handle_expect_revert(false, &expected_revert, status, retdata) {
                     Err(retdata) => (Return::Revert, remaining_gas, retdata),
                     Ok((_, retdata)) => (Return::Return, remaining_gas, retdata),
                 }
@@ -260,24 +502,27 @@ where
         }
 
         // Handle expected emits at current depth
-        if !self
+        if let Some(err) = self
             .expected_emits
             .iter()
             .filter(|expected| expected.depth == data.subroutine.depth())
-            .all(|expected| expected.found)
+            .find_map(ExpectedEmit::error)
         {
-            return (
-                Return::Revert,
-                remaining_gas,
-                "Log != expected log".to_string().encode().into(),
-            )
+            return (Return::Revert, remaining_gas, err.encode().into())
         } else {
-            // Clear the emits we expected at this depth that have been found
-            self.expected_emits.retain(|expected| !expected.found)
+            // Clear the emits we expected at this depth that have been found, and the counted
+            // emits of this depth
+            self.expected_emits.retain(|expected| {
+                !expected.found &&
+                    (expected.count.is_none() || expected.depth != data.subroutine.depth())
+            })
         }
 
         // If the depth is 0, then this is the root call terminating
         if data.subroutine.depth() == 0 {
+            // Save the state of the selected fork before the transaction is committed
+            self.forks.on_transaction_end(data);
+
             // Handle expected calls that were not fulfilled
             if let Some((address, expecteds)) =
                 self.expected_calls.iter().find(|(_, expecteds)| !expecteds.is_empty())
@@ -296,6 +541,23 @@ where
                 )
             }
 
+            // Handle an expected gas usage without a call
+            if self.expected_gas_usage.take().is_some() {
+                return (
+                    Return::Revert,
+                    remaining_gas,
+                    "Expected a call after expectGasUsage, but got none"
+                        .to_string()
+                        .encode()
+                        .into(),
+                )
+            }
+
+            // Handle expected creations that were not fulfilled
+            if let Some(expected) = self.expected_creates.first() {
+                return (Return::Revert, remaining_gas, expected.to_string().encode().into())
+            }
+
             // Check if we have any leftover expected emits
             if !self.expected_emits.is_empty() {
                 return (
@@ -338,7 +600,7 @@ where
     fn create_end(
         &mut self,
         data: &mut EVMData<'_, DB>,
-        _: &CreateInputs,
+        call: &CreateInputs,
         status: Return,
         address: Option<Address>,
         remaining_gas: Gas,
@@ -354,17 +616,68 @@ where
             }
         }
 
+        // Handle expected creations
+        if let Some(created) = address.filter(|_| matches!(status, return_ok!())) {
+            if let Some(found_match) =
+                self.expected_creates.iter().position(|expected| expected.matches(call, created))
+            {
+                self.expected_creates.remove(found_match);
+            }
+        }
+
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
             if data.subroutine.depth() <= expected_revert.depth {
                 let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match handle_expect_revert(true, &expected_revert.reason, status, retdata) {
+                return match handle_expect_revert(true, &expected_revert, status, retdata) {
                     Err(retdata) => (Return::Revert, None, remaining_gas, retdata),
                     Ok((address, retdata)) => (Return::Return, address, remaining_gas, retdata),
                 }
             }
         }
 
+        // Save the state of the selected fork before the transaction is committed
+        if data.subroutine.depth() == 0 {
+            self.forks.on_transaction_end(data);
+        }
+
         (status, address, remaining_gas, retdata)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::Token;
+
+    #[test]
+    fn can_report_recent_cheatcode_calls() {
+        let mut cheatcodes = Cheatcodes::default();
+        for number in 0..REPORTED_CHEATCODE_CALLS + 1 {
+            let roll = HEVM_ABI.function("roll").unwrap();
+            let input = roll.encode_input(&[Token::Uint(number.into())]).unwrap();
+            cheatcodes.record_call(&input.into());
+        }
+        let stop_prank = HEVM_ABI.function("stopPrank").unwrap().encode_input(&[]).unwrap();
+        cheatcodes.record_call(&stop_prank.into());
+        assert_eq!(cheatcodes.recent_calls.len(), REPORTED_CHEATCODE_CALLS);
+        assert_eq!(format_cheatcode_call(&cheatcodes.recent_calls[0]), "vm.roll(2)");
+
+        let err: Bytes = "No prank in progress".to_string().encode().into();
+        cheatcodes.record_failure(&err);
+        let failure = cheatcodes.failure.clone().unwrap();
+        let reason = failure.report("No prank in progress".to_string(), &err);
+        assert!(reason.starts_with("No prank in progress\n\nCheatcode calls leading up to"));
+        assert!(reason.ends_with("\n    vm.roll(5)\n    vm.stopPrank() <- failed"));
+
+        // a revert for another reason is reported as is
+        let other: Bytes = "other".to_string().encode().into();
+        assert_eq!(failure.report("other".to_string(), &other), "other");
+
+        // expected reverts are not failures
+        cheatcodes.failure = None;
+        cheatcodes.expected_revert = Some(Default::default());
+        cheatcodes.record_failure(&err);
+        assert!(cheatcodes.failure.is_none());
+    }
+}
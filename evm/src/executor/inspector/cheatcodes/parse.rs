@@ -0,0 +1,192 @@
+use crate::abi::HEVMCalls;
+use bytes::Bytes;
+use ethers::{
+    abi::{self, AbiEncode, ParamType, Token},
+    types::{Address, I256, U256},
+    utils::to_checksum,
+};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Parses a string as a value of type `ty`
+///
+/// Integers are decimal, or hex if prefixed with `0x`. Hex encoded signed integers are in two's
+/// complement. Bytes are hex encoded, `bytes32` values shorter than 32 bytes are right padded.
+pub(super) fn parse_value(value: &str, ty: &ParamType) -> Result<Token, String> {
+    let trimmed = value.trim();
+    let hex_value = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    match ty {
+        ParamType::Bool => {
+            trimmed.to_lowercase().parse().map(Token::Bool).map_err(|err| err.to_string())
+        }
+        ParamType::Uint(_) => match trimmed.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).map_err(|err| err.to_string()),
+            None => U256::from_dec_str(trimmed).map_err(|err| err.to_string()),
+        }
+        .map(Token::Uint),
+        ParamType::Int(_) => match trimmed.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).map_err(|err| err.to_string()),
+            None => I256::from_dec_str(trimmed).map(I256::into_raw).map_err(|err| err.to_string()),
+        }
+        .map(Token::Int),
+        ParamType::Address => {
+            Address::from_str(trimmed).map(Token::Address).map_err(|err| err.to_string())
+        }
+        ParamType::FixedBytes(size) => {
+            let mut bytes = hex::decode(hex_value).map_err(|err| err.to_string())?;
+            if bytes.len() > *size {
+                return Err(format!("{} bytes are longer than {size} bytes", bytes.len()))
+            }
+            bytes.resize(*size, 0);
+            Ok(Token::FixedBytes(bytes))
+        }
+        ParamType::Bytes => hex::decode(hex_value).map(Token::Bytes).map_err(|err| err.to_string()),
+        _ => Ok(Token::String(value.to_string())),
+    }
+}
+
+fn parse(value: &str, ty: ParamType) -> Result<Bytes, Bytes> {
+    let token = parse_value(value, &ty)
+        .map_err(|err| format!("Failed to parse `{value}` as {ty}: {err}").encode())?;
+    Ok(abi::encode(&[token]).into())
+}
+
+fn to_string(value: String) -> Result<Bytes, Bytes> {
+    Ok(abi::encode(&[Token::String(value)]).into())
+}
+
+/// Selects the value at `key` in the JSON value
+///
+/// The key is a path of object keys and array indices, e.g. `.tokens[0].address`, optionally
+/// prefixed with `$`. An empty key, `.` or `$` select the whole value.
+fn select_json<'a>(json: &'a Value, key: &str) -> Result<&'a Value, String> {
+    let mut value = json;
+    let mut rest = key.strip_prefix('$').unwrap_or(key);
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(|c| c == '.' || c == '[').unwrap_or(after.len());
+            let (name, tail) = after.split_at(end);
+            if name.is_empty() {
+                if tail.is_empty() {
+                    break
+                }
+                return Err(format!("Empty key in `{key}`"))
+            }
+            value = value.get(name).ok_or_else(|| format!("No value at `{name}` of `{key}`"))?;
+            rest = tail;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| format!("Unclosed `[` in `{key}`"))?;
+            let index: usize =
+                after[..end].parse().map_err(|_| format!("Invalid index in `{key}`"))?;
+            value = value.get(index).ok_or_else(|| format!("No value at [{index}] of `{key}`"))?;
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("Invalid key `{key}`, expected `.name` or `[index]` segments"))
+        }
+    }
+    Ok(value)
+}
+
+/// Converts a JSON value to the token it is ABI encoded as
+///
+/// Non-negative integers are `uint256`, negative integers are `int256`. Hex strings prefixed with
+/// `0x` are `address` if they are 20 bytes long, `bytes32` if they are 32 bytes long, and `bytes`
+/// otherwise, other strings are `string`. Objects are tuples of their values in the alphabetical
+/// order of their keys.
+fn json_to_token(value: &Value) -> Result<Token, String> {
+    Ok(match value {
+        Value::Null => return Err("null can not be ABI encoded".to_string()),
+        Value::Bool(value) => Token::Bool(*value),
+        Value::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                Token::Uint(value.into())
+            } else if let Some(value) = number.as_i64() {
+                Token::Int(I256::from(value).into_raw())
+            } else {
+                return Err(format!(
+                    "{number} is not an integer in the range of 64 bits, use a string instead"
+                ))
+            }
+        }
+        Value::String(value) => match value.strip_prefix("0x").map(hex::decode) {
+            Some(Ok(bytes)) if bytes.len() == 20 => Token::Address(Address::from_slice(&bytes)),
+            Some(Ok(bytes)) if bytes.len() == 32 => Token::FixedBytes(bytes),
+            Some(Ok(bytes)) => Token::Bytes(bytes),
+            _ => Token::String(value.clone()),
+        },
+        Value::Array(values) => {
+            Token::Array(values.iter().map(json_to_token).collect::<Result<_, _>>()?)
+        }
+        Value::Object(values) => {
+            Token::Tuple(values.values().map(json_to_token).collect::<Result<_, _>>()?)
+        }
+    })
+}
+
+/// Returns the ABI encoding of the value at `key` in the JSON string, see [json_to_token]
+fn parse_json(json: &str, key: &str) -> Result<Bytes, Bytes> {
+    let encoded = serde_json::from_str::<Value>(json)
+        .map_err(|err| format!("Invalid JSON: {err}"))
+        .and_then(|json| json_to_token(select_json(&json, key)?))
+        .map(|token| abi::encode(&[token]))
+        .map_err(|err| format!("Failed to parse JSON at `{key}`: {err}").encode())?;
+    Ok(abi::encode(&[Token::Bytes(encoded)]).into())
+}
+
+pub fn apply(call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
+    Some(match call {
+        HEVMCalls::ToString0(inner) => to_string(to_checksum(&inner.0, None)),
+        HEVMCalls::ToString1(inner) => to_string(format!("0x{}", hex::encode(&inner.0))),
+        HEVMCalls::ToString2(inner) => to_string(format!("0x{}", hex::encode(inner.0))),
+        HEVMCalls::ToString3(inner) => to_string(inner.0.to_string()),
+        HEVMCalls::ToString4(inner) => to_string(inner.0.to_string()),
+        HEVMCalls::ToString5(inner) => to_string(inner.0.to_string()),
+        HEVMCalls::ParseBytes(inner) => parse(&inner.0, ParamType::Bytes),
+        HEVMCalls::ParseAddress(inner) => parse(&inner.0, ParamType::Address),
+        HEVMCalls::ParseUint(inner) => parse(&inner.0, ParamType::Uint(256)),
+        HEVMCalls::ParseInt(inner) => parse(&inner.0, ParamType::Int(256)),
+        HEVMCalls::ParseBytes32(inner) => parse(&inner.0, ParamType::FixedBytes(32)),
+        HEVMCalls::ParseBool(inner) => parse(&inner.0, ParamType::Bool),
+        HEVMCalls::ParseJson0(inner) => parse_json(&inner.0, &inner.1),
+        HEVMCalls::ParseJson1(inner) => parse_json(&inner.0, ""),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_select_json_values() {
+        let json: Value = serde_json::from_str(
+            r#"{"tokens": [{"symbol": "DAI", "decimals": 18}], "owner": "0x01"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(select_json(&json, "").unwrap(), &json);
+        assert_eq!(select_json(&json, "$").unwrap(), &json);
+        assert_eq!(select_json(&json, ".owner").unwrap(), "0x01");
+        assert_eq!(select_json(&json, "$.tokens[0].decimals").unwrap(), 18);
+        assert!(select_json(&json, ".tokens[1]").is_err());
+        assert!(select_json(&json, "tokens").is_err());
+    }
+
+    #[test]
+    fn can_convert_json_to_tokens() {
+        let json: Value = serde_json::from_str(
+            r#"{"b": -1, "a": [1, 2], "c": "0x0000000000000000000000000000000000000001"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            json_to_token(&json).unwrap(),
+            Token::Tuple(vec![
+                Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into())]),
+                Token::Int(I256::from(-1).into_raw()),
+                Token::Address(Address::from_low_u64_be(1)),
+            ])
+        );
+        assert!(json_to_token(&Value::Null).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use super::{fork::new_account, Cheatcodes, Forks, RecordedLog};
+use crate::abi::HEVMCalls;
+use bytes::Bytes;
+use ethers::{
+    abi::{self, AbiEncode, Token},
+    types::{Address, U256},
+};
+use revm::{Account, BlockEnv, Database, EVMData};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The state of the EVM when `snapshot` was called
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    /// The accounts loaded in the journaled state, with their balances, nonces, code and storage
+    accounts: BTreeMap<Address, Account>,
+    /// The block environment, which may have been changed with `warp` or `roll`
+    block: BlockEnv,
+    /// The forks and the selected fork
+    forks: Forks,
+    /// The number of logs the log collector collected
+    logs: usize,
+    /// The logs recorded since `recordLogs` was called
+    recorded_logs: Option<Vec<RecordedLog>>,
+}
+
+fn snapshot<DB: Database>(state: &mut Cheatcodes, data: &mut EVMData<'_, DB>) -> Bytes {
+    state.snapshots.push(Snapshot {
+        accounts: data.subroutine.state().iter().map(|(k, v)| (*k, v.clone())).collect(),
+        block: data.env.block.clone(),
+        forks: state.forks.clone(),
+        logs: state.collected_logs,
+        recorded_logs: state.recorded_logs.clone(),
+    });
+    abi::encode(&[Token::Uint((state.snapshots.len() - 1).into())]).into()
+}
+
+/// Restores the state of the snapshot, and returns `false` if there is no such snapshot
+///
+/// The accounts are restored through the journal, like the test contract had changed them, and
+/// accounts that were loaded after the snapshot was taken are reset to the state they are loaded
+/// with. Only the test contract can restore a snapshot, because the journal of the calls between
+/// it and a nested call can't be rolled back. The snapshot is kept, so the same state can be
+/// restored multiple times.
+fn revert_to<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    id: U256,
+) -> Result<Bytes, Bytes> {
+    // the calls of the test contract run at depth 1
+    if data.subroutine.depth() > 1 {
+        return Err("`revertTo` can only be called by the test contract".to_string().encode().into())
+    }
+    let snapshot = match usize::try_from(id).ok().and_then(|id| state.snapshots.get(id)) {
+        Some(snapshot) => snapshot.clone(),
+        None => return Ok(abi::encode(&[Token::Bool(false)]).into()),
+    };
+
+    // the forks are restored first, so accounts are reset to the state of the selected fork
+    state.forks = snapshot.forks;
+    data.env.block = snapshot.block;
+    state.recorded_logs = snapshot.recorded_logs;
+    state.reverted_logs = Some(snapshot.logs);
+
+    let addresses: BTreeSet<Address> = data
+        .subroutine
+        .state()
+        .keys()
+        .chain(snapshot.accounts.keys())
+        .copied()
+        .collect();
+    for address in addresses {
+        let account = match snapshot.accounts.get(&address) {
+            Some(account) => account.clone(),
+            None => match state.forks.account_to_load(address) {
+                Some(account) => account,
+                None => new_account(data.db.basic(address), |hash| data.db.code_by_hash(hash)),
+            },
+        };
+        restore_account(state, data, address, &account);
+    }
+    Ok(abi::encode(&[Token::Bool(true)]).into())
+}
+
+/// Sets the account in the journaled state to the state of `target`
+///
+/// The code, balance and storage are changed through the journal, so the changes are rolled back
+/// with the call that made them. The nonce is set directly, like `setNonce` does.
+fn restore_account<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    address: Address,
+    target: &Account,
+) {
+    data.subroutine.load_account(address, data.db);
+    // we can safely unwrap because `load_account` inserted the account
+    let current = data.subroutine.state().get(&address).unwrap().clone();
+
+    if current.info.code_hash != target.info.code_hash {
+        let code = match &target.info.code {
+            Some(code) => code.clone(),
+            None => data.db.code_by_hash(target.info.code_hash),
+        };
+        data.subroutine.set_code(address, code, target.info.code_hash);
+    }
+    if target.info.balance > current.info.balance {
+        data.subroutine.balance_add(address, target.info.balance - current.info.balance);
+    } else if target.info.balance < current.info.balance {
+        data.subroutine.balance_sub(address, current.info.balance - target.info.balance);
+    }
+    data.subroutine.state().get_mut(&address).unwrap().info.nonce = target.info.nonce;
+
+    let slots: BTreeSet<U256> =
+        current.storage.keys().chain(target.storage.keys()).copied().collect();
+    for slot in slots {
+        let value = match target.storage.get(&slot) {
+            Some(value) => *value,
+            None if target.filth.abandon_old_storage() => U256::zero(),
+            // the slot was loaded after the snapshot was taken
+            None => state
+                .forks
+                .slot_to_load(address, slot)
+                .unwrap_or_else(|| data.db.storage(address, slot)),
+        };
+        if current.storage.get(&slot) != Some(&value) {
+            data.subroutine.sstore(address, slot, value, data.db);
+        }
+    }
+}
+
+pub fn apply<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    Some(match call {
+        HEVMCalls::Snapshot(_) => Ok(snapshot(state, data)),
+        HEVMCalls::RevertTo(inner) => revert_to(state, data, inner.0),
+        _ => return None,
+    })
+}
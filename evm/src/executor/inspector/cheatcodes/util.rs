@@ -1,15 +1,29 @@
-use crate::abi::HEVMCalls;
+use crate::abi::{HEVMCalls, SignedDelegation};
 use bytes::Bytes;
 use ethers::{
-    abi::AbiEncode,
+    abi::{self, AbiEncode, Token},
     prelude::{k256::ecdsa::SigningKey, LocalWallet, Signer},
-    types::{H256, U256},
+    signers::{coins_bip39::English, MnemonicBuilder},
+    types::{Address, Signature, H256, U256},
     utils,
 };
-use revm::{Database, EVMData};
+use foundry_utils::delegation_digest;
+use revm::{Database, EVMData, KECCAK_EMPTY};
 
 use super::Cheatcodes;
 
+/// The derivation path of the accounts of a mnemonic, the index of the account is appended
+const DEFAULT_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
+
+/// The prefix of an EIP-7702 delegation designator, `0xef0100 ++ implementation`
+const DELEGATION_DESIGNATOR_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// The gas of an EIP-7702 authorization of an account that does not exist
+const PER_EMPTY_ACCOUNT_COST: u64 = 25_000;
+
+/// The gas of an EIP-7702 authorization of an existing account
+const PER_AUTH_BASE_COST: u64 = 12_500;
+
 fn addr(private_key: U256) -> Result<Bytes, Bytes> {
     if private_key.is_zero() {
         return Err("Private key cannot be 0.".to_string().encode().into())
@@ -48,6 +62,179 @@ fn sign(private_key: U256, digest: H256, chain_id: U256) -> Result<Bytes, Bytes>
     Ok((sig.v, r_bytes, s_bytes).encode().into())
 }
 
+/// Signs the `digest` and returns the EIP-2098 compact signature `(r, vs)`
+fn sign_compact(private_key: U256, digest: H256) -> Result<Bytes, Bytes> {
+    let sig = LocalWallet::from(signing_key(private_key)?).sign_hash(digest);
+
+    let mut r_bytes = [0u8; 32];
+    let mut vs_bytes = [0u8; 32];
+    sig.r.to_big_endian(&mut r_bytes);
+    sig.s.to_big_endian(&mut vs_bytes);
+    // `s` is in the lower half of the curve order, so its highest bit is free for the y parity
+    if sig.v == 28 {
+        vs_bytes[0] |= 0x80;
+    }
+
+    Ok((r_bytes, vs_bytes).encode().into())
+}
+
+/// Signs the `digest` with all `private_keys` and concatenates the `r ++ s ++ v` signatures in
+/// ascending order of the signers, the order Safe style multisigs check them in
+fn sign_aggregate(private_keys: &[U256], digest: H256) -> Result<Bytes, Bytes> {
+    let mut signatures = private_keys
+        .iter()
+        .map(|private_key| {
+            let wallet = LocalWallet::from(signing_key(*private_key)?);
+            Ok((wallet.address(), wallet.sign_hash(digest)))
+        })
+        .collect::<Result<Vec<_>, Bytes>>()?;
+    signatures.sort_by_key(|(signer, _)| *signer);
+
+    if let Some(pair) = signatures.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(format!("Duplicate signer {:?}", pair[0].0).encode().into())
+    }
+
+    let aggregated = signatures.iter().flat_map(|(_, sig)| sig.to_vec()).collect();
+    Ok(abi::encode(&[Token::Bytes(aggregated)]).into())
+}
+
+/// Returns the signing key of a non zero `private_key`
+fn signing_key(private_key: U256) -> Result<SigningKey, Bytes> {
+    if private_key.is_zero() {
+        return Err("Private key cannot be 0.".to_string().encode().into())
+    }
+
+    let mut bytes: [u8; 32] = [0; 32];
+    private_key.to_big_endian(&mut bytes);
+
+    SigningKey::from_bytes(&bytes).map_err(|err| err.to_string().encode().into())
+}
+
+/// Signs an EIP-7702 authorization of the account of `private_key` to delegate to
+/// `implementation`, for the current nonce of the account
+fn sign_delegation<DB: Database>(
+    data: &mut EVMData<'_, DB>,
+    implementation: Address,
+    private_key: U256,
+) -> Result<Bytes, Bytes> {
+    if private_key.is_zero() {
+        return Err("Private key cannot be 0.".to_string().encode().into())
+    }
+
+    let mut bytes: [u8; 32] = [0; 32];
+    private_key.to_big_endian(&mut bytes);
+
+    let key = SigningKey::from_bytes(&bytes).map_err(|err| err.to_string().encode())?;
+    let authority = utils::secret_key_to_address(&key);
+    data.subroutine.load_account(authority, data.db);
+    let nonce = data.subroutine.account(authority).info.nonce;
+
+    let digest = delegation_digest(data.env.cfg.chain_id, implementation, nonce);
+    let sig = LocalWallet::from(key).sign_hash(digest);
+
+    let mut r_bytes = [0u8; 32];
+    let mut s_bytes = [0u8; 32];
+    sig.r.to_big_endian(&mut r_bytes);
+    sig.s.to_big_endian(&mut s_bytes);
+
+    // authorizations are signed with the y parity instead of `v`
+    let y_parity = (sig.v - 27) as u8;
+    Ok((y_parity, r_bytes, s_bytes, nonce, implementation).encode().into())
+}
+
+/// Applies a signed EIP-7702 authorization to the account that signed it
+///
+/// The delegation designator `0xef0100 ++ implementation` is written to the account, delegating
+/// to the zero address clears it. The nonce of the account is incremented and the gas of the
+/// authorization is charged to the caller.
+fn attach_delegation<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    delegation: &SignedDelegation,
+) -> Result<Bytes, Bytes> {
+    let digest =
+        delegation_digest(data.env.cfg.chain_id, delegation.implementation, delegation.nonce);
+    let signature = Signature {
+        r: U256::from_big_endian(&delegation.r),
+        s: U256::from_big_endian(&delegation.s),
+        v: delegation.v as u64 + 27,
+    };
+    let authority = signature.recover(digest).map_err(|err| err.to_string().encode())?;
+
+    data.subroutine.load_account(authority, data.db);
+    // we can safely unwrap because `load_account` inserts the authority into the state
+    let account = data.subroutine.state().get_mut(&authority).unwrap();
+    if account.info.nonce != delegation.nonce {
+        return Err(format!(
+            "Invalid delegation nonce {}, the nonce of {authority:?} is {}",
+            delegation.nonce, account.info.nonce
+        )
+        .encode()
+        .into())
+    }
+    let exists = account.info.nonce != 0 ||
+        !account.info.balance.is_zero() ||
+        !(account.info.code_hash == KECCAK_EMPTY || account.info.code_hash.is_zero());
+    account.info.nonce += 1;
+    state.cheatcode_gas += if exists { PER_AUTH_BASE_COST } else { PER_EMPTY_ACCOUNT_COST };
+
+    let (code, hash) = if delegation.implementation.is_zero() {
+        (Bytes::new(), KECCAK_EMPTY)
+    } else {
+        let mut code = DELEGATION_DESIGNATOR_PREFIX.to_vec();
+        code.extend_from_slice(delegation.implementation.as_bytes());
+        let hash = H256(utils::keccak256(&code));
+        (code.into(), hash)
+    };
+    data.subroutine.set_code(authority, code, hash);
+    Ok(Bytes::new())
+}
+
+/// Returns the implementation the code delegates to, if it's an EIP-7702 delegation designator
+pub fn delegated_implementation(code: &[u8]) -> Option<Address> {
+    (code.len() == 23 && code.starts_with(&DELEGATION_DESIGNATOR_PREFIX))
+        .then(|| Address::from_slice(&code[3..]))
+}
+
+fn derive_key(mnemonic: &str, path: &str, index: u32) -> Result<Bytes, Bytes> {
+    let path = format!("{}/{index}", path.trim_end_matches('/'));
+    let wallet = MnemonicBuilder::<English>::default()
+        .phrase(mnemonic)
+        .derivation_path(&path)
+        .map_err(|err| err.to_string().encode())?
+        .build()
+        .map_err(|err| err.to_string().encode())?;
+
+    let private_key = U256::from_big_endian(wallet.signer().to_bytes().as_slice());
+    Ok(private_key.encode().into())
+}
+
+/// Returns the address and private key of the account of the label, the private key is
+/// `keccak256(label)`.
+///
+/// The address is labeled in traces and, the first time it is made, funded with the configured
+/// `labeled_account_balance`, even if it was labeled with `label` before.
+fn make_account<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    label: &str,
+) -> Result<(Address, U256), Bytes> {
+    let hash = utils::keccak256(label.as_bytes());
+    let key = SigningKey::from_bytes(&hash).map_err(|err| err.to_string().encode())?;
+    let address = utils::secret_key_to_address(&key);
+
+    state.labels.insert(address, label.to_string());
+    let is_new = state.funded_accounts.insert(address);
+    if is_new && !state.labeled_account_balance.is_zero() {
+        data.subroutine.load_account(address, data.db);
+        let balance = data.subroutine.account(address).info.balance;
+        if balance < state.labeled_account_balance {
+            data.subroutine.balance_add(address, state.labeled_account_balance - balance);
+        }
+    }
+    Ok((address, U256::from_big_endian(&hash)))
+}
+
 pub fn apply<DB: Database>(
     state: &mut Cheatcodes,
     data: &mut EVMData<'_, DB>,
@@ -56,10 +243,24 @@ pub fn apply<DB: Database>(
     Some(match call {
         HEVMCalls::Addr(inner) => addr(inner.0),
         HEVMCalls::Sign(inner) => sign(inner.0, inner.1.into(), data.env.cfg.chain_id),
+        HEVMCalls::SignCompact(inner) => sign_compact(inner.0, inner.1.into()),
+        HEVMCalls::SignAggregate(inner) => sign_aggregate(&inner.0, inner.1.into()),
+        HEVMCalls::SignDelegation(inner) => sign_delegation(data, inner.0, inner.1),
+        HEVMCalls::AttachDelegation(inner) => attach_delegation(state, data, &inner.0),
+        HEVMCalls::DeriveKey0(inner) => {
+            derive_key(&inner.0, DEFAULT_DERIVATION_PATH_PREFIX, inner.1)
+        }
+        HEVMCalls::DeriveKey1(inner) => derive_key(&inner.0, &inner.1, inner.2),
         HEVMCalls::Label(inner) => {
             state.labels.insert(inner.0, inner.1.clone());
             Ok(Bytes::new())
         }
+        HEVMCalls::MakeAddr(inner) => {
+            make_account(state, data, &inner.0).map(|(address, _)| address.encode().into())
+        }
+        HEVMCalls::MakeAccount(inner) => make_account(state, data, &inner.0).map(|(address, key)| {
+            abi::encode(&[Token::Address(address), Token::Uint(key)]).into()
+        }),
         _ => return None,
     })
 }
@@ -0,0 +1,49 @@
+use crate::coverage::{HitMap, HitMaps};
+use ethers::{types::H256, utils::keccak256};
+use revm::{db::Database, EVMData, Inspector, Interpreter, Return};
+use std::collections::HashMap;
+
+/// An inspector that records which instructions of every executed contract were hit.
+#[derive(Default, Debug)]
+pub struct CoverageCollector {
+    /// The hit maps of all executed contracts
+    pub maps: HitMaps,
+    /// The code hashes of the running interpreters, keyed by the address of their code buffer
+    ///
+    /// This avoids hashing the code on every step. Interpreters that share a buffer run the same
+    /// code, and the entry of a buffer is overwritten when a new interpreter is initialized.
+    code_hashes: HashMap<usize, H256>,
+}
+
+impl<DB> Inspector<DB> for CoverageCollector
+where
+    DB: Database,
+{
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        _: &mut EVMData<'_, DB>,
+        _: bool,
+    ) -> Return {
+        let code = interp.contract().code.clone();
+        let hash = H256::from_slice(&keccak256(&code));
+        self.code_hashes.insert(code.as_ptr() as usize, hash);
+        self.maps.entry(hash).or_insert_with(|| HitMap::new(code));
+
+        Return::Continue
+    }
+
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        _: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        let hash = self.code_hashes.get(&(interpreter.contract.code.as_ptr() as usize));
+        if let Some(map) = hash.and_then(|hash| self.maps.get_mut(hash)) {
+            map.hit(interpreter.program_counter());
+        }
+
+        Return::Continue
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     debug::{DebugArena, DebugNode, DebugStep, Instruction},
     executor::{
-        inspector::utils::{gas_used, get_create_address},
+        inspector::utils::{gas_refunded, gas_used, get_create_address},
         CHEATCODE_ADDRESS,
     },
     CallKind,
@@ -181,7 +181,7 @@ where
                 .expect("no instruction counter map")
                 .get(&pc)
                 .expect("unknown ic for pc"),
-            total_gas_used: gas_used(data.env.cfg.spec_id, total_gas_spent, gas.refunded() as u64),
+            total_gas_used: gas_used(data.env.cfg.spec_id, total_gas_spent, gas_refunded(gas)),
         });
 
         Return::Continue
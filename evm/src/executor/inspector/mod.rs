@@ -14,10 +14,16 @@ mod stack;
 pub use stack::{InspectorData, InspectorStack};
 
 mod cheatcodes;
-pub use cheatcodes::Cheatcodes;
+pub use cheatcodes::{CheatcodeFailure, Cheatcodes, ExecutionContext};
 
+mod coverage;
+pub use coverage::CoverageCollector;
+
+use super::fork::MultiFork;
 use ethers::types::U256;
+use foundry_config::{FsPermissions, ResolvedRpcEndpoints};
 use revm::BlockEnv;
+use std::path::PathBuf;
 
 #[derive(Default, Clone, Debug)]
 pub struct InspectorStackConfig {
@@ -38,6 +44,24 @@ pub struct InspectorStackConfig {
     pub tracing: bool,
     /// Whether or not the debugger is enabled
     pub debugger: bool,
+    /// Whether or not coverage info should be collected
+    pub coverage: bool,
+    /// Named RPC endpoints, passed to the cheatcodes
+    pub rpc_endpoints: ResolvedRpcEndpoints,
+    /// The directory relative paths of the file system cheatcodes are resolved against
+    pub root: PathBuf,
+    /// The paths the file system cheatcodes may access, passed to the cheatcodes
+    pub fs_permissions: FsPermissions,
+    /// Spawns the backends of forks created by the cheatcodes, if forking is enabled
+    pub multi_fork: Option<MultiFork>,
+    /// Whether the cheatcodes that run commands, or access the file system, env vars or the
+    /// network are disabled
+    pub sandbox: bool,
+    /// The command the EVM is executed by, passed to the cheatcodes
+    pub execution_context: ExecutionContext,
+    /// The balance the addresses created with `makeAddr` are funded with, passed to the
+    /// cheatcodes
+    pub labeled_account_balance: U256,
 }
 
 impl InspectorStackConfig {
@@ -49,6 +73,13 @@ impl InspectorStackConfig {
         if let Some(ref mut cheatcodes) = stack.cheatcodes {
             cheatcodes.block = Some(self.block.clone());
             cheatcodes.gas_price = Some(self.gas_price);
+            cheatcodes.rpc_endpoints = self.rpc_endpoints.clone();
+            cheatcodes.root = self.root.clone();
+            cheatcodes.fs_permissions = self.fs_permissions.clone();
+            cheatcodes.multi_fork = self.multi_fork.clone();
+            cheatcodes.sandbox = self.sandbox;
+            cheatcodes.execution_context = self.execution_context;
+            cheatcodes.labeled_account_balance = self.labeled_account_balance;
         }
 
         if self.tracing {
@@ -57,6 +88,9 @@ impl InspectorStackConfig {
         if self.debugger {
             stack.debugger = Some(Debugger::default());
         }
+        if self.coverage {
+            stack.coverage = Some(CoverageCollector::default());
+        }
         stack
     }
 }
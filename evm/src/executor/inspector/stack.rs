@@ -1,5 +1,5 @@
-use super::{Cheatcodes, Debugger, LogCollector, Tracer};
-use crate::{debug::DebugArena, trace::CallTraceArena};
+use super::{CheatcodeFailure, Cheatcodes, CoverageCollector, Debugger, LogCollector, Tracer};
+use crate::{coverage::HitMaps, debug::DebugArena, trace::CallTraceArena};
 use bytes::Bytes;
 use ethers::{
     abi::RawLog,
@@ -25,6 +25,8 @@ pub struct InspectorData {
     pub labels: BTreeMap<Address, String>,
     pub traces: Option<CallTraceArena>,
     pub debug: Option<DebugArena>,
+    pub coverage: Option<HitMaps>,
+    pub cheatcode_failure: Option<CheatcodeFailure>,
     pub cheatcodes: Option<Cheatcodes>,
 }
 
@@ -38,10 +40,12 @@ pub struct InspectorStack {
     pub logs: Option<LogCollector>,
     pub cheatcodes: Option<Cheatcodes>,
     pub debugger: Option<Debugger>,
+    pub coverage: Option<CoverageCollector>,
 }
 
 impl InspectorStack {
-    pub fn collect_inspector_states(self) -> InspectorData {
+    pub fn collect_inspector_states(mut self) -> InspectorData {
+        self.sync_logs();
         InspectorData {
             logs: self.logs.map(|logs| logs.logs).unwrap_or_default(),
             labels: self
@@ -51,9 +55,25 @@ impl InspectorStack {
                 .unwrap_or_default(),
             traces: self.tracer.map(|tracer| tracer.traces),
             debug: self.debugger.map(|debugger| debugger.arena),
+            coverage: self.coverage.map(|coverage| coverage.maps),
+            cheatcode_failure: self
+                .cheatcodes
+                .as_ref()
+                .and_then(|cheatcodes| cheatcodes.failure.clone()),
             cheatcodes: self.cheatcodes,
         }
     }
+
+    /// Removes the logs collected after the snapshot restored with `revertTo` was taken, and
+    /// passes the number of collected logs to the cheatcodes for `snapshot`
+    fn sync_logs(&mut self) {
+        if let (Some(cheatcodes), Some(logs)) = (&mut self.cheatcodes, &mut self.logs) {
+            if let Some(len) = cheatcodes.reverted_logs.take() {
+                logs.logs.truncate(len);
+            }
+            cheatcodes.collected_logs = logs.logs.len();
+        }
+    }
 }
 
 impl<DB> Inspector<DB> for InspectorStack
@@ -68,7 +88,13 @@ where
     ) -> Return {
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.coverage,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes
+            ],
             {
                 let status = inspector.initialize_interp(interpreter, data, is_static);
 
@@ -90,7 +116,13 @@ where
     ) -> Return {
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.coverage,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes
+            ],
             {
                 let status = inspector.step(interpreter, data, is_static);
 
@@ -111,6 +143,7 @@ where
         topics: &[H256],
         data: &Bytes,
     ) {
+        self.sync_logs();
         call_inspectors!(inspector, [&mut self.tracer, &mut self.logs, &mut self.cheatcodes], {
             inspector.log(evm_data, address, topics, data);
         });
@@ -145,6 +178,7 @@ where
         call: &mut CallInputs,
         is_static: bool,
     ) -> (Return, Gas, Bytes) {
+        self.sync_logs();
         call_inspectors!(
             inspector,
             [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
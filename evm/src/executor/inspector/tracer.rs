@@ -1,5 +1,5 @@
 use crate::{
-    executor::inspector::utils::{gas_used, get_create_address},
+    executor::inspector::utils::{gas_refunded, gas_used, get_create_address},
     trace::{
         CallTrace, CallTraceArena, LogCallOrder, RawOrDecodedCall, RawOrDecodedLog,
         RawOrDecodedReturnData,
@@ -11,7 +11,9 @@ use ethers::{
     abi::RawLog,
     types::{Address, H256, U256},
 };
-use revm::{return_ok, CallInputs, CreateInputs, Database, EVMData, Gas, Inspector, Return};
+use revm::{
+    return_ok, CallInputs, CreateInputs, Database, EVMData, Gas, Inspector, Interpreter, Return,
+};
 
 /// An inspector that collects call traces.
 #[derive(Default, Debug)]
@@ -30,6 +32,9 @@ impl Tracer {
         kind: CallKind,
         caller: Address,
     ) {
+        // the caller's last executed instruction is the one that started this call
+        let call_site_pc =
+            self.trace_stack.last().and_then(|idx| self.traces.arena[*idx].trace.last_pc);
         self.trace_stack.push(self.traces.push_trace(
             0,
             CallTrace {
@@ -40,6 +45,7 @@ impl Tracer {
                 value,
                 status: Return::Continue,
                 caller,
+                call_site_pc,
                 ..Default::default()
             },
         ));
@@ -49,6 +55,7 @@ impl Tracer {
         &mut self,
         status: Return,
         cost: u64,
+        refunded: u64,
         output: Vec<u8>,
         address: Option<Address>,
     ) {
@@ -59,6 +66,7 @@ impl Tracer {
         trace.status = status;
         trace.success = success;
         trace.gas_cost = cost;
+        trace.gas_refunded = refunded;
         trace.output = RawOrDecodedReturnData::Raw(output);
 
         if let Some(address) = address {
@@ -89,6 +97,19 @@ where
         (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        _: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        if let Some(id) = self.trace_stack.last() {
+            self.traces.arena[*id].trace.last_pc = Some(interpreter.program_counter());
+        }
+
+        Return::Continue
+    }
+
     fn log(&mut self, _: &mut EVMData<'_, DB>, _: &Address, topics: &[H256], data: &Bytes) {
         let node = &mut self.traces.arena[*self.trace_stack.last().expect("no ongoing trace")];
         node.ordering.push(LogCallOrder::Log(node.logs.len()));
@@ -107,7 +128,8 @@ where
     ) -> (Return, Gas, Bytes) {
         self.fill_trace(
             status,
-            gas_used(data.env.cfg.spec_id, gas.spend(), gas.refunded() as u64),
+            gas_used(data.env.cfg.spec_id, gas.spend(), gas_refunded(&gas)),
+            gas_refunded(&gas),
             retdata.to_vec(),
             None,
         );
@@ -156,7 +178,8 @@ where
         };
         self.fill_trace(
             status,
-            gas_used(data.env.cfg.spec_id, gas.spend(), gas.refunded() as u64),
+            gas_used(data.env.cfg.spec_id, gas.spend(), gas_refunded(&gas)),
+            gas_refunded(&gas),
             code,
             address,
         );
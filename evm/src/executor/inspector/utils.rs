@@ -2,7 +2,7 @@ use ethers::{
     types::Address,
     utils::{get_contract_address, get_create2_address},
 };
-use revm::{CreateInputs, CreateScheme, SpecId};
+use revm::{CreateInputs, CreateScheme, Gas, SpecId};
 
 /// Returns [Return::Continue] on an error, discarding the error.
 ///
@@ -34,3 +34,11 @@ pub fn gas_used(spec: SpecId, spent: u64, refunded: u64) -> u64 {
     let refund_quotient = if SpecId::enabled(spec, SpecId::LONDON) { 5 } else { 2 };
     spent - (refunded).min(spent / refund_quotient)
 }
+
+/// Returns the gas refunded so far, or 0 if the refund is negative
+///
+/// The refund of a call frame can be negative, e.g. when it restores a storage slot that was
+/// cleared by an earlier frame.
+pub fn gas_refunded(gas: &Gas) -> u64 {
+    u64::try_from(gas.refunded()).unwrap_or_default()
+}
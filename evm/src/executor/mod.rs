@@ -2,7 +2,8 @@
 pub mod abi;
 pub use abi::{
     patch_hardhat_console_selector, HardhatConsoleCalls, CHEATCODE_ADDRESS, CONSOLE_ABI,
-    HARDHAT_CONSOLE_ABI, HARDHAT_CONSOLE_ADDRESS,
+    DEFAULT_CREATE2_DEPLOYER, DEFAULT_CREATE2_DEPLOYER_CODE, HARDHAT_CONSOLE_ABI,
+    HARDHAT_CONSOLE_ADDRESS,
 };
 
 /// Executor configuration
@@ -26,12 +27,13 @@ pub use revm::db::DatabaseRef;
 
 pub use revm::Env;
 
-use self::inspector::{InspectorData, InspectorStackConfig};
-use crate::{debug::DebugArena, trace::CallTraceArena, CALLER};
+use self::inspector::{CheatcodeFailure, InspectorData, InspectorStackConfig};
+use crate::{coverage::HitMaps, debug::DebugArena, trace::CallTraceArena, CALLER};
 use bytes::Bytes;
 use ethers::{
     abi::{Abi, Detokenize, RawLog, Tokenize},
-    prelude::{decode_function_data, encode_function_data, Address, U256},
+    prelude::{decode_function_data, encode_function_data, Address, H256, U256},
+    utils::keccak256,
 };
 use eyre::Result;
 use foundry_utils::IntoFunction;
@@ -81,6 +83,8 @@ pub struct DeployResult {
     pub traces: Option<CallTraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
+    /// The coverage info collected during the deployment
+    pub coverage: Option<HitMaps>,
 }
 
 /// The result of a call.
@@ -102,6 +106,8 @@ pub struct CallResult<D: Detokenize> {
     pub traces: Option<CallTraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
+    /// The coverage info collected during the call
+    pub coverage: Option<HitMaps>,
     /// The changeset of the state.
     ///
     /// This is only present if the changed state was not committed to the database (i.e. if you
@@ -130,11 +136,15 @@ pub struct RawCallResult {
     pub traces: Option<CallTraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
+    /// The coverage info collected during the call
+    pub coverage: Option<HitMaps>,
     /// The changeset of the state.
     ///
     /// This is only present if the changed state was not committed to the database (i.e. if you
     /// used `call` and `call_raw` not `call_committing` or `call_raw_committing`).
     pub state_changeset: Option<StateChangeset>,
+    /// The last unexpected cheatcode failure during the call
+    pub cheatcode_failure: Option<CheatcodeFailure>,
 }
 
 impl Default for RawCallResult {
@@ -149,11 +159,14 @@ impl Default for RawCallResult {
             labels: BTreeMap::new(),
             traces: None,
             debug: None,
+            coverage: None,
             state_changeset: None,
+            cheatcode_failure: None,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct Executor<DB: DatabaseRef> {
     // Note: We do not store an EVM here, since we are really
     // only interested in the database. REVM's `EVM` is a thin
@@ -217,6 +230,21 @@ where
         self
     }
 
+    /// Set the code of an account.
+    pub fn set_code(&mut self, address: Address, code: Bytes) -> &mut Self {
+        let mut account = self.db.basic(address);
+        account.code_hash = H256::from_slice(&keccak256(&code));
+        account.code = Some(code);
+
+        self.db.insert_cache(address, account);
+        self
+    }
+
+    /// Returns true if there is code deployed at the address
+    pub fn has_code(&self, address: Address) -> bool {
+        self.db.basic(address).code.map(|code| !code.is_empty()).unwrap_or_default()
+    }
+
     pub fn set_tracing(&mut self, tracing: bool) -> &mut Self {
         self.inspector_config.tracing = tracing;
         self
@@ -227,6 +255,21 @@ where
         self
     }
 
+    pub fn set_coverage(&mut self, coverage: bool) -> &mut Self {
+        self.inspector_config.coverage = coverage;
+        self
+    }
+
+    /// Starts recording the logs of the following committed calls, `getRecordedLogs` returns them
+    ///
+    /// Logs recorded so far are discarded, nothing is recorded if cheatcodes are disabled.
+    pub fn record_logs(&mut self) -> &mut Self {
+        if let Some(cheatcodes) = &mut self.inspector_config.cheatcodes {
+            cheatcodes.recorded_logs = Some(Default::default());
+        }
+        self
+    }
+
     pub fn set_gas_limit(&mut self, gas_limit: U256) -> &mut Self {
         self.gas_limit = gas_limit;
         self
@@ -261,7 +304,9 @@ where
             labels,
             traces,
             debug,
+            coverage,
             state_changeset,
+            cheatcode_failure,
         } = self.call_raw_committing(from, to, calldata, value)?;
         match status {
             return_ok!() => {
@@ -275,12 +320,12 @@ where
                     labels,
                     traces,
                     debug,
+                    coverage,
                     state_changeset,
                 })
             }
             _ => {
-                let reason = foundry_utils::decode_revert(result.as_ref(), abi)
-                    .unwrap_or_else(|_| format!("{:?}", status));
+                let reason = revert_reason(status, &result, abi, cheatcode_failure.as_ref());
                 Err(EvmError::Execution {
                     reverted,
                     reason,
@@ -321,7 +366,7 @@ where
             _ => Bytes::default(),
         };
 
-        let InspectorData { logs, labels, traces, debug, cheatcodes } =
+        let InspectorData { logs, labels, traces, debug, coverage, cheatcode_failure, cheatcodes } =
             inspector.collect_inspector_states();
 
         // Persist the changed block environment
@@ -340,7 +385,9 @@ where
             labels,
             traces,
             debug,
+            coverage,
             state_changeset: None,
+            cheatcode_failure,
         })
     }
 
@@ -368,7 +415,9 @@ where
             labels,
             traces,
             debug,
+            coverage,
             state_changeset,
+            cheatcode_failure,
         } = self.call_raw(from, to, calldata, value)?;
         match status {
             return_ok!() => {
@@ -382,12 +431,12 @@ where
                     labels,
                     traces,
                     debug,
+                    coverage,
                     state_changeset,
                 })
             }
             _ => {
-                let reason = foundry_utils::decode_revert(result.as_ref(), abi)
-                    .unwrap_or_else(|_| format!("{:?}", status));
+                let reason = revert_reason(status, &result, abi, cheatcode_failure.as_ref());
                 Err(EvmError::Execution {
                     reverted,
                     reason,
@@ -428,7 +477,7 @@ where
             _ => Bytes::default(),
         };
 
-        let InspectorData { logs, labels, traces, debug, .. } =
+        let InspectorData { logs, labels, traces, debug, coverage, cheatcode_failure, .. } =
             inspector.collect_inspector_states();
         Ok(RawCallResult {
             status,
@@ -440,7 +489,9 @@ where
             labels,
             traces,
             debug,
+            coverage,
             state_changeset: Some(state_changeset),
+            cheatcode_failure,
         })
     }
 
@@ -458,7 +509,7 @@ where
 
         let mut inspector = self.inspector_config.stack();
         let (status, out, gas, _) = evm.inspect_commit(&mut inspector);
-        let InspectorData { logs, labels, traces, debug, cheatcodes, .. } =
+        let InspectorData { logs, labels, traces, debug, coverage, cheatcode_failure, cheatcodes } =
             inspector.collect_inspector_states();
 
         let result = match out {
@@ -485,8 +536,7 @@ where
                 }
             }
             _ => {
-                let reason = foundry_utils::decode_revert(result.as_ref(), abi)
-                    .unwrap_or_else(|_| format!("{:?}", status));
+                let reason = revert_reason(status, &result, abi, cheatcode_failure.as_ref());
                 return Err(EvmError::Execution {
                     reverted: true,
                     reason,
@@ -507,7 +557,7 @@ where
         // Persist cheatcode state
         self.inspector_config.cheatcodes = cheatcodes;
 
-        Ok(DeployResult { address, gas, logs, traces, debug })
+        Ok(DeployResult { address, gas, logs, traces, debug, coverage })
     }
 
     /// Check if a call to a test contract was successful.
@@ -578,3 +628,21 @@ fn stipend(calldata: &[u8], spec: SpecId) -> u64 {
     let non_zero_data_cost = if SpecId::enabled(spec, SpecId::ISTANBUL) { 16 } else { 68 };
     calldata.iter().fold(21000, |sum, byte| sum + if *byte == 0 { 4 } else { non_zero_data_cost })
 }
+
+/// Decodes the revert reason of a failed call
+///
+/// If the call reverted because a cheatcode failed, the cheatcode calls leading up to the failure
+/// are appended to the reason.
+fn revert_reason(
+    status: Return,
+    result: &[u8],
+    abi: Option<&Abi>,
+    cheatcode_failure: Option<&CheatcodeFailure>,
+) -> String {
+    let reason =
+        foundry_utils::decode_revert(result, abi).unwrap_or_else(|_| format!("{:?}", status));
+    match cheatcode_failure {
+        Some(failure) => failure.report(reason, result),
+        None => reason,
+    }
+}
@@ -3,12 +3,18 @@ use ethers::{
     solc::utils::RuntimeOrHandle,
     types::{Address, Chain, U256},
 };
+use eyre::WrapErr;
 use revm::{BlockEnv, CfgEnv, SpecId, TxEnv};
 use serde::{Deserialize, Deserializer, Serialize};
 
 use foundry_common;
+use foundry_config::{cache::StorageCachingConfig, Config, FsPermissions, RpcEndpoints};
+use std::path::PathBuf;
 
-use super::fork::environment;
+use super::{
+    fork::{environment, JsonBlockCacheDB},
+    Fork,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EvmOpts {
@@ -28,6 +34,11 @@ pub struct EvmOpts {
     /// the initial balance of each deployed test contract
     pub initial_balance: U256,
 
+    /// the balance the addresses created with the `makeAddr` and `makeAccount` cheatcodes are
+    /// funded with
+    #[serde(default)]
+    pub labeled_account_balance: U256,
+
     /// the address which will be executing all tests
     pub sender: Address,
 
@@ -39,13 +50,54 @@ pub struct EvmOpts {
 
     /// The memory limit of the EVM in bytes.
     pub memory_limit: u64,
+
+    /// Named RPC endpoints, resolvable via the `rpcUrl` cheatcode
+    #[serde(default)]
+    pub rpc_endpoints: RpcEndpoints,
+
+    /// The paths the file system cheatcodes may access, relative to the project root
+    #[serde(default)]
+    pub fs_permissions: FsPermissions,
+
+    /// Disables the cheatcodes that run commands, or access the file system, env vars or the
+    /// network, regardless of the other options
+    #[serde(default)]
+    pub sandbox: bool,
+
+    /// Disables network access, forks are only served from the fork cache
+    #[serde(default, deserialize_with = "foundry_config::figment::util::bool_from_str_or_int")]
+    pub offline: bool,
 }
 
 impl EvmOpts {
+    /// Returns the options in sandbox mode, for running the tests of untrusted projects
+    ///
+    /// This disables FFI, forking, the RPC endpoints and the file system permissions, and the
+    /// cheatcodes that access env vars or the network.
+    #[must_use]
+    pub fn sandboxed(mut self) -> Self {
+        self.sandbox = true;
+        self.ffi = false;
+        self.fork_url = None;
+        self.fork_block_number = None;
+        self.rpc_endpoints = Default::default();
+        self.fs_permissions = Default::default();
+        self
+    }
+
     pub async fn evm_env(&self) -> revm::Env {
+        self.try_evm_env().await.unwrap_or_else(|err| panic!("{err:?}"))
+    }
+
+    /// Returns the environment like [EvmOpts::evm_env], or an error if the environment of the fork
+    /// can not be fetched
+    pub async fn try_evm_env(&self) -> eyre::Result<revm::Env> {
         if let Some(ref fork_url) = self.fork_url {
-            let provider =
-                Provider::try_from(fork_url.as_str()).expect("could not instantiated provider");
+            if self.offline {
+                return self.cached_fork_env(fork_url)
+            }
+            let provider = Provider::try_from(fork_url.as_str())
+                .wrap_err("could not instantiated provider")?;
             environment(
                 &provider,
                 self.memory_limit,
@@ -55,9 +107,9 @@ impl EvmOpts {
                 self.sender,
             )
             .await
-            .expect("could not instantiate forked environment")
+            .wrap_err("could not instantiate forked environment")
         } else {
-            revm::Env {
+            Ok(revm::Env {
                 block: BlockEnv {
                     number: self.env.block_number.into(),
                     coinbase: self.env.block_coinbase,
@@ -78,8 +130,104 @@ impl EvmOpts {
                     caller: self.sender,
                     ..Default::default()
                 },
-            }
+            })
+        }
+    }
+
+    /// Returns the environment of the fork stored in the fork cache, used in offline mode
+    fn cached_fork_env(&self, fork_url: &str) -> eyre::Result<revm::Env> {
+        let block = self.fork_block_number.ok_or_else(|| {
+            eyre::eyre!(
+                "Forking {fork_url} offline requires a pinned block (`--fork-block-number`) whose \
+                 state is cached"
+            )
+        })?;
+        let path = Config::foundry_block_cache_file(self.get_offline_chain_id()?, block)
+            .ok_or_else(|| eyre::eyre!("Failed to locate the fork cache directory"))?;
+        let cache = JsonBlockCacheDB::load(&path).map_err(|_| {
+            eyre::eyre!(
+                "The state of {fork_url} at block {block} is not cached at {}, it can not be \
+                 fetched offline",
+                path.display()
+            )
+        })?;
+        let meta = cache.meta().read();
+        Ok(revm::Env {
+            cfg: CfgEnv { memory_limit: self.memory_limit, ..meta.cfg_env.clone() },
+            block: meta.block_env.clone(),
+            tx: TxEnv {
+                caller: self.sender,
+                gas_price: self.env.gas_price.map(U256::from).unwrap_or(meta.block_env.basefee),
+                chain_id: Some(meta.cfg_env.chain_id.as_u64()),
+                gas_limit: meta.block_env.gas_limit.as_u64(),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Returns the [Fork] to use, if any.
+    ///
+    /// storage caching for the [Fork] will be enabled if
+    ///   - `fork_url` is present
+    ///   - `fork_block_number` is present
+    ///   - [StorageCachingConfig] allows the `fork_url` +  chain id pair
+    ///   - storage is allowed (`no_storage_caching = false`)
+    ///
+    /// If all these criteria are met, then storage caching is enabled and storage info will be
+    /// written to [Config::foundry_cache_dir()]/<str(chainid)>/<block>/storage.json
+    ///
+    /// for `mainnet` and `--fork-block-number 14435000` on mac the corresponding storage cache will
+    /// be at `~/.foundry/cache/mainnet/14435000/storage.json`
+    pub fn get_fork(&self, config: &StorageCachingConfig) -> Option<Fork> {
+        self.get_fork_with_chain_id(config, self.get_chain_id())
+    }
+
+    /// Returns the [Fork] to use like [EvmOpts::get_fork], for the already known chain id of the
+    /// fork, which does not block on fetching the chain id
+    pub fn get_fork_with_chain_id(
+        &self,
+        config: &StorageCachingConfig,
+        chain_id: u64,
+    ) -> Option<Fork> {
+        let url = self.fork_url.as_ref()?;
+        Some(Fork {
+            url: url.clone(),
+            pin_block: self.fork_block_number,
+            cache_path: self.get_block_storage_path(config, chain_id),
+            chain_id,
+            offline: self.offline,
+        })
+    }
+
+    /// Returns the path where the cache file should be stored
+    ///
+    /// or `None` if caching should not be enabled
+    ///
+    /// See also [ Config::foundry_block_cache_file()]
+    fn get_block_storage_path(
+        &self,
+        config: &StorageCachingConfig,
+        chain_id: u64,
+    ) -> Option<PathBuf> {
+        let url = self.fork_url.as_ref()?;
+        // cache only if block explicitly pinned
+        let block = self.fork_block_number?;
+        if self.offline {
+            // the cache is the only source of the fork state, it is not used if the chain is
+            // unknown as it could be the cache of another chain
+            let chain_id = self.get_offline_chain_id().ok()?;
+            return Config::foundry_block_cache_file(chain_id, block)
+        }
+        if self.no_storage_caching {
+            // storage caching explicitly opted out of
+            return None
+        }
+
+        if config.enable_for_endpoint(url) && config.enable_for_chain_id(chain_id) {
+            return Config::foundry_block_cache_file(chain_id, block)
         }
+
+        None
     }
 
     /// Returns the gas limit to use
@@ -99,13 +247,36 @@ impl EvmOpts {
         self.get_remote_chain_id().map_or(Chain::Mainnet as u64, |id| id as u64)
     }
 
+    /// Returns the chain id of the fork in offline mode, where it can't be fetched from the RPC
+    ///
+    /// This is the configured `chain_id`, or mainnet if `fork_url` contains "mainnet", otherwise
+    /// the cache of the fork can't be located.
+    pub fn get_offline_chain_id(&self) -> eyre::Result<u64> {
+        if let Some(id) = self.env.chain_id {
+            return Ok(id)
+        }
+        match self.fork_url.as_deref() {
+            Some(url) if url.contains("mainnet") => Ok(Chain::Mainnet as u64),
+            Some(url) => eyre::bail!(
+                "The chain id of {url} can not be fetched offline, set `chain_id` in the config to \
+                 locate its fork cache"
+            ),
+            None => Ok(self.get_chain_id()),
+        }
+    }
+
     /// Returns the chain ID from the RPC, if any.
+    ///
+    /// The RPC is not queried in offline mode.
     pub fn get_remote_chain_id(&self) -> Option<Chain> {
         if let Some(ref url) = self.fork_url {
             if url.contains("mainnet") {
                 tracing::trace!("auto detected mainnet chain from url {url}");
                 return Some(Chain::Mainnet)
             }
+            if self.offline {
+                return None
+            }
             let provider = Provider::try_from(url.as_str())
                 .unwrap_or_else(|_| panic!("Failed to establish provider to {url}"));
 
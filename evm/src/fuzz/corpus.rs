@@ -0,0 +1,155 @@
+use ethers::{types::Bytes, utils::keccak256};
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The maximum number of inputs that are stored in and loaded from a corpus
+pub const MAX_CORPUS_SIZE: usize = 1024;
+
+/// A persistent collection of interesting inputs of a single fuzz test.
+///
+/// Every input is stored as hex encoded calldata in its own file, named after the hash of the
+/// calldata, so the same input is only ever stored once. Once the corpus holds more than its
+/// maximum size, the inputs that were stored first are evicted.
+#[derive(Debug, Clone)]
+pub struct FuzzCorpus {
+    /// The directory the inputs are stored in
+    dir: PathBuf,
+    /// All inputs that were loaded from the corpus directory
+    inputs: Vec<Bytes>,
+    /// The maximum number of inputs stored in the directory
+    max_size: usize,
+}
+
+impl FuzzCorpus {
+    /// Loads the corpus stored in `dir`, files that can't be decoded are skipped.
+    ///
+    /// The directory does not need to exist yet.
+    pub fn load(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        // sorted, so the inputs are drawn from the corpus in the same order on every run
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .collect();
+        paths.sort();
+        let inputs = paths
+            .into_iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|content| content.trim().parse::<Bytes>().ok())
+            .take(MAX_CORPUS_SIZE)
+            .collect();
+        Self { dir, inputs, max_size: MAX_CORPUS_SIZE }
+    }
+
+    /// Sets the maximum number of inputs stored in the corpus directory
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Returns all inputs of the corpus
+    pub fn inputs(&self) -> &[Bytes] {
+        &self.inputs
+    }
+
+    /// Returns true if the corpus contains no inputs
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Adds the `input` to the corpus directory
+    pub fn persist(&self, input: &Bytes) -> io::Result<()> {
+        self.persist_all(std::slice::from_ref(input))
+    }
+
+    /// Adds the `inputs` to the corpus directory, then evicts the oldest other inputs if the
+    /// corpus holds more than its maximum size
+    pub fn persist_all(&self, inputs: &[Bytes]) -> io::Result<()> {
+        if inputs.is_empty() {
+            return Ok(())
+        }
+        let mut persisted = BTreeSet::new();
+        for input in inputs {
+            let path = self.dir.join(hex::encode(keccak256(input.as_ref())));
+            if !path.exists() {
+                fs::create_dir_all(&self.dir)?;
+                fs::write(&path, format!("0x{}", hex::encode(input.as_ref())))?;
+            }
+            persisted.insert(path);
+        }
+        self.evict(&persisted)
+    }
+
+    /// Removes the least recently modified inputs, except those in `keep`, until the corpus
+    /// directory holds at most `max_size` inputs
+    fn evict(&self, keep: &BTreeSet<PathBuf>) -> io::Result<()> {
+        let mut paths: Vec<(SystemTime, PathBuf)> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+                Some((modified.unwrap_or(UNIX_EPOCH), entry.path()))
+            })
+            .collect();
+        if paths.len() <= self.max_size {
+            return Ok(())
+        }
+        let excess = paths.len() - self.max_size;
+        paths.retain(|(_, path)| !keep.contains(path));
+        paths.sort();
+        for (_, path) in paths.into_iter().take(excess) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_persist_and_load_corpus() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("corpus");
+        assert!(FuzzCorpus::load(&dir).is_empty());
+
+        let corpus = FuzzCorpus::load(&dir);
+        let input: Bytes = vec![1u8, 2, 3, 4].into();
+        corpus.persist(&input).unwrap();
+        corpus.persist(&input).unwrap();
+
+        let corpus = FuzzCorpus::load(&dir);
+        assert_eq!(corpus.inputs(), &[input]);
+    }
+
+    #[test]
+    fn loads_corpus_in_stable_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let corpus = FuzzCorpus::load(tmp.path());
+        let mut inputs: Vec<Bytes> = (0u8..8).map(|i| vec![i].into()).collect();
+        inputs.iter().for_each(|input| corpus.persist(input).unwrap());
+
+        inputs.sort_by_key(|input| hex::encode(keccak256(input.as_ref())));
+        assert_eq!(FuzzCorpus::load(tmp.path()).inputs(), inputs.as_slice());
+    }
+
+    #[test]
+    fn evicts_oldest_inputs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let corpus = FuzzCorpus::load(tmp.path()).with_max_size(4);
+        let old: Vec<Bytes> = (0u8..4).map(|i| vec![i].into()).collect();
+        corpus.persist_all(&old).unwrap();
+        assert_eq!(FuzzCorpus::load(tmp.path()).inputs().len(), 4);
+
+        let new: Vec<Bytes> = (4u8..6).map(|i| vec![i].into()).collect();
+        corpus.persist_all(&new).unwrap();
+        let inputs = FuzzCorpus::load(tmp.path()).inputs().to_vec();
+        assert_eq!(inputs.len(), 4);
+        assert!(new.iter().all(|input| inputs.contains(input)));
+    }
+}
@@ -0,0 +1,406 @@
+use super::{
+    strategies::{build_initial_state, collect_state_from_call},
+    timeout_reason, weighted_calldata, BaseCounterExample, CounterExample,
+    DEFAULT_DICTIONARY_WEIGHT,
+};
+use crate::{
+    executor::{Executor, RawCallResult},
+    trace::CallTraceArena,
+};
+use ethers::{
+    abi::{Abi, Function, RawLog, StateMutability},
+    types::{Address, Bytes},
+};
+use proptest::{
+    strategy::{Strategy, Union},
+    test_runner::{TestCaseError, TestError, TestRunner},
+};
+use revm::db::DatabaseRef;
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+/// A contract that is called by the invariant fuzzer
+#[derive(Debug, Clone)]
+pub struct TargetContract {
+    /// The address the contract is deployed at
+    pub address: Address,
+    /// The name of the contract
+    pub name: String,
+    /// The functions of the contract that may be called
+    pub functions: Vec<Function>,
+}
+
+impl TargetContract {
+    /// Creates a new target, all functions of `abi` that are neither `view` nor `pure` are called
+    pub fn new(address: Address, name: impl Into<String>, abi: &Abi) -> Self {
+        let functions = abi
+            .functions()
+            .filter(|func| {
+                !matches!(func.state_mutability, StateMutability::Pure | StateMutability::View)
+            })
+            .cloned()
+            .collect();
+        Self { address, name: name.into(), functions }
+    }
+
+    /// Returns the function the calldata calls
+    pub fn function(&self, calldata: &Bytes) -> Option<&Function> {
+        self.functions.iter().find(|func| calldata.as_ref().starts_with(&func.short_signature()))
+    }
+}
+
+/// A single call of the call sequence of an invariant test run
+#[derive(Debug, Clone)]
+pub struct InvariantCall {
+    /// The account making the call
+    pub sender: Address,
+    /// The called contract
+    pub target: Address,
+    /// The calldata of the call
+    pub calldata: Bytes,
+}
+
+impl InvariantCall {
+    /// Returns the name of the called function in the form `<contract>.<function>`
+    pub fn name(&self, targets: &[TargetContract]) -> String {
+        let target = targets.iter().find(|target| target.address == self.target);
+        let func = target.and_then(|target| target.function(&self.calldata));
+        match (target, func) {
+            (Some(target), Some(func)) => format!("{}.{}", target.name, func.name),
+            (Some(target), None) => format!("{}.<unknown>", target.name),
+            _ => format!("{:?}", self.target),
+        }
+    }
+}
+
+/// Wrapper around an [`Executor`] which provides support for invariant tests.
+///
+/// Every run executes a random sequence of calls against the target contracts and checks the
+/// invariant after each call. Sequences that break the invariant are shrunk by removing calls
+/// that are not needed to reproduce the failure.
+///
+/// The logs of the calls of a sequence are recorded, the invariant and the `afterInvariant`
+/// hook, which is called once the sequence completed, can check properties of them with
+/// `getRecordedLogs`.
+pub struct InvariantExecutor<'a, DB: DatabaseRef> {
+    /// The VM, every run starts from its state
+    executor: &'a Executor<DB>,
+    /// The fuzzer, its number of cases is the number of runs
+    runner: TestRunner,
+    /// The account that calls the target contracts and the invariant
+    sender: Address,
+    /// The maximum number of calls in a single run
+    depth: u32,
+    /// Whether a reverting call fails the invariant test
+    fail_on_revert: bool,
+    /// The maximum wall-clock time of the invariant test
+    timeout: Option<Duration>,
+    /// The percentage of calls whose arguments are drawn from the dictionary
+    dictionary_weight: u32,
+    /// The function of the test contract that is called after every call sequence
+    after_invariant: Option<Function>,
+}
+
+impl<'a, DB> InvariantExecutor<'a, DB>
+where
+    DB: DatabaseRef + Clone,
+{
+    /// Instantiates an invariant executor given a testrunner
+    pub fn new(
+        executor: &'a Executor<DB>,
+        runner: TestRunner,
+        sender: Address,
+        depth: u32,
+        fail_on_revert: bool,
+    ) -> Self {
+        Self {
+            executor,
+            runner,
+            sender,
+            depth,
+            fail_on_revert,
+            timeout: None,
+            dictionary_weight: DEFAULT_DICTIONARY_WEIGHT,
+            after_invariant: None,
+        }
+    }
+
+    /// Sets the time after which no more runs are started and the test fails
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the percentage of calls whose arguments are drawn from the dictionary
+    pub fn with_dictionary_weight(mut self, weight: u32) -> Self {
+        self.dictionary_weight = weight;
+        self
+    }
+
+    /// Sets the function of the test contract that is called after every call sequence, e.g. to
+    /// check properties of all logs of the sequence
+    pub fn with_after_invariant(mut self, after_invariant: Option<Function>) -> Self {
+        self.after_invariant = after_invariant;
+        self
+    }
+
+    /// Fuzzes the `invariant` of the test contract at `address` with call sequences against the
+    /// `targets`
+    pub fn invariant_fuzz(
+        &self,
+        invariant: &Function,
+        address: Address,
+        targets: &[TargetContract],
+        errors: Option<&Abi>,
+    ) -> InvariantTestResult {
+        let invariant_calldata: Bytes =
+            invariant.encode_input(&[]).expect("invariants do not have inputs").into();
+        let after_invariant_calldata: Option<Bytes> = self.after_invariant.as_ref().map(|func| {
+            func.encode_input(&[]).expect("afterInvariant does not have inputs").into()
+        });
+
+        // The invariant has to hold before any call is made
+        let (success, call) = self.call_invariant(self.executor, address, &invariant_calldata);
+        if !success {
+            return InvariantTestResult::failed(decode_reason(&call, errors), call)
+        }
+
+        let functions: Vec<(Address, Function)> = targets
+            .iter()
+            .flat_map(|target| target.functions.iter().map(|func| (target.address, func.clone())))
+            .collect();
+        if functions.is_empty() {
+            return InvariantTestResult::failed(
+                Some("No target contracts with state changing functions found".to_string()),
+                RawCallResult::default(),
+            )
+        }
+
+        // The dictionary of [fuzz_calldata_from_state], it grows with the logs of every call
+        let state = build_initial_state(&self.executor.db);
+
+        let sender = self.sender;
+        let dictionary = state.clone();
+        let dictionary_weight = self.dictionary_weight;
+        let call_strat = proptest::sample::select(functions).prop_flat_map(move |(target, func)| {
+            Union::new_weighted(weighted_calldata(&func, &dictionary, dictionary_weight))
+                .prop_map(move |calldata| InvariantCall { sender, target, calldata })
+        });
+        let strat = proptest::collection::vec(call_strat, 1..=self.depth.max(1) as usize);
+
+        let runs = RefCell::new(0usize);
+        let calls = RefCell::new(0usize);
+        let reverts = RefCell::new(0usize);
+        let reverted_calls: RefCell<BTreeMap<String, usize>> = RefCell::new(BTreeMap::new());
+
+        // Stores the shortest call sequence that failed so far, and the failing call
+        let failure: RefCell<(Vec<InvariantCall>, RawCallResult)> =
+            RefCell::new(Default::default());
+
+        // Once the deadline passed every run fails right away
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let timed_out = Cell::new(false);
+
+        tracing::debug!(invariant = ?invariant.name, "fuzzing invariant");
+        let run_result = self.runner.clone().run(&strat, |sequence| {
+            // a failure that was already found is still shrunk and reported
+            let failed = !failure.borrow().0.is_empty();
+            if timed_out.get() ||
+                (!failed && deadline.map_or(false, |deadline| Instant::now() >= deadline))
+            {
+                timed_out.set(true);
+                return Err(TestCaseError::fail("timed out"))
+            }
+            *runs.borrow_mut() += 1;
+            let mut executor = self.executor.clone();
+            executor.record_logs();
+            for (idx, call) in sequence.iter().enumerate() {
+                let result = executor
+                    .call_raw_committing(
+                        call.sender,
+                        call.target,
+                        call.calldata.0.clone(),
+                        0.into(),
+                    )
+                    .expect("could not make raw evm call");
+                *calls.borrow_mut() += 1;
+                collect_state_from_call(
+                    &result.logs,
+                    &result.state_changeset.clone().unwrap_or_default(),
+                    state.clone(),
+                );
+
+                if result.reverted {
+                    *reverts.borrow_mut() += 1;
+                    if self.fail_on_revert {
+                        // Report the reverting call itself, the invariant is not checked
+                        let reason = match decode_reason(&result, errors) {
+                            Some(reason) => format!("{} reverted: {reason}", call.name(targets)),
+                            None => format!("{} reverted", call.name(targets)),
+                        };
+                        *failure.borrow_mut() = (sequence[..=idx].to_vec(), result);
+                        return Err(TestCaseError::fail(reason))
+                    }
+                    // The reverted call did not change any state, the sequence continues
+                    *reverted_calls.borrow_mut().entry(call.name(targets)).or_default() += 1;
+                }
+
+                let (success, call) =
+                    self.call_invariant(&executor, address, &invariant_calldata);
+                if !success {
+                    let reason = decode_reason(&call, errors).unwrap_or_default();
+                    *failure.borrow_mut() = (sequence[..=idx].to_vec(), call);
+                    return Err(TestCaseError::fail(reason))
+                }
+            }
+
+            if let Some(calldata) = &after_invariant_calldata {
+                let (success, call) = self.call_invariant(&executor, address, calldata);
+                if !success {
+                    let reason = match decode_reason(&call, errors) {
+                        Some(reason) => format!("afterInvariant failed: {reason}"),
+                        None => "afterInvariant failed".to_string(),
+                    };
+                    *failure.borrow_mut() = (sequence.clone(), call);
+                    return Err(TestCaseError::fail(reason))
+                }
+            }
+            Ok(())
+        });
+
+        let (sequence, call) = failure.into_inner();
+        let mut result = InvariantTestResult {
+            success: run_result.is_ok(),
+            reason: None,
+            counterexample: None,
+            runs: runs.into_inner(),
+            calls: calls.into_inner(),
+            reverts: reverts.into_inner(),
+            reverted_calls: reverted_calls.into_inner(),
+            logs: call.logs,
+            traces: call.traces,
+            labeled_addresses: call.labels,
+        };
+
+        match run_result {
+            _ if timed_out.get() => {
+                result.success = false;
+                result.reason =
+                    Some(timeout_reason(self.timeout.unwrap_or_default(), result.runs));
+            }
+            Err(TestError::Abort(reason)) => {
+                result.reason = Some(reason.to_string());
+            }
+            Err(TestError::Fail(reason, _)) => {
+                let reason = reason.to_string();
+                result.reason = if reason.is_empty() { None } else { Some(reason) };
+                result.counterexample = Some(CounterExample::Sequence(
+                    sequence.into_iter().map(|call| counterexample(call, targets)).collect(),
+                ));
+            }
+            _ => (),
+        }
+
+        result
+    }
+
+    /// Calls the invariant on the current state of `executor` and returns whether it holds
+    fn call_invariant(
+        &self,
+        executor: &Executor<DB>,
+        address: Address,
+        calldata: &Bytes,
+    ) -> (bool, RawCallResult) {
+        let call = executor
+            .call_raw(self.sender, address, calldata.0.clone(), 0.into())
+            .expect("could not make raw evm call");
+        let success = executor.is_success(
+            address,
+            call.reverted,
+            call.state_changeset.clone().expect("we should have a state changeset"),
+            false,
+        );
+        (success, call)
+    }
+}
+
+/// Decodes the revert reason of a call, `None` if there is none
+fn decode_reason(call: &RawCallResult, errors: Option<&Abi>) -> Option<String> {
+    foundry_utils::decode_revert(call.result.as_ref(), errors)
+        .ok()
+        .filter(|reason| !reason.is_empty())
+}
+
+/// Converts a call of a failing sequence into a counter example, decoding its arguments
+fn counterexample(call: InvariantCall, targets: &[TargetContract]) -> BaseCounterExample {
+    let target = targets.iter().find(|target| target.address == call.target);
+    let func = target.and_then(|target| target.function(&call.calldata));
+    let args = func
+        .and_then(|func| func.decode_input(&call.calldata.as_ref()[4..]).ok())
+        .unwrap_or_default();
+
+    BaseCounterExample {
+        sender: Some(call.sender),
+        addr: Some(call.target),
+        contract_name: target.map(|target| target.name.clone()),
+        calldata: call.calldata,
+        args,
+    }
+}
+
+/// The outcome of an invariant test
+#[derive(Debug)]
+pub struct InvariantTestResult {
+    /// Whether the invariant held in all runs
+    pub success: bool,
+
+    /// The revert reason of the call that broke the invariant, if any
+    pub reason: Option<String>,
+
+    /// The shrunk call sequence that broke the invariant
+    pub counterexample: Option<CounterExample>,
+
+    /// The number of call sequences that were executed
+    pub runs: usize,
+
+    /// The number of calls made to the target contracts
+    pub calls: usize,
+
+    /// The number of calls to the target contracts that reverted
+    pub reverts: usize,
+
+    /// The number of reverted calls per function, `<contract>.<function> -> reverts`
+    ///
+    /// Only recorded if reverting calls do not fail the test.
+    pub reverted_calls: BTreeMap<String, usize>,
+
+    /// Any captured & parsed as strings logs of the call that broke the invariant
+    pub logs: Vec<RawLog>,
+
+    /// Traces of the call that broke the invariant
+    pub traces: Option<CallTraceArena>,
+
+    /// Labeled addresses
+    pub labeled_addresses: BTreeMap<Address, String>,
+}
+
+impl InvariantTestResult {
+    /// A result for an invariant test that failed without executing any call sequence
+    fn failed(reason: Option<String>, call: RawCallResult) -> Self {
+        Self {
+            success: false,
+            reason,
+            counterexample: None,
+            runs: 0,
+            calls: 0,
+            reverts: 0,
+            reverted_calls: BTreeMap::new(),
+            logs: call.logs,
+            traces: call.traces,
+            labeled_addresses: call.labels,
+        }
+    }
+}
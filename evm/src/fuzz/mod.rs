@@ -1,8 +1,22 @@
 mod strategies;
+pub use strategies::fuzz_param;
+
+mod corpus;
+pub use corpus::FuzzCorpus;
+
+mod stats;
+pub use stats::{Distribution, FuzzInputStats, ParamStats};
+
+mod invariant;
+pub use invariant::{InvariantCall, InvariantExecutor, InvariantTestResult, TargetContract};
+
+mod shrink;
+pub use shrink::shrink_args;
 
 pub use proptest::test_runner::{Config as FuzzConfig, Reason};
 
 use crate::{
+    coverage::{merge_coverage, HitMaps},
     executor::{Executor, RawCallResult},
     trace::CallTraceArena,
 };
@@ -10,10 +24,18 @@ use ethers::{
     abi::{Abi, Function, RawLog, Token},
     types::{Address, Bytes},
 };
-use proptest::test_runner::{TestCaseError, TestError, TestRunner};
+use proptest::{
+    strategy::Strategy,
+    test_runner::{RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner},
+};
 use revm::db::DatabaseRef;
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::BTreeMap, fmt};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant, SystemTime},
+};
 use strategies::{
     build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_state,
     EvmFuzzState,
@@ -22,6 +44,9 @@ use strategies::{
 /// Magic return code for the `assume` cheatcode
 pub const ASSUME_MAGIC_RETURN_CODE: &[u8] = b"FOUNDRY::ASSUME";
 
+/// The default percentage of fuzz inputs whose values are drawn from the dictionary
+pub const DEFAULT_DICTIONARY_WEIGHT: u32 = 40;
+
 /// Wrapper around an [`Executor`] which provides fuzzing support using [`proptest`](https://docs.rs/proptest/1.0.0/proptest/).
 ///
 /// After instantiation, calling `fuzz` will proceed to hammer the deployed smart contract with
@@ -34,6 +59,12 @@ pub struct FuzzedExecutor<'a, DB: DatabaseRef> {
     runner: TestRunner,
     /// The account that calls tests
     sender: Address,
+    /// Interesting inputs of previous runs, new interesting inputs are added to it
+    corpus: Option<FuzzCorpus>,
+    /// The maximum wall-clock time of the fuzz test
+    timeout: Option<Duration>,
+    /// The percentage of inputs whose values are drawn from the dictionary
+    dictionary_weight: u32,
 }
 
 impl<'a, DB> FuzzedExecutor<'a, DB>
@@ -42,7 +73,33 @@ where
 {
     /// Instantiates a fuzzed executor given a testrunner
     pub fn new(executor: &'a Executor<DB>, runner: TestRunner, sender: Address) -> Self {
-        Self { executor, runner, sender }
+        Self {
+            executor,
+            runner,
+            sender,
+            corpus: None,
+            timeout: None,
+            dictionary_weight: DEFAULT_DICTIONARY_WEIGHT,
+        }
+    }
+
+    /// Sets the corpus the fuzzer draws inputs from and persists interesting inputs to
+    pub fn with_corpus(mut self, corpus: FuzzCorpus) -> Self {
+        self.corpus = Some(corpus);
+        self
+    }
+
+    /// Sets the time after which no more cases are run and the test fails
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the percentage of inputs whose values are drawn from the dictionary, see
+    /// [build_initial_state]
+    pub fn with_dictionary_weight(mut self, weight: u32) -> Self {
+        self.dictionary_weight = weight;
+        self
     }
 
     /// Fuzzes the provided function, assuming it is available at the contract at `address`
@@ -63,31 +120,61 @@ where
         // Stores the result and calldata of the last failed call, if any.
         let counterexample: RefCell<(Bytes, RawCallResult)> = RefCell::new(Default::default());
 
-        // Stores fuzz state for use with [fuzz_calldata_from_state]
+        // The dictionary of [fuzz_calldata_from_state], it grows with every call
         let state: EvmFuzzState = build_initial_state(&self.executor.db);
 
-        // TODO: We should have a `FuzzerOpts` struct where we can configure the fuzzer. When we
-        // have that, we should add a way to configure strategy weights
-        let strat = proptest::strategy::Union::new_weighted(vec![
-            (60, fuzz_calldata(func.clone())),
-            (40, fuzz_calldata_from_state(func.clone(), state.clone())),
-        ]);
+        // Stores inputs that reverted or discovered new state, these are added to the corpus
+        let interesting: RefCell<Vec<Bytes>> = RefCell::new(Default::default());
+
+        // Stores the coverage of all fuzz runs
+        let coverage: RefCell<Option<HitMaps>> = RefCell::new(None);
+
+        let mut strats = weighted_calldata(func, &state, self.dictionary_weight);
+        if let Some(corpus) = self.corpus.as_ref().filter(|corpus| !corpus.is_empty()) {
+            strats.push((20, proptest::sample::select(corpus.inputs().to_vec()).boxed()));
+        }
+        let strat = proptest::strategy::Union::new_weighted(strats);
+
+        // Counts the runs rejected by `assume` and the runs that hit the assertions of the test
+        let rejects = Cell::new(0usize);
+        let assertion_hits = Cell::new(0usize);
+
+        // Once the deadline passed every case fails right away
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let timed_out = Cell::new(false);
+
         tracing::debug!(func = ?func.name, should_fail, "fuzzing");
         let run_result = self.runner.clone().run(&strat, |calldata| {
-            let call = self
+            // a failure that was already found is still shrunk and reported
+            let failed = !counterexample.borrow().0.is_empty();
+            if timed_out.get() ||
+                (!failed && deadline.map_or(false, |deadline| Instant::now() >= deadline))
+            {
+                timed_out.set(true);
+                return Err(TestCaseError::fail("timed out"))
+            }
+
+            let mut call = self
                 .executor
                 .call_raw(self.sender, address, calldata.0.clone(), 0.into())
                 .expect("could not make raw evm call");
+            merge_coverage(&mut coverage.borrow_mut(), call.coverage.take());
             let state_changeset =
                 call.state_changeset.as_ref().expect("we should have a state changeset");
 
             // Build fuzzer state
+            let known_state = state.borrow().len();
             collect_state_from_call(&call.logs, state_changeset, state.clone());
+            if self.corpus.is_some() && (call.reverted || state.borrow().len() > known_state) {
+                interesting.borrow_mut().push(calldata.clone());
+            }
 
             // When assume cheat code is triggered return a special string "FOUNDRY::ASSUME"
             if call.result.as_ref() == ASSUME_MAGIC_RETURN_CODE {
+                rejects.set(rejects.get() + 1);
                 return Err(TestCaseError::reject("ASSUME: Too many rejects"))
             }
+            assertion_hits.set(assertion_hits.get() + 1);
 
             let success = self.executor.is_success(
                 address,
@@ -121,47 +208,224 @@ where
             }
         });
 
-        let (calldata, call) = counterexample.into_inner();
+        if let Some(ref corpus) = self.corpus {
+            if let Err(err) = corpus.persist_all(&interesting.into_inner()) {
+                tracing::warn!(?err, "failed to persist fuzz inputs");
+            }
+        }
+
+        if timed_out.get() {
+            let cases = cases.into_inner();
+            let reason = timeout_reason(self.timeout.unwrap_or_default(), cases.len());
+            let (_, call) = counterexample.into_inner();
+            return FuzzTestResult {
+                cases: FuzzedCases::new(cases)
+                    .with_counts(rejects.get(), assertion_hits.get()),
+                success: false,
+                reason: Some(reason),
+                counterexample: None,
+                logs: call.logs,
+                traces: call.traces,
+                labeled_addresses: call.labels,
+                coverage: coverage.into_inner(),
+            }
+        }
+
+        let (mut calldata, mut call) = counterexample.into_inner();
+        let mut reason = None;
+        if let Err(TestError::Fail(..)) = run_result {
+            (calldata, call) = self.shrink(func, address, should_fail, calldata, call);
+            reason = foundry_utils::decode_revert(call.result.as_ref(), errors)
+                .ok()
+                .filter(|reason| !reason.is_empty());
+        }
+
         let mut result = FuzzTestResult {
-            cases: FuzzedCases::new(cases.into_inner()),
+            cases: FuzzedCases::new(cases.into_inner())
+                .with_counts(rejects.get(), assertion_hits.get()),
             success: run_result.is_ok(),
             reason: None,
             counterexample: None,
             logs: call.logs,
             traces: call.traces,
             labeled_addresses: call.labels,
+            coverage: coverage.into_inner(),
         };
 
         match run_result {
             Err(TestError::Abort(reason)) => {
                 result.reason = Some(reason.to_string());
             }
-            Err(TestError::Fail(reason, _)) => {
-                let reason = reason.to_string();
-                result.reason = if reason.is_empty() { None } else { Some(reason) };
+            Err(TestError::Fail(..)) => {
+                result.reason = reason;
 
                 let args = func
                     .decode_input(&calldata.as_ref()[4..])
                     .expect("could not decode fuzzer inputs");
-                result.counterexample = Some(CounterExample { calldata, args });
+                result.counterexample =
+                    Some(CounterExample::Single(BaseCounterExample::new(calldata, args)));
             }
             _ => (),
         }
 
         result
     }
+
+    /// Shrinks the arguments of the failing `calldata` to a minimal input that still fails the
+    /// test, see [`shrink_args`]
+    ///
+    /// Returns the calldata and result of the minimal failing call.
+    fn shrink(
+        &self,
+        func: &Function,
+        address: Address,
+        should_fail: bool,
+        calldata: Bytes,
+        call: RawCallResult,
+    ) -> (Bytes, RawCallResult) {
+        let args = match func.decode_input(&calldata.as_ref()[4..]) {
+            Ok(args) => args,
+            Err(_) => return (calldata, call),
+        };
+
+        let failing = RefCell::new(None);
+        let args = shrink_args(args, |args| {
+            let calldata: Bytes = match func.encode_input(args) {
+                Ok(calldata) => calldata.into(),
+                Err(_) => return false,
+            };
+            let call = self
+                .executor
+                .call_raw(self.sender, address, calldata.0.clone(), 0.into())
+                .expect("could not make raw evm call");
+            if call.result.as_ref() == ASSUME_MAGIC_RETURN_CODE {
+                return false
+            }
+            let success = self.executor.is_success(
+                address,
+                call.reverted,
+                call.state_changeset.clone().expect("we should have a state changeset"),
+                should_fail,
+            );
+            if !success {
+                *failing.borrow_mut() = Some((args.to_vec(), calldata, call));
+            }
+            !success
+        });
+
+        // The last failing candidate is the shrunk input, unless no candidate failed at all
+        match failing.into_inner() {
+            Some((failing_args, shrunk, shrunk_call)) if failing_args == args => {
+                (shrunk, shrunk_call)
+            }
+            _ => (calldata, call),
+        }
+    }
+}
+
+/// Returns the strategies for the calldata of `func`, `dictionary_weight` percent of which draw
+/// their values from the dictionary
+///
+/// Strategies with a weight of zero are left out, [proptest::strategy::Union] panics on them.
+fn weighted_calldata(
+    func: &Function,
+    state: &EvmFuzzState,
+    dictionary_weight: u32,
+) -> Vec<(u32, proptest::strategy::BoxedStrategy<Bytes>)> {
+    let dictionary_weight = dictionary_weight.min(100);
+    let mut strats = Vec::with_capacity(3);
+    if dictionary_weight < 100 {
+        strats.push((100 - dictionary_weight, fuzz_calldata(func.clone())));
+    }
+    if dictionary_weight > 0 {
+        strats.push((dictionary_weight, fuzz_calldata_from_state(func.clone(), state.clone())));
+    }
+    strats
+}
+
+/// Returns the failure reason of a fuzz or invariant test that exceeded its timeout
+fn timeout_reason(timeout: Duration, runs: usize) -> String {
+    format!("Timed out after {timeout:?}, {runs} runs completed")
+}
+
+/// Returns a fuzzer whose inputs are generated from `seed`, the same seed always generates the
+/// same inputs
+pub fn seeded_runner(config: FuzzConfig, seed: u64) -> TestRunner {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&seed.to_be_bytes());
+    TestRunner::new_with_rng(config, TestRng::from_seed(RngAlgorithm::ChaCha, &bytes))
+}
+
+/// Returns a new seed for a fuzz run that was not given one
+pub fn random_seed() -> u64 {
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    now.as_nanos() as u64
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum CounterExample {
+    /// Call used as a counter example for fuzz tests
+    Single(BaseCounterExample),
+    /// Sequence of calls used as a counter example for invariant tests
+    Sequence(Vec<BaseCounterExample>),
+}
+
+impl CounterExample {
+    /// Returns the calldata of a fuzz test counter example, `None` for call sequences
+    pub fn calldata(&self) -> Option<&Bytes> {
+        match self {
+            CounterExample::Single(example) => Some(&example.calldata),
+            CounterExample::Sequence(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for CounterExample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CounterExample::Single(example) => example.fmt(f),
+            CounterExample::Sequence(sequence) => {
+                for call in sequence {
+                    writeln!(f, "{}", call)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
+/// A single call of a counter example
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct CounterExample {
+pub struct BaseCounterExample {
+    /// The sender of the call, only set for calls of a sequence
+    pub sender: Option<Address>,
+    /// The called contract, only set for calls of a sequence
+    pub addr: Option<Address>,
+    /// The name of the called contract, if known
+    pub contract_name: Option<String>,
     pub calldata: Bytes,
 
     #[serde(skip)]
     pub args: Vec<Token>,
 }
 
-impl fmt::Display for CounterExample {
+impl BaseCounterExample {
+    pub fn new(calldata: Bytes, args: Vec<Token>) -> Self {
+        Self { sender: None, addr: None, contract_name: None, calldata, args }
+    }
+}
+
+impl fmt::Display for BaseCounterExample {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(sender) = self.sender {
+            write!(f, "sender={:?} ", sender)?;
+        }
+        if let Some(addr) = self.addr {
+            match self.contract_name {
+                Some(ref name) => write!(f, "addr=[{}]{:?} ", name, addr)?,
+                None => write!(f, "addr={:?} ", addr)?,
+            }
+        }
         let args = foundry_utils::format_tokens(&self.args).collect::<Vec<_>>().join(", ");
         write!(f, "calldata=0x{}, args=[{}]", hex::encode(&self.calldata), args)
     }
@@ -194,19 +458,49 @@ pub struct FuzzTestResult {
 
     /// Labeled addresses
     pub labeled_addresses: BTreeMap<Address, String>,
+
+    /// The coverage of all fuzz runs, if coverage is collected
+    pub coverage: Option<HitMaps>,
 }
 
 /// Container type for all successful test cases
+///
+/// It is serialized as the list of cases, the counts of rejected runs and assertion hits are only
+/// kept in memory.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct FuzzedCases {
     cases: Vec<FuzzCase>,
+    /// The number of runs that were rejected by `assume`
+    #[serde(skip)]
+    rejects: usize,
+    /// The number of runs that passed all `assume` calls, so the assertions of the test were hit
+    #[serde(skip)]
+    assertion_hits: usize,
 }
 
 impl FuzzedCases {
     pub fn new(mut cases: Vec<FuzzCase>) -> Self {
         cases.sort_by_key(|c| c.gas);
-        Self { cases }
+        Self { cases, rejects: 0, assertion_hits: 0 }
+    }
+
+    /// Sets the number of rejected runs and of runs that hit the assertions of the test
+    #[must_use]
+    pub fn with_counts(mut self, rejects: usize, assertion_hits: usize) -> Self {
+        self.rejects = rejects;
+        self.assertion_hits = assertion_hits;
+        self
+    }
+
+    /// Returns the number of runs that were rejected by `assume`
+    pub fn rejects(&self) -> usize {
+        self.rejects
+    }
+
+    /// Returns the number of runs that passed all `assume` calls
+    pub fn assertion_hits(&self) -> usize {
+        self.assertion_hits
     }
 
     pub fn cases(&self) -> &[FuzzCase] {
@@ -273,3 +567,49 @@ pub struct FuzzCase {
     /// The initial gas stipend for the transaction
     pub stipend: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{Param, ParamType, StateMutability};
+    use proptest::strategy::{Union, ValueTree};
+
+    #[test]
+    fn skips_zero_dictionary_weights() {
+        #[allow(deprecated)]
+        let func = Function {
+            name: "testFuzz".to_string(),
+            inputs: vec![Param {
+                name: "x".to_string(),
+                kind: ParamType::Uint(256),
+                internal_type: None,
+            }],
+            outputs: vec![],
+            constant: None,
+            state_mutability: StateMutability::NonPayable,
+        };
+        let state = EvmFuzzState::default();
+
+        for (weight, arms) in [(0, 1), (40, 2), (100, 1), (200, 1)] {
+            let strats = weighted_calldata(&func, &state, weight);
+            assert_eq!(strats.len(), arms);
+            assert!(strats.iter().all(|(weight, _)| *weight > 0));
+
+            let mut runner = TestRunner::default();
+            let calldata = Union::new_weighted(strats).new_tree(&mut runner).unwrap().current();
+            assert_eq!(calldata.len(), 36);
+        }
+    }
+
+    #[test]
+    fn serializes_fuzzed_cases_as_list() {
+        let case = FuzzCase { calldata: Bytes::from(vec![1]), gas: 2, stipend: 3 };
+        let cases = FuzzedCases::new(vec![case]).with_counts(4, 5);
+        let json = serde_json::to_value(&cases).unwrap();
+        assert_eq!(json, serde_json::json!([{ "calldata": "0x01", "gas": 2, "stipend": 3 }]));
+
+        let cases: FuzzedCases = serde_json::from_value(json).unwrap();
+        assert_eq!(cases.cases().len(), 1);
+        assert_eq!((cases.rejects(), cases.assertion_hits()), (0, 0));
+    }
+}
@@ -0,0 +1,99 @@
+use ethers::{
+    abi::Token,
+    types::{Address, I256, U256},
+};
+
+/// Minimizes the arguments of a failing fuzz case.
+///
+/// Every argument is replaced, one after another, with a simpler value as long as `fails` still
+/// returns `true` for the resulting arguments: numbers are moved towards zero, booleans are set to
+/// `false`, addresses to the zero address and dynamic values are emptied.
+pub fn shrink_args(mut args: Vec<Token>, mut fails: impl FnMut(&[Token]) -> bool) -> Vec<Token> {
+    for idx in 0..args.len() {
+        let mut fails_with = |token: Token| {
+            let mut candidate = args.clone();
+            candidate[idx] = token;
+            fails(&candidate)
+        };
+
+        let shrunk = match args[idx].clone() {
+            Token::Uint(value) => Token::Uint(shrink_uint(value, |v| fails_with(Token::Uint(v)))),
+            Token::Int(value) => {
+                let value = I256::from_raw(value);
+                let negative = value.is_negative();
+                let to_token = |magnitude: U256| {
+                    let value = I256::from_raw(magnitude);
+                    Token::Int(if negative { value.overflowing_neg().0 } else { value }.into_raw())
+                };
+                to_token(shrink_uint(value.unsigned_abs(), |v| fails_with(to_token(v))))
+            }
+            token => match simplest(&token) {
+                Some(simple) if fails_with(simple.clone()) => simple,
+                _ => token,
+            },
+        };
+        args[idx] = shrunk;
+    }
+    args
+}
+
+/// Returns the smallest value in `0..=value` found via binary search for which `fails` returns
+/// `true`, assuming it does for `value`
+fn shrink_uint(value: U256, mut fails: impl FnMut(U256) -> bool) -> U256 {
+    if value.is_zero() || fails(U256::zero()) {
+        return U256::zero()
+    }
+    let (mut passing, mut failing) = (U256::zero(), value);
+    while failing - passing > U256::one() {
+        let mid = passing + (failing - passing) / 2;
+        if fails(mid) {
+            failing = mid;
+        } else {
+            passing = mid;
+        }
+    }
+    failing
+}
+
+/// Returns the simplest value of the token's type, if it is not already the simplest value
+fn simplest(token: &Token) -> Option<Token> {
+    let simple = match token {
+        Token::Bool(_) => Token::Bool(false),
+        Token::Address(_) => Token::Address(Address::zero()),
+        Token::Bytes(_) => Token::Bytes(Vec::new()),
+        Token::String(_) => Token::String(String::new()),
+        Token::FixedBytes(bytes) => Token::FixedBytes(vec![0; bytes.len()]),
+        Token::Array(_) => Token::Array(Vec::new()),
+        _ => return None,
+    };
+    (simple != *token).then(|| simple)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_shrink_args() {
+        let args = vec![
+            Token::Uint(123456.into()),
+            Token::Int(I256::from(-5000).into_raw()),
+            Token::Bool(true),
+            Token::Bytes(vec![1, 2, 3]),
+        ];
+        let shrunk = shrink_args(args, |args| {
+            args[0].clone().into_uint().unwrap() >= 1000.into() &&
+                I256::from_raw(args[1].clone().into_int().unwrap()) <= I256::from(-42)
+        });
+
+        assert_eq!(
+            shrunk,
+            vec![
+                Token::Uint(1000.into()),
+                Token::Int(I256::from(-42).into_raw()),
+                Token::Bool(false),
+                Token::Bytes(vec![]),
+            ]
+        );
+    }
+}
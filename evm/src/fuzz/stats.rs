@@ -0,0 +1,239 @@
+use super::FuzzedCases;
+use ethers::{
+    abi::{Function, ParamType, Token},
+    types::{I256, U256},
+};
+use std::{collections::HashSet, fmt};
+
+/// The width in bits of a single histogram bucket
+const BUCKET_BITS: usize = 32;
+
+/// The distribution of the inputs a fuzz test was run with, per parameter, and how many runs were
+/// rejected by `assume`.
+///
+/// This can be used to verify that `assume` or `bound` do not collapse the input space.
+#[derive(Debug, Clone)]
+pub struct FuzzInputStats {
+    /// The number of cases the statistics are based on
+    pub runs: usize,
+    /// The number of runs that were rejected by `assume`
+    pub rejects: usize,
+    /// The number of runs that passed all `assume` calls and hit the assertions of the test
+    pub assertion_hits: usize,
+    /// Statistics of every parameter of the test function
+    pub params: Vec<ParamStats>,
+}
+
+/// The distribution of the values generated for a single parameter
+#[derive(Debug, Clone)]
+pub struct ParamStats {
+    /// The name of the parameter, or its position if it is unnamed
+    pub name: String,
+    /// The type of the parameter
+    pub kind: ParamType,
+    /// The distribution of the values
+    pub distribution: Distribution,
+}
+
+/// The distribution of generated values
+#[derive(Debug, Clone)]
+pub enum Distribution {
+    /// Integers, with their magnitudes bucketed by bit length
+    Number {
+        min: String,
+        median: String,
+        max: String,
+        /// The number of negative values
+        negative: usize,
+        /// `upper bit length of the magnitude -> number of values`
+        buckets: Vec<(usize, usize)>,
+    },
+    /// Booleans
+    Bool { truthy: usize, falsy: usize },
+    /// All other types, only the number of distinct values is tracked
+    Other { distinct: usize },
+}
+
+impl FuzzInputStats {
+    /// Computes the distribution of the inputs of all `cases` of the fuzz test `func`
+    ///
+    /// Cases whose calldata can not be decoded are skipped.
+    pub fn new(func: &Function, cases: &FuzzedCases) -> Self {
+        let inputs: Vec<Vec<Token>> = cases
+            .cases()
+            .iter()
+            .filter_map(|case| func.decode_input(case.calldata.as_ref().get(4..)?).ok())
+            .collect();
+
+        let params = func
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(idx, param)| {
+                let values = inputs.iter().filter_map(|tokens| tokens.get(idx));
+                let name =
+                    if param.name.is_empty() { format!("#{}", idx) } else { param.name.clone() };
+                ParamStats {
+                    name,
+                    kind: param.kind.clone(),
+                    distribution: Distribution::new(&param.kind, values),
+                }
+            })
+            .collect();
+
+        Self {
+            runs: inputs.len(),
+            rejects: cases.rejects(),
+            assertion_hits: cases.assertion_hits(),
+            params,
+        }
+    }
+}
+
+impl Distribution {
+    fn new<'a>(kind: &ParamType, values: impl Iterator<Item = &'a Token>) -> Self {
+        match kind {
+            ParamType::Uint(_) => {
+                let mut values: Vec<U256> = values.filter_map(|t| t.clone().into_uint()).collect();
+                values.sort();
+                let buckets = bucket(values.iter().copied());
+                Self::number(&values, 0, buckets)
+            }
+            ParamType::Int(_) => {
+                let mut values: Vec<I256> =
+                    values.filter_map(|t| t.clone().into_int()).map(I256::from_raw).collect();
+                values.sort();
+                let negative = values.iter().filter(|v| v.is_negative()).count();
+                let buckets = bucket(values.iter().map(|v| v.unsigned_abs()));
+                Self::number(&values, negative, buckets)
+            }
+            ParamType::Bool => {
+                let (truthy, falsy) = values.fold((0, 0), |(truthy, falsy), token| match token {
+                    Token::Bool(true) => (truthy + 1, falsy),
+                    _ => (truthy, falsy + 1),
+                });
+                Self::Bool { truthy, falsy }
+            }
+            _ => {
+                let distinct = values.map(|t| t.to_string()).collect::<HashSet<_>>().len();
+                Self::Other { distinct }
+            }
+        }
+    }
+
+    fn number<T: fmt::Display>(
+        sorted: &[T],
+        negative: usize,
+        buckets: Vec<(usize, usize)>,
+    ) -> Self {
+        let get = |idx: usize| sorted.get(idx).map(|v| v.to_string()).unwrap_or_default();
+        Self::Number {
+            min: get(0),
+            median: get(sorted.len() / 2),
+            max: get(sorted.len().saturating_sub(1)),
+            negative,
+            buckets,
+        }
+    }
+}
+
+/// Buckets the values by their bit length, returns `upper bit length -> number of values` for all
+/// non empty buckets
+fn bucket(values: impl Iterator<Item = U256>) -> Vec<(usize, usize)> {
+    let mut counts = vec![0usize; 256 / BUCKET_BITS + 1];
+    for value in values {
+        counts[(value.bits() + BUCKET_BITS - 1) / BUCKET_BITS] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .filter(|(_, count)| *count > 0)
+        .map(|(idx, count)| (idx * BUCKET_BITS, count))
+        .collect()
+}
+
+impl fmt::Display for FuzzInputStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attempts = self.rejects + self.assertion_hits;
+        writeln!(
+            f,
+            "Assertions hit: {} of {} runs, rejected by assume: {} ({}%)",
+            self.assertion_hits,
+            attempts,
+            self.rejects,
+            self.rejects * 100 / attempts.max(1)
+        )?;
+        writeln!(f, "Input distribution ({} runs):", self.runs)?;
+        for param in &self.params {
+            write!(f, "  {} ({}): ", param.name, param.kind)?;
+            match &param.distribution {
+                Distribution::Number { min, median, max, negative, buckets } => {
+                    write!(f, "min: {}, ~: {}, max: {}", min, median, max)?;
+                    if *negative > 0 {
+                        write!(f, ", negative: {}", negative)?;
+                    }
+                    writeln!(f)?;
+                    for (bits, count) in buckets {
+                        let range =
+                            if *bits == 0 { "0".to_string() } else { format!("< 2^{}", bits) };
+                        let bar = "#".repeat(count * 40 / self.runs.max(1));
+                        writeln!(f, "    {:>8} | {:>6} {}", range, count, bar)?;
+                    }
+                }
+                Distribution::Bool { truthy, falsy } => {
+                    writeln!(f, "true: {}, false: {}", truthy, falsy)?;
+                }
+                Distribution::Other { distinct } => {
+                    writeln!(f, "{} distinct values", distinct)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz::FuzzCase;
+    use ethers::abi::AbiParser;
+
+    #[test]
+    fn can_bucket_uints() {
+        let func =
+            AbiParser::default().parse_function("function testFuzz(uint256,bool)").unwrap();
+        let cases = [0u64, 1, u64::MAX]
+            .iter()
+            .map(|x| FuzzCase {
+                calldata: func
+                    .encode_input(&[Token::Uint((*x).into()), Token::Bool(*x == 1)])
+                    .unwrap()
+                    .into(),
+                gas: 0,
+                stipend: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let cases = FuzzedCases::new(cases).with_counts(7, 3);
+        let stats = FuzzInputStats::new(&func, &cases);
+        assert_eq!(stats.runs, 3);
+        assert_eq!(stats.rejects, 7);
+        assert_eq!(stats.assertion_hits, 3);
+        assert!(stats
+            .to_string()
+            .contains("Assertions hit: 3 of 10 runs, rejected by assume: 7 (70%)"));
+        match &stats.params[0].distribution {
+            Distribution::Number { min, median, max, buckets, .. } => {
+                assert_eq!(min, "0");
+                assert_eq!(median, "1");
+                assert_eq!(max, &u64::MAX.to_string());
+                assert_eq!(buckets, &vec![(0, 1), (32, 1), (64, 1)]);
+            }
+            _ => unreachable!(),
+        }
+        match &stats.params[1].distribution {
+            Distribution::Bool { truthy, falsy } => assert_eq!((*truthy, *falsy), (1, 2)),
+            _ => unreachable!(),
+        }
+    }
+}
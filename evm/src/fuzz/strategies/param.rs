@@ -75,13 +75,13 @@ pub fn fuzz_param(param: &ParamType) -> impl Strategy<Value = Token> {
 /// Works with ABI Encoder v2 tuples.
 pub fn fuzz_param_from_state(param: &ParamType, state: EvmFuzzState) -> BoxedStrategy<Token> {
     // These are to comply with lifetime requirements
-    let state_len = state.borrow().len();
     let s = state.clone();
 
-    // Select a value from the state
-    let value = any::<prop::sample::Index>()
-        .prop_map(move |index| index.index(state_len))
-        .prop_map(move |index| *s.borrow().iter().nth(index).unwrap());
+    // Select a value from the state, including the values collected since the strategy was built
+    let value = any::<prop::sample::Index>().prop_map(move |index| {
+        let state = s.borrow();
+        *state.iter().nth(index.index(state.len())).unwrap()
+    });
 
     // Convert the value based on the parameter type
     match param {
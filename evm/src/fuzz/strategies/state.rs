@@ -10,12 +10,15 @@ use revm::{
     db::{CacheDB, DatabaseRef},
     opcode, spec_opcode_gas, SpecId,
 };
-use std::{cell::RefCell, collections::HashSet, io::Write, rc::Rc};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
 
-/// A set of arbitrary 32 byte data from the VM used to generate values for the strategy.
+/// The fuzz dictionary, a set of 32 byte words from the VM used to generate values for the
+/// strategy: the constants of deployed bytecode, storage slots and values, and log topics and data.
 ///
-/// Wrapped in a shareable container.
-pub type EvmFuzzState = Rc<RefCell<HashSet<[u8; 32]>>>;
+/// Words are big endian, so a value taken from the dictionary is the number that was pushed or
+/// stored. Wrapped in a shareable container. The values are ordered, so the same seed selects the
+/// same values.
+pub type EvmFuzzState = Rc<RefCell<BTreeSet<[u8; 32]>>>;
 
 /// Given a function and some state, it returns a strategy which generated valid calldata for the
 /// given function's input types, based on state taken from the EVM.
@@ -48,27 +51,31 @@ This is a bug, please open an issue: https://github.com/foundry-rs/foundry/issue
 
 /// Builds the initial [EvmFuzzState] from a database.
 pub fn build_initial_state<DB: DatabaseRef>(db: &CacheDB<DB>) -> EvmFuzzState {
-    let mut state: HashSet<[u8; 32]> = HashSet::new();
+    let mut state: BTreeSet<[u8; 32]> = BTreeSet::new();
     for (address, storage) in db.storage() {
         let info = db.basic(*address);
 
         // Insert basic account information
         state.insert(H256::from(*address).into());
-        state.insert(utils::u256_to_h256_le(info.balance).into());
-        state.insert(utils::u256_to_h256_le(U256::from(info.nonce)).into());
+        state.insert(utils::u256_to_h256_be(info.balance).into());
+        state.insert(utils::u256_to_h256_be(U256::from(info.nonce)).into());
 
         // Insert storage
         for (slot, value) in storage {
-            state.insert(utils::u256_to_h256_le(*slot).into());
-            state.insert(utils::u256_to_h256_le(*value).into());
+            state.insert(utils::u256_to_h256_be(*slot).into());
+            state.insert(utils::u256_to_h256_be(*value).into());
         }
+
+        // Insert push bytes
+        let code = info.code.unwrap_or_else(|| db.code_by_hash(info.code_hash));
+        state.extend(collect_push_bytes(code));
     }
 
     // need at least some state data if db is empty otherwise we can't select random data for state
     // fuzzing
     if state.is_empty() {
-        // prefill with a random addresses
-        state.insert(H256::from(Address::random()).into());
+        // prefill with an arbitrary address
+        state.insert(H256::from(Address::repeat_byte(0x42)).into());
     }
 
     Rc::new(RefCell::new(state))
@@ -85,35 +92,25 @@ pub fn collect_state_from_call(
     for (address, account) in state_changeset {
         // Insert basic account information
         state.insert(H256::from(*address).into());
-        state.insert(utils::u256_to_h256_le(account.info.balance).into());
-        state.insert(utils::u256_to_h256_le(U256::from(account.info.nonce)).into());
+        state.insert(utils::u256_to_h256_be(account.info.balance).into());
+        state.insert(utils::u256_to_h256_be(U256::from(account.info.nonce)).into());
 
         // Insert storage
         for (slot, value) in &account.storage {
-            state.insert(utils::u256_to_h256_le(*slot).into());
-            state.insert(utils::u256_to_h256_le(*value).into());
+            state.insert(utils::u256_to_h256_be(*slot).into());
+            state.insert(utils::u256_to_h256_be(*value).into());
         }
 
         // Insert push bytes
         if let Some(code) = &account.info.code {
-            for push_byte in collect_push_bytes(code.clone()) {
-                state.insert(push_byte);
-            }
+            state.extend(collect_push_bytes(code.clone()));
         }
+    }
 
-        // Insert log topics and data
-        for log in logs {
-            log.topics.iter().for_each(|topic| {
-                state.insert(topic.0);
-            });
-            log.data.chunks(32).for_each(|chunk| {
-                let mut buffer: [u8; 32] = [0; 32];
-                let _ = (&mut buffer[..])
-                    .write(chunk)
-                    .expect("log data chunk was larger than 32 bytes");
-                state.insert(buffer);
-            });
-        }
+    // Insert log topics and data
+    for log in logs {
+        state.extend(log.topics.iter().map(|topic| topic.0));
+        state.extend(log.data.chunks(32).map(right_aligned));
     }
 }
 
@@ -145,11 +142,7 @@ fn collect_push_bytes(code: Bytes) -> Vec<[u8; 32]> {
                 return bytes
             }
 
-            let mut buffer: [u8; 32] = [0; 32];
-            let _ = (&mut buffer[..])
-                .write(&code[push_start..push_end])
-                .expect("push was larger than 32 bytes");
-            bytes.push(buffer);
+            bytes.push(right_aligned(&code[push_start..push_end]));
             i += push_size;
         }
         i += 1;
@@ -157,3 +150,30 @@ fn collect_push_bytes(code: Bytes) -> Vec<[u8; 32]> {
 
     bytes
 }
+
+/// Pads the bytes of at most 32 bytes to a word, like the EVM pads pushed values
+fn right_aligned(bytes: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(bytes);
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{strategy::ValueTree, test_runner::TestRunner};
+
+    #[test]
+    fn dictionary_values_are_the_pushed_values() {
+        // PUSH4 0xdeadbeef, PUSH1 0x01
+        let code = Bytes::from(vec![0x63, 0xde, 0xad, 0xbe, 0xef, 0x60, 0x01]);
+        let words = collect_push_bytes(code);
+        assert_eq!(words[0], <[u8; 32]>::from(utils::u256_to_h256_be(0xdeadbeefu64.into())));
+        assert_eq!(words[1], <[u8; 32]>::from(utils::u256_to_h256_be(1u64.into())));
+
+        let state: EvmFuzzState = Rc::new(RefCell::new(words[..1].iter().copied().collect()));
+        let strat = fuzz_param_from_state(&ethers::abi::ParamType::Uint(32), state);
+        let value = strat.new_tree(&mut TestRunner::default()).unwrap().current();
+        assert_eq!(value, ethers::abi::Token::Uint(0xdeadbeefu64.into()));
+    }
+}
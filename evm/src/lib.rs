@@ -7,6 +7,9 @@ pub mod trace;
 /// Debugger data structures
 pub mod debug;
 
+/// Coverage data structures
+pub mod coverage;
+
 /// Forge test execution backends
 pub mod executor;
 
@@ -84,3 +87,14 @@ impl From<CallKind> for CallType {
         }
     }
 }
+
+impl From<CallType> for CallKind {
+    fn from(ty: CallType) -> Self {
+        match ty {
+            CallType::StaticCall => CallKind::StaticCall,
+            CallType::CallCode => CallKind::CallCode,
+            CallType::DelegateCall => CallKind::DelegateCall,
+            CallType::Call | CallType::None => CallKind::Call,
+        }
+    }
+}
@@ -42,6 +42,17 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Add the functions, events and errors of known ABIs to the decoder.
+    ///
+    /// Calls to these functions are decoded by their selector, even if the called address is not
+    /// identified.
+    pub fn with_abis<'a>(mut self, abis: impl IntoIterator<Item = &'a Abi>) -> Self {
+        for abi in abis {
+            self.decoder.collect_abi(abi);
+        }
+        self
+    }
+
     /// Build the decoder.
     pub fn build(self) -> CallTraceDecoder {
         self.decoder
@@ -197,28 +208,30 @@ impl CallTraceDecoder {
             }
 
             if let Some(abi) = &identity.abi {
-                // Store known functions for the address
-                abi.functions()
-                    .map(|func| (func.short_signature(), func.clone()))
-                    .for_each(|(sig, func)| self.functions.entry(sig).or_default().push(func));
+                self.collect_abi(abi);
+            }
+        });
+    }
 
-                // Flatten events from all ABIs
-                abi.events()
-                    .map(|event| ((event.signature(), indexed_inputs(event)), event.clone()))
-                    .for_each(|(sig, event)| {
-                        self.events.entry(sig).or_default().push(event);
-                    });
+    /// Stores the functions, events and errors of the ABI
+    fn collect_abi(&mut self, abi: &Abi) {
+        // Store known functions for the address
+        abi.functions()
+            .map(|func| (func.short_signature(), func.clone()))
+            .for_each(|(sig, func)| self.functions.entry(sig).or_default().push(func));
 
-                // Flatten errors from all ABIs
-                abi.errors().for_each(|error| {
-                    let entry = self
-                        .errors
-                        .errors
-                        .entry(error.name.clone())
-                        .or_insert_with(Default::default);
-                    entry.push(error.clone());
-                });
-            }
+        // Flatten events from all ABIs
+        abi.events()
+            .map(|event| ((event.signature(), indexed_inputs(event)), event.clone()))
+            .for_each(|(sig, event)| {
+                self.events.entry(sig).or_default().push(event);
+            });
+
+        // Flatten errors from all ABIs
+        abi.errors().for_each(|error| {
+            let entry =
+                self.errors.errors.entry(error.name.clone()).or_insert_with(Default::default);
+            entry.push(error.clone());
         });
     }
 
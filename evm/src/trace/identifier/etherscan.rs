@@ -11,14 +11,20 @@ use futures::{
     stream::{FuturesUnordered, Stream, StreamExt},
     task::{Context, Poll},
 };
-use std::{borrow::Cow, path::PathBuf, pin::Pin};
+use std::{borrow::Cow, cell::RefCell, collections::BTreeMap, path::PathBuf, pin::Pin};
 use tokio::time::{Duration, Interval};
 use tracing::{trace, warn};
 
 /// A trace identifier that tries to identify addresses using Etherscan.
+///
+/// Every address is only requested once, responses are also cached on disk if a cache path is
+/// set.
 pub struct EtherscanIdentifier {
     /// The Etherscan client
     client: Option<etherscan::Client>,
+    /// The names and ABIs of the addresses that were already requested, `None` if Etherscan has
+    /// no verified source for the address
+    identities: RefCell<BTreeMap<Address, Option<(String, Abi)>>>,
 }
 
 impl EtherscanIdentifier {
@@ -43,6 +49,7 @@ impl EtherscanIdentifier {
                     etherscan::Client::new_cached(chain.into(), key, cache_path, ttl).ok()
                 })
             }),
+            identities: Default::default(),
         }
     }
 }
@@ -52,24 +59,43 @@ impl TraceIdentifier for EtherscanIdentifier {
         &self,
         addresses: Vec<(&Address, Option<&Vec<u8>>)>,
     ) -> Vec<AddressIdentity> {
-        self.client.as_ref().map_or(Default::default(), |client| {
+        let client = match self.client.as_ref() {
+            Some(client) => client,
+            None => return Vec::new(),
+        };
+        let mut identities = self.identities.borrow_mut();
+
+        let unknown: Vec<Address> = addresses
+            .iter()
+            .map(|(address, _)| **address)
+            .filter(|address| !identities.contains_key(address))
+            .collect();
+        if !unknown.is_empty() {
             let mut fetcher = EtherscanFetcher::new(client.clone(), Duration::from_secs(1), 5);
+            for address in &unknown {
+                fetcher.push(*address);
+            }
+            let fetched: Vec<_> = RuntimeOrHandle::new().block_on(fetcher.collect());
 
-            for (addr, _) in addresses {
-                fetcher.push(*addr);
+            // unverified addresses are remembered as well, so they are not requested again
+            identities.extend(unknown.into_iter().map(|address| (address, None)));
+            for (address, name, abi) in fetched {
+                identities.insert(address, Some((name, abi)));
             }
+        }
 
-            let fut = fetcher
-                .map(|(address, label, abi)| AddressIdentity {
-                    address,
-                    label: Some(label.clone()),
-                    contract: Some(label),
-                    abi: Some(Cow::Owned(abi)),
+        addresses
+            .into_iter()
+            .filter_map(|(address, _)| {
+                let (name, abi) = identities.get(address)?.as_ref()?;
+                Some(AddressIdentity {
+                    address: *address,
+                    label: Some(name.clone()),
+                    contract: Some(name.clone()),
+                    abi: Some(Cow::Owned(abi.clone())),
                 })
-                .collect();
-
-            RuntimeOrHandle::new().block_on(fut)
-        })
+            })
+            .collect()
     }
 }
 
@@ -6,17 +6,20 @@ use ethers::{
 use std::{borrow::Cow, collections::BTreeMap};
 
 /// A trace identifier that tries to identify addresses using local contracts.
-pub struct LocalTraceIdentifier {
-    local_contracts: BTreeMap<Vec<u8>, (String, Abi)>,
+///
+/// The identifier borrows the ABIs and runtime code of the known contracts, so creating one is
+/// cheap, e.g. for every invariant test.
+pub struct LocalTraceIdentifier<'a> {
+    local_contracts: BTreeMap<&'a [u8], (&'a str, &'a Abi)>,
 }
 
-impl LocalTraceIdentifier {
-    pub fn new(known_contracts: &BTreeMap<ArtifactId, (Abi, Vec<u8>)>) -> Self {
+impl<'a> LocalTraceIdentifier<'a> {
+    pub fn new(known_contracts: &'a BTreeMap<ArtifactId, (Abi, Vec<u8>)>) -> Self {
         Self {
             local_contracts: known_contracts
                 .iter()
                 .map(|(id, (abi, runtime_code))| {
-                    (runtime_code.clone(), (id.name.clone(), abi.clone()))
+                    (runtime_code.as_slice(), (id.name.as_str(), abi))
                 })
                 .collect(),
         }
@@ -28,7 +31,7 @@ impl LocalTraceIdentifier {
     }
 }
 
-impl TraceIdentifier for LocalTraceIdentifier {
+impl<'a> TraceIdentifier for LocalTraceIdentifier<'a> {
     fn identify_addresses(
         &self,
         addresses: Vec<(&Address, Option<&Vec<u8>>)>,
@@ -44,9 +47,9 @@ impl TraceIdentifier for LocalTraceIdentifier {
 
                 Some(AddressIdentity {
                     address: *address,
-                    contract: Some(name.clone()),
-                    label: Some(name.clone()),
-                    abi: Some(Cow::Borrowed(abi)),
+                    contract: Some(name.to_string()),
+                    label: Some(name.to_string()),
+                    abi: Some(Cow::Borrowed(*abi)),
                 })
             })
             .collect()
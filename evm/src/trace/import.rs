@@ -0,0 +1,280 @@
+use super::{
+    CallTrace, CallTraceArena, LogCallOrder, RawOrDecodedCall, RawOrDecodedLog,
+    RawOrDecodedReturnData,
+};
+use crate::CallKind;
+use ethers::{
+    abi::RawLog,
+    types::{Action, Address, Bytes, Res, H256, U256},
+};
+use eyre::WrapErr;
+use revm::Return;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A call frame of geth's `callTracer`, as returned by `debug_traceTransaction`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethCallFrame {
+    /// The opcode of the call, e.g. `CALL` or `CREATE2`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub from: Address,
+    /// The called address, or the address of the created contract
+    #[serde(default)]
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub value: Option<U256>,
+    #[serde(default)]
+    pub gas_used: U256,
+    #[serde(default)]
+    pub input: Bytes,
+    #[serde(default)]
+    pub output: Option<Bytes>,
+    /// Set if the call failed
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<GethCallFrame>,
+    /// The emitted logs, only recorded if the tracer is configured with `withLog`
+    #[serde(default)]
+    pub logs: Vec<GethCallLog>,
+}
+
+/// A log emitted in a [`GethCallFrame`]
+#[derive(Debug, Clone, Deserialize)]
+pub struct GethCallLog {
+    pub topics: Vec<H256>,
+    #[serde(default)]
+    pub data: Bytes,
+}
+
+/// A single trace of parity's `trace_transaction` or `trace_replayTransaction`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTrace {
+    pub action: Action,
+    #[serde(default)]
+    pub result: Option<Res>,
+    /// Set if the call failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// The position of the trace in the call tree
+    pub trace_address: Vec<usize>,
+}
+
+/// Parses the trace of a transaction recorded by a node
+///
+/// Supports the output of geth's `callTracer` and of parity's `trace_transaction` and
+/// `trace_replayTransaction`, with or without the JSON-RPC response around it.
+pub fn parse_trace(json: &str) -> eyre::Result<CallTraceArena> {
+    let value: Value = serde_json::from_str(json).wrap_err("The trace is not valid JSON")?;
+    let value = take_field(take_field(value, "result"), "trace");
+    if value.is_array() {
+        let traces: Vec<ParityTrace> =
+            serde_json::from_value(value).wrap_err("Failed to parse the parity trace")?;
+        Ok(from_parity_traces(traces))
+    } else {
+        let frame: GethCallFrame =
+            serde_json::from_value(value).wrap_err("Failed to parse the geth call trace")?;
+        Ok(from_geth_call_frame(frame))
+    }
+}
+
+/// Returns the field of a JSON object, or the value itself if it does not have the field
+fn take_field(value: Value, field: &str) -> Value {
+    match value {
+        Value::Object(mut object) if object.contains_key(field) => {
+            object.remove(field).unwrap_or_default()
+        }
+        value => value,
+    }
+}
+
+/// Converts the root frame of geth's `callTracer` into a call trace arena
+pub fn from_geth_call_frame(frame: GethCallFrame) -> CallTraceArena {
+    let mut arena = CallTraceArena::default();
+    push_geth_frame(&mut arena, frame, 0);
+    arena
+}
+
+fn push_geth_frame(arena: &mut CallTraceArena, frame: GethCallFrame, depth: usize) {
+    let kind = match frame.kind.as_str() {
+        "CALL" => CallKind::Call,
+        "STATICCALL" => CallKind::StaticCall,
+        "CALLCODE" => CallKind::CallCode,
+        "DELEGATECALL" => CallKind::DelegateCall,
+        "CREATE" | "CREATE2" => CallKind::Create,
+        // self destructs are recorded as frames, but they are not calls
+        _ => return,
+    };
+
+    let idx = push_trace(
+        arena,
+        CallTrace {
+            depth,
+            success: frame.error.is_none(),
+            caller: frame.from,
+            address: frame.to.unwrap_or_default(),
+            kind,
+            value: frame.value.unwrap_or_default(),
+            data: RawOrDecodedCall::Raw(frame.input.to_vec()),
+            output: RawOrDecodedReturnData::Raw(
+                frame.output.map(|output| output.to_vec()).unwrap_or_default(),
+            ),
+            gas_cost: frame.gas_used.as_u64(),
+            ..Default::default()
+        },
+    );
+
+    for call in frame.calls {
+        push_geth_frame(arena, call, depth + 1);
+    }
+    // The position of the logs between the subcalls is not recorded
+    let node = &mut arena.arena[idx];
+    for log in frame.logs {
+        node.ordering.push(LogCallOrder::Log(node.logs.len()));
+        let log = RawLog { topics: log.topics, data: log.data.to_vec() };
+        node.logs.push(RawOrDecodedLog::Raw(log));
+    }
+}
+
+/// Converts the traces of parity's `trace_transaction` into a call trace arena
+///
+/// The traces must be ordered like the node returns them, every call before its subcalls.
+pub fn from_parity_traces(traces: Vec<ParityTrace>) -> CallTraceArena {
+    let mut arena = CallTraceArena::default();
+    for trace in traces {
+        let (kind, caller, address, value, data) = match trace.action {
+            Action::Call(call) => {
+                (call.call_type.into(), call.from, call.to, call.value, call.input)
+            }
+            Action::Create(create) => {
+                (CallKind::Create, create.from, Address::zero(), create.value, create.init)
+            }
+            // self destructs and block rewards are not calls
+            _ => continue,
+        };
+        let (address, output, gas_used) = match trace.result {
+            Some(Res::Call(result)) => (address, result.output, result.gas_used),
+            Some(Res::Create(result)) => (result.address, result.code, result.gas_used),
+            _ => (address, Bytes::default(), U256::zero()),
+        };
+
+        push_trace(
+            &mut arena,
+            CallTrace {
+                depth: trace.trace_address.len(),
+                success: trace.error.is_none(),
+                caller,
+                address,
+                kind,
+                value,
+                data: RawOrDecodedCall::Raw(data.to_vec()),
+                output: RawOrDecodedReturnData::Raw(output.to_vec()),
+                gas_cost: gas_used.as_u64(),
+                ..Default::default()
+            },
+        );
+    }
+    arena
+}
+
+/// Adds the trace to the arena and returns its index, the status is derived from its success
+fn push_trace(arena: &mut CallTraceArena, mut trace: CallTrace) -> usize {
+    trace.status = if trace.success { Return::Return } else { Return::Revert };
+    if trace.depth == 0 {
+        arena.arena[0].trace = trace;
+        0
+    } else {
+        arena.push_trace(0, trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_geth_call_trace() {
+        let json = r#"{
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "type": "CALL",
+                "from": "0x0000000000000000000000000000000000000001",
+                "to": "0x0000000000000000000000000000000000000002",
+                "value": "0x0",
+                "gas": "0x10000",
+                "gasUsed": "0x5208",
+                "input": "0xa9059cbb",
+                "output": "0x",
+                "calls": [{
+                    "type": "STATICCALL",
+                    "from": "0x0000000000000000000000000000000000000002",
+                    "to": "0x0000000000000000000000000000000000000003",
+                    "gas": "0x1000",
+                    "gasUsed": "0x100",
+                    "input": "0x70a08231",
+                    "error": "execution reverted"
+                }]
+            }
+        }"#;
+        let arena = parse_trace(json).unwrap();
+
+        assert_eq!(arena.arena.len(), 2);
+        let root = &arena.arena[0];
+        assert_eq!(root.trace.gas_cost, 21000);
+        assert!(root.trace.success);
+        assert_eq!(root.children, vec![1]);
+        let call = &arena.arena[1].trace;
+        assert_eq!(call.kind, CallKind::StaticCall);
+        assert_eq!(call.depth, 1);
+        assert!(!call.success);
+        assert_eq!(call.status, Return::Revert);
+    }
+
+    #[test]
+    fn can_parse_parity_traces() {
+        let json = r#"[
+            {
+                "action": {
+                    "callType": "call",
+                    "from": "0x0000000000000000000000000000000000000001",
+                    "to": "0x0000000000000000000000000000000000000002",
+                    "gas": "0x10000",
+                    "input": "0xa9059cbb",
+                    "value": "0x0"
+                },
+                "result": { "gasUsed": "0x5208", "output": "0x" },
+                "subtraces": 1,
+                "traceAddress": [],
+                "type": "call"
+            },
+            {
+                "action": {
+                    "from": "0x0000000000000000000000000000000000000002",
+                    "gas": "0x1000",
+                    "init": "0x6080",
+                    "value": "0x0"
+                },
+                "result": {
+                    "address": "0x0000000000000000000000000000000000000004",
+                    "code": "0x6080",
+                    "gasUsed": "0x100"
+                },
+                "subtraces": 0,
+                "traceAddress": [0],
+                "type": "create"
+            }
+        ]"#;
+        let arena = parse_trace(json).unwrap();
+
+        assert_eq!(arena.arena.len(), 2);
+        assert_eq!(arena.arena[0].trace.kind, CallKind::Call);
+        let create = &arena.arena[1].trace;
+        assert!(create.created());
+        assert_eq!(create.address, Address::from_low_u64_be(4));
+        assert_eq!(create.output, RawOrDecodedReturnData::Raw(vec![0x60, 0x80]));
+    }
+}
@@ -4,10 +4,13 @@
 pub mod identifier;
 
 mod decoder;
+/// Call traces recorded by nodes, e.g. geth's `callTracer`.
+pub mod import;
 pub mod node;
 mod utils;
 
 pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder};
+pub use utils::label_addresses;
 
 use crate::{abi::CHEATCODE_ADDRESS, CallKind};
 use ethers::{
@@ -89,63 +92,117 @@ const BRANCH: &str = "  ├─ ";
 const CALL: &str = "→ ";
 const RETURN: &str = "← ";
 
-impl fmt::Display for CallTraceArena {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fn inner(
-            arena: &CallTraceArena,
-            writer: &mut (impl Write + ?Sized),
-            idx: usize,
-            left: &str,
-            child: &str,
-        ) -> fmt::Result {
-            let node = &arena.arena[idx];
-
-            // Display trace header
-            writeln!(writer, "{}{}", left, node.trace)?;
-
-            // Display logs and subcalls
-            let left_prefix = format!("{child}{BRANCH}");
-            let right_prefix = format!("{child}{PIPE}");
-            for child in &node.ordering {
-                match child {
-                    LogCallOrder::Log(index) => {
-                        let mut log = String::new();
-                        write!(log, "{}", node.logs[*index])?;
-
-                        // Prepend our tree structure symbols to each line of the displayed log
-                        log.lines().enumerate().try_for_each(|(i, line)| {
-                            writeln!(
-                                writer,
-                                "{}{}",
-                                if i == 0 { &left_prefix } else { &right_prefix },
-                                line
-                            )
-                        })?;
-                    }
-                    LogCallOrder::Call(index) => {
-                        inner(arena, writer, node.children[*index], &left_prefix, &right_prefix)?;
-                    }
-                }
+/// Options for displaying call traces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TraceOptions {
+    /// Show the gas used by every call itself, excluding its subcalls, and the gas it refunded
+    pub gas: bool,
+    /// Show static calls on a single line, without their subcalls and logs
+    pub collapse_static_calls: bool,
+}
+
+impl CallTraceArena {
+    /// Returns the traces formatted with the given options
+    pub fn display(&self, options: TraceOptions) -> String {
+        let mut out = String::new();
+        self.fmt_node(&mut out, 0, "  ", "  ", options).expect("writing to a string never fails");
+        out
+    }
+
+    fn fmt_node(
+        &self,
+        writer: &mut (impl Write + ?Sized),
+        idx: usize,
+        left: &str,
+        child: &str,
+        options: TraceOptions,
+    ) -> fmt::Result {
+        let node = &self.arena[idx];
+        let color = trace_color(&node.trace);
+
+        if options.collapse_static_calls && node.kind() == CallKind::StaticCall {
+            let output = &node.trace.output;
+            write!(writer, "{}{} {}{}", left, node.trace, color.paint(RETURN), output)?;
+            if options.gas {
+                write!(writer, " {}", self.own_gas(node))?;
             }
+            return writeln!(writer)
+        }
 
-            // Display trace return data
-            let color = trace_color(&node.trace);
-            write!(writer, "{}{}", child, EDGE)?;
-            write!(writer, "{}", color.paint(RETURN))?;
-            if node.trace.created() {
-                if let RawOrDecodedReturnData::Raw(bytes) = &node.trace.output {
-                    writeln!(writer, "{} bytes of code", bytes.len())?;
-                } else {
-                    unreachable!("We should never have decoded calldata for contract creations");
+        // Display trace header
+        writeln!(writer, "{}{}", left, node.trace)?;
+
+        // Display logs and subcalls
+        let left_prefix = format!("{child}{BRANCH}");
+        let right_prefix = format!("{child}{PIPE}");
+        for child in &node.ordering {
+            match child {
+                LogCallOrder::Log(index) => {
+                    let mut log = String::new();
+                    write!(log, "{}", node.logs[*index])?;
+
+                    // Prepend our tree structure symbols to each line of the displayed log
+                    log.lines().enumerate().try_for_each(|(i, line)| {
+                        writeln!(
+                            writer,
+                            "{}{}",
+                            if i == 0 { &left_prefix } else { &right_prefix },
+                            line
+                        )
+                    })?;
+                }
+                LogCallOrder::Call(index) => {
+                    self.fmt_node(
+                        writer,
+                        node.children[*index],
+                        &left_prefix,
+                        &right_prefix,
+                        options,
+                    )?;
                 }
-            } else {
-                writeln!(writer, "{}", node.trace.output)?;
             }
+        }
 
-            Ok(())
+        // Display trace return data
+        write!(writer, "{}{}", child, EDGE)?;
+        write!(writer, "{}", color.paint(RETURN))?;
+        if node.trace.created() {
+            if let RawOrDecodedReturnData::Raw(bytes) = &node.trace.output {
+                write!(writer, "{} bytes of code", bytes.len())?;
+            } else {
+                unreachable!("We should never have decoded calldata for contract creations");
+            }
+        } else {
+            write!(writer, "{}", node.trace.output)?;
+        }
+        if options.gas {
+            write!(writer, " {}", self.own_gas(node))?;
         }
+        writeln!(writer)
+    }
 
-        inner(self, f, 0, "  ", "  ")
+    /// Returns the gas used and refunded by the call itself, excluding its subcalls
+    ///
+    /// The refund of a call includes the refunds of its successful subcalls, which are
+    /// subtracted like their gas.
+    fn own_gas(&self, node: &CallTraceNode) -> Paint<String> {
+        let children = node.children.iter().map(|id| &self.arena[*id].trace);
+        let (subcalls, subcall_refunds) = children.fold((0u64, 0u64), |(gas, refunds), trace| {
+            let refunded = if trace.success { trace.gas_refunded } else { 0 };
+            (gas.saturating_add(trace.gas_cost), refunds.saturating_add(refunded))
+        });
+        Paint::new(format!(
+            "[gas: {} own, {} refunded]",
+            node.trace.gas_cost.saturating_sub(subcalls),
+            node.trace.gas_refunded.saturating_sub(subcall_refunds)
+        ))
+        .dimmed()
+    }
+}
+
+impl fmt::Display for CallTraceArena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_node(f, 0, "  ", "  ", TraceOptions::default())
     }
 }
 
@@ -304,10 +361,21 @@ pub struct CallTrace {
     pub output: RawOrDecodedReturnData,
     /// The gas cost of the call
     pub gas_cost: u64,
+    /// The gas refunded by the call and its successful subcalls, e.g. for clearing storage, or 0
+    /// if the refund is negative
+    #[serde(default)]
+    pub gas_refunded: u64,
     /// The status of the trace's call
     pub status: Return,
     /// call context of the runtime
     pub call_context: Option<CallContext>,
+    /// The program counter of the last executed instruction, which is the location of the revert
+    /// for failed calls
+    #[serde(default)]
+    pub last_pc: Option<usize>,
+    /// The program counter of the call or create instruction in the caller that started this call
+    #[serde(default)]
+    pub call_site_pc: Option<usize>,
 }
 
 // === impl CallTrace ===
@@ -323,6 +391,7 @@ impl CallTrace {
         self.output = new_trace.output;
         self.address = new_trace.address;
         self.gas_cost = new_trace.gas_cost;
+        self.gas_refunded = new_trace.gas_refunded;
     }
 
     /// Whether this is a contract creation or not
@@ -345,8 +414,11 @@ impl Default for CallTrace {
             data: Default::default(),
             output: Default::default(),
             gas_cost: Default::default(),
+            gas_refunded: Default::default(),
             status: Return::Continue,
             call_context: Default::default(),
+            last_pc: Default::default(),
+            call_site_pc: Default::default(),
         }
     }
 }
@@ -422,3 +494,66 @@ fn trace_color(trace: &CallTrace) -> Color {
         Color::Red
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_display_traces_with_options() {
+        Paint::disable();
+        let root = CallTrace {
+            success: true,
+            label: Some("Test".to_string()),
+            data: RawOrDecodedCall::Decoded("test".to_string(), vec![]),
+            gas_cost: 1000,
+            gas_refunded: 4900,
+            ..Default::default()
+        };
+        let call = CallTrace {
+            depth: 1,
+            success: true,
+            label: Some("Token".to_string()),
+            kind: CallKind::StaticCall,
+            data: RawOrDecodedCall::Decoded("balanceOf".to_string(), vec!["1".to_string()]),
+            output: RawOrDecodedReturnData::Decoded("5".to_string()),
+            gas_cost: 300,
+            ..Default::default()
+        };
+        let clear = CallTrace {
+            depth: 1,
+            success: true,
+            label: Some("Token".to_string()),
+            data: RawOrDecodedCall::Decoded("clear".to_string(), vec![]),
+            output: RawOrDecodedReturnData::Decoded("()".to_string()),
+            gas_cost: 200,
+            gas_refunded: 4800,
+            ..Default::default()
+        };
+        let arena = CallTraceArena {
+            arena: vec![
+                CallTraceNode {
+                    children: vec![1, 2],
+                    ordering: vec![LogCallOrder::Call(0), LogCallOrder::Call(1)],
+                    trace: root,
+                    ..Default::default()
+                },
+                CallTraceNode { parent: Some(0), idx: 1, trace: call, ..Default::default() },
+                CallTraceNode { parent: Some(0), idx: 2, trace: clear, ..Default::default() },
+            ],
+        };
+
+        let options = TraceOptions { gas: true, collapse_static_calls: true };
+        assert_eq!(
+            arena.display(options),
+            concat!(
+                "  [1000] Test::test() \n",
+                "    ├─ [300] Token::balanceOf(1) [staticcall] ← 5 [gas: 300 own, 0 refunded]\n",
+                "    ├─ [200] Token::clear() \n",
+                "    │   └─ ← () [gas: 200 own, 4800 refunded]\n",
+                "    └─ ← () [gas: 500 own, 100 refunded]\n",
+            )
+        );
+        assert_eq!(arena.display(TraceOptions::default()), arena.to_string());
+    }
+}
@@ -2,7 +2,7 @@
 
 use ethers::abi::{Abi, Address, Function, Token};
 use foundry_utils::format_token;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Returns the label for the given `token`
 ///
@@ -21,6 +21,37 @@ pub fn label(token: &Token, labels: &HashMap<Address, String>) -> String {
     }
 }
 
+/// Labels every labeled address in the `text`, e.g. in revert reasons and console logs
+///
+/// Addresses are formatted like labeled tokens, see [label].
+pub fn label_addresses(text: &str, labels: &BTreeMap<Address, String>) -> String {
+    if labels.is_empty() {
+        return text.to_string()
+    }
+    let mut labeled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("0x") {
+        let candidate = &rest[start + 2..];
+        let len = candidate.bytes().take_while(u8::is_ascii_hexdigit).count();
+        let preceded = rest[..start].bytes().last().map_or(false, |b| b.is_ascii_alphanumeric());
+        let label = (len == 40 && !preceded)
+            .then(|| candidate[..40].parse::<Address>().ok())
+            .flatten()
+            .and_then(|addr| Some((addr, labels.get(&addr)?)));
+        let end = start + 2 + len;
+        match label {
+            Some((addr, label)) => {
+                labeled.push_str(&rest[..start]);
+                labeled.push_str(&format!("{}: [{:?}]", label, addr));
+            }
+            None => labeled.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    labeled.push_str(rest);
+    labeled
+}
+
 pub(crate) fn decode_cheatcode_inputs(
     func: &Function,
     data: &[u8],
@@ -33,3 +64,24 @@ pub(crate) fn decode_cheatcode_inputs(
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_label_addresses_in_text() {
+        let alice = Address::from_low_u64_be(1);
+        let labels = BTreeMap::from([(alice, "alice".to_string())]);
+
+        let text = "transfer from 0x0000000000000000000000000000000000000001 failed";
+        assert_eq!(
+            label_addresses(text, &labels),
+            "transfer from alice: [0x0000000000000000000000000000000000000001] failed"
+        );
+        // unlabeled addresses and other hex values are kept
+        let text = "0x0000000000000000000000000000000000000002 0x01 \
+                    0x000000000000000000000000000000000000000000000000000000000000000001";
+        assert_eq!(label_addresses(text, &labels), text);
+    }
+}
@@ -1,4 +1,8 @@
-use ethers::prelude::{H256, U256};
+use ethers::{
+    prelude::{H256, U256},
+    solc::EvmVersion,
+};
+use revm::SpecId;
 
 /// Small helper function to convert [U256] into [H256].
 pub fn u256_to_h256_le(u: U256) -> H256 {
@@ -23,3 +27,22 @@ pub fn h256_to_u256_be(storage: H256) -> U256 {
 pub fn h256_to_u256_le(storage: H256) -> U256 {
     U256::from_little_endian(storage.as_bytes())
 }
+
+/// Returns the revm spec of the EVM version
+///
+/// # Panics
+///
+/// If the EVM version is not supported, see [try_evm_spec]
+pub fn evm_spec(evm: &EvmVersion) -> SpecId {
+    try_evm_spec(evm).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Returns the revm spec of the EVM version, or an error if the EVM version is not supported
+pub fn try_evm_spec(evm: &EvmVersion) -> eyre::Result<SpecId> {
+    match evm {
+        EvmVersion::Istanbul => Ok(SpecId::ISTANBUL),
+        EvmVersion::Berlin => Ok(SpecId::BERLIN),
+        EvmVersion::London => Ok(SpecId::LONDON),
+        _ => eyre::bail!("Unsupported EVM version {evm}"),
+    }
+}
@@ -0,0 +1,61 @@
+// Node bindings for foundry's executor, see `ffi/README.md`.
+const ffi = require("ffi-napi");
+const ref = require("ref-napi");
+
+const lib = ffi.Library(process.env.FOUNDRY_FFI_LIB || "libfoundry_ffi", {
+  foundry_executor_new: ["pointer", ["string", "uint64"]],
+  foundry_executor_free: ["void", ["pointer"]],
+  foundry_string_free: ["void", ["pointer"]],
+  foundry_set_balance: ["pointer", ["pointer", "string", "string"]],
+  foundry_deploy: ["pointer", ["pointer", "string", "string", "string"]],
+  foundry_call: ["pointer", ["pointer", "string", "string", "string", "string", "bool"]],
+  foundry_snapshot: ["uint64", ["pointer"]],
+  foundry_revert: ["bool", ["pointer", "uint64"]],
+});
+
+function result(ptr) {
+  const json = ref.readCString(ptr, 0);
+  lib.foundry_string_free(ptr);
+  const value = JSON.parse(json);
+  if (value.error !== undefined) {
+    throw new Error(value.error);
+  }
+  return value;
+}
+
+// An EVM with an empty state, or forked from `forkUrl` at `forkBlock` (latest if 0).
+class Executor {
+  constructor(forkUrl = null, forkBlock = 0) {
+    this.ptr = lib.foundry_executor_new(forkUrl, forkBlock);
+    if (this.ptr.isNull()) {
+      throw new Error("could not create the executor");
+    }
+  }
+
+  free() {
+    lib.foundry_executor_free(this.ptr);
+    this.ptr = ref.NULL;
+  }
+
+  setBalance(address, value) {
+    result(lib.foundry_set_balance(this.ptr, address, String(value)));
+  }
+
+  deploy(sender, code, value = 0) {
+    return result(lib.foundry_deploy(this.ptr, sender, code, String(value)));
+  }
+
+  call(sender, to, calldata, value = 0, commit = false) {
+    return result(lib.foundry_call(this.ptr, sender, to, calldata, String(value), commit));
+  }
+
+  snapshot() {
+    return lib.foundry_snapshot(this.ptr);
+  }
+
+  revert(snapshotId) {
+    return lib.foundry_revert(this.ptr, snapshotId);
+  }
+}
+
+module.exports = { Executor };
@@ -0,0 +1,79 @@
+"""Python bindings for foundry's executor, see `ffi/README.md`."""
+
+import ctypes
+import json
+import os
+
+_lib = ctypes.CDLL(os.environ.get("FOUNDRY_FFI_LIB", "libfoundry_ffi.so"))
+
+_lib.foundry_executor_new.argtypes = [ctypes.c_char_p, ctypes.c_uint64]
+_lib.foundry_executor_new.restype = ctypes.c_void_p
+_lib.foundry_executor_free.argtypes = [ctypes.c_void_p]
+_lib.foundry_string_free.argtypes = [ctypes.c_void_p]
+_lib.foundry_set_balance.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_char_p]
+_lib.foundry_set_balance.restype = ctypes.c_void_p
+_lib.foundry_deploy.argtypes = [ctypes.c_void_p] + [ctypes.c_char_p] * 3
+_lib.foundry_deploy.restype = ctypes.c_void_p
+_lib.foundry_call.argtypes = [ctypes.c_void_p] + [ctypes.c_char_p] * 4 + [ctypes.c_bool]
+_lib.foundry_call.restype = ctypes.c_void_p
+_lib.foundry_snapshot.argtypes = [ctypes.c_void_p]
+_lib.foundry_snapshot.restype = ctypes.c_uint64
+_lib.foundry_revert.argtypes = [ctypes.c_void_p, ctypes.c_uint64]
+_lib.foundry_revert.restype = ctypes.c_bool
+
+
+class FoundryError(Exception):
+    pass
+
+
+def _result(ptr):
+    try:
+        result = json.loads(ctypes.string_at(ptr).decode())
+    finally:
+        _lib.foundry_string_free(ptr)
+    if "error" in result:
+        raise FoundryError(result["error"])
+    return result
+
+
+class Executor:
+    """An EVM with an empty state, or forked from `fork_url` at `fork_block` (latest if 0)."""
+
+    def __init__(self, fork_url=None, fork_block=0):
+        url = fork_url.encode() if fork_url else None
+        self._ptr = _lib.foundry_executor_new(url, fork_block)
+        if not self._ptr:
+            raise FoundryError("could not create the executor")
+
+    def __del__(self):
+        if getattr(self, "_ptr", None):
+            _lib.foundry_executor_free(self._ptr)
+            self._ptr = None
+
+    def set_balance(self, address, value):
+        _result(_lib.foundry_set_balance(self._ptr, address.encode(), str(value).encode()))
+
+    def deploy(self, sender, code, value=0):
+        """Deploys the creation code, returns the address, gas and trace of the deployment."""
+        return _result(
+            _lib.foundry_deploy(self._ptr, sender.encode(), code.encode(), str(value).encode())
+        )
+
+    def call(self, sender, to, calldata, value=0, commit=False):
+        """Calls `to`, the state changes are only kept if `commit` is true."""
+        return _result(
+            _lib.foundry_call(
+                self._ptr,
+                sender.encode(),
+                to.encode(),
+                calldata.encode(),
+                str(value).encode(),
+                commit,
+            )
+        )
+
+    def snapshot(self):
+        return _lib.foundry_snapshot(self._ptr)
+
+    def revert(self, snapshot_id):
+        return _lib.foundry_revert(self._ptr, snapshot_id)
@@ -0,0 +1,23 @@
+/* C ABI of foundry's executor, see `ffi/src/lib.rs` for the documentation of every function. */
+#ifndef FOUNDRY_H
+#define FOUNDRY_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+typedef struct FoundryExecutor FoundryExecutor;
+
+FoundryExecutor *foundry_executor_new(const char *fork_url, uint64_t fork_block);
+void foundry_executor_free(FoundryExecutor *executor);
+void foundry_string_free(char *s);
+
+char *foundry_set_balance(FoundryExecutor *executor, const char *address, const char *value);
+char *foundry_deploy(FoundryExecutor *executor, const char *from, const char *code,
+                     const char *value);
+char *foundry_call(FoundryExecutor *executor, const char *from, const char *to,
+                   const char *calldata, const char *value, bool commit);
+
+uint64_t foundry_snapshot(FoundryExecutor *executor);
+bool foundry_revert(FoundryExecutor *executor, uint64_t id);
+
+#endif
@@ -0,0 +1,304 @@
+//! A C ABI for foundry's executor, so it can be driven from other languages, e.g. from Python or
+//! Node with the bindings in `bindings/`.
+//!
+//! Functions that return a result return it as a JSON string, which must be freed with
+//! [foundry_string_free]. Failures are returned as `{"error": "<message>"}`. Addresses, code and
+//! calldata are hex strings, values are decimal or `0x` prefixed hex strings.
+
+use ethers::{
+    solc::utils::RuntimeOrHandle,
+    types::{Address, U256},
+};
+use eyre::WrapErr;
+use foundry_config::{figment::Figment, Config};
+use foundry_evm::{
+    executor::{builder::Backend, opts::EvmOpts, EvmError, Executor, ExecutorBuilder},
+    trace::CallTraceArena,
+};
+use serde_json::{json, Value};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+use yansi::Paint;
+
+/// An executor and the states saved with [foundry_snapshot]
+pub struct FoundryExecutor {
+    executor: Executor<Backend>,
+    /// The saved executors, the id of a snapshot is its index
+    snapshots: Vec<Executor<Backend>>,
+}
+
+/// Creates an executor with an empty state, or on a fork if `fork_url` is not null
+///
+/// `fork_block` pins the block of the fork, `0` forks the latest block. Returns null if the
+/// executor could not be created.
+///
+/// # Safety
+///
+/// `fork_url` must be null or a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_executor_new(
+    fork_url: *const c_char,
+    fork_block: u64,
+) -> *mut FoundryExecutor {
+    // traces are returned as text, without terminal colors
+    Paint::disable();
+    let executor = panic::catch_unwind(|| {
+        let mut evm_opts = Figment::from(Config::default()).extract::<EvmOpts>()?;
+        if !fork_url.is_null() {
+            evm_opts.fork_url = Some(str_arg(fork_url)?.to_string());
+            evm_opts.fork_block_number = (fork_block != 0).then(|| fork_block);
+        }
+        let runtime = RuntimeOrHandle::new();
+        let env = runtime.block_on(evm_opts.evm_env());
+        let fork = evm_opts.get_fork(&Default::default());
+        let db = runtime.block_on(Backend::new(fork, &env));
+        let executor = ExecutorBuilder::new()
+            .with_cheatcodes(false)
+            .with_config(env)
+            .with_gas_limit(evm_opts.gas_limit())
+            .with_tracing()
+            .build(db);
+        Ok::<_, eyre::Report>(FoundryExecutor { executor, snapshots: Vec::new() })
+    });
+    match executor {
+        Ok(Ok(executor)) => Box::into_raw(Box::new(executor)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Frees an executor created with [foundry_executor_new]
+///
+/// # Safety
+///
+/// `executor` must be null or a pointer returned by [foundry_executor_new] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_executor_free(executor: *mut FoundryExecutor) {
+    if !executor.is_null() {
+        drop(Box::from_raw(executor));
+    }
+}
+
+/// Frees a string returned by any of the functions of this library
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Sets the balance of the account, returns `{"success": true}`
+///
+/// # Safety
+///
+/// `executor` must be a valid executor, the other arguments valid nul terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_set_balance(
+    executor: *mut FoundryExecutor,
+    address: *const c_char,
+    value: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let executor = executor_arg(executor)?;
+        executor.executor.set_balance(address_arg(address)?, value_arg(value)?);
+        Ok(json!({ "success": true }))
+    })
+}
+
+/// Deploys the creation code and commits the new state
+///
+/// Returns `{"success", "address", "gas", "reason", "trace", "trace_text"}`, `reason` is only set
+/// if the deployment reverted.
+///
+/// # Safety
+///
+/// `executor` must be a valid executor, the other arguments valid nul terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_deploy(
+    executor: *mut FoundryExecutor,
+    from: *const c_char,
+    code: *const c_char,
+    value: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let executor = executor_arg(executor)?;
+        let (from, code, value) = (address_arg(from)?, hex_arg(code)?, value_arg(value)?);
+        match executor.executor.deploy(from, code.into(), value, None) {
+            Ok(result) => Ok(json!({
+                "success": true,
+                "address": result.address,
+                "gas": result.gas,
+                "trace": trace_json(&result.traces)?,
+                "trace_text": trace_text(&result.traces),
+            })),
+            Err(EvmError::Execution { reason, gas, traces, .. }) => Ok(json!({
+                "success": false,
+                "reason": reason,
+                "gas": gas,
+                "trace": trace_json(&traces)?,
+                "trace_text": trace_text(&traces),
+            })),
+            Err(err) => Err(eyre::eyre!("{err}")),
+        }
+    })
+}
+
+/// Calls the account, the new state is only committed if `commit` is true
+///
+/// Returns `{"success", "output", "gas", "logs", "trace", "trace_text"}`.
+///
+/// # Safety
+///
+/// `executor` must be a valid executor, the other arguments valid nul terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_call(
+    executor: *mut FoundryExecutor,
+    from: *const c_char,
+    to: *const c_char,
+    calldata: *const c_char,
+    value: *const c_char,
+    commit: bool,
+) -> *mut c_char {
+    respond(|| {
+        let executor = &mut executor_arg(executor)?.executor;
+        let (from, to) = (address_arg(from)?, address_arg(to)?);
+        let (calldata, value) = (hex_arg(calldata)?.into(), value_arg(value)?);
+        let result = if commit {
+            executor.call_raw_committing(from, to, calldata, value)?
+        } else {
+            executor.call_raw(from, to, calldata, value)?
+        };
+        let logs: Vec<Value> = result
+            .logs
+            .iter()
+            .map(|log| {
+                json!({ "topics": log.topics, "data": format!("0x{}", hex::encode(&log.data)) })
+            })
+            .collect();
+        Ok(json!({
+            "success": !result.reverted,
+            "output": format!("0x{}", hex::encode(&result.result)),
+            "gas": result.gas,
+            "logs": logs,
+            "trace": trace_json(&result.traces)?,
+            "trace_text": trace_text(&result.traces),
+        }))
+    })
+}
+
+/// Saves the current state and returns the id of the snapshot
+///
+/// # Safety
+///
+/// `executor` must be a valid executor.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_snapshot(executor: *mut FoundryExecutor) -> u64 {
+    let executor = &mut *executor;
+    executor.snapshots.push(executor.executor.clone());
+    (executor.snapshots.len() - 1) as u64
+}
+
+/// Restores the state of the snapshot, returns false if there is no snapshot with the id
+///
+/// The snapshot is kept, so the state can be restored again.
+///
+/// # Safety
+///
+/// `executor` must be a valid executor.
+#[no_mangle]
+pub unsafe extern "C" fn foundry_revert(executor: *mut FoundryExecutor, id: u64) -> bool {
+    let executor = &mut *executor;
+    match executor.snapshots.get(id as usize) {
+        Some(snapshot) => {
+            executor.executor = snapshot.clone();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Runs `f` and returns its result or error as a JSON string
+fn respond(f: impl FnOnce() -> eyre::Result<Value>) -> *mut c_char {
+    let value = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(err)) => json!({ "error": format!("{err:#}") }),
+        Err(_) => json!({ "error": "the executor panicked" }),
+    };
+    CString::new(value.to_string()).expect("JSON strings have no nul bytes").into_raw()
+}
+
+unsafe fn executor_arg<'a>(
+    executor: *mut FoundryExecutor,
+) -> eyre::Result<&'a mut FoundryExecutor> {
+    executor.as_mut().ok_or_else(|| eyre::eyre!("executor is null"))
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> eyre::Result<&'a str> {
+    if s.is_null() {
+        eyre::bail!("string argument is null")
+    }
+    Ok(CStr::from_ptr(s).to_str()?)
+}
+
+unsafe fn address_arg(s: *const c_char) -> eyre::Result<Address> {
+    let s = str_arg(s)?;
+    s.parse().wrap_err_with(|| format!("invalid address {s}"))
+}
+
+unsafe fn hex_arg(s: *const c_char) -> eyre::Result<Vec<u8>> {
+    let s = str_arg(s)?;
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).wrap_err_with(|| format!("invalid hex {s}"))
+}
+
+/// Parses a decimal or `0x` prefixed hex value, null is zero
+unsafe fn value_arg(s: *const c_char) -> eyre::Result<U256> {
+    if s.is_null() {
+        return Ok(U256::zero())
+    }
+    let s = str_arg(s)?;
+    let value = match s.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(s).ok(),
+    };
+    value.ok_or_else(|| eyre::eyre!("invalid value {s}"))
+}
+
+fn trace_json(traces: &Option<CallTraceArena>) -> eyre::Result<Value> {
+    Ok(serde_json::to_value(traces)?)
+}
+
+fn trace_text(traces: &Option<CallTraceArena>) -> Option<String> {
+    traces.as_ref().map(|traces| traces.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_snapshot_and_revert() {
+        unsafe {
+            let executor = foundry_executor_new(ptr::null(), 0);
+            assert!(!executor.is_null());
+            let address = CString::new("0x1000000000000000000000000000000000000001").unwrap();
+            let balance = CString::new("100").unwrap();
+
+            let snapshot = foundry_snapshot(executor);
+            foundry_string_free(foundry_set_balance(executor, address.as_ptr(), balance.as_ptr()));
+            let target = address.to_str().unwrap().parse().unwrap();
+            assert_eq!((*executor).executor.get_balance(target), 100.into());
+
+            assert!(foundry_revert(executor, snapshot));
+            assert_eq!((*executor).executor.get_balance(target), 0.into());
+            assert!(!foundry_revert(executor, snapshot + 1));
+            foundry_executor_free(executor);
+        }
+    }
+}
@@ -2,33 +2,16 @@
 
 use std::fmt::Write;
 
+use foundry_config::fmt::{FormatterConfig, IndentStyle, IntTypes};
 use indent_write::fmt::IndentWriter;
 use itertools::Itertools;
 use solang_parser::pt::*;
 
 use crate::{
-    helpers, 
-    solang_ext::*,
+    loc::*,
     visit::{ParameterList, VResult, Visitable, Visitor},
 };
 
-/// Contains the config and rule set
-#[derive(Debug, Clone)]
-pub struct FormatterConfig {
-    /// Maximum line length where formatter will try to wrap the line
-    pub line_length: usize,
-    /// Number of spaces per indentation level
-    pub tab_width: usize,
-    /// Print spaces between brackets
-    pub bracket_spacing: bool,
-}
-
-impl Default for FormatterConfig {
-    fn default() -> Self {
-        FormatterConfig { line_length: 80, tab_width: 4, bracket_spacing: false }
-    }
-}
-
 // TODO: use it inside Formatter since they're sharing same fields
 #[derive(Default)]
 struct FormatBuffer {
@@ -109,6 +92,18 @@ impl<'a, W: Write> Formatter<'a, W> {
         write!(self, "{}", if self.config.bracket_spacing { "{ }" } else { "{}" })
     }
 
+    /// Write an integer type with respect to `config.int_types` setting:
+    /// `uint256` if `Long`, `uint` if `Short`, as written in the source if `Preserve`
+    fn write_int_type(&mut self, loc: Loc, name: &str, bits: u16) -> VResult {
+        match self.config.int_types {
+            IntTypes::Long => write!(self, "{name}{bits}")?,
+            IntTypes::Short if bits == 256 => write!(self, "{name}")?,
+            IntTypes::Short => write!(self, "{name}{bits}")?,
+            IntTypes::Preserve => self.visit_source(loc)?,
+        }
+        Ok(())
+    }
+
     /// Length of the line `s` with respect to already written line and indentation
     fn len_indented_with_current(&self, s: impl AsRef<str>) -> usize {
         (self.config.tab_width * self.level)
@@ -190,7 +185,10 @@ impl<'a, W: Write> Write for Formatter<'a, W> {
             };
 
         if *pending_indent {
-            let indent = " ".repeat(self.config.tab_width * level);
+            let indent = match self.config.style {
+                IndentStyle::Space => " ".repeat(self.config.tab_width * level),
+                IndentStyle::Tab => "\t".repeat(level),
+            };
             IndentWriter::new(&indent, w).write_str(s)?;
         } else {
             w.write_str(s)?;
@@ -499,8 +497,8 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
                 Type::Payable => write!(self, "payable")?,
                 Type::Bool => write!(self, "bool")?,
                 Type::String => write!(self, "string")?,
-                Type::Int(n) => write!(self, "int{}", n)?,
-                Type::Uint(n) => write!(self, "uint{}", n)?,
+                Type::Int(n) => self.write_int_type(loc, "int", *n)?,
+                Type::Uint(n) => self.write_int_type(loc, "uint", *n)?,
                 Type::Bytes(n) => write!(self, "bytes{}", n)?,
                 Type::Rational => write!(self, "rational")?,
                 Type::DynamicBytes => write!(self, "bytes")?,
@@ -1159,6 +1157,21 @@ mod tests {
                                 "bracket-spacing" => {
                                     config.bracket_spacing = value.parse().unwrap()
                                 }
+                                "style" => {
+                                    config.style = match value {
+                                        "space" => IndentStyle::Space,
+                                        "tab" => IndentStyle::Tab,
+                                        _ => panic!("Unknown style: {value}"),
+                                    }
+                                }
+                                "int-types" => {
+                                    config.int_types = match value {
+                                        "long" => IntTypes::Long,
+                                        "short" => IntTypes::Short,
+                                        "preserve" => IntTypes::Preserve,
+                                        _ => panic!("Unknown int types: {value}"),
+                                    }
+                                }
                                 _ => panic!("Unknown config key: {key}"),
                             }
                         }
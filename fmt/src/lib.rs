@@ -1,8 +1,9 @@
 #![doc = include_str!("../README.md")]
 
-// mod formatter;
-// mod loc;
-// mod visit;
-//
-// pub use formatter::{Formatter, FormatterConfig};
-// pub use visit::Visitable;
+mod formatter;
+mod loc;
+mod visit;
+
+pub use formatter::Formatter;
+pub use foundry_config::fmt::{FormatterConfig, IndentStyle, IntTypes};
+pub use visit::Visitable;
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// An external static analyzer `forge analyze` can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Analyzer {
+    /// https://github.com/crytic/slither
+    Slither,
+    /// https://github.com/ConsenSys/mythril
+    Mythril,
+}
+
+impl Analyzer {
+    /// The name of the executable of the analyzer
+    pub fn executable(&self) -> &'static str {
+        match self {
+            Analyzer::Slither => "slither",
+            Analyzer::Mythril => "myth",
+        }
+    }
+}
+
+impl fmt::Display for Analyzer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Analyzer::Slither => f.write_str("slither"),
+            Analyzer::Mythril => f.write_str("mythril"),
+        }
+    }
+}
+
+impl FromStr for Analyzer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "slither" => Ok(Analyzer::Slither),
+            "mythril" | "myth" => Ok(Analyzer::Mythril),
+            _ => Err(format!("Unknown analyzer `{s}`, expected `slither` or `mythril`")),
+        }
+    }
+}
+
+/// The severity of a finding, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Informational,
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    /// The SARIF level of findings of the severity
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::High => "error",
+            Severity::Medium => "warning",
+            Severity::Low | Severity::Informational => "note",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Informational => f.write_str("informational"),
+            Severity::Low => f.write_str("low"),
+            Severity::Medium => f.write_str("medium"),
+            Severity::High => f.write_str("high"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "informational" | "info" | "optimization" => Ok(Severity::Informational),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            _ => Err(format!(
+                "Unknown severity `{s}`, expected `informational`, `low`, `medium` or `high`"
+            )),
+        }
+    }
+}
+
+/// A finding of an analyzer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// The analyzer that reported the finding
+    pub analyzer: Analyzer,
+    /// The id of the check that reported the finding, e.g. `reentrancy-eth` or `SWC-107`
+    pub check: String,
+    pub severity: Severity,
+    pub description: String,
+    /// The source file of the finding, as reported by the analyzer
+    pub file: Option<String>,
+    /// The first line of the finding in `file`
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.check, self.analyzer)?;
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, " {file}:{line}")?,
+            (Some(file), None) => write!(f, " {file}")?,
+            _ => {}
+        }
+        write!(f, "\n    {}", self.description.replace('\n', "\n    "))
+    }
+}
+
+/// Parses the findings of the JSON output of `slither --json -`
+pub fn parse_slither(output: &str) -> eyre::Result<Vec<Finding>> {
+    let output: Value = serde_json::from_str(output)?;
+    if output["success"] != Value::Bool(true) {
+        eyre::bail!("slither failed: {}", output["error"].as_str().unwrap_or("unknown error"))
+    }
+    let detectors = output["results"]["detectors"].as_array().cloned().unwrap_or_default();
+    detectors
+        .iter()
+        .map(|detector| {
+            let location = detector["elements"]
+                .as_array()
+                .and_then(|elements| elements.first())
+                .map(|element| &element["source_mapping"]);
+            Ok(Finding {
+                analyzer: Analyzer::Slither,
+                check: detector["check"].as_str().unwrap_or_default().to_string(),
+                severity: detector["impact"]
+                    .as_str()
+                    .unwrap_or("informational")
+                    .parse()
+                    .map_err(|err: String| eyre::eyre!(err))?,
+                description: detector["description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                file: location
+                    .and_then(|location| location["filename_relative"].as_str())
+                    .map(str::to_string),
+                line: location
+                    .and_then(|location| location["lines"].as_array()?.first()?.as_u64())
+                    .map(|line| line as usize),
+            })
+        })
+        .collect()
+}
+
+/// Parses the findings of the JSON output of `myth analyze -o json`
+pub fn parse_mythril(output: &str) -> eyre::Result<Vec<Finding>> {
+    let output: Value = serde_json::from_str(output)?;
+    if let Some(error) = output["error"].as_str() {
+        eyre::bail!("mythril failed: {error}")
+    }
+    let issues = output["issues"].as_array().cloned().unwrap_or_default();
+    issues
+        .iter()
+        .map(|issue| {
+            let title = issue["title"].as_str().unwrap_or_default();
+            let description = issue["description"].as_str().unwrap_or_default();
+            Ok(Finding {
+                analyzer: Analyzer::Mythril,
+                check: match issue["swc-id"].as_str() {
+                    Some(id) => format!("SWC-{id}"),
+                    None => title.to_string(),
+                },
+                severity: issue["severity"]
+                    .as_str()
+                    .unwrap_or("informational")
+                    .parse()
+                    .map_err(|err: String| eyre::eyre!(err))?,
+                description: format!("{title}: {}", description.trim()),
+                file: issue["filename"].as_str().map(str::to_string),
+                line: issue["lineno"].as_u64().map(|line| line as usize),
+            })
+        })
+        .collect()
+}
+
+/// Returns the findings as a SARIF 2.1.0 log with one run per analyzer, e.g. for GitHub code
+/// scanning
+pub fn to_sarif(findings: &[Finding]) -> Value {
+    let mut by_analyzer: BTreeMap<Analyzer, Vec<&Finding>> = BTreeMap::new();
+    for finding in findings {
+        by_analyzer.entry(finding.analyzer).or_default().push(finding);
+    }
+    let runs: Vec<Value> = by_analyzer
+        .into_iter()
+        .map(|(analyzer, findings)| {
+            let mut rules: Vec<&str> = findings.iter().map(|f| f.check.as_str()).collect();
+            rules.sort_unstable();
+            rules.dedup();
+            let results: Vec<Value> = findings
+                .iter()
+                .map(|finding| {
+                    let mut result = json!({
+                        "ruleId": finding.check,
+                        "level": finding.severity.sarif_level(),
+                        "message": { "text": finding.description },
+                    });
+                    if let Some(file) = &finding.file {
+                        let mut location = json!({ "artifactLocation": { "uri": file } });
+                        if let Some(line) = finding.line {
+                            location["region"] = json!({ "startLine": line });
+                        }
+                        result["locations"] = json!([{ "physicalLocation": location }]);
+                    }
+                    result
+                })
+                .collect();
+            json!({
+                "tool": {
+                    "driver": {
+                        "name": analyzer.to_string(),
+                        "rules": rules
+                            .iter()
+                            .map(|rule| json!({ "id": rule, "shortDescription": { "text": rule } }))
+                            .collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_findings() {
+        let slither = r#"{"success": true, "error": null, "results": {"detectors": [{
+            "check": "reentrancy-eth",
+            "impact": "High",
+            "confidence": "Medium",
+            "description": "Reentrancy in Vault.withdraw()\n",
+            "elements": [
+                {"source_mapping": {"filename_relative": "src/Vault.sol", "lines": [12, 13]}}
+            ]
+        }]}}"#;
+        let findings = parse_slither(slither).unwrap();
+        assert_eq!(
+            findings,
+            vec![Finding {
+                analyzer: Analyzer::Slither,
+                check: "reentrancy-eth".to_string(),
+                severity: Severity::High,
+                description: "Reentrancy in Vault.withdraw()".to_string(),
+                file: Some("src/Vault.sol".to_string()),
+                line: Some(12),
+            }]
+        );
+        assert!(parse_slither(r#"{"success": false, "error": "compilation failed"}"#).is_err());
+
+        let mythril = r#"{"success": true, "error": null, "issues": [{
+            "title": "Unprotected Selfdestruct",
+            "swc-id": "106",
+            "severity": "Low",
+            "description": "Any sender can cause the contract to self-destruct.",
+            "filename": "src/Vault.sol",
+            "lineno": 20
+        }]}"#;
+        let findings = parse_mythril(mythril).unwrap();
+        assert_eq!(findings[0].check, "SWC-106");
+        assert_eq!(findings[0].severity, Severity::Low);
+        assert_eq!(findings[0].line, Some(20));
+
+        let sarif = to_sarif(&findings);
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "mythril");
+        assert_eq!(sarif["runs"][0]["results"][0]["level"], "note");
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"]
+                ["startLine"],
+            20
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{runner::is_bench_function, ContractRunner, TestFilter};
+use eyre::Result;
+use foundry_evm::executor::{CallResult, DatabaseRef, EvmError};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Instant};
+
+/// Benchmark results of all contracts, `contract identifier -> benchmark signature -> result`
+pub type BenchResults = BTreeMap<String, BTreeMap<String, BenchResult>>;
+
+/// How often every benchmark is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchConfig {
+    /// The number of runs before the benchmark is measured, which are discarded
+    pub warmup: usize,
+    /// The number of measured runs
+    pub iterations: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self { warmup: 5, iterations: 50 }
+    }
+}
+
+/// The measurements of every run of a benchmark
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchResult {
+    /// The gas used by every measured run
+    pub gas: Vec<u64>,
+    /// The wall time of every measured run in nanoseconds
+    pub time: Vec<u64>,
+    /// The revert reason if the benchmark, or the setup of its contract, reverted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl BenchResult {
+    /// Returns true if the benchmark did not revert
+    pub fn success(&self) -> bool {
+        self.reason.is_none()
+    }
+
+    pub fn gas_stats(&self) -> Stats {
+        Stats::new(&to_f64(&self.gas))
+    }
+
+    pub fn time_stats(&self) -> Stats {
+        Stats::new(&to_f64(&self.time))
+    }
+}
+
+/// Summary statistics of a sample
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub median: f64,
+    pub mean: f64,
+    /// The sample standard deviation
+    pub stddev: f64,
+}
+
+impl Stats {
+    pub fn new(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default()
+        }
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = if samples.len() > 1 {
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.)
+        } else {
+            0.
+        };
+        Self { median: median(samples), mean, stddev: variance.sqrt() }
+    }
+}
+
+/// The change of a measurement of a benchmark compared to its baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change {
+    /// The change of the median in percent of the baseline's median
+    pub percent: f64,
+    /// The probability of a difference at least this large if the samples are from the same
+    /// distribution, see [`mann_whitney_p`]
+    pub p_value: f64,
+}
+
+impl Change {
+    pub fn new(baseline: &[u64], current: &[u64]) -> Self {
+        let (baseline, current) = (to_f64(baseline), to_f64(current));
+        let (base, head) = (median(&baseline), median(&current));
+        let percent = if base == 0. { 0. } else { (head - base) * 100. / base };
+        Self { percent, p_value: mann_whitney_p(&baseline, &current) }
+    }
+
+    /// Returns true if the change is significant at the significance level `alpha`
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// The changes of a benchmark's gas usage and wall time compared to its baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchComparison {
+    pub gas: Change,
+    pub time: Change,
+}
+
+impl BenchComparison {
+    pub fn new(baseline: &BenchResult, current: &BenchResult) -> Self {
+        Self {
+            gas: Change::new(&baseline.gas, &current.gas),
+            time: Change::new(&baseline.time, &current.time),
+        }
+    }
+
+    /// Returns true if the benchmark uses significantly more gas or is significantly slower
+    pub fn is_regression(&self, alpha: f64) -> bool {
+        (self.gas.is_significant(alpha) && self.gas.percent > 0.) ||
+            (self.time.is_significant(alpha) && self.time.percent > 0.)
+    }
+}
+
+fn to_f64(samples: &[u64]) -> Vec<f64> {
+    samples.iter().map(|x| *x as f64).collect()
+}
+
+fn median(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Returns the two-sided p-value of the Mann-Whitney U test of the two samples
+///
+/// The test does not assume normally distributed samples, so it is robust against the outliers
+/// of wall time measurements. The p-value is approximated with the normal distribution, corrected
+/// for ties, which is accurate for samples of about 10 or more runs.
+pub fn mann_whitney_p(a: &[f64], b: &[f64]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 1.
+    }
+    let mut values: Vec<(f64, bool)> =
+        a.iter().map(|x| (*x, true)).chain(b.iter().map(|x| (*x, false))).collect();
+    values.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+
+    // values that are equal share the average of their ranks
+    let (mut rank_sum, mut ties) = (0., 0.);
+    let mut start = 0;
+    while start < values.len() {
+        let end = start + values[start..].iter().take_while(|v| v.0 == values[start].0).count();
+        let rank = (start + end + 1) as f64 / 2.;
+        rank_sum += rank * values[start..end].iter().filter(|v| v.1).count() as f64;
+        let tied = (end - start) as f64;
+        ties += tied.powi(3) - tied;
+        start = end;
+    }
+
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+    let u = rank_sum - n1 * (n1 + 1.) / 2.;
+    let mean = n1 * n2 / 2.;
+    let variance = n1 * n2 / 12. * ((n + 1.) - ties / (n * (n - 1.)));
+    if variance <= 0. {
+        return if u == mean { 1. } else { 0. }
+    }
+    let z = (u - mean).abs() / variance.sqrt();
+    (2. * (1. - normal_cdf(z))).clamp(0., 1.)
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1. + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Approximates the error function with a maximum error of 1.5e-7 (Abramowitz and Stegun 7.1.26)
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1. / (1. + 0.3275911 * x);
+    let poly = t *
+        (0.254829592 +
+            t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1. - poly * (-x * x).exp())
+}
+
+impl<'a, DB: DatabaseRef + Clone + Send + Sync> ContractRunner<'a, DB> {
+    /// Runs all benchmarks of the contract whose names match the filter
+    ///
+    /// Every run calls the benchmark on the state after the setup, so all runs execute the same
+    /// code. Benchmarks are run one after another to not distort the wall times.
+    pub fn run_benchmarks(
+        &mut self,
+        filter: &impl TestFilter,
+        config: BenchConfig,
+    ) -> Result<BTreeMap<String, BenchResult>> {
+        let needs_setup = self.contract.functions().any(|func| func.name == "setUp");
+        let setup = self.setup(needs_setup)?;
+        if setup.setup_failed {
+            let result = BenchResult { reason: setup.reason, ..Default::default() };
+            return Ok([("setUp()".to_string(), result)].into())
+        }
+
+        let mut results = BTreeMap::new();
+        let benches = self
+            .contract
+            .functions()
+            .filter(|func| is_bench_function(func) && filter.matches_test(func.signature()));
+        for func in benches {
+            let mut result = BenchResult::default();
+            for run in 0..config.warmup + config.iterations {
+                let start = Instant::now();
+                let call = self.executor.call::<(), _, _>(
+                    self.sender,
+                    setup.address,
+                    func.clone(),
+                    (),
+                    0.into(),
+                    self.errors,
+                );
+                let time = start.elapsed();
+                match call {
+                    Ok(CallResult { gas, stipend, .. }) if run >= config.warmup => {
+                        result.gas.push(gas.overflowing_sub(stipend).0);
+                        result.time.push(time.as_nanos() as u64);
+                    }
+                    Ok(_) => {}
+                    Err(EvmError::Execution { reason, .. }) => {
+                        result = BenchResult { reason: Some(reason), ..Default::default() };
+                        break
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            results.insert(func.signature(), result);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_compute_stats() {
+        let stats = Stats::new(&[2., 4., 4., 4., 5., 5., 7., 9.]);
+        assert_eq!(stats.median, 4.5);
+        assert_eq!(stats.mean, 5.);
+        assert!((stats.stddev - 2.138).abs() < 1e-3);
+    }
+
+    #[test]
+    fn can_test_significance() {
+        let baseline: Vec<f64> = (0..30).map(|x| 100. + (x % 7) as f64).collect();
+        let same: Vec<f64> = (0..30).map(|x| 100. + ((x + 3) % 7) as f64).collect();
+        let slower: Vec<f64> = baseline.iter().map(|x| x + 5.).collect();
+
+        assert!(mann_whitney_p(&baseline, &same) > 0.05);
+        assert!(mann_whitney_p(&baseline, &slower) < 0.05);
+
+        // deterministic gas
+        assert_eq!(mann_whitney_p(&[21000.; 10], &[21000.; 10]), 1.);
+        assert!(mann_whitney_p(&[21000.; 10], &[21001.; 10]) < 0.001);
+    }
+}
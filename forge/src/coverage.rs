@@ -0,0 +1,850 @@
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
+use ethers::solc::sourcemap::SourceMap;
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt, io::Write};
+
+pub use foundry_evm::coverage::{merge_coverage, HitMap, HitMaps};
+
+use crate::matches_any_glob;
+use foundry_config::{CoverageConfig, CoverageThresholds};
+
+/// A comment that excludes the contract, function or modifier that follows it from coverage
+/// reports
+pub const COVERAGE_IGNORE_MARKER: &str = "/* forge-coverage: ignore */";
+
+/// An item of a source file whose coverage is reported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageItem {
+    pub kind: CoverageItemKind,
+    pub loc: SourceLocation,
+    /// How often the item was executed
+    pub hits: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverageItemKind {
+    /// A statement
+    Line,
+    /// A path of a branch, e.g. the `true` or `false` path of an `if` statement
+    Branch { branch_id: usize, path_id: usize },
+    /// A function or modifier
+    Function { name: String },
+}
+
+/// The location of an item in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The byte offset of the item in the source file
+    pub start: usize,
+    /// The length of the item in bytes
+    pub length: usize,
+    /// The line the item starts on, starting at 1
+    pub line: usize,
+}
+
+impl fmt::Display for CoverageItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            CoverageItemKind::Line => write!(f, "Line")?,
+            CoverageItemKind::Branch { branch_id, path_id } => {
+                write!(f, "Branch (branch: {branch_id}, path: {path_id})")?
+            }
+            CoverageItemKind::Function { name } => write!(f, r#"Function "{name}""#)?,
+        }
+        write!(
+            f,
+            ", line {}, bytes {}-{}, hits: {}",
+            self.loc.line,
+            self.loc.start,
+            self.loc.start + self.loc.length,
+            self.hits
+        )
+    }
+}
+
+/// A source file to analyze
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// The id the compiler assigned to the file, used in source maps
+    pub id: u32,
+    /// The path of the file
+    pub path: String,
+    /// The source code
+    pub content: String,
+    /// The AST of the file
+    pub ast: Value,
+}
+
+/// The number of hits of all source ranges that instructions of executed contracts map to,
+/// `(source id, start, length) -> hits`
+#[derive(Debug, Clone, Default)]
+pub struct SourceHits {
+    hits: BTreeMap<(u32, usize, usize), u64>,
+}
+
+impl SourceHits {
+    /// Adds the hit maps of all contracts whose runtime code is known
+    ///
+    /// `contracts` are the runtime code and source maps of the compiled contracts. A hit map
+    /// belongs to a contract if their code only differs in a few bytes, e.g. in immutables.
+    pub fn add_hit_maps(&mut self, maps: &HitMaps, contracts: &[(Vec<u8>, SourceMap)]) {
+        for map in maps.values() {
+            let contract = contracts.iter().find(|(code, _)| is_same_code(code, &map.bytecode));
+            if let Some((code, source_map)) = contract {
+                self.add_hit_map(map, code, source_map);
+            }
+        }
+    }
+
+    /// Adds the hits of a single contract
+    ///
+    /// An instruction is counted once per execution of its source range, not once per
+    /// instruction, so the hits of all instructions of a range are not summed up.
+    fn add_hit_map(&mut self, map: &HitMap, code: &[u8], source_map: &SourceMap) {
+        let ics = instruction_counters(code);
+        let mut hits: BTreeMap<(u32, usize, usize), u64> = BTreeMap::new();
+        for (pc, count) in &map.hits {
+            let element = match ics.get(*pc).and_then(|ic| source_map.get(*ic)) {
+                Some(element) => element,
+                None => continue,
+            };
+            if let Some(index) = element.index {
+                let range = hits.entry((index, element.offset, element.length)).or_default();
+                *range = (*range).max(*count);
+            }
+        }
+        for (range, count) in hits {
+            *self.hits.entry(range).or_default() += count;
+        }
+    }
+
+    /// Returns the hits of the source range, which are the hits of instructions that map to
+    /// exactly this range or, if there are none, the most hits of a range within it
+    fn hits_in(&self, id: u32, start: usize, length: usize) -> u64 {
+        if let Some(hits) = self.hits.get(&(id, start, length)) {
+            return *hits
+        }
+        self.hits
+            .range((id, start, 0)..=(id, start + length, usize::MAX))
+            .filter(|((_, offset, len), _)| offset + len <= start + length)
+            .map(|(_, hits)| *hits)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Returns true if the code of a contract only differs in a few bytes from the executed code
+fn is_same_code(code: &[u8], executed: &[u8]) -> bool {
+    code.len() == executed.len() &&
+        code.iter().zip(executed).filter(|(a, b)| a != b).count() * 10 < code.len()
+}
+
+/// Maps every program counter of the code to its instruction counter, which is the index of the
+/// instruction in the source map
+pub fn instruction_counters(code: &[u8]) -> Vec<usize> {
+    let mut ics = Vec::with_capacity(code.len());
+    let mut pc = 0;
+    while pc < code.len() {
+        let ic = ics.last().map(|ic| ic + 1).unwrap_or_default();
+        // PUSH1 to PUSH32 are followed by their immediate value
+        let len = match code[pc] {
+            op @ 0x60..=0x7f => (op - 0x60) as usize + 2,
+            _ => 1,
+        };
+        ics.extend(std::iter::repeat(ic).take(len));
+        pc += len;
+    }
+    ics.truncate(code.len());
+    ics
+}
+
+/// The coverage of all source files
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    /// `source path -> items`
+    pub files: BTreeMap<String, Vec<CoverageItem>>,
+}
+
+impl CoverageReport {
+    /// Collects the coverage items of all source files and their hits
+    ///
+    /// Contracts whose name matches any of the `ignored_contracts` globs, and contracts, functions
+    /// and modifiers marked with [`COVERAGE_IGNORE_MARKER`] are not reported.
+    pub fn new(sources: &[SourceFile], hits: &SourceHits, ignored_contracts: &[String]) -> Self {
+        let files = sources
+            .iter()
+            .map(|source| {
+                let mut analyzer = Analyzer {
+                    source,
+                    hits,
+                    ignored_contracts,
+                    items: Vec::new(),
+                    branches: 0,
+                };
+                analyzer.visit(&source.ast);
+                (source.path.clone(), analyzer.items)
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Returns the summary of every file
+    pub fn summaries(&self) -> BTreeMap<&str, CoverageSummary> {
+        self.files
+            .iter()
+            .map(|(path, items)| (path.as_str(), CoverageSummary::new(items)))
+            .collect()
+    }
+
+    /// Returns a table with the coverage summary of every file and the total coverage
+    pub fn summary_table(&self) -> Table {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+        table.set_header(
+            ["File", "% Lines", "% Branches", "% Functions"]
+                .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+        );
+
+        let mut total = CoverageSummary::default();
+        for (path, summary) in self.summaries() {
+            table.add_row(summary.row(path));
+            total.add(&summary);
+        }
+        table.add_row(total.row("Total"));
+        table
+    }
+
+    /// Writes the report in the lcov tracefile format
+    pub fn write_lcov(&self, mut out: impl Write) -> std::io::Result<()> {
+        for (path, items) in &self.files {
+            writeln!(out, "TN:")?;
+            writeln!(out, "SF:{path}")?;
+
+            let summary = CoverageSummary::new(items);
+            for item in items {
+                if let CoverageItemKind::Function { name } = &item.kind {
+                    writeln!(out, "FN:{},{name}", item.loc.line)?;
+                    writeln!(out, "FNDA:{},{name}", item.hits)?;
+                }
+            }
+            writeln!(out, "FNF:{}", summary.functions)?;
+            writeln!(out, "FNH:{}", summary.hit_functions)?;
+
+            for (line, hits) in lines(items) {
+                writeln!(out, "DA:{line},{hits}")?;
+            }
+            writeln!(out, "LF:{}", summary.lines)?;
+            writeln!(out, "LH:{}", summary.hit_lines)?;
+
+            for item in items {
+                if let CoverageItemKind::Branch { branch_id, path_id } = item.kind {
+                    let taken =
+                        if item.hits == 0 { "-".to_string() } else { item.hits.to_string() };
+                    writeln!(out, "BRDA:{},{branch_id},{path_id},{taken}", item.loc.line)?;
+                }
+            }
+            writeln!(out, "BRF:{}", summary.branches)?;
+            writeln!(out, "BRH:{}", summary.hit_branches)?;
+            writeln!(out, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Returns a message for every coverage that is below its minimum
+    ///
+    /// The global thresholds apply to the total coverage, the messages list the files below the
+    /// minimum, lowest first. The thresholds of `paths` apply to every matching file.
+    pub fn check_thresholds(&self, config: &CoverageConfig) -> Vec<String> {
+        let summaries = self.summaries();
+        let mut total = CoverageSummary::default();
+        summaries.values().for_each(|summary| total.add(summary));
+
+        let mut failures = Vec::new();
+        for (metric, (hits, count), min) in total.below(&config.thresholds) {
+            let mut files: Vec<(&str, f64)> = summaries
+                .iter()
+                .map(|(path, summary)| {
+                    let (hits, count) = summary.metric(metric);
+                    (*path, percentage(hits, count))
+                })
+                .filter(|(_, coverage)| *coverage < min)
+                .collect();
+            files.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let files: Vec<String> =
+                files.iter().map(|(path, coverage)| format!("{path} ({coverage:.2}%)")).collect();
+            failures.push(format!(
+                "Total {metric} coverage {:.2}% is below the minimum of {min}%, files below the \
+                 minimum: {}",
+                percentage(hits, count),
+                files.join(", ")
+            ));
+        }
+
+        for (path, summary) in &summaries {
+            if let Some(thresholds) = config.path_thresholds(path) {
+                for (metric, (hits, count), min) in summary.below(thresholds) {
+                    failures.push(format!(
+                        "{path}: {metric} coverage {:.2}% is below the minimum of {min}%",
+                        percentage(hits, count)
+                    ));
+                }
+            }
+        }
+        failures
+    }
+
+    /// Renders the report as HTML pages, `file name -> page`
+    ///
+    /// `index.html` lists the summary of every file and links to a page per file, which renders
+    /// its source with the hits of every line and the branch paths that were taken or missed.
+    pub fn html_pages(&self, sources: &[SourceFile]) -> BTreeMap<String, String> {
+        let mut pages = BTreeMap::new();
+        let mut rows = String::new();
+        let mut total = CoverageSummary::default();
+        for (path, summary) in self.summaries() {
+            let link = format!(
+                r#"<a href="{}">{}</a>"#,
+                escape_html(&html_page_name(path)),
+                escape_html(path)
+            );
+            rows.push_str(&summary.html_row(&link));
+            total.add(&summary);
+
+            let content = sources
+                .iter()
+                .find(|source| source.path == path)
+                .map(|source| source.content.as_str())
+                .unwrap_or_default();
+            pages.insert(html_page_name(path), html_file_page(path, content, &self.files[path]));
+        }
+        rows.push_str(&total.html_row("Total"));
+
+        let body = format!(
+            "<h1>Coverage report</h1>\n\
+             <table class=\"summary\">\n{HTML_SUMMARY_HEADER}{rows}</table>\n"
+        );
+        pages.insert("index.html".to_string(), html_document("Coverage report", &body));
+        pages
+    }
+}
+
+/// Returns the most hits of a statement per line, `line -> hits`
+fn lines(items: &[CoverageItem]) -> BTreeMap<usize, u64> {
+    items.iter().filter(|item| item.kind == CoverageItemKind::Line).fold(
+        BTreeMap::new(),
+        |mut lines, item| {
+            let hits = lines.entry(item.loc.line).or_default();
+            *hits = item.hits.max(*hits);
+            lines
+        },
+    )
+}
+
+/// The number of lines, branches and functions of a file and how many of them were hit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageSummary {
+    pub lines: usize,
+    pub hit_lines: usize,
+    pub branches: usize,
+    pub hit_branches: usize,
+    pub functions: usize,
+    pub hit_functions: usize,
+}
+
+impl CoverageSummary {
+    pub fn new(items: &[CoverageItem]) -> Self {
+        let lines = lines(items);
+        let mut summary = Self {
+            lines: lines.len(),
+            hit_lines: lines.values().filter(|hits| **hits > 0).count(),
+            ..Default::default()
+        };
+        for item in items {
+            match item.kind {
+                CoverageItemKind::Line => {}
+                CoverageItemKind::Branch { .. } => {
+                    summary.branches += 1;
+                    summary.hit_branches += (item.hits > 0) as usize;
+                }
+                CoverageItemKind::Function { .. } => {
+                    summary.functions += 1;
+                    summary.hit_functions += (item.hits > 0) as usize;
+                }
+            }
+        }
+        summary
+    }
+
+    pub fn add(&mut self, other: &CoverageSummary) {
+        self.lines += other.lines;
+        self.hit_lines += other.hit_lines;
+        self.branches += other.branches;
+        self.hit_branches += other.hit_branches;
+        self.functions += other.functions;
+        self.hit_functions += other.hit_functions;
+    }
+
+    /// Returns the hits and the number of items of the metric, `line`, `branch` or `function`
+    fn metric(&self, metric: &str) -> (usize, usize) {
+        match metric {
+            "line" => (self.hit_lines, self.lines),
+            "branch" => (self.hit_branches, self.branches),
+            _ => (self.hit_functions, self.functions),
+        }
+    }
+
+    /// Returns the metrics whose coverage is below the minimum, with their hits and number of
+    /// items, and the minimum
+    fn below(&self, thresholds: &CoverageThresholds) -> Vec<(&'static str, (usize, usize), f64)> {
+        [
+            ("line", thresholds.min_lines),
+            ("branch", thresholds.min_branches),
+            ("function", thresholds.min_functions),
+        ]
+        .into_iter()
+        .filter_map(|(metric, min)| {
+            let (hits, count) = self.metric(metric);
+            min.filter(|min| percentage(hits, count) < *min).map(|min| (metric, (hits, count), min))
+        })
+        .collect()
+    }
+
+    /// Returns a row of the summary table of the HTML report, `name` is not escaped
+    fn html_row(&self, name: &str) -> String {
+        format!(
+            "<tr><td>{name}</td>{}{}{}</tr>\n",
+            html_percentage_cell(self.hit_lines, self.lines),
+            html_percentage_cell(self.hit_branches, self.branches),
+            html_percentage_cell(self.hit_functions, self.functions)
+        )
+    }
+
+    fn row(&self, name: &str) -> Vec<Cell> {
+        vec![
+            Cell::new(name),
+            percentage_cell(self.hit_lines, self.lines),
+            percentage_cell(self.hit_branches, self.branches),
+            percentage_cell(self.hit_functions, self.functions),
+        ]
+    }
+}
+
+/// Returns the coverage in percent, 100% if there is nothing to cover
+fn percentage(hits: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.
+    } else {
+        hits as f64 / total as f64 * 100.
+    }
+}
+
+fn percentage_cell(hits: usize, total: usize) -> Cell {
+    let percentage = percentage(hits, total);
+    let color = match percentage {
+        p if p >= 90. => Color::Green,
+        p if p >= 50. => Color::Yellow,
+        _ => Color::Red,
+    };
+    Cell::new(format!("{percentage:.2}% ({hits}/{total})")).fg(color)
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+td, th { padding: 2px 8px; text-align: left; }
+.summary td, .summary th { border: 1px solid #ccc; }
+.high { background: #c8f0c8; }
+.medium { background: #f8f0b0; }
+.low { background: #f8c8c8; }
+.source td { font-family: monospace; white-space: pre; }
+.source .number, .source .hits { color: #888; text-align: right; }
+tr.hit .code { background: #dff5df; }
+tr.partial .code { background: #f8f0b0; }
+tr.miss .code { background: #f8d8d8; }
+.taken { color: #2a2; }
+.missed { color: #c22; font-weight: bold; }";
+
+const HTML_SUMMARY_HEADER: &str =
+    "<tr><th>File</th><th>% Lines</th><th>% Branches</th><th>% Functions</th></tr>\n";
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(title)
+    )
+}
+
+/// Returns the file name of the page of a source file, e.g. `src_Counter.sol.html`
+fn html_page_name(path: &str) -> String {
+    format!("{}.html", path.replace('/', "_").replace('\\', "_"))
+}
+
+fn html_percentage_cell(hits: usize, total: usize) -> String {
+    let percentage = percentage(hits, total);
+    let class = match percentage {
+        p if p >= 90. => "high",
+        p if p >= 50. => "medium",
+        _ => "low",
+    };
+    format!(r#"<td class="{class}">{percentage:.2}% ({hits}/{total})</td>"#)
+}
+
+/// Renders the source of a file, every line is marked as hit, missed or partially hit if some of
+/// its branch paths were not taken
+fn html_file_page(path: &str, content: &str, items: &[CoverageItem]) -> String {
+    let line_hits = lines(items);
+    let mut branches: BTreeMap<usize, Vec<&CoverageItem>> = BTreeMap::new();
+    let mut missed_functions = Vec::new();
+    for item in items {
+        match item.kind {
+            CoverageItemKind::Branch { .. } => {
+                branches.entry(item.loc.line).or_default().push(item)
+            }
+            CoverageItemKind::Function { .. } if item.hits == 0 => {
+                missed_functions.push(item.loc.line)
+            }
+            _ => {}
+        }
+    }
+
+    let mut rows = String::new();
+    for (idx, code) in content.lines().enumerate() {
+        let line = idx + 1;
+        let hits = line_hits.get(&line);
+        let line_branches = branches.get(&line).map(Vec::as_slice).unwrap_or_default();
+        let class = match hits {
+            Some(0) => "miss",
+            Some(_) if line_branches.iter().any(|branch| branch.hits == 0) => "partial",
+            Some(_) => "hit",
+            None if missed_functions.contains(&line) => "miss",
+            None => "",
+        };
+        let branch_markers: String = line_branches
+            .iter()
+            .map(|branch| {
+                let (class, marker) =
+                    if branch.hits == 0 { ("missed", '-') } else { ("taken", '+') };
+                let title = escape_html(&branch.to_string());
+                format!(r#"<span class="{class}" title="{title}">{marker}</span>"#)
+            })
+            .collect();
+        rows.push_str(&format!(
+            "<tr class=\"{class}\"><td class=\"number\">{line}</td><td class=\"hits\">{}</td>\
+             <td>{branch_markers}</td><td class=\"code\">{}</td></tr>\n",
+            hits.map(u64::to_string).unwrap_or_default(),
+            escape_html(code)
+        ));
+    }
+
+    let summary = CoverageSummary::new(items);
+    let body = format!(
+        "<p><a href=\"index.html\">All files</a></p>\n<h1>{}</h1>\n\
+         <table class=\"summary\">\n{HTML_SUMMARY_HEADER}{}</table>\n\
+         <table class=\"source\">\n{rows}</table>\n",
+        escape_html(path),
+        summary.html_row(&escape_html(path))
+    );
+    html_document(path, &body)
+}
+
+/// Escapes the characters that are not allowed in HTML text and attributes
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Collects the coverage items of a source file by walking its AST
+struct Analyzer<'a> {
+    source: &'a SourceFile,
+    hits: &'a SourceHits,
+    /// Globs of the names of contracts that are not reported
+    ignored_contracts: &'a [String],
+    items: Vec<CoverageItem>,
+    /// The number of branches found so far
+    branches: usize,
+}
+
+impl<'a> Analyzer<'a> {
+    fn visit(&mut self, node: &Value) {
+        match node {
+            Value::Array(nodes) => nodes.iter().for_each(|node| self.visit(node)),
+            Value::Object(fields) => {
+                if let Some(node_type) = fields.get("nodeType").and_then(Value::as_str) {
+                    if self.is_ignored(node_type, node) {
+                        return
+                    }
+                    self.visit_node(node_type, node);
+                }
+                fields.values().for_each(|value| self.visit(value))
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_node(&mut self, node_type: &str, node: &Value) {
+        match node_type {
+            "FunctionDefinition" | "ModifierDefinition" if !node["body"].is_null() => {
+                let name = match node["name"].as_str() {
+                    Some(name) if !name.is_empty() => name,
+                    // constructors, fallback and receive functions have no name
+                    _ => node["kind"].as_str().unwrap_or("function"),
+                };
+                self.push(CoverageItemKind::Function { name: name.to_string() }, node);
+            }
+            "ExpressionStatement" |
+            "VariableDeclarationStatement" |
+            "Return" |
+            "EmitStatement" |
+            "RevertStatement" |
+            "Break" |
+            "Continue" => {
+                self.push(CoverageItemKind::Line, node);
+            }
+            "ForStatement" | "WhileStatement" | "DoWhileStatement" => {
+                self.push(CoverageItemKind::Line, &node["condition"]);
+            }
+            "IfStatement" => {
+                self.push(CoverageItemKind::Line, &node["condition"]);
+
+                let branch_id = self.branches;
+                self.branches += 1;
+                let true_hits = self.push(
+                    CoverageItemKind::Branch { branch_id, path_id: 0 },
+                    &node["trueBody"],
+                );
+                if node["falseBody"].is_null() {
+                    // without an else block, the false path is taken whenever the condition is
+                    // evaluated without entering the true path
+                    let condition_hits = self.hits_of(&node["condition"]);
+                    if let Some(loc) = self.location(&node["condition"]) {
+                        self.items.push(CoverageItem {
+                            kind: CoverageItemKind::Branch { branch_id, path_id: 1 },
+                            loc,
+                            hits: condition_hits.saturating_sub(true_hits),
+                        });
+                    }
+                } else {
+                    let kind = CoverageItemKind::Branch { branch_id, path_id: 1 };
+                    self.push(kind, &node["falseBody"]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns true if the node and everything in it is excluded from the report
+    fn is_ignored(&self, node_type: &str, node: &Value) -> bool {
+        match node_type {
+            "ContractDefinition" => {
+                node["name"]
+                    .as_str()
+                    .map_or(false, |name| matches_any_glob(self.ignored_contracts, name)) ||
+                    self.is_marked_ignored(node)
+            }
+            "FunctionDefinition" | "ModifierDefinition" => self.is_marked_ignored(node),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the node, or its documentation, is preceded by the
+    /// [`COVERAGE_IGNORE_MARKER`]
+    fn is_marked_ignored(&self, node: &Value) -> bool {
+        let start = match self.location(&node["documentation"]).or_else(|| self.location(node)) {
+            Some(loc) => loc.start,
+            None => return false,
+        };
+        self.source
+            .content
+            .get(..start)
+            .map_or(false, |before| before.trim_end().ends_with(COVERAGE_IGNORE_MARKER))
+    }
+
+    /// Adds an item for the node and returns its hits
+    fn push(&mut self, kind: CoverageItemKind, node: &Value) -> u64 {
+        let hits = self.hits_of(node);
+        if let Some(loc) = self.location(node) {
+            self.items.push(CoverageItem { kind, loc, hits });
+        }
+        hits
+    }
+
+    fn hits_of(&self, node: &Value) -> u64 {
+        match self.location(node) {
+            Some(loc) => self.hits.hits_in(self.source.id, loc.start, loc.length),
+            None => 0,
+        }
+    }
+
+    fn location(&self, node: &Value) -> Option<SourceLocation> {
+        source_location(&self.source.content, node)
+    }
+}
+
+/// Parses the `src` field of the AST node, `<start>:<length>:<source id>`
+pub(crate) fn source_location(content: &str, node: &Value) -> Option<SourceLocation> {
+    let mut src = node["src"].as_str()?.split(':').map(str::parse::<usize>);
+    let (start, length) = (src.next()?.ok()?, src.next()?.ok()?);
+    let line = content.get(..start)?.matches('\n').count() + 1;
+    Some(SourceLocation { start, length, line })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_map_program_counters_to_instructions() {
+        // PUSH1 0x80, PUSH2 0x0102, ADD, STOP
+        let code = [0x60, 0x80, 0x61, 0x01, 0x02, 0x01, 0x00];
+        assert_eq!(instruction_counters(&code), vec![0, 0, 1, 1, 1, 2, 3]);
+        // truncated push data at the end of the code
+        assert_eq!(instruction_counters(&[0x00, 0x62, 0x01]), vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn can_ignore_contracts_and_functions() {
+        let content = concat!(
+            "contract A {\n",
+            "    /* forge-coverage: ignore */\n",
+            "    function f() public {}\n",
+            "    function g() public {}\n",
+            "}\n",
+            "contract MockB {\n",
+            "    function h() public {}\n",
+            "}\n",
+        );
+        let src = |needle: &str, length: usize| {
+            format!("{}:{length}:0", content.find(needle).unwrap())
+        };
+        let function = |name: &str| {
+            serde_json::json!({
+                "nodeType": "FunctionDefinition",
+                "name": name,
+                "src": src(&format!("function {name}"), 22),
+                "body": { "nodeType": "Block", "statements": [] }
+            })
+        };
+        let ast = serde_json::json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                {
+                    "nodeType": "ContractDefinition",
+                    "name": "A",
+                    "src": src("contract A", 10),
+                    "nodes": [function("f"), function("g")]
+                },
+                {
+                    "nodeType": "ContractDefinition",
+                    "name": "MockB",
+                    "src": src("contract MockB", 14),
+                    "nodes": [function("h")]
+                }
+            ]
+        });
+        let source =
+            SourceFile { id: 0, path: "src/A.sol".to_string(), content: content.to_string(), ast };
+
+        let report =
+            CoverageReport::new(&[source], &SourceHits::default(), &["Mock*".to_string()]);
+        let names: Vec<_> = report.files["src/A.sol"]
+            .iter()
+            .filter_map(|item| match &item.kind {
+                CoverageItemKind::Function { name } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["g"]);
+    }
+
+    #[test]
+    fn can_render_html_pages() {
+        let content = "function f(uint x) {\n    if (x > 1) {}\n    x < 2;\n}\n";
+        let item = |kind, line, hits| CoverageItem {
+            kind,
+            loc: SourceLocation { start: 0, length: 0, line },
+            hits,
+        };
+        let report = CoverageReport {
+            files: BTreeMap::from([(
+                "src/A.sol".to_string(),
+                vec![
+                    item(CoverageItemKind::Function { name: "f".to_string() }, 1, 1),
+                    item(CoverageItemKind::Line, 2, 1),
+                    item(CoverageItemKind::Branch { branch_id: 0, path_id: 0 }, 2, 1),
+                    item(CoverageItemKind::Branch { branch_id: 0, path_id: 1 }, 2, 0),
+                    item(CoverageItemKind::Line, 3, 0),
+                ],
+            )]),
+        };
+        let source = SourceFile {
+            id: 0,
+            path: "src/A.sol".to_string(),
+            content: content.to_string(),
+            ast: Value::Null,
+        };
+
+        let pages = report.html_pages(&[source]);
+        assert_eq!(pages.keys().collect::<Vec<_>>(), vec!["index.html", "src_A.sol.html"]);
+        assert!(pages["index.html"].contains(r#"<a href="src_A.sol.html">src/A.sol</a>"#));
+        assert!(pages["index.html"].contains(r#"<td class="medium">50.00% (1/2)</td>"#));
+
+        let page = &pages["src_A.sol.html"];
+        assert!(page.contains(r#"<tr class="partial"><td class="number">2</td>"#));
+        assert!(page.contains(r#"<tr class="miss"><td class="number">3</td>"#));
+        assert!(page.contains("x &lt; 2;"));
+        assert!(page.contains(r#"<span class="missed""#));
+    }
+
+    #[test]
+    fn can_write_lcov() {
+        let item = |kind, line, hits| CoverageItem {
+            kind,
+            loc: SourceLocation { start: 0, length: 0, line },
+            hits,
+        };
+        let report = CoverageReport {
+            files: BTreeMap::from([(
+                "src/Counter.sol".to_string(),
+                vec![
+                    item(CoverageItemKind::Function { name: "increment".to_string() }, 4, 3),
+                    item(CoverageItemKind::Line, 5, 3),
+                    item(CoverageItemKind::Branch { branch_id: 0, path_id: 0 }, 5, 3),
+                    item(CoverageItemKind::Branch { branch_id: 0, path_id: 1 }, 5, 0),
+                    item(CoverageItemKind::Line, 6, 0),
+                ],
+            )]),
+        };
+
+        let mut lcov = Vec::new();
+        report.write_lcov(&mut lcov).unwrap();
+        assert_eq!(
+            String::from_utf8(lcov).unwrap(),
+            "TN:
+SF:src/Counter.sol
+FN:4,increment
+FNDA:3,increment
+FNF:1
+FNH:1
+DA:5,3
+DA:6,0
+LF:2
+LH:1
+BRDA:5,0,0,3
+BRDA:5,0,1,-
+BRF:2
+BRH:1
+end_of_record
+"
+        );
+    }
+}
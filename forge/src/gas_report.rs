@@ -1,5 +1,6 @@
 use crate::{
     executor::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    matches_any_glob,
     trace::{CallTraceArena, RawOrDecodedCall, TraceKind},
 };
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
@@ -10,6 +11,8 @@ use std::{collections::BTreeMap, fmt::Display};
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct GasReport {
     pub report_for: Vec<String>,
+    /// Contracts that are not reported, e.g. `Mock*`
+    pub ignore: Vec<String>,
     pub contracts: BTreeMap<String, ContractInfo>,
 }
 
@@ -30,8 +33,8 @@ pub struct GasInfo {
 }
 
 impl GasReport {
-    pub fn new(report_for: Vec<String>) -> Self {
-        Self { report_for, ..Default::default() }
+    pub fn new(report_for: Vec<String>, ignore: Vec<String>) -> Self {
+        Self { report_for, ignore, ..Default::default() }
     }
 
     pub fn analyze(&mut self, traces: &[(TraceKind, CallTraceArena)]) {
@@ -55,7 +58,7 @@ impl GasReport {
 
         if let Some(name) = &trace.contract {
             let report_for = self.report_for.iter().any(|s| s == name);
-            if report_for || report_for_all {
+            if (report_for || report_for_all) && !matches_any_glob(&self.ignore, name) {
                 let mut contract_report =
                     self.contracts.entry(name.to_string()).or_insert_with(Default::default);
 
@@ -66,7 +69,9 @@ impl GasReport {
                     }
                     // TODO: More robust test contract filtering
                     RawOrDecodedCall::Decoded(func, _)
-                        if !func.starts_with("test") && func != "setUp" =>
+                        if !func.starts_with("test") &&
+                            !func.starts_with("invariant") &&
+                            func != "setUp" =>
                     {
                         let function_report = contract_report
                             .functions
@@ -0,0 +1,278 @@
+use eyre::{Result, WrapErr};
+use std::collections::BTreeMap;
+
+/// The prefix of natspec lines that configure the test contract or function
+pub const INLINE_CONFIG_PREFIX: &str = "forge-config:";
+
+/// The fork a test contract pins with `forge-config:` lines in the natspec above it
+///
+/// ```solidity
+/// /// forge-config: default.fork.url = "mainnet"
+/// /// forge-config: default.fork.block = 15000000
+/// contract VaultForkTest is Test {}
+/// ```
+///
+/// The url is either an alias of the `[rpc_endpoints]` config or a url. Lines of the `default`
+/// profile apply to all profiles, lines of the selected profile override them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InlineForkConfig {
+    /// The rpc endpoint alias or url of the fork
+    pub url: Option<String>,
+    /// The block of the fork
+    pub block: Option<u64>,
+}
+
+impl InlineForkConfig {
+    /// Parses the fork config in the natspec of the contract `contract` declared in `source`
+    ///
+    /// Returns the empty config if the contract is not declared in `source`.
+    pub fn parse(source: &str, contract: &str, profile: &str) -> Result<Self> {
+        let lines: Vec<&str> = source.lines().collect();
+        let natspec = match contract_declaration(&lines, contract) {
+            Some(idx) => natspec(&lines, idx),
+            None => return Ok(Self::default()),
+        };
+        let mut config = Self::default();
+        for (key, value) in settings(&natspec, profile, contract)? {
+            match key.strip_prefix("fork.") {
+                Some("url") => config.url = Some(value.trim_matches('"').to_string()),
+                Some("block") => config.block = Some(parse_value(&key, &value, contract)?),
+                Some(_) => eyre::bail!("Unknown inline config key `{key}` of {contract}"),
+                None => {}
+            }
+        }
+        Ok(config)
+    }
+
+    /// Whether the contract pins neither a fork url nor a block
+    pub fn is_empty(&self) -> bool {
+        self.url.is_none() && self.block.is_none()
+    }
+}
+
+/// The fuzz settings a test contract or test function overrides with `forge-config:` lines in
+/// the natspec above it
+///
+/// ```solidity
+/// contract VaultTest is Test {
+///     /// forge-config: default.fuzz.runs = 5000
+///     function testDeposit(uint256 amount) public {}
+/// }
+/// ```
+///
+/// The settings of a function override those of its contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InlineFuzzConfig {
+    /// The number of fuzz runs
+    pub runs: Option<u32>,
+    /// The maximum number of rejected inputs of a single `vm.assume`
+    pub max_local_rejects: Option<u32>,
+    /// The maximum number of rejected inputs of the whole test
+    pub max_global_rejects: Option<u32>,
+    /// The percentage of inputs drawn from the fuzz dictionary
+    pub dictionary_weight: Option<u32>,
+}
+
+impl InlineFuzzConfig {
+    /// Parses the fuzz config in the natspec of the function `function` of the contract
+    /// `contract` declared in `source`, and in the natspec of the contract
+    pub fn parse(source: &str, contract: &str, function: &str, profile: &str) -> Result<Self> {
+        let lines: Vec<&str> = source.lines().collect();
+        let contract_idx = match contract_declaration(&lines, contract) {
+            Some(idx) => idx,
+            None => return Ok(Self::default()),
+        };
+        let mut config = Self::default();
+        config.apply_settings(&natspec(&lines, contract_idx), profile, contract)?;
+        if let Some(idx) = function_declaration(&lines, contract_idx, function) {
+            let name = format!("{contract}.{function}");
+            config.apply_settings(&natspec(&lines, idx), profile, &name)?;
+        }
+        Ok(config)
+    }
+
+    /// Whether no fuzz setting is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Overrides the settings of the fuzzer config
+    pub fn apply(&self, config: &mut proptest::test_runner::Config) {
+        if let Some(runs) = self.runs {
+            config.cases = runs;
+        }
+        if let Some(max_local_rejects) = self.max_local_rejects {
+            config.max_local_rejects = max_local_rejects;
+        }
+        if let Some(max_global_rejects) = self.max_global_rejects {
+            config.max_global_rejects = max_global_rejects;
+        }
+    }
+
+    fn apply_settings(&mut self, natspec: &[&str], profile: &str, item: &str) -> Result<()> {
+        for (key, value) in settings(natspec, profile, item)? {
+            let setting = match key.strip_prefix("fuzz.") {
+                Some("runs") => &mut self.runs,
+                Some("max_local_rejects") => &mut self.max_local_rejects,
+                Some("max_global_rejects") => &mut self.max_global_rejects,
+                Some("dictionary_weight") => &mut self.dictionary_weight,
+                Some(_) => eyre::bail!("Unknown inline config key `{key}` of {item}"),
+                None => continue,
+            };
+            *setting = Some(parse_value(&key, &value, item)?);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the `forge-config:` settings of the natspec, `key -> value`
+///
+/// Settings of the `default` profile apply to all profiles, settings of `profile` override them,
+/// settings of other profiles are ignored.
+fn settings(natspec: &[&str], profile: &str, item: &str) -> Result<BTreeMap<String, String>> {
+    let (mut default, mut selected) = (BTreeMap::new(), BTreeMap::new());
+    for line in natspec {
+        let setting = match line.strip_prefix(INLINE_CONFIG_PREFIX) {
+            Some(setting) => setting,
+            None => continue,
+        };
+        let (key, value) = setting
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!("Invalid inline config of {item}: `{line}`"))?;
+        let (line_profile, key) = key.trim().split_once('.').unwrap_or(("", key.trim()));
+        let settings = if line_profile == "default" {
+            &mut default
+        } else if line_profile == profile {
+            &mut selected
+        } else {
+            continue
+        };
+        settings.insert(key.to_string(), value.trim().to_string());
+    }
+    default.extend(selected);
+    Ok(default)
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str, item: &str) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .wrap_err_with(|| format!("Invalid value `{value}` of `{key}` in inline config of {item}"))
+}
+
+/// Returns the index of the line that declares the contract `contract`
+fn contract_declaration(lines: &[&str], contract: &str) -> Option<usize> {
+    lines.iter().position(|line| {
+        let line = line.trim_start();
+        let line = line.strip_prefix("abstract ").unwrap_or(line).trim_start();
+        line.strip_prefix("contract ").map_or(false, |rest| starts_with_ident(rest, contract))
+    })
+}
+
+/// Returns the index of the first line after `start` that declares the function `function`
+fn function_declaration(lines: &[&str], start: usize, function: &str) -> Option<usize> {
+    let idx = lines[start..].iter().position(|line| {
+        line.trim_start()
+            .strip_prefix("function ")
+            .map_or(false, |rest| starts_with_ident(rest, function))
+    })?;
+    Some(start + idx)
+}
+
+/// Returns true if `s` starts with the identifier `ident`, and not just with a prefix of it
+fn starts_with_ident(s: &str, ident: &str) -> bool {
+    s.trim_start().strip_prefix(ident).map_or(false, |rest| {
+        !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$')
+    })
+}
+
+/// Returns the natspec lines directly above the line `idx`, without the comment markers
+fn natspec<'a>(lines: &[&'a str], idx: usize) -> Vec<&'a str> {
+    let mut natspec: Vec<&str> = lines[..idx]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .take_while(|line| line.starts_with('/') || line.starts_with('*'))
+        .map(|line| line.trim_start_matches(|c| c == '/' || c == '*').trim())
+        .collect();
+    natspec.reverse();
+    natspec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_inline_fork_config() {
+        let source = r#"
+/// forge-config: default.fork.url = "mainnet"
+/// forge-config: default.fork.block = 15000000
+/// forge-config: ci.fork.block = 16000000
+contract ForkTest {}
+
+/**
+ * @notice not forked
+ */
+contract LocalTest {}
+
+/// forge-config: default.fork.url = "optimism"
+
+abstract contract Detached {}
+"#;
+        let config = InlineForkConfig::parse(source, "ForkTest", "default").unwrap();
+        assert_eq!(config.url.as_deref(), Some("mainnet"));
+        assert_eq!(config.block, Some(15000000));
+        let config = InlineForkConfig::parse(source, "ForkTest", "ci").unwrap();
+        assert_eq!(config.block, Some(16000000));
+
+        assert!(InlineForkConfig::parse(source, "LocalTest", "default").unwrap().is_empty());
+        assert!(InlineForkConfig::parse(source, "Detached", "default").unwrap().is_empty());
+        assert!(InlineForkConfig::parse(source, "Fork", "default").unwrap().is_empty());
+
+        let invalid = "/// forge-config: default.fork.block = latest\ncontract A {}";
+        assert!(InlineForkConfig::parse(invalid, "A", "default").is_err());
+    }
+
+    #[test]
+    fn can_parse_inline_fuzz_config() {
+        let source = r#"
+/// forge-config: default.fuzz.runs = 100
+/// forge-config: default.fuzz.max_global_rejects = 1000
+contract VaultTest {
+    /// forge-config: default.fuzz.runs = 5000
+    /// forge-config: ci.fuzz.runs = 10000
+    /// forge-config: default.fuzz.dictionary_weight = 80
+    function testDeposit(uint256 amount) public {}
+
+    function testDepositAll(uint256 amount) public {}
+}
+
+contract PoolTest {
+    /// forge-config: default.fuzz.runs = many
+    function testSwap(uint256 amount) public {}
+}
+"#;
+        let config =
+            InlineFuzzConfig::parse(source, "VaultTest", "testDeposit", "default").unwrap();
+        assert_eq!(config.runs, Some(5000));
+        assert_eq!(config.max_global_rejects, Some(1000));
+        assert_eq!(config.dictionary_weight, Some(80));
+        let config = InlineFuzzConfig::parse(source, "VaultTest", "testDeposit", "ci").unwrap();
+        assert_eq!(config.runs, Some(10000));
+        let config =
+            InlineFuzzConfig::parse(source, "VaultTest", "testDepositAll", "default").unwrap();
+        assert_eq!(config.runs, Some(100));
+
+        let mut fuzz = proptest::test_runner::Config::default();
+        config.apply(&mut fuzz);
+        assert_eq!((fuzz.cases, fuzz.max_global_rejects), (100, 1000));
+
+        assert!(InlineFuzzConfig::parse(source, "PoolTest", "testSwap", "default").is_err());
+        assert!(InlineFuzzConfig::parse(source, "Missing", "testSwap", "default")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -9,15 +9,86 @@ pub use runner::{ContractRunner, SuiteResult, TestKind, TestKindGas, TestResult}
 mod multi_runner;
 pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder};
 
+/// Caching of test results across runs
+pub mod result_cache;
+
+/// Coverage analysis and reports
+pub mod coverage;
+
+/// Benchmarks of `bench` functions and their comparison with a baseline
+pub mod bench;
+
+/// Mutants of source files for mutation testing
+pub mod mutation;
+
+/// Source-mapped stack traces of reverted tests
+pub mod stack_trace;
+
+/// Config of single test contracts in their natspec
+pub mod inline_config;
+
+/// Findings of external static analyzers
+pub mod analysis;
+
 pub trait TestFilter {
     fn matches_test(&self, test_name: impl AsRef<str>) -> bool;
     fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool;
     fn matches_path(&self, path: impl AsRef<str>) -> bool;
+
+    /// Returns true if the test `test_name` of the contract `contract_name`, declared in the source
+    /// file `path`, should be run.
+    ///
+    /// By default this checks each part independently, filters that address a single test by its
+    /// fully qualified id can override this.
+    fn matches_qualified_test(
+        &self,
+        path: impl AsRef<str>,
+        contract_name: impl AsRef<str>,
+        test_name: impl AsRef<str>,
+    ) -> bool {
+        self.matches_path(path) &&
+            self.matches_contract(contract_name) &&
+            self.matches_test(test_name)
+    }
+}
+
+/// Returns true if `name` matches any of the glob patterns, e.g. `Mock*`
+///
+/// Patterns that are not valid globs only match if they are equal to `name`. Paths are matched
+/// with `/` separators, also on Windows.
+pub fn matches_any_glob(patterns: &[String], name: &str) -> bool {
+    let name = foundry_config::slash_path(name);
+    let name = name.as_str();
+    patterns.iter().any(|pattern| {
+        glob::Pattern::new(pattern).map_or(pattern == name, |pattern| pattern.matches(name))
+    })
 }
 
 /// The Forge EVM backend
 pub use foundry_evm::*;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn can_match_globs() {
+        let patterns = vec!["src/mocks/*".to_string(), "Mock*".to_string()];
+        assert!(matches_any_glob(&patterns, "src/mocks/Token.sol"));
+        assert!(matches_any_glob(&patterns, "MockToken"));
+        assert!(!matches_any_glob(&patterns, "src/Token.sol"));
+
+        // native separators, `\` on Windows
+        let native = Path::new("src").join("mocks").join("Token.sol");
+        assert!(matches_any_glob(&patterns, &native.to_string_lossy()));
+
+        // invalid globs only match the exact name
+        assert!(matches_any_glob(&["[Token".to_string()], "[Token"));
+        assert!(!matches_any_glob(&["[Token".to_string()], "Token"));
+    }
+}
+
 #[cfg(test)]
 pub mod test_helpers {
     use crate::TestFilter;
@@ -26,6 +97,7 @@ pub mod test_helpers {
         solc::{artifacts::Libraries, utils::RuntimeOrHandle, Project, ProjectPathsConfig},
         types::{Address, U256},
     };
+    use foundry_config::{FsAccessPermission, FsPermissions, PathPermission, RpcEndpoints};
     use foundry_evm::{
         executor::{
             builder::Backend,
@@ -84,8 +156,14 @@ pub mod test_helpers {
         },
         sender: Address::from_str("00a329c0648769a73afac7f9381e08fb43dbea72").unwrap(),
         initial_balance: U256::MAX,
+        labeled_account_balance: U256::exp10(20),
         ffi: true,
         memory_limit: 2u64.pow(24),
+        rpc_endpoints: RpcEndpoints::new([("mainnet", "https://example.com/mainnet")]),
+        fs_permissions: FsPermissions::new([PathPermission::new(
+            FsAccessPermission::ReadWrite,
+            "./fixtures",
+        )]),
         ..Default::default()
     });
 
@@ -1,4 +1,10 @@
-use crate::{ContractRunner, SuiteResult, TestFilter};
+use crate::{
+    bench::{BenchConfig, BenchResults},
+    inline_config::{InlineForkConfig, InlineFuzzConfig},
+    result_cache::{self, TestResultsCache},
+    runner::{is_bench_function, is_deterministic_function, is_test_function},
+    ContractRunner, SuiteResult, TestFilter, TestResult,
+};
 use ethers::{
     abi::Abi,
     prelude::{artifacts::CompactContractBytecode, ArtifactId, ArtifactOutput},
@@ -6,13 +12,24 @@ use ethers::{
     types::{Address, Bytes, U256},
 };
 use eyre::Result;
-use foundry_evm::executor::{
-    builder::Backend, opts::EvmOpts, DatabaseRef, Executor, ExecutorBuilder, Fork, SpecId,
+use foundry_config::{cache::StorageCachingConfig, Config, InvariantConfig};
+use foundry_evm::{
+    executor::{
+        builder::Backend, fork::MultiFork, inspector::ExecutionContext, opts::EvmOpts, DatabaseRef,
+        Env, Executor, ExecutorBuilder, Fork, SpecId,
+    },
+    fuzz::DEFAULT_DICTIONARY_WEIGHT,
 };
 use foundry_utils::PostLinkInput;
 use proptest::test_runner::TestRunner;
-use rayon::prelude::*;
-use std::{collections::BTreeMap, marker::Sync, path::Path, sync::mpsc::Sender};
+use rayon::{prelude::*, ThreadPoolBuilder};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::Sync,
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
+    time::Duration,
+};
 
 /// Builder used for instantiating the multi-contract runner
 #[derive(Debug, Default)]
@@ -28,6 +45,30 @@ pub struct MultiContractRunnerBuilder {
     pub evm_spec: Option<SpecId>,
     /// The fork config
     pub fork: Option<Fork>,
+    /// Which chains and endpoints the storage of forks is cached for, also used for the forks
+    /// test contracts pin in their natspec
+    pub rpc_storage_caching: StorageCachingConfig,
+    /// Results of previous runs, used to skip tests whose inputs did not change
+    pub result_cache: Option<TestResultsCache>,
+    /// Recorded fuzz inputs to replay, `artifact identifier -> test signature -> calldata`
+    pub replay_inputs: BTreeMap<String, BTreeMap<String, Bytes>>,
+    /// The directory fuzz corpora are persisted to
+    pub fuzz_corpus_dir: Option<PathBuf>,
+    /// The percentage of fuzz inputs drawn from the fuzz dictionary, defaults to
+    /// [DEFAULT_DICTIONARY_WEIGHT]
+    pub fuzz_dictionary_weight: Option<u32>,
+    /// The settings for invariant tests
+    pub invariant_config: InvariantConfig,
+    /// Constructor arguments of parameterized test contracts, `contract name -> argument sets`
+    pub constructor_args: BTreeMap<String, Vec<Vec<String>>>,
+    /// The directory the JSON fixtures of table tests are read from
+    pub table_fixtures_dir: Option<PathBuf>,
+    /// Whether to collect coverage info
+    pub coverage: bool,
+    /// The number of threads test contracts are run on, defaults to the number of CPUs
+    pub jobs: Option<usize>,
+    /// The maximum wall-clock time of a single test
+    pub timeout: Option<Duration>,
 }
 
 pub type DeployableContracts = BTreeMap<ArtifactId, (Abi, Bytes, Vec<Bytes>)>;
@@ -44,10 +85,12 @@ impl MultiContractRunnerBuilder {
     where
         A: ArtifactOutput,
     {
+        let root = root.as_ref().to_path_buf();
+
         // This is just the contracts compiled, but we need to merge this with the read cached
         // artifacts
         let contracts = output
-            .with_stripped_file_prefixes(root)
+            .with_stripped_file_prefixes(&root)
             .into_artifacts()
             .map(|(i, c)| (i, c.into_contract_bytecode()))
             .collect::<Vec<(ArtifactId, CompactContractBytecode)>>();
@@ -85,12 +128,46 @@ impl MultiContractRunnerBuilder {
                     };
 
                 let abi = contract.abi.expect("We should have an abi by now");
-                // if its a test, add it to deployable contracts
-                if abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true) &&
-                    abi.functions().any(|func| func.name.starts_with("test"))
-                {
-                    deployable_contracts
-                        .insert(id.clone(), (abi.clone(), bytecode, dependencies.to_vec()));
+                // if its a test or has benchmarks, add it to deployable contracts
+                if abi.functions().any(|func| is_test_function(func) || is_bench_function(func)) {
+                    match abi.constructor.as_ref().filter(|c| !c.inputs.is_empty()) {
+                        None => {
+                            deployable_contracts.insert(
+                                id.clone(),
+                                (abi.clone(), bytecode, dependencies.to_vec()),
+                            );
+                        }
+                        // parameterized test contracts are deployed once per argument set
+                        Some(constructor) => {
+                            let sets = self.constructor_args.get(&id.name).into_iter().flatten();
+                            for (idx, args) in sets.enumerate() {
+                                if args.len() != constructor.inputs.len() {
+                                    eyre::bail!(
+                                        "Constructor argument set {} of {} has {} arguments, \
+                                         expected {}",
+                                        idx,
+                                        id.name,
+                                        args.len(),
+                                        constructor.inputs.len()
+                                    )
+                                }
+                                let params = constructor
+                                    .inputs
+                                    .iter()
+                                    .map(|input| &input.kind)
+                                    .zip(args.iter().map(String::as_str));
+                                let tokens = foundry_utils::parse_tokens(params, true)?;
+                                let code = constructor.encode_input(bytecode.to_vec(), &tokens)?;
+
+                                let mut instance = id.clone();
+                                instance.name = format!("{}[{}]", id.name, idx);
+                                deployable_contracts.insert(
+                                    instance,
+                                    (abi.clone(), code.into(), dependencies.to_vec()),
+                                );
+                            }
+                        }
+                    }
                 }
 
                 contract
@@ -104,8 +181,9 @@ impl MultiContractRunnerBuilder {
 
         let execution_info = foundry_utils::flatten_known_contracts(&known_contracts);
         Ok(MultiContractRunner {
+            root,
             contracts: deployable_contracts,
-            known_contracts,
+            known_contracts: Arc::new(known_contracts),
             evm_opts,
             evm_spec: self.evm_spec.unwrap_or(SpecId::LONDON),
             sender: self.sender,
@@ -113,6 +191,18 @@ impl MultiContractRunnerBuilder {
             errors: Some(execution_info.2),
             source_paths,
             fork: self.fork,
+            rpc_storage_caching: self.rpc_storage_caching,
+            result_cache: self.result_cache,
+            replay_inputs: self.replay_inputs,
+            fuzz_corpus_dir: self.fuzz_corpus_dir,
+            fuzz_dictionary_weight: self
+                .fuzz_dictionary_weight
+                .unwrap_or(DEFAULT_DICTIONARY_WEIGHT),
+            invariant_config: self.invariant_config,
+            table_fixtures_dir: self.table_fixtures_dir,
+            coverage: self.coverage,
+            jobs: self.jobs,
+            timeout: self.timeout,
         })
     }
 
@@ -145,16 +235,93 @@ impl MultiContractRunnerBuilder {
         self.fork = fork;
         self
     }
+
+    #[must_use]
+    pub fn with_rpc_storage_caching(mut self, rpc_storage_caching: StorageCachingConfig) -> Self {
+        self.rpc_storage_caching = rpc_storage_caching;
+        self
+    }
+
+    #[must_use]
+    pub fn with_result_cache(mut self, result_cache: Option<TestResultsCache>) -> Self {
+        self.result_cache = result_cache;
+        self
+    }
+
+    #[must_use]
+    pub fn with_replay_inputs(
+        mut self,
+        replay_inputs: BTreeMap<String, BTreeMap<String, Bytes>>,
+    ) -> Self {
+        self.replay_inputs = replay_inputs;
+        self
+    }
+
+    #[must_use]
+    pub fn with_fuzz_corpus_dir(mut self, fuzz_corpus_dir: Option<PathBuf>) -> Self {
+        self.fuzz_corpus_dir = fuzz_corpus_dir;
+        self
+    }
+
+    #[must_use]
+    pub fn with_fuzz_dictionary_weight(mut self, weight: u32) -> Self {
+        self.fuzz_dictionary_weight = Some(weight);
+        self
+    }
+
+    #[must_use]
+    pub fn with_invariant_config(mut self, invariant_config: InvariantConfig) -> Self {
+        self.invariant_config = invariant_config;
+        self
+    }
+
+    #[must_use]
+    pub fn with_constructor_args(
+        mut self,
+        constructor_args: BTreeMap<String, Vec<Vec<String>>>,
+    ) -> Self {
+        self.constructor_args = constructor_args;
+        self
+    }
+
+    #[must_use]
+    pub fn with_table_fixtures_dir(mut self, table_fixtures_dir: Option<PathBuf>) -> Self {
+        self.table_fixtures_dir = table_fixtures_dir;
+        self
+    }
+
+    #[must_use]
+    pub fn set_coverage(mut self, enable: bool) -> Self {
+        self.coverage = enable;
+        self
+    }
+
+    #[must_use]
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
 }
 
 /// A multi contract runner receives a set of contracts deployed in an EVM instance and proceeds
 /// to run all test functions in these contracts.
 pub struct MultiContractRunner {
+    /// The project root, relative paths of the file system cheatcodes are resolved against it
+    pub root: PathBuf,
     /// Mapping of contract name to Abi, creation bytecode and library bytecode which
     /// needs to be deployed & linked against
     pub contracts: DeployableContracts,
     /// Compiled contracts by name that have an Abi and runtime bytecode
-    pub known_contracts: BTreeMap<ArtifactId, (Abi, Vec<u8>)>,
+    ///
+    /// They are shared with the trace decoders of the test results, e.g. of the streaming test
+    /// reporter, instead of copying every ABI.
+    pub known_contracts: Arc<BTreeMap<ArtifactId, (Abi, Vec<u8>)>>,
     /// The EVM instance used in the test runner
     pub evm_opts: EvmOpts,
     /// The EVM spec
@@ -169,6 +336,28 @@ pub struct MultiContractRunner {
     pub source_paths: BTreeMap<String, String>,
     /// The fork config
     pub fork: Option<Fork>,
+    /// Which chains and endpoints the storage of forks is cached for
+    pub rpc_storage_caching: StorageCachingConfig,
+    /// Results of previous runs, used to skip tests whose inputs did not change
+    pub result_cache: Option<TestResultsCache>,
+    /// Recorded fuzz inputs to replay, `artifact identifier -> test signature -> calldata`
+    pub replay_inputs: BTreeMap<String, BTreeMap<String, Bytes>>,
+    /// The directory fuzz corpora are persisted to, `<dir>/<source>/<contract>/<test signature>`
+    pub fuzz_corpus_dir: Option<PathBuf>,
+    /// The percentage of fuzz inputs drawn from the fuzz dictionary
+    pub fuzz_dictionary_weight: u32,
+    /// The settings for invariant tests
+    pub invariant_config: InvariantConfig,
+    /// The directory the JSON fixtures of table tests are read from,
+    /// `<dir>/<contract>/<function name>.json`
+    pub table_fixtures_dir: Option<PathBuf>,
+    /// Whether to collect coverage info
+    pub coverage: bool,
+    /// The number of threads test contracts are run on, defaults to the number of CPUs
+    pub jobs: Option<usize>,
+    /// The maximum wall-clock time of a single test, fuzz and invariant tests are aborted when
+    /// it is exceeded
+    pub timeout: Option<Duration>,
 }
 
 impl MultiContractRunner {
@@ -179,10 +368,11 @@ impl MultiContractRunner {
                 filter.matches_path(id.source.to_string_lossy()) &&
                     filter.matches_contract(&id.name)
             })
-            .flat_map(|(_, (abi, _, _))| {
-                abi.functions().filter(|func| filter.matches_test(func.signature()))
+            .map(|(id, (abi, _, _))| {
+                let filter = ArtifactFilter::new(filter, id);
+                abi.functions().filter(|func| filter.matches_test(func.signature())).count()
             })
-            .count()
+            .sum()
     }
 
     // Get all tests of matching path and contract
@@ -193,8 +383,8 @@ impl MultiContractRunner {
                 filter.matches_path(id.source.to_string_lossy()) &&
                     filter.matches_contract(&id.name)
             })
-            .flat_map(|(_, (abi, _, _))| abi.functions().map(|func| func.name.clone()))
-            .filter(|sig| sig.starts_with("test"))
+            .flat_map(|(_, (abi, _, _))| abi.functions().filter(|func| is_test_function(func)))
+            .map(|func| func.name.clone())
             .collect()
     }
 
@@ -208,13 +398,18 @@ impl MultiContractRunner {
                 filter.matches_path(id.source.to_string_lossy()) &&
                     filter.matches_contract(&id.name)
             })
-            .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
+            .filter(|(id, (abi, _, _))| {
+                let filter = ArtifactFilter::new(filter, id);
+                abi.functions()
+                    .any(|func| is_test_function(func) && filter.matches_test(&func.name))
+            })
             .map(|(id, (abi, _, _))| {
                 let source = id.source.as_path().display().to_string();
                 let name = id.name.clone();
+                let filter = ArtifactFilter::new(filter, id);
                 let tests = abi
                     .functions()
-                    .filter(|func| func.name.starts_with("test"))
+                    .filter(|func| is_test_function(func))
                     .filter(|func| filter.matches_test(func.signature()))
                     .map(|func| func.name.clone())
                     .collect::<Vec<_>>();
@@ -234,63 +429,278 @@ impl MultiContractRunner {
         include_fuzz_tests: bool,
     ) -> Result<BTreeMap<String, SuiteResult>> {
         let runtime = RuntimeOrHandle::new();
-        let env = runtime.block_on(self.evm_opts.evm_env());
+        let env = runtime.block_on(self.evm_opts.try_evm_env())?;
 
         // the db backend that serves all the data
         let db = runtime.block_on(Backend::new(self.fork.take(), &env));
 
-        let results = self
-            .contracts
-            .par_iter()
-            .filter(|(id, _)| {
-                filter.matches_path(id.source.to_string_lossy()) &&
-                    filter.matches_contract(&id.name)
-            })
-            .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
-            .map(|(id, (abi, deploy_code, libs))| {
-                let mut builder = ExecutorBuilder::new()
-                    .with_cheatcodes(self.evm_opts.ffi)
-                    .with_config(env.clone())
-                    .with_spec(self.evm_spec)
-                    .with_gas_limit(self.evm_opts.gas_limit());
-
-                if self.evm_opts.verbosity >= 3 {
-                    builder = builder.with_tracing();
+        // forks created by the tests share their backends across all test contracts
+        let multi_fork = MultiFork::new(env.clone()).with_offline(self.evm_opts.offline);
+
+        // test contracts that pin their own fork in their natspec are run on its backend instead,
+        // invalid inline fork configs are reported as warnings like invalid fuzz configs
+        let (inline_forks, inline_fork_warnings) = self.inline_forks(filter, &runtime);
+
+        // every test contract is run with its own executor on one of the pool's threads
+        let run = || {
+            self.contracts
+                .par_iter()
+                .filter(|(id, _)| {
+                    filter.matches_path(id.source.to_string_lossy()) &&
+                        filter.matches_contract(&id.name)
+                })
+                .filter(|(id, (abi, _, _))| {
+                    let filter = ArtifactFilter::new(filter, id);
+                    abi.functions().any(|func| filter.matches_test(&func.name))
+                })
+                .map(|(id, (abi, deploy_code, libs))| {
+                    let (env, db) = inline_forks
+                        .get(&id.identifier())
+                        .map_or((&env, &db), |(env, db)| (env, db));
+                    let mut builder = ExecutorBuilder::new()
+                        .with_cheatcodes(self.evm_opts.ffi)
+                        .with_config(env.clone())
+                        .with_spec(self.evm_spec)
+                        .with_gas_limit(self.evm_opts.gas_limit())
+                        .with_rpc_endpoints(self.evm_opts.rpc_endpoints.resolved())
+                        .with_fs_permissions(&self.root, &self.evm_opts.fs_permissions)
+                        .with_sandbox(self.evm_opts.sandbox)
+                        .with_labeled_account_balance(self.evm_opts.labeled_account_balance)
+                        .with_forks(multi_fork.clone());
+
+                    if self.evm_opts.verbosity >= 3 {
+                        builder = builder.with_tracing();
+                    }
+                    if self.coverage {
+                        builder = builder
+                            .with_coverage()
+                            .with_execution_context(ExecutionContext::Coverage);
+                    }
+
+                    let filter = ArtifactFilter::new(filter, id);
+                    let cached =
+                        self.cached_results(id, deploy_code, libs, &filter, include_fuzz_tests);
+                    let filter = filter.skip(cached.keys().cloned().collect());
+
+                    let needs_run = abi.functions().any(|func| {
+                        is_test_function(func) &&
+                            filter.matches_test(func.signature()) &&
+                            (include_fuzz_tests || is_deterministic_function(func))
+                    });
+                    let mut result = if needs_run {
+                        let executor = builder.build(db.clone());
+                        self.run_tests(
+                            &id.identifier(),
+                            abi,
+                            executor,
+                            deploy_code.clone(),
+                            libs,
+                            (&filter, include_fuzz_tests),
+                        )?
+                    } else {
+                        SuiteResult::new(Duration::ZERO, BTreeMap::new(), Vec::new())
+                    };
+                    for (sig, cached) in cached {
+                        result.test_results.entry(sig).or_insert(cached);
+                    }
+                    if let Some(warning) = inline_fork_warnings.get(&id.identifier()) {
+                        result.warnings.push(warning.clone());
+                    }
+                    Ok((id.identifier(), result))
+                })
+                .filter_map(Result::<_>::ok)
+                .filter(|(_, results)| !results.is_empty())
+                .map_with(stream_result, |stream_result, (name, result)| {
+                    if let Some(stream_result) = stream_result.as_ref() {
+                        stream_result.send((name.clone(), result.clone())).unwrap();
+                    }
+                    (name, result)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let results = match self.jobs {
+            Some(jobs) => ThreadPoolBuilder::new().num_threads(jobs).build()?.install(run),
+            None => run(),
+        };
+
+        if let Some(cache) = self.result_cache.as_mut() {
+            for (id, (_, deploy_code, libs)) in self.contracts.iter() {
+                let identifier = id.identifier();
+                if let Some(result) = results.get(&identifier) {
+                    cache.insert(identifier, result_cache::fingerprint(deploy_code, libs), result);
                 }
+            }
+        }
+        Ok(results)
+    }
 
-                let executor = builder.build(db.clone());
-                let result = self.run_tests(
-                    &id.identifier(),
-                    abi,
-                    executor,
-                    deploy_code.clone(),
-                    libs,
-                    (filter, include_fuzz_tests),
-                )?;
-                Ok((id.identifier(), result))
-            })
-            .filter_map(Result::<_>::ok)
-            .filter(|(_, results)| !results.is_empty())
-            .map_with(stream_result, |stream_result, (name, result)| {
-                if let Some(stream_result) = stream_result.as_ref() {
-                    stream_result.send((name.clone(), result.clone())).unwrap();
+    /// Runs the benchmarks of all contracts that match the `filter`
+    ///
+    /// Contracts are run one after another, so the wall times are not distorted by other
+    /// benchmarks running at the same time.
+    pub fn bench(
+        &mut self,
+        filter: &(impl TestFilter + Send + Sync),
+        config: BenchConfig,
+    ) -> Result<BenchResults> {
+        let runtime = RuntimeOrHandle::new();
+        let env = runtime.block_on(self.evm_opts.try_evm_env())?;
+        let db = runtime.block_on(Backend::new(self.fork.take(), &env));
+        let multi_fork = MultiFork::new(env.clone()).with_offline(self.evm_opts.offline);
+
+        let mut results = BenchResults::new();
+        for (id, (abi, deploy_code, libs)) in self.contracts.iter() {
+            if !(filter.matches_path(id.source.to_string_lossy()) &&
+                filter.matches_contract(&id.name))
+            {
+                continue
+            }
+            let filter = ArtifactFilter::new(filter, id);
+            let has_benches = abi
+                .functions()
+                .any(|func| is_bench_function(func) && filter.matches_test(&func.name));
+            if !has_benches {
+                continue
+            }
+
+            let executor = ExecutorBuilder::new()
+                .with_cheatcodes(self.evm_opts.ffi)
+                .with_config(env.clone())
+                .with_spec(self.evm_spec)
+                .with_gas_limit(self.evm_opts.gas_limit())
+                .with_rpc_endpoints(self.evm_opts.rpc_endpoints.resolved())
+                .with_fs_permissions(&self.root, &self.evm_opts.fs_permissions)
+                .with_sandbox(self.evm_opts.sandbox)
+                .with_labeled_account_balance(self.evm_opts.labeled_account_balance)
+                .with_forks(multi_fork.clone())
+                .build(db.clone());
+            let mut runner = ContractRunner::new(
+                executor,
+                abi,
+                deploy_code.clone(),
+                self.evm_opts.initial_balance,
+                self.sender,
+                self.errors.as_ref(),
+                libs,
+            );
+            let result = runner.run_benchmarks(&filter, config)?;
+            if !result.is_empty() {
+                results.insert(id.identifier(), result);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Returns the env and backend of every test contract matching the `filter` that pins its own
+    /// fork in its natspec, and the errors of invalid inline fork configs, by contract identifier
+    ///
+    /// Contracts that pin the same fork share its backend, contracts with an invalid config run on
+    /// the global fork. Inline forks are ignored in sandbox mode, which disables forking.
+    fn inline_forks(
+        &self,
+        filter: &impl TestFilter,
+        runtime: &RuntimeOrHandle,
+    ) -> (BTreeMap<String, (Env, Backend)>, BTreeMap<String, String>) {
+        let mut inline_forks = BTreeMap::new();
+        let mut warnings = BTreeMap::new();
+        if self.evm_opts.sandbox {
+            return (inline_forks, warnings)
+        }
+        let profile = Config::selected_profile();
+        let mut backends: BTreeMap<(String, Option<u64>), (Env, Backend)> = BTreeMap::new();
+        for id in self.contracts.keys() {
+            if !(filter.matches_path(id.source.to_string_lossy()) &&
+                filter.matches_contract(&id.name))
+            {
+                continue
+            }
+            let source = match std::fs::read_to_string(self.root.join(&id.source)) {
+                Ok(source) => source,
+                Err(_) => continue,
+            };
+            // instances of parameterized test contracts are named `<name>[<idx>]`
+            let name = id.name.split('[').next().unwrap_or(&id.name);
+            let fork = InlineForkConfig::parse(&source, name, profile.as_str().as_str()).and_then(
+                |config| {
+                    if config.is_empty() {
+                        return Ok(None)
+                    }
+                    let url = match config.url.or_else(|| self.evm_opts.fork_url.clone()) {
+                        Some(url) if url.contains("://") => url,
+                        Some(alias) => {
+                            self.evm_opts.rpc_endpoints.resolved().get(&alias).map_err(|err| {
+                                eyre::eyre!("Failed to resolve the fork of {}: {err}", id.name)
+                            })?
+                        }
+                        None => eyre::bail!("{} pins a fork block but no fork url", id.name),
+                    };
+                    Ok(Some((url, config.block)))
+                },
+            );
+            let (url, block) = match fork {
+                Ok(Some(fork)) => fork,
+                Ok(None) => continue,
+                Err(err) => {
+                    warnings.insert(id.identifier(), format!("{err:#}"));
+                    continue
                 }
-                (name, result)
+            };
+
+            let key = (url.clone(), block);
+            if !backends.contains_key(&key) {
+                let mut evm_opts = self.evm_opts.clone();
+                evm_opts.fork_url = Some(url);
+                evm_opts.fork_block_number = block;
+                let env = match runtime.block_on(evm_opts.try_evm_env()) {
+                    Ok(env) => env,
+                    Err(err) => {
+                        warnings.insert(id.identifier(), format!("{err:#}"));
+                        continue
+                    }
+                };
+                let fork = evm_opts.get_fork(&self.rpc_storage_caching);
+                let db = runtime.block_on(Backend::new(fork, &env));
+                backends.insert(key.clone(), (env, db));
+            }
+            inline_forks.insert(id.identifier(), backends[&key].clone());
+        }
+        (inline_forks, warnings)
+    }
+
+    /// Returns the cached results of all tests of the contract `id` that match the `filter`,
+    /// provided the contract did not change since they were cached
+    fn cached_results(
+        &self,
+        id: &ArtifactId,
+        deploy_code: &Bytes,
+        libs: &[Bytes],
+        filter: &impl TestFilter,
+        include_fuzz_tests: bool,
+    ) -> BTreeMap<String, TestResult> {
+        let fingerprint = result_cache::fingerprint(deploy_code, libs);
+        self.result_cache
+            .as_ref()
+            .and_then(|cache| cache.suite(&id.identifier(), &fingerprint))
+            .into_iter()
+            .flatten()
+            .filter(|(sig, result)| {
+                (sig.starts_with("test") || sig.starts_with("invariant")) &&
+                    filter.matches_test(sig) &&
+                    (include_fuzz_tests || !(result.is_fuzz() || result.is_invariant()))
             })
-            .collect::<BTreeMap<_, _>>();
-        Ok(results)
+            .map(|(sig, result)| (sig.clone(), TestResult { cached: true, ..result.clone() }))
+            .collect()
     }
 
-    // The _name field is unused because we only want it for tracing
     #[tracing::instrument(
         name = "contract",
         skip_all,
         err,
-        fields(name = %_name)
+        fields(name = %name)
     )]
-    fn run_tests<DB: DatabaseRef + Send + Sync>(
+    fn run_tests<DB: DatabaseRef + Clone + Send + Sync>(
         &self,
-        _name: &str,
+        name: &str,
         contract: &Abi,
         executor: Executor<DB>,
         deploy_code: Bytes,
@@ -306,7 +716,121 @@ impl MultiContractRunner {
             self.errors.as_ref(),
             libs,
         );
-        runner.run_tests(filter, self.fuzzer.clone(), include_fuzz_tests)
+        if let Some(inputs) = self.replay_inputs.get(name) {
+            runner.replay_inputs = inputs.clone();
+        }
+        runner.corpus_dir = self.fuzz_corpus_dir.as_ref().map(|dir| corpus_dir(dir, name));
+        runner.table_fixtures_dir = self
+            .table_fixtures_dir
+            .as_ref()
+            .map(|dir| dir.join(name.rsplit(':').next().unwrap_or(name)));
+        runner.fuzz_dictionary_weight = self.fuzz_dictionary_weight;
+        runner.invariant_config = self.invariant_config;
+        runner.timeout = self.timeout;
+        runner.known_contracts = Some(self.known_contracts.as_ref());
+        runner.name = name.to_string();
+
+        // invalid inline configs are reported as warnings, the tests run with the global config
+        let mut warnings = Vec::new();
+        match self.inline_fuzz_configs(name, contract) {
+            Ok(configs) => runner.inline_fuzz_configs = configs,
+            Err(err) => warnings.push(format!("{err:#}")),
+        }
+        let mut result = runner.run_tests(filter, self.fuzzer.clone(), include_fuzz_tests)?;
+        result.warnings.extend(warnings);
+        Ok(result)
+    }
+
+    /// Returns the fuzz settings the fuzz tests of the contract `name` override in their natspec,
+    /// `test function name -> config`
+    fn inline_fuzz_configs(
+        &self,
+        name: &str,
+        contract: &Abi,
+    ) -> Result<BTreeMap<String, InlineFuzzConfig>> {
+        let mut configs = BTreeMap::new();
+        let (path, contract_name) = match name.rsplit_once(':') {
+            Some(parts) => parts,
+            None => return Ok(configs),
+        };
+        let source = match std::fs::read_to_string(self.root.join(path)) {
+            Ok(source) => source,
+            Err(_) => return Ok(configs),
+        };
+        // instances of parameterized test contracts are named `<name>[<idx>]`
+        let contract_name = contract_name.split('[').next().unwrap_or(contract_name);
+        let profile = Config::selected_profile();
+        for func in contract.functions().filter(|func| is_test_function(func)) {
+            if func.inputs.is_empty() || configs.contains_key(&func.name) {
+                continue
+            }
+            let config = InlineFuzzConfig::parse(
+                &source,
+                contract_name,
+                &func.name,
+                profile.as_str().as_str(),
+            )?;
+            if !config.is_empty() {
+                configs.insert(func.name.clone(), config);
+            }
+        }
+        Ok(configs)
+    }
+}
+
+/// A [`TestFilter`] scoped to the tests of a single artifact.
+///
+/// Test names are matched via [`TestFilter::matches_qualified_test`] so that filters addressing
+/// tests by their fully qualified id can take the source file and contract into account.
+struct ArtifactFilter<'a, F> {
+    filter: &'a F,
+    path: String,
+    contract_name: &'a str,
+    /// Tests that should not be run, because their results are already known
+    skip: BTreeSet<String>,
+}
+
+impl<'a, F: TestFilter> ArtifactFilter<'a, F> {
+    fn new(filter: &'a F, id: &'a ArtifactId) -> Self {
+        Self {
+            filter,
+            path: id.source.to_string_lossy().into_owned(),
+            contract_name: &id.name,
+            skip: Default::default(),
+        }
+    }
+
+    fn skip(mut self, skip: BTreeSet<String>) -> Self {
+        self.skip = skip;
+        self
+    }
+}
+
+impl<'a, F: TestFilter> TestFilter for ArtifactFilter<'a, F> {
+    fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
+        let test_name = test_name.as_ref();
+        !self.skip.contains(test_name) &&
+            self.filter.matches_qualified_test(&self.path, self.contract_name, test_name)
+    }
+
+    fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
+        self.filter.matches_contract(contract_name)
+    }
+
+    fn matches_path(&self, path: impl AsRef<str>) -> bool {
+        self.filter.matches_path(path)
+    }
+}
+
+/// Returns the directory the fuzz corpora of the test contract with the artifact `identifier`,
+/// `<source>:<contract>`, are stored in: `<dir>/<source>/<contract>`
+///
+/// The source is part of the path, so contracts of the same name in different files do not share
+/// a corpus.
+fn corpus_dir(dir: &Path, identifier: &str) -> PathBuf {
+    match identifier.rsplit_once(':') {
+        Some((source, contract)) => dir.join(source).join(contract),
+        None => dir.join(identifier),
     }
 }
 
@@ -318,8 +842,9 @@ mod tests {
         test_helpers::{
             filter::Filter, COMPILED, COMPILED_WITH_LIBS, EVM_OPTS, LIBS_PROJECT, PROJECT,
         },
+        TestKind,
     };
-    use foundry_evm::trace::TraceKind;
+    use foundry_evm::{fuzz::CounterExample, trace::TraceKind};
 
     /// Builds a base runner
     fn base_runner() -> MultiContractRunnerBuilder {
@@ -344,9 +869,16 @@ mod tests {
 
         opts.env.chain_id = None; // clear chain id so the correct one gets fetched from the RPC
         opts.fork_url = Some(rpc.to_string());
+        opts.rpc_endpoints = foundry_config::RpcEndpoints::new([("mainnet", rpc)]);
         let chain_id = opts.get_chain_id();
 
-        let fork = Some(Fork { cache_path: None, url: rpc.to_string(), pin_block: None, chain_id });
+        let fork = Some(Fork {
+            cache_path: None,
+            url: rpc.to_string(),
+            pin_block: None,
+            chain_id,
+            offline: false,
+        });
         base_runner()
             .with_fork(fork)
             .build(&(*LIBS_PROJECT).paths.root, (*COMPILED_WITH_LIBS).clone(), opts)
@@ -422,6 +954,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_corpus_dir() {
+        let dir = Path::new("cache/fuzz");
+        let a = corpus_dir(dir, "test/a/Vault.t.sol:VaultTest");
+        let b = corpus_dir(dir, "test/b/Vault.t.sol:VaultTest");
+        assert_eq!(a, Path::new("cache/fuzz/test/a/Vault.t.sol/VaultTest"));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_jobs() {
+        let filter = Filter::new(".*", ".*", ".*core");
+        let outcomes = |results: BTreeMap<String, SuiteResult>| {
+            results
+                .into_iter()
+                .flat_map(|(id, suite)| {
+                    suite
+                        .test_results
+                        .into_iter()
+                        .map(move |(sig, result)| ((id.clone(), sig), result.success))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let results = runner().test(&filter, None, true).unwrap();
+        let mut single_threaded = base_runner()
+            .with_jobs(Some(1))
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let single_threaded_results = single_threaded.test(&filter, None, true).unwrap();
+
+        assert!(!results.is_empty());
+        assert_eq!(outcomes(results), outcomes(single_threaded_results));
+    }
+
     #[test]
     fn test_core() {
         let mut runner = runner();
@@ -1028,6 +1595,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_snapshot_logs() {
+        let mut runner = runner();
+        let results = runner
+            .test(&Filter::new("testRevertToRemovesLogs", ".*", ".*cheats"), None, true)
+            .unwrap();
+
+        let suite = &results["cheats/Snapshot.t.sol:SnapshotTest"];
+        let result = &suite.test_results["testRevertToRemovesLogs()"];
+        assert!(result.success, "{:?}", result.reason);
+        assert_eq!(decode_console_logs(&result.logs), vec!["before"]);
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut runner = base_runner()
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone().sandboxed())
+            .unwrap();
+        let filter =
+            Filter::new("^(testFfi|testReadFile|testEnvUint|testRpcUrl)$", ".*", ".*cheats");
+        let suite_result = runner.test(&filter, None, true).unwrap();
+
+        let results: Vec<_> =
+            suite_result.into_values().flat_map(|suite| suite.test_results).collect();
+        assert_eq!(results.len(), 4);
+        for (test_name, result) in results {
+            assert!(!result.success, "Test {} passed in sandbox mode", test_name);
+            assert!(
+                result.reason.as_deref().unwrap_or_default().contains("sandbox mode"),
+                "Test {} failed for another reason: {:?}",
+                test_name,
+                result.reason
+            );
+        }
+    }
+
+    #[test]
+    fn test_bench() {
+        let mut runner = runner();
+        let config = BenchConfig { warmup: 1, iterations: 3 };
+        let results = runner.bench(&Filter::new(".*", ".*", ".*bench"), config).unwrap();
+
+        let results = &results["bench/Bench.t.sol:BenchTest"];
+        assert_eq!(results.len(), 3);
+        let increment = &results["benchIncrement()"];
+        assert!(increment.success());
+        assert_eq!(increment.gas.len(), 3);
+        assert_eq!(increment.time.len(), 3);
+        // every run starts from the state after the setup
+        assert!(increment.gas.iter().all(|gas| *gas == increment.gas[0]));
+        assert!(results["benchLoop()"].gas[0] > increment.gas[0]);
+        assert_eq!(results["benchRevert()"].reason.as_deref(), Some("not benchmarkable"));
+
+        // benchmarks are not run as tests
+        let tests = runner.test(&Filter::new(".*", ".*", ".*bench"), None, true).unwrap();
+        assert!(tests.is_empty());
+    }
+
     #[test]
     fn test_fuzz() {
         let mut runner = runner();
@@ -1057,6 +1682,194 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_timeout() {
+        let mut runner = base_runner()
+            .with_timeout(Some(Duration::ZERO))
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let results = runner
+            .test(&Filter::new("testSuccessfulFuzz", ".*", ".*fuzz"), None, true)
+            .unwrap();
+
+        let suite = &results["fuzz/Fuzz.t.sol:FuzzTest"];
+        let result = &suite.test_results["testSuccessfulFuzz(uint128,uint128)"];
+        assert!(!result.success);
+        assert_eq!(result.reason, Some("Timed out after 0ns, 0 runs completed".to_string()));
+        assert!(result.counterexample.is_none());
+    }
+
+    #[test]
+    fn test_invariant() {
+        let mut runner = base_runner()
+            .fuzzer(TestRunner::new(Default::default()))
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let results = runner.test(&Filter::new(".*", ".*", ".*invariant"), None, true).unwrap();
+
+        assert_multiple(
+            &results,
+            BTreeMap::from([
+                (
+                    "invariant/Invariant.t.sol:InvariantBreakerTest",
+                    vec![("invariantNeverFalse()", false, Some("false.".to_string()), None, None)],
+                ),
+                (
+                    "invariant/Invariant.t.sol:InvariantCounterTest",
+                    vec![("invariantCountIsBounded()", true, None, None, None)],
+                ),
+                (
+                    "invariant/Invariant.t.sol:InvariantRevertTest",
+                    vec![("invariantSumIsEven()", true, None, None, None)],
+                ),
+                (
+                    "invariant/InvariantLogs.t.sol:InvariantLogsTest",
+                    vec![("invariantSupplyMatchesTransfers()", true, None, None, None)],
+                ),
+                (
+                    "invariant/InvariantLogs.t.sol:InvariantLogsBreakerTest",
+                    vec![(
+                        "invariantTrue()",
+                        false,
+                        Some("afterInvariant failed: supply mismatch".to_string()),
+                        None,
+                        None,
+                    )],
+                ),
+            ]),
+        );
+
+        let result = &results["invariant/Invariant.t.sol:InvariantBreakerTest"].test_results
+            ["invariantNeverFalse()"];
+        assert!(matches!(result.counterexample, Some(CounterExample::Sequence(_))));
+
+        // reverting calls are recorded, the sequence continues
+        let result = &results["invariant/Invariant.t.sol:InvariantRevertTest"].test_results
+            ["invariantSumIsEven()"];
+        match &result.kind {
+            TestKind::Invariant { reverts, reverted_calls, .. } => {
+                assert!(*reverts > 0);
+                assert_eq!(reverted_calls.get("RevertingHandler.add"), Some(reverts));
+            }
+            _ => panic!("expected an invariant test"),
+        }
+    }
+
+    #[test]
+    fn test_invariant_fail_on_revert() {
+        let mut runner = base_runner()
+            .fuzzer(TestRunner::new(Default::default()))
+            .with_invariant_config(InvariantConfig { fail_on_revert: true, ..Default::default() })
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let results = runner
+            .test(&Filter::new(".*", "InvariantRevertTest", ".*invariant"), None, true)
+            .unwrap();
+
+        assert_multiple(
+            &results,
+            BTreeMap::from([(
+                "invariant/Invariant.t.sol:InvariantRevertTest",
+                vec![(
+                    "invariantSumIsEven()",
+                    false,
+                    Some("RevertingHandler.add reverted: odd".to_string()),
+                    None,
+                    None,
+                )],
+            )]),
+        );
+    }
+
+    #[test]
+    fn test_parameterized() {
+        let mut runner = base_runner()
+            .with_constructor_args(BTreeMap::from([(
+                "ParameterizedTest".to_string(),
+                vec![
+                    vec!["6".to_string(), "USDC".to_string()],
+                    vec!["18".to_string(), "DAI".to_string()],
+                ],
+            )]))
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let results = runner
+            .test(&Filter::new(".*", "ParameterizedTest", ".*core"), None, true)
+            .unwrap();
+
+        assert_multiple(
+            &results,
+            BTreeMap::from([
+                (
+                    "core/Parameterized.t.sol:ParameterizedTest[0]",
+                    vec![
+                        ("testScale()", true, None, None, None),
+                        ("testSixDecimals()", true, None, None, None),
+                    ],
+                ),
+                (
+                    "core/Parameterized.t.sol:ParameterizedTest[1]",
+                    vec![
+                        ("testScale()", true, None, None, None),
+                        (
+                            "testSixDecimals()",
+                            false,
+                            Some("DAI does not have 6 decimals".to_string()),
+                            None,
+                            None,
+                        ),
+                    ],
+                ),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let mut runner = base_runner()
+            .with_table_fixtures_dir(Some(PROJECT.paths.root.join("fixtures")))
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let results = runner.test(&Filter::new(".*", ".*", ".*table"), None, false).unwrap();
+
+        assert_multiple(
+            &results,
+            BTreeMap::from([
+                (
+                    "table/Table.t.sol:TableTest",
+                    vec![
+                        ("tableTestTransfer(uint256,address)[0]", true, None, None, None),
+                        ("tableTestTransfer(uint256,address)[1]", true, None, None, None),
+                        (
+                            "tableTestTransfer(uint256,address)[2]",
+                            false,
+                            Some("amount too large".to_string()),
+                            None,
+                            None,
+                        ),
+                        (
+                            "tableTestMissingFixture(uint256)",
+                            false,
+                            Some(
+                                "No fixture found for parameter `value`, expected `fixtureValue()`"
+                                    .to_string(),
+                            ),
+                            None,
+                            None,
+                        ),
+                    ],
+                ),
+                (
+                    "table/Table.t.sol:TableJsonTest",
+                    vec![
+                        ("tableTestDouble(uint256,uint256)[0]", true, None, None, None),
+                        ("tableTestDouble(uint256,uint256)[1]", true, None, None, None),
+                    ],
+                ),
+            ]),
+        );
+    }
+
     #[test]
     fn test_trace() {
         let mut runner = tracing_runner();
@@ -0,0 +1,310 @@
+use crate::coverage::{source_location, SourceFile, SourceLocation};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The kind of change a mutant makes to the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationOperator {
+    /// Replaces a binary operator, e.g. `+` with `-`
+    SwapOperator,
+    /// Negates the condition of an `if`, a loop, a ternary or a `require`
+    NegateCondition,
+    /// Deletes an expression statement, e.g. an assignment or a call
+    DeleteStatement,
+}
+
+impl fmt::Display for MutationOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationOperator::SwapOperator => write!(f, "operator swap"),
+            MutationOperator::NegateCondition => write!(f, "condition negation"),
+            MutationOperator::DeleteStatement => write!(f, "statement deletion"),
+        }
+    }
+}
+
+/// A single change of a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutant {
+    /// The path of the source file
+    pub path: String,
+    pub operator: MutationOperator,
+    /// The location of the replaced code
+    pub loc: SourceLocation,
+    pub original: String,
+    pub replacement: String,
+}
+
+impl Mutant {
+    /// Returns the content of the source file with the mutation applied
+    pub fn apply(&self, content: &str) -> String {
+        let end = self.loc.start + self.loc.length;
+        format!("{}{}{}", &content[..self.loc.start], self.replacement, &content[end..])
+    }
+}
+
+impl fmt::Display for Mutant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} {}: `{}`", self.path, self.loc.line, self.operator, self.original)?;
+        if !self.replacement.is_empty() {
+            write!(f, " -> `{}`", self.replacement)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns all mutants of the source file
+///
+/// Interfaces and assembly blocks are not mutated.
+pub fn mutants(source: &SourceFile) -> Vec<Mutant> {
+    let mut generator = Generator { source, mutants: Vec::new() };
+    generator.visit(&source.ast);
+    generator.mutants
+}
+
+/// Returns the operators a binary operator is replaced with
+fn operator_replacements(operator: &str) -> &'static [&'static str] {
+    match operator {
+        "+" => &["-"],
+        "-" => &["+"],
+        "*" => &["/"],
+        "/" => &["*"],
+        "%" | "**" => &["*"],
+        "<" => &["<=", ">="],
+        "<=" => &["<", ">"],
+        ">" => &[">=", "<="],
+        ">=" => &[">", "<"],
+        "==" => &["!="],
+        "!=" => &["=="],
+        "&&" => &["||"],
+        "||" => &["&&"],
+        "&" => &["|"],
+        "|" | "^" => &["&"],
+        "<<" => &[">>"],
+        ">>" => &["<<"],
+        _ => &[],
+    }
+}
+
+/// Collects the mutants of a source file by walking its AST
+struct Generator<'a> {
+    source: &'a SourceFile,
+    mutants: Vec<Mutant>,
+}
+
+impl<'a> Generator<'a> {
+    fn visit(&mut self, node: &Value) {
+        match node {
+            Value::Array(nodes) => nodes.iter().for_each(|node| self.visit(node)),
+            Value::Object(fields) => {
+                if let Some(node_type) = fields.get("nodeType").and_then(Value::as_str) {
+                    if node_type == "InlineAssembly" ||
+                        (node_type == "ContractDefinition" && node["contractKind"] == "interface")
+                    {
+                        return
+                    }
+                    self.visit_node(node_type, node);
+                }
+                fields.values().for_each(|value| self.visit(value))
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_node(&mut self, node_type: &str, node: &Value) {
+        match node_type {
+            "BinaryOperation" => self.swap_operator(node),
+            "IfStatement" |
+            "WhileStatement" |
+            "DoWhileStatement" |
+            "ForStatement" |
+            "Conditional" => self.negate(&node["condition"]),
+            "FunctionCall" if node["expression"]["name"] == "require" => {
+                self.negate(&node["arguments"][0])
+            }
+            // only statements of blocks are deleted, e.g. not the loop expression of a `for`
+            "Block" | "UncheckedBlock" => {
+                for statement in node["statements"].as_array().into_iter().flatten() {
+                    if statement["nodeType"] == "ExpressionStatement" {
+                        self.delete(statement);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn swap_operator(&mut self, node: &Value) {
+        let operator = node["operator"].as_str().unwrap_or_default();
+        let content = &self.source.content;
+        let (left, right) = match (
+            source_location(content, &node["leftExpression"]),
+            source_location(content, &node["rightExpression"]),
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return,
+        };
+        // the operator is somewhere between the operands, which may be followed by parentheses
+        let between = left.start + left.length;
+        let offset = match content.get(between..right.start).and_then(|s| s.find(operator)) {
+            Some(offset) => offset,
+            None => return,
+        };
+        let start = between + offset;
+        let loc = SourceLocation { start, length: operator.len(), line: left.line };
+        for replacement in operator_replacements(operator) {
+            self.push(MutationOperator::SwapOperator, loc.clone(), replacement.to_string());
+        }
+    }
+
+    fn negate(&mut self, condition: &Value) {
+        if let Some(loc) = source_location(&self.source.content, condition) {
+            let original = &self.source.content[loc.start..loc.start + loc.length];
+            let replacement = format!("!({original})");
+            self.push(MutationOperator::NegateCondition, loc, replacement);
+        }
+    }
+
+    fn delete(&mut self, statement: &Value) {
+        let content = &self.source.content;
+        let mut loc = match source_location(content, statement) {
+            Some(loc) => loc,
+            None => return,
+        };
+        // include the semicolon, so no empty statement is left
+        let end = loc.start + loc.length;
+        if !content[loc.start..end].ends_with(';') {
+            let rest = &content[end..];
+            if rest.trim_start().starts_with(';') {
+                loc.length += rest.len() - rest.trim_start().len() + 1;
+            }
+        }
+        self.push(MutationOperator::DeleteStatement, loc, String::new());
+    }
+
+    fn push(&mut self, operator: MutationOperator, loc: SourceLocation, replacement: String) {
+        let original = self.source.content[loc.start..loc.start + loc.length].to_string();
+        let path = self.source.path.clone();
+        self.mutants.push(Mutant { path, operator, loc, original, replacement });
+    }
+}
+
+/// The files every source file imports directly, `path -> imported paths`
+#[derive(Debug, Clone, Default)]
+pub struct Imports(BTreeMap<PathBuf, BTreeSet<PathBuf>>);
+
+impl Imports {
+    /// Collects the imports of the source files from their ASTs
+    ///
+    /// Imported paths are relative to `root`, like the paths of the source files.
+    pub fn new(sources: &[SourceFile], root: &Path) -> Self {
+        let mut imports = BTreeMap::new();
+        for source in sources {
+            let imported: &mut BTreeSet<PathBuf> =
+                imports.entry(PathBuf::from(&source.path)).or_default();
+            for node in source.ast["nodes"].as_array().into_iter().flatten() {
+                if node["nodeType"] != "ImportDirective" {
+                    continue
+                }
+                if let Some(path) = node["absolutePath"].as_str() {
+                    let path = Path::new(path);
+                    imported.insert(path.strip_prefix(root).unwrap_or(path).to_path_buf());
+                }
+            }
+        }
+        Self(imports)
+    }
+
+    /// Returns the file and all files that import it, directly or indirectly
+    pub fn dependents(&self, path: &Path) -> BTreeSet<PathBuf> {
+        let mut dependents = BTreeSet::from([path.to_path_buf()]);
+        loop {
+            let new: Vec<_> = self
+                .0
+                .iter()
+                .filter(|(file, imported)| {
+                    !dependents.contains(*file) && imported.iter().any(|i| dependents.contains(i))
+                })
+                .map(|(file, _)| file.clone())
+                .collect();
+            if new.is_empty() {
+                return dependents
+            }
+            dependents.extend(new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn source(content: &str, ast: Value) -> SourceFile {
+        SourceFile { id: 0, path: "src/A.sol".to_string(), content: content.to_string(), ast }
+    }
+
+    #[test]
+    fn can_generate_mutants() {
+        let content = "function f(uint a, uint b) { if (a < b) { g(a); } }";
+        let src = |needle: &str, length: usize| {
+            format!("{}:{length}:0", content.find(needle).unwrap())
+        };
+        let ast = json!({
+            "nodeType": "IfStatement",
+            "src": src("if (", 20),
+            "condition": {
+                "nodeType": "BinaryOperation",
+                "operator": "<",
+                "src": src("a < b", 5),
+                "leftExpression": { "nodeType": "Identifier", "src": src("a < b", 1) },
+                "rightExpression": { "nodeType": "Identifier", "src": src("b)", 1) }
+            },
+            "trueBody": {
+                "nodeType": "Block",
+                "src": src("{ g", 9),
+                "statements": [{ "nodeType": "ExpressionStatement", "src": src("g(a)", 4) }]
+            }
+        });
+        let source = source(content, ast);
+
+        let mutated: Vec<_> =
+            mutants(&source).iter().map(|mutant| mutant.apply(content)).collect();
+        assert_eq!(
+            mutated,
+            vec![
+                "function f(uint a, uint b) { if (!(a < b)) { g(a); } }",
+                "function f(uint a, uint b) { if (a <= b) { g(a); } }",
+                "function f(uint a, uint b) { if (a >= b) { g(a); } }",
+                "function f(uint a, uint b) { if (a < b) {  } }",
+            ]
+        );
+    }
+
+    #[test]
+    fn can_find_dependents() {
+        let import = |path: &str| json!({ "nodeType": "ImportDirective", "absolutePath": path });
+        let file = |path: &str, imports: Vec<Value>| SourceFile {
+            id: 0,
+            path: path.to_string(),
+            content: String::new(),
+            ast: json!({ "nodeType": "SourceUnit", "nodes": imports }),
+        };
+        let sources = [
+            file("src/A.sol", vec![]),
+            file("src/B.sol", vec![import("/root/src/A.sol")]),
+            file("test/B.t.sol", vec![import("/root/src/B.sol")]),
+            file("test/C.t.sol", vec![]),
+        ];
+
+        let imports = Imports::new(&sources, Path::new("/root"));
+        assert_eq!(
+            imports.dependents(Path::new("src/A.sol")),
+            BTreeSet::from(["src/A.sol".into(), "src/B.sol".into(), "test/B.t.sol".into()])
+        );
+    }
+}
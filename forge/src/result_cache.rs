@@ -0,0 +1,238 @@
+use crate::{runner::test_signature, SuiteResult, TestResult};
+use ethers::{types::Bytes, utils::keccak256};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The file name of the test result cache, stored in the project's cache directory
+pub const TEST_RESULTS_CACHE_FILENAME: &str = "test-results.json";
+
+/// The file name of the recorded test failures, stored in the project's cache directory
+pub const TEST_FAILURES_CACHE_FILENAME: &str = "test-failures.json";
+
+/// The directory the minimal failing inputs of fuzz tests are written to, relative to the
+/// project's cache directory
+pub const FUZZ_FAILURES_DIR: &str = "fuzz/failures";
+
+/// Caches the results of previous test runs so tests whose inputs did not change can be skipped.
+///
+/// A cached result is only reused if the configuration the tests were run with is identical (see
+/// [`TestResultsCache::config_hash`]) and the test contract's bytecode and linked libraries are
+/// unchanged.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TestResultsCache {
+    /// Hash of all configuration values that affect the outcome of a test
+    pub config_hash: String,
+    /// Cached results per test contract, `artifact identifier -> CachedSuite`
+    pub suites: BTreeMap<String, CachedSuite>,
+    /// Where the cache is stored
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// The cached results of a single test contract
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CachedSuite {
+    /// Hash of the contract's creation code and all linked libraries
+    pub fingerprint: String,
+    /// Individual test results. `test signature -> TestResult`
+    pub test_results: BTreeMap<String, TestResult>,
+}
+
+impl TestResultsCache {
+    /// Reads the cache stored at `path`
+    ///
+    /// Returns an empty cache if the file does not exist, can not be read or was created with a
+    /// different `config_hash`.
+    pub fn read(path: impl Into<PathBuf>, config_hash: impl Into<String>) -> Self {
+        let path = path.into();
+        let config_hash = config_hash.into();
+        let cache = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<TestResultsCache>(&content).ok())
+            .filter(|cache| cache.config_hash == config_hash);
+
+        match cache {
+            Some(cache) => Self { path, ..cache },
+            None => Self { config_hash, path, ..Default::default() },
+        }
+    }
+
+    /// Writes the cache to its `path`
+    pub fn write(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Returns the cached test results of the contract `id`, if its fingerprint still matches
+    pub fn suite(&self, id: &str, fingerprint: &str) -> Option<&BTreeMap<String, TestResult>> {
+        self.suites
+            .get(id)
+            .filter(|suite| suite.fingerprint == fingerprint)
+            .map(|suite| &suite.test_results)
+    }
+
+    /// Stores the results of a freshly executed `suite`
+    ///
+    /// Results of tests that were not executed in this run are kept as long as the fingerprint of
+    /// the contract did not change.
+    pub fn insert(&mut self, id: impl Into<String>, fingerprint: String, suite: &SuiteResult) {
+        let entry = self.suites.entry(id.into()).or_default();
+        if entry.fingerprint != fingerprint {
+            *entry = CachedSuite { fingerprint, ..Default::default() };
+        }
+        for (sig, result) in suite.test_results.iter().filter(|(_, result)| !result.cached) {
+            entry.test_results.insert(sig.clone(), result.clone());
+        }
+    }
+}
+
+/// Returns the fingerprint of a test contract, the hash of its creation code and all its linked
+/// libraries
+pub fn fingerprint(deploy_code: &Bytes, libs: &[Bytes]) -> String {
+    let mut data = deploy_code.to_vec();
+    for lib in libs {
+        data.extend_from_slice(lib.as_ref());
+    }
+    hex::encode(keccak256(data))
+}
+
+/// Records the tests that failed in previous runs, so they can be replayed
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TestFailures {
+    /// Failed tests per test contract, `artifact identifier -> test signature -> calldata`
+    ///
+    /// The calldata is the counterexample of a failed fuzz test and `None` for standard tests
+    pub failures: BTreeMap<String, BTreeMap<String, Option<Bytes>>>,
+}
+
+impl TestFailures {
+    /// Reads the recorded failures stored at `path`, returns an empty set if there are none
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default())
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the recorded failures to `path`
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns true if no failures are recorded
+    pub fn is_empty(&self) -> bool {
+        self.failures.values().all(|tests| tests.is_empty())
+    }
+
+    /// Updates the recorded failures with the outcome of the tests that were executed
+    ///
+    /// Failing tests are added, tests that passed are removed. A table test is recorded as failed
+    /// if any of its rows failed.
+    pub fn update(&mut self, results: &BTreeMap<String, SuiteResult>) {
+        for (id, suite) in results {
+            let failures = self.failures.entry(id.clone()).or_default();
+            for (name, _) in suite.test_results.iter().filter(|(_, result)| result.success) {
+                failures.remove(test_signature(name));
+            }
+            for (name, result) in suite.test_results.iter().filter(|(_, result)| !result.success) {
+                let calldata = result
+                    .counterexample
+                    .as_ref()
+                    .filter(|_| result.is_fuzz())
+                    .and_then(|c| c.calldata())
+                    .cloned();
+                failures.insert(test_signature(name).to_string(), calldata);
+            }
+        }
+        self.failures.retain(|_, tests| !tests.is_empty());
+    }
+
+    /// Returns the recorded fuzz inputs, `artifact identifier -> test signature -> calldata`
+    pub fn replay_inputs(&self) -> BTreeMap<String, BTreeMap<String, Bytes>> {
+        self.failures
+            .iter()
+            .map(|(id, tests)| {
+                let inputs = tests
+                    .iter()
+                    .filter_map(|(sig, calldata)| Some((sig.clone(), calldata.clone()?)))
+                    .collect();
+                (id.clone(), inputs)
+            })
+            .collect()
+    }
+}
+
+/// A minimal failing input of a fuzz test, stored in the `FUZZ_FAILURES_DIR` of the project's
+/// cache directory so the failure can be replayed exactly with `--fuzz-input`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuzzFailure {
+    /// The artifact identifier of the test contract
+    pub contract: String,
+    /// The signature of the fuzz test
+    pub test: String,
+    /// The shrunk counterexample
+    pub calldata: Bytes,
+    /// The decoded arguments of the counterexample, for display only
+    pub args: Vec<String>,
+    /// The revert reason of the failing call, if any
+    pub reason: Option<String>,
+}
+
+impl FuzzFailure {
+    /// Returns the file the failure of the test `sig` of contract `id`, `<source>:<contract>`,
+    /// is stored in, relative to `dir`: `<source>/<contract>.<testName>.json`
+    ///
+    /// The source is part of the path, so contracts of the same name in different files do not
+    /// overwrite each other's failures.
+    pub fn path(dir: impl AsRef<Path>, id: &str, sig: &str) -> PathBuf {
+        let test = sig.split('(').next().unwrap_or(sig);
+        match id.rsplit_once(':') {
+            Some((source, contract)) => {
+                dir.as_ref().join(source).join(format!("{}.{}.json", contract, test))
+            }
+            None => dir.as_ref().join(format!("{}.{}.json", id, test)),
+        }
+    }
+
+    /// Reads a failure stored at `path`
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the failure to `path`
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzz_failure_path() {
+        let dir = Path::new("cache/fuzz/failures");
+        let a = FuzzFailure::path(dir, "test/a/Vault.t.sol:VaultTest", "testFuzz(uint256)");
+        let b = FuzzFailure::path(dir, "test/b/Vault.t.sol:VaultTest", "testFuzz(uint256)");
+        assert_eq!(a, Path::new("cache/fuzz/failures/test/a/Vault.t.sol/VaultTest.testFuzz.json"));
+        assert_ne!(a, b);
+    }
+}
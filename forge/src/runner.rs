@@ -1,13 +1,23 @@
-use crate::TestFilter;
+use crate::{inline_config::InlineFuzzConfig, TestFilter};
 use ethers::{
-    abi::{Abi, Function, RawLog},
+    abi::{Abi, Function, RawLog, Token},
+    prelude::ArtifactId,
     types::{Address, Bytes, U256},
+    utils::get_contract_address,
 };
 use eyre::Result;
+use foundry_config::{InvariantConfig, PROFILE_TARGET};
 use foundry_evm::{
-    executor::{CallResult, DatabaseRef, DeployResult, EvmError, Executor},
-    fuzz::{CounterExample, FuzzedCases, FuzzedExecutor},
-    trace::{CallTraceArena, TraceKind},
+    coverage::{merge_coverage, HitMaps},
+    executor::{CallResult, DatabaseRef, DeployResult, EvmError, Executor, RawCallResult},
+    fuzz::{
+        BaseCounterExample, CounterExample, FuzzCase, FuzzCorpus, FuzzedCases, FuzzedExecutor,
+        InvariantExecutor, TargetContract, DEFAULT_DICTIONARY_WEIGHT,
+    },
+    trace::{
+        identifier::{LocalTraceIdentifier, TraceIdentifier},
+        CallTraceArena, TraceKind,
+    },
     CALLER,
 };
 use proptest::test_runner::TestRunner;
@@ -16,6 +26,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     fmt,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
@@ -76,6 +87,18 @@ pub struct TestResult {
 
     /// Labeled addresses
     pub labeled_addresses: BTreeMap<Address, String>,
+
+    /// The coverage info collected during the test, including the setup
+    #[serde(skip)]
+    pub coverage: Option<HitMaps>,
+
+    /// How long the test took to run, excluding the setup
+    #[serde(default)]
+    pub duration: Duration,
+
+    /// Whether this result was taken from the test result cache instead of running the test
+    #[serde(default)]
+    pub cached: bool,
 }
 
 impl TestResult {
@@ -83,6 +106,60 @@ impl TestResult {
     pub fn is_fuzz(&self) -> bool {
         matches!(self.kind, TestKind::Fuzz(_))
     }
+
+    /// Returns `true` if this is the result of an invariant test
+    pub fn is_invariant(&self) -> bool {
+        matches!(self.kind, TestKind::Invariant { .. })
+    }
+}
+
+/// Returns `true` if the function is a test function, i.e. a standard, fuzz, invariant or table
+/// test
+pub(crate) fn is_test_function(func: &Function) -> bool {
+    func.name.starts_with("test") || is_invariant_function(func) || is_table_function(func)
+}
+
+/// Returns `true` if the function is an invariant test, prefixed with `invariant`
+pub(crate) fn is_invariant_function(func: &Function) -> bool {
+    func.name.starts_with("invariant") && func.inputs.is_empty()
+}
+
+/// Returns `true` if the function is a table test, prefixed with `table`
+///
+/// Table tests are run once per row of their fixture, see [`ContractRunner::run_table_test`]
+pub(crate) fn is_table_function(func: &Function) -> bool {
+    func.name.starts_with("table") && !func.inputs.is_empty()
+}
+
+/// Returns `true` if the function is a benchmark, prefixed with `bench`
+///
+/// Benchmarks are not run by `forge test`, see [`ContractRunner::run_benchmarks`]
+pub(crate) fn is_bench_function(func: &Function) -> bool {
+    func.name.starts_with("bench") && func.inputs.is_empty()
+}
+
+/// Returns `true` if the test function is run when fuzz tests are excluded
+pub(crate) fn is_deterministic_function(func: &Function) -> bool {
+    is_table_function(func) || (func.inputs.is_empty() && !is_invariant_function(func))
+}
+
+/// Returns the name of the result of row `idx` of the table test `sig`: `sig[idx]`
+pub(crate) fn table_row_name(sig: &str, idx: usize) -> String {
+    format!("{}[{}]", sig, idx)
+}
+
+/// Returns the signature of the test function a result belongs to, stripping the row index of
+/// table test results
+///
+/// Only a `[idx]` suffix after the parameter list is stripped, array parameters like
+/// `test(uint256[])` are kept.
+pub(crate) fn test_signature(name: &str) -> &str {
+    if let Some((sig, idx)) = name.strip_suffix(']').and_then(|name| name.rsplit_once('[')) {
+        if sig.ends_with(')') && !idx.is_empty() && idx.bytes().all(|b| b.is_ascii_digit()) {
+            return sig
+        }
+    }
+    name
 }
 
 /// Used gas by a test
@@ -90,6 +167,7 @@ impl TestResult {
 pub enum TestKindGas {
     Standard(u64),
     Fuzz { runs: usize, mean: u64, median: u64 },
+    Invariant { runs: usize, calls: usize, reverts: usize },
 }
 
 impl fmt::Display for TestKindGas {
@@ -101,6 +179,9 @@ impl fmt::Display for TestKindGas {
             TestKindGas::Fuzz { runs, mean, median } => {
                 write!(f, "(runs: {}, μ: {}, ~: {})", runs, mean, median)
             }
+            TestKindGas::Invariant { runs, calls, reverts } => {
+                write!(f, "(runs: {}, calls: {}, reverts: {})", runs, calls, reverts)
+            }
         }
     }
 }
@@ -112,6 +193,8 @@ impl TestKindGas {
             TestKindGas::Standard(gas) => *gas,
             // We use the median for comparisons
             TestKindGas::Fuzz { median, .. } => *median,
+            // Invariant tests do not have a meaningful gas value
+            TestKindGas::Invariant { .. } => 0,
         }
     }
 }
@@ -125,6 +208,17 @@ pub enum TestKind {
     Standard(u64),
     /// A solidity fuzz test, that stores all test cases
     Fuzz(FuzzedCases),
+    /// An invariant test
+    ///
+    /// Holds the number of executed call sequences, calls and reverted calls, and the number of
+    /// reverted calls per function
+    Invariant {
+        runs: usize,
+        calls: usize,
+        reverts: usize,
+        #[serde(default)]
+        reverted_calls: BTreeMap<String, usize>,
+    },
 }
 
 impl TestKind {
@@ -137,6 +231,9 @@ impl TestKind {
                 median: fuzzed.median_gas(false),
                 mean: fuzzed.mean_gas(false),
             },
+            TestKind::Invariant { runs, calls, reverts, .. } => {
+                TestKindGas::Invariant { runs: *runs, calls: *calls, reverts: *reverts }
+            }
         }
     }
 }
@@ -155,6 +252,8 @@ pub struct TestSetup {
     pub setup_failed: bool,
     /// The reason the setup failed
     pub reason: Option<String>,
+    /// The coverage info collected during the deployment and setup
+    pub coverage: Option<HitMaps>,
 }
 
 pub struct ContractRunner<'a, DB: DatabaseRef> {
@@ -174,6 +273,30 @@ pub struct ContractRunner<'a, DB: DatabaseRef> {
     pub initial_balance: U256,
     /// The address which will be used as the `from` field in all EVM calls
     pub sender: Address,
+
+    /// Fuzz inputs to replay instead of fuzzing, `test signature -> calldata`
+    pub replay_inputs: BTreeMap<String, Bytes>,
+    /// The directory the fuzz corpora of this contract's tests are stored in, if any
+    pub corpus_dir: Option<PathBuf>,
+    /// Fuzz settings the tests override in their natspec, `test function name -> config`
+    pub inline_fuzz_configs: BTreeMap<String, InlineFuzzConfig>,
+
+    /// The percentage of fuzz inputs drawn from the fuzz dictionary
+    pub fuzz_dictionary_weight: u32,
+    /// The settings for invariant tests
+    pub invariant_config: InvariantConfig,
+    /// Compiled contracts by name that have an Abi and runtime bytecode, used to identify the
+    /// target contracts of invariant tests
+    pub known_contracts: Option<&'a BTreeMap<ArtifactId, (Abi, Vec<u8>)>>,
+
+    /// The directory the JSON fixtures of this contract's table tests are stored in, if any
+    pub table_fixtures_dir: Option<PathBuf>,
+
+    /// The maximum wall-clock time of a single test
+    pub timeout: Option<Duration>,
+    /// The identifier of the test contract, e.g. `src/Vault.t.sol:VaultTest`, the profiler of
+    /// `--profile-exec` reports the tests with
+    pub name: String,
 }
 
 impl<'a, DB: DatabaseRef> ContractRunner<'a, DB> {
@@ -195,11 +318,26 @@ impl<'a, DB: DatabaseRef> ContractRunner<'a, DB> {
             sender: sender.unwrap_or_default(),
             errors,
             predeploy_libs,
+            replay_inputs: BTreeMap::new(),
+            corpus_dir: None,
+            inline_fuzz_configs: BTreeMap::new(),
+            fuzz_dictionary_weight: DEFAULT_DICTIONARY_WEIGHT,
+            invariant_config: Default::default(),
+            known_contracts: None,
+            table_fixtures_dir: None,
+            timeout: None,
+            name: String::new(),
         }
     }
+
+    /// Returns the span the profiler of `--profile-exec` times the execution of the test with
+    fn profile_span(&self, test: &str) -> tracing::Span {
+        let contract = self.name.rsplit(':').next().unwrap_or_default();
+        tracing::info_span!(target: PROFILE_TARGET, "evm", name = %format!("{contract}.{test}"))
+    }
 }
 
-impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
+impl<'a, DB: DatabaseRef + Clone + Send + Sync> ContractRunner<'a, DB> {
     /// Deploys the test contract inside the runner from the sending account, and optionally runs
     /// the `setUp` function on the test contract.
     pub fn setup(&mut self, setup: bool) -> Result<TestSetup> {
@@ -212,12 +350,14 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
 
         // Deploy libraries
         let mut traces: Vec<(TraceKind, CallTraceArena)> = vec![];
+        let mut coverage = None;
         for code in self.predeploy_libs.iter() {
             match self.executor.deploy(self.sender, code.0.clone(), 0u32.into(), self.errors) {
-                Ok(DeployResult { traces: tmp_traces, .. }) => {
+                Ok(DeployResult { traces: tmp_traces, coverage: tmp_coverage, .. }) => {
                     if let Some(tmp_traces) = tmp_traces {
                         traces.push((TraceKind::Deployment, tmp_traces));
                     }
+                    merge_coverage(&mut coverage, tmp_coverage);
                 }
                 Err(EvmError::Execution { reason, traces, logs, labels, .. }) => {
                     // If we failed to call the constructor, force the tracekind to be setup so
@@ -235,6 +375,7 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                         labeled_addresses: labels,
                         setup_failed: true,
                         reason: Some(reason),
+                        coverage,
                     })
                 }
                 e => eyre::bail!("Unrecoverable error: {:?}", e),
@@ -242,7 +383,13 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
         }
 
         // Deploy an instance of the contract
-        let DeployResult { address, mut logs, traces: constructor_traces, .. } = match self
+        let DeployResult {
+            address,
+            mut logs,
+            traces: constructor_traces,
+            coverage: constructor_coverage,
+            ..
+        } = match self
             .executor
             .deploy(self.sender, self.code.0.clone(), 0u32.into(), self.errors)
         {
@@ -261,12 +408,14 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                     labeled_addresses: labels,
                     setup_failed: true,
                     reason: Some(reason),
+                    coverage,
                 })
             }
             e => eyre::bail!("Unrecoverable error: {:?}", e),
         };
 
         traces.extend(constructor_traces.map(|traces| (TraceKind::Deployment, traces)).into_iter());
+        merge_coverage(&mut coverage, constructor_coverage);
 
         // Now we set the contracts initial balance, and we also reset `self.sender`s balance to
         // the initial balance we want
@@ -280,7 +429,10 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                 .executor
                 .setup(address)
             {
-                Ok(CallResult { traces, labels, logs, .. }) => (false, logs, traces, labels, None),
+                Ok(CallResult { traces, labels, logs, coverage: setup_coverage, .. }) => {
+                    merge_coverage(&mut coverage, setup_coverage);
+                    (false, logs, traces, labels, None)
+                }
                 Err(EvmError::Execution { traces, labels, logs, reason, .. }) => {
                     (true, logs, traces, labels, Some(format!("Setup failed: {reason}")))
                 }
@@ -295,9 +447,9 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)).into_iter());
             logs.extend_from_slice(&setup_logs);
 
-            TestSetup { address, logs, traces, labeled_addresses, setup_failed, reason }
+            TestSetup { address, logs, traces, labeled_addresses, setup_failed, reason, coverage }
         } else {
-            TestSetup { address, logs, traces, ..Default::default() }
+            TestSetup { address, logs, traces, coverage, ..Default::default() }
         })
     }
 
@@ -341,6 +493,9 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                         kind: TestKind::Standard(0),
                         traces: vec![],
                         labeled_addresses: BTreeMap::new(),
+                        coverage: None,
+                        duration: Duration::ZERO,
+                        cached: false,
                     },
                 )]
                 .into(),
@@ -348,7 +503,10 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             ))
         }
 
-        let setup = self.setup(needs_setup)?;
+        let setup = {
+            let _span = self.profile_span("setUp()").entered();
+            self.setup(needs_setup)?
+        };
         if setup.setup_failed {
             // The setup failed, so we return a single test result for `setUp`
             return Ok(SuiteResult::new(
@@ -363,6 +521,9 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                         kind: TestKind::Standard(0),
                         traces: setup.traces,
                         labeled_addresses: setup.labeled_addresses,
+                        coverage: setup.coverage,
+                        duration: Duration::ZERO,
+                        cached: false,
                     },
                 )]
                 .into(),
@@ -376,27 +537,66 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             .functions()
             .into_iter()
             .filter(|func| {
-                func.name.starts_with("test") &&
+                is_test_function(func) &&
                     filter.matches_test(func.signature()) &&
-                    (include_fuzz_tests || func.inputs.is_empty())
+                    (include_fuzz_tests || is_deterministic_function(func))
             })
             .map(|func| (func, func.name.starts_with("testFail")))
             .collect();
 
-        let test_results = tests
+        // The contracts invariant tests call
+        let targets = if tests.iter().any(|(func, _)| is_invariant_function(func)) {
+            self.invariant_targets(setup.address)?
+        } else {
+            Vec::new()
+        };
+
+        // Table tests report one result per row
+        let table_results = tests
             .par_iter()
+            .filter(|(func, _)| is_table_function(func))
+            .map(|(func, should_fail)| {
+                let _span = self.profile_span(&func.signature()).entered();
+                self.run_table_test(func, *should_fail, setup.clone())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut test_results = tests
+            .par_iter()
+            .filter(|(func, _)| !is_table_function(func))
             .filter_map(|(func, should_fail)| {
-                let result = if func.inputs.is_empty() {
+                let _span = self.profile_span(&func.signature()).entered();
+                let result = if is_invariant_function(func) {
+                    fuzzer.as_ref().map(|fuzzer| {
+                        self.run_invariant_test(func, fuzzer, setup.clone(), &targets)
+                    })
+                } else if func.inputs.is_empty() {
                     Some(self.run_test(func, *should_fail, setup.clone()))
+                } else if let Some(calldata) = self.replay_inputs.get(&func.signature()) {
+                    Some(self.replay_fuzz_case(
+                        func,
+                        *should_fail,
+                        calldata.clone(),
+                        setup.clone(),
+                    ))
                 } else {
                     fuzzer.as_ref().map(|fuzzer| {
-                        self.run_fuzz_test(func, *should_fail, fuzzer.clone(), setup.clone())
+                        let fuzzer = match self.inline_fuzz_configs.get(&func.name) {
+                            Some(inline) => {
+                                let mut config = fuzzer.config().clone();
+                                inline.apply(&mut config);
+                                with_fuzz_config(fuzzer, config)
+                            }
+                            None => fuzzer.clone(),
+                        };
+                        self.run_fuzz_test(func, *should_fail, fuzzer, setup.clone())
                     })
                 };
 
                 result.map(|result| Ok((func.signature(), result?)))
             })
             .collect::<Result<BTreeMap<_, _>>>()?;
+        test_results.extend(table_results.into_iter().flatten());
 
         let duration = start.elapsed();
         if !test_results.is_empty() {
@@ -418,11 +618,12 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
         should_fail: bool,
         setup: TestSetup,
     ) -> Result<TestResult> {
-        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
+        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, mut coverage, .. } =
+            setup;
 
         // Run unit test
         let start = Instant::now();
-        let (reverted, reason, gas, stipend, execution_traces, state_changeset) = match self
+        let (reverted, mut reason, gas, stipend, execution_traces, state_changeset) = match self
             .executor
             .call::<(), _, _>(self.sender, address, func.clone(), (), 0.into(), self.errors)
         {
@@ -434,10 +635,12 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                 traces: execution_trace,
                 labels: new_labels,
                 state_changeset,
+                coverage: execution_coverage,
                 ..
             }) => {
                 labeled_addresses.extend(new_labels);
                 logs.extend(execution_logs);
+                merge_coverage(&mut coverage, execution_coverage);
                 (reverted, None, gas, stipend, execution_trace, state_changeset)
             }
             Err(EvmError::Execution {
@@ -462,13 +665,20 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
         };
         traces.extend(execution_traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
 
-        let success = self.executor.is_success(
+        let mut success = self.executor.is_success(
             setup.address,
             reverted,
             state_changeset.expect("we should have a state changeset"),
             should_fail,
         );
 
+        // A single call can't be aborted, but a test that took too long still fails
+        if let Some(timeout) = self.timeout.filter(|timeout| success && start.elapsed() > *timeout)
+        {
+            success = false;
+            reason = Some(format!("Timed out after {timeout:?}"));
+        }
+
         // Record test execution time
         tracing::debug!(
             duration = ?start.elapsed(),
@@ -484,6 +694,9 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             kind: TestKind::Standard(gas.overflowing_sub(stipend).0),
             traces,
             labeled_addresses,
+            coverage,
+            duration: start.elapsed(),
+            cached: false,
         })
     }
 
@@ -495,21 +708,29 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
         runner: TestRunner,
         setup: TestSetup,
     ) -> Result<TestResult> {
-        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
+        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, mut coverage, .. } =
+            setup;
 
         // Run fuzz test
         let start = Instant::now();
-        let mut result = FuzzedExecutor::new(&self.executor, runner, self.sender).fuzz(
-            func,
-            address,
-            should_fail,
-            self.errors,
-        );
+        let dictionary_weight = self
+            .inline_fuzz_configs
+            .get(&func.name)
+            .and_then(|inline| inline.dictionary_weight)
+            .unwrap_or(self.fuzz_dictionary_weight);
+        let mut fuzzer = FuzzedExecutor::new(&self.executor, runner, self.sender)
+            .with_timeout(self.timeout)
+            .with_dictionary_weight(dictionary_weight);
+        if let Some(ref dir) = self.corpus_dir {
+            fuzzer = fuzzer.with_corpus(FuzzCorpus::load(dir.join(func.signature())));
+        }
+        let mut result = fuzzer.fuzz(func, address, should_fail, self.errors);
 
-        // Record logs, labels and traces
+        // Record logs, labels, traces and coverage
         logs.append(&mut result.logs);
         labeled_addresses.append(&mut result.labeled_addresses);
         traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
+        merge_coverage(&mut coverage, result.coverage);
 
         // Record test execution time
         tracing::debug!(
@@ -525,6 +746,385 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             kind: TestKind::Fuzz(result.cases),
             traces,
             labeled_addresses,
+            coverage,
+            duration: start.elapsed(),
+            cached: false,
+        })
+    }
+
+    #[tracing::instrument(name = "invariant-test", skip_all, fields(name = %func.signature()))]
+    pub fn run_invariant_test(
+        &self,
+        func: &Function,
+        fuzzer: &TestRunner,
+        setup: TestSetup,
+        targets: &[TargetContract],
+    ) -> Result<TestResult> {
+        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, coverage, .. } =
+            setup;
+
+        // Every run is a sequence of up to `depth` calls
+        let mut config = fuzzer.config().clone();
+        config.cases = self.invariant_config.runs;
+        let runner = with_fuzz_config(fuzzer, config);
+
+        // The optional hook that is called after every call sequence
+        let after_invariant = self
+            .contract
+            .functions()
+            .find(|func| func.name == "afterInvariant" && func.inputs.is_empty())
+            .cloned();
+
+        let start = Instant::now();
+        let mut result = InvariantExecutor::new(
+            &self.executor,
+            runner,
+            self.sender,
+            self.invariant_config.depth,
+            self.invariant_config.fail_on_revert,
+        )
+        .with_timeout(self.timeout)
+        .with_dictionary_weight(self.fuzz_dictionary_weight)
+        .with_after_invariant(after_invariant)
+        .invariant_fuzz(func, address, targets, self.errors);
+
+        // Record logs, labels and traces
+        logs.append(&mut result.logs);
+        labeled_addresses.append(&mut result.labeled_addresses);
+        traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
+
+        tracing::debug!(
+            duration = ?start.elapsed(),
+            success = %result.success
+        );
+
+        Ok(TestResult {
+            success: result.success,
+            reason: result.reason,
+            counterexample: result.counterexample,
+            logs,
+            kind: TestKind::Invariant {
+                runs: result.runs,
+                calls: result.calls,
+                reverts: result.reverts,
+                reverted_calls: result.reverted_calls,
+            },
+            traces,
+            labeled_addresses,
+            coverage,
+            duration: start.elapsed(),
+            cached: false,
+        })
+    }
+
+    /// Runs a table test once per row of its fixture
+    ///
+    /// Every row is reported as its own result, named `sig[row]`, see [`table_row_name`]. If the
+    /// rows can't be resolved a single failed result is returned for the test function.
+    #[tracing::instrument(name = "table-test", skip_all, fields(name = %func.signature(), %should_fail))]
+    pub fn run_table_test(
+        &self,
+        func: &Function,
+        should_fail: bool,
+        setup: TestSetup,
+    ) -> Result<Vec<(String, TestResult)>> {
+        let rows = match self.table_rows(func, setup.address) {
+            Ok(rows) => rows,
+            Err(reason) => {
+                let TestSetup { logs, traces, labeled_addresses, coverage, .. } = setup;
+                return Ok(vec![(
+                    func.signature(),
+                    TestResult {
+                        success: false,
+                        reason: Some(reason),
+                        counterexample: None,
+                        logs,
+                        kind: TestKind::Standard(0),
+                        traces,
+                        labeled_addresses,
+                        coverage,
+                        duration: Duration::ZERO,
+                        cached: false,
+                    },
+                )])
+            }
+        };
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(idx, row)| {
+                let TestSetup {
+                    address, mut logs, mut traces, mut labeled_addresses, mut coverage, ..
+                } = setup.clone();
+                let calldata: Bytes = func.encode_input(&row)?.into();
+
+                let start = Instant::now();
+                let RawCallResult {
+                    reverted,
+                    result,
+                    gas,
+                    stipend,
+                    logs: execution_logs,
+                    labels,
+                    traces: execution_traces,
+                    coverage: execution_coverage,
+                    state_changeset,
+                    ..
+                } = self.executor.call_raw(self.sender, address, calldata.0.clone(), 0.into())?;
+                logs.extend(execution_logs);
+                labeled_addresses.extend(labels);
+                merge_coverage(&mut coverage, execution_coverage);
+                traces.extend(
+                    execution_traces.map(|traces| (TraceKind::Execution, traces)).into_iter(),
+                );
+
+                let success = self.executor.is_success(
+                    address,
+                    reverted,
+                    state_changeset.expect("we should have a state changeset"),
+                    should_fail,
+                );
+
+                let (reason, counterexample) = if success {
+                    (None, None)
+                } else {
+                    let reason = foundry_utils::decode_revert(result.as_ref(), self.errors)
+                        .ok()
+                        .filter(|reason| !reason.is_empty());
+                    (reason, Some(CounterExample::Single(BaseCounterExample::new(calldata, row))))
+                };
+
+                Ok((
+                    table_row_name(&func.signature(), idx),
+                    TestResult {
+                        success,
+                        reason,
+                        counterexample,
+                        logs,
+                        kind: TestKind::Standard(gas.overflowing_sub(stipend).0),
+                        traces,
+                        labeled_addresses,
+                        coverage,
+                        duration: start.elapsed(),
+                        cached: false,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the rows of the table test `func`
+    ///
+    /// The rows are read from the JSON fixture `<table_fixtures_dir>/<function name>.json` if it
+    /// exists, an array of rows where every row is an array of arguments. Otherwise every
+    /// parameter needs a companion getter on the test contract that returns one column of the
+    /// table, for example `fixtureAmount()` for the parameter `amount`.
+    fn table_rows(&self, func: &Function, address: Address) -> Result<Vec<Vec<Token>>, String> {
+        let fixture = self
+            .table_fixtures_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", func.name)))
+            .filter(|path| path.exists());
+        if let Some(path) = fixture {
+            let rows = foundry_config::constructor_args::read_fixture(&path)
+                .map_err(|err| err.to_string())?;
+            return rows
+                .iter()
+                .enumerate()
+                .map(|(idx, row)| {
+                    if row.len() != func.inputs.len() {
+                        return Err(format!(
+                            "Row {} of {} has {} values, expected {}",
+                            idx,
+                            path.display(),
+                            row.len(),
+                            func.inputs.len()
+                        ))
+                    }
+                    let params = func
+                        .inputs
+                        .iter()
+                        .map(|input| &input.kind)
+                        .zip(row.iter().map(String::as_str));
+                    foundry_utils::parse_tokens(params, true).map_err(|err| err.to_string())
+                })
+                .collect()
+        }
+
+        let mut columns = Vec::with_capacity(func.inputs.len());
+        for input in &func.inputs {
+            let mut chars = input.name.chars();
+            let getter_name = match chars.next() {
+                Some(first) => format!("fixture{}{}", first.to_uppercase(), chars.as_str()),
+                None => return Err("Table tests can not have unnamed parameters".to_string()),
+            };
+            let getter = self.contract.function(&getter_name).map_err(|_| {
+                format!(
+                    "No fixture found for parameter `{}`, expected `{}()`",
+                    input.name, getter_name
+                )
+            })?;
+            let calldata = getter.encode_input(&[]).map_err(|err| err.to_string())?;
+            let call = self
+                .executor
+                .call_raw(self.sender, address, calldata.into(), 0.into())
+                .map_err(|err| err.to_string())?;
+            if call.reverted {
+                return Err(format!("Fixture `{}()` reverted", getter_name))
+            }
+            let column = match getter.decode_output(call.result.as_ref()) {
+                Ok(mut tokens) if tokens.len() == 1 => match tokens.remove(0) {
+                    Token::Array(values) | Token::FixedArray(values) => values,
+                    _ => return Err(format!("Fixture `{}()` must return an array", getter_name)),
+                },
+                _ => return Err(format!("Fixture `{}()` must return an array", getter_name)),
+            };
+            columns.push(column);
+        }
+
+        let rows = columns.first().map(Vec::len).unwrap_or_default();
+        if columns.iter().any(|column| column.len() != rows) {
+            return Err(format!("The fixtures of {} have different lengths", func.name))
+        }
+        Ok((0..rows)
+            .map(|idx| columns.iter().map(|column| column[idx].clone()).collect())
+            .collect())
+    }
+
+    /// Returns the contracts that are called by invariant tests
+    ///
+    /// These are the contracts returned by `targetContracts()` if the test contract defines it,
+    /// otherwise all contracts the test contract deployed. Contracts that can't be identified by
+    /// their runtime code are skipped.
+    fn invariant_targets(&self, address: Address) -> Result<Vec<TargetContract>> {
+        let known_contracts = match self.known_contracts {
+            Some(known_contracts) => known_contracts,
+            None => return Ok(Vec::new()),
+        };
+
+        let addresses: Vec<Address> = match self.contract.function("targetContracts") {
+            Ok(func) => {
+                self.executor
+                    .call::<Vec<Address>, _, _>(
+                        self.sender,
+                        address,
+                        func.clone(),
+                        (),
+                        0.into(),
+                        self.errors,
+                    )?
+                    .result
+            }
+            Err(_) => {
+                let nonce = self.executor.db.basic(address).nonce;
+                (1..nonce).map(|nonce| get_contract_address(address, nonce)).collect()
+            }
+        };
+
+        let codes: Vec<(Address, Option<Vec<u8>>)> = addresses
+            .into_iter()
+            .map(|addr| (addr, self.executor.db.basic(addr).code.map(|code| code.to_vec())))
+            .collect();
+
+        Ok(LocalTraceIdentifier::new(known_contracts)
+            .identify_addresses(codes.iter().map(|(addr, code)| (addr, code.as_ref())).collect())
+            .into_iter()
+            .filter_map(|identity| {
+                Some(TargetContract::new(identity.address, identity.contract?, &identity.abi?))
+            })
+            .collect())
+    }
+
+    /// Runs a fuzz test with a single, previously recorded, input instead of fuzzing it
+    #[tracing::instrument(name = "replay", skip_all, fields(name = %func.signature(), %should_fail))]
+    pub fn replay_fuzz_case(
+        &self,
+        func: &Function,
+        should_fail: bool,
+        calldata: Bytes,
+        setup: TestSetup,
+    ) -> Result<TestResult> {
+        // the input is read from the failure cache or a user supplied file
+        if calldata.len() < 4 || calldata.as_ref()[..4] != func.short_signature() {
+            eyre::bail!(
+                "The recorded input 0x{} is no call of {}",
+                hex::encode(calldata.as_ref()),
+                func.signature()
+            )
+        }
+
+        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, mut coverage, .. } =
+            setup;
+
+        let start = Instant::now();
+        let RawCallResult {
+            reverted,
+            result,
+            gas,
+            stipend,
+            logs: execution_logs,
+            labels,
+            traces: execution_traces,
+            coverage: execution_coverage,
+            state_changeset,
+            ..
+        } = self.executor.call_raw(self.sender, address, calldata.0.clone(), 0.into())?;
+        logs.extend(execution_logs);
+        labeled_addresses.extend(labels);
+        merge_coverage(&mut coverage, execution_coverage);
+        traces.extend(execution_traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
+
+        let success = self.executor.is_success(
+            address,
+            reverted,
+            state_changeset.expect("we should have a state changeset"),
+            should_fail,
+        );
+
+        let (reason, counterexample, cases) = if success {
+            (None, None, vec![FuzzCase { calldata, gas, stipend }])
+        } else {
+            let reason = foundry_utils::decode_revert(result.as_ref(), self.errors)
+                .ok()
+                .filter(|reason| !reason.is_empty());
+            let args = func.decode_input(&calldata.as_ref()[4..])?;
+            (reason, Some(CounterExample::Single(BaseCounterExample::new(calldata, args))), vec![])
+        };
+
+        Ok(TestResult {
+            success,
+            reason,
+            counterexample,
+            logs,
+            kind: TestKind::Fuzz(FuzzedCases::new(cases)),
+            traces,
+            labeled_addresses,
+            coverage,
+            duration: start.elapsed(),
+            cached: false,
         })
     }
 }
+
+/// Returns a fuzzer with the given config that draws its inputs from the rng of `fuzzer`, so runs
+/// with a fixed seed stay reproducible
+fn with_fuzz_config(fuzzer: &TestRunner, config: proptest::test_runner::Config) -> TestRunner {
+    TestRunner::new_with_rng(config, fuzzer.clone().new_rng())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_strip_table_row_index() {
+        assert_eq!(test_signature("testFoo()"), "testFoo()");
+        assert_eq!(test_signature(&table_row_name("tableFoo(uint256)", 3)), "tableFoo(uint256)");
+        assert_eq!(test_signature("testFoo(uint256[])"), "testFoo(uint256[])");
+        assert_eq!(test_signature("testFoo(uint256[2])"), "testFoo(uint256[2])");
+        assert_eq!(
+            test_signature("tableFoo(uint256[],bytes32[4])[12]"),
+            "tableFoo(uint256[],bytes32[4])"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{coverage::instruction_counters, trace::CallTraceArena};
+use ethers::solc::sourcemap::SourceMap;
+use std::{collections::BTreeMap, fmt};
+
+/// A call of a reverted call chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    /// The name of the called contract, or its address if it is unknown
+    pub contract: String,
+    /// The source file and line the call reverted at, if the contract has a source map
+    pub location: Option<(String, usize)>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some((path, line)) => write!(f, "at {} ({path}:{line})", self.contract),
+            None => write!(f, "at {} (unknown location)", self.contract),
+        }
+    }
+}
+
+/// Maps the program counters of reverted calls to lines of their Solidity sources
+#[derive(Debug, Clone, Default)]
+pub struct StackTraceDecoder {
+    /// The runtime code and source map of every known contract, by contract name
+    contracts: BTreeMap<String, (Vec<u8>, SourceMap)>,
+    /// The path and content of every source file, by source id
+    sources: BTreeMap<u32, (String, String)>,
+}
+
+impl StackTraceDecoder {
+    pub fn new(
+        contracts: BTreeMap<String, (Vec<u8>, SourceMap)>,
+        sources: BTreeMap<u32, (String, String)>,
+    ) -> Self {
+        Self { contracts, sources }
+    }
+
+    /// Returns the frames of the call chain that caused the revert, innermost call first
+    ///
+    /// The traces must be decoded, so the called contracts are known. A reverted call is caused by
+    /// its last subcall if that reverted too, otherwise it reverted itself. Calls that were
+    /// reverted by a subcall are located at the instruction that made the subcall, e.g. at the
+    /// `vm.` call of a failed cheatcode.
+    pub fn stack_trace(&self, traces: &CallTraceArena) -> Vec<StackFrame> {
+        let mut frames = Vec::new();
+        let mut node = match traces.arena.first() {
+            Some(node) if !node.trace.success => node,
+            _ => return frames,
+        };
+        loop {
+            let trace = &node.trace;
+            let contract = trace.contract.clone().unwrap_or_else(|| format!("{:?}", trace.address));
+            let failed_child = node
+                .children
+                .last()
+                .map(|id| &traces.arena[*id])
+                .filter(|child| !child.trace.success);
+            let pc = match failed_child {
+                Some(child) => child.trace.call_site_pc.or(trace.last_pc),
+                None => trace.last_pc,
+            };
+            let location = pc.and_then(|pc| self.source_line(&contract, pc));
+            frames.push(StackFrame { contract, location });
+
+            match failed_child {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        frames.reverse();
+        frames
+    }
+
+    /// Returns the source file and line the instruction at `pc` of the contract maps to
+    fn source_line(&self, contract: &str, pc: usize) -> Option<(String, usize)> {
+        let (code, source_map) = self.contracts.get(contract)?;
+        let ic = *instruction_counters(code).get(pc)?;
+        let element = source_map.get(ic)?;
+        let (path, content) = self.sources.get(&element.index?)?;
+        let line = content.get(..element.offset)?.matches('\n').count() + 1;
+        Some((path.clone(), line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{node::CallTraceNode, CallTrace};
+    use ethers::solc::sourcemap::parse;
+
+    #[test]
+    fn can_map_reverts_to_source_lines() {
+        let content = "contract A {\n    function f() {\n        revert();\n    }\n}\n";
+        // PUSH1 0x00, DUP1, REVERT
+        let code = vec![0x60, 0x00, 0x80, 0xfd];
+        let revert = content.find("revert").unwrap();
+        let source_map = parse(&format!("0:55:0:-;;{revert}:8:0")).unwrap();
+        let decoder = StackTraceDecoder::new(
+            [("A".to_string(), (code, source_map))].into(),
+            [(0, ("src/A.sol".to_string(), content.to_string()))].into(),
+        );
+
+        let node = |contract: Option<&str>, last_pc: usize, children: Vec<usize>| CallTraceNode {
+            children,
+            trace: CallTrace {
+                contract: contract.map(str::to_string),
+                last_pc: Some(last_pc),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut call = node(Some("A"), 3, vec![]);
+        call.trace.call_site_pc = Some(2);
+        let traces = CallTraceArena { arena: vec![node(Some("ATest"), 10, vec![1]), call] };
+
+        let frames = decoder.stack_trace(&traces);
+        assert_eq!(
+            frames,
+            vec![
+                StackFrame {
+                    contract: "A".to_string(),
+                    location: Some(("src/A.sol".to_string(), 3))
+                },
+                StackFrame { contract: "ATest".to_string(), location: None },
+            ]
+        );
+        assert_eq!(frames[0].to_string(), "at A (src/A.sol:3)");
+    }
+}
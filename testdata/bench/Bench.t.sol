@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+contract BenchCounter {
+    uint256 public count;
+
+    function increment() public {
+        count++;
+    }
+}
+
+contract BenchTest {
+    BenchCounter counter;
+
+    function setUp() public {
+        counter = new BenchCounter();
+    }
+
+    function benchIncrement() public {
+        counter.increment();
+    }
+
+    function benchLoop() public {
+        for (uint256 i = 0; i < 100; i++) {
+            counter.increment();
+        }
+    }
+
+    function benchRevert() public {
+        revert("not benchmarkable");
+    }
+}
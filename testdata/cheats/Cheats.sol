@@ -2,24 +2,79 @@
 pragma solidity >=0.8.0;
 
 interface Cheats {
+    struct BlockContext {
+        uint256 number;
+        uint256 timestamp;
+        uint256 basefee;
+        uint256 prevrandao;
+        address coinbase;
+    }
+
+    enum CallerMode {
+        None,
+        Prank,
+        RecurrentPrank
+    }
+
+    enum ExecutionContext {
+        Test,
+        Coverage,
+        Script,
+        Broadcast
+    }
+
+    struct Log {
+        bytes32[] topics;
+        bytes data;
+        address emitter;
+    }
+
+    struct SignedDelegation {
+        uint8 v;
+        bytes32 r;
+        bytes32 s;
+        uint64 nonce;
+        address implementation;
+    }
+
     // Set block.timestamp (newTimestamp)
     function warp(uint256) external;
     // Set block.height (newHeight)
     function roll(uint256) external;
+    // Moves block.timestamp by a signed amount of seconds (delta)
+    function warpBy(int256) external;
+    // Moves block.height by a signed amount of blocks (delta)
+    function rollBy(int256) external;
     // Set block.basefee (newBasefee)
     function fee(uint256) external;
     // Set block.coinbase (who)
     function coinbase(address) external;
+    // Sets block.height, block.timestamp, block.basefee, block.difficulty and block.coinbase at once
+    function setBlock(BlockContext calldata) external;
     // Loads a storage slot from an address (who, slot)
     function load(address,bytes32) external returns (bytes32);
     // Stores a value to an address' storage slot, (who, slot, value)
     function store(address,bytes32,bytes32) external;
     // Signs data, (privateKey, digest) => (v, r, s)
     function sign(uint256,bytes32) external returns (uint8,bytes32,bytes32);
+    // Signs data and returns the EIP-2098 compact signature, (privateKey, digest) => (r, vs)
+    function signCompact(uint256,bytes32) external returns (bytes32,bytes32);
+    // Signs data with all keys and concatenates the (r, s, v) signatures in ascending order of the signers, (privateKeys, digest) => (signatures)
+    function signAggregate(uint256[] calldata,bytes32) external returns (bytes memory);
+    // Signs an EIP-7702 authorization to delegate to an implementation for the current nonce of the signer, v is the y parity, (implementation, privateKey) => (delegation)
+    function signDelegation(address,uint256) external returns (SignedDelegation memory);
+    // Applies a signed EIP-7702 authorization, writes the `0xef0100 ++ implementation` designator to the signer and charges the authorization gas
+    function attachDelegation(SignedDelegation calldata) external;
     // Gets address for a given private key, (privateKey) => (address)
     function addr(uint256) external returns (address);
+    // Derives a private key from a mnemonic at the index of the path m/44'/60'/0'/0, (mnemonic, index) => (privateKey)
+    function deriveKey(string calldata, uint32) external returns (uint256);
+    // Derives a private key from a mnemonic at the index of a derivation path, (mnemonic, path, index) => (privateKey)
+    function deriveKey(string calldata, string calldata, uint32) external returns (uint256);
     // Performs a foreign function call via terminal, (stringInputs) => (result)
     function ffi(string[] calldata) external returns (bytes memory);
+    // Sends the input to a long running process started once per command, (stringInputs, input) => (result)
+    function differential(string[] calldata, bytes calldata) external returns (bytes memory);
     // Sets the *next* call's msg.sender to be the input address
     function prank(address) external;
     // Sets all subsequent calls' msg.sender to be the input address until `stopPrank` is called
@@ -30,6 +85,10 @@ interface Cheats {
     function startPrank(address,address) external;
     // Resets subsequent calls' msg.sender to be `address(this)`
     function stopPrank() external;
+    // Reads the active prank mode, and the pranked msg.sender and tx.origin, which are the default sender if not pranked
+    function readCallers() external returns (CallerMode callerMode, address msgSender, address txOrigin);
+    // Returns whether the code runs in the context, Test also matches Coverage and Script also matches Broadcast
+    function isContext(ExecutionContext) external returns (bool);
     // Sets an address' balance, (who, newBalance)
     function deal(address, uint256) external;
     // Sets an address' code, (who, newCode)
@@ -38,20 +97,34 @@ interface Cheats {
     function expectRevert() external;
     function expectRevert(bytes calldata) external;
     function expectRevert(bytes4) external;
+    // Expects an error on next call that starts with the selector, ignoring the error arguments
+    function expectPartialRevert(bytes4) external;
     // Record all storage reads and writes
     function record() external;
     // Gets all accessed reads and write slot from a recording session, for a given address
     function accesses(address) external returns (bytes32[] memory reads, bytes32[] memory writes);
+    // Record all emitted logs
+    function recordLogs() external;
+    // Gets the logs emitted since `recordLogs` or the last call to `getRecordedLogs`
+    function getRecordedLogs() external returns (Log[] memory);
     // Prepare an expected log with (bool checkTopic1, bool checkTopic2, bool checkTopic3, bool checkData).
     // Call this function, then emit an event, then call a function. Internally after the call, we check if
     // logs were emitted in the expected order with the expected topics and data (as specified by the booleans).
     // Second form also checks supplied address against emitting contract.
     function expectEmit(bool,bool,bool,bool) external;
     function expectEmit(bool,bool,bool,bool,address) external;
+    // Same as above, but expects exactly `count` matching logs, in any order, instead of the next
+    // log to match. A count of 0 asserts that no matching log is emitted.
+    function expectEmit(bool,bool,bool,bool,uint64) external;
+    function expectEmit(bool,bool,bool,bool,address,uint64) external;
+    // Expects exactly `count` logs with the given topics and data, without emitting a reference
+    // log. Topics equal to `ANY_TOPIC` (keccak256("ANY_TOPIC")) and empty data match anything.
+    function expectLog(bytes32[] calldata topics, bytes calldata data, uint64 count) external;
     // Mocks a call to an address, returning specified data.
     // Calldata can either be strict or a partial match, e.g. if you only
     // pass a Solidity selector to the expected calldata, then the entire Solidity
     // function will be mocked.
+    // If the address has no code, a single STOP opcode is etched to it.
     function mockCall(address,bytes calldata,bytes calldata) external;
     // Mocks a call to an address with a specific msg.value, returning specified data.
     // Calldata match takes precedence over msg.value in case of ambiguity.
@@ -63,10 +136,22 @@ interface Cheats {
     function expectCall(address,bytes calldata) external;
     // Expect a call to an address with the specified msg.value and calldata
     function expectCall(address,uint256,bytes calldata) external;
+    // Expect a contract creation with creation code that starts with the given bytes
+    function expectCreate(bytes calldata) external;
+    // Expect a contract to be created with CREATE2 at the given address
+    function expectCreate2(address) external;
+    // Expect the next call to use between min and max gas (inclusive), excluding the calling
+    // overhead
+    function expectGasUsage(uint64,uint64) external;
     // Gets the code from an artifact file. Takes in the relative path to the json file
     function getCode(string calldata) external returns (bytes memory);
     // Labels an address in call traces
     function label(address, string calldata) external;
+    // Returns the address of the private key `keccak256(label)`, labeled with the label and funded
+    // with the `labeled_account_balance` configured in `foundry.toml` the first time it is made
+    function makeAddr(string calldata) external returns(address);
+    // Like `makeAddr`, but also returns the private key of the address
+    function makeAccount(string calldata) external returns(address,uint256);
     // If the condition is false, discard this run's fuzz inputs and generate new ones
     function assume(bool) external;
     // Set nonce for an account
@@ -75,4 +160,69 @@ interface Cheats {
     function getNonce(address) external returns(uint64);
     // Set block.chainid (newChainId)
     function chainId(uint256) external;
+    // Returns the url of the rpc endpoint with the given alias, as configured in `[rpc_endpoints]`
+    function rpcUrl(string calldata) external returns(string memory);
+    // Creates a fork of the given endpoint, a url or an alias in `[rpc_endpoints]`, at the latest
+    // or the given block, and returns its id. Forks of the same block share the RPC cache
+    function createFork(string calldata) external returns(uint256);
+    function createFork(string calldata,uint256) external returns(uint256);
+    // Selects the fork with the given id. Every fork keeps its own state, only the state of the
+    // test contract, the sender and the accounts made persistent is shared between forks
+    function selectFork(uint256) external;
+    // Rolls the selected fork, or the fork with the given id, to the given block, discarding its state
+    function rollFork(uint256) external;
+    function rollFork(uint256,uint256) external;
+    // Keeps the state of the account when another fork is selected, like the test contract's
+    function makePersistent(address) external;
+    // Returns whether the account keeps its state when another fork is selected
+    function isPersistent(address) external returns(bool);
+    // Snapshots the state of the EVM: all accounts, their storage and the block environment,
+    // and returns the id of the snapshot
+    function snapshot() external returns(uint256);
+    // Restores the state of the snapshot with the given id, returns false if it does not exist
+    function revertTo(uint256) external returns(bool);
+    // Reads the file at the given path, relative to the project root, if `fs_permissions` allow it
+    function readFile(string calldata) external returns(string memory);
+    // Writes the data to the file at the given path, relative to the project root, if
+    // `fs_permissions` allow it. The file is created if it does not exist, and replaced if it does
+    function writeFile(string calldata,string calldata) external;
+    // Sets the balance, nonce, code and storage of all accounts in the genesis alloc JSON file at the
+    // given path, relative to the project root, if `fs_permissions` allow reading it
+    function loadAllocs(string calldata) external;
+    // Sets the env var with the given name to the given value
+    function setEnv(string calldata,string calldata) external;
+    // Reads the env var with the given name and parses it as the return type. Integers are
+    // decimal, or hex if prefixed with 0x, bytes are hex encoded
+    function envBool(string calldata) external returns(bool);
+    function envUint(string calldata) external returns(uint256);
+    function envInt(string calldata) external returns(int256);
+    function envAddress(string calldata) external returns(address);
+    function envBytes32(string calldata) external returns(bytes32);
+    function envString(string calldata) external returns(string memory);
+    function envBytes(string calldata) external returns(bytes memory);
+    // Same as above, but returns the given default value if the env var is not set
+    function envBool(string calldata,bool) external returns(bool);
+    function envUint(string calldata,uint256) external returns(uint256);
+    function envInt(string calldata,int256) external returns(int256);
+    function envAddress(string calldata,address) external returns(address);
+    function envBytes32(string calldata,bytes32) external returns(bytes32);
+    function envString(string calldata,string calldata) external returns(string memory);
+    function envBytes(string calldata,bytes calldata) external returns(bytes memory);
+    // Convert values to strings
+    function toString(address) external returns(string memory);
+    function toString(bytes calldata) external returns(string memory);
+    function toString(bytes32) external returns(string memory);
+    function toString(bool) external returns(string memory);
+    function toString(uint256) external returns(string memory);
+    function toString(int256) external returns(string memory);
+    // Parse strings as values
+    function parseBytes(string calldata) external returns(bytes memory);
+    function parseAddress(string calldata) external returns(address);
+    function parseUint(string calldata) external returns(uint256);
+    function parseInt(string calldata) external returns(int256);
+    function parseBytes32(string calldata) external returns(bytes32);
+    function parseBool(string calldata) external returns(bool);
+    // ABI encode values of JSON strings
+    function parseJson(string calldata,string calldata) external returns(bytes memory);
+    function parseJson(string calldata) external returns(bytes memory);
 }
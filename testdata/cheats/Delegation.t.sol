@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public {
+        count++;
+    }
+}
+
+contract DoubleCounter {
+    uint256 public count;
+
+    function increment() public {
+        count += 2;
+    }
+}
+
+contract DelegationTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    uint256 constant PK = 0xa11ce;
+
+    function testAttachDelegation() public {
+        Counter implementation = new Counter();
+        address authority = cheats.addr(PK);
+
+        Cheats.SignedDelegation memory delegation = cheats.signDelegation(address(implementation), PK);
+        assertEq(delegation.nonce, 0);
+        assertEq(delegation.implementation, address(implementation));
+
+        cheats.attachDelegation(delegation);
+        assertEq(cheats.getNonce(authority), 1);
+
+        Counter(authority).increment();
+        assertEq(Counter(authority).count(), 1);
+        assertEq(implementation.count(), 0);
+    }
+
+    function testClearDelegation() public {
+        Counter implementation = new Counter();
+        address authority = cheats.addr(PK);
+
+        cheats.attachDelegation(cheats.signDelegation(address(implementation), PK));
+        assertEq(authority.code.length, 23);
+
+        cheats.attachDelegation(cheats.signDelegation(address(0), PK));
+        assertEq(authority.code.length, 0);
+    }
+
+    function testDelegationDesignator() public {
+        Counter implementation = new Counter();
+        address authority = cheats.addr(PK);
+
+        cheats.attachDelegation(cheats.signDelegation(address(implementation), PK));
+        bytes memory designator = abi.encodePacked(hex"ef0100", address(implementation));
+        assertEq(keccak256(authority.code), keccak256(designator));
+        assertEq(authority.codehash, keccak256(designator));
+    }
+
+    function testRedelegate() public {
+        address authority = cheats.addr(PK);
+
+        cheats.attachDelegation(cheats.signDelegation(address(new Counter()), PK));
+        Counter(authority).increment();
+
+        cheats.attachDelegation(cheats.signDelegation(address(new DoubleCounter()), PK));
+        Counter(authority).increment();
+        assertEq(Counter(authority).count(), 3);
+        assertEq(cheats.getNonce(authority), 2);
+    }
+
+    function testDelegationChargesGas() public {
+        Cheats.SignedDelegation memory delegation = cheats.signDelegation(address(new Counter()), PK);
+
+        uint256 gasBefore = gasleft();
+        cheats.attachDelegation(delegation);
+        assertGe(gasBefore - gasleft(), 25000);
+    }
+
+    function testFailReplayDelegation() public {
+        Counter implementation = new Counter();
+        Cheats.SignedDelegation memory delegation = cheats.signDelegation(address(implementation), PK);
+        cheats.attachDelegation(delegation);
+        cheats.attachDelegation(delegation);
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract DeriveKeyTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    string constant MNEMONIC = "test test test test test test test test test test test junk";
+
+    function testDeriveKey() public {
+        uint256 pk = cheats.deriveKey(MNEMONIC, 0);
+        assertEq(pk, 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80);
+        assertEq(cheats.addr(pk), 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266);
+
+        pk = cheats.deriveKey(MNEMONIC, 1);
+        assertEq(pk, 0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d);
+        assertEq(cheats.addr(pk), 0x70997970C51812dc3A010C7d01b50e0d17dc79C8);
+    }
+
+    function testDeriveKeyWithPath() public {
+        assertEq(
+            cheats.deriveKey(MNEMONIC, "m/44'/60'/0'/0/", 1),
+            cheats.deriveKey(MNEMONIC, 1)
+        );
+        assertEq(
+            cheats.deriveKey(MNEMONIC, "m/44'/60'/0'/0", 1),
+            cheats.deriveKey(MNEMONIC, 1)
+        );
+        assertTrue(cheats.deriveKey(MNEMONIC, "m/44'/60'/1'/0/", 0) != cheats.deriveKey(MNEMONIC, 0));
+    }
+
+    function testSignWithDerivedKey(bytes32 digest) public {
+        uint256 pk = cheats.deriveKey(MNEMONIC, 0);
+        (uint8 v, bytes32 r, bytes32 s) = cheats.sign(pk, digest);
+        assertEq(ecrecover(digest, v, r, s), cheats.addr(pk));
+    }
+
+    function testFailDeriveKeyInvalidMnemonic() public {
+        cheats.deriveKey("not a mnemonic", 0);
+    }
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract DifferentialTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function echo() internal pure returns (string[] memory inputs) {
+        inputs = new string[](1);
+        inputs[0] = "cat";
+    }
+
+    function testDifferential(uint256 x) public {
+        bytes memory res = cheats.differential(echo(), abi.encode(x));
+        assertEq(abi.decode(res, (uint256)), x, "differential failed");
+    }
+
+    function testDifferentialReusesProcess() public {
+        string[] memory inputs = new string[](3);
+        inputs[0] = "sh";
+        inputs[1] = "-c";
+        // prints the number of inputs read so far
+        inputs[2] = "i=0; while read line; do i=$((i+1)); printf '0x%064x\\n' $i; done";
+
+        cheats.differential(inputs, "");
+        bytes memory res = cheats.differential(inputs, "");
+        assertEq(abi.decode(res, (uint256)), 2, "process was not reused");
+    }
+
+    function testFailDifferentialExited() public {
+        string[] memory inputs = new string[](1);
+        inputs[0] = "true";
+        cheats.differential(inputs, "");
+    }
+}
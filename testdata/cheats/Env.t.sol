@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract EnvTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testEnvBool() public {
+        cheats.setEnv("_FOUNDRY_ENV_BOOL", "true");
+        assertTrue(cheats.envBool("_FOUNDRY_ENV_BOOL"));
+        cheats.setEnv("_FOUNDRY_ENV_BOOL", "False");
+        assertTrue(!cheats.envBool("_FOUNDRY_ENV_BOOL"));
+    }
+
+    function testEnvUint() public {
+        cheats.setEnv("_FOUNDRY_ENV_UINT", "1000");
+        assertEq(cheats.envUint("_FOUNDRY_ENV_UINT"), 1000);
+        cheats.setEnv("_FOUNDRY_ENV_UINT", "0xff");
+        assertEq(cheats.envUint("_FOUNDRY_ENV_UINT"), 255);
+    }
+
+    function testEnvInt() public {
+        cheats.setEnv("_FOUNDRY_ENV_INT", "-1000");
+        assertEq(cheats.envInt("_FOUNDRY_ENV_INT"), -1000);
+        cheats.setEnv(
+            "_FOUNDRY_ENV_INT",
+            "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        );
+        assertEq(cheats.envInt("_FOUNDRY_ENV_INT"), -1);
+    }
+
+    function testEnvAddress() public {
+        cheats.setEnv("_FOUNDRY_ENV_ADDRESS", "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D");
+        assertEq(cheats.envAddress("_FOUNDRY_ENV_ADDRESS"), HEVM_ADDRESS);
+    }
+
+    function testEnvBytes32() public {
+        cheats.setEnv("_FOUNDRY_ENV_BYTES32", "0x1234");
+        assertEq(cheats.envBytes32("_FOUNDRY_ENV_BYTES32"), bytes32(hex"1234"));
+    }
+
+    function testEnvString() public {
+        cheats.setEnv("_FOUNDRY_ENV_STRING", "hello, world");
+        assertEq(cheats.envString("_FOUNDRY_ENV_STRING"), "hello, world");
+    }
+
+    function testEnvBytes() public {
+        cheats.setEnv("_FOUNDRY_ENV_BYTES", "0x7109709ecfa91a80626ff3989d68f67f5b1dd12d");
+        assertEq(
+            keccak256(cheats.envBytes("_FOUNDRY_ENV_BYTES")),
+            keccak256(abi.encodePacked(HEVM_ADDRESS))
+        );
+    }
+
+    function testEnvDefaults() public {
+        string memory unset = "_FOUNDRY_ENV_UNSET";
+        assertTrue(cheats.envBool(unset, true));
+        assertEq(cheats.envUint(unset, 42), 42);
+        assertEq(cheats.envInt(unset, -42), -42);
+        assertEq(cheats.envAddress(unset, address(1)), address(1));
+        assertEq(cheats.envBytes32(unset, bytes32(uint256(1))), bytes32(uint256(1)));
+        assertEq(cheats.envString(unset, "default"), "default");
+        assertEq(keccak256(cheats.envBytes(unset, hex"01")), keccak256(hex"01"));
+    }
+
+    function testEnvDefaultIgnoredIfSet() public {
+        cheats.setEnv("_FOUNDRY_ENV_SET", "1");
+        assertEq(cheats.envUint("_FOUNDRY_ENV_SET", 42), 1);
+    }
+
+    function testFailEnvUnset() public {
+        cheats.envUint("_FOUNDRY_ENV_UNSET");
+    }
+
+    function testFailEnvInvalid() public {
+        cheats.setEnv("_FOUNDRY_ENV_INVALID", "not a number");
+        cheats.envUint("_FOUNDRY_ENV_INVALID");
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Child {
+    uint256 public value;
+
+    constructor(uint256 _value) {
+        value = _value;
+    }
+}
+
+contract Factory {
+    function create(uint256 value) public returns (Child) {
+        return new Child(value);
+    }
+
+    function create2(uint256 value, bytes32 salt) public returns (Child) {
+        return new Child{salt: salt}(value);
+    }
+
+    function predict(uint256 value, bytes32 salt) public view returns (address) {
+        bytes32 hash = keccak256(
+            abi.encodePacked(
+                bytes1(0xff),
+                address(this),
+                salt,
+                keccak256(abi.encodePacked(type(Child).creationCode, value))
+            )
+        );
+        return address(uint160(uint256(hash)));
+    }
+}
+
+contract ExpectCreateTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testExpectCreate() public {
+        Factory factory = new Factory();
+        cheats.expectCreate(type(Child).creationCode);
+        factory.create(1);
+    }
+
+    function testExpectCreateWithArgs() public {
+        Factory factory = new Factory();
+        cheats.expectCreate(abi.encodePacked(type(Child).creationCode, uint256(42)));
+        factory.create(42);
+    }
+
+    function testFailExpectCreateWithArgs() public {
+        Factory factory = new Factory();
+        cheats.expectCreate(abi.encodePacked(type(Child).creationCode, uint256(42)));
+        factory.create(1);
+    }
+
+    function testFailExpectCreateWithoutCreation() public {
+        cheats.expectCreate(type(Child).creationCode);
+    }
+
+    function testExpectCreate2() public {
+        Factory factory = new Factory();
+        address predicted = factory.predict(1, bytes32(uint256(7)));
+        cheats.expectCreate2(predicted);
+        factory.create2(1, bytes32(uint256(7)));
+    }
+
+    function testFailExpectCreate2WithCreate() public {
+        Factory factory = new Factory();
+        address predicted = factory.predict(1, bytes32(uint256(7)));
+        cheats.expectCreate2(predicted);
+        factory.create(1);
+    }
+
+    function testFailExpectCreate2WithOtherSalt() public {
+        Factory factory = new Factory();
+        address predicted = factory.predict(1, bytes32(uint256(7)));
+        cheats.expectCreate2(predicted);
+        factory.create2(1, bytes32(uint256(8)));
+    }
+}
@@ -71,6 +71,7 @@ contract LowLevelCaller {
 
 contract ExpectEmitTest is DSTest {
     Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    bytes32 constant ANY_TOPIC = keccak256("ANY_TOPIC");
     Emitter emitter;
 
     event Something(
@@ -263,4 +264,79 @@ contract ExpectEmitTest is DSTest {
         // amounts of indexed topics.
         emitter.emitEvent(1, 2, 3, 4);
     }
+
+    function testExpectEmitCount() public {
+        cheats.expectEmit(true, false, false, true, 2);
+        emit Something(1, 0, 0, 4);
+        emitter.emitMultiple(
+            [uint256(1), uint256(1)],
+            [uint256(2), uint256(5)],
+            [uint256(3), uint256(6)],
+            [uint256(4), uint256(4)]
+        );
+    }
+
+    function testFailExpectEmitCountTooFew() public {
+        cheats.expectEmit(true, true, true, true, 2);
+        emit Something(1, 2, 3, 4);
+        emitter.emitEvent(1, 2, 3, 4);
+    }
+
+    function testFailExpectEmitCountTooMany() public {
+        cheats.expectEmit(true, false, false, false, 1);
+        emit Something(1, 0, 0, 0);
+        emitter.emitMultiple(
+            [uint256(1), uint256(1)],
+            [uint256(2), uint256(5)],
+            [uint256(3), uint256(6)],
+            [uint256(4), uint256(7)]
+        );
+    }
+
+    function testExpectEmitCountWithAddress() public {
+        Emitter other = new Emitter();
+        cheats.expectEmit(true, true, true, true, address(emitter), 0);
+        emit Something(1, 2, 3, 4);
+        other.emitEvent(1, 2, 3, 4);
+    }
+
+    function testExpectNoEmit() public {
+        // Only topic 0 is checked, so no `Something` event may be emitted
+        cheats.expectEmit(false, false, false, false, 0);
+        emit Something(0, 0, 0, 0);
+        emitter.emitSomethingElse(1);
+    }
+
+    function testFailExpectNoEmit() public {
+        cheats.expectEmit(false, false, false, false, 0);
+        emit Something(0, 0, 0, 0);
+        emitter.emitEvent(1, 2, 3, 4);
+    }
+
+    function testExpectLogWithWildcards() public {
+        bytes32[] memory topics = new bytes32[](4);
+        topics[0] = keccak256("Something(uint256,uint256,uint256,uint256)");
+        topics[1] = bytes32(uint256(1));
+        topics[2] = ANY_TOPIC;
+        topics[3] = bytes32(uint256(3));
+
+        cheats.expectLog(topics, "", 2);
+        emitter.emitMultiple(
+            [uint256(1), uint256(1)],
+            [uint256(2), uint256(5)],
+            [uint256(3), uint256(3)],
+            [uint256(4), uint256(7)]
+        );
+    }
+
+    function testFailExpectLogWithWildcards() public {
+        bytes32[] memory topics = new bytes32[](4);
+        topics[0] = keccak256("Something(uint256,uint256,uint256,uint256)");
+        topics[1] = ANY_TOPIC;
+        topics[2] = ANY_TOPIC;
+        topics[3] = bytes32(uint256(3));
+
+        cheats.expectLog(topics, abi.encode(uint256(4)), 1);
+        emitter.emitEvent(1, 2, 3, 5);
+    }
 }
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Hook {
+    uint256 public counter;
+
+    function cheap() public pure returns (uint256) {
+        return 1;
+    }
+
+    function expensive() public {
+        // a fresh storage write costs more than 20000 gas
+        counter = 1;
+    }
+
+    function nested() public returns (uint256) {
+        return this.cheap();
+    }
+}
+
+contract ExpectGasUsageTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    Hook hook;
+
+    function setUp() public {
+        hook = new Hook();
+    }
+
+    function testExpectGasUsage() public {
+        cheats.expectGasUsage(0, 1000);
+        hook.cheap();
+    }
+
+    function testExpectGasUsageOfStorageWrite() public {
+        cheats.expectGasUsage(20000, 30000);
+        hook.expensive();
+    }
+
+    function testExpectGasUsageOnlyAppliesToNextCall() public {
+        cheats.expectGasUsage(0, 1000);
+        hook.cheap();
+        hook.expensive();
+    }
+
+    function testExpectGasUsageIncludesNestedCalls() public {
+        cheats.expectGasUsage(1000, 10000);
+        hook.nested();
+    }
+
+    function testFailExpectGasUsageAboveMax() public {
+        cheats.expectGasUsage(0, 1000);
+        hook.expensive();
+    }
+
+    function testFailExpectGasUsageBelowMin() public {
+        cheats.expectGasUsage(20000, 30000);
+        hook.cheap();
+    }
+
+    function testFailExpectGasUsageWithoutCall() public {
+        cheats.expectGasUsage(0, 1000);
+    }
+
+    function testFailExpectGasUsageInvalidRange() public {
+        cheats.expectGasUsage(1000, 0);
+    }
+}
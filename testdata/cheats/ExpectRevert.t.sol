@@ -6,6 +6,7 @@ import "./Cheats.sol";
 
 contract Reverter {
     error CustomError();
+    error CustomErrorWithArgs(uint256 code, address who);
 
     function revertWithMessage(string memory message) public pure {
         require(false, message);
@@ -21,6 +22,10 @@ contract Reverter {
         revert CustomError();
     }
 
+    function revertWithCustomErrorArgs(uint256 code) public view {
+        revert CustomErrorWithArgs(code, msg.sender);
+    }
+
     function nestedRevert(Reverter inner, string memory message) public pure {
         inner.revertWithMessage(message);
     }
@@ -73,6 +78,40 @@ contract ExpectRevertTest is DSTest {
         reverter.revertWithCustomError();
     }
 
+    function testExpectRevertCustomErrorWithArgs() public {
+        Reverter reverter = new Reverter();
+        cheats.expectRevert(
+            abi.encodeWithSelector(Reverter.CustomErrorWithArgs.selector, 42, address(this))
+        );
+        reverter.revertWithCustomErrorArgs(42);
+    }
+
+    function testExpectPartialRevertCustomErrorSelector() public {
+        Reverter reverter = new Reverter();
+        cheats.expectPartialRevert(Reverter.CustomErrorWithArgs.selector);
+        reverter.revertWithCustomErrorArgs(42);
+    }
+
+    function testFailExpectRevertSelectorOfErrorWithArgs() public {
+        Reverter reverter = new Reverter();
+        cheats.expectRevert(Reverter.CustomErrorWithArgs.selector);
+        reverter.revertWithCustomErrorArgs(42);
+    }
+
+    function testFailExpectRevertCustomErrorArgsDoNotMatch() public {
+        Reverter reverter = new Reverter();
+        cheats.expectRevert(
+            abi.encodeWithSelector(Reverter.CustomErrorWithArgs.selector, 1, address(this))
+        );
+        reverter.revertWithCustomErrorArgs(42);
+    }
+
+    function testFailExpectRevertCustomErrorSelectorDoesNotMatch() public {
+        Reverter reverter = new Reverter();
+        cheats.expectPartialRevert(Reverter.CustomError.selector);
+        reverter.revertWithCustomErrorArgs(42);
+    }
+
     function testExpectRevertNested() public {
         Reverter reverter = new Reverter();
         Reverter inner = new Reverter();
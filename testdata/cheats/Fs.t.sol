@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract FsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testReadFile() public {
+        assertEq(cheats.readFile("./fixtures/File/read.txt"), "hello\nworld\n");
+    }
+
+    function testWriteFile() public {
+        string memory path = "./fixtures/File/write.txt";
+        cheats.writeFile(path, "hello");
+        assertEq(cheats.readFile(path), "hello");
+
+        cheats.writeFile(path, "world");
+        assertEq(cheats.readFile(path), "world");
+    }
+
+    function testFailReadFileNotAllowed() public {
+        cheats.readFile("foundry.toml");
+    }
+
+    function testFailWriteFileOutsideRoot() public {
+        cheats.writeFile("fixtures/../../secret.txt", "secret");
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract IsContextTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testIsContext() public {
+        assertTrue(cheats.isContext(Cheats.ExecutionContext.Test));
+        assertTrue(!cheats.isContext(Cheats.ExecutionContext.Coverage));
+        assertTrue(!cheats.isContext(Cheats.ExecutionContext.Script));
+        assertTrue(!cheats.isContext(Cheats.ExecutionContext.Broadcast));
+    }
+}
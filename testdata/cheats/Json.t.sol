@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract JsonTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    // the fields are in the alphabetical order of their keys
+    struct Token {
+        address addr;
+        uint256 decimals;
+        string symbol;
+    }
+
+    string constant json =
+        '{"owner": "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D", "offset": -5, "paused": false,'
+        ' "tokens": [{"symbol": "DAI", "decimals": 18, "addr": "0x6b175474e89094c44da98b954eedeac495271d0f"}]}';
+
+    function testParseJsonValues() public {
+        assertEq(abi.decode(cheats.parseJson(json, ".owner"), (address)), HEVM_ADDRESS);
+        assertEq(abi.decode(cheats.parseJson(json, "$.offset"), (int256)), -5);
+        assertTrue(!abi.decode(cheats.parseJson(json, ".paused"), (bool)));
+        assertEq(abi.decode(cheats.parseJson(json, ".tokens[0].decimals"), (uint256)), 18);
+        assertEq(abi.decode(cheats.parseJson(json, ".tokens[0].symbol"), (string)), "DAI");
+    }
+
+    function testParseJsonStruct() public {
+        Token memory token = abi.decode(cheats.parseJson(json, ".tokens[0]"), (Token));
+        assertEq(token.addr, 0x6B175474E89094C44Da98b954EedeAC495271d0F);
+        assertEq(token.decimals, 18);
+        assertEq(token.symbol, "DAI");
+
+        Token[] memory tokens = abi.decode(cheats.parseJson(json, ".tokens"), (Token[]));
+        assertEq(tokens.length, 1);
+        assertEq(tokens[0].symbol, "DAI");
+    }
+
+    function testParseJsonWhole() public {
+        assertEq(abi.decode(cheats.parseJson("[1, 2, 3]"), (uint256[]))[2], 3);
+    }
+
+    function testFailParseJsonMissingKey() public {
+        cheats.parseJson(json, ".tokens[1]");
+    }
+
+    function testFailParseJsonInvalid() public {
+        cheats.parseJson("{", "");
+    }
+}
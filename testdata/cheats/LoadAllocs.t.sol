@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract LoadAllocsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    address constant ACCOUNT = 0x1000000000000000000000000000000000000001;
+    address constant CONTRACT = 0x2000000000000000000000000000000000000002;
+
+    function testLoadAllocs() public {
+        cheats.loadAllocs("./fixtures/Allocs/genesis.json");
+
+        assertEq(ACCOUNT.balance, 1 ether);
+        assertEq(cheats.getNonce(ACCOUNT), 5);
+
+        assertEq(CONTRACT.balance, 0);
+        assertEq(uint256(cheats.load(CONTRACT, bytes32(uint256(0)))), 42);
+        assertEq(uint256(cheats.load(CONTRACT, bytes32(uint256(1)))), 7);
+
+        // the code returns the value of slot 0
+        (bool success, bytes memory data) = CONTRACT.staticcall("");
+        assertTrue(success);
+        assertEq(abi.decode(data, (uint256)), 42);
+    }
+
+    function testFailLoadAllocsNotAllowed() public {
+        cheats.loadAllocs("foundry.toml");
+    }
+}
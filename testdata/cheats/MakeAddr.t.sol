@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract MakeAddrTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testMakeAddr() public {
+        address alice = cheats.makeAddr("alice");
+        assertEq(alice, cheats.addr(uint256(keccak256("alice"))));
+        assertEq(cheats.makeAddr("alice"), alice);
+        assertTrue(alice != cheats.makeAddr("bob"));
+    }
+
+    function testMakeAccount() public {
+        (address alice, uint256 key) = cheats.makeAccount("alice");
+        assertEq(key, uint256(keccak256("alice")));
+        assertEq(alice, cheats.makeAddr("alice"));
+    }
+
+    function testMakeAddrFundsOnce() public {
+        // `labeled_account_balance` is 100 ether in the test config
+        address alice = cheats.makeAddr("alice");
+        assertEq(alice.balance, 100 ether);
+
+        cheats.deal(alice, 1 ether);
+        cheats.makeAddr("alice");
+        assertEq(alice.balance, 1 ether);
+    }
+
+    function testMakeAddrFundsLabeledAddress() public {
+        address carol = cheats.addr(uint256(keccak256("carol")));
+        cheats.label(carol, "carol");
+        assertEq(cheats.makeAddr("carol"), carol);
+        assertEq(carol.balance, 100 ether);
+    }
+}
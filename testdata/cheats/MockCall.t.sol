@@ -188,4 +188,16 @@ contract MockCallTest is DSTest {
         assertEq(mock.pay{value: 10}(2), 2);
         assertEq(mock.pay(2), 2);
     }
+
+    function testMockCallEmptyAccount() public {
+        Mock mock = Mock(address(100));
+
+        cheats.mockCall(
+            address(mock),
+            abi.encodeWithSelector(mock.numberB.selector),
+            abi.encode(10)
+        );
+
+        assertEq(mock.numberB(), 10);
+    }
 }
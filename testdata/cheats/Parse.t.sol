@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract ParseTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testToString() public {
+        assertEq(cheats.toString(HEVM_ADDRESS), "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D");
+        assertEq(cheats.toString(hex"7109709e"), "0x7109709e");
+        assertEq(
+            cheats.toString(bytes32(uint256(1))),
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assertEq(cheats.toString(true), "true");
+        assertEq(cheats.toString(uint256(1000)), "1000");
+        assertEq(cheats.toString(int256(-1000)), "-1000");
+    }
+
+    function testParse() public {
+        assertEq(cheats.parseBytes("0x7109709e"), hex"7109709e");
+        assertEq(cheats.parseAddress("0x7109709ECfa91a80626fF3989D68f67F5b1DD12D"), HEVM_ADDRESS);
+        assertEq(cheats.parseUint("1000"), 1000);
+        assertEq(cheats.parseUint("0xff"), 255);
+        assertEq(cheats.parseInt("-1000"), -1000);
+        assertEq(cheats.parseBytes32("0x01"), bytes32(uint256(1) << 248));
+        assertTrue(cheats.parseBool("true"));
+        assertTrue(!cheats.parseBool("False"));
+    }
+
+    function testParseToStringRoundtrip() public {
+        assertEq(cheats.parseUint(cheats.toString(uint256(type(uint256).max))), type(uint256).max);
+        assertEq(cheats.parseInt(cheats.toString(type(int256).min)), type(int256).min);
+        assertEq(cheats.parseAddress(cheats.toString(address(this))), address(this));
+    }
+
+    function testFailParseUint() public {
+        cheats.parseUint("one");
+    }
+
+    function testFailParseAddress() public {
+        cheats.parseAddress("0x1234");
+    }
+}
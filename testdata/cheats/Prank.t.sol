@@ -323,4 +323,28 @@ contract PrankTest is DSTest {
             "tx.origin was not set correctly"
         );
     }
+
+    function testReadCallers(address sender, address origin) public {
+        (Cheats.CallerMode mode, address msgSender, address txOrigin) = cheats.readCallers();
+        assertEq(uint256(mode), uint256(Cheats.CallerMode.None));
+        assertEq(msgSender, tx.origin);
+        assertEq(txOrigin, tx.origin);
+
+        cheats.prank(sender);
+        (mode, msgSender, txOrigin) = cheats.readCallers();
+        assertEq(uint256(mode), uint256(Cheats.CallerMode.Prank));
+        assertEq(msgSender, sender);
+        assertEq(txOrigin, tx.origin);
+        cheats.stopPrank();
+
+        cheats.startPrank(sender, origin);
+        (mode, msgSender, txOrigin) = cheats.readCallers();
+        assertEq(uint256(mode), uint256(Cheats.CallerMode.RecurrentPrank));
+        assertEq(msgSender, sender);
+        assertEq(txOrigin, origin);
+        cheats.stopPrank();
+
+        (mode, , ) = cheats.readCallers();
+        assertEq(uint256(mode), uint256(Cheats.CallerMode.None));
+    }
 }
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Emitter {
+    event LogTopic1(uint256 indexed topic1, bytes data);
+    event LogTopic12(uint256 indexed topic1, uint256 indexed topic2, bytes data);
+
+    function emitEvent(uint256 topic1, bytes memory data) public {
+        emit LogTopic1(topic1, data);
+    }
+
+    function emitEvent(uint256 topic1, uint256 topic2, bytes memory data) public {
+        emit LogTopic12(topic1, topic2, data);
+    }
+}
+
+contract RecordLogsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    Emitter emitter;
+
+    function setUp() public {
+        emitter = new Emitter();
+    }
+
+    function testRecordLogs() public {
+        cheats.recordLogs();
+        emitter.emitEvent(1, "one");
+        emitter.emitEvent(2, 3, "two");
+
+        Cheats.Log[] memory logs = cheats.getRecordedLogs();
+        assertEq(logs.length, 2);
+
+        assertEq(logs[0].emitter, address(emitter));
+        assertEq(logs[0].topics.length, 2);
+        assertEq(logs[0].topics[0], keccak256("LogTopic1(uint256,bytes)"));
+        assertEq(logs[0].topics[1], bytes32(uint256(1)));
+        assertEq(abi.decode(logs[0].data, (string)), "one");
+
+        assertEq(logs[1].topics.length, 3);
+        assertEq(logs[1].topics[0], keccak256("LogTopic12(uint256,uint256,bytes)"));
+        assertEq(logs[1].topics[1], bytes32(uint256(2)));
+        assertEq(logs[1].topics[2], bytes32(uint256(3)));
+        assertEq(abi.decode(logs[1].data, (string)), "two");
+    }
+
+    function testGetRecordedLogsClearsLogs() public {
+        cheats.recordLogs();
+        emitter.emitEvent(1, "one");
+        assertEq(cheats.getRecordedLogs().length, 1);
+
+        emitter.emitEvent(2, "two");
+        Cheats.Log[] memory logs = cheats.getRecordedLogs();
+        assertEq(logs.length, 1);
+        assertEq(logs[0].topics[1], bytes32(uint256(2)));
+    }
+
+    function testNoLogsWithoutRecording() public {
+        emitter.emitEvent(1, "one");
+        assertEq(cheats.getRecordedLogs().length, 0);
+    }
+}
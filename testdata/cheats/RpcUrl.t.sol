@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract RpcUrlTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testRpcUrl() public {
+        string memory url = cheats.rpcUrl("mainnet");
+        assertEq(url, "https://example.com/mainnet");
+    }
+}
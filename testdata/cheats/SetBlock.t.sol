@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract SetBlockTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testRollBy() public {
+        cheats.roll(10);
+        cheats.rollBy(5);
+        assertEq(block.number, 15, "rollBy failed");
+        cheats.rollBy(-10);
+        assertEq(block.number, 5, "rollBy failed");
+    }
+
+    function testWarpBy() public {
+        cheats.warp(100);
+        cheats.warpBy(20);
+        assertEq(block.timestamp, 120, "warpBy failed");
+        cheats.warpBy(-120);
+        assertEq(block.timestamp, 0, "warpBy failed");
+    }
+
+    function testFailRollByUnderflow() public {
+        cheats.roll(10);
+        cheats.rollBy(-11);
+    }
+
+    function testFailWarpByUnderflow() public {
+        cheats.warp(10);
+        cheats.warpBy(-11);
+    }
+
+    function testSetBlock() public {
+        cheats.setBlock(Cheats.BlockContext({
+            number: 100,
+            timestamp: 1000,
+            basefee: 10 gwei,
+            prevrandao: 42,
+            coinbase: address(0xbeef)
+        }));
+        assertEq(block.number, 100, "number failed");
+        assertEq(block.timestamp, 1000, "timestamp failed");
+        assertEq(block.basefee, 10 gwei, "basefee failed");
+        assertEq(block.difficulty, 42, "prevrandao failed");
+        assertEq(block.coinbase, address(0xbeef), "coinbase failed");
+    }
+}
@@ -20,4 +20,40 @@ contract SignTest is DSTest {
     function testSignMessage(uint248 pk, bytes memory message) public {
         testSignDigest(pk, keccak256(message));
     }
+
+    function testSignCompact(uint248 pk, bytes32 digest) public {
+        cheats.assume(pk != 0);
+
+        (uint8 v, bytes32 r, bytes32 s) = cheats.sign(pk, digest);
+        (bytes32 compactR, bytes32 vs) = cheats.signCompact(pk, digest);
+        assertEq(compactR, r);
+        assertEq(vs & bytes32(type(uint256).max >> 1), s);
+        assertEq(uint8(uint256(vs >> 255)) + 27, v);
+    }
+
+    function testSignAggregate(bytes32 digest) public {
+        uint256[] memory pks = new uint256[](3);
+        pks[0] = 1;
+        pks[1] = 2;
+        pks[2] = 3;
+
+        bytes memory signatures = cheats.signAggregate(pks, digest);
+        assertEq(signatures.length, 3 * 65);
+
+        address previous = address(0);
+        for (uint256 i = 0; i < pks.length; i++) {
+            bytes32 r;
+            bytes32 s;
+            uint8 v;
+            assembly {
+                let offset := add(signatures, mul(i, 65))
+                r := mload(add(offset, 32))
+                s := mload(add(offset, 64))
+                v := byte(0, mload(add(offset, 96)))
+            }
+            address signer = ecrecover(digest, v, r, s);
+            assertTrue(signer > previous, "signers not in ascending order");
+            previous = signer;
+        }
+    }
 }
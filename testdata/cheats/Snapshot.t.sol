@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public {
+        count++;
+    }
+}
+
+contract SnapshotReverter {
+    Cheats constant cheats = Cheats(0x7109709ECfa91a80626fF3989D68f67F5b1DD12D);
+
+    function revertTo(uint256 snapshot) public returns (bool) {
+        return cheats.revertTo(snapshot);
+    }
+}
+
+contract SnapshotTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    Counter counter;
+    uint256 value;
+
+    function setUp() public {
+        counter = new Counter();
+        counter.increment();
+        value = 1;
+    }
+
+    function testSnapshot() public {
+        uint256 snapshot = cheats.snapshot();
+
+        counter.increment();
+        value = 2;
+        cheats.deal(address(100), 1 ether);
+        cheats.warp(1000);
+        cheats.roll(50);
+        assertEq(counter.count(), 2);
+
+        assertTrue(cheats.revertTo(snapshot));
+        assertEq(counter.count(), 1);
+        assertEq(value, 1);
+        assertEq(address(100).balance, 0);
+        assertEq(block.timestamp, 1);
+        assertEq(block.number, 1);
+    }
+
+    function testRevertToMultipleTimes() public {
+        uint256 snapshot = cheats.snapshot();
+
+        counter.increment();
+        assertTrue(cheats.revertTo(snapshot));
+        assertEq(counter.count(), 1);
+
+        counter.increment();
+        counter.increment();
+        assertTrue(cheats.revertTo(snapshot));
+        assertEq(counter.count(), 1);
+    }
+
+    function testRevertToNestedSnapshots() public {
+        uint256 first = cheats.snapshot();
+        counter.increment();
+        uint256 second = cheats.snapshot();
+        counter.increment();
+
+        assertTrue(cheats.revertTo(second));
+        assertEq(counter.count(), 2);
+        assertTrue(cheats.revertTo(first));
+        assertEq(counter.count(), 1);
+    }
+
+    function testRevertToUnknownSnapshot() public {
+        assertTrue(!cheats.revertTo(1000));
+    }
+
+    function testSnapshotDeployedContract() public {
+        uint256 snapshot = cheats.snapshot();
+        Counter deployed = new Counter();
+        assertTrue(address(deployed).code.length > 0);
+
+        assertTrue(cheats.revertTo(snapshot));
+        assertEq(address(deployed).code.length, 0);
+    }
+
+    function testRevertToInNestedCall() public {
+        uint256 snapshot = cheats.snapshot();
+        counter.increment();
+
+        SnapshotReverter reverter = new SnapshotReverter();
+        cheats.expectRevert(abi.encode("`revertTo` can only be called by the test contract"));
+        reverter.revertTo(snapshot);
+        assertEq(counter.count(), 2);
+    }
+
+    function testRevertToRemovesLogs() public {
+        cheats.recordLogs();
+        emit log("before");
+        uint256 snapshot = cheats.snapshot();
+        emit log("after");
+
+        assertTrue(cheats.revertTo(snapshot));
+        Cheats.Log[] memory logs = cheats.getRecordedLogs();
+        assertEq(logs.length, 1);
+    }
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract ParameterizedTest is DSTest {
+    uint8 decimals;
+    string symbol;
+
+    constructor(uint8 _decimals, string memory _symbol) {
+        decimals = _decimals;
+        symbol = _symbol;
+    }
+
+    function testScale() public {
+        assertEq(10**decimals / 10**(decimals - 1), 10);
+    }
+
+    function testSixDecimals() public {
+        require(decimals == 6, string(abi.encodePacked(symbol, " does not have 6 decimals")));
+    }
+}
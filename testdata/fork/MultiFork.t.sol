@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+interface IWETH {
+    function deposit() external payable;
+    function balanceOf(address) external view returns (uint256);
+}
+
+contract MultiForkTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    IWETH constant WETH = IWETH(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2);
+
+    uint256 constant BLOCK = 15_000_000;
+
+    uint256 first;
+    uint256 second;
+
+    function setUp() public {
+        first = cheats.createFork("mainnet", BLOCK);
+        second = cheats.createFork("mainnet", BLOCK + 100);
+    }
+
+    function testSelectFork() public {
+        cheats.selectFork(first);
+        assertEq(block.number, BLOCK);
+
+        cheats.selectFork(second);
+        assertEq(block.number, BLOCK + 100);
+    }
+
+    function testForksHaveIndependentState() public {
+        cheats.selectFork(first);
+        uint256 balance = WETH.balanceOf(address(this));
+        WETH.deposit{value: 1 ether}();
+        assertEq(WETH.balanceOf(address(this)), balance + 1 ether);
+
+        cheats.selectFork(second);
+        assertEq(WETH.balanceOf(address(this)), balance);
+
+        cheats.selectFork(first);
+        assertEq(WETH.balanceOf(address(this)), balance + 1 ether);
+    }
+
+    function testTestContractIsPersistent() public {
+        cheats.selectFork(first);
+        first = 42;
+
+        cheats.selectFork(second);
+        assertEq(first, 42);
+    }
+
+    function testMakePersistent() public {
+        address alice = cheats.makeAddr("alice");
+        cheats.makePersistent(alice);
+        assertTrue(cheats.isPersistent(alice));
+        assertTrue(cheats.isPersistent(address(this)));
+
+        cheats.selectFork(first);
+        cheats.deal(alice, 42 ether);
+
+        cheats.selectFork(second);
+        assertEq(alice.balance, 42 ether);
+    }
+
+    function testAccountsAreNotPersistent() public {
+        address bob = cheats.makeAddr("bob");
+        assertTrue(!cheats.isPersistent(bob));
+
+        cheats.selectFork(first);
+        cheats.deal(bob, 42 ether);
+
+        cheats.selectFork(second);
+        assertEq(bob.balance, 0);
+    }
+
+    function testRollFork() public {
+        cheats.selectFork(first);
+        cheats.rollFork(BLOCK + 1);
+        assertEq(block.number, BLOCK + 1);
+
+        cheats.rollFork(second, BLOCK + 2);
+        cheats.selectFork(second);
+        assertEq(block.number, BLOCK + 2);
+    }
+
+    function testFailSelectUnknownFork() public {
+        cheats.selectFork(100);
+    }
+
+    receive() external payable {}
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract Breakable {
+    bool public flag0 = true;
+    bool public flag1 = true;
+
+    function set0(int256 val) public returns (bool) {
+        if (val % 100 == 0) flag0 = false;
+        return flag0;
+    }
+
+    function set1(int256 val) public returns (bool) {
+        if (val % 10 == 0 && !flag0) flag1 = false;
+        return flag1;
+    }
+}
+
+contract InvariantBreakerTest is DSTest {
+    Breakable breakable;
+
+    function setUp() public {
+        breakable = new Breakable();
+    }
+
+    function invariantNeverFalse() public {
+        require(breakable.flag1(), "false.");
+    }
+}
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public {
+        count++;
+    }
+}
+
+contract InvariantCounterTest is DSTest {
+    Counter counter;
+
+    function setUp() public {
+        counter = new Counter();
+    }
+
+    function targetContracts() public view returns (address[] memory targets) {
+        targets = new address[](1);
+        targets[0] = address(counter);
+    }
+
+    function invariantCountIsBounded() public {
+        // every run starts from the state after `setUp`
+        assertLe(counter.count(), 15);
+    }
+}
+
+contract RevertingHandler {
+    uint256 public sum;
+
+    function add(uint256 val) public {
+        require(val % 2 == 0, "odd");
+        sum = (sum + val) % 1000;
+    }
+}
+
+contract InvariantRevertTest is DSTest {
+    RevertingHandler handler;
+
+    function setUp() public {
+        handler = new RevertingHandler();
+    }
+
+    function targetContracts() public view returns (address[] memory targets) {
+        targets = new address[](1);
+        targets[0] = address(handler);
+    }
+
+    function invariantSumIsEven() public {
+        assertEq(handler.sum() % 2, 0);
+    }
+}
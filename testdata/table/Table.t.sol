@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract TableTest is DSTest {
+    function fixtureAmount() public pure returns (uint256[] memory amounts) {
+        amounts = new uint256[](3);
+        amounts[0] = 1;
+        amounts[1] = 100;
+        amounts[2] = 10001;
+    }
+
+    function fixtureTo() public pure returns (address[] memory to) {
+        to = new address[](3);
+        to[0] = address(0x1);
+        to[1] = address(0x2);
+        to[2] = address(0x3);
+    }
+
+    function tableTestTransfer(uint256 amount, address to) public {
+        require(to != address(0), "zero address");
+        require(amount <= 10000, "amount too large");
+    }
+
+    function tableTestMissingFixture(uint256 value) public {}
+}
+
+contract TableJsonTest is DSTest {
+    function tableTestDouble(uint256 value, uint256 doubled) public {
+        assertEq(value * 2, doubled);
+    }
+}
@@ -6,7 +6,13 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ethers::{solc::artifacts::ContractBytecodeSome, types::Address};
+use ethers::{
+    solc::{
+        artifacts::ContractBytecodeSome,
+        sourcemap::{SourceMap, SyntaxError},
+    },
+    types::{Address, U256},
+};
 use eyre::Result;
 use forge::{
     debug::{DebugStep, Instruction},
@@ -14,7 +20,7 @@ use forge::{
 };
 use std::{
     cmp::{max, min},
-    collections::{BTreeMap, HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     io,
     sync::mpsc,
     thread,
@@ -55,8 +61,11 @@ pub struct Tui {
     /// current step in the debug steps
     current_step: usize,
     identified_contracts: HashMap<Address, String>,
-    known_contracts: HashMap<String, ContractBytecodeSome>,
+    /// The parsed source maps of the identified contracts, by contract name
+    source_maps: HashMap<String, ContractSourceMaps>,
     source_code: BTreeMap<u32, String>,
+    /// Source lines execution stops at, `(source index, line)`
+    breakpoints: BTreeSet<(u32, usize)>,
 }
 
 impl Tui {
@@ -75,14 +84,23 @@ impl Tui {
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
         terminal.hide_cursor();
+        // the source maps are looked up at every step, they are parsed once per contract
+        let source_maps = identified_contracts
+            .values()
+            .filter_map(|name| {
+                let known = known_contracts.get(name)?;
+                Some((name.clone(), ContractSourceMaps::new(known)))
+            })
+            .collect();
         Ok(Tui {
             debug_arena,
             terminal,
             key_buffer: String::new(),
             current_step,
             identified_contracts,
-            known_contracts,
+            source_maps,
             source_code,
+            breakpoints: BTreeSet::new(),
         })
     }
 
@@ -105,7 +123,7 @@ impl Tui {
         f: &mut Frame<B>,
         address: Address,
         identified_contracts: &HashMap<Address, String>,
-        known_contracts: &HashMap<String, ContractBytecodeSome>,
+        source_maps: &HashMap<String, ContractSourceMaps>,
         source_code: &BTreeMap<u32, String>,
         debug_steps: &[DebugStep],
         opcode_list: &[String],
@@ -114,6 +132,8 @@ impl Tui {
         draw_memory: &mut DrawMemory,
         stack_labels: bool,
         mem_utf: bool,
+        storage: &BTreeMap<U256, U256>,
+        breakpoint: bool,
     ) {
         let total_size = f.size();
         if total_size.width < 225 {
@@ -121,7 +141,7 @@ impl Tui {
                 f,
                 address,
                 identified_contracts,
-                known_contracts,
+                source_maps,
                 source_code,
                 debug_steps,
                 opcode_list,
@@ -130,13 +150,15 @@ impl Tui {
                 draw_memory,
                 stack_labels,
                 mem_utf,
+                storage,
+                breakpoint,
             );
         } else {
             Tui::square_layout(
                 f,
                 address,
                 identified_contracts,
-                known_contracts,
+                source_maps,
                 source_code,
                 debug_steps,
                 opcode_list,
@@ -145,6 +167,8 @@ impl Tui {
                 draw_memory,
                 stack_labels,
                 mem_utf,
+                storage,
+                breakpoint,
             );
         }
     }
@@ -154,7 +178,7 @@ impl Tui {
         f: &mut Frame<B>,
         address: Address,
         identified_contracts: &HashMap<Address, String>,
-        known_contracts: &HashMap<String, ContractBytecodeSome>,
+        source_maps: &HashMap<String, ContractSourceMaps>,
         source_code: &BTreeMap<u32, String>,
         debug_steps: &[DebugStep],
         opcode_list: &[String],
@@ -163,6 +187,8 @@ impl Tui {
         draw_memory: &mut DrawMemory,
         stack_labels: bool,
         mem_utf: bool,
+        storage: &BTreeMap<U256, U256>,
+        breakpoint: bool,
     ) {
         let total_size = f.size();
         if let [app, footer] = Layout::default()
@@ -170,14 +196,15 @@ impl Tui {
             .constraints([Constraint::Ratio(98, 100), Constraint::Ratio(2, 100)].as_ref())
             .split(total_size)[..]
         {
-            if let [op_pane, stack_pane, memory_pane, src_pane] = Layout::default()
+            if let [op_pane, stack_pane, memory_pane, storage_pane, src_pane] = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints(
                     [
-                        Constraint::Ratio(1, 6),
-                        Constraint::Ratio(1, 6),
-                        Constraint::Ratio(1, 6),
-                        Constraint::Ratio(3, 6),
+                        Constraint::Ratio(2, 12),
+                        Constraint::Ratio(2, 12),
+                        Constraint::Ratio(2, 12),
+                        Constraint::Ratio(1, 12),
+                        Constraint::Ratio(5, 12),
                     ]
                     .as_ref(),
                 )
@@ -188,10 +215,11 @@ impl Tui {
                     f,
                     address,
                     identified_contracts,
-                    known_contracts,
+                    source_maps,
                     source_code,
                     debug_steps[current_step].ic,
                     call_kind,
+                    breakpoint,
                     src_pane,
                 );
                 Tui::draw_op_list(
@@ -212,6 +240,7 @@ impl Tui {
                     draw_memory,
                 );
                 Tui::draw_memory(f, debug_steps, current_step, memory_pane, mem_utf, draw_memory);
+                Tui::draw_storage(f, storage, storage_pane);
             } else {
                 panic!("unable to create vertical panes")
             }
@@ -225,7 +254,7 @@ impl Tui {
         f: &mut Frame<B>,
         address: Address,
         identified_contracts: &HashMap<Address, String>,
-        known_contracts: &HashMap<String, ContractBytecodeSome>,
+        source_maps: &HashMap<String, ContractSourceMaps>,
         source_code: &BTreeMap<u32, String>,
         debug_steps: &[DebugStep],
         opcode_list: &[String],
@@ -234,6 +263,8 @@ impl Tui {
         draw_memory: &mut DrawMemory,
         stack_labels: bool,
         mem_utf: bool,
+        storage: &BTreeMap<U256, U256>,
+        breakpoint: bool,
     ) {
         let total_size = f.size();
 
@@ -255,9 +286,16 @@ impl Tui {
                     .constraints([Constraint::Ratio(1, 4), Constraint::Ratio(3, 4)].as_ref())
                     .split(left_pane)[..]
                 {
-                    if let [stack_pane, memory_pane] = Layout::default()
+                    if let [stack_pane, memory_pane, storage_pane] = Layout::default()
                         .direction(Direction::Vertical)
-                        .constraints([Constraint::Ratio(1, 4), Constraint::Ratio(3, 4)].as_ref())
+                        .constraints(
+                            [
+                                Constraint::Ratio(1, 4),
+                                Constraint::Ratio(2, 4),
+                                Constraint::Ratio(1, 4),
+                            ]
+                            .as_ref(),
+                        )
                         .split(right_pane)[..]
                     {
                         Tui::draw_footer(f, footer);
@@ -265,10 +303,11 @@ impl Tui {
                             f,
                             address,
                             identified_contracts,
-                            known_contracts,
+                            source_maps,
                             source_code,
                             debug_steps[current_step].ic,
                             call_kind,
+                            breakpoint,
                             src_pane,
                         );
                         Tui::draw_op_list(
@@ -296,6 +335,7 @@ impl Tui {
                             mem_utf,
                             draw_memory,
                         );
+                        Tui::draw_storage(f, storage, storage_pane);
                     }
                 } else {
                     panic!("Couldn't generate horizontal split layout 1:2.");
@@ -312,7 +352,7 @@ impl Tui {
         let block_controls = Block::default();
 
         let text_output = Text::from(Span::styled(
-            "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [t]: toggle stack labels | [m]: toggle memory decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll memory",
+            "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [b]: toggle breakpoint | [n/N]: next/prev breakpoint | [t]: toggle stack labels | [m]: toggle memory decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll memory",
             Style::default().add_modifier(Modifier::DIM)
         ));
         let paragraph = Paragraph::new(text_output)
@@ -327,32 +367,30 @@ impl Tui {
         f: &mut Frame<B>,
         address: Address,
         identified_contracts: &HashMap<Address, String>,
-        known_contracts: &HashMap<String, ContractBytecodeSome>,
+        source_maps: &HashMap<String, ContractSourceMaps>,
         source_code: &BTreeMap<u32, String>,
         ic: usize,
         call_kind: CallKind,
+        breakpoint: bool,
         area: Rect,
     ) {
+        let title = match call_kind {
+            CallKind::Create => "Contract creation",
+            CallKind::Call => "Contract call",
+            CallKind::StaticCall => "Contract staticcall",
+            CallKind::CallCode => "Contract callcode",
+            CallKind::DelegateCall => "Contract delegatecall",
+        };
         let block_source_code = Block::default()
-            .title(match call_kind {
-                CallKind::Create => "Contract creation",
-                CallKind::Call => "Contract call",
-                CallKind::StaticCall => "Contract staticcall",
-                CallKind::CallCode => "Contract callcode",
-                CallKind::DelegateCall => "Contract delegatecall",
-            })
+            .title(if breakpoint { format!("{title} | Breakpoint") } else { title.to_string() })
             .borders(Borders::ALL);
 
         let mut text_output: Text = Text::from("");
 
         if let Some(contract_name) = identified_contracts.get(&address) {
-            if let Some(known) = known_contracts.get(contract_name) {
+            if let Some(source_maps) = source_maps.get(contract_name) {
                 // grab either the creation source map or runtime sourcemap
-                if let Some(sourcemap) = if matches!(call_kind, CallKind::Create) {
-                    known.bytecode.source_map()
-                } else {
-                    known.deployed_bytecode.bytecode.as_ref().expect("no bytecode").source_map()
-                } {
+                if let Some(sourcemap) = source_maps.get(call_kind) {
                     match sourcemap {
                         Ok(sourcemap) => {
                             // we are handed a vector of SourceElements that give
@@ -907,6 +945,140 @@ impl Tui {
         let paragraph = Paragraph::new(text).block(stack_space).wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
     }
+
+    /// Draw the storage slots written so far into the storage pane
+    fn draw_storage<B: Backend>(f: &mut Frame<B>, storage: &BTreeMap<U256, U256>, area: Rect) {
+        let storage_space = Block::default()
+            .title(format!("Storage writes: {}", storage.len()))
+            .borders(Borders::ALL);
+        let text: Vec<Spans> = storage
+            .iter()
+            .map(|(slot, value)| {
+                Spans::from(vec![
+                    Span::styled(format!("{slot:#x}"), Style::default().fg(Color::Cyan)),
+                    Span::raw(" => "),
+                    Span::styled(format!("{value:#x}"), Style::default().fg(Color::White)),
+                ])
+            })
+            .collect();
+        let paragraph = Paragraph::new(text).block(storage_space).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
+    /// Returns the source index and line of the source code the step executes, if it is known
+    fn source_line(
+        &self,
+        address: Address,
+        call_kind: CallKind,
+        ic: usize,
+    ) -> Option<(u32, usize)> {
+        let contract_name = self.identified_contracts.get(&address)?;
+        let sourcemap = self.source_maps.get(contract_name)?.get(call_kind)?.as_ref().ok()?;
+        let element = sourcemap.get(ic)?;
+        let source_idx = element.index?;
+        let source = self.source_code.get(&source_idx)?;
+        let line = source.get(..element.offset)?.matches('\n').count();
+        Some((source_idx, line))
+    }
+
+    /// Returns the step at which execution first reaches a breakpoint line after the step at
+    /// `(call index, step)`, searching backwards if `forward` is false
+    fn find_breakpoint(
+        &self,
+        debug_call: &[(Address, Vec<DebugStep>, CallKind)],
+        from: (usize, usize),
+        forward: bool,
+    ) -> Option<(usize, usize)> {
+        find_breakpoint(debug_call, from, forward, &self.breakpoints, |address, call_kind, ic| {
+            self.source_line(address, call_kind, ic)
+        })
+    }
+}
+
+/// The parsed creation and runtime source maps of a contract
+struct ContractSourceMaps {
+    creation: Option<Result<SourceMap, SyntaxError>>,
+    runtime: Option<Result<SourceMap, SyntaxError>>,
+}
+
+impl ContractSourceMaps {
+    fn new(known: &ContractBytecodeSome) -> Self {
+        Self {
+            creation: known.bytecode.source_map(),
+            runtime: known.deployed_bytecode.bytecode.as_ref().and_then(|code| code.source_map()),
+        }
+    }
+
+    /// Returns the source map of the code that is executed by a call of the kind
+    fn get(&self, call_kind: CallKind) -> Option<&Result<SourceMap, SyntaxError>> {
+        if matches!(call_kind, CallKind::Create) {
+            self.creation.as_ref()
+        } else {
+            self.runtime.as_ref()
+        }
+    }
+}
+
+/// Returns the step at which execution first reaches one of the `breakpoints` after the step at
+/// `(call index, step)`, searching backwards if `forward` is false
+///
+/// `line_at` maps a step to its `(source index, line)`, see [`Tui::source_line`]
+fn find_breakpoint(
+    debug_call: &[(Address, Vec<DebugStep>, CallKind)],
+    from: (usize, usize),
+    forward: bool,
+    breakpoints: &BTreeSet<(u32, usize)>,
+    line_at: impl Fn(Address, CallKind, usize) -> Option<(u32, usize)>,
+) -> Option<(usize, usize)> {
+    let mut positions: Vec<(usize, usize)> = debug_call
+        .iter()
+        .enumerate()
+        .flat_map(|(call, (_, steps, _))| (0..steps.len()).map(move |step| (call, step)))
+        .collect();
+    if !forward {
+        positions.reverse();
+    }
+    let line_at = |(call, step): (usize, usize)| {
+        let (address, steps, call_kind) = &debug_call[call];
+        line_at(*address, *call_kind, steps[step].ic)
+    };
+
+    let mut previous = line_at(from);
+    for position in positions.into_iter().skip_while(|position| *position != from).skip(1) {
+        let line = line_at(position);
+        // stop once per line, not at every instruction of it
+        if line != previous && line.map_or(false, |line| breakpoints.contains(&line)) {
+            return Some(position)
+        }
+        previous = line;
+    }
+    None
+}
+
+/// Returns the storage slots written by SSTORE instructions of calls to `address` until the
+/// step at `(call index, step)`, and their last written values
+fn storage_writes(
+    debug_call: &[(Address, Vec<DebugStep>, CallKind)],
+    address: Address,
+    (call_index, current_step): (usize, usize),
+) -> BTreeMap<U256, U256> {
+    let mut storage = BTreeMap::new();
+    for (idx, (call_address, steps, _)) in debug_call.iter().enumerate().take(call_index + 1) {
+        if *call_address != address {
+            continue
+        }
+        let executed = if idx == call_index { &steps[..current_step] } else { &steps[..] };
+        for step in executed {
+            if matches!(step.instruction, Instruction::OpCode(opcode::SSTORE)) &&
+                step.stack.len() >= 2
+            {
+                let slot = step.stack[step.stack.len() - 1];
+                let value = step.stack[step.stack.len() - 2];
+                storage.insert(slot, value);
+            }
+        }
+    }
+    storage
 }
 
 impl Ui for Tui {
@@ -960,6 +1132,9 @@ impl Ui for Tui {
 
         let mut stack_labels = false;
         let mut mem_utf = false;
+        // The storage writes are only collected again when the current step changes
+        let mut storage = BTreeMap::new();
+        let mut last_position = None;
         // UI thread that manages drawing
         loop {
             if last_index != draw_memory.inner_call_index {
@@ -1123,6 +1298,33 @@ impl Ui for Tui {
                         }
                         self.key_buffer.clear();
                     }
+                    // Toggle a breakpoint on the current source line
+                    KeyCode::Char('b') => {
+                        let (address, steps, call_kind) =
+                            &debug_call[draw_memory.inner_call_index];
+                        let ic = steps[self.current_step].ic;
+                        if let Some(line) = self.source_line(*address, *call_kind, ic) {
+                            if !self.breakpoints.remove(&line) {
+                                self.breakpoints.insert(line);
+                            }
+                        }
+                        self.key_buffer.clear();
+                    }
+                    // Continue to the next or previous breakpoint
+                    KeyCode::Char('n') | KeyCode::Char('N') => {
+                        let forward = event.code == KeyCode::Char('n');
+                        for _ in 0..Tui::buffer_as_number(&self.key_buffer, 1) {
+                            let from = (draw_memory.inner_call_index, self.current_step);
+                            match self.find_breakpoint(&debug_call, from, forward) {
+                                Some((call, step)) => {
+                                    draw_memory.inner_call_index = call;
+                                    self.current_step = step;
+                                }
+                                None => break,
+                            }
+                        }
+                        self.key_buffer.clear();
+                    }
                     // toggle stack labels
                     KeyCode::Char('t') => {
                         stack_labels = !stack_labels;
@@ -1172,12 +1374,22 @@ impl Ui for Tui {
             }
             // Draw
             let current_step = self.current_step;
+            let position = (draw_memory.inner_call_index, current_step);
+            if last_position != Some(position) {
+                let address = debug_call[draw_memory.inner_call_index].0;
+                storage = storage_writes(&debug_call, address, position);
+                last_position = Some(position);
+            }
+            let (address, steps, call_kind) = &debug_call[draw_memory.inner_call_index];
+            let breakpoint = self
+                .source_line(*address, *call_kind, steps[current_step].ic)
+                .map_or(false, |line| self.breakpoints.contains(&line));
             self.terminal.draw(|f| {
                 Tui::draw_layout(
                     f,
                     debug_call[draw_memory.inner_call_index].0,
                     &self.identified_contracts,
-                    &self.known_contracts,
+                    &self.source_maps,
                     &self.source_code,
                     &debug_call[draw_memory.inner_call_index].1[..],
                     &opcode_list,
@@ -1186,6 +1398,8 @@ impl Ui for Tui {
                     &mut draw_memory,
                     stack_labels,
                     mem_utf,
+                    &storage,
+                    breakpoint,
                 )
             })?;
         }
@@ -1218,3 +1432,83 @@ impl DrawMemory {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(ic: usize, op: u8, stack: &[u64]) -> DebugStep {
+        DebugStep {
+            ic,
+            instruction: Instruction::OpCode(op),
+            stack: stack.iter().copied().map(U256::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn can_find_breakpoints() {
+        let (a, b) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let debug_call = vec![
+            (a, (0..6).map(|ic| step(ic, opcode::ADD, &[])).collect::<Vec<_>>(), CallKind::Call),
+            (b, (0..4).map(|ic| step(ic, opcode::ADD, &[])).collect::<Vec<_>>(), CallKind::Call),
+        ];
+        // two instructions per line in `a`, one per line in `b`
+        let line_at = |address: Address, _: CallKind, ic: usize| {
+            Some(if address == a { (0, ic / 2) } else { (1, ic) })
+        };
+
+        let breakpoints = BTreeSet::from([(0, 2)]);
+        assert_eq!(find_breakpoint(&debug_call, (0, 0), true, &breakpoints, line_at), Some((0, 4)));
+        // stops once per line
+        assert_eq!(find_breakpoint(&debug_call, (0, 4), true, &breakpoints, line_at), None);
+        assert_eq!(
+            find_breakpoint(&debug_call, (1, 3), false, &breakpoints, line_at),
+            Some((0, 5))
+        );
+        assert_eq!(find_breakpoint(&debug_call, (0, 5), false, &breakpoints, line_at), None);
+
+        let breakpoints = BTreeSet::from([(0, 0), (1, 1)]);
+        assert_eq!(find_breakpoint(&debug_call, (0, 4), true, &breakpoints, line_at), Some((1, 1)));
+        assert_eq!(
+            find_breakpoint(&debug_call, (1, 0), false, &breakpoints, line_at),
+            Some((0, 1))
+        );
+        assert_eq!(find_breakpoint(&debug_call, (1, 1), true, &breakpoints, line_at), None);
+    }
+
+    #[test]
+    fn can_collect_storage_writes() {
+        let (a, b) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        // the slot is on top of the stack, followed by the value
+        let debug_call = vec![
+            (
+                a,
+                vec![
+                    step(0, opcode::SSTORE, &[1, 5]),
+                    step(1, opcode::SSTORE, &[2, 5]),
+                    step(2, opcode::SLOAD, &[7, 6]),
+                ],
+                CallKind::Call,
+            ),
+            (b, vec![step(0, opcode::SSTORE, &[9, 5])], CallKind::Call),
+            (
+                a,
+                vec![step(0, opcode::SSTORE, &[3, 6]), step(1, opcode::SSTORE, &[4])],
+                CallKind::Call,
+            ),
+        ];
+        let writes = |pairs: &[(u64, u64)]| -> BTreeMap<U256, U256> {
+            pairs.iter().map(|(slot, value)| (U256::from(*slot), U256::from(*value))).collect()
+        };
+
+        assert_eq!(storage_writes(&debug_call, a, (0, 0)), BTreeMap::new());
+        assert_eq!(storage_writes(&debug_call, a, (0, 1)), writes(&[(5, 1)]));
+        assert_eq!(storage_writes(&debug_call, a, (0, 3)), writes(&[(5, 2)]));
+        // writes of other contracts are ignored
+        assert_eq!(storage_writes(&debug_call, a, (2, 0)), writes(&[(5, 2)]));
+        assert_eq!(storage_writes(&debug_call, b, (2, 0)), writes(&[(5, 9)]));
+        // SSTOREs with a short stack are skipped
+        assert_eq!(storage_writes(&debug_call, a, (2, 2)), writes(&[(5, 2), (6, 3)]));
+    }
+}
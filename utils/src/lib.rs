@@ -644,6 +644,78 @@ pub async fn fourbyte_event(topic: &str) -> Result<Vec<(String, i32)>> {
         .collect::<Vec<(String, i32)>>())
 }
 
+/// The number of signatures a registry imported and the number it already knew
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportedSignatures {
+    pub imported: usize,
+    pub duplicated: usize,
+}
+
+/// Uploads function, error and event signatures like `transfer(address,uint256)` to the
+/// openchain.xyz signature database, errors are uploaded as functions
+pub async fn openchain_import(
+    functions: &[String],
+    events: &[String],
+) -> Result<ImportedSignatures> {
+    #[derive(Deserialize)]
+    struct Imported {
+        imported: BTreeMap<String, String>,
+        duplicated: BTreeMap<String, String>,
+    }
+
+    #[derive(Deserialize)]
+    struct ImportResult {
+        function: Imported,
+        event: Imported,
+    }
+
+    #[derive(Deserialize)]
+    struct ApiResponse {
+        ok: bool,
+        result: Option<ImportResult>,
+        error: Option<String>,
+    }
+
+    let url = "https://api.openchain.xyz/signature-database/v1/import";
+    let body = serde_json::json!({ "function": functions, "event": events });
+    let res = reqwest::Client::new().post(url).json(&body).send().await?;
+    let api_response = res.json::<ApiResponse>().await?;
+    match api_response.result {
+        Some(result) if api_response.ok => Ok(ImportedSignatures {
+            imported: result.function.imported.len() + result.event.imported.len(),
+            duplicated: result.function.duplicated.len() + result.event.duplicated.len(),
+        }),
+        _ => eyre::bail!(
+            "openchain.xyz did not import the signatures: {}",
+            api_response.error.unwrap_or_default()
+        ),
+    }
+}
+
+/// Uploads the signatures of the functions and events of the ABI to 4byte.directory
+pub async fn fourbyte_import(abi: &Abi) -> Result<ImportedSignatures> {
+    #[derive(Deserialize)]
+    struct ApiResponse {
+        num_imported: usize,
+        num_duplicates: usize,
+    }
+
+    let url = "https://www.4byte.directory/api/v1/import-abi/";
+    let body = serde_json::json!({ "contract_abi": serde_json::to_string(abi)? });
+    let res = reqwest::Client::new().post(url).json(&body).send().await?;
+    let res = res.text().await?;
+    let api_response = match serde_json::from_str::<ApiResponse>(&res) {
+        Ok(inner) => inner,
+        Err(err) => {
+            eyre::bail!("Could not decode response:\n {res}.\nError: {err}")
+        }
+    };
+    Ok(ImportedSignatures {
+        imported: api_response.num_imported,
+        duplicated: api_response.num_duplicates,
+    })
+}
+
 /// Pretty print calldata and if available, fetch possible function signatures
 ///
 /// ```no_run
@@ -1032,6 +1104,39 @@ impl Retry {
     }
 }
 
+/// The prefix of the messages EIP-7702 authorizations are signed over
+pub const EIP7702_MAGIC: u8 = 0x05;
+
+/// Returns the digest an EIP-7702 authorization to delegate to `implementation` is signed over,
+/// `keccak256(0x05 || rlp([chain_id, implementation, nonce]))`
+pub fn delegation_digest(chain_id: U256, implementation: Address, nonce: u64) -> H256 {
+    let mut stream = rlp::RlpStream::new_list(3);
+    stream.append(&chain_id).append(&implementation).append(&nonce);
+    let mut message = vec![EIP7702_MAGIC];
+    message.extend_from_slice(&stream.out());
+    ethers_core::utils::keccak256(message).into()
+}
+
+/// RLP encodes a signed EIP-7702 authorization the way it is included in the authorization list
+/// of a type 4 transaction, `rlp([chain_id, implementation, nonce, y_parity, r, s])`
+pub fn encode_signed_delegation(
+    chain_id: U256,
+    implementation: Address,
+    nonce: u64,
+    signature: &Signature,
+) -> Vec<u8> {
+    let y_parity = signature.v.saturating_sub(27) as u8;
+    let mut stream = rlp::RlpStream::new_list(6);
+    stream
+        .append(&chain_id)
+        .append(&implementation)
+        .append(&nonce)
+        .append(&y_parity)
+        .append(&signature.r)
+        .append(&signature.s);
+    stream.out().to_vec()
+}
+
 /// Enables tracing
 #[cfg(any(feature = "test"))]
 pub fn init_tracing_subscriber() {
@@ -1273,6 +1378,19 @@ mod tests {
         .await;
     }
 
+    #[test]
+    fn encodes_delegation_digest() {
+        let implementation = Address::repeat_byte(0x11);
+        // 0x05 || rlp([1, implementation, 0])
+        let mut message = vec![0x05, 0xd7, 0x01, 0x94];
+        message.extend_from_slice(implementation.as_bytes());
+        message.push(0x80);
+        assert_eq!(
+            delegation_digest(1.into(), implementation, 0),
+            H256::from(ethers_core::utils::keccak256(message))
+        );
+    }
+
     #[test]
     #[cfg(any(target_os = "linux", target_os = "macos"))]
     fn abi2solidity() {